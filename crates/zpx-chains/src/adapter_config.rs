@@ -0,0 +1,163 @@
+//! Versioned config blob stored by each adapter program in its own
+//! [`ADAPTER_CONFIG_SEED`] PDA (written by the adapter's
+//! `initialize_adapter_config`, read in `process_transfer`).
+//!
+//! The first byte is a schema tag; adapters reject tags they do not know
+//! instead of guessing at the layout. Schema v1 (little-endian):
+//!
+//! ```text
+//! tag(1) | token_messenger(32) | message_transmitter(32) | domains_len(1)
+//!        | domains_len * (chain_id u64 | cctp_domain u32)
+//! ```
+
+use crate::{CctpDomain, ChainId};
+
+/// PDA seed for the adapter config account, derived under the adapter program id.
+pub const ADAPTER_CONFIG_SEED: &[u8] = b"adapter_config";
+
+pub const SCHEMA_V1: u8 = 1;
+
+/// Maximum chain -> CCTP domain entries in a v1 blob.
+pub const MAX_DOMAINS: usize = 16;
+
+const V1_HEADER_LEN: usize = 1 + 32 + 32 + 1;
+const V1_ENTRY_LEN: usize = 8 + 4;
+
+/// Encoded length of a v1 blob with `domains` entries.
+pub const fn v1_len(domains: usize) -> usize {
+    V1_HEADER_LEN + domains * V1_ENTRY_LEN
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AdapterConfigError {
+    Empty,
+    UnknownSchema(u8),
+    Truncated,
+    TooManyDomains,
+}
+
+/// Schema v1: CCTP program ids plus the adapter's chain -> domain map.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AdapterConfigV1 {
+    pub token_messenger: [u8; 32],
+    pub message_transmitter: [u8; 32],
+    pub domains_len: u8,
+    pub domains: [(ChainId, CctpDomain); MAX_DOMAINS],
+}
+
+/// Any adapter config schema this crate can parse.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AdapterConfig {
+    V1(AdapterConfigV1),
+}
+
+impl AdapterConfig {
+    /// Parse a blob by its schema tag. Trailing bytes (account padding) are ignored.
+    pub fn parse(data: &[u8]) -> Result<Self, AdapterConfigError> {
+        match data.first() {
+            None => Err(AdapterConfigError::Empty),
+            Some(&SCHEMA_V1) => AdapterConfigV1::parse(data).map(AdapterConfig::V1),
+            Some(&tag) => Err(AdapterConfigError::UnknownSchema(tag)),
+        }
+    }
+}
+
+impl AdapterConfigV1 {
+    fn parse(data: &[u8]) -> Result<Self, AdapterConfigError> {
+        if data.len() < V1_HEADER_LEN {
+            return Err(AdapterConfigError::Truncated);
+        }
+        let mut cfg = AdapterConfigV1 {
+            token_messenger: data[1..33].try_into().unwrap(),
+            message_transmitter: data[33..65].try_into().unwrap(),
+            domains_len: data[65],
+            domains: [(ChainId(0), CctpDomain(0)); MAX_DOMAINS],
+        };
+        let n = cfg.domains_len as usize;
+        if n > MAX_DOMAINS {
+            return Err(AdapterConfigError::TooManyDomains);
+        }
+        if data.len() < v1_len(n) {
+            return Err(AdapterConfigError::Truncated);
+        }
+        for (i, entry) in data[V1_HEADER_LEN..v1_len(n)]
+            .chunks_exact(V1_ENTRY_LEN)
+            .enumerate()
+        {
+            cfg.domains[i] = (
+                ChainId(u64::from_le_bytes(entry[..8].try_into().unwrap())),
+                CctpDomain(u32::from_le_bytes(entry[8..].try_into().unwrap())),
+            );
+        }
+        Ok(cfg)
+    }
+
+    /// Write the v1 encoding into `out`, returning the number of bytes written.
+    pub fn encode(&self, out: &mut [u8]) -> Result<usize, AdapterConfigError> {
+        let n = self.domains_len as usize;
+        if n > MAX_DOMAINS {
+            return Err(AdapterConfigError::TooManyDomains);
+        }
+        let len = v1_len(n);
+        if out.len() < len {
+            return Err(AdapterConfigError::Truncated);
+        }
+        out[0] = SCHEMA_V1;
+        out[1..33].copy_from_slice(&self.token_messenger);
+        out[33..65].copy_from_slice(&self.message_transmitter);
+        out[65] = self.domains_len;
+        for (entry, (chain, domain)) in out[V1_HEADER_LEN..len]
+            .chunks_exact_mut(V1_ENTRY_LEN)
+            .zip(&self.domains[..n])
+        {
+            entry[..8].copy_from_slice(&chain.0.to_le_bytes());
+            entry[8..].copy_from_slice(&domain.0.to_le_bytes());
+        }
+        Ok(len)
+    }
+
+    /// CCTP domain configured for `chain`, if any.
+    pub fn domain_for(&self, chain: ChainId) -> Option<CctpDomain> {
+        self.domains[..self.domains_len as usize]
+            .iter()
+            .find(|(c, _)| *c == chain)
+            .map(|(_, d)| *d)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BASE, CCTP_BASE, CCTP_ETHEREUM, ETHEREUM};
+
+    #[test]
+    fn v1_round_trips_and_rejects_unknown_schema() {
+        let mut domains = [(ChainId(0), CctpDomain(0)); MAX_DOMAINS];
+        domains[0] = (ETHEREUM, CCTP_ETHEREUM);
+        domains[1] = (BASE, CCTP_BASE);
+        let cfg = AdapterConfigV1 {
+            token_messenger: [7; 32],
+            message_transmitter: [9; 32],
+            domains_len: 2,
+            domains,
+        };
+        let mut buf = [0u8; v1_len(MAX_DOMAINS)];
+        let len = cfg.encode(&mut buf).unwrap();
+        assert_eq!(len, v1_len(2));
+        let AdapterConfig::V1(parsed) = AdapterConfig::parse(&buf).unwrap();
+        assert_eq!(parsed, cfg);
+        assert_eq!(parsed.domain_for(BASE), Some(CCTP_BASE));
+        assert_eq!(parsed.domain_for(ChainId(10)), None);
+
+        assert_eq!(
+            AdapterConfig::parse(&buf[..len - 1]),
+            Err(AdapterConfigError::Truncated)
+        );
+        buf[0] = 2;
+        assert_eq!(
+            AdapterConfig::parse(&buf),
+            Err(AdapterConfigError::UnknownSchema(2))
+        );
+        assert_eq!(AdapterConfig::parse(&[]), Err(AdapterConfigError::Empty));
+    }
+}
@@ -0,0 +1,155 @@
+//! Chain identifiers shared by the router, adapters and off-chain tooling.
+//!
+//! The router carries EVM-style chain ids on the wire as `u16` (see the
+//! `ChainIdOutOfRange` guard in zpx_router); CCTP and Wormhole use their own
+//! numbering, mapped here from the chain id.
+#![no_std]
+
+pub mod adapter_config;
+
+/// EVM-style chain id as carried in router events and message hashes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ChainId(pub u64);
+
+/// Circle CCTP domain id.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CctpDomain(pub u32);
+
+/// How a destination chain encodes a 32-byte recipient.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AddressFormat {
+    /// 20-byte address left-padded with 12 zero bytes.
+    Evm,
+    /// Raw 32-byte ed25519 public key.
+    Solana,
+    /// 32-byte hash of a Cosmos bech32 account (Noble).
+    Cosmos,
+    /// 32-byte Bitcoin script hash or taproot output key.
+    Bitcoin,
+}
+
+/// Wormhole chain id.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WormholeChain(pub u16);
+
+// Chain ids
+pub const ETHEREUM: ChainId = ChainId(1);
+pub const OPTIMISM: ChainId = ChainId(10);
+pub const POLYGON: ChainId = ChainId(137);
+pub const BASE: ChainId = ChainId(8453);
+pub const ARBITRUM: ChainId = ChainId(42161);
+pub const AVALANCHE: ChainId = ChainId(43114);
+
+// CCTP domains
+pub const CCTP_ETHEREUM: CctpDomain = CctpDomain(0);
+pub const CCTP_AVALANCHE: CctpDomain = CctpDomain(1);
+pub const CCTP_OPTIMISM: CctpDomain = CctpDomain(2);
+pub const CCTP_ARBITRUM: CctpDomain = CctpDomain(3);
+pub const CCTP_NOBLE: CctpDomain = CctpDomain(4);
+pub const CCTP_SOLANA: CctpDomain = CctpDomain(5);
+pub const CCTP_BASE: CctpDomain = CctpDomain(6);
+pub const CCTP_POLYGON: CctpDomain = CctpDomain(7);
+
+// Wormhole chains
+pub const WORMHOLE_SOLANA: WormholeChain = WormholeChain(1);
+pub const WORMHOLE_ETHEREUM: WormholeChain = WormholeChain(2);
+pub const WORMHOLE_POLYGON: WormholeChain = WormholeChain(5);
+pub const WORMHOLE_AVALANCHE: WormholeChain = WormholeChain(6);
+pub const WORMHOLE_ARBITRUM: WormholeChain = WormholeChain(23);
+pub const WORMHOLE_OPTIMISM: WormholeChain = WormholeChain(24);
+pub const WORMHOLE_BASE: WormholeChain = WormholeChain(30);
+
+/// (chain id, CCTP domain, Wormhole chain) for every chain the router knows about.
+pub const CHAINS: &[(ChainId, CctpDomain, WormholeChain)] = &[
+    (ETHEREUM, CCTP_ETHEREUM, WORMHOLE_ETHEREUM),
+    (OPTIMISM, CCTP_OPTIMISM, WORMHOLE_OPTIMISM),
+    (POLYGON, CCTP_POLYGON, WORMHOLE_POLYGON),
+    (BASE, CCTP_BASE, WORMHOLE_BASE),
+    (ARBITRUM, CCTP_ARBITRUM, WORMHOLE_ARBITRUM),
+    (AVALANCHE, CCTP_AVALANCHE, WORMHOLE_AVALANCHE),
+];
+
+/// Recipient address format for a CCTP destination domain, if known.
+pub fn address_format(domain: CctpDomain) -> Option<AddressFormat> {
+    match domain {
+        CCTP_SOLANA => Some(AddressFormat::Solana),
+        CCTP_NOBLE => Some(AddressFormat::Cosmos),
+        d => chain_for_cctp_domain(d).map(|_| AddressFormat::Evm),
+    }
+}
+
+/// True when `recipient` is a left-padded, non-zero 20-byte EVM address.
+pub fn is_valid_evm_recipient(recipient: &[u8; 32]) -> bool {
+    recipient[..12].iter().all(|b| *b == 0) && recipient[12..].iter().any(|b| *b != 0)
+}
+
+/// True when `id` fits the router's `u16` wire encoding without truncation.
+pub const fn fits_wire(id: u64) -> bool {
+    id <= u16::MAX as u64
+}
+
+/// True when `id` is a non-zero chain id that fits the wire encoding.
+pub const fn is_valid_chain_id(id: u64) -> bool {
+    id != 0 && fits_wire(id)
+}
+
+/// True when `id` is listed in [`CHAINS`].
+pub fn is_known_chain(id: ChainId) -> bool {
+    CHAINS.iter().any(|(c, _, _)| *c == id)
+}
+
+/// CCTP domain for a chain id, if CCTP serves that chain.
+pub fn cctp_domain(id: ChainId) -> Option<CctpDomain> {
+    CHAINS.iter().find(|(c, _, _)| *c == id).map(|(_, d, _)| *d)
+}
+
+/// Wormhole chain for a chain id, if known.
+pub fn wormhole_chain(id: ChainId) -> Option<WormholeChain> {
+    CHAINS.iter().find(|(c, _, _)| *c == id).map(|(_, _, w)| *w)
+}
+
+/// Reverse lookup: chain id served by a CCTP domain.
+pub fn chain_for_cctp_domain(domain: CctpDomain) -> Option<ChainId> {
+    CHAINS
+        .iter()
+        .find(|(_, d, _)| *d == domain)
+        .map(|(c, _, _)| *c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mappings_are_unique_and_round_trip() {
+        for (i, (c, d, w)) in CHAINS.iter().enumerate() {
+            assert!(is_valid_chain_id(c.0));
+            assert_eq!(cctp_domain(*c), Some(*d));
+            assert_eq!(wormhole_chain(*c), Some(*w));
+            assert_eq!(chain_for_cctp_domain(*d), Some(*c));
+            for (c2, d2, w2) in &CHAINS[i + 1..] {
+                assert!(c != c2 && d != d2 && w != w2);
+            }
+        }
+        assert!(!is_valid_chain_id(0));
+        assert!(!fits_wire(u16::MAX as u64 + 1));
+        assert_eq!(cctp_domain(ChainId(999)), None);
+    }
+
+    #[test]
+    fn address_formats_and_evm_recipients() {
+        for (_, d, _) in CHAINS {
+            assert_eq!(address_format(*d), Some(AddressFormat::Evm));
+        }
+        assert_eq!(address_format(CCTP_SOLANA), Some(AddressFormat::Solana));
+        assert_eq!(address_format(CCTP_NOBLE), Some(AddressFormat::Cosmos));
+        assert_eq!(address_format(CctpDomain(99)), None);
+
+        let mut r = [0u8; 32];
+        assert!(!is_valid_evm_recipient(&r));
+        r[31] = 1;
+        assert!(is_valid_evm_recipient(&r));
+        r[11] = 1;
+        assert!(!is_valid_evm_recipient(&r));
+    }
+}
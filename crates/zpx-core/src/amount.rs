@@ -0,0 +1,87 @@
+//! Cross-chain amount encoding. Messages carry the amount as a 32-byte big-endian uint256.
+//! By default that is the raw SPL amount. Routes with decimals normalization carry it in
+//! the canonical 18-decimal representation the EVM side uses.
+
+/// Decimals of the canonical cross-chain representation.
+pub const CANONICAL_DECIMALS: u8 = 18;
+
+/// Why an amount could not be converted; the router maps each to its ErrorCode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AmountError {
+    /// Mints with more than CANONICAL_DECIMALS cannot be normalized losslessly
+    UnsupportedDecimals,
+    /// The canonical amount has dust below the mint's smallest unit
+    PrecisionLoss,
+    /// The amount does not fit in a u64 of the mint's units
+    Overflow,
+}
+
+fn scale(decimals: u8) -> Result<u128, AmountError> {
+    if decimals > CANONICAL_DECIMALS {
+        return Err(AmountError::UnsupportedDecimals);
+    }
+    Ok(10u128.pow((CANONICAL_DECIMALS - decimals) as u32))
+}
+
+/// `amount` base units of a mint with `decimals`, as a canonical 18-decimal uint256.
+/// Exact: u64::MAX * 10^18 still fits in 128 bits.
+pub fn to_canonical(amount: u64, decimals: u8) -> Result<[u8; 32], AmountError> {
+    let mut out = [0u8; 32];
+    out[16..].copy_from_slice(&((amount as u128) * scale(decimals)?).to_be_bytes());
+    Ok(out)
+}
+
+/// Base units of a mint with `decimals` for a canonical 18-decimal uint256. Rejects amounts
+/// that would round (dust below the mint's unit) or overflow a u64.
+pub fn from_canonical(amount_be: &[u8; 32], decimals: u8) -> Result<u64, AmountError> {
+    let scale = scale(decimals)?;
+    if amount_be[..16].iter().any(|b| *b != 0) {
+        return Err(AmountError::Overflow);
+    }
+    let canonical = u128::from_be_bytes(amount_be[16..].try_into().unwrap());
+    if canonical % scale != 0 {
+        return Err(AmountError::PrecisionLoss);
+    }
+    u64::try_from(canonical / scale).map_err(|_| AmountError::Overflow)
+}
+
+/// The uint256 amount word of a message: raw units when the route does not normalize
+/// (`decimals` = None), otherwise the canonical 18-decimal amount.
+pub fn amount_word(amount: u64, decimals: Option<u8>) -> Result<[u8; 32], AmountError> {
+    match decimals {
+        Some(decimals) => to_canonical(amount, decimals),
+        None => Ok(crate::hash::amount_be(amount)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_and_rejects_lossy_amounts() {
+        let one_usdc = to_canonical(1_000_000, 6).unwrap();
+        assert_eq!(
+            u128::from_be_bytes(one_usdc[16..].try_into().unwrap()),
+            1_000_000_000_000_000_000
+        );
+        assert_eq!(from_canonical(&one_usdc, 6), Ok(1_000_000));
+        assert_eq!(from_canonical(&one_usdc, 9), Ok(1_000_000_000));
+        assert_eq!(
+            from_canonical(&to_canonical(u64::MAX, 0).unwrap(), 0),
+            Ok(u64::MAX)
+        );
+        assert_eq!(
+            from_canonical(&crate::hash::amount_be(1), 6),
+            Err(AmountError::PrecisionLoss)
+        );
+        assert_eq!(from_canonical(&[0xff; 32], 18), Err(AmountError::Overflow));
+        assert_eq!(
+            from_canonical(&to_canonical(u64::MAX, 0).unwrap(), 1),
+            Err(AmountError::Overflow)
+        );
+        assert_eq!(to_canonical(1, 19), Err(AmountError::UnsupportedDecimals));
+        assert_eq!(amount_word(7, None), Ok(crate::hash::amount_be(7)));
+        assert_eq!(amount_word(7, Some(18)), Ok(crate::hash::amount_be(7)));
+    }
+}
@@ -0,0 +1,306 @@
+//! Fee arithmetic. Basis-point fees round down; caps are passed in so the same code serves
+//! mainnet and devnet-relaxed builds.
+
+/// Upper bound of the fee split table.
+pub const MAX_FEE_SPLIT_RECIPIENTS: usize = 4;
+
+/// Upper bound of the amount brackets in a [`FeePolicy`].
+pub const MAX_FEE_TIERS: usize = 4;
+
+/// Why a fee computation was rejected; the router maps each to its ErrorCode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeeError {
+    ZeroAmount,
+    ProtocolFeeTooHigh,
+    RelayerFeeTooHigh,
+    FeesExceedAmount,
+    InvalidDiscount,
+    MathOverflow,
+    InvalidPolicy,
+}
+
+/// Fees and net amount of a transfer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeeQuote {
+    pub protocol_fee: u64,
+    pub relayer_fee: u64,
+    pub forward_amount: u64,
+}
+
+/// `amount * bps / 10_000`, rounded down.
+pub fn bps_fee(amount: u64, bps: u16) -> u64 {
+    ((amount as u128) * (bps as u128) / 10_000u128) as u64
+}
+
+/// Validate fees against the caps; returns (forward_amount, total_fees). A zero
+/// `relayer_bps_cap` leaves the relayer fee bounded only by the amount.
+pub fn compute_fees_and_forward(
+    amount: u64,
+    protocol_fee: u64,
+    relayer_fee: u64,
+    protocol_bps_cap: u16,
+    relayer_bps_cap: u16,
+) -> Result<(u64, u64), FeeError> {
+    if amount == 0 {
+        return Err(FeeError::ZeroAmount);
+    }
+    if (protocol_fee as u128) * 10_000u128 > (amount as u128) * (protocol_bps_cap as u128) {
+        return Err(FeeError::ProtocolFeeTooHigh);
+    }
+    if relayer_bps_cap > 0
+        && (relayer_fee as u128) * 10_000u128 > (amount as u128) * (relayer_bps_cap as u128)
+    {
+        return Err(FeeError::RelayerFeeTooHigh);
+    }
+    let total_fees = protocol_fee
+        .checked_add(relayer_fee)
+        .ok_or(FeeError::MathOverflow)?;
+    if total_fees > amount {
+        return Err(FeeError::FeesExceedAmount);
+    }
+    Ok((amount - total_fees, total_fees))
+}
+
+/// Quote a forward at the hub's configured rates, as forward_via_spoke charges it: both
+/// fees are bps of the gross amount and something must be left to forward.
+pub fn quote(
+    amount: u64,
+    protocol_fee_bps: u16,
+    relayer_fee_bps: u16,
+) -> Result<FeeQuote, FeeError> {
+    evaluate_fee_policy(&FeePolicy::flat(protocol_fee_bps, relayer_fee_bps), amount)
+}
+
+/// Rates for amounts from `min_amount` up to the next tier's `min_amount`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeeTier {
+    pub min_amount: u64,
+    pub protocol_bps: u16,
+    pub relayer_bps: u16,
+}
+
+/// Floor and ceiling applied to a bps fee; a zero `max` leaves the fee uncapped.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeeBounds {
+    pub min: u64,
+    pub max: u64,
+}
+
+impl FeeBounds {
+    /// `fee` raised to the floor, then cut to the ceiling.
+    pub fn apply(&self, fee: u64) -> u64 {
+        let fee = fee.max(self.min);
+        if self.max > 0 {
+            fee.min(self.max)
+        } else {
+            fee
+        }
+    }
+}
+
+/// Fee schedule of one route: tiers sorted by `min_amount`, the first starting at 0, plus
+/// per-fee flat minimums and caps.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeePolicy {
+    pub tiers_len: u8,
+    pub tiers: [FeeTier; MAX_FEE_TIERS],
+    pub protocol: FeeBounds,
+    pub relayer: FeeBounds,
+}
+
+impl FeePolicy {
+    /// Single-tier policy charging the same bps at every amount, without bounds.
+    pub fn flat(protocol_bps: u16, relayer_bps: u16) -> Self {
+        let mut tiers = [FeeTier::default(); MAX_FEE_TIERS];
+        tiers[0] = FeeTier {
+            min_amount: 0,
+            protocol_bps,
+            relayer_bps,
+        };
+        FeePolicy {
+            tiers_len: 1,
+            tiers,
+            protocol: FeeBounds::default(),
+            relayer: FeeBounds::default(),
+        }
+    }
+
+    /// The same schedule with the protocol and/or relayer fee dropped (rates and bounds).
+    pub fn waive(mut self, protocol: bool, relayer: bool) -> Self {
+        for t in self.tiers.iter_mut() {
+            if protocol {
+                t.protocol_bps = 0;
+            }
+            if relayer {
+                t.relayer_bps = 0;
+            }
+        }
+        if protocol {
+            self.protocol = FeeBounds::default();
+        }
+        if relayer {
+            self.relayer = FeeBounds::default();
+        }
+        self
+    }
+
+    pub fn tiers(&self) -> &[FeeTier] {
+        &self.tiers[..(self.tiers_len as usize).min(MAX_FEE_TIERS)]
+    }
+
+    /// Tier that prices `amount`: the last one whose `min_amount` it reaches.
+    pub fn tier_for(&self, amount: u64) -> Option<&FeeTier> {
+        self.tiers().iter().rev().find(|t| t.min_amount <= amount)
+    }
+
+    /// Reject malformed schedules and rates above the caps. A zero `relayer_bps_cap` leaves
+    /// relayer rates bounded only by 10_000 bps.
+    pub fn validate(&self, protocol_bps_cap: u16, relayer_bps_cap: u16) -> Result<(), FeeError> {
+        let len = self.tiers_len as usize;
+        if len == 0 || len > MAX_FEE_TIERS || self.tiers[0].min_amount != 0 {
+            return Err(FeeError::InvalidPolicy);
+        }
+        let tiers = self.tiers();
+        if tiers.windows(2).any(|w| w[0].min_amount >= w[1].min_amount) {
+            return Err(FeeError::InvalidPolicy);
+        }
+        let relayer_cap = if relayer_bps_cap > 0 {
+            relayer_bps_cap
+        } else {
+            10_000
+        };
+        for t in tiers {
+            if t.protocol_bps > protocol_bps_cap {
+                return Err(FeeError::ProtocolFeeTooHigh);
+            }
+            if t.relayer_bps > relayer_cap {
+                return Err(FeeError::RelayerFeeTooHigh);
+            }
+        }
+        for b in [self.protocol, self.relayer] {
+            if b.max > 0 && b.min > b.max {
+                return Err(FeeError::InvalidPolicy);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Fees of `amount` under `policy`: each fee is its tier's bps of the gross amount, raised to
+/// its minimum and cut to its cap, and something must be left to forward.
+pub fn evaluate_fee_policy(policy: &FeePolicy, amount: u64) -> Result<FeeQuote, FeeError> {
+    if amount == 0 {
+        return Err(FeeError::ZeroAmount);
+    }
+    let tier = policy.tier_for(amount).ok_or(FeeError::InvalidPolicy)?;
+    let protocol_fee = policy.protocol.apply(bps_fee(amount, tier.protocol_bps));
+    let relayer_fee = policy.relayer.apply(bps_fee(amount, tier.relayer_bps));
+    let total_fees = protocol_fee
+        .checked_add(relayer_fee)
+        .ok_or(FeeError::MathOverflow)?;
+    if total_fees > amount {
+        return Err(FeeError::FeesExceedAmount);
+    }
+    if amount == total_fees {
+        return Err(FeeError::ZeroAmount);
+    }
+    Ok(FeeQuote {
+        protocol_fee,
+        relayer_fee,
+        forward_amount: amount - total_fees,
+    })
+}
+
+/// Per-recipient amounts for `fee`; rounding dust goes to the last share so the shares
+/// always sum to `fee`.
+pub fn fee_split_shares(fee: u64, bps: &[u16]) -> [u64; MAX_FEE_SPLIT_RECIPIENTS] {
+    let mut shares = [0u64; MAX_FEE_SPLIT_RECIPIENTS];
+    for (share, b) in shares.iter_mut().zip(bps) {
+        *share = bps_fee(fee, *b);
+    }
+    if let Some((last, rest)) = shares[..bps.len()].split_last_mut() {
+        *last = fee - rest.iter().sum::<u64>();
+    }
+    shares
+}
+
+/// Fixed-point scale of ZPX-per-token rates.
+pub const ZPX_RATE_SCALE: u128 = 1_000_000_000;
+
+/// ZPX owed for an in-kind `protocol_fee` at `zpx_per_token_e9`, after `discount_bps`.
+pub fn zpx_fee_for(
+    protocol_fee: u64,
+    zpx_per_token_e9: u64,
+    discount_bps: u16,
+) -> Result<u64, FeeError> {
+    if discount_bps > 10_000 {
+        return Err(FeeError::InvalidDiscount);
+    }
+    let gross = (protocol_fee as u128) * (zpx_per_token_e9 as u128) / ZPX_RATE_SCALE;
+    let net = gross * (10_000u128 - discount_bps as u128) / 10_000u128;
+    u64::try_from(net).map_err(|_| FeeError::MathOverflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_round_down_and_keep_a_forward_amount() {
+        let q = quote(1_000_001, 5, 1_000).unwrap();
+        assert_eq!(q.protocol_fee, 500);
+        assert_eq!(q.relayer_fee, 100_000);
+        assert_eq!(q.forward_amount, 1_000_001 - 100_500);
+        assert_eq!(quote(0, 5, 5), Err(FeeError::ZeroAmount));
+        assert_eq!(quote(1, 10_000, 0), Err(FeeError::ZeroAmount));
+        assert_eq!(quote(10, 10_000, 10_000), Err(FeeError::FeesExceedAmount));
+        assert_eq!(
+            compute_fees_and_forward(10_000, 6, 0, 5, 0),
+            Err(FeeError::ProtocolFeeTooHigh)
+        );
+        assert_eq!(
+            compute_fees_and_forward(10_000, 5, 9_995, 5, 0),
+            Ok((0, 10_000))
+        );
+        assert_eq!(fee_split_shares(10, &[3_333, 3_333, 3_334]), [3, 3, 4, 0]);
+        assert_eq!(zpx_fee_for(1, 1, 10_001), Err(FeeError::InvalidDiscount));
+    }
+
+    #[test]
+    fn policies_pick_the_bracket_then_apply_bounds() {
+        let mut policy = FeePolicy::flat(10, 5);
+        policy.tiers[1] = FeeTier {
+            min_amount: 1_000_000,
+            protocol_bps: 5,
+            relayer_bps: 0,
+        };
+        policy.tiers_len = 2;
+        policy.protocol = FeeBounds { min: 100, max: 400 };
+        assert_eq!(policy.validate(5, 0), Err(FeeError::ProtocolFeeTooHigh));
+        assert_eq!(policy.validate(10, 0), Ok(()));
+        // Below the second bracket: 10 bps lifted to the 100 floor
+        let q = evaluate_fee_policy(&policy, 50_000).unwrap();
+        assert_eq!((q.protocol_fee, q.relayer_fee), (100, 25));
+        // Second bracket: 5 bps, cut to the 400 cap
+        let q = evaluate_fee_policy(&policy, 10_000_000).unwrap();
+        assert_eq!((q.protocol_fee, q.relayer_fee), (400, 0));
+        assert_eq!(
+            evaluate_fee_policy(&policy, 50),
+            Err(FeeError::FeesExceedAmount)
+        );
+        let q = evaluate_fee_policy(&policy.waive(true, false), 50).unwrap();
+        assert_eq!((q.protocol_fee, q.forward_amount), (0, 50));
+
+        policy.tiers[1].min_amount = 0;
+        assert_eq!(policy.validate(10, 0), Err(FeeError::InvalidPolicy));
+        policy.tiers_len = 0;
+        assert_eq!(
+            evaluate_fee_policy(&policy, 1),
+            Err(FeeError::InvalidPolicy)
+        );
+        assert_eq!(
+            evaluate_fee_policy(&FeePolicy::flat(5, 1_000), 1_000_001),
+            quote(1_000_001, 5, 1_000)
+        );
+    }
+}
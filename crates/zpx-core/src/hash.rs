@@ -0,0 +1,207 @@
+//! Canonical hashes shared with the EVM side and the off-chain vector generator. Integers are
+//! packed big-endian (abi.encodePacked order), every other field is 32 bytes.
+
+/// A keccak256 implementation over concatenated `parts`.
+pub trait HashBackend {
+    fn keccak256(parts: &[&[u8]]) -> [u8; 32];
+}
+
+/// solana_program's keccak: the sol_keccak256 syscall on-chain (85 CU + 1 CU per
+/// 2 bytes, versus a software permutation billed per instruction).
+#[cfg(feature = "solana")]
+pub struct Syscall;
+
+#[cfg(feature = "solana")]
+impl HashBackend for Syscall {
+    fn keccak256(parts: &[&[u8]]) -> [u8; 32] {
+        solana_program::keccak::hashv(parts).to_bytes()
+    }
+}
+
+/// Pure-Rust keccak for off-chain tooling (vector generator, SDKs, benches, wasm).
+#[cfg(not(target_os = "solana"))]
+pub struct PureRust;
+
+#[cfg(not(target_os = "solana"))]
+impl HashBackend for PureRust {
+    fn keccak256(parts: &[&[u8]]) -> [u8; 32] {
+        use tiny_keccak::{Hasher, Keccak};
+        let mut hasher = Keccak::v256();
+        for part in parts {
+            hasher.update(part);
+        }
+        let mut out = [0u8; 32];
+        hasher.finalize(&mut out);
+        out
+    }
+}
+
+#[cfg(target_os = "solana")]
+pub type Backend = Syscall;
+#[cfg(not(target_os = "solana"))]
+pub type Backend = PureRust;
+
+pub fn keccak256(parts: &[&[u8]]) -> [u8; 32] {
+    Backend::keccak256(parts)
+}
+
+/// Version of the domain-separated scheme, shared with the EVM router. Bump it with
+/// any change to the packed fields.
+pub const HASH_SCHEMA_VERSION: u8 = 1;
+/// Protocol tag at the front of the domain separator.
+pub const DOMAIN_TAG: &[u8] = b"zoopx.router.message";
+/// Deployment environments mixed into the separator, so devnet and mainnet hashes of
+/// the same message never collide.
+pub const ENV_LOCAL: u8 = 0;
+pub const ENV_DEVNET: u8 = 1;
+pub const ENV_MAINNET: u8 = 2;
+
+/// How message hashes and route ids are derived.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HashScheme {
+    /// Pre-separation packing, kept while the EVM side migrates.
+    Legacy,
+    /// Packed fields prefixed with a 32-byte domain separator.
+    Domain([u8; 32]),
+}
+
+impl HashScheme {
+    /// keccak256(DOMAIN_TAG | hub program id | env u8 | version u8)
+    pub fn domain(hub: [u8; 32], env: u8, version: u8) -> Self {
+        HashScheme::Domain(keccak256(&[DOMAIN_TAG, &hub, &[env, version]]))
+    }
+
+    /// Scheme of a message stamped with `version` by the hub `hub` in `env`: 0 is the legacy
+    /// packing, anything else is domain-separated.
+    pub fn for_version(hub: [u8; 32], env: u8, version: u8) -> Self {
+        if version == 0 {
+            HashScheme::Legacy
+        } else {
+            HashScheme::domain(hub, env, version)
+        }
+    }
+
+    fn prefix(&self) -> &[u8] {
+        match self {
+            HashScheme::Legacy => &[],
+            HashScheme::Domain(separator) => separator,
+        }
+    }
+}
+
+/// `amount` as the 32-byte big-endian uint256 the EVM side packs.
+pub fn amount_be(amount: u64) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    out[16..].copy_from_slice(&(amount as u128).to_be_bytes());
+    out
+}
+
+/// keccak256(separator | src_chain u64 | dst_chain u64 | initiator | msg_hash | nonce u64)
+pub fn global_route_id(
+    scheme: HashScheme,
+    src_chain: u64,
+    dst_chain: u64,
+    initiator: [u8; 32],
+    msg_hash: [u8; 32],
+    nonce: u64,
+) -> [u8; 32] {
+    keccak256(&[
+        scheme.prefix(),
+        &src_chain.to_be_bytes(),
+        &dst_chain.to_be_bytes(),
+        &initiator,
+        &msg_hash,
+        &nonce.to_be_bytes(),
+    ])
+}
+
+/// keccak256(separator | src_chain u64 | src_adapter | recipient | asset | amount u256
+/// | payload_hash | nonce u64 | dst_chain u64)
+#[allow(clippy::too_many_arguments)]
+pub fn message_hash_be(
+    scheme: HashScheme,
+    src_chain: u64,
+    src_adapter: [u8; 32],
+    recipient: [u8; 32],
+    asset: [u8; 32],
+    amount_be: [u8; 32],
+    payload_hash: [u8; 32],
+    nonce: u64,
+    dst_chain: u64,
+) -> [u8; 32] {
+    keccak256(&[
+        scheme.prefix(),
+        &src_chain.to_be_bytes(),
+        &src_adapter,
+        &recipient,
+        &asset,
+        &amount_be,
+        &payload_hash,
+        &nonce.to_be_bytes(),
+        &dst_chain.to_be_bytes(),
+    ])
+}
+
+/// Source-leg message hash as stamped by universal_bridge_transfer: the recipient slot is
+/// zero and the amount is the forwarded (post-fee) amount in raw units.
+#[allow(clippy::too_many_arguments)]
+pub fn source_message_hash(
+    scheme: HashScheme,
+    src_chain: u64,
+    dst_chain: u64,
+    target_adapter: [u8; 32],
+    mint: [u8; 32],
+    forward_amount: u64,
+    payload_hash: [u8; 32],
+    nonce: u64,
+) -> [u8; 32] {
+    source_message_hash_word(
+        scheme,
+        src_chain,
+        dst_chain,
+        target_adapter,
+        mint,
+        amount_be(forward_amount),
+        payload_hash,
+        nonce,
+    )
+}
+
+/// `source_message_hash` over an already encoded amount word (see `amount::amount_word`).
+#[allow(clippy::too_many_arguments)]
+pub fn source_message_hash_word(
+    scheme: HashScheme,
+    src_chain: u64,
+    dst_chain: u64,
+    target_adapter: [u8; 32],
+    mint: [u8; 32],
+    amount_word: [u8; 32],
+    payload_hash: [u8; 32],
+    nonce: u64,
+) -> [u8; 32] {
+    message_hash_be(
+        scheme,
+        src_chain,
+        target_adapter,
+        [0u8; 32],
+        mint,
+        amount_word,
+        payload_hash,
+        nonce,
+        dst_chain,
+    )
+}
+
+/// Tag of the validity envelope wrapped around the payload hash on routes with a max age.
+pub const VALIDITY_TAG: &[u8] = b"zoopx.router.validity";
+
+/// keccak256(VALIDITY_TAG | sent_at u64 | expires_at u64 | payload_hash): the payload hash
+/// committed to by messages carrying a validity window (unix seconds)
+pub fn validity_payload_hash(payload_hash: [u8; 32], sent_at: u64, expires_at: u64) -> [u8; 32] {
+    keccak256(&[
+        VALIDITY_TAG,
+        &sent_at.to_be_bytes(),
+        &expires_at.to_be_bytes(),
+        &payload_hash,
+    ])
+}
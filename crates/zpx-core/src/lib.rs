@@ -0,0 +1,21 @@
+//! Pure router logic shared by the on-chain program, off-chain tooling and the browser SDK:
+//! message hashes and route ids, amount normalization, fee quotes, adapter payload checks,
+//! typed recipients and PDA derivation.
+//!
+//! The crate is `no_std` and allocation-free so it builds for SBF and `wasm32-unknown-unknown`
+//! alike. zpx_router re-exports or wraps every item here, so the program and its clients
+//! compute the same bytes; `tests/core_parity.rs` in the router pins that.
+#![no_std]
+#![forbid(unsafe_code)]
+
+#[cfg(all(target_os = "solana", not(feature = "solana")))]
+compile_error!("SBF builds of zpx-core need the `solana` feature for the keccak syscall");
+
+pub mod amount;
+pub mod fees;
+pub mod hash;
+pub mod payload;
+pub mod pda;
+pub mod recipient;
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -0,0 +1,194 @@
+//! Adapter payload checks the router runs before any funds move, and the payload hash a
+//! message commits to.
+
+use crate::hash::{keccak256, validity_payload_hash};
+
+/// Largest adapter payload the router accepts.
+pub const MAX_PAYLOAD_LEN: usize = 512;
+/// Longest magic prefix a payload schema can require.
+pub const PAYLOAD_MAGIC_MAX_LEN: usize = 4;
+
+/// Prefix of a compressed bridge payload:
+/// `COMPRESSED_PAYLOAD_MAGIC || algorithm || decompressed_len (u32 LE) || compressed body`.
+pub const COMPRESSED_PAYLOAD_MAGIC: [u8; 4] = *b"ZPXZ";
+pub const COMPRESSED_HEADER_LEN: usize = COMPRESSED_PAYLOAD_MAGIC.len() + 1 + 4;
+pub const COMPRESSION_ZSTD: u8 = 1;
+pub const COMPRESSION_DEFLATE: u8 = 2;
+/// Largest decompressed size a compressed payload may declare, so the destination leg never
+/// has to unpack more than this.
+pub const MAX_DECOMPRESSED_PAYLOAD_LEN: u32 = 16 * 1024;
+
+/// Why a payload was rejected; the router maps each to its ErrorCode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PayloadError {
+    TooLarge,
+    SchemaMismatch,
+    UnsupportedVersion,
+    UnsupportedCompression,
+    DecompressedTooLarge,
+}
+
+/// Per-spoke payload descriptor, mirroring the router's on-chain PayloadSchema.
+/// Layout expected when enabled: `magic[..magic_len] || version (if required) || body`.
+/// A zeroed schema (max_len == 0) disables validation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PayloadSchema {
+    pub min_len: u16,
+    pub max_len: u16,
+    pub require_version: bool,
+    /// Oldest supported payload version
+    pub version: u8,
+    pub magic_len: u8,
+    pub magic: [u8; PAYLOAD_MAGIC_MAX_LEN],
+    /// Newest supported payload version; 0 accepts exactly `version`
+    pub max_version: u8,
+}
+
+impl PayloadSchema {
+    /// Inclusive range of payload versions this spoke accepts.
+    pub fn supported_versions(&self) -> core::ops::RangeInclusive<u8> {
+        self.version..=self.max_version.max(self.version)
+    }
+
+    /// Reject descriptors that could never match a payload.
+    pub fn check_config(&self) -> Result<(), PayloadError> {
+        if self.max_len == 0 {
+            return Ok(());
+        }
+        let header = self.magic_len as usize + self.require_version as usize;
+        let ok = self.magic_len as usize <= PAYLOAD_MAGIC_MAX_LEN
+            && self.min_len <= self.max_len
+            && header <= self.max_len as usize
+            && self.max_len as usize <= MAX_PAYLOAD_LEN
+            && (self.max_version == 0 || self.max_version >= self.version);
+        if ok {
+            Ok(())
+        } else {
+            Err(PayloadError::SchemaMismatch)
+        }
+    }
+
+    /// Validate an adapter payload against this descriptor.
+    pub fn validate(&self, payload: &[u8]) -> Result<(), PayloadError> {
+        if self.max_len == 0 {
+            return Ok(());
+        }
+        let magic_len = self.magic_len as usize;
+        if payload.len() < self.min_len as usize
+            || payload.len() > self.max_len as usize
+            || payload.len() < magic_len + self.require_version as usize
+            || payload[..magic_len] != self.magic[..magic_len]
+        {
+            return Err(PayloadError::SchemaMismatch);
+        }
+        // Relayers built for another payload version must fail loudly, not mis-parse
+        if self.require_version && !self.supported_versions().contains(&payload[magic_len]) {
+            return Err(PayloadError::UnsupportedVersion);
+        }
+        Ok(())
+    }
+}
+
+/// Size check applied to every adapter payload.
+pub fn check_len(payload_len: usize) -> Result<(), PayloadError> {
+    if payload_len > MAX_PAYLOAD_LEN {
+        return Err(PayloadError::TooLarge);
+    }
+    Ok(())
+}
+
+/// Payload hash a source-leg message commits to: keccak256(payload), wrapped in the
+/// validity envelope when the route carries a (sent_at, expires_at) window.
+pub fn payload_hash(payload: &[u8], validity: Option<(u64, u64)>) -> [u8; 32] {
+    let raw = keccak256(&[payload]);
+    match validity {
+        Some((sent_at, expires_at)) => validity_payload_hash(raw, sent_at, expires_at),
+        None => raw,
+    }
+}
+
+/// Compression metadata of a payload carrying the compressed header. The router never
+/// decompresses; it only bounds what the destination leg will have to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CompressedPayload {
+    pub algorithm: u8,
+    pub compressed_len: u32,
+    pub decompressed_len: u32,
+}
+
+/// Parse the compressed header, if `payload` has one. Payloads without the magic prefix are
+/// plain calldata and yield `None`.
+pub fn compression_header(payload: &[u8]) -> Result<Option<CompressedPayload>, PayloadError> {
+    if !payload.starts_with(&COMPRESSED_PAYLOAD_MAGIC) {
+        return Ok(None);
+    }
+    if payload.len() <= COMPRESSED_HEADER_LEN {
+        return Err(PayloadError::SchemaMismatch);
+    }
+    let algorithm = payload[COMPRESSED_PAYLOAD_MAGIC.len()];
+    if !matches!(algorithm, COMPRESSION_ZSTD | COMPRESSION_DEFLATE) {
+        return Err(PayloadError::UnsupportedCompression);
+    }
+    let mut len = [0u8; 4];
+    len.copy_from_slice(&payload[COMPRESSED_HEADER_LEN - 4..COMPRESSED_HEADER_LEN]);
+    let decompressed_len = u32::from_le_bytes(len);
+    if decompressed_len == 0 {
+        return Err(PayloadError::SchemaMismatch);
+    }
+    if decompressed_len > MAX_DECOMPRESSED_PAYLOAD_LEN {
+        return Err(PayloadError::DecompressedTooLarge);
+    }
+    Ok(Some(CompressedPayload {
+        algorithm,
+        compressed_len: (payload.len() - COMPRESSED_HEADER_LEN) as u32,
+        decompressed_len,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compressed(algorithm: u8, decompressed_len: u32, body: &[u8]) -> [u8; 64] {
+        let mut out = [0u8; 64];
+        out[..4].copy_from_slice(&COMPRESSED_PAYLOAD_MAGIC);
+        out[4] = algorithm;
+        out[5..9].copy_from_slice(&decompressed_len.to_le_bytes());
+        out[9..9 + body.len()].copy_from_slice(body);
+        out
+    }
+
+    #[test]
+    fn compression_header_bounds_declared_size() {
+        assert_eq!(compression_header(b"plain calldata"), Ok(None));
+        let zstd = compressed(COMPRESSION_ZSTD, 4096, &[7; 10]);
+        assert_eq!(
+            compression_header(&zstd[..19]),
+            Ok(Some(CompressedPayload {
+                algorithm: COMPRESSION_ZSTD,
+                compressed_len: 10,
+                decompressed_len: 4096,
+            }))
+        );
+        let max = compressed(COMPRESSION_DEFLATE, MAX_DECOMPRESSED_PAYLOAD_LEN, &[1]);
+        assert!(compression_header(&max[..10]).is_ok());
+        let too_large = compressed(COMPRESSION_DEFLATE, MAX_DECOMPRESSED_PAYLOAD_LEN + 1, &[1]);
+        assert_eq!(
+            compression_header(&too_large[..10]),
+            Err(PayloadError::DecompressedTooLarge)
+        );
+        assert_eq!(
+            compression_header(&compressed(9, 64, &[1])[..10]),
+            Err(PayloadError::UnsupportedCompression)
+        );
+        // A header with no body, or declaring nothing to unpack, is malformed
+        assert_eq!(
+            compression_header(&zstd[..COMPRESSED_HEADER_LEN]),
+            Err(PayloadError::SchemaMismatch)
+        );
+        assert_eq!(
+            compression_header(&compressed(COMPRESSION_ZSTD, 0, &[1])[..10]),
+            Err(PayloadError::SchemaMismatch)
+        );
+    }
+}
@@ -0,0 +1,132 @@
+//! Router PDA seeds and off-chain derivation. On-chain code derives through
+//! `Pubkey::find_program_address`; the derivation here is the same algorithm without the
+//! solana_program dependency, so browsers can compute account addresses locally.
+
+pub const CONFIG_SEED: &[u8] = b"zpx_config";
+pub const FORWARD_ESCROW_SEED: &[u8] = b"forward_escrow";
+pub const FORWARD_ESCROW_VAULT_SEED: &[u8] = b"forward_escrow_vault";
+pub const HUB_PROTOCOL_VAULT_SEED: &[u8] = b"hub_protocol_vault";
+pub const HUB_RELAYER_VAULT_SEED: &[u8] = b"hub_relayer_vault";
+pub const REPLAY_SEED: &[u8] = b"replay";
+pub const MESSAGE_SEED: &[u8] = b"message";
+pub const ROUTE_VALIDITY_SEED: &[u8] = b"route_validity";
+pub const RELAYER_ACTIVITY_SEED: &[u8] = b"relayer_activity";
+pub const SPOKE_RELAYERS_SEED: &[u8] = b"spoke_relayers";
+pub const HUB_REGISTRY_SEED: &[u8] = b"hub_registry";
+pub const RENT_LEDGER_SEED: &[u8] = b"rent_ledger";
+
+#[cfg(not(target_os = "solana"))]
+pub use derive::*;
+
+#[cfg(not(target_os = "solana"))]
+mod derive {
+    use super::*;
+    use curve25519_dalek::edwards::CompressedEdwardsY;
+    use sha2::{Digest, Sha256};
+
+    const MAX_SEEDS: usize = 16;
+    const MAX_SEED_LEN: usize = 32;
+    const PDA_MARKER: &[u8] = b"ProgramDerivedAddress";
+
+    /// `Pubkey::create_program_address`: None for over-long seeds or an on-curve result.
+    pub fn create_program_address(seeds: &[&[u8]], program_id: &[u8; 32]) -> Option<[u8; 32]> {
+        if seeds.len() > MAX_SEEDS || seeds.iter().any(|s| s.len() > MAX_SEED_LEN) {
+            return None;
+        }
+        let mut hasher = Sha256::new();
+        for seed in seeds {
+            hasher.update(seed);
+        }
+        hasher.update(program_id);
+        hasher.update(PDA_MARKER);
+        let address: [u8; 32] = hasher.finalize().into();
+        if CompressedEdwardsY(address).decompress().is_some() {
+            return None;
+        }
+        Some(address)
+    }
+
+    /// `Pubkey::find_program_address` for up to 15 caller seeds: the first off-curve
+    /// address from bump 255 down.
+    pub fn find_program_address(seeds: &[&[u8]], program_id: &[u8; 32]) -> ([u8; 32], u8) {
+        assert!(seeds.len() < MAX_SEEDS, "too many seeds");
+        for bump in (0..=u8::MAX).rev() {
+            let bump_seed = [bump];
+            let mut with_bump: [&[u8]; MAX_SEEDS] = [&[]; MAX_SEEDS];
+            with_bump[..seeds.len()].copy_from_slice(seeds);
+            with_bump[seeds.len()] = &bump_seed;
+            if let Some(address) = create_program_address(&with_bump[..=seeds.len()], program_id) {
+                return (address, bump);
+            }
+        }
+        panic!("no viable bump");
+    }
+
+    pub fn config(program_id: &[u8; 32]) -> ([u8; 32], u8) {
+        find_program_address(&[CONFIG_SEED], program_id)
+    }
+
+    /// [b"forward_escrow", user, nonce u64 le]
+    pub fn forward_escrow(program_id: &[u8; 32], user: &[u8; 32], nonce: u64) -> ([u8; 32], u8) {
+        find_program_address(
+            &[FORWARD_ESCROW_SEED, user, &nonce.to_le_bytes()],
+            program_id,
+        )
+    }
+
+    pub fn forward_escrow_vault(program_id: &[u8; 32], escrow: &[u8; 32]) -> ([u8; 32], u8) {
+        find_program_address(&[FORWARD_ESCROW_VAULT_SEED, escrow], program_id)
+    }
+
+    pub fn hub_protocol_vault(program_id: &[u8; 32], mint: &[u8; 32]) -> ([u8; 32], u8) {
+        find_program_address(&[HUB_PROTOCOL_VAULT_SEED, mint], program_id)
+    }
+
+    pub fn hub_relayer_vault(program_id: &[u8; 32], mint: &[u8; 32]) -> ([u8; 32], u8) {
+        find_program_address(&[HUB_RELAYER_VAULT_SEED, mint], program_id)
+    }
+
+    /// Replay marker of a finalized message.
+    pub fn replay(program_id: &[u8; 32], message_hash: &[u8; 32]) -> ([u8; 32], u8) {
+        find_program_address(&[REPLAY_SEED, message_hash], program_id)
+    }
+
+    /// MessagePreimage [b"message", initiator, nonce u64 le]
+    pub fn message_preimage(
+        program_id: &[u8; 32],
+        initiator: &[u8; 32],
+        nonce: u64,
+    ) -> ([u8; 32], u8) {
+        find_program_address(&[MESSAGE_SEED, initiator, &nonce.to_le_bytes()], program_id)
+    }
+
+    pub fn route_validity(program_id: &[u8; 32], chain_id: u64) -> ([u8; 32], u8) {
+        find_program_address(&[ROUTE_VALIDITY_SEED, &chain_id.to_le_bytes()], program_id)
+    }
+
+    pub fn relayer_activity(program_id: &[u8; 32], relayer: &[u8; 32]) -> ([u8; 32], u8) {
+        find_program_address(&[RELAYER_ACTIVITY_SEED, relayer], program_id)
+    }
+
+    pub fn spoke_relayers(program_id: &[u8; 32], spoke_id: u32) -> ([u8; 32], u8) {
+        find_program_address(&[SPOKE_RELAYERS_SEED, &spoke_id.to_le_bytes()], program_id)
+    }
+
+    pub fn hub_registry(program_id: &[u8; 32]) -> ([u8; 32], u8) {
+        find_program_address(&[HUB_REGISTRY_SEED], program_id)
+    }
+
+    /// Sandbox config, `[b"zpx_config", namespace]`; an empty namespace is the canonical one.
+    pub fn sandbox_config(program_id: &[u8; 32], namespace: &[u8]) -> ([u8; 32], u8) {
+        find_program_address(&[CONFIG_SEED, namespace], program_id)
+    }
+
+    /// Sandbox registry, `[b"hub_registry", namespace]`.
+    pub fn sandbox_hub_registry(program_id: &[u8; 32], namespace: &[u8]) -> ([u8; 32], u8) {
+        find_program_address(&[HUB_REGISTRY_SEED, namespace], program_id)
+    }
+
+    pub fn rent_ledger(program_id: &[u8; 32]) -> ([u8; 32], u8) {
+        find_program_address(&[RENT_LEDGER_SEED], program_id)
+    }
+}
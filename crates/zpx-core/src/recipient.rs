@@ -0,0 +1,182 @@
+//! Typed cross-chain recipients. A recipient travels as a one-byte tag naming its address
+//! format followed by the 32-byte word CCTP and the message hash carry, so a 20-byte EVM
+//! address can never be read back as a Solana key (or the reverse).
+
+use zpx_chains::{address_format, AddressFormat, CctpDomain};
+
+/// Length of `Recipient::encode`: tag + 32-byte word.
+pub const RECIPIENT_ENCODED_LEN: usize = 33;
+
+pub const TAG_EVM20: u8 = 1;
+pub const TAG_SOLANA32: u8 = 2;
+pub const TAG_COSMOS32: u8 = 3;
+pub const TAG_BTC_SCRIPT_HASH: u8 = 4;
+
+/// Why a recipient was rejected; the router maps each to its ErrorCode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecipientError {
+    /// Tag byte is not one of the TAG_* constants
+    UnknownTag,
+    /// Encoding is not RECIPIENT_ENCODED_LEN bytes
+    InvalidLength,
+    /// An EVM word with non-zero bytes in its 12-byte padding
+    NonCanonical,
+    /// The all-zero address
+    ZeroAddress,
+    /// The recipient's format is not the destination chain's address format
+    FormatMismatch,
+}
+
+/// Recipient of a cross-chain transfer, tagged with its address format.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Recipient {
+    /// 20-byte EVM address
+    Evm20([u8; 20]),
+    /// ed25519 public key
+    Solana32([u8; 32]),
+    /// 32-byte hash of a Cosmos bech32 account's data part (e.g. Noble forwarding)
+    Cosmos32([u8; 32]),
+    /// Bitcoin witness script hash (P2WSH) or taproot output key
+    BtcScriptHash([u8; 32]),
+}
+
+impl Recipient {
+    pub fn tag(&self) -> u8 {
+        match self {
+            Recipient::Evm20(_) => TAG_EVM20,
+            Recipient::Solana32(_) => TAG_SOLANA32,
+            Recipient::Cosmos32(_) => TAG_COSMOS32,
+            Recipient::BtcScriptHash(_) => TAG_BTC_SCRIPT_HASH,
+        }
+    }
+
+    pub fn format(&self) -> AddressFormat {
+        match self {
+            Recipient::Evm20(_) => AddressFormat::Evm,
+            Recipient::Solana32(_) => AddressFormat::Solana,
+            Recipient::Cosmos32(_) => AddressFormat::Cosmos,
+            Recipient::BtcScriptHash(_) => AddressFormat::Bitcoin,
+        }
+    }
+
+    /// The 32-byte recipient word (`mint_recipient`); EVM addresses are left-padded.
+    pub fn word(&self) -> [u8; 32] {
+        match self {
+            Recipient::Evm20(address) => {
+                let mut word = [0u8; 32];
+                word[12..].copy_from_slice(address);
+                word
+            }
+            Recipient::Solana32(word)
+            | Recipient::Cosmos32(word)
+            | Recipient::BtcScriptHash(word) => *word,
+        }
+    }
+
+    /// `tag || word`.
+    pub fn encode(&self) -> [u8; RECIPIENT_ENCODED_LEN] {
+        let mut out = [0u8; RECIPIENT_ENCODED_LEN];
+        out[0] = self.tag();
+        out[1..].copy_from_slice(&self.word());
+        out
+    }
+
+    /// Parse `tag || word`, rejecting unknown tags and non-canonical EVM padding.
+    pub fn decode(bytes: &[u8]) -> Result<Self, RecipientError> {
+        if bytes.len() != RECIPIENT_ENCODED_LEN {
+            return Err(RecipientError::InvalidLength);
+        }
+        let word: [u8; 32] = bytes[1..].try_into().unwrap();
+        match bytes[0] {
+            TAG_EVM20 => {
+                if word[..12].iter().any(|b| *b != 0) {
+                    return Err(RecipientError::NonCanonical);
+                }
+                Ok(Recipient::Evm20(word[12..].try_into().unwrap()))
+            }
+            TAG_SOLANA32 => Ok(Recipient::Solana32(word)),
+            TAG_COSMOS32 => Ok(Recipient::Cosmos32(word)),
+            TAG_BTC_SCRIPT_HASH => Ok(Recipient::BtcScriptHash(word)),
+            _ => Err(RecipientError::UnknownTag),
+        }
+    }
+
+    /// Non-zero and encoded in `format`.
+    pub fn check_format(&self, format: AddressFormat) -> Result<(), RecipientError> {
+        if self.word().iter().all(|b| *b == 0) {
+            return Err(RecipientError::ZeroAddress);
+        }
+        if self.format() != format {
+            return Err(RecipientError::FormatMismatch);
+        }
+        Ok(())
+    }
+}
+
+/// Validate `recipient` against the address format registered for CCTP domain
+/// `dst_domain`. Domains without a registered format only reject the zero address.
+pub fn check_for_domain(recipient: &Recipient, dst_domain: u32) -> Result<(), RecipientError> {
+    match address_format(CctpDomain(dst_domain)) {
+        Some(format) => recipient.check_format(format),
+        None => recipient.check_format(recipient.format()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodings_round_trip_and_match_destination_formats() {
+        let evm = Recipient::Evm20([0xab; 20]);
+        let mut word = [0u8; 32];
+        word[12..].copy_from_slice(&[0xab; 20]);
+        assert_eq!(evm.word(), word);
+        assert_eq!(evm.encode()[0], TAG_EVM20);
+        for r in [
+            evm,
+            Recipient::Solana32([1; 32]),
+            Recipient::Cosmos32([2; 32]),
+            Recipient::BtcScriptHash([3; 32]),
+        ] {
+            assert_eq!(Recipient::decode(&r.encode()), Ok(r));
+        }
+
+        let mut padded = evm.encode();
+        padded[1] = 1;
+        assert_eq!(
+            Recipient::decode(&padded),
+            Err(RecipientError::NonCanonical)
+        );
+        padded[0] = 9;
+        assert_eq!(Recipient::decode(&padded), Err(RecipientError::UnknownTag));
+        assert_eq!(
+            Recipient::decode(&[TAG_SOLANA32; 32]),
+            Err(RecipientError::InvalidLength)
+        );
+
+        let base = zpx_chains::CCTP_BASE.0;
+        assert_eq!(check_for_domain(&evm, base), Ok(()));
+        assert_eq!(
+            check_for_domain(&Recipient::Solana32([1; 32]), base),
+            Err(RecipientError::FormatMismatch)
+        );
+        assert_eq!(
+            check_for_domain(&evm, zpx_chains::CCTP_SOLANA.0),
+            Err(RecipientError::FormatMismatch)
+        );
+        assert_eq!(
+            check_for_domain(&Recipient::Cosmos32([2; 32]), zpx_chains::CCTP_NOBLE.0),
+            Ok(())
+        );
+        assert_eq!(
+            check_for_domain(&Recipient::Evm20([0; 20]), base),
+            Err(RecipientError::ZeroAddress)
+        );
+        // Unregistered domains accept any non-zero recipient
+        assert_eq!(
+            check_for_domain(&Recipient::BtcScriptHash([3; 32]), 99),
+            Ok(())
+        );
+    }
+}
@@ -0,0 +1,201 @@
+//! wasm-bindgen exports for the browser SDK. Byte arguments are 32-byte `Uint8Array`s;
+//! amounts, chain ids and nonces are `bigint`s.
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use wasm_bindgen::prelude::*;
+
+use crate::{amount, fees, hash, payload, pda, recipient};
+
+fn bytes32(name: &str, bytes: &[u8]) -> Result<[u8; 32], JsError> {
+    bytes
+        .try_into()
+        .map_err(|_| JsError::new(&alloc::format!("{name} must be 32 bytes")))
+}
+
+/// Fees of a forward at the hub's configured rates.
+#[wasm_bindgen]
+pub struct FeeQuote {
+    pub protocol_fee: u64,
+    pub relayer_fee: u64,
+    pub forward_amount: u64,
+}
+
+/// A program derived address and its bump.
+#[wasm_bindgen(getter_with_clone)]
+pub struct Pda {
+    pub address: Vec<u8>,
+    pub bump: u8,
+}
+
+impl From<([u8; 32], u8)> for Pda {
+    fn from((address, bump): ([u8; 32], u8)) -> Self {
+        Pda {
+            address: address.to_vec(),
+            bump,
+        }
+    }
+}
+
+#[wasm_bindgen(js_name = feeQuote)]
+pub fn fee_quote(
+    amount: u64,
+    protocol_fee_bps: u16,
+    relayer_fee_bps: u16,
+) -> Result<FeeQuote, JsError> {
+    let q = fees::quote(amount, protocol_fee_bps, relayer_fee_bps)
+        .map_err(|e| JsError::new(&alloc::format!("{e:?}")))?;
+    Ok(FeeQuote {
+        protocol_fee: q.protocol_fee,
+        relayer_fee: q.relayer_fee,
+        forward_amount: q.forward_amount,
+    })
+}
+
+/// Payload hash a message commits to; pass both validity bounds or neither.
+#[wasm_bindgen(js_name = payloadHash)]
+pub fn payload_hash(data: &[u8], sent_at: Option<u64>, expires_at: Option<u64>) -> Vec<u8> {
+    payload::payload_hash(data, sent_at.zip(expires_at)).to_vec()
+}
+
+/// Source-leg message hash stamped by a hub (`hub` = router program id) in `env` with
+/// hash schema `version` (0 = legacy packing). Pass the mint's `amount_decimals` on routes
+/// with decimals normalization; omit it for raw amounts.
+#[allow(clippy::too_many_arguments)]
+#[wasm_bindgen(js_name = messageHash)]
+pub fn message_hash(
+    hub: &[u8],
+    env: u8,
+    version: u8,
+    src_chain: u64,
+    dst_chain: u64,
+    target_adapter: &[u8],
+    mint: &[u8],
+    forward_amount: u64,
+    payload_hash: &[u8],
+    nonce: u64,
+    amount_decimals: Option<u8>,
+) -> Result<Vec<u8>, JsError> {
+    let scheme = hash::HashScheme::for_version(bytes32("hub", hub)?, env, version);
+    Ok(hash::source_message_hash_word(
+        scheme,
+        src_chain,
+        dst_chain,
+        bytes32("target_adapter", target_adapter)?,
+        bytes32("mint", mint)?,
+        amount::amount_word(forward_amount, amount_decimals)
+            .map_err(|e| JsError::new(&alloc::format!("{e:?}")))?,
+        bytes32("payload_hash", payload_hash)?,
+        nonce,
+    )
+    .to_vec())
+}
+
+/// `amount` base units of a mint with `decimals` as a canonical 18-decimal uint256.
+#[wasm_bindgen(js_name = toCanonicalAmount)]
+pub fn to_canonical_amount(amount: u64, decimals: u8) -> Result<Vec<u8>, JsError> {
+    amount::to_canonical(amount, decimals)
+        .map(|word| word.to_vec())
+        .map_err(|e| JsError::new(&alloc::format!("{e:?}")))
+}
+
+/// Base units of a mint with `decimals` for a canonical 18-decimal uint256.
+#[wasm_bindgen(js_name = fromCanonicalAmount)]
+pub fn from_canonical_amount(amount_be: &[u8], decimals: u8) -> Result<u64, JsError> {
+    amount::from_canonical(&bytes32("amount_be", amount_be)?, decimals)
+        .map_err(|e| JsError::new(&alloc::format!("{e:?}")))
+}
+
+/// `tag || word` encoding of a recipient: `tag` is one of the recipient TAG_* constants and
+/// `address` 20 bytes for EVM, 32 otherwise. Fails when the address is not valid for the
+/// CCTP destination domain `dst_domain`.
+#[wasm_bindgen(js_name = encodeRecipient)]
+pub fn encode_recipient(tag: u8, address: &[u8], dst_domain: u32) -> Result<Vec<u8>, JsError> {
+    let r = if tag == recipient::TAG_EVM20 {
+        let address: [u8; 20] = address
+            .try_into()
+            .map_err(|_| JsError::new("EVM address must be 20 bytes"))?;
+        recipient::Recipient::Evm20(address)
+    } else {
+        let mut encoded = [0u8; recipient::RECIPIENT_ENCODED_LEN];
+        encoded[0] = tag;
+        encoded[1..].copy_from_slice(&bytes32("address", address)?);
+        recipient::Recipient::decode(&encoded)
+            .map_err(|e| JsError::new(&alloc::format!("{e:?}")))?
+    };
+    recipient::check_for_domain(&r, dst_domain)
+        .map_err(|e| JsError::new(&alloc::format!("{e:?}")))?;
+    Ok(r.encode().to_vec())
+}
+
+#[allow(clippy::too_many_arguments)]
+#[wasm_bindgen(js_name = globalRouteId)]
+pub fn global_route_id(
+    hub: &[u8],
+    env: u8,
+    version: u8,
+    src_chain: u64,
+    dst_chain: u64,
+    initiator: &[u8],
+    message_hash: &[u8],
+    nonce: u64,
+) -> Result<Vec<u8>, JsError> {
+    let scheme = hash::HashScheme::for_version(bytes32("hub", hub)?, env, version);
+    Ok(hash::global_route_id(
+        scheme,
+        src_chain,
+        dst_chain,
+        bytes32("initiator", initiator)?,
+        bytes32("message_hash", message_hash)?,
+        nonce,
+    )
+    .to_vec())
+}
+
+#[wasm_bindgen(js_name = configAddress)]
+pub fn config_address(program_id: &[u8]) -> Result<Pda, JsError> {
+    Ok(pda::config(&bytes32("program_id", program_id)?).into())
+}
+
+#[wasm_bindgen(js_name = forwardEscrowAddress)]
+pub fn forward_escrow_address(program_id: &[u8], user: &[u8], nonce: u64) -> Result<Pda, JsError> {
+    Ok(pda::forward_escrow(
+        &bytes32("program_id", program_id)?,
+        &bytes32("user", user)?,
+        nonce,
+    )
+    .into())
+}
+
+#[wasm_bindgen(js_name = forwardEscrowVaultAddress)]
+pub fn forward_escrow_vault_address(program_id: &[u8], escrow: &[u8]) -> Result<Pda, JsError> {
+    Ok(pda::forward_escrow_vault(
+        &bytes32("program_id", program_id)?,
+        &bytes32("escrow", escrow)?,
+    )
+    .into())
+}
+
+#[wasm_bindgen(js_name = replayAddress)]
+pub fn replay_address(program_id: &[u8], message_hash: &[u8]) -> Result<Pda, JsError> {
+    Ok(pda::replay(
+        &bytes32("program_id", program_id)?,
+        &bytes32("message_hash", message_hash)?,
+    )
+    .into())
+}
+
+#[wasm_bindgen(js_name = messagePreimageAddress)]
+pub fn message_preimage_address(
+    program_id: &[u8],
+    initiator: &[u8],
+    nonce: u64,
+) -> Result<Pda, JsError> {
+    Ok(pda::message_preimage(
+        &bytes32("program_id", program_id)?,
+        &bytes32("initiator", initiator)?,
+        nonce,
+    )
+    .into())
+}
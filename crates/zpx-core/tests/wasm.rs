@@ -0,0 +1,73 @@
+//! Browser parity checks: `wasm-pack test --headless --firefox crates/zpx-core -- --features wasm`.
+//! Expected values are the native results, which tests/core_parity.rs in zpx_router pins to
+//! the program.
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+use wasm_bindgen_test::*;
+use zpx_core::{amount, fees, hash, pda, wasm};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+const HUB: [u8; 32] = [7; 32];
+
+#[wasm_bindgen_test]
+fn hashes_match_native() {
+    let payload_hash = wasm::payload_hash(b"zoopx", Some(10), Some(20));
+    assert_eq!(
+        payload_hash,
+        hash::validity_payload_hash(hash::keccak256(&[b"zoopx"]), 10, 20)
+    );
+    let msg = wasm::message_hash(
+        &HUB,
+        hash::ENV_MAINNET,
+        1,
+        10,
+        8453,
+        &[1; 32],
+        &[3; 32],
+        1_000_000,
+        &payload_hash,
+        7,
+        None,
+    )
+    .unwrap();
+    let scheme = hash::HashScheme::domain(HUB, hash::ENV_MAINNET, 1);
+    let expected = hash::source_message_hash(
+        scheme,
+        10,
+        8453,
+        [1; 32],
+        [3; 32],
+        1_000_000,
+        payload_hash.clone().try_into().unwrap(),
+        7,
+    );
+    assert_eq!(msg, expected);
+    assert_eq!(
+        wasm::global_route_id(&HUB, hash::ENV_MAINNET, 1, 10, 8453, &[9; 32], &msg, 7).unwrap(),
+        hash::global_route_id(scheme, 10, 8453, [9; 32], expected, 7)
+    );
+    let canonical = wasm::to_canonical_amount(1_000_000, 6).unwrap();
+    assert_eq!(canonical, amount::to_canonical(1_000_000, 6).unwrap());
+    assert_eq!(
+        wasm::from_canonical_amount(&canonical, 6).unwrap(),
+        1_000_000
+    );
+}
+
+#[wasm_bindgen_test]
+fn quotes_and_addresses_match_native() {
+    let q = wasm::fee_quote(1_000_000, 5, 100).unwrap();
+    let native = fees::quote(1_000_000, 5, 100).unwrap();
+    assert_eq!(
+        (q.protocol_fee, q.relayer_fee, q.forward_amount),
+        (
+            native.protocol_fee,
+            native.relayer_fee,
+            native.forward_amount
+        )
+    );
+    let escrow = wasm::forward_escrow_address(&HUB, &[2; 32], 42).unwrap();
+    let (address, bump) = pda::forward_escrow(&HUB, &[2; 32], 42);
+    assert_eq!((escrow.address, escrow.bump), (address.to_vec(), bump));
+}
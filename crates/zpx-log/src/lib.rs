@@ -0,0 +1,25 @@
+//! Structured program logging shared by the ZoopX on-chain programs.
+//!
+//! `zpx_log!` emits one `zpx: event=<name> key=value ...` line through the caller's
+//! `anchor_lang::prelude::msg!`. Logging is compiled in only when the *calling* crate enables
+//! its `zpx-log` feature, so production builds pay no compute units for it (arguments are
+//! not evaluated either).
+//!
+//! ```ignore
+//! zpx_log!("fee_computed", protocol_fee = proto_fee, relayer_fee = relayer_fee);
+//! // zpx: event=fee_computed protocol_fee=10 relayer_fee=5
+//! ```
+#![no_std]
+
+#[macro_export]
+macro_rules! zpx_log {
+    ($event:literal $(, $key:ident = $value:expr)* $(,)?) => {
+        #[cfg(feature = "zpx-log")]
+        {
+            ::anchor_lang::prelude::msg!(
+                concat!("zpx: event=", $event $(, " ", stringify!($key), "={}")*)
+                $(, $value)*
+            );
+        }
+    };
+}
@@ -0,0 +1,239 @@
+//! Replay markers: one PDA per message hash, `[b"replay", message_hash]` under the program
+//! that finalizes the message. The layout matches the router's Anchor `Replay` account so
+//! markers written here deserialize as `Account<Replay>` there, and legacy 9-byte markers
+//! (discriminator + processed flag) are still understood.
+
+use core::ops::Range;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program::invoke_signed, pubkey::Pubkey,
+    rent::Rent, system_instruction, system_program, sysvar::Sysvar,
+};
+
+pub const REPLAY_SEED: &[u8] = b"replay";
+/// Anchor discriminator of `Replay` (sha256("account:Replay")[..8]).
+pub const REPLAY_DISCRIMINATOR: [u8; 8] = [38, 228, 204, 46, 251, 28, 124, 105];
+// discriminator(8) + processed(1) + finalizer(32) + origin_tx_hash(32)
+pub const REPLAY_SPACE: usize = 8 + 1 + 32 + 32;
+/// Discriminator + processed flag; the size of legacy markers.
+pub const REPLAY_LEGACY_SPACE: usize = 8 + 1;
+pub const REPLAY_PROCESSED: usize = 8;
+pub const REPLAY_FINALIZER: Range<usize> = 9..41;
+pub const REPLAY_ORIGIN_TX_HASH: Range<usize> = 41..73;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReplayError {
+    /// The account is not the `[b"replay", message_hash]` PDA
+    InvalidPda,
+    /// The account exists but is not owned by the program
+    InvalidOwner,
+    /// Too short for a marker, or not a Replay account
+    AccountTooSmall,
+    /// The message was already finalized
+    AlreadyProcessed,
+    /// The marker is missing or not processed yet
+    NotProcessed,
+}
+
+/// Decoded marker; `finalizer` / `origin_tx_hash` are None on legacy markers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReplayMarker {
+    pub processed: bool,
+    pub finalizer: Option<Pubkey>,
+    pub origin_tx_hash: Option<[u8; 32]>,
+}
+
+pub fn replay_address(message_hash: &[u8; 32], program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[REPLAY_SEED, message_hash], program_id)
+}
+
+/// Check `key` is the replay PDA of `message_hash` and return its bump.
+pub fn verify_address(
+    key: &Pubkey,
+    message_hash: &[u8; 32],
+    program_id: &Pubkey,
+) -> Result<u8, ReplayError> {
+    let (expected, bump) = replay_address(message_hash, program_id);
+    if *key != expected {
+        return Err(ReplayError::InvalidPda);
+    }
+    Ok(bump)
+}
+
+/// Decode a marker from raw account data.
+pub fn read(data: &[u8]) -> Result<ReplayMarker, ReplayError> {
+    if data.len() < REPLAY_LEGACY_SPACE || data[..8] != REPLAY_DISCRIMINATOR {
+        return Err(ReplayError::AccountTooSmall);
+    }
+    let full = data.len() >= REPLAY_ORIGIN_TX_HASH.end;
+    Ok(ReplayMarker {
+        processed: data[REPLAY_PROCESSED] == 1,
+        finalizer: (data.len() >= REPLAY_FINALIZER.end)
+            .then(|| Pubkey::new_from_array(data[REPLAY_FINALIZER].try_into().unwrap())),
+        origin_tx_hash: full.then(|| data[REPLAY_ORIGIN_TX_HASH].try_into().unwrap()),
+    })
+}
+
+/// Write a processed marker into `data`. Fields the buffer is too short for (legacy
+/// markers) are skipped.
+pub fn write_processed(data: &mut [u8], finalizer: &Pubkey, origin_tx_hash: &[u8; 32]) {
+    data[..8].copy_from_slice(&REPLAY_DISCRIMINATOR);
+    data[REPLAY_PROCESSED] = 1;
+    if data.len() >= REPLAY_FINALIZER.end {
+        data[REPLAY_FINALIZER].copy_from_slice(finalizer.as_ref());
+    }
+    if data.len() >= REPLAY_ORIGIN_TX_HASH.end {
+        data[REPLAY_ORIGIN_TX_HASH].copy_from_slice(origin_tx_hash);
+    }
+}
+
+/// Existing (pre-created) marker: must belong to `program_id`, be a Replay account and not
+/// be processed yet; it is then marked processed.
+pub fn check_and_mark(
+    replay: &AccountInfo,
+    program_id: &Pubkey,
+    finalizer: &Pubkey,
+    origin_tx_hash: &[u8; 32],
+) -> Result<(), ReplayError> {
+    if replay.owner != program_id {
+        return Err(ReplayError::InvalidOwner);
+    }
+    let mut data = replay
+        .try_borrow_mut_data()
+        .map_err(|_| ReplayError::AccountTooSmall)?;
+    if read(&data)?.processed {
+        return Err(ReplayError::AlreadyProcessed);
+    }
+    write_processed(&mut data, finalizer, origin_tx_hash);
+    Ok(())
+}
+
+/// Whether `replay` is a processed marker of `program_id`; returns the decoded marker.
+pub fn require_processed(
+    replay: &AccountInfo,
+    program_id: &Pubkey,
+) -> Result<ReplayMarker, ReplayError> {
+    if replay.owner != program_id {
+        return Err(ReplayError::NotProcessed);
+    }
+    let data = replay
+        .try_borrow_data()
+        .map_err(|_| ReplayError::NotProcessed)?;
+    match read(&data) {
+        Ok(marker) if marker.processed => Ok(marker),
+        _ => Err(ReplayError::NotProcessed),
+    }
+}
+
+/// First use: create the marker PDA (REPLAY_SPACE, rent paid by `payer`) and write it as
+/// processed.
+#[allow(clippy::too_many_arguments)]
+pub fn create_processed<'info>(
+    payer: &AccountInfo<'info>,
+    replay: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    message_hash: &[u8; 32],
+    bump: u8,
+    program_id: &Pubkey,
+    finalizer: &Pubkey,
+    origin_tx_hash: &[u8; 32],
+) -> ProgramResult {
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            replay.key,
+            Rent::get()?.minimum_balance(REPLAY_SPACE),
+            REPLAY_SPACE as u64,
+            program_id,
+        ),
+        &[payer.clone(), replay.clone(), system_program.clone()],
+        &[&[REPLAY_SEED, message_hash, &[bump]]],
+    )?;
+    write_processed(
+        &mut replay.try_borrow_mut_data()?,
+        finalizer,
+        origin_tx_hash,
+    );
+    Ok(())
+}
+
+/// Rent recovery: close a processed marker into `destination`. Returns the lamports moved.
+/// Closing forgets the message, so callers must only do this once a replay can no longer
+/// be delivered (e.g. past the route's validity window).
+pub fn close<'info>(
+    replay: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+    program_id: &Pubkey,
+) -> Result<u64, ReplayError> {
+    require_processed(replay, program_id)?;
+    let lamports = replay.lamports();
+    **destination
+        .try_borrow_mut_lamports()
+        .map_err(|_| ReplayError::InvalidOwner)? += lamports;
+    **replay
+        .try_borrow_mut_lamports()
+        .map_err(|_| ReplayError::InvalidOwner)? = 0;
+    replay.assign(&system_program::ID);
+    replay
+        .realloc(0, false)
+        .map_err(|_| ReplayError::AccountTooSmall)?;
+    Ok(lamports)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn marker_layout_round_trips() {
+        let finalizer = Pubkey::new_unique();
+        let mut data = vec![0u8; REPLAY_SPACE];
+        data[..8].copy_from_slice(&REPLAY_DISCRIMINATOR);
+        assert!(!read(&data).unwrap().processed);
+        write_processed(&mut data, &finalizer, &[3; 32]);
+        assert_eq!(
+            read(&data).unwrap(),
+            ReplayMarker {
+                processed: true,
+                finalizer: Some(finalizer),
+                origin_tx_hash: Some([3; 32]),
+            }
+        );
+        let mut legacy = vec![0u8; REPLAY_LEGACY_SPACE];
+        write_processed(&mut legacy, &finalizer, &[3; 32]);
+        assert_eq!(read(&legacy).unwrap().finalizer, None);
+        assert_eq!(read(&legacy[..8]), Err(ReplayError::AccountTooSmall));
+    }
+
+    #[test]
+    fn check_and_mark_refuses_a_second_use() {
+        let program_id = Pubkey::new_unique();
+        let key = Pubkey::new_unique();
+        let mut lamports = 1_000_000;
+        let mut data = vec![0u8; REPLAY_SPACE];
+        data[..8].copy_from_slice(&REPLAY_DISCRIMINATOR);
+        let replay = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &program_id,
+            false,
+            0,
+        );
+        let finalizer = Pubkey::new_unique();
+        check_and_mark(&replay, &program_id, &finalizer, &[0; 32]).unwrap();
+        assert_eq!(
+            check_and_mark(&replay, &program_id, &finalizer, &[0; 32]),
+            Err(ReplayError::AlreadyProcessed)
+        );
+        assert_eq!(
+            check_and_mark(&replay, &Pubkey::new_unique(), &finalizer, &[0; 32]),
+            Err(ReplayError::InvalidOwner)
+        );
+        assert_eq!(
+            require_processed(&replay, &program_id).unwrap().finalizer,
+            Some(finalizer)
+        );
+    }
+}
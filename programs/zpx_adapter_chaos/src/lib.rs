@@ -0,0 +1,197 @@
+// SPDX-License-Identifier: MIT
+//! Failure injection adapter for chaos testing the router. It implements the adapter
+//! `validate_payload` interface that `zpx_router::dry_run_adapter` calls and the
+//! `dispatch_batch` interface of `zpx_router::forward_batch_via_spoke`. Its behaviour comes
+//! from the ChaosConfig PDA: pass, fail with a chosen error code, burn compute, write
+//! oversized return data, or fail until the caller's nth attempt. QA and program-tests only;
+//! never register it on a mainnet spoke.
+#![allow(unexpected_cfgs)]
+#![forbid(unsafe_code)]
+#![allow(clippy::result_large_err)]
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::{set_return_data, MAX_RETURN_DATA};
+
+declare_id!("ChaosAdapter11111111111111111111111111111111");
+
+pub const CHAOS_CONFIG_SEED: &[u8] = b"chaos_config";
+
+/// Validate and return `[1]`, like a healthy adapter
+pub const MODE_PASS: u8 = 0;
+/// Fail with `ProgramError::Custom(error_code)`
+pub const MODE_FAIL: u8 = 1;
+/// Consume about `burn_cu` compute units, then pass
+pub const MODE_BURN_CU: u8 = 2;
+/// Write `return_data_len` bytes of 0xEE as return data instead of `[1]`
+pub const MODE_LARGE_RETURN: u8 = 3;
+/// Fail with `error_code` until the attempt number in the payload reaches `succeed_after`
+pub const MODE_FLAKY: u8 = 4;
+pub const MODE_COUNT: u8 = 5;
+
+/// Compute units one burn round costs on SBF (a sha256 of 32 bytes plus loop overhead).
+const BURN_ROUND_CU: u32 = 100;
+
+#[program]
+pub mod zpx_adapter_chaos {
+    use super::*;
+
+    /// Create the ChaosConfig PDA in MODE_PASS with the signer as its authority.
+    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+        let cfg = &mut ctx.accounts.config;
+        cfg.authority = ctx.accounts.authority.key();
+        cfg.mode = MODE_PASS;
+        cfg.error_code = 0;
+        cfg.burn_cu = 0;
+        cfg.return_data_len = 0;
+        cfg.succeed_after = 0;
+        cfg.bump = ctx.bumps.get("config").copied().unwrap();
+        Ok(())
+    }
+
+    /// Select the failure mode and its parameters (authority-only).
+    pub fn configure(
+        ctx: Context<Configure>,
+        mode: u8,
+        error_code: u32,
+        burn_cu: u32,
+        return_data_len: u16,
+        succeed_after: u32,
+    ) -> Result<()> {
+        require!(mode < MODE_COUNT, ChaosError::UnknownMode);
+        require!(
+            return_data_len as usize <= MAX_RETURN_DATA,
+            ChaosError::ReturnDataTooLarge
+        );
+        let cfg = &mut ctx.accounts.config;
+        cfg.mode = mode;
+        cfg.error_code = error_code;
+        cfg.burn_cu = burn_cu;
+        cfg.return_data_len = return_data_len;
+        cfg.succeed_after = succeed_after;
+        Ok(())
+    }
+
+    /// Adapter interface called by the router's dry run. The ChaosConfig PDA must be the
+    /// first forwarded account.
+    pub fn validate_payload(ctx: Context<ValidatePayload>, payload: Vec<u8>) -> Result<()> {
+        apply_mode(&ctx.accounts.config, &payload)
+    }
+
+    /// Adapter interface called by the router's batched forward, with the ChaosConfig PDA as
+    /// the first forwarded account. Logs the batch and then behaves as validate_payload does.
+    pub fn dispatch_batch(
+        ctx: Context<ValidatePayload>,
+        entries: Vec<BatchDispatch>,
+        payload: Vec<u8>,
+    ) -> Result<()> {
+        let total = entries.iter().map(|e| e.net_amount as u128).sum::<u128>();
+        msg!("chaos batch of {} entries, {} total", entries.len(), total);
+        apply_mode(&ctx.accounts.config, &payload)
+    }
+}
+
+/// Fail, burn, or set return data as `cfg.mode` says; `[1]` on a plain pass.
+fn apply_mode(cfg: &ChaosConfig, payload: &[u8]) -> Result<()> {
+    match cfg.mode {
+        MODE_FAIL => return Err(ProgramError::Custom(cfg.error_code).into()),
+        MODE_BURN_CU => burn_compute(cfg.burn_cu),
+        MODE_LARGE_RETURN => {
+            set_return_data(&vec![0xEE; cfg.return_data_len as usize]);
+            return Ok(());
+        }
+        MODE_FLAKY if attempt(payload) < cfg.succeed_after => {
+            return Err(ProgramError::Custom(cfg.error_code).into());
+        }
+        _ => {}
+    }
+    set_return_data(&[1]);
+    Ok(())
+}
+
+/// One message of a router batch; same layout as `zpx_router::BatchDispatch`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct BatchDispatch {
+    pub user: Pubkey,
+    pub nonce: u64,
+    pub net_amount: u64,
+    pub dst_domain: u32,
+    pub mint_recipient: [u8; 32],
+}
+
+/// Attempt number a relayer harness puts in the first 4 payload bytes (little endian; 0
+/// when shorter). A failed attempt rolls back any on-chain counter, so retries are counted
+/// by the caller rather than in the ChaosConfig.
+pub fn attempt(payload: &[u8]) -> u32 {
+    payload
+        .get(..4)
+        .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+        .unwrap_or_default()
+}
+
+/// Hash in a loop for about `cu` compute units (`cu / BURN_ROUND_CU` rounds).
+fn burn_compute(cu: u32) {
+    let mut digest = [0u8; 32];
+    for _ in 0..cu / BURN_ROUND_CU {
+        digest = anchor_lang::solana_program::hash::hash(&digest).to_bytes();
+    }
+    msg!("chaos burned {} CU ({:x})", cu, digest[0]);
+}
+
+#[account]
+pub struct ChaosConfig {
+    pub authority: Pubkey,
+    /// One of MODE_*
+    pub mode: u8,
+    pub error_code: u32,
+    pub burn_cu: u32,
+    pub return_data_len: u16,
+    pub succeed_after: u32,
+    pub bump: u8,
+}
+
+// discriminator(8) + authority(32) + mode(1) + error_code(4) + burn_cu(4)
+// + return_data_len(2) + succeed_after(4) + bump(1)
+pub const CHAOS_CONFIG_SPACE: usize = 8 + 32 + 1 + 4 + 4 + 2 + 4 + 1;
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        init,
+        payer = authority,
+        space = CHAOS_CONFIG_SPACE,
+        seeds = [CHAOS_CONFIG_SEED],
+        bump
+    )]
+    pub config: Account<'info, ChaosConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Configure<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [CHAOS_CONFIG_SEED],
+        bump = config.bump,
+        has_one = authority @ ChaosError::Unauthorized
+    )]
+    pub config: Account<'info, ChaosConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ValidatePayload<'info> {
+    #[account(seeds = [CHAOS_CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ChaosConfig>,
+}
+
+#[error_code]
+pub enum ChaosError {
+    #[msg("Unauthorized")]
+    Unauthorized,
+    #[msg("Unknown chaos mode")]
+    UnknownMode,
+    #[msg("Return data length exceeds MAX_RETURN_DATA")]
+    ReturnDataTooLarge,
+}
@@ -4,7 +4,12 @@
 #![allow(unexpected_cfgs)]
 
 use anchor_lang::prelude::*;
+use zpx_log::zpx_log;
 declare_id!("11111111111111111111111111111111");
+
+/// Seed of this program's PDA that signs zpx_router::withdraw_lp_fees CPIs to pull LP fees
+/// out of the router's hub_lp_vault. Must match zpx_router::LP_AUTHORITY_SEED.
+pub const LP_AUTHORITY_SEED: &[u8] = b"lp_authority";
 // Temporarily gate the Anchor `#[program]` macro behind the `with-anchor` feature so
 // that cargo-based builds and checks can run without Anchor's procedural-macro safety
 // checks. To enable Anchor-specific checks (for Anchor builds), add `--features with-anchor`
@@ -13,6 +18,7 @@ declare_id!("11111111111111111111111111111111");
 pub mod zpx_lp_vaults {
     use super::*;
     pub fn ping(_ctx: Context<Ping>) -> Result<()> {
+        zpx_log!("ping");
         Ok(())
     }
 }
@@ -0,0 +1,380 @@
+//! forward_usdc_fast against the generic forward_via_spoke on identical escrows.
+//!
+//! Run with `cargo bench -p zpx_router --bench forward_paths`. Both paths run natively
+//! under solana-program-test, so wall time stands in for compute: the gap is the registry
+//! scan and the PDA derivations (a sha256 and curve check per bump tried) that the fast
+//! path replaces with cached-key comparisons. Account count and transaction size are exact.
+
+use anchor_lang::{AccountSerialize, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::Instruction,
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    sysvar,
+    transaction::Transaction,
+};
+use std::time::{Duration, Instant};
+use zpx_router::{Config, Registry, SpokeEntry, MAX_SPOKES, USDC_FAST_PATH_SEED};
+
+const ITERS: u64 = 50;
+const AMOUNT: u64 = 1_000_000;
+const SPOKE_ID: u32 = 1;
+
+fn anchor_account<T: AccountSerialize>(value: &T) -> Account {
+    let mut data = Vec::new();
+    value.try_serialize(&mut data).unwrap();
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: zpx_router::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn packed_account<T: Pack>(value: T) -> Account {
+    let mut data = vec![0u8; T::LEN];
+    value.pack_into_slice(&mut data);
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: spl_token::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> Account {
+    packed_account(spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        delegate: COption::None,
+        state: spl_token::state::AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    })
+}
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &zpx_router::ID).0
+}
+
+type BuildIx = fn(&Bench, u64) -> Instruction;
+
+struct Bench {
+    admin: Keypair,
+    relayer: Keypair,
+    user: Keypair,
+    mint: Pubkey,
+    from: Pubkey,
+    adapter_target: Pubkey,
+    relayer_ata: Pubkey,
+}
+
+impl Bench {
+    fn escrow(&self, nonce: u64) -> (Pubkey, Pubkey) {
+        let escrow = pda(&[
+            b"forward_escrow",
+            self.user.pubkey().as_ref(),
+            &nonce.to_le_bytes(),
+        ]);
+        (escrow, pda(&[b"forward_escrow_vault", escrow.as_ref()]))
+    }
+
+    fn deposit(&self, nonce: u64) -> Instruction {
+        let (escrow, escrow_vault) = self.escrow(nonce);
+        Instruction {
+            program_id: zpx_router::ID,
+            accounts: zpx_router::accounts::DepositForwardEscrow {
+                user: self.user.pubkey(),
+                mint: self.mint,
+                from: self.from,
+                escrow,
+                escrow_vault,
+                config: pda(&[b"zpx_config"]),
+                rent_ledger: pda(&[b"rent_ledger"]),
+                token_program: spl_token::ID,
+                system_program: solana_sdk::system_program::ID,
+                rent: sysvar::rent::ID,
+            }
+            .to_account_metas(None),
+            data: zpx_router::instruction::DepositForwardEscrow {
+                nonce,
+                spoke_id: SPOKE_ID,
+                amount: AMOUNT,
+                dst_domain: zpx_chains::CCTP_BASE.0,
+                recipient: zpx_router::Recipient::Evm20([1; 20]),
+                lamport_budget: 0,
+                relayer_tip: 0,
+                expiry_reminder_slot: 0,
+            }
+            .data(),
+        }
+    }
+
+    fn generic(&self, nonce: u64) -> Instruction {
+        let (escrow, escrow_vault) = self.escrow(nonce);
+        let (r, mint) = (self.relayer.pubkey(), self.mint);
+        let mut mint_recipient = [0u8; 32];
+        mint_recipient[12..].copy_from_slice(&[1; 20]);
+        Instruction {
+            program_id: zpx_router::ID,
+            accounts: zpx_router::accounts::ForwardViaSpoke {
+                user: self.user.pubkey(),
+                relayer: r,
+                mint,
+                escrow,
+                escrow_vault,
+                hub_protocol_vault: pda(&[b"hub_protocol_vault", mint.as_ref()]),
+                hub_relayer_vault: pda(&[b"hub_relayer_vault", mint.as_ref()]),
+                relayer_token_account: self.relayer_ata,
+                adapter_target_token_account: self.adapter_target,
+                registry: pda(&[b"hub_registry"]),
+                config: pda(&[b"zpx_config"]),
+                message_account: Pubkey::new_unique(),
+                adapter_program_data: Pubkey::new_unique(),
+                vault_registry: pda(&[b"vault_registry", mint.as_ref()]),
+                spoke_relayers: pda(&[b"spoke_relayers", &SPOKE_ID.to_le_bytes()]),
+                router_stats: pda(&[b"router_stats"]),
+                mint_pause: pda(&[b"mint_pause", mint.as_ref()]),
+                mint_allowlist: pda(&[b"mint_allowlist"]),
+                rent_ledger: pda(&[b"rent_ledger"]),
+                fee_policy: pda(&[
+                    b"fee_policy",
+                    mint.as_ref(),
+                    &zpx_chains::BASE.0.to_le_bytes(),
+                ]),
+                relayer_cosign: pda(&[b"relayer_cosign", mint.as_ref()]),
+                forward_state: pda(&[b"forward_state", escrow.as_ref()]),
+                relayer_vesting_config: pda(&[b"relayer_vesting_config"]),
+                relayer_vesting: pda(&[b"relayer_vesting", r.as_ref(), mint.as_ref()]),
+                token_program: spl_token::ID,
+                system_program: solana_sdk::system_program::ID,
+                relayer_activity: pda(&[b"relayer_activity", r.as_ref()]),
+            }
+            .to_account_metas(None),
+            data: zpx_router::instruction::ForwardViaSpoke {
+                spoke_id: SPOKE_ID,
+                amount: AMOUNT,
+                dst_domain: zpx_chains::CCTP_BASE.0,
+                mint_recipient,
+                is_protocol_fee: true,
+                is_relayer_fee: true,
+                _nonce: nonce,
+                relayer_fee_lamports: 0,
+                adapter_payload: vec![],
+            }
+            .data(),
+        }
+    }
+
+    fn fast(&self, nonce: u64) -> Instruction {
+        let (escrow, escrow_vault) = self.escrow(nonce);
+        let mint = self.mint;
+        Instruction {
+            program_id: zpx_router::ID,
+            accounts: zpx_router::accounts::ForwardUsdcFast {
+                relayer: self.relayer.pubkey(),
+                user: self.user.pubkey(),
+                escrow,
+                escrow_vault,
+                fast_path: pda(&[USDC_FAST_PATH_SEED, mint.as_ref()]),
+                config: pda(&[b"zpx_config"]),
+                registry: pda(&[b"hub_registry"]),
+                mint_pause: pda(&[b"mint_pause", mint.as_ref()]),
+                spoke_relayers: pda(&[b"spoke_relayers", &SPOKE_ID.to_le_bytes()]),
+                hub_protocol_vault: pda(&[b"hub_protocol_vault", mint.as_ref()]),
+                relayer_token_account: self.relayer_ata,
+                adapter_target_token_account: self.adapter_target,
+                router_stats: pda(&[b"router_stats"]),
+                rent_ledger: pda(&[b"rent_ledger"]),
+                fee_policy: pda(&[
+                    b"fee_policy",
+                    mint.as_ref(),
+                    &zpx_chains::BASE.0.to_le_bytes(),
+                ]),
+                relayer_cosign: pda(&[b"relayer_cosign", mint.as_ref()]),
+                token_program: spl_token::ID,
+            }
+            .to_account_metas(None),
+            data: zpx_router::instruction::ForwardUsdcFast {
+                is_protocol_fee: true,
+                is_relayer_fee: true,
+            }
+            .data(),
+        }
+    }
+}
+
+async fn run() {
+    let b = Bench {
+        admin: Keypair::new(),
+        relayer: Keypair::new(),
+        user: Keypair::new(),
+        mint: Pubkey::new_unique(),
+        from: Pubkey::new_unique(),
+        adapter_target: Pubkey::new_unique(),
+        relayer_ata: Pubkey::new_unique(),
+    };
+    let custody = Pubkey::new_unique();
+    let mut program_test =
+        ProgramTest::new("zpx_router", zpx_router::ID, processor!(zpx_router::entry));
+    let (config, config_bump) = Pubkey::find_program_address(&[b"zpx_config"], &zpx_router::ID);
+    let (registry, registry_bump) =
+        Pubkey::find_program_address(&[b"hub_registry"], &zpx_router::ID);
+    program_test.add_account(
+        config,
+        anchor_account(&Config {
+            admin: b.admin.pubkey(),
+            fee_recipient: Pubkey::new_unique(),
+            src_chain_id: 1,
+            relayer_fee_bps: 100,
+            protocol_fee_bps: 5,
+            relayer_pubkey: b.relayer.pubkey(),
+            accept_any_token: false,
+            allowed_token_mint: b.mint,
+            direct_relayer_payout_default: true,
+            min_forward_amount: 0,
+            adapters_len: 0,
+            adapters: [Pubkey::default(); 8],
+            paused: false,
+            bump: config_bump,
+            max_relayer_fee_lamports: 0,
+            usd_limits_enabled: false,
+            strict_vaults: false,
+            lp_fee_bps: 0,
+            lp_vault_program: Pubkey::default(),
+            killed: false,
+            kill_release_slot: 0,
+            guardian: Pubkey::default(),
+            admin_op_keys: [0; 8],
+            admin_op_cursor: 0,
+            legacy_message_hash: false,
+            features: zpx_router::FEATURES_DEFAULT,
+            bridge_hook: Pubkey::default(),
+            bridge_hook_best_effort: false,
+            namespace_len: 0,
+            namespace: [0; zpx_router::SANDBOX_NAMESPACE_LEN],
+            schema_version: zpx_router::CONFIG_SCHEMA_VERSION,
+            rent_refund_policy: zpx_router::RENT_REFUND_PAYER,
+            rent_refund_caller_bps: 0,
+            config_version: 0,
+            paused_families: 0,
+        }),
+    );
+    // A full registry with the USDC spoke last: the generic path's worst-case scan
+    let mut spokes = [SpokeEntry::default(); MAX_SPOKES];
+    for (i, spoke) in spokes.iter_mut().enumerate() {
+        *spoke = SpokeEntry {
+            spoke_id: 100 + i as u32,
+            adapter_program: Pubkey::new_unique(),
+            enabled: true,
+            ..SpokeEntry::default()
+        };
+    }
+    spokes[MAX_SPOKES - 1].spoke_id = SPOKE_ID;
+    spokes[MAX_SPOKES - 1].target_owner = custody;
+    program_test.add_account(
+        registry,
+        anchor_account(&Registry {
+            spokes_len: MAX_SPOKES as u8,
+            spokes,
+            bump: registry_bump,
+            schema_version: zpx_router::REGISTRY_SCHEMA_VERSION,
+        }),
+    );
+    program_test.add_account(
+        b.mint,
+        packed_account(spl_token::state::Mint {
+            mint_authority: COption::None,
+            supply: 2 * ITERS * AMOUNT,
+            decimals: 6,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        }),
+    );
+    program_test.add_account(
+        b.from,
+        token_account(b.mint, b.user.pubkey(), 2 * ITERS * AMOUNT),
+    );
+    program_test.add_account(b.adapter_target, token_account(b.mint, custody, 0));
+    program_test.add_account(b.relayer_ata, token_account(b.mint, b.relayer.pubkey(), 0));
+    for seed in [&b"hub_protocol_vault"[..], b"hub_relayer_vault"] {
+        let vault = pda(&[seed, b.mint.as_ref()]);
+        program_test.add_account(vault, token_account(b.mint, vault, 0));
+    }
+    for wallet in [&b.admin, &b.relayer, &b.user] {
+        program_test.add_account(
+            wallet.pubkey(),
+            Account::new(100_000_000_000, 0, &solana_sdk::system_program::ID),
+        );
+    }
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let tx = |ix: Instruction, signer: &Keypair| {
+        Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&payer.pubkey()),
+            &[&payer, signer],
+            recent_blockhash,
+        )
+    };
+    let configure = Instruction {
+        program_id: zpx_router::ID,
+        accounts: zpx_router::accounts::ConfigureUsdcFastPath {
+            authority: b.admin.pubkey(),
+            config,
+            registry,
+            mint: b.mint,
+            hub_protocol_vault: pda(&[b"hub_protocol_vault", b.mint.as_ref()]),
+            adapter_target_token_account: b.adapter_target,
+            fast_path: pda(&[USDC_FAST_PATH_SEED, b.mint.as_ref()]),
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zpx_router::instruction::ConfigureUsdcFastPath { spoke_id: SPOKE_ID }.data(),
+    };
+    banks_client
+        .process_transaction(tx(configure, &b.admin))
+        .await
+        .unwrap();
+    for nonce in 0..2 * ITERS {
+        banks_client
+            .process_transaction(tx(b.deposit(nonce), &b.user))
+            .await
+            .unwrap();
+    }
+
+    let paths: [(&str, BuildIx, u64); 2] =
+        [("generic", Bench::generic, 0), ("fast", Bench::fast, ITERS)];
+    for (name, build, first_nonce) in paths {
+        let sample = tx(build(&b, first_nonce), &b.relayer);
+        let accounts = build(&b, first_nonce).accounts.len();
+        // shortvec signature count + signatures + message
+        let bytes = 1 + 64 * sample.signatures.len() + sample.message.serialize().len();
+        let mut elapsed = Duration::ZERO;
+        for nonce in first_nonce..first_nonce + ITERS {
+            let t = tx(build(&b, nonce), &b.relayer);
+            let start = Instant::now();
+            banks_client.process_transaction(t).await.unwrap();
+            elapsed += start.elapsed();
+        }
+        let per = elapsed / ITERS as u32;
+        println!("{name:>8}: {accounts} accounts, {bytes} tx bytes, {per:?}/forward");
+    }
+}
+
+fn main() {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(run());
+}
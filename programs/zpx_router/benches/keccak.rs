@@ -0,0 +1,39 @@
+//! Off-chain keccak backends on a message-hash-sized preimage (8+32*5+8+8 bytes).
+//!
+//! Run with `cargo bench -p zpx_router --bench keccak`. Wall time here only compares the
+//! host implementations; on-chain the Syscall backend is billed by the runtime's keccak
+//! cost model (printed below) instead of per SBF instruction of a software permutation.
+
+use std::hint::black_box;
+use std::time::Instant;
+use zpx_router::hash::{HashBackend, PureRust, Syscall};
+
+const ITERS: u32 = 200_000;
+// ComputeBudget::sha256_base_cost / sha256_byte_cost, shared by sol_keccak256
+const SYSCALL_BASE_CU: u64 = 85;
+const SYSCALL_BYTE_CU: u64 = 1;
+
+fn bench<B: HashBackend>(name: &str, parts: &[&[u8]]) -> [u8; 32] {
+    let start = Instant::now();
+    let mut out = [0u8; 32];
+    for _ in 0..ITERS {
+        out = B::keccak256(black_box(parts));
+    }
+    let per = start.elapsed() / ITERS;
+    println!("{name:>10}: {per:?}/hash");
+    out
+}
+
+fn main() {
+    let word = [0x5au8; 32];
+    let chain = 1u64.to_be_bytes();
+    let parts: [&[u8]; 8] = [&chain, &word, &word, &word, &word, &word, &chain, &chain];
+    let len: usize = parts.iter().map(|p| p.len()).sum();
+
+    let a = bench::<Syscall>("syscall", &parts);
+    let b = bench::<PureRust>("tiny-keccak", &parts);
+    assert_eq!(a, b, "backends disagree");
+
+    let cu = SYSCALL_BASE_CU + SYSCALL_BYTE_CU * (len as u64 / 2);
+    println!("on-chain syscall cost for {len} bytes: {cu} CU");
+}
@@ -0,0 +1,66 @@
+//! Moves parity vectors between this repo and a local checkout of the EVM repo (no network):
+//!
+//! ```sh
+//! cargo run -p zpx_router --features evm-vectors --bin evm-vectors -- refresh <hashes.evm.json>
+//! cargo run -p zpx_router --features evm-vectors --bin evm-vectors -- export [<hashes.svm.json>]
+//! cargo run -p zpx_router --features evm-vectors --bin evm-vectors -- check [<hashes.evm.json>]
+//! ```
+//!
+//! `refresh` copies the EVM generator's output into tests/fixtures, `export` writes this
+//! crate's outputs for the EVM repo's parity test, and `check` lists mismatches. `refresh` and
+//! `check` exit non-zero when any vector disagrees.
+
+mod parity;
+
+use std::path::PathBuf;
+
+fn fail(e: String) -> ! {
+    eprintln!("evm-vectors: {e}");
+    std::process::exit(1);
+}
+
+fn report(doc: &serde_json::Value) {
+    let found = parity::mismatches(doc).unwrap_or_else(|e| fail(e));
+    for mismatch in &found {
+        eprintln!("{mismatch}");
+    }
+    if !found.is_empty() {
+        fail(format!("{} vector(s) disagree", found.len()));
+    }
+}
+
+fn write(path: &PathBuf, doc: &serde_json::Value) {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).unwrap();
+    }
+    std::fs::write(path, serde_json::to_string_pretty(doc).unwrap() + "\n")
+        .unwrap_or_else(|e| fail(format!("{}: {e}", path.display())));
+    println!("{}", path.display());
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let path = args.get(1).map(PathBuf::from);
+    match args.first().map(String::as_str) {
+        Some("refresh") => {
+            let from = path.unwrap_or_else(|| fail("refresh needs the EVM vector file".into()));
+            let doc = parity::load(&from).unwrap_or_else(|e| fail(e));
+            write(&parity::fixture_path(), &doc);
+            report(&doc);
+        }
+        Some("export") => {
+            let doc = parity::load(&parity::fixture_path()).unwrap_or_else(|e| fail(e));
+            let ours = parity::recompute(&doc).unwrap_or_else(|e| fail(e));
+            let out = path.unwrap_or_else(|| {
+                std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+                    .join("../../target/vectors/hashes.svm.json")
+            });
+            write(&out, &ours);
+        }
+        Some("check") => {
+            let from = path.unwrap_or_else(parity::fixture_path);
+            report(&parity::load(&from).unwrap_or_else(|e| fail(e)));
+        }
+        _ => fail("usage: evm-vectors refresh <file> | export [<file>] | check [<file>]".into()),
+    }
+}
@@ -0,0 +1,227 @@
+//! Parity of the router's hash module and payload/recipient codecs with the EVM router, over
+//! the vector file the EVM repo's generator writes (`hashes.evm.json`). Each vector carries its
+//! inputs and the EVM side's outputs; `recompute` replaces every output with what this crate
+//! derives from the same inputs, and `mismatches` lists where the two differ.
+
+use serde_json::{Map, Value};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use zpx_router::hash::{global_route_id, message_hash_be, HashScheme};
+use zpx_router::zpx_core::payload::payload_hash;
+use zpx_router::zpx_core::recipient::Recipient;
+
+/// Layout version of the vector file; both generators must agree on it.
+pub const VECTORS_VERSION: u64 = 1;
+
+pub fn fixture_path() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/hashes.evm.json")
+}
+
+pub fn load(path: &Path) -> Result<Value, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    serde_json::from_str(&text).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+/// One output the EVM side and this crate disagree on.
+#[derive(Debug)]
+pub struct Mismatch {
+    pub vector: String,
+    pub field: &'static str,
+    pub evm: String,
+    pub solana: String,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}: evm {} != solana {}",
+            self.vector, self.field, self.evm, self.solana
+        )
+    }
+}
+
+/// Outputs per section; everything else in a vector is an input.
+const OUTPUTS: &[(&str, &[&str])] = &[
+    ("domains", &["separator"]),
+    ("messages", &["messageHash", "routeId"]),
+    ("payloads", &["payloadHash"]),
+    ("recipients", &["encoded"]),
+];
+
+/// `doc` with every output recomputed by this crate: the file the EVM repo's parity test
+/// reads back.
+pub fn recompute(doc: &Value) -> Result<Value, String> {
+    let version = doc["version"].as_u64().ok_or("missing version")?;
+    if version != VECTORS_VERSION {
+        return Err(format!(
+            "vector file version {version}, expected {VECTORS_VERSION}"
+        ));
+    }
+    let mut out = doc.clone();
+    out["hub"] = hex(&zpx_router::ID.to_bytes()).into();
+
+    let hub = zpx_router::ID.to_bytes();
+    let mut schemes = vec![("legacy".to_string(), HashScheme::Legacy)];
+    for domain in section(&mut out, "domains")? {
+        let env = u8::try_from(uint(domain, "env")?).map_err(|e| e.to_string())?;
+        let version = u8::try_from(uint(domain, "version")?).map_err(|e| e.to_string())?;
+        let scheme = HashScheme::domain(hub, env, version);
+        if let HashScheme::Domain(separator) = scheme {
+            domain.insert("separator".into(), hex(&separator).into());
+        }
+        schemes.push((string(domain, "name")?, scheme));
+    }
+
+    for message in section(&mut out, "messages")? {
+        let name = string(message, "scheme")?;
+        let scheme = schemes
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, s)| *s)
+            .ok_or_else(|| format!("unknown scheme {name}"))?;
+        let src_chain = uint(message, "srcChainId")?;
+        let dst_chain = uint(message, "dstChainId")?;
+        let nonce = uint(message, "nonce")?;
+        let mut amount = [0u8; 32];
+        amount[16..].copy_from_slice(&uint128(message, "amount")?.to_be_bytes());
+        let msg_hash = message_hash_be(
+            scheme,
+            src_chain,
+            word(message, "srcAdapter")?,
+            word(message, "recipient")?,
+            word(message, "asset")?,
+            amount,
+            word(message, "payloadHash")?,
+            nonce,
+            dst_chain,
+        );
+        let route_id = global_route_id(
+            scheme,
+            src_chain,
+            dst_chain,
+            word(message, "routeInitiator")?,
+            msg_hash,
+            nonce,
+        );
+        message.insert("messageHash".into(), hex(&msg_hash).into());
+        message.insert("routeId".into(), hex(&route_id).into());
+    }
+
+    for payload in section(&mut out, "payloads")? {
+        let bytes = bytes(payload, "payload")?;
+        let validity = match (payload.get("sentAt"), payload.get("expiresAt")) {
+            (None, None) => None,
+            _ => Some((uint(payload, "sentAt")?, uint(payload, "expiresAt")?)),
+        };
+        payload.insert(
+            "payloadHash".into(),
+            hex(&payload_hash(&bytes, validity)).into(),
+        );
+    }
+
+    for recipient in section(&mut out, "recipients")? {
+        let tag = u8::try_from(uint(recipient, "tag")?).map_err(|e| e.to_string())?;
+        let mut tagged = vec![tag];
+        tagged.extend_from_slice(&word(recipient, "word")?);
+        let encoded = match Recipient::decode(&tagged) {
+            Ok(decoded) => hex(&decoded.encode()),
+            Err(e) => format!("{e:?}"),
+        };
+        recipient.insert("encoded".into(), encoded.into());
+    }
+    Ok(out)
+}
+
+/// Every output (and the hub address) where `doc` disagrees with `recompute(doc)`.
+pub fn mismatches(doc: &Value) -> Result<Vec<Mismatch>, String> {
+    let ours = recompute(doc)?;
+    let mut found = Vec::new();
+    if ours["hub"] != doc["hub"] {
+        found.push(Mismatch {
+            vector: "file".into(),
+            field: "hub",
+            evm: doc["hub"].to_string(),
+            solana: ours["hub"].to_string(),
+        });
+    }
+    for (name, fields) in OUTPUTS {
+        let theirs = doc[*name].as_array().ok_or(format!("missing {name}"))?;
+        for (evm, solana) in theirs.iter().zip(ours[*name].as_array().unwrap()) {
+            for field in *fields {
+                if evm[*field] != solana[*field] {
+                    found.push(Mismatch {
+                        vector: format!("{name}/{}", evm["name"].as_str().unwrap_or("?")),
+                        field,
+                        evm: evm[*field].to_string(),
+                        solana: solana[*field].to_string(),
+                    });
+                }
+            }
+        }
+    }
+    Ok(found)
+}
+
+fn section<'a>(doc: &'a mut Value, name: &str) -> Result<Vec<&'a mut Map<String, Value>>, String> {
+    doc[name]
+        .as_array_mut()
+        .ok_or(format!("missing {name}"))?
+        .iter_mut()
+        .map(|v| v.as_object_mut().ok_or(format!("{name}: expected objects")))
+        .collect()
+}
+
+fn field<'a>(vector: &'a Map<String, Value>, key: &str) -> Result<&'a Value, String> {
+    vector.get(key).ok_or_else(|| {
+        let name = vector.get("name").and_then(Value::as_str).unwrap_or("?");
+        format!("{name}: missing {key}")
+    })
+}
+
+fn string(vector: &Map<String, Value>, key: &str) -> Result<String, String> {
+    field(vector, key)?
+        .as_str()
+        .map(str::to_string)
+        .ok_or(format!("{key}: expected a string"))
+}
+
+/// Integers travel as decimal strings (uint64 and uint256 overflow JS numbers); small ones may
+/// also be plain JSON numbers.
+fn uint128(vector: &Map<String, Value>, key: &str) -> Result<u128, String> {
+    match field(vector, key)? {
+        Value::Number(n) => n.as_u64().map(u128::from),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+    .ok_or(format!("{key}: expected an unsigned integer"))
+}
+
+fn uint(vector: &Map<String, Value>, key: &str) -> Result<u64, String> {
+    u64::try_from(uint128(vector, key)?).map_err(|_| format!("{key}: exceeds u64"))
+}
+
+fn bytes(vector: &Map<String, Value>, key: &str) -> Result<Vec<u8>, String> {
+    let s = string(vector, key)?;
+    let digits = s
+        .strip_prefix("0x")
+        .ok_or(format!("{key}: expected 0x hex"))?;
+    if digits.len() % 2 != 0 {
+        return Err(format!("{key}: odd hex length"));
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).map_err(|e| format!("{key}: {e}")))
+        .collect()
+}
+
+fn word(vector: &Map<String, Value>, key: &str) -> Result<[u8; 32], String> {
+    bytes(vector, key)?
+        .try_into()
+        .map_err(|_| format!("{key}: expected 32 bytes"))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    let digits: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+    format!("0x{digits}")
+}
@@ -0,0 +1,217 @@
+//! IDL post-processing for TS clients. anchor-syn 0.26 cannot size `[T; CONST]` arrays, so
+//! array-length constants are substituted with their values before parsing. The parsed IDL
+//! then gets doc strings on events and event fields (anchor-syn drops them), an
+//! `"encoding": "hex"` hint on every `[u8; 32]` field, and a `metadata` block carrying the
+//! program address and IDL_SCHEMA_VERSION.
+
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Bumped whenever the post-processing changes the shape of the emitted JSON.
+pub const IDL_SCHEMA_VERSION: u32 = 1;
+
+/// Array lengths re-exported from other crates, so they are not declared in lib.rs.
+const EXTERNAL_LENGTHS: &[(&str, usize)] = &[
+    (
+        "MAX_FEE_SPLIT_RECIPIENTS",
+        zpx_router::MAX_FEE_SPLIT_RECIPIENTS,
+    ),
+    ("MAX_FEE_TIERS", zpx_router::MAX_FEE_TIERS),
+    ("PAYLOAD_MAGIC_MAX_LEN", zpx_router::PAYLOAD_MAGIC_MAX_LEN),
+];
+
+pub fn lib_path() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("src/lib.rs")
+}
+
+/// Build the post-processed IDL of the program whose source is `lib`.
+pub fn build(lib: &Path) -> Result<Value, String> {
+    let source = std::fs::read_to_string(lib).map_err(|e| format!("{}: {e}", lib.display()))?;
+    let file = syn::parse_file(&source).map_err(|e| format!("{}: {e}", lib.display()))?;
+    let sized = substitute_lengths(&source, &usize_consts(&file.items));
+
+    let tmp = std::env::temp_dir().join(format!("zpx_router_idl_{}.rs", std::process::id()));
+    std::fs::write(&tmp, sized).map_err(|e| e.to_string())?;
+    // anchor-syn panics on array lengths it cannot read
+    let parsed = std::panic::catch_unwind(|| {
+        anchor_syn::idl::file::parse(
+            &tmp,
+            env!("CARGO_PKG_VERSION").to_string(),
+            false,
+            false,
+            false,
+        )
+    });
+    let _ = std::fs::remove_file(&tmp);
+    let idl = parsed
+        .map_err(|_| "anchor-syn failed; is an array length missing from EXTERNAL_LENGTHS?")?
+        .map_err(|e| e.to_string())?
+        .ok_or("no #[program] module found")?;
+
+    let mut idl = serde_json::to_value(idl).map_err(|e| e.to_string())?;
+    attach_event_docs(&mut idl, &event_docs(&file.items));
+    mark_hex_fields(&mut idl);
+    idl["metadata"] = json!({
+        "address": zpx_router::ID.to_string(),
+        "schemaVersion": IDL_SCHEMA_VERSION,
+    });
+    Ok(idl)
+}
+
+/// `const NAME: usize = <literal>;` items anywhere in the file.
+fn usize_consts(items: &[syn::Item]) -> HashMap<String, usize> {
+    let mut consts: HashMap<String, usize> = EXTERNAL_LENGTHS
+        .iter()
+        .map(|(name, len)| (name.to_string(), *len))
+        .collect();
+    for item in items {
+        match item {
+            syn::Item::Const(c) => {
+                let is_usize = matches!(&*c.ty, syn::Type::Path(p) if p.path.is_ident("usize"));
+                if let (
+                    true,
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Int(n),
+                        ..
+                    }),
+                ) = (is_usize, &*c.expr)
+                {
+                    if let Ok(value) = n.base10_parse() {
+                        consts.insert(c.ident.to_string(), value);
+                    }
+                }
+            }
+            syn::Item::Mod(m) => {
+                if let Some((_, nested)) = &m.content {
+                    consts.extend(usize_consts(nested));
+                }
+            }
+            _ => {}
+        }
+    }
+    consts
+}
+
+/// Replace every `; NAME]` array length whose value is known. Unknown names (e.g. computed
+/// sizes in handler code) are left alone; if one sits in an IDL type, parsing fails.
+fn substitute_lengths(source: &str, consts: &HashMap<String, usize>) -> String {
+    let mut out = String::with_capacity(source.len());
+    let mut rest = source;
+    while let Some(pos) = rest.find("; ") {
+        let (head, tail) = rest.split_at(pos + 2);
+        out.push_str(head);
+        let name_len = tail
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(tail.len());
+        match consts.get(&tail[..name_len]) {
+            Some(value) if tail[name_len..].starts_with(']') => {
+                out.push_str(&value.to_string());
+                rest = &tail[name_len..];
+            }
+            _ => rest = tail,
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// anchor-syn's rendering of a Rust field name (`route_id` -> `routeId`).
+pub fn camel(name: &str) -> String {
+    let mut parts = name.split('_');
+    let mut out = parts.next().unwrap_or_default().to_string();
+    for part in parts {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            out.extend(first.to_uppercase());
+            out.push_str(chars.as_str());
+        }
+    }
+    out
+}
+
+fn docs(attrs: &[syn::Attribute]) -> Vec<String> {
+    attrs
+        .iter()
+        .filter(|a| a.path.is_ident("doc"))
+        .filter_map(|a| match a.parse_meta() {
+            Ok(syn::Meta::NameValue(syn::MetaNameValue {
+                lit: syn::Lit::Str(s),
+                ..
+            })) => Some(s.value().trim().to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+struct EventDocs {
+    docs: Vec<String>,
+    fields: HashMap<String, Vec<String>>,
+}
+
+fn event_docs(items: &[syn::Item]) -> HashMap<String, EventDocs> {
+    let mut out = HashMap::new();
+    for item in items {
+        match item {
+            syn::Item::Struct(s) if s.attrs.iter().any(|a| a.path.is_ident("event")) => {
+                let fields = s
+                    .fields
+                    .iter()
+                    .filter_map(|f| Some((camel(&f.ident.as_ref()?.to_string()), docs(&f.attrs))))
+                    .collect();
+                out.insert(
+                    s.ident.to_string(),
+                    EventDocs {
+                        docs: docs(&s.attrs),
+                        fields,
+                    },
+                );
+            }
+            syn::Item::Mod(m) => {
+                if let Some((_, nested)) = &m.content {
+                    out.extend(event_docs(nested));
+                }
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+fn attach_event_docs(idl: &mut Value, source: &HashMap<String, EventDocs>) {
+    let Some(events) = idl["events"].as_array_mut() else {
+        return;
+    };
+    for event in events {
+        let Some(found) = event["name"].as_str().and_then(|n| source.get(n)) else {
+            continue;
+        };
+        if !found.docs.is_empty() {
+            event["docs"] = json!(found.docs);
+        }
+        for field in event["fields"].as_array_mut().into_iter().flatten() {
+            let field_docs = field["name"].as_str().and_then(|n| found.fields.get(n));
+            if let Some(d) = field_docs.filter(|d| !d.is_empty()) {
+                field["docs"] = json!(d);
+            }
+        }
+    }
+}
+
+fn is_bytes32(ty: &Value) -> bool {
+    *ty == json!({ "array": ["u8", 32] })
+}
+
+/// Tag every `[u8; 32]` field / argument (events, accounts, types, instruction args).
+fn mark_hex_fields(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            if map.contains_key("name") && map.get("type").is_some_and(is_bytes32) {
+                map.insert("encoding".to_string(), json!("hex"));
+            }
+            map.values_mut().for_each(mark_hex_fields);
+        }
+        Value::Array(items) => items.iter_mut().for_each(mark_hex_fields),
+        _ => {}
+    }
+}
@@ -0,0 +1,23 @@
+//! Writes the TS-client IDL (see idl.rs) to `target/idl/zpx_router.json` or the path given:
+//! `cargo run -p zpx_router --features idl-extra --bin idl-extra [-- <out.json>]`
+
+mod idl;
+
+fn main() {
+    let out = std::env::args()
+        .nth(1)
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(|| {
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("../../target/idl/zpx_router.json")
+        });
+    let idl = idl::build(&idl::lib_path()).unwrap_or_else(|e| {
+        eprintln!("idl-extra: {e}");
+        std::process::exit(1);
+    });
+    if let Some(dir) = out.parent() {
+        std::fs::create_dir_all(dir).unwrap();
+    }
+    std::fs::write(&out, serde_json::to_string_pretty(&idl).unwrap() + "\n").unwrap();
+    println!("{}", out.display());
+}
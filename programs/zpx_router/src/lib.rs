@@ -9,49 +9,544 @@
 #![allow(clippy::result_large_err)]
 #![allow(clippy::field_reassign_with_default)]
 use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
 use anchor_spl::token::{self as token, Mint, Token, TokenAccount};
 
-// Minimal internal hash helpers (stubbed for tests). In later phases replace with
-// a proper keccak implementation matching the production spec.
-mod hash {
-    pub fn global_route_id(
-        _src_chain: u64,
-        _dst_chain: u64,
-        _initiator: [u8; 32],
-        _msg_hash: [u8; 32],
-        _nonce: u64,
-    ) -> [u8; 32] {
-        [0u8; 32]
+// Canonical hashes shared with the EVM side and the off-chain vector generator, defined in
+// zpx-core so browsers and tooling hash exactly like the program.
+pub mod hash {
+    pub use zpx_core::hash::*;
+}
+/// EIP-712 hashing and signer recovery for bridge intents signed by the EVM order-flow
+/// system. Solana has no verifyingContract address, so the domain binds the hub program id
+/// as its `salt` instead.
+pub mod intent {
+    use super::hash::keccak256;
+    use super::BridgeIntent;
+    use anchor_lang::solana_program::secp256k1_recover::secp256k1_recover;
+
+    pub const EIP712_DOMAIN_TYPE: &[u8] =
+        b"EIP712Domain(string name,string version,uint256 chainId,bytes32 salt)";
+    pub const BRIDGE_INTENT_TYPE: &[u8] = b"BridgeIntent(bytes32 user,bytes32 mint,uint64 escrowNonce,uint32 spokeId,uint64 amount,uint32 dstDomain,bytes32 mintRecipient,uint64 relayerFeeLamports,bytes32 payloadHash,uint64 deadline)";
+    pub const DOMAIN_NAME: &[u8] = b"ZoopX Router";
+    pub const DOMAIN_VERSION: &[u8] = b"1";
+    /// Half the secp256k1 group order; signatures with a larger `s` are malleable
+    const HALF_ORDER: [u8; 32] = [
+        0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0x5d, 0x57, 0x6e, 0x73, 0x57, 0xa4, 0x50, 0x1d, 0xdf, 0xe9, 0x2f, 0x46, 0x68, 0x1b,
+        0x20, 0xa0,
+    ];
+
+    /// ABI encoding of an unsigned integer: one big-endian, left-padded word
+    fn word(v: u64) -> [u8; 32] {
+        let mut w = [0u8; 32];
+        w[24..].copy_from_slice(&v.to_be_bytes());
+        w
     }
 
-    pub fn keccak256(_parts: &[&[u8]]) -> [u8; 32] {
-        [0u8; 32]
+    /// keccak256(typeHash(EIP712Domain) | keccak(name) | keccak(version) | chainId | salt)
+    pub fn domain_separator(chain_id: u64, hub: [u8; 32]) -> [u8; 32] {
+        keccak256(&[
+            &keccak256(&[EIP712_DOMAIN_TYPE]),
+            &keccak256(&[DOMAIN_NAME]),
+            &keccak256(&[DOMAIN_VERSION]),
+            &word(chain_id),
+            &hub,
+        ])
     }
 
-    pub fn message_hash_be(
-        _src_chain: u64,
-        _src_adapter: [u8; 32],
-        _recipient: [u8; 32],
-        _asset: [u8; 32],
-        _amount_be: [u8; 32],
-        _payload_hash: [u8; 32],
-        _nonce: u64,
-        _dst_chain: u64,
-    ) -> [u8; 32] {
-        [0u8; 32]
+    /// hashStruct(BridgeIntent): the type hash followed by one word per field
+    pub fn struct_hash(intent: &BridgeIntent) -> [u8; 32] {
+        keccak256(&[
+            &keccak256(&[BRIDGE_INTENT_TYPE]),
+            intent.user.as_ref(),
+            intent.mint.as_ref(),
+            &word(intent.escrow_nonce),
+            &word(intent.spoke_id as u64),
+            &word(intent.amount),
+            &word(intent.dst_domain as u64),
+            &intent.mint_recipient,
+            &word(intent.relayer_fee_lamports),
+            &intent.payload_hash,
+            &word(intent.deadline),
+        ])
+    }
+
+    /// keccak256(0x19 0x01 | domain separator | hashStruct(intent)), the digest EVM wallets sign
+    pub fn digest(separator: [u8; 32], intent: &BridgeIntent) -> [u8; 32] {
+        keccak256(&[b"\x19\x01", &separator, &struct_hash(intent)])
+    }
+
+    /// EVM address that produced `signature` (r | s | v, v in {0, 1, 27, 28}) over `digest`,
+    /// through the secp256k1_recover syscall. None for malformed or high-s signatures.
+    pub fn recover_signer(digest: &[u8; 32], signature: &[u8; 65]) -> Option<[u8; 20]> {
+        let recovery_id = match signature[64] {
+            0 | 27 => 0,
+            1 | 28 => 1,
+            _ => return None,
+        };
+        if signature[32..64] > HALF_ORDER[..] {
+            return None;
+        }
+        let pubkey = secp256k1_recover(digest, recovery_id, &signature[..64]).ok()?;
+        let hashed = keccak256(&[&pubkey.to_bytes()]);
+        let mut address = [0u8; 20];
+        address.copy_from_slice(&hashed[12..]);
+        Some(address)
+    }
+}
+/// Wormhole Queries (CCQ) responses: guardian-signed results of an eth_call against a remote
+/// EVM chain. Only the shape the router submits is decoded: one off-chain request holding a
+/// single eth_call, answered by a single 32-byte result.
+pub mod ccq {
+    use super::hash::keccak256;
+    use super::intent::recover_signer;
+    use super::GuardianSignature;
+    use anchor_lang::prelude::*;
+
+    /// Wormhole core bridge; owns the GuardianSet accounts at [b"GuardianSet", index (BE)]
+    pub const WORMHOLE_CORE_PROGRAM_ID: Pubkey =
+        anchor_lang::solana_program::pubkey!("worm2ZoG2kUd4vFXhvjh93UUH596ayRfgQ2MgjNMTth");
+    /// Guardians sign keccak256(RESPONSE_PREFIX | keccak256(response))
+    pub const RESPONSE_PREFIX: &[u8] = b"query_response_0000000000000000000|";
+    pub const QUERY_VERSION: u8 = 1;
+    pub const QUERY_TYPE_ETH_CALL: u8 = 1;
+    /// The EVM router's getter for the root of its fee configuration
+    pub const CONFIG_ROOT_SIGNATURE: &[u8] = b"configRoot()";
+    /// Off-chain requests are identified by the requester's signature
+    const REQUEST_SIGNATURE_LEN: usize = 65;
+
+    /// Wormhole core GuardianSet account (Borsh, no discriminator)
+    #[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+    pub struct GuardianSet {
+        pub index: u32,
+        pub keys: Vec<[u8; 20]>,
+        pub creation_time: u32,
+        pub expiration_time: u32,
+    }
+
+    impl GuardianSet {
+        /// Expired sets stop verifying; 0 = the current set, which never expires
+        pub fn active(&self, now: u64) -> bool {
+            self.expiration_time == 0 || now < self.expiration_time as u64
+        }
+
+        /// Two thirds of the guardians plus one
+        pub fn quorum(&self) -> usize {
+            self.keys.len() * 2 / 3 + 1
+        }
+    }
+
+    /// An eth_call proven by a query response
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct EthCallResult {
+        pub chain_id: u16,
+        pub to: [u8; 20],
+        pub block_number: u64,
+        pub block_time_us: u64,
+        pub result: [u8; 32],
+    }
+
+    pub fn digest(response: &[u8]) -> [u8; 32] {
+        keccak256(&[RESPONSE_PREFIX, &keccak256(&[response])])
+    }
+
+    pub fn config_root_selector() -> [u8; 4] {
+        let hash = keccak256(&[CONFIG_ROOT_SIGNATURE]);
+        [hash[0], hash[1], hash[2], hash[3]]
+    }
+
+    /// Whether `signatures` carry a quorum of `set` over `response`. Guardian indices must be
+    /// strictly increasing, so no guardian counts twice.
+    pub fn verify_quorum(
+        response: &[u8],
+        signatures: &[GuardianSignature],
+        set: &GuardianSet,
+    ) -> bool {
+        let digest = digest(response);
+        let mut last = None;
+        for signature in signatures {
+            let index = signature.index;
+            if last.is_some_and(|last| index <= last) {
+                return false;
+            }
+            last = Some(index);
+            let Some(guardian) = set.keys.get(index as usize) else {
+                return false;
+            };
+            if recover_signer(&digest, &signature.signature) != Some(*guardian) {
+                return false;
+            }
+        }
+        signatures.len() >= set.quorum()
+    }
+
+    struct Reader<'a>(&'a [u8]);
+
+    impl<'a> Reader<'a> {
+        fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+            if self.0.len() < n {
+                return None;
+            }
+            let (head, tail) = self.0.split_at(n);
+            self.0 = tail;
+            Some(head)
+        }
+
+        fn u8(&mut self) -> Option<u8> {
+            self.take(1).map(|b| b[0])
+        }
+
+        fn u16(&mut self) -> Option<u16> {
+            self.take(2).map(|b| u16::from_be_bytes([b[0], b[1]]))
+        }
+
+        fn u32(&mut self) -> Option<u32> {
+            self.take(4)
+                .map(|b| u32::from_be_bytes(b.try_into().unwrap()))
+        }
+
+        fn u64(&mut self) -> Option<u64> {
+            self.take(8)
+                .map(|b| u64::from_be_bytes(b.try_into().unwrap()))
+        }
+
+        /// u32 length-prefixed bytes
+        fn bytes(&mut self) -> Option<&'a [u8]> {
+            let len = self.u32()? as usize;
+            self.take(len)
+        }
+
+        fn done(&self) -> bool {
+            self.0.is_empty()
+        }
+    }
+
+    /// Decode a response to one off-chain request for a single eth_call of `selector`. None
+    /// for any other shape or trailing bytes.
+    pub fn parse_eth_call(response: &[u8], selector: [u8; 4]) -> Option<EthCallResult> {
+        let mut r = Reader(response);
+        if r.u8()? != QUERY_VERSION || r.u16()? != 0 {
+            return None;
+        }
+        r.take(REQUEST_SIGNATURE_LEN)?;
+        let mut request = Reader(r.bytes()?);
+        if r.u8()? != 1 {
+            return None;
+        }
+        let chain_id = r.u16()?;
+        if r.u8()? != QUERY_TYPE_ETH_CALL {
+            return None;
+        }
+        let mut call_response = Reader(r.bytes()?);
+        if !r.done() {
+            return None;
+        }
+
+        // The request must ask the same chain for exactly one call of `selector`
+        if request.u8()? != QUERY_VERSION {
+            return None;
+        }
+        request.u32()?;
+        if request.u8()? != 1 || request.u16()? != chain_id || request.u8()? != QUERY_TYPE_ETH_CALL
+        {
+            return None;
+        }
+        let mut call = Reader(request.bytes()?);
+        if !request.done() {
+            return None;
+        }
+        call.bytes()?;
+        if call.u8()? != 1 {
+            return None;
+        }
+        let to: [u8; 20] = call.take(20)?.try_into().ok()?;
+        if call.bytes()? != selector || !call.done() {
+            return None;
+        }
+
+        let block_number = call_response.u64()?;
+        call_response.take(32)?;
+        let block_time_us = call_response.u64()?;
+        if call_response.u8()? != 1 {
+            return None;
+        }
+        let result: [u8; 32] = call_response.bytes()?.try_into().ok()?;
+        if !call_response.done() {
+            return None;
+        }
+        Some(EthCallResult {
+            chain_id,
+            to,
+            block_number,
+            block_time_us,
+            result,
+        })
     }
 }
+/// Time source for timelocks, deadlines and vesting. Reads the Clock sysvar; builds with
+/// `test-instructions` let program-tests pin slot and timestamp by passing the ClockOverride
+/// PDA (see set_clock_override) as a remaining account.
+pub mod time {
+    use super::*;
+
+    pub const CLOCK_OVERRIDE_SEED: &[u8] = b"clock_override";
+
+    pub fn now(remaining: &[AccountInfo], program_id: &Pubkey) -> Result<Clock> {
+        let mut clock = Clock::get()?;
+        if cfg!(feature = "test-instructions") {
+            let (expected, _) = Pubkey::find_program_address(&[CLOCK_OVERRIDE_SEED], program_id);
+            if let Some(ai) = remaining
+                .iter()
+                .find(|ai| ai.key() == expected && ai.owner == program_id)
+            {
+                let pinned = ClockOverride::try_deserialize(&mut &ai.try_borrow_data()?[..])?;
+                clock.slot = pinned.slot;
+                clock.unix_timestamp = pinned.unix_timestamp;
+            }
+        }
+        Ok(clock)
+    }
+}
+// Minimal Pyth v2 price account reader. Only the aggregate price block is decoded, which
+// keeps the Pyth SDK (and its solana-program pin) out of the program's dependency tree.
+mod oracle {
+    pub const PYTH_MAGIC: u32 = 0xa1b2c3d4;
+    pub const PYTH_VERSION_2: u32 = 2;
+    pub const PYTH_ATYPE_PRICE: u32 = 3;
+    pub const PYTH_STATUS_TRADING: u32 = 1;
+    // Offsets into the v2 price account
+    const EXPO_OFFSET: usize = 20;
+    const AGG_OFFSET: usize = 208;
+    const AGG_LEN: usize = 32;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct PythPrice {
+        pub price: i64,
+        pub conf: u64,
+        pub expo: i32,
+        pub status: u32,
+        pub pub_slot: u64,
+    }
+
+    fn u32_at(data: &[u8], off: usize) -> u32 {
+        u32::from_le_bytes(data[off..off + 4].try_into().unwrap())
+    }
+
+    fn u64_at(data: &[u8], off: usize) -> u64 {
+        u64::from_le_bytes(data[off..off + 8].try_into().unwrap())
+    }
+
+    pub fn parse_pyth_price(data: &[u8]) -> Option<PythPrice> {
+        if data.len() < AGG_OFFSET + AGG_LEN
+            || u32_at(data, 0) != PYTH_MAGIC
+            || u32_at(data, 4) != PYTH_VERSION_2
+            || u32_at(data, 8) != PYTH_ATYPE_PRICE
+        {
+            return None;
+        }
+        Some(PythPrice {
+            expo: u32_at(data, EXPO_OFFSET) as i32,
+            price: u64_at(data, AGG_OFFSET) as i64,
+            conf: u64_at(data, AGG_OFFSET + 8),
+            status: u32_at(data, AGG_OFFSET + 16),
+            pub_slot: u64_at(data, AGG_OFFSET + 24),
+        })
+    }
+}
+/// Client-side helpers for partner-sponsored ("gas-less") transactions: the sponsor pays the
+/// fee and any rent (as `rent_payer`) while the user only signs for its tokens.
+#[cfg(not(target_os = "solana"))]
+pub mod sponsored {
+    use anchor_lang::solana_program::{
+        hash::Hash, instruction::Instruction, message::Message, pubkey::Pubkey, system_instruction,
+    };
+
+    /// Message paid for by `sponsor` whose lifetime is anchored to a durable nonce rather than
+    /// a recent blockhash, so the user can sign ahead of time and the sponsor submits later.
+    /// `advance_nonce_account` is prepended as the runtime requires. Required signers: the
+    /// sponsor, the nonce authority, and every signer of `instructions` (e.g. the user).
+    pub fn sponsored_message(
+        instructions: &[Instruction],
+        sponsor: &Pubkey,
+        nonce_account: &Pubkey,
+        nonce_authority: &Pubkey,
+        nonce_hash: Hash,
+    ) -> Message {
+        let mut ixs = Vec::with_capacity(instructions.len() + 1);
+        ixs.push(system_instruction::advance_nonce_account(
+            nonce_account,
+            nonce_authority,
+        ));
+        ixs.extend_from_slice(instructions);
+        Message::new_with_blockhash(&ixs, Some(sponsor), &nonce_hash)
+    }
+}
+
+/// Off-chain reads of the versioned router accounts for the client SDK, indexers and
+/// migration tooling. Config and Registry carry a schema version byte at a fixed offset; later
+/// layouts only append after it, and layouts from before it existed are too short to hold it
+/// and read as version 0. Accounts from an incompatible deployment are rejected with a typed error
+/// instead of being misparsed.
+#[cfg(not(target_os = "solana"))]
+pub mod schema {
+    use super::{
+        Config, Registry, CONFIG_SCHEMA_VERSION, CONFIG_V1_SPACE, REGISTRY_SCHEMA_VERSION,
+        REGISTRY_SPACE,
+    };
+    use anchor_lang::{AccountDeserialize, Discriminator};
+
+    /// Why account data was rejected.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum SchemaError {
+        /// Shorter than a discriminator, or the discriminator of another account type
+        DiscriminatorMismatch,
+        /// Written with a layout version this build does not read
+        UnsupportedVersion { found: u8, expected: u8 },
+        /// Right discriminator and version, but the body does not deserialize
+        Corrupt,
+    }
+
+    /// How stored data relates to the layout this build writes.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Migration {
+        Current,
+        /// Written by an older program; the account needs migrating before use
+        Outdated {
+            found: u8,
+        },
+        /// Written by a newer program; the tooling needs upgrading
+        Newer {
+            found: u8,
+        },
+    }
+
+    /// A router account whose layout carries a schema version byte.
+    pub trait VersionedAccount: AccountDeserialize + Discriminator {
+        const SCHEMA_VERSION: u8;
+        /// Offset of the version byte: the last byte of the layout that introduced it.
+        const VERSION_OFFSET: usize;
+
+        /// Stored layout version after checking the discriminator; 0 for pre-versioning data.
+        fn schema_version(data: &[u8]) -> Result<u8, SchemaError> {
+            if data.len() < 8 || data[..8] != Self::DISCRIMINATOR {
+                return Err(SchemaError::DiscriminatorMismatch);
+            }
+            Ok(data.get(Self::VERSION_OFFSET).copied().unwrap_or(0))
+        }
+
+        fn migration(data: &[u8]) -> Result<Migration, SchemaError> {
+            let found = Self::schema_version(data)?;
+            Ok(match found.cmp(&Self::SCHEMA_VERSION) {
+                core::cmp::Ordering::Equal => Migration::Current,
+                core::cmp::Ordering::Less => Migration::Outdated { found },
+                core::cmp::Ordering::Greater => Migration::Newer { found },
+            })
+        }
+
+        /// Deserialize raw account data, accepting only the current layout version.
+        fn try_from_account_data(data: &[u8]) -> Result<Self, SchemaError> {
+            let found = Self::schema_version(data)?;
+            if found != Self::SCHEMA_VERSION {
+                return Err(SchemaError::UnsupportedVersion {
+                    found,
+                    expected: Self::SCHEMA_VERSION,
+                });
+            }
+            Self::try_deserialize(&mut &data[..]).map_err(|_| SchemaError::Corrupt)
+        }
+    }
+
+    impl VersionedAccount for Config {
+        const SCHEMA_VERSION: u8 = CONFIG_SCHEMA_VERSION;
+        const VERSION_OFFSET: usize = CONFIG_V1_SPACE - 1;
+    }
+
+    impl VersionedAccount for Registry {
+        const SCHEMA_VERSION: u8 = REGISTRY_SCHEMA_VERSION;
+        const VERSION_OFFSET: usize = REGISTRY_SPACE - 1;
+    }
+}
+
 use anchor_lang::solana_program::{
-    program::invoke_signed, pubkey::Pubkey, rent::Rent, system_instruction,
+    instruction::AccountMeta,
+    program::{get_return_data, invoke_signed, set_return_data},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
 };
-use anchor_lang::Discriminator;
-use hash::{global_route_id, keccak256, message_hash_be};
+use hash::{global_route_id, keccak256, message_hash_be, HashScheme, HASH_SCHEMA_VERSION};
+use oracle::PythPrice;
+pub use zpx_core;
+pub use zpx_core::fees::{
+    evaluate_fee_policy, fee_split_shares, MAX_FEE_SPLIT_RECIPIENTS, MAX_FEE_TIERS,
+};
+pub use zpx_core::payload::{
+    CompressedPayload, COMPRESSED_PAYLOAD_MAGIC, COMPRESSION_DEFLATE, COMPRESSION_ZSTD,
+    MAX_DECOMPRESSED_PAYLOAD_LEN, PAYLOAD_MAGIC_MAX_LEN,
+};
+use zpx_core::{amount, fees, payload, recipient};
+use zpx_log::zpx_log;
+pub use zpx_replay_guard as replay_guard;
+use zpx_replay_guard::{ReplayError, REPLAY_SPACE};
 
 // Updated to use vault-program.json derived pubkey
 declare_id!("zoopxFVyJcE2LAcMqDnKjWx9jv7UWDkDvqviVVypVPz");
 
+// `devnet-relaxed` swaps in permissive validation for QA clusters; it must never ship
+// in a mainnet build.
+#[cfg(all(feature = "devnet-relaxed", feature = "mainnet"))]
+compile_error!("feature `devnet-relaxed` cannot be combined with feature `mainnet`");
+#[cfg(all(feature = "test-instructions", feature = "mainnet"))]
+compile_error!("feature `test-instructions` cannot be combined with feature `mainnet`");
+#[cfg(all(feature = "sandbox", feature = "mainnet"))]
+compile_error!("feature `sandbox` cannot be combined with feature `mainnet`");
+
+/// Heap size used with `custom-heap`. Replaces the default 32 KiB bump heap; every
+/// transaction calling the program must then request the frame first with
+/// `ComputeBudgetInstruction::request_heap_frame(LARGE_HEAP_LEN)`.
+pub const LARGE_HEAP_LEN: usize = 256 * 1024;
+
+// The entrypoint macro only installs its default allocator without `custom-heap`
+#[cfg(all(feature = "custom-heap", target_os = "solana"))]
+#[global_allocator]
+static ALLOCATOR: anchor_lang::solana_program::entrypoint::BumpAllocator =
+    anchor_lang::solana_program::entrypoint::BumpAllocator {
+        start: anchor_lang::solana_program::entrypoint::HEAP_START_ADDRESS as usize,
+        len: LARGE_HEAP_LEN,
+    };
+
+#[cfg(not(feature = "devnet-relaxed"))]
 const FEE_CAP_BPS: u16 = 5; // protocol fee cap (0.05%)
+#[cfg(not(feature = "devnet-relaxed"))]
 const RELAYER_FEE_CAP_BPS: u16 = 1000; // relayer fee cap (10%) – adjustable in config
+#[cfg(feature = "devnet-relaxed")]
+const FEE_CAP_BPS: u16 = 10_000; // devnet-relaxed: protocol fee bounded only by amount
+#[cfg(feature = "devnet-relaxed")]
+const RELAYER_FEE_CAP_BPS: u16 = 10_000; // devnet-relaxed: relayer fee bounded only by amount
+
+// A mainnet build without a break-glass sentinel would leave global_kill unusable.
+#[cfg(feature = "mainnet")]
+const _: () = assert!(
+    option_env!("ZPX_KILL_SENTINEL").is_some(),
+    "mainnet builds must set ZPX_KILL_SENTINEL"
+);
+
+/// Slots between schedule_kill_release and release_global_kill (~48h at 400ms slots).
+pub const KILL_RELEASE_DELAY_SLOTS: u64 = 432_000;
+/// Slots between propose_adapter and activate_adapter (~48h at 400ms slots).
+pub const ADAPTER_ACTIVATION_DELAY_SLOTS: u64 = 432_000;
+pub const ADAPTER_PROPOSAL_SEED: &[u8] = b"adapter_proposal";
+pub const ADAPTER_SEED: &[u8] = b"adapter";
+
+/// Break-glass key allowed to sign global_kill, fixed per deployment at build time through
+/// the base58 `ZPX_KILL_SENTINEL` env var. Builds without it fall back to the default
+/// pubkey, which can never sign.
+pub fn kill_sentinel() -> Pubkey {
+    option_env!("ZPX_KILL_SENTINEL")
+        .and_then(|s| s.parse().ok())
+        .unwrap_or_default()
+}
 
 #[program]
 pub mod zpx_router {
@@ -75,29 +570,102 @@ pub mod zpx_router {
             crate::ID.to_string() != "11111111111111111111111111111111",
             ErrorCode::PlaceholderProgramId
         );
+        // Explicit re-init protection instead of an opaque system-program failure
+        let config_ai = ctx.accounts.config.to_account_info();
         require!(
-            relayer_fee_bps <= RELAYER_FEE_CAP_BPS,
-            ErrorCode::RelayerFeeTooHigh
+            config_ai.owner != ctx.program_id && config_ai.data_len() == 0,
+            ErrorCode::ConfigAlreadyInitialized
         );
-        require!(
-            protocol_fee_bps <= FEE_CAP_BPS,
-            ErrorCode::ProtocolFeeTooHigh
+        let bump = ctx.bumps.get("config").copied().unwrap();
+        let cfg = build_config(
+            admin,
+            fee_recipient,
+            src_chain_id,
+            relayer_fee_bps,
+            protocol_fee_bps,
+            relayer_pubkey,
+            accept_any_token,
+            allowed_token_mint,
+            direct_relayer_payout_default,
+            min_forward_amount,
+            bump,
+        )?;
+        create_pda_account(
+            &ctx.accounts.payer.to_account_info(),
+            &config_ai,
+            &ctx.accounts.system_program.to_account_info(),
+            CONFIG_SPACE,
+            &[b"zpx_config", &[bump]],
+            ctx.program_id,
+        )?;
+        cfg.try_serialize(&mut &mut config_ai.try_borrow_mut_data()?[..])?;
+        emit!(ConfigUpdated {
+            admin,
+            fee_recipient,
+            src_chain_id,
+            relayer_fee_bps
+        });
+        Ok(())
+    }
+
+    /// Devnet escape hatch: wipe and re-create the config in place (current admin only).
+    /// Works even if the stored layout no longer deserializes; the account is resized to
+    /// the current layout. Inert unless built with `devnet-relaxed`.
+    pub fn reinitialize_config_devnet(
+        ctx: Context<ReinitializeConfigDevnet>,
+        admin: Pubkey,
+        fee_recipient: Pubkey,
+        src_chain_id: u64,
+        relayer_fee_bps: u16,
+        protocol_fee_bps: u16,
+        relayer_pubkey: Pubkey,
+        accept_any_token: bool,
+        allowed_token_mint: Pubkey,
+        direct_relayer_payout_default: bool,
+        min_forward_amount: u64,
+    ) -> Result<()> {
+        require!(cfg!(feature = "devnet-relaxed"), ErrorCode::DevnetOnly);
+        let config_ai = ctx.accounts.config.to_account_info();
+        require_keys_eq!(*config_ai.owner, *ctx.program_id, ErrorCode::Unauthorized);
+        // admin is the first field after the discriminator in every Config layout so far
+        let current_admin = {
+            let data = config_ai.try_borrow_data()?;
+            require!(data.len() >= 8 + 32, ErrorCode::Unauthorized);
+            Pubkey::try_from(&data[8..40]).unwrap()
+        };
+        require_keys_eq!(
+            current_admin,
+            ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
         );
-        let cfg = &mut ctx.accounts.config;
-        cfg.admin = admin;
-        cfg.fee_recipient = fee_recipient;
-        cfg.src_chain_id = src_chain_id;
-        cfg.relayer_fee_bps = relayer_fee_bps;
-        cfg.protocol_fee_bps = protocol_fee_bps;
-        cfg.relayer_pubkey = relayer_pubkey;
-        cfg.accept_any_token = accept_any_token;
-        cfg.allowed_token_mint = allowed_token_mint;
-        cfg.direct_relayer_payout_default = direct_relayer_payout_default;
-        cfg.min_forward_amount = min_forward_amount;
-        cfg.adapters_len = 0;
-        cfg.adapters = [Pubkey::default(); 8];
-        cfg.paused = false;
-        cfg.bump = ctx.bumps.get("config").copied().unwrap();
+        let bump = ctx.bumps.get("config").copied().unwrap();
+        let cfg = build_config(
+            admin,
+            fee_recipient,
+            src_chain_id,
+            relayer_fee_bps,
+            protocol_fee_bps,
+            relayer_pubkey,
+            accept_any_token,
+            allowed_token_mint,
+            direct_relayer_payout_default,
+            min_forward_amount,
+            bump,
+        )?;
+        let required = Rent::get()?.minimum_balance(CONFIG_SPACE);
+        let top_up = required.saturating_sub(config_ai.lamports());
+        if top_up > 0 {
+            anchor_lang::solana_program::program::invoke(
+                &system_instruction::transfer(&ctx.accounts.authority.key(), config_ai.key, top_up),
+                &[
+                    ctx.accounts.authority.to_account_info(),
+                    config_ai.clone(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+            )?;
+        }
+        config_ai.realloc(CONFIG_SPACE, true)?;
+        cfg.try_serialize(&mut &mut config_ai.try_borrow_mut_data()?[..])?;
         emit!(ConfigUpdated {
             admin,
             fee_recipient,
@@ -119,14 +687,58 @@ pub mod zpx_router {
         direct_relayer_payout_default: Option<bool>,
         min_forward_amount: Option<u64>,
         paused: Option<bool>,
+        max_relayer_fee_lamports: Option<u64>,
+        usd_limits_enabled: Option<bool>,
+        strict_vaults: Option<bool>,
+        fee_recipient_grace_slots: Option<u64>,
+        lp_fee_bps: Option<u16>,
+        lp_vault_program: Option<Pubkey>,
+        guardian: Option<Pubkey>,
+        idempotency_key: Option<u64>,
+        legacy_message_hash: Option<bool>,
     ) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::UpdateConfig {
+                fee_recipient,
+                src_chain_id,
+                relayer_fee_bps,
+                protocol_fee_bps,
+                relayer_pubkey,
+                accept_any_token,
+                allowed_token_mint,
+                direct_relayer_payout_default,
+                min_forward_amount,
+                paused,
+                max_relayer_fee_lamports,
+                usd_limits_enabled,
+                strict_vaults,
+                fee_recipient_grace_slots,
+                lp_fee_bps,
+                lp_vault_program,
+                guardian,
+                idempotency_key,
+                legacy_message_hash,
+            },
+        )?;
         let cfg = &mut ctx.accounts.config;
-        // Explicit admin check (defense in depth)
-        require!(
-            cfg.admin == ctx.accounts.authority.key(),
-            ErrorCode::Unauthorized
-        );
+        if cfg.replayed_admin_op(idempotency_key)? {
+            return Ok(());
+        }
         if let Some(fr) = fee_recipient {
+            if fr != cfg.fee_recipient {
+                let history = &mut ctx.accounts.fee_recipient_history;
+                history.record(fr, Clock::get()?.slot);
+                emit!(FeeRecipientRotated {
+                    previous: cfg.fee_recipient,
+                    current: fr,
+                    epoch: history.epoch,
+                    activated_at_slot: history.entries[history.head()].activated_at_slot,
+                });
+            }
             cfg.fee_recipient = fr;
         }
         if let Some(s) = src_chain_id {
@@ -156,14 +768,146 @@ pub mod zpx_router {
             cfg.min_forward_amount = m;
         }
         if let Some(p) = paused {
+            require!(p || !cfg.killed, ErrorCode::GlobalKilled);
             cfg.paused = p;
         }
+        if let Some(l) = max_relayer_fee_lamports {
+            cfg.max_relayer_fee_lamports = l;
+        }
+        if let Some(u) = usd_limits_enabled {
+            cfg.usd_limits_enabled = u;
+        }
+        if let Some(sv) = strict_vaults {
+            cfg.strict_vaults = sv;
+        }
+        if let Some(g) = fee_recipient_grace_slots {
+            ctx.accounts.fee_recipient_history.grace_slots = g;
+        }
+        if let Some(lp) = lp_fee_bps {
+            require!(lp <= FEE_CAP_BPS, ErrorCode::ProtocolFeeTooHigh);
+            cfg.lp_fee_bps = lp;
+        }
+        if let Some(lvp) = lp_vault_program {
+            cfg.lp_vault_program = lvp;
+        }
+        if let Some(g) = guardian {
+            cfg.guardian = g;
+        }
+        if let Some(l) = legacy_message_hash {
+            cfg.legacy_message_hash = l;
+        }
+        bump_config_version(ctx.remaining_accounts, ctx.program_id, cfg, false)?;
         emit!(ConfigUpdated {
             admin: cfg.admin,
             fee_recipient: cfg.fee_recipient,
             src_chain_id: cfg.src_chain_id,
             relayer_fee_bps: cfg.relayer_fee_bps
         });
+        sync_route_availability(ctx.remaining_accounts, ctx.program_id, cfg, None)
+    }
+
+    /// Create the fee recipient history ring buffer seeded with the current recipient (admin-only).
+    /// Flip one rollout switch (admin-only); see the FEATURE_* constants.
+    pub fn set_feature(ctx: Context<SetFeature>, feature: u8, enabled: bool) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::SetFeature { feature, enabled },
+        )?;
+        require!(feature < FEATURE_COUNT, ErrorCode::UnknownFeature);
+        let cfg = &mut ctx.accounts.config;
+        if enabled {
+            cfg.features |= 1 << feature;
+        } else {
+            cfg.features &= !(1 << feature);
+        }
+        bump_config_version(ctx.remaining_accounts, ctx.program_id, cfg, false)?;
+        emit!(FeatureToggled {
+            feature,
+            enabled,
+            features: cfg.features,
+        });
+        Ok(())
+    }
+
+    /// Pause or resume one instruction family (INSTRUCTION_FAMILY_*) without touching the
+    /// others (admin-only), e.g. freeze withdrawals during an investigation while users keep
+    /// bridging. Paused handlers fail at entry with InstructionFamilyPaused.
+    pub fn set_instruction_pause(ctx: Context<SetFeature>, family: u8, paused: bool) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::SetInstructionPause { family, paused },
+        )?;
+        require!(
+            family < INSTRUCTION_FAMILY_COUNT,
+            ErrorCode::UnknownInstructionFamily
+        );
+        let cfg = &mut ctx.accounts.config;
+        if paused {
+            cfg.paused_families |= 1 << family;
+        } else {
+            cfg.paused_families &= !(1 << family);
+        }
+        bump_config_version(ctx.remaining_accounts, ctx.program_id, cfg, false)?;
+        sync_route_availability(ctx.remaining_accounts, ctx.program_id, cfg, None)?;
+        emit!(InstructionPauseUpdated {
+            family,
+            paused,
+            paused_families: cfg.paused_families,
+        });
+        Ok(())
+    }
+
+    /// Register (or clear with the default pubkey) the program CPI'd after every successful
+    /// universal_bridge_transfer. `best_effort` skips a missing hook account instead of
+    /// failing the bridge.
+    pub fn set_bridge_hook(
+        ctx: Context<SetBridgeHook>,
+        hook: Pubkey,
+        best_effort: bool,
+    ) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::SetBridgeHook { hook, best_effort },
+        )?;
+        // The router never calls itself; anything else must be a deployed program
+        require_keys_neq!(hook, crate::ID, ErrorCode::InvalidBridgeHook);
+        let cfg = &mut ctx.accounts.config;
+        cfg.bridge_hook = hook;
+        cfg.bridge_hook_best_effort = best_effort;
+        bump_config_version(ctx.remaining_accounts, ctx.program_id, cfg, false)?;
+        emit!(BridgeHookUpdated { hook, best_effort });
+        Ok(())
+    }
+
+    pub fn initialize_fee_recipient_history(
+        ctx: Context<InitializeFeeRecipientHistory>,
+    ) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::InitializeFeeRecipientHistory {},
+        )?;
+        let cfg = &ctx.accounts.config;
+        let history = &mut ctx.accounts.fee_recipient_history;
+        history.epoch = 0;
+        history.entries = [FeeRecipientEpoch::default(); FEE_RECIPIENT_HISTORY_LEN];
+        history.entries[0] = FeeRecipientEpoch {
+            fee_recipient: cfg.fee_recipient,
+            activated_at_slot: Clock::get()?.slot,
+        };
+        history.bump = ctx.bumps.get("fee_recipient_history").copied().unwrap();
+        history.grace_slots = 0;
         Ok(())
     }
 
@@ -171,35 +915,103 @@ pub mod zpx_router {
         let registry = &mut ctx.accounts.registry;
         registry.spokes_len = 0;
         registry.bump = ctx.bumps.get("registry").copied().unwrap();
+        registry.schema_version = REGISTRY_SCHEMA_VERSION;
         Ok(())
     }
 
-    pub fn admin_withdraw(ctx: Context<AdminWithdraw>, amount: u64) -> Result<()> {
-        let cfg = &ctx.accounts.config;
+    /// Devnet self-serve: create an isolated config + registry pair under
+    /// `[b"zpx_config", namespace]` / `[b"hub_registry", namespace]`. Every instruction that
+    /// takes the config resolves the registry through the same namespace, so the pair behaves
+    /// as an independent router instance. Inert unless built with `sandbox`.
+    pub fn initialize_sandbox(
+        ctx: Context<InitializeSandbox>,
+        namespace: String,
+        admin: Pubkey,
+        fee_recipient: Pubkey,
+        src_chain_id: u64,
+        relayer_fee_bps: u16,
+        protocol_fee_bps: u16,
+        relayer_pubkey: Pubkey,
+        accept_any_token: bool,
+        allowed_token_mint: Pubkey,
+        min_forward_amount: u64,
+    ) -> Result<()> {
+        require!(cfg!(feature = "sandbox"), ErrorCode::SandboxDisabled);
+        let ns = namespace.as_bytes();
         require!(
-            cfg.admin == ctx.accounts.authority.key(),
-            ErrorCode::Unauthorized
-        );
-        // Ensure hub_protocol_vault matches expected PDA for this mint
-        let seeds: &[&[u8]] = &[b"hub_protocol_vault", &ctx.accounts.mint.key().to_bytes()];
-        let (expected_vault, bump) = Pubkey::find_program_address(seeds, ctx.program_id);
-        // Ensure the provided token account matches the expected PDA and that
-        // the token account's authority (owner field) equals the PDA. Also
-        // ensure the account itself is owned by the SPL Token program.
-        require_keys_eq!(
-            expected_vault,
-            ctx.accounts.hub_protocol_vault.key(),
-            ErrorCode::InvalidVaultPda
-        );
-        require_keys_eq!(
-            ctx.accounts.hub_protocol_vault.owner,
-            expected_vault,
-            ErrorCode::InvalidVaultOwner
+            !ns.is_empty() && ns.len() <= SANDBOX_NAMESPACE_LEN,
+            ErrorCode::InvalidSandboxNamespace
         );
+        let config_ai = ctx.accounts.config.to_account_info();
         require!(
-            ctx.accounts.hub_protocol_vault.to_account_info().owner == &token::ID,
-            ErrorCode::InvalidTokenProgram
+            config_ai.owner != ctx.program_id && config_ai.data_len() == 0,
+            ErrorCode::ConfigAlreadyInitialized
         );
+        let bump = ctx.bumps.get("config").copied().unwrap();
+        let mut cfg = build_config(
+            admin,
+            fee_recipient,
+            src_chain_id,
+            relayer_fee_bps,
+            protocol_fee_bps,
+            relayer_pubkey,
+            accept_any_token,
+            allowed_token_mint,
+            false,
+            min_forward_amount,
+            bump,
+        )?;
+        cfg.namespace_len = ns.len() as u8;
+        cfg.namespace[..ns.len()].copy_from_slice(ns);
+        create_pda_account(
+            &ctx.accounts.payer.to_account_info(),
+            &config_ai,
+            &ctx.accounts.system_program.to_account_info(),
+            CONFIG_SPACE,
+            &[b"zpx_config", ns, &[bump]],
+            ctx.program_id,
+        )?;
+        cfg.try_serialize(&mut &mut config_ai.try_borrow_mut_data()?[..])?;
+        let registry = &mut ctx.accounts.registry;
+        registry.spokes_len = 0;
+        registry.bump = ctx.bumps.get("registry").copied().unwrap();
+        registry.schema_version = REGISTRY_SCHEMA_VERSION;
+        emit!(SandboxInitialized {
+            namespace: cfg.namespace,
+            config: config_ai.key(),
+            registry: registry.key(),
+            admin,
+        });
+        Ok(())
+    }
+
+    pub fn admin_withdraw(ctx: Context<AdminWithdraw>, amount: u64) -> Result<()> {
+        ctx.accounts
+            .config
+            .require_family_active(INSTRUCTION_FAMILY_WITHDRAW)?;
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::AdminWithdraw { amount },
+        )?;
+        let cfg = &ctx.accounts.config;
+        require!(!cfg.killed, ErrorCode::GlobalKilled);
+        // Ensure hub_protocol_vault matches expected PDA for this mint
+        let bump = verify_hub_vault(
+            &ctx.accounts.hub_protocol_vault,
+            b"hub_protocol_vault",
+            &ctx.accounts.mint.key(),
+            ctx.program_id,
+        )?;
+        check_vault_pattern(
+            &ctx.accounts.vault_registry,
+            &ctx.accounts.mint.key(),
+            &ctx.accounts.hub_protocol_vault.key(),
+            cfg.strict_vaults,
+            ctx.program_id,
+        )?;
 
         // Use program-signed CPI to move tokens from the PDA vault to the destination
         let signer_seeds: &[&[&[u8]]] = &[&[
@@ -209,7 +1021,7 @@ pub mod zpx_router {
         ]];
         token::transfer(
             CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
+                token_cpi_program(&ctx.accounts.token_program)?,
                 token::Transfer {
                     from: ctx.accounts.hub_protocol_vault.to_account_info(),
                     to: ctx.accounts.destination.to_account_info(),
@@ -222,1226 +1034,15987 @@ pub mod zpx_router {
         Ok(())
     }
 
-    pub fn add_adapter(ctx: Context<AdminConfig>, adapter: Pubkey) -> Result<()> {
-        let cfg = &mut ctx.accounts.config;
-        // Explicit admin check (defense in depth)
-        require!(
-            cfg.admin == ctx.accounts.authority.key(),
-            ErrorCode::Unauthorized
-        );
-        let len = cfg.adapters_len as usize;
-        for i in 0..len {
-            if cfg.adapters[i] == adapter {
-                return err!(ErrorCode::AdapterAlreadyExists);
-            }
-        }
-        require!(len < 8, ErrorCode::AdapterListFull);
-        cfg.adapters[len] = adapter;
-        cfg.adapters_len += 1;
-        emit!(AdapterAdded {
-            admin: cfg.admin,
-            program: adapter
-        });
+    /// Recompute the message hash from a stored MessagePreimage and compare it with
+    /// `expected_message_hash`. Read-only; the result is returned via return data as
+    /// `[matches as u8] ++ recomputed_hash` so third-party verifiers can CPI into it.
+    pub fn verify_preimage(
+        ctx: Context<VerifyPreimage>,
+        expected_message_hash: [u8; 32],
+    ) -> Result<()> {
+        let recomputed = ctx.accounts.message.recompute_message_hash()?;
+        let mut out = [0u8; 33];
+        out[0] = (recomputed == expected_message_hash) as u8;
+        out[1..].copy_from_slice(&recomputed);
+        set_return_data(&out);
         Ok(())
     }
 
-    pub fn remove_adapter(ctx: Context<AdminConfig>, adapter: Pubkey) -> Result<()> {
-        let cfg = &mut ctx.accounts.config;
-        // Explicit admin check (defense in depth)
-        require!(
-            cfg.admin == ctx.accounts.authority.key(),
-            ErrorCode::Unauthorized
-        );
-        let len = cfg.adapters_len as usize;
-        let mut idx = None;
-        for i in 0..len {
-            if cfg.adapters[i] == adapter {
-                idx = Some(i);
-                break;
-            }
-        }
-        let i = idx.ok_or_else(|| error!(ErrorCode::AdapterNotAllowed))?;
-        let last = len - 1;
-        if i != last {
-            cfg.adapters[i] = cfg.adapters[last];
-        }
-        cfg.adapters[last] = Pubkey::default();
-        cfg.adapters_len -= 1;
-        emit!(AdapterRemoved {
-            admin: cfg.admin,
-            program: adapter
+    /// Check a proposed escrow bridge without moving funds: derivable PDAs, the user's ATA,
+    /// mint pause and allowlist, spoke status and the user's balance. Never fails on a
+    /// problem; the findings are returned via return data as a Borsh PreflightReport whose
+    /// `problems` is a PREFLIGHT_* bitmask (0 = the bridge should go through).
+    pub fn preflight(ctx: Context<Preflight>, route: RouteDescriptor) -> Result<()> {
+        let report = preflight_route(ctx.accounts, &route, ctx.program_id, Clock::get()?.slot)?;
+        set_return_data(&report.try_to_vec()?);
+        Ok(())
+    }
+
+    /// Create the canonical protocol/relayer vaults for a mint at their PDA addresses
+    /// (token authority = the vault PDA itself) and record them in a VaultRegistry (admin-only).
+    pub fn initialize_hub_vaults(ctx: Context<InitializeHubVaults>) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::InitializeHubVaults {},
+        )?;
+        let accounts = &mut *ctx.accounts;
+        init_hub_vaults(
+            &accounts.authority,
+            &accounts.mint,
+            &accounts.hub_protocol_vault,
+            &accounts.hub_relayer_vault,
+            &mut accounts.vault_registry,
+            ctx.bumps.get("vault_registry").copied().unwrap(),
+            &accounts.rent_ledger,
+            &accounts.token_program,
+            &accounts.system_program,
+            ctx.program_id,
+        )?;
+        let registry = &ctx.accounts.vault_registry;
+        emit!(HubVaultsInitialized {
+            mint: registry.mint,
+            protocol_vault: registry.protocol_vault,
+            relayer_vault: registry.relayer_vault,
+            version: registry.version,
         });
         Ok(())
     }
 
-    /// Thin source-leg entrypoint (no vault logic). Pull -> skim -> forward -> emit.
-    pub fn universal_bridge_transfer(
-        ctx: Context<UniversalBridgeTransfer>,
+    /// Create the canonical hub_lp_vault for a mint (admin-only). LP fees skimmed on the
+    /// destination leg accumulate here for the zpx_lp_vaults program to withdraw.
+    pub fn initialize_hub_lp_vault(ctx: Context<InitializeHubLpVault>) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::InitializeHubLpVault {},
+        )?;
+        create_hub_vault(
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.hub_lp_vault.to_account_info(),
+            &ctx.accounts.mint.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.token_program.to_account_info(),
+            b"hub_lp_vault",
+            ctx.program_id,
+        )?;
+        record_rent(
+            &ctx.accounts.rent_ledger.to_account_info(),
+            ctx.program_id,
+            RENT_KIND_VAULT,
+            TokenAccount::LEN,
+            true,
+        )
+    }
+
+    /// Destination-leg delivery with LP fee: after finalize_message_v1 marked `message_hash`
+    /// processed, the relayer delivers `amount` from its token account, with `config.lp_fee_bps`
+    /// skimmed into hub_lp_vault and the rest sent to the recipient. Once per message.
+    pub fn settle_dest_lp_fee(
+        ctx: Context<SettleDestLpFee>,
+        message_hash: [u8; 32],
         amount: u64,
-        protocol_fee: u64,
-        relayer_fee: u64,
-        payload: Vec<u8>,
-        dst_chain_id: u64,
-        nonce: u64,
     ) -> Result<()> {
+        ctx.accounts
+            .config
+            .require_family_active(INSTRUCTION_FAMILY_FINALIZE)?;
         let cfg = &ctx.accounts.config;
-        // Chain id width guard to avoid silent truncation when emitting u16
-        require!(
-            cfg.src_chain_id <= u16::MAX as u64 && dst_chain_id <= u16::MAX as u64,
-            ErrorCode::ChainIdOutOfRange
-        );
-        // Defensive: correct token program
         require!(
-            ctx.accounts.token_program.key() == Token::id(),
-            ErrorCode::InvalidTokenProgram
+            ctx.accounts.relayer.key() == cfg.relayer_pubkey
+                || ctx.accounts.relayer.key() == cfg.admin,
+            ErrorCode::Unauthorized
         );
         require!(!cfg.paused, ErrorCode::Paused);
-        require!(cfg.src_chain_id != 0, ErrorCode::SrcChainNotSet);
-        validate_common(amount, payload.len(), cfg.paused, cfg.src_chain_id)?;
-        validate_payload_len(payload.len())?;
-        // Adapter allowlist: ensure target is allowed
-        require!(
-            is_allowed_adapter_cfg(cfg, &ctx.accounts.target_adapter_program.key()),
-            ErrorCode::AdapterNotAllowed
-        );
-        let (forward_amount, total_fees) =
-            compute_fees_and_forward(amount, protocol_fee, relayer_fee, cfg.relayer_fee_bps)?;
-
-        // Strict ATA derivation: ensure provided ATA matches expected associated account for fee recipient
-        // Use the associated token program PDA derivation with token program id as parameter.
-        // Expected = get_associated_token_address_with_program_id(fee_recipient, mint, token_program.key())
-        let ata_seeds: &[&[u8]] = &[
-            &cfg.fee_recipient.to_bytes(),
-            &ctx.accounts.token_program.key().to_bytes(),
-            &ctx.accounts.mint.key().to_bytes(),
-        ];
-        let (expected_fee_ata, _bump) =
-            Pubkey::find_program_address(ata_seeds, &anchor_spl::associated_token::ID);
-        require!(
-            ctx.accounts.fee_recipient_ata.key() == expected_fee_ata,
-            ErrorCode::InvalidFeeRecipientAta
-        );
-        // Extra checks for safety
-        require!(
-            ctx.accounts.fee_recipient_ata.owner == Token::id(),
-            ErrorCode::InvalidTokenProgram
-        );
-        require!(
-            ctx.accounts.fee_recipient_ata.mint == ctx.accounts.mint.key(),
-            ErrorCode::InvalidFeeRecipientAta
+        require!(amount > 0, ErrorCode::ZeroAmount);
+        // The message must have been finalized (replay marker processed)
+        let replay_ai = ctx.accounts.replay.to_account_info();
+        replay_guard::verify_address(replay_ai.key, &message_hash, ctx.program_id)
+            .map_err(ErrorCode::from)?;
+        require_keys_eq!(
+            *replay_ai.owner,
+            *ctx.program_id,
+            ErrorCode::InvalidReplayOwner
         );
-
-        // Transfer: user -> fee_recipient (fees)
-        if total_fees > 0 {
+        replay_guard::require_processed(&replay_ai, ctx.program_id).map_err(ErrorCode::from)?;
+        verify_hub_vault(
+            &ctx.accounts.hub_lp_vault,
+            b"hub_lp_vault",
+            &ctx.accounts.mint.key(),
+            ctx.program_id,
+        )?;
+        let lp_fee = fees::bps_fee(amount, cfg.lp_fee_bps);
+        let delivered = amount - lp_fee;
+        ctx.accounts.lp_skim.bump = ctx.bumps.get("lp_skim").copied().unwrap();
+        if lp_fee > 0 {
             token::transfer(
                 CpiContext::new(
-                    ctx.accounts.token_program.to_account_info(),
+                    token_cpi_program(&ctx.accounts.token_program)?,
                     token::Transfer {
-                        from: ctx.accounts.from.to_account_info(),
-                        to: ctx.accounts.fee_recipient_ata.to_account_info(),
-                        authority: ctx.accounts.user.to_account_info(),
+                        from: ctx.accounts.relayer_token_account.to_account_info(),
+                        to: ctx.accounts.hub_lp_vault.to_account_info(),
+                        authority: ctx.accounts.relayer.to_account_info(),
                     },
                 ),
-                total_fees,
+                lp_fee,
             )?;
         }
-
-        // Transfer: user -> target (forward amount)
-        if forward_amount > 0 {
+        if delivered > 0 {
             token::transfer(
                 CpiContext::new(
-                    ctx.accounts.token_program.to_account_info(),
+                    token_cpi_program(&ctx.accounts.token_program)?,
                     token::Transfer {
-                        from: ctx.accounts.from.to_account_info(),
-                        to: ctx.accounts.target_token_account.to_account_info(),
-                        authority: ctx.accounts.user.to_account_info(),
+                        from: ctx.accounts.relayer_token_account.to_account_info(),
+                        to: ctx.accounts.recipient_token_account.to_account_info(),
+                        authority: ctx.accounts.relayer.to_account_info(),
                     },
                 ),
-                forward_amount,
+                delivered,
             )?;
         }
+        emit!(LpFeeApplied {
+            message_hash,
+            mint: ctx.accounts.mint.key(),
+            amount,
+            lp_fee,
+            lp_bps: cfg.lp_fee_bps,
+            recipient: ctx.accounts.recipient_token_account.owner,
+        });
+        Ok(())
+    }
 
-        // Canonical hashes
-        let payload_hash = keccak256(&[payload.as_slice()]);
-        let src_adapter_32 = ctx.accounts.target_adapter_program.key().to_bytes(); // adapter-agnostic: target program as srcAdapter
-        let recipient_32 = [0u8; 32]; // unknown on source leg (recipient resolved on dest)
-        let asset_32 = ctx.accounts.mint.key().to_bytes();
-        let mut amount_be = [0u8; 32];
-        amount_be[16..].copy_from_slice(&(forward_amount as u128).to_be_bytes());
-        let msg_hash = message_hash_be(
-            cfg.src_chain_id,
-            src_adapter_32,
-            recipient_32,
-            asset_32,
-            amount_be,
-            payload_hash,
-            nonce,
-            dst_chain_id,
+    /// Withdraw accumulated LP fees. Only the zpx_lp_vaults program can call this, signing
+    /// with its [b"lp_authority"] PDA, so LP depositors receive the fee stream.
+    pub fn withdraw_lp_fees(ctx: Context<WithdrawLpFees>, amount: u64) -> Result<()> {
+        ctx.accounts
+            .config
+            .require_family_active(INSTRUCTION_FAMILY_WITHDRAW)?;
+        let cfg = &ctx.accounts.config;
+        require!(!cfg.killed, ErrorCode::GlobalKilled);
+        require!(
+            cfg.lp_vault_program != Pubkey::default(),
+            ErrorCode::Unauthorized
         );
-        let initiator_32 = ctx.accounts.user.key().to_bytes();
-        let global_route = global_route_id(
-            cfg.src_chain_id,
-            dst_chain_id,
-            initiator_32,
-            msg_hash,
-            nonce,
+        let (lp_authority, _) =
+            Pubkey::find_program_address(&[LP_AUTHORITY_SEED], &cfg.lp_vault_program);
+        require_keys_eq!(
+            ctx.accounts.lp_authority.key(),
+            lp_authority,
+            ErrorCode::Unauthorized
         );
-
-        // Events per EVM schema
-        emit!(BridgeInitiated {
-            route_id: [0u8; 32],
-            user: ctx.accounts.user.key(),
-            token: ctx.accounts.mint.key(),
-            target: ctx.accounts.target_adapter_program.key(),
-            forwarded_amount: forward_amount,
-            protocol_fee,
-            relayer_fee,
-            payload_hash,
-            src_chain_id: cfg.src_chain_id as u16, // EVM uses u16; store u64 but emit lower 16 bits
-            dst_chain_id: dst_chain_id as u16,
-            nonce,
-        });
-        emit!(UniversalBridgeInitiated {
-            route_id: [0u8; 32],
-            payload_hash,
-            message_hash: msg_hash,
-            global_route_id: global_route,
-            user: ctx.accounts.user.key(),
-            token: ctx.accounts.mint.key(),
-            target: ctx.accounts.target_adapter_program.key(),
-            forwarded_amount: forward_amount,
-            protocol_fee,
-            relayer_fee,
-            src_chain_id: cfg.src_chain_id as u16,
-            dst_chain_id: dst_chain_id as u16,
-            nonce,
+        let mint = ctx.accounts.mint.key();
+        let bump = verify_hub_vault(
+            &ctx.accounts.hub_lp_vault,
+            b"hub_lp_vault",
+            &mint,
+            ctx.program_id,
+        )?;
+        let signer_seeds: &[&[&[u8]]] = &[&[b"hub_lp_vault", mint.as_ref(), &[bump]]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                token_cpi_program(&ctx.accounts.token_program)?,
+                token::Transfer {
+                    from: ctx.accounts.hub_lp_vault.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                    authority: ctx.accounts.hub_lp_vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+        emit!(LpFeesWithdrawn {
+            mint,
+            amount,
+            destination: ctx.accounts.destination.key(),
         });
-        if total_fees > 0 {
-            emit!(FeeAppliedSource {
-                message_hash: msg_hash,
-                asset: ctx.accounts.mint.key(),
-                payer: ctx.accounts.user.key(),
-                target: ctx.accounts.target_adapter_program.key(),
-                protocol_fee,
-                relayer_fee,
-                fee_recipient: cfg.fee_recipient,
-                applied_at: Clock::get()?.unix_timestamp as u64,
-            });
-        }
         Ok(())
     }
 
-    // Test helper: perform a CPI to the provided adapter program. Used by program-tests
-    // to validate CPI failure handling and rollback semantics.
-    pub fn bridge_with_adapter_cpi(ctx: Context<BridgeWithAdapterCpi>) -> Result<()> {
-        // Build instruction data: adapter's `fail_now` has no args, instruction index 0
-        let ix = anchor_lang::solana_program::instruction::Instruction {
-            program_id: ctx.accounts.adapter_program.key(),
-            accounts: vec![],
-            data: vec![0u8],
-        };
-        // Perform CPI and propagate error. Pass the adapter account info so the runtime
-        // has ownership/context for the CPI.
-        anchor_lang::solana_program::program::invoke(
-            &ix,
-            &[ctx.accounts.adapter_program.to_account_info()],
-        )
-        .map_err(|_| error!(ErrorCode::Unauthorized))?;
+    /// Set the period over which relayer fees paid into hub_relayer_vault unlock (admin-only).
+    /// 0 releases new fees immediately; already-locked balances keep their schedule.
+    pub fn set_relayer_vesting(ctx: Context<SetRelayerVesting>, period_slots: u64) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::SetRelayerVesting { period_slots },
+        )?;
+        let (expected, bump) =
+            Pubkey::find_program_address(&[b"relayer_vesting_config"], ctx.program_id);
+        let vesting_ai = ctx.accounts.vesting_config.to_account_info();
+        require_keys_eq!(vesting_ai.key(), expected, ErrorCode::InvalidRelayerVesting);
+        if vesting_ai.owner != ctx.program_id {
+            create_pda_account(
+                &ctx.accounts.authority.to_account_info(),
+                &vesting_ai,
+                &ctx.accounts.system_program.to_account_info(),
+                RELAYER_VESTING_CONFIG_SPACE,
+                &[b"relayer_vesting_config", &[bump]],
+                ctx.program_id,
+            )?;
+        }
+        RelayerVestingConfig { period_slots, bump }
+            .try_serialize(&mut &mut vesting_ai.try_borrow_mut_data()?[..])?;
+        emit!(RelayerVestingUpdated { period_slots });
         Ok(())
     }
 
-    /// Hub: create a new spoke registry entry (admin-only)
-    pub fn create_spoke(
-        ctx: Context<CreateSpoke>,
-        spoke_id: u32,
-        adapter_program: Pubkey,
-        direct_relayer_payout: bool,
-        version: u8,
-        metadata: Option<String>,
+    /// Set the max message age (seconds, 0 = unbounded) for the route to and from `chain_id`
+    /// (admin-only). Outbound messages embed a validity window of that length; finalize
+    /// rejects inbound ones older than it with MessageExpired.
+    pub fn set_route_max_age(
+        ctx: Context<SetRouteMaxAge>,
+        chain_id: u64,
+        max_age_secs: u64,
     ) -> Result<()> {
-        let registry = &mut ctx.accounts.registry;
-        // Only admin PDA or config.admin can create spokes
-        let cfg = &ctx.accounts.config;
-        require!(
-            cfg.admin == ctx.accounts.authority.key() || ctx.accounts.admin.key() == cfg.admin,
-            ErrorCode::Unauthorized
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::SetRouteMaxAge {
+                chain_id,
+                max_age_secs,
+            },
+        )?;
+        let (expected, bump) = Pubkey::find_program_address(
+            &[b"route_validity", &chain_id.to_le_bytes()],
+            ctx.program_id,
         );
-        let len = registry.spokes_len as usize;
-        require!(len < MAX_SPOKES, ErrorCode::AdapterListFull);
-        // ensure unique spoke_id
-        for i in 0..len {
-            if registry.spokes[i].spoke_id == spoke_id {
-                return err!(ErrorCode::AdapterAlreadyExists);
-            }
+        let route_ai = ctx.accounts.route_validity.to_account_info();
+        require_keys_eq!(route_ai.key(), expected, ErrorCode::InvalidRouteValidity);
+        if route_ai.owner != ctx.program_id {
+            create_pda_account(
+                &ctx.accounts.authority.to_account_info(),
+                &route_ai,
+                &ctx.accounts.system_program.to_account_info(),
+                ROUTE_VALIDITY_SPACE,
+                &[b"route_validity", &chain_id.to_le_bytes(), &[bump]],
+                ctx.program_id,
+            )?;
         }
-        let mut entry = SpokeEntry::default();
-        entry.spoke_id = spoke_id;
-        entry.adapter_program = adapter_program;
-        entry.enabled = true;
-        entry.paused = false;
-        entry.direct_relayer_payout = direct_relayer_payout;
-        entry.version = version;
-        if let Some(m) = metadata {
-            let bytes = m.as_bytes();
-            let mut meta = [0u8; SPOKE_METADATA_LEN];
-            meta[..bytes.len().min(SPOKE_METADATA_LEN)]
-                .copy_from_slice(&bytes[..bytes.len().min(SPOKE_METADATA_LEN)]);
-            entry.metadata = meta;
+        RouteValidity {
+            chain_id,
+            max_age_secs,
+            bump,
         }
-        entry.created_at_slot = Clock::get()?.slot;
-        registry.spokes[len] = entry;
-        registry.spokes_len += 1;
+        .try_serialize(&mut &mut route_ai.try_borrow_mut_data()?[..])?;
+        emit!(RouteMaxAgeUpdated {
+            chain_id,
+            max_age_secs,
+        });
         Ok(())
     }
 
-    pub fn update_spoke(
-        ctx: Context<UpdateSpoke>,
-        spoke_id: u32,
-        adapter_program: Option<Pubkey>,
-        direct_relayer_payout: Option<bool>,
-        paused: Option<bool>,
-        metadata: Option<String>,
+    /// Register the EVM router on `src_chain_id` whose config root inbound messages from that
+    /// chain are checked against (admin-only). `wormhole_chain` and `evm_router` name the
+    /// eth_call a Wormhole query must prove; `expected_root` is the configRoot() the hub
+    /// accepts and `max_age_secs` how old the proven EVM block may be at finalize. Moving the
+    /// router clears any verified root.
+    pub fn set_remote_config(
+        ctx: Context<SetRemoteConfig>,
+        src_chain_id: u64,
+        wormhole_chain: u16,
+        evm_router: [u8; 20],
+        expected_root: [u8; 32],
+        max_age_secs: u64,
     ) -> Result<()> {
-        let registry = &mut ctx.accounts.registry;
-        let cfg = &ctx.accounts.config;
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::SetRemoteConfig {
+                src_chain_id,
+                wormhole_chain,
+                evm_router,
+                expected_root,
+                max_age_secs,
+            },
+        )?;
         require!(
-            cfg.admin == ctx.accounts.authority.key() || ctx.accounts.admin.key() == cfg.admin,
-            ErrorCode::Unauthorized
+            wormhole_chain != 0 && evm_router != [0; 20] && max_age_secs > 0,
+            ErrorCode::InvalidRemoteConfig
         );
-        let len = registry.spokes_len as usize;
-        let mut idx = None;
-        for i in 0..len {
-            if registry.spokes[i].spoke_id == spoke_id {
-                idx = Some(i);
-                break;
-            }
-        }
-        let i = idx.ok_or_else(|| error!(ErrorCode::AdapterNotAllowed))?;
-        if let Some(p) = adapter_program {
-            registry.spokes[i].adapter_program = p;
-        }
-        if let Some(d) = direct_relayer_payout {
-            registry.spokes[i].direct_relayer_payout = d;
-        }
-        if let Some(p) = paused {
-            registry.spokes[i].paused = p;
-        }
-        if let Some(m) = metadata {
-            let bytes = m.as_bytes();
-            let mut meta = [0u8; SPOKE_METADATA_LEN];
-            meta[..bytes.len().min(SPOKE_METADATA_LEN)]
-                .copy_from_slice(&bytes[..bytes.len().min(SPOKE_METADATA_LEN)]);
-            registry.spokes[i].metadata = meta;
+        let (expected, bump) = Pubkey::find_program_address(
+            &[REMOTE_CONFIG_SEED, &src_chain_id.to_le_bytes()],
+            ctx.program_id,
+        );
+        let remote_ai = ctx.accounts.remote_config.to_account_info();
+        require_keys_eq!(
+            remote_ai.key(),
+            expected,
+            ErrorCode::InvalidRemoteConfigAccount
+        );
+        let mut remote = if remote_ai.owner != ctx.program_id {
+            create_pda_account(
+                &ctx.accounts.authority.to_account_info(),
+                &remote_ai,
+                &ctx.accounts.system_program.to_account_info(),
+                REMOTE_CONFIG_SPACE,
+                &[REMOTE_CONFIG_SEED, &src_chain_id.to_le_bytes(), &[bump]],
+                ctx.program_id,
+            )?;
+            RemoteConfig::default()
+        } else {
+            RemoteConfig::try_deserialize(&mut &remote_ai.try_borrow_data()?[..])?
+        };
+        if remote.wormhole_chain != wormhole_chain || remote.evm_router != evm_router {
+            remote.clear_verified();
         }
+        remote.src_chain_id = src_chain_id;
+        remote.wormhole_chain = wormhole_chain;
+        remote.evm_router = evm_router;
+        remote.expected_root = expected_root;
+        remote.max_age_secs = max_age_secs;
+        remote.bump = bump;
+        remote.try_serialize(&mut &mut remote_ai.try_borrow_mut_data()?[..])?;
+        emit!(RemoteConfigSet {
+            src_chain_id,
+            wormhole_chain,
+            evm_router,
+            expected_root,
+            max_age_secs,
+        });
         Ok(())
     }
 
-    pub fn pause_spoke(ctx: Context<PauseSpoke>, spoke_id: u32) -> Result<()> {
-        let registry = &mut ctx.accounts.registry;
-        let cfg = &ctx.accounts.config;
+    /// Store the EVM router config root proven by a Wormhole query response (permissionless).
+    /// `response` must answer one eth_call of configRoot() on the registered router, and
+    /// `signatures` (ascending guardian indices) must reach quorum of the
+    /// unexpired `guardian_set`. Only responses for a newer block than the stored one land.
+    /// A 13-guardian quorum needs a raised compute budget (one secp256k1 recovery each).
+    pub fn verify_remote_config(
+        ctx: Context<VerifyRemoteConfig>,
+        src_chain_id: u64,
+        response: Vec<u8>,
+        signatures: Vec<GuardianSignature>,
+    ) -> Result<()> {
+        let set = load_guardian_set(&ctx.accounts.guardian_set)?;
+        let now = Clock::get()?;
         require!(
-            cfg.admin == ctx.accounts.authority.key() || ctx.accounts.admin.key() == cfg.admin,
-            ErrorCode::Unauthorized
+            set.active(u64::try_from(now.unix_timestamp).unwrap_or_default()),
+            ErrorCode::GuardianSetExpired
         );
-        let len = registry.spokes_len as usize;
-        let mut idx = None;
-        for i in 0..len {
-            if registry.spokes[i].spoke_id == spoke_id {
-                idx = Some(i);
-                break;
-            }
-        }
-        let i = idx.ok_or_else(|| error!(ErrorCode::AdapterNotAllowed))?;
-        registry.spokes[i].paused = true;
+        require!(
+            ccq::verify_quorum(&response, &signatures, &set),
+            ErrorCode::GuardianQuorumNotReached
+        );
+        let call = ccq::parse_eth_call(&response, ccq::config_root_selector())
+            .ok_or(ErrorCode::InvalidQueryResponse)?;
+        let remote = &mut ctx.accounts.remote_config;
+        require!(
+            call.chain_id == remote.wormhole_chain && call.to == remote.evm_router,
+            ErrorCode::RemoteConfigQueryMismatch
+        );
+        require!(
+            call.block_number > remote.verified_block,
+            ErrorCode::StaleRemoteConfigResponse
+        );
+        remote.verified_root = call.result;
+        remote.verified_block = call.block_number;
+        remote.verified_block_time_us = call.block_time_us;
+        remote.verified_slot = now.slot;
+        emit!(RemoteConfigVerified {
+            src_chain_id,
+            guardian_set_index: set.index,
+            config_root: call.result,
+            block_number: call.block_number,
+            block_time_us: call.block_time_us,
+            matches: call.result == remote.expected_root,
+        });
         Ok(())
     }
 
-    pub fn enable_spoke(ctx: Context<PauseSpoke>, spoke_id: u32) -> Result<()> {
-        let registry = &mut ctx.accounts.registry;
-        let cfg = &ctx.accounts.config;
+    /// Register or update an ecosystem partner (admin-only). Bridges referred by the
+    /// partner hold `rebate_bps` of their protocol fee for `payout` to claim.
+    pub fn set_partner(
+        ctx: Context<SetPartner>,
+        partner_id: u32,
+        payout: Pubkey,
+        rebate_bps: u16,
+    ) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::SetPartner {
+                partner_id,
+                payout,
+                rebate_bps,
+            },
+        )?;
         require!(
-            cfg.admin == ctx.accounts.authority.key() || ctx.accounts.admin.key() == cfg.admin,
-            ErrorCode::Unauthorized
+            rebate_bps <= 10_000 && payout != Pubkey::default(),
+            ErrorCode::InvalidRebateBps
         );
-        let len = registry.spokes_len as usize;
-        let mut idx = None;
-        for i in 0..len {
-            if registry.spokes[i].spoke_id == spoke_id {
-                idx = Some(i);
-                break;
-            }
+        let (expected, bump) =
+            Pubkey::find_program_address(&[b"partner", &partner_id.to_le_bytes()], ctx.program_id);
+        let partner_ai = ctx.accounts.partner.to_account_info();
+        require_keys_eq!(partner_ai.key(), expected, ErrorCode::InvalidPartnerAccount);
+        if partner_ai.owner != ctx.program_id {
+            create_pda_account(
+                &ctx.accounts.authority.to_account_info(),
+                &partner_ai,
+                &ctx.accounts.system_program.to_account_info(),
+                PARTNER_SPACE,
+                &[b"partner", &partner_id.to_le_bytes(), &[bump]],
+                ctx.program_id,
+            )?;
         }
-        let i = idx.ok_or_else(|| error!(ErrorCode::AdapterNotAllowed))?;
-        registry.spokes[i].paused = false;
+        Partner {
+            partner_id,
+            payout,
+            rebate_bps,
+            bump,
+        }
+        .try_serialize(&mut &mut partner_ai.try_borrow_mut_data()?[..])?;
+        emit!(PartnerSet {
+            partner_id,
+            payout,
+            rebate_bps,
+        });
         Ok(())
     }
 
-    /// Forward via spoke: hub-level fee skimming and CPI into adapter
-    #[allow(clippy::too_many_arguments)]
-    pub fn forward_via_spoke(
-        ctx: Context<ForwardViaSpoke>,
-        spoke_id: u32,
-        amount: u64,
-        dst_domain: u32,
-        _mint_recipient: [u8; 32],
-        is_protocol_fee: bool,
-        is_relayer_fee: bool,
-        _nonce: u64,
+    /// Pay a partner's unclaimed rebate in `mint` out of the rebate vault (payout-signed).
+    pub fn claim_partner_rebate(ctx: Context<ClaimPartnerRebate>) -> Result<()> {
+        ctx.accounts
+            .config
+            .require_family_active(INSTRUCTION_FAMILY_WITHDRAW)?;
+        require!(!ctx.accounts.config.killed, ErrorCode::GlobalKilled);
+        let mint = ctx.accounts.mint.key();
+        let rebate = &mut ctx.accounts.partner_rebate;
+        let amount = rebate.unclaimed();
+        require!(amount > 0, ErrorCode::NoPartnerRebate);
+        rebate.claimed = rebate.accrued;
+        let bump = ctx.bumps.get("partner_rebate_vault").copied().unwrap();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"partner_rebate_vault", mint.as_ref(), &[bump]]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                token_cpi_program(&ctx.accounts.token_program)?,
+                token::Transfer {
+                    from: ctx.accounts.partner_rebate_vault.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                    authority: ctx.accounts.partner_rebate_vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+        emit!(PartnerRebateClaimed {
+            partner_id: ctx.accounts.partner.partner_id,
+            mint,
+            payout: ctx.accounts.payout.key(),
+            amount,
+        });
+        Ok(())
+    }
+
+    /// Set the fee schedule of the (mint, dst_chain_id) route (admin-only): up to
+    /// MAX_FEE_TIERS amount brackets sorted by `min_amount` (the first at 0), plus flat
+    /// minimums and caps per fee. Tier rates are held to FEE_CAP_BPS / RELAYER_FEE_CAP_BPS.
+    pub fn set_fee_policy(
+        ctx: Context<SetFeePolicy>,
+        dst_chain_id: u64,
+        tiers: Vec<FeeTier>,
+        protocol_min_fee: u64,
+        protocol_max_fee: u64,
+        relayer_min_fee: u64,
+        relayer_max_fee: u64,
     ) -> Result<()> {
-        // Validate caller is relayer or admin
-        let cfg = &ctx.accounts.config;
-        require!(
-            ctx.accounts.relayer.key() == cfg.relayer_pubkey
-                || ctx.accounts.relayer.key() == cfg.admin,
-            ErrorCode::Unauthorized
-        );
-        // Lookup spoke
-        let registry = &ctx.accounts.registry;
-        let mut idx = None;
-        for i in 0..(registry.spokes_len as usize) {
-            if registry.spokes[i].spoke_id == spoke_id {
-                idx = Some(i);
-                break;
-            }
-        }
-        let i = idx.ok_or_else(|| error!(ErrorCode::AdapterNotAllowed))?;
-        let spoke = &registry.spokes[i];
-        require!(spoke.enabled && !spoke.paused, ErrorCode::AdapterNotAllowed);
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::SetFeePolicy {
+                dst_chain_id,
+                tiers: tiers.clone(),
+                protocol_min_fee,
+                protocol_max_fee,
+                relayer_min_fee,
+                relayer_max_fee,
+            },
+        )?;
+        let mint = ctx.accounts.mint.key();
+        let policy = FeePolicy::new(
+            mint,
+            dst_chain_id,
+            &tiers,
+            protocol_min_fee,
+            protocol_max_fee,
+            relayer_min_fee,
+            relayer_max_fee,
+        )?;
+        policy.store(
+            &ctx.accounts.authority,
+            &ctx.accounts.fee_policy,
+            &ctx.accounts.system_program,
+            ctx.program_id,
+        )?;
+        emit!(FeePolicyUpdated {
+            mint,
+            dst_chain_id,
+            tiers,
+            protocol_min_fee,
+            protocol_max_fee,
+            relayer_min_fee,
+            relayer_max_fee,
+        });
+        Ok(())
+    }
 
-        // Enforce hub-level fee caps (configured on init/update)
-        require!(
-            cfg.protocol_fee_bps <= FEE_CAP_BPS,
-            ErrorCode::ProtocolFeeTooHigh
-        );
+    /// Require registered relayers to co-sign forwards of `mint` whose escrowed amount is at
+    /// least `threshold` (admin-only). Each co-signer carries a stake weight; the signers'
+    /// weights must sum to `required_weight`, so equal weights give K-of-N. A zero
+    /// `threshold` turns co-signing off.
+    pub fn set_relayer_cosign(
+        ctx: Context<SetRelayerCosign>,
+        threshold: u64,
+        required_weight: u64,
+        cosigners: Vec<Pubkey>,
+        weights: Vec<u64>,
+    ) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::SetRelayerCosign {
+                threshold,
+                required_weight,
+                cosigners: cosigners.clone(),
+                weights: weights.clone(),
+            },
+        )?;
         require!(
-            cfg.relayer_fee_bps <= RELAYER_FEE_CAP_BPS,
-            ErrorCode::RelayerFeeTooHigh
+            cosigners.len() == weights.len() && cosigners.len() <= MAX_RELAYER_COSIGNERS,
+            ErrorCode::InvalidRelayerCosign
         );
-
-        // Compute fees (use hub-configured bps, and allow skipping via flags)
-        require!(amount > 0, ErrorCode::ZeroAmount);
-        let proto_fee = if is_protocol_fee {
-            ((amount as u128) * (cfg.protocol_fee_bps as u128) / 10_000u128) as u64
-        } else {
-            0
-        };
-        let relayer_fee = if is_relayer_fee {
-            ((amount as u128) * (cfg.relayer_fee_bps as u128) / 10_000u128) as u64
-        } else {
-            0
+        let mint = ctx.accounts.mint.key();
+        let mut cosign = RelayerCosign {
+            mint,
+            threshold,
+            required_weight,
+            cosigners_len: cosigners.len() as u8,
+            cosigners: [Pubkey::default(); MAX_RELAYER_COSIGNERS],
+            weights: [0; MAX_RELAYER_COSIGNERS],
+            bump: 0,
         };
-        let total_fees = proto_fee
-            .checked_add(relayer_fee)
-            .ok_or(ErrorCode::MathOverflow)?;
-        require!(total_fees <= amount, ErrorCode::FeesExceedAmount);
-        let net_amount = amount - total_fees;
-        require!(net_amount > 0, ErrorCode::ZeroAmount);
+        cosign.cosigners[..cosigners.len()].copy_from_slice(&cosigners);
+        cosign.weights[..weights.len()].copy_from_slice(&weights);
+        require!(cosign.is_valid(), ErrorCode::InvalidRelayerCosign);
 
-        // Transfer fees to vaults or relayer
-        // Protocol fee -> hub_protocol_fee_vault (PDA)
-        // Validate vault PDAs are correct. The token accounts provided must have
-        // their authority (owner field) set to the corresponding PDA and the
-        // account data must be owned by the SPL Token program.
-        let (expected_proto_vault, _pbump) = Pubkey::find_program_address(
-            &[b"hub_protocol_vault", &ctx.accounts.mint.key().to_bytes()],
-            ctx.program_id,
-        );
-        require_keys_eq!(
-            expected_proto_vault,
-            ctx.accounts.hub_protocol_vault.key(),
-            ErrorCode::InvalidVaultPda
-        );
+        let (expected, bump) =
+            Pubkey::find_program_address(&[b"relayer_cosign", mint.as_ref()], ctx.program_id);
+        let cosign_ai = ctx.accounts.relayer_cosign.to_account_info();
         require_keys_eq!(
-            ctx.accounts.hub_protocol_vault.owner,
-            expected_proto_vault,
-            ErrorCode::InvalidVaultOwner
+            cosign_ai.key(),
+            expected,
+            ErrorCode::InvalidRelayerCosignAccount
         );
+        if cosign_ai.owner != ctx.program_id {
+            create_pda_account(
+                &ctx.accounts.authority.to_account_info(),
+                &cosign_ai,
+                &ctx.accounts.system_program.to_account_info(),
+                RELAYER_COSIGN_SPACE,
+                &[b"relayer_cosign", mint.as_ref(), &[bump]],
+                ctx.program_id,
+            )?;
+        }
+        cosign.bump = bump;
+        cosign.try_serialize(&mut &mut cosign_ai.try_borrow_mut_data()?[..])?;
+        emit!(RelayerCosignUpdated {
+            mint,
+            threshold,
+            required_weight,
+            cosigners,
+            weights,
+        });
+        Ok(())
+    }
+
+    /// Carry amounts of `mint` on the route to/from `chain_id` in the canonical 18-decimal
+    /// representation (`normalize`), or in raw units (admin-only).
+    pub fn set_route_decimals(
+        ctx: Context<SetRouteDecimals>,
+        chain_id: u64,
+        normalize: bool,
+    ) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::SetRouteDecimals {
+                chain_id,
+                normalize,
+            },
+        )?;
+        let mint = ctx.accounts.mint.key();
+        let decimals = ctx.accounts.mint.decimals;
         require!(
-            ctx.accounts.hub_protocol_vault.to_account_info().owner == &token::ID,
-            ErrorCode::InvalidTokenProgram
+            decimals <= amount::CANONICAL_DECIMALS,
+            ErrorCode::UnsupportedDecimals
         );
-        let (expected_relayer_vault, _rbump) = Pubkey::find_program_address(
-            &[b"hub_relayer_vault", &ctx.accounts.mint.key().to_bytes()],
+        let (expected, bump) = Pubkey::find_program_address(
+            &[b"route_decimals", &chain_id.to_le_bytes(), mint.as_ref()],
             ctx.program_id,
         );
-        require_keys_eq!(
-            expected_relayer_vault,
-            ctx.accounts.hub_relayer_vault.key(),
-            ErrorCode::InvalidVaultPda
-        );
-        require_keys_eq!(
-            ctx.accounts.hub_relayer_vault.owner,
-            expected_relayer_vault,
-            ErrorCode::InvalidVaultOwner
-        );
-        require!(
-            ctx.accounts.hub_relayer_vault.to_account_info().owner == &token::ID,
-            ErrorCode::InvalidTokenProgram
-        );
-        if proto_fee > 0 {
-            token::transfer(
-                CpiContext::new(
-                    ctx.accounts.token_program.to_account_info(),
-                    token::Transfer {
-                        from: ctx.accounts.from.to_account_info(),
-                        to: ctx.accounts.hub_protocol_vault.to_account_info(),
-                        authority: ctx.accounts.user.to_account_info(),
-                    },
-                ),
-                proto_fee,
+        let route_ai = ctx.accounts.route_decimals.to_account_info();
+        require_keys_eq!(route_ai.key(), expected, ErrorCode::InvalidRouteDecimals);
+        if route_ai.owner != ctx.program_id {
+            create_pda_account(
+                &ctx.accounts.authority.to_account_info(),
+                &route_ai,
+                &ctx.accounts.system_program.to_account_info(),
+                ROUTE_DECIMALS_SPACE,
+                &[
+                    b"route_decimals",
+                    &chain_id.to_le_bytes(),
+                    mint.as_ref(),
+                    &[bump],
+                ],
+                ctx.program_id,
             )?;
         }
-
-        // Relayer fee -> direct payout or hub_relayer_vault
-        if relayer_fee > 0 {
-            if spoke.direct_relayer_payout || cfg.direct_relayer_payout_default {
-                // Ensure relayer token account belongs to configured relayer pubkey
-                require!(
-                    ctx.accounts.relayer_token_account.owner == cfg.relayer_pubkey,
-                    ErrorCode::Unauthorized
-                );
-                token::transfer(
-                    CpiContext::new(
-                        ctx.accounts.token_program.to_account_info(),
-                        token::Transfer {
-                            from: ctx.accounts.from.to_account_info(),
-                            to: ctx.accounts.relayer_token_account.to_account_info(),
-                            authority: ctx.accounts.user.to_account_info(),
-                        },
-                    ),
-                    relayer_fee,
-                )?;
-            } else {
-                token::transfer(
-                    CpiContext::new(
-                        ctx.accounts.token_program.to_account_info(),
-                        token::Transfer {
-                            from: ctx.accounts.from.to_account_info(),
-                            to: ctx.accounts.hub_relayer_vault.to_account_info(),
-                            authority: ctx.accounts.user.to_account_info(),
-                        },
-                    ),
-                    relayer_fee,
-                )?;
-            }
+        RouteDecimals {
+            chain_id,
+            mint,
+            decimals,
+            normalize,
+            bump,
         }
+        .try_serialize(&mut &mut route_ai.try_borrow_mut_data()?[..])?;
+        emit!(RouteDecimalsUpdated {
+            chain_id,
+            mint,
+            decimals,
+            normalize,
+        });
+        Ok(())
+    }
 
-        // Transfer net amount to adapter target token account
-        if net_amount > 0 {
-            token::transfer(
-                CpiContext::new(
-                    ctx.accounts.token_program.to_account_info(),
-                    token::Transfer {
-                        from: ctx.accounts.from.to_account_info(),
-                        to: ctx.accounts.adapter_target_token_account.to_account_info(),
-                        authority: ctx.accounts.user.to_account_info(),
-                    },
-                ),
-                net_amount,
+    /// Set the reward crank_finalize_message_v1 pays per message in `mint` base units, and
+    /// the total it may still pay out of hub_protocol_vault (admin-only). `budget` replaces
+    /// the remaining budget; 0 stops rewards.
+    pub fn set_crank_reward(ctx: Context<SetCrankReward>, amount: u64, budget: u64) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::SetCrankReward { amount, budget },
+        )?;
+        let mint = ctx.accounts.mint.key();
+        let (expected, bump) =
+            Pubkey::find_program_address(&[CRANK_REWARD_SEED, mint.as_ref()], ctx.program_id);
+        let reward_ai = ctx.accounts.crank_reward.to_account_info();
+        require_keys_eq!(reward_ai.key(), expected, ErrorCode::InvalidCrankReward);
+        if reward_ai.owner != ctx.program_id {
+            create_pda_account(
+                &ctx.accounts.authority.to_account_info(),
+                &reward_ai,
+                &ctx.accounts.system_program.to_account_info(),
+                CRANK_REWARD_SPACE,
+                &[CRANK_REWARD_SEED, mint.as_ref(), &[bump]],
+                ctx.program_id,
             )?;
         }
+        CrankReward {
+            mint,
+            amount,
+            budget,
+            bump,
+        }
+        .try_serialize(&mut &mut reward_ai.try_borrow_mut_data()?[..])?;
+        emit!(CrankRewardUpdated {
+            mint,
+            amount,
+            budget,
+        });
+        Ok(())
+    }
 
-        // CPI passthrough to adapter omitted in Phase 1 (TODO: add adapter CPI with explicit account layout)
-
-        emit!(Forwarded {
-            user: ctx.accounts.user.key(),
+    /// Pay the caller's vested relayer fees for `mint` out of hub_relayer_vault.
+    pub fn relayer_claim_fees(ctx: Context<RelayerClaimFees>) -> Result<()> {
+        ctx.accounts
+            .config
+            .require_family_active(INSTRUCTION_FAMILY_WITHDRAW)?;
+        require!(!ctx.accounts.config.killed, ErrorCode::GlobalKilled);
+        let mint = ctx.accounts.mint.key();
+        let bump = verify_hub_vault(
+            &ctx.accounts.hub_relayer_vault,
+            b"hub_relayer_vault",
+            &mint,
+            ctx.program_id,
+        )?;
+        let vesting = &mut ctx.accounts.relayer_vesting;
+        vesting.checkpoint(time::now(ctx.remaining_accounts, ctx.program_id)?.slot);
+        let amount = std::mem::take(&mut vesting.unlocked);
+        require!(amount > 0, ErrorCode::NothingVested);
+        vesting.claimed_total = vesting
+            .claimed_total
+            .checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let still_locked = vesting.locked;
+        let signer_seeds: &[&[&[u8]]] = &[&[b"hub_relayer_vault", mint.as_ref(), &[bump]]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                token_cpi_program(&ctx.accounts.token_program)?,
+                token::Transfer {
+                    from: ctx.accounts.hub_relayer_vault.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                    authority: ctx.accounts.hub_relayer_vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+        emit!(RelayerFeesClaimed {
             relayer: ctx.accounts.relayer.key(),
-            spoke_id,
-            adapter_program: spoke.adapter_program,
+            mint,
+            amount,
+            still_locked,
+        });
+        Ok(())
+    }
+
+    /// Emergency override: make a relayer's entire locked balance claimable now (admin-only).
+    pub fn unlock_relayer_vesting(ctx: Context<UnlockRelayerVesting>) -> Result<()> {
+        ctx.accounts
+            .config
+            .require_family_active(INSTRUCTION_FAMILY_WITHDRAW)?;
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::UnlockRelayerVesting {},
+        )?;
+        let vesting = &mut ctx.accounts.relayer_vesting;
+        let amount = vesting.unlock_all();
+        emit!(RelayerVestingUnlocked {
+            relayer: vesting.relayer,
+            mint: vesting.mint,
             amount,
-            protocol_fee: proto_fee,
-            relayer_fee,
-            net_amount,
-            dst_domain,
-            message_account: ctx.accounts.message_account.key(),
         });
+        Ok(())
+    }
 
+    /// Create an (empty) relayer allowlist for a spoke (admin-only). While the list is
+    /// non-empty, forward_via_spoke on that spoke only accepts the listed relayers (or admin).
+    pub fn create_spoke_relayers(ctx: Context<CreateSpokeRelayers>, spoke_id: u32) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::CreateSpokeRelayers { spoke_id },
+        )?;
+        let list = &mut ctx.accounts.spoke_relayers;
+        list.spoke_id = spoke_id;
+        list.relayers_len = 0;
+        list.relayers = [Pubkey::default(); MAX_SPOKE_RELAYERS];
+        list.bump = ctx.bumps.get("spoke_relayers").copied().unwrap();
         Ok(())
     }
 
-    /// Destination finalize path (stateless): mark message replay and emit telemetry.
-    /// No token movement. Creates a minimal 1-byte PDA at seeds (b"replay", message_hash) owned by this program.
-    #[allow(clippy::too_many_arguments)]
-    pub fn finalize_message_v1(
-        ctx: Context<FinalizeMessageV1>,
-        message_hash: [u8; 32],
-        src_chain_id: u64,
-        dst_chain_id: u64,
-        forwarded_amount: u64,
-        nonce: u64,
-        payload_hash: [u8; 32],
-        src_adapter: Pubkey,
-        asset_mint: Pubkey,
-        _initiator: Pubkey,
+    /// Create the (empty) protocol fee split table (admin-only).
+    pub fn initialize_fee_split(ctx: Context<InitializeFeeSplit>) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::InitializeFeeSplit {},
+        )?;
+        let split = &mut ctx.accounts.fee_split;
+        split.recipients_len = 0;
+        split.recipients = [Pubkey::default(); MAX_FEE_SPLIT_RECIPIENTS];
+        split.bps = [0; MAX_FEE_SPLIT_RECIPIENTS];
+        split.bump = ctx.bumps.get("fee_split").copied().unwrap();
+        Ok(())
+    }
+
+    /// Replace the protocol fee split (admin-only). Shares must sum to 10_000 bps; an empty
+    /// table sends the whole protocol fee to `fee_recipient_ata` again.
+    pub fn set_fee_split(
+        ctx: Context<SetFeeSplit>,
+        recipients: Vec<Pubkey>,
+        bps: Vec<u16>,
     ) -> Result<()> {
-        // Build canonical message hash matching source-leg schema
-        let src_adapter_32 = src_adapter.to_bytes();
-        let recipient_32 = [0u8; 32];
-        let asset_32 = asset_mint.to_bytes();
-        let mut amount_be = [0u8; 32];
-        amount_be[16..].copy_from_slice(&(forwarded_amount as u128).to_be_bytes());
-        let computed_hash = message_hash_be(
-            src_chain_id,
-            src_adapter_32,
-            recipient_32,
-            asset_32,
-            amount_be,
-            payload_hash,
-            nonce,
-            dst_chain_id,
-        );
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::SetFeeSplit {
+                recipients: recipients.clone(),
+                bps: bps.clone(),
+            },
+        )?;
+        check_fee_split(&recipients, &bps)?;
+        let split = &mut ctx.accounts.fee_split;
+        split.recipients = [Pubkey::default(); MAX_FEE_SPLIT_RECIPIENTS];
+        split.bps = [0; MAX_FEE_SPLIT_RECIPIENTS];
+        split.recipients[..recipients.len()].copy_from_slice(&recipients);
+        split.bps[..bps.len()].copy_from_slice(&bps);
+        split.recipients_len = recipients.len() as u8;
+        Ok(())
+    }
 
-        // Chain id width guard to avoid truncation when emitting u16
+    pub fn add_spoke_relayer(ctx: Context<SpokeRelayersAdmin>, relayer: Pubkey) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::AddSpokeRelayer { relayer },
+        )?;
+        let list = &mut ctx.accounts.spoke_relayers;
+        let len = list.relayers_len as usize;
         require!(
-            src_chain_id <= u16::MAX as u64 && dst_chain_id <= u16::MAX as u64,
-            ErrorCode::ChainIdOutOfRange
+            !list.relayers[..len].contains(&relayer),
+            ErrorCode::RelayerAlreadyListed
         );
+        require!(len < MAX_SPOKE_RELAYERS, ErrorCode::AdapterListFull);
+        list.relayers[len] = relayer;
+        list.relayers_len += 1;
+        emit!(SpokeRelayerAdded {
+            spoke_id: list.spoke_id,
+            relayer,
+        });
+        Ok(())
+    }
 
-        // Ensure router is not paused at destination finalize
-        require!(!ctx.accounts.config.paused, ErrorCode::Paused);
+    pub fn remove_spoke_relayer(ctx: Context<SpokeRelayersAdmin>, relayer: Pubkey) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::RemoveSpokeRelayer { relayer },
+        )?;
+        let list = &mut ctx.accounts.spoke_relayers;
+        let len = list.relayers_len as usize;
+        let i = list.relayers[..len]
+            .iter()
+            .position(|r| *r == relayer)
+            .ok_or_else(|| error!(ErrorCode::RelayerNotListed))?;
+        let last = len - 1;
+        list.relayers[i] = list.relayers[last];
+        list.relayers[last] = Pubkey::default();
+        list.relayers_len -= 1;
+        emit!(SpokeRelayerRemoved {
+            spoke_id: list.spoke_id,
+            relayer,
+        });
+        Ok(())
+    }
 
-        // Auth gate: make sure the declared source adapter is in the configured allowlist.
-        // This prevents arbitrary callers from forging finalize events for adapters that are
-        // not known/approved by the router config.
-        require!(
-            is_allowed_adapter_cfg(&ctx.accounts.config, &src_adapter),
-            ErrorCode::AdapterNotAllowed
-        );
+    /// Drop a spoke's relayer allowlist entirely (admin-only); the spoke reverts to the
+    /// global relayer check.
+    pub fn close_spoke_relayers(ctx: Context<CloseSpokeRelayers>) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::CloseSpokeRelayers {},
+        )?;
+        Ok(())
+    }
 
-        // 1) Hash parity enforcement
-        require!(computed_hash == message_hash, ErrorCode::HashMismatch);
+    /// Create the (empty) mint allowlist (admin-only). With `accept_any_token` off, both
+    /// transfer paths accept `allowed_token_mint` plus any mint listed here.
+    pub fn initialize_mint_allowlist(ctx: Context<InitializeMintAllowlist>) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::InitializeMintAllowlist {},
+        )?;
+        let list = &mut ctx.accounts.mint_allowlist;
+        list.mints_len = 0;
+        list.mints = [Pubkey::default(); MAX_ALLOWED_MINTS];
+        list.bump = ctx.bumps.get("mint_allowlist").copied().unwrap();
+        Ok(())
+    }
 
-        // 2) Manual replay PDA enforcement + stateful replay guard
-        // Seeds and expected PDA
-        let seeds: &[&[u8]] = &[b"replay", &message_hash];
-        let (expected_replay, bump) = Pubkey::find_program_address(seeds, ctx.program_id);
-        let replay_ai = &ctx.accounts.replay.to_account_info();
-        // Ensure provided account matches seeds
-        require_keys_eq!(
-            replay_ai.key(),
-            expected_replay,
-            ErrorCode::InvalidReplayPda
-        );
-
-        // (Verbose diagnostics removed post-verification; keeping minimal branch logs below.)
-        if replay_ai.data_len() == 0 {
-            // First use: create PDA, write discriminator + processed=1
-            let space: usize = Replay::DISCRIMINATOR.len() + 1; // 8 + 1
-            let lamports = Rent::get()?.minimum_balance(space);
-            let create_ix = system_instruction::create_account(
-                &ctx.accounts.relayer.key(),
-                &expected_replay,
-                lamports,
-                space as u64,
-                ctx.program_id,
-            );
-            invoke_signed(
-                &create_ix,
-                &[
-                    ctx.accounts.relayer.to_account_info(),
-                    replay_ai.clone(),
-                    ctx.accounts.system_program.to_account_info(),
-                ],
-                &[&[b"replay", &message_hash, &[bump]]],
-            )?;
-            let mut data = replay_ai.try_borrow_mut_data()?;
-            data[0..8].copy_from_slice(&Replay::DISCRIMINATOR);
-            data[8] = 1u8; // processed
-                           // Minimal trace for testing (can be removed later)
-            msg!("replay:create processed=1");
-        } else {
-            // Subsequent use: verify owner, layout, and processed flag
-            require_keys_eq!(
-                *replay_ai.owner,
-                *ctx.program_id,
-                ErrorCode::InvalidReplayOwner
-            );
-            let data = replay_ai.try_borrow_data()?;
-            // Need at least discriminator (8) + 1 byte flag
-            require!(
-                data.len() > Replay::DISCRIMINATOR.len(),
-                ErrorCode::ReplayAccountTooSmall
-            );
-            require!(
-                data[0..8] == Replay::DISCRIMINATOR,
-                ErrorCode::ReplayAccountTooSmall
-            );
-            // If already processed -> replay
-            if data[8] == 1 {
-                return err!(ErrorCode::ReplayAlreadyProcessed);
-            }
-            drop(data);
-            let mut data_mut = replay_ai.try_borrow_mut_data()?;
-            data_mut[8] = 1u8;
-            msg!("replay:mark processed=1");
-        }
+    pub fn add_allowed_mint(ctx: Context<MintAllowlistAdmin>, mint: Pubkey) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::AddAllowedMint { mint },
+        )?;
+        ctx.accounts.mint_allowlist.add(mint)?;
+        emit!(AllowedMintAdded { mint });
+        Ok(())
+    }
 
-        // Emit telemetry event (no fee movement in v1)
-        emit!(FeeAppliedDest {
-            message_hash,
-            src_chain_id: src_chain_id as u16,
-            dst_chain_id: dst_chain_id as u16,
-            router: crate::ID,
-            asset: asset_mint,
-            amount: forwarded_amount,
-            protocol_bps: 0,
-            lp_bps: 0,
-            collector: ctx.accounts.config.fee_recipient,
-            applied_at: Clock::get()?.unix_timestamp as u64,
-        });
+    pub fn remove_allowed_mint(ctx: Context<MintAllowlistAdmin>, mint: Pubkey) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::RemoveAllowedMint { mint },
+        )?;
+        let list = &mut ctx.accounts.mint_allowlist;
+        let len = list.mints_len as usize;
+        let i = list.mints[..len]
+            .iter()
+            .position(|m| *m == mint)
+            .ok_or_else(|| error!(ErrorCode::MintNotAllowed))?;
+        let last = len - 1;
+        list.mints[i] = list.mints[last];
+        list.mints[last] = Pubkey::default();
+        list.mints_len -= 1;
+        emit!(AllowedMintRemoved { mint });
+        Ok(())
+    }
 
+    /// Create the (empty) set of EVM addresses whose bridge intents forward_with_intent
+    /// accepts (admin-only).
+    pub fn initialize_intent_signers(ctx: Context<InitializeIntentSigners>) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::InitializeIntentSigners {},
+        )?;
+        let set = &mut ctx.accounts.intent_signers;
+        set.signers_len = 0;
+        set.signers = [[0u8; 20]; MAX_INTENT_SIGNERS];
+        set.bump = ctx.bumps.get("intent_signers").copied().unwrap();
         Ok(())
     }
-}
 
-// ------------ Accounts / Config / Events / Errors ------------
-#[account]
-pub struct Config {
-    pub admin: Pubkey,
-    pub fee_recipient: Pubkey,
-    pub src_chain_id: u64,
-    pub relayer_fee_bps: u16,
-    pub protocol_fee_bps: u16,
-    pub relayer_pubkey: Pubkey,
-    pub accept_any_token: bool,
-    pub allowed_token_mint: Pubkey,
-    pub direct_relayer_payout_default: bool,
-    pub min_forward_amount: u64,
-    pub adapters_len: u8,
-    pub adapters: [Pubkey; 8],
-    pub paused: bool,
-    pub bump: u8,
-}
+    pub fn add_intent_signer(ctx: Context<IntentSignersAdmin>, signer: [u8; 20]) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::AddIntentSigner { signer },
+        )?;
+        let set = &mut ctx.accounts.intent_signers;
+        let len = set.signers_len as usize;
+        require!(
+            !set.signers[..len].contains(&signer),
+            ErrorCode::IntentSignerAlreadyRegistered
+        );
+        require!(len < MAX_INTENT_SIGNERS, ErrorCode::AdapterListFull);
+        set.signers[len] = signer;
+        set.signers_len += 1;
+        emit!(IntentSignerAdded { signer });
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct InitializeConfig<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    #[account(
-        init,
-        payer = payer,
-        // space calc: discriminator(8) + admin(32) + fee_recipient(32) + src_chain_id(8) + relayer_fee_bps(2)
-        // + protocol_fee_bps(2) + relayer_pubkey(32) + accept_any_token(1) + allowed_token_mint(32)
-        // + direct_relayer_payout_default(1) + min_forward_amount(8) + adapters_len(1) + adapters(32*8) + paused(1) + bump(1)
-        space = 8 + 32 + 32 + 8 + 2 + 2 + 32 + 1 + 32 + 1 + 8 + 1 + (32*8) + 1 + 1,
-        seeds = [b"zpx_config"],
-        bump
-    )]
-    pub config: Account<'info, Config>,
-    pub system_program: Program<'info, System>,
-}
+    pub fn remove_intent_signer(ctx: Context<IntentSignersAdmin>, signer: [u8; 20]) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::RemoveIntentSigner { signer },
+        )?;
+        let set = &mut ctx.accounts.intent_signers;
+        let len = set.signers_len as usize;
+        let i = set.signers[..len]
+            .iter()
+            .position(|s| *s == signer)
+            .ok_or_else(|| error!(ErrorCode::IntentSignerNotRegistered))?;
+        let last = len - 1;
+        set.signers[i] = set.signers[last];
+        set.signers[last] = [0u8; 20];
+        set.signers_len -= 1;
+        emit!(IntentSignerRemoved { signer });
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct AdminWithdraw<'info> {
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    #[account(mut, seeds=[b"zpx_config"], bump=config.bump)]
-    pub config: Account<'info, Config>,
-    #[account(mut)]
-    pub hub_protocol_vault: Account<'info, TokenAccount>,
-    pub mint: Account<'info, Mint>,
-    #[account(mut, constraint = destination.mint == mint.key())]
-    pub destination: Account<'info, TokenAccount>,
-    pub token_program: Program<'info, Token>,
-}
+    /// Adapter addition, step 1 (admin-only): record `adapter` as pending. An allowlisted
+    /// adapter becomes a CPI target, so additions always go through the timelock; it can be
+    /// activated ADAPTER_ACTIVATION_DELAY_SLOTS later, and the guardian can veto it meanwhile.
+    pub fn propose_adapter(ctx: Context<ProposeAdapter>, adapter: Pubkey) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::ProposeAdapter { adapter },
+        )?;
+        let cfg = &ctx.accounts.config;
+        require!(adapter != Pubkey::default(), ErrorCode::AdapterNotAllowed);
+        require!(
+            !is_allowed_adapter_cfg(cfg, &adapter)
+                && ctx.accounts.adapter_entry.owner != ctx.program_id,
+            ErrorCode::AdapterAlreadyExists
+        );
+        let activation_slot = time::now(ctx.remaining_accounts, ctx.program_id)?
+            .slot
+            .checked_add(ADAPTER_ACTIVATION_DELAY_SLOTS)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.adapter = adapter;
+        proposal.proposer = ctx.accounts.authority.key();
+        proposal.activation_slot = activation_slot;
+        proposal.bump = ctx.bumps.get("proposal").copied().unwrap();
+        emit!(AdapterProposed {
+            admin: proposal.proposer,
+            program: adapter,
+            activation_slot,
+        });
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct InitializeRegistry<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    #[account(
-        init,
-        payer = payer,
-        space = 8 + 1 + (112 * MAX_SPOKES) + 1,
-        seeds = [b"hub_registry"],
-        bump
-    )]
-    pub registry: Account<'info, Registry>,
-    pub system_program: Program<'info, System>,
-}
+    /// Adapter addition, step 2 (admin-only, after the timelock): allowlist the proposed
+    /// adapter by creating its AdapterEntry PDA, and close the proposal.
+    pub fn activate_adapter(ctx: Context<ActivateAdapter>, adapter: Pubkey) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::ActivateAdapter { adapter },
+        )?;
+        let activation_slot = ctx.accounts.proposal.activation_slot;
+        let slot = time::now(ctx.remaining_accounts, ctx.program_id)?.slot;
+        require!(slot >= activation_slot, ErrorCode::AdapterActivationLocked);
+        let cfg = &mut ctx.accounts.config;
+        require!(
+            !is_allowed_adapter_cfg(cfg, &adapter),
+            ErrorCode::AdapterAlreadyExists
+        );
+        create_adapter_entry(
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.adapter_entry.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &adapter,
+            slot,
+            ctx.program_id,
+        )?;
+        bump_config_version(ctx.remaining_accounts, ctx.program_id, cfg, false)?;
+        emit!(AdapterActivated {
+            admin: cfg.admin,
+            program: adapter,
+            activation_slot,
+            slot,
+        });
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct UpdateConfig<'info> {
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    #[account(
-        seeds=[b"zpx_config"],
-        bump=config.bump,
-        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
-    )]
-    pub config: Account<'info, Config>,
-}
+    /// Drop a pending adapter proposal (admin or guardian); rent goes back to the proposer.
+    pub fn cancel_adapter_proposal(
+        ctx: Context<CancelAdapterProposal>,
+        adapter: Pubkey,
+    ) -> Result<()> {
+        let cfg = &ctx.accounts.config;
+        let authority = ctx.accounts.authority.key();
+        require!(
+            authority == cfg.admin
+                || (cfg.guardian != Pubkey::default() && authority == cfg.guardian),
+            ErrorCode::Unauthorized
+        );
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            cfg,
+            authority,
+            &instruction::CancelAdapterProposal { adapter },
+        )?;
+        emit!(AdapterProposalCancelled {
+            authority,
+            program: adapter,
+        });
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct AdminConfig<'info> {
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    #[account(mut, seeds=[b"zpx_config"], bump=config.bump)]
-    pub config: Account<'info, Config>,
-}
+    /// Drop an adapter from the allowlist (admin-only, immediate): close its AdapterEntry,
+    /// or remove it from the legacy Config.adapters array if it was never migrated.
+    pub fn remove_adapter(
+        ctx: Context<RemoveAdapter>,
+        adapter: Pubkey,
+        idempotency_key: Option<u64>,
+    ) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::RemoveAdapter {
+                adapter,
+                idempotency_key,
+            },
+        )?;
+        let cfg = &mut ctx.accounts.config;
+        if cfg.replayed_admin_op(idempotency_key)? {
+            return Ok(());
+        }
+        let entry = ctx.accounts.adapter_entry.to_account_info();
+        if entry.owner == ctx.program_id {
+            close_program_account(&entry, &ctx.accounts.authority.to_account_info())?;
+        } else {
+            require!(
+                remove_legacy_adapter(cfg, &adapter),
+                ErrorCode::AdapterNotAllowed
+            );
+        }
+        bump_config_version(ctx.remaining_accounts, ctx.program_id, cfg, false)?;
+        emit!(AdapterRemoved {
+            admin: cfg.admin,
+            program: adapter
+        });
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct CreateSpoke<'info> {
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    #[account(seeds=[b"zpx_config"], bump=config.bump)]
-    pub config: Account<'info, Config>,
-    #[account(mut, seeds=[b"hub_registry"], bump=registry.bump)]
-    pub registry: Account<'info, Registry>,
-    /// CHECK: admin PDA (optional)
-    pub admin: UncheckedAccount<'info>,
-    pub system_program: Program<'info, System>,
-}
+    /// Move one adapter from the legacy Config.adapters array to its AdapterEntry PDA
+    /// (admin-only). It stays allowlisted throughout; no timelock applies.
+    pub fn migrate_adapter(ctx: Context<MigrateAdapter>, adapter: Pubkey) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::MigrateAdapter { adapter },
+        )?;
+        let cfg = &mut ctx.accounts.config;
+        require!(
+            remove_legacy_adapter(cfg, &adapter),
+            ErrorCode::AdapterNotLegacy
+        );
+        let slot = Clock::get()?.slot;
+        create_adapter_entry(
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.adapter_entry.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &adapter,
+            slot,
+            ctx.program_id,
+        )?;
+        bump_config_version(ctx.remaining_accounts, ctx.program_id, cfg, false)?;
+        emit!(AdapterMigrated {
+            admin: cfg.admin,
+            program: adapter,
+            legacy_left: cfg.adapters_len,
+        });
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct UpdateSpoke<'info> {
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    #[account(seeds=[b"zpx_config"], bump=config.bump)]
-    pub config: Account<'info, Config>,
-    #[account(mut, seeds=[b"hub_registry"], bump=registry.bump)]
-    pub registry: Account<'info, Registry>,
-    /// CHECK: admin PDA (optional)
-    pub admin: UncheckedAccount<'info>,
-}
+    /// Thin source-leg entrypoint (no vault logic). Pull -> skim -> forward -> emit.
+    /// Remaining accounts when `config.usd_limits_enabled`: [oracle_config, pyth_price],
+    /// followed by one recipient ATA per share when a fee split is configured; anything after
+    /// that is forwarded to the bridge hook.
+    pub fn universal_bridge_transfer<'info>(
+        ctx: Context<'_, '_, '_, 'info, UniversalBridgeTransfer<'info>>,
+        amount: u64,
+        protocol_fee: u64,
+        relayer_fee: u64,
+        payload: Vec<u8>,
+        dst_chain_id: u64,
+        nonce: u64,
+        store_preimage: bool,
+        skip_receipt: bool,
+    ) -> Result<()> {
+        ctx.accounts
+            .config
+            .require_family_active(INSTRUCTION_FAMILY_BRIDGE)?;
+        let cfg = &ctx.accounts.config;
+        // Chain id width guard to avoid silent truncation when emitting u16
+        require!(
+            zpx_chains::fits_wire(cfg.src_chain_id) && zpx_chains::fits_wire(dst_chain_id),
+            ErrorCode::ChainIdOutOfRange
+        );
+        // Defensive: correct token program
+        assert_token_program(&ctx.accounts.token_program)?;
+        require!(!cfg.paused, ErrorCode::Paused);
+        require!(cfg.src_chain_id != 0, ErrorCode::SrcChainNotSet);
+        validate_common(amount, payload.len(), cfg.paused, cfg.src_chain_id)?;
+        validate_payload_len(payload.len())?;
+        let compression = payload_compression(&payload)?;
+        require_mint_active(
+            &ctx.accounts.mint_pause,
+            &ctx.accounts.mint.key(),
+            ctx.program_id,
+        )?;
+        require_mint_allowed(
+            &ctx.accounts.mint_allowlist,
+            cfg,
+            &ctx.accounts.mint.key(),
+            ctx.program_id,
+        )?;
+        if cfg.usd_limits_enabled {
+            enforce_usd_min_forward(
+                ctx.remaining_accounts,
+                ctx.program_id,
+                &ctx.accounts.mint,
+                amount,
+            )?;
+        }
+        // Adapter allowlist: ensure target is allowed
+        require!(
+            adapter_allowed(
+                cfg,
+                ctx.remaining_accounts,
+                &ctx.accounts.target_adapter_program.key(),
+                ctx.program_id,
+            ),
+            ErrorCode::AdapterNotAllowed
+        );
+        let fee_policy = load_fee_policy(
+            &ctx.accounts.fee_policy,
+            &ctx.accounts.mint.key(),
+            dst_chain_id,
+            ctx.program_id,
+        )?;
+        let (forward_amount, total_fees) = match fee_policy {
+            Some(policy) => policy_fees(&policy, amount, protocol_fee, relayer_fee)?,
+            None => {
+                compute_fees_and_forward(amount, protocol_fee, relayer_fee, cfg.relayer_fee_bps)?
+            }
+        };
+        zpx_log!(
+            "fee_computed",
+            amount = amount,
+            protocol_fee = protocol_fee,
+            relayer_fee = relayer_fee,
+            forward_amount = forward_amount,
+        );
 
-#[derive(Accounts)]
-pub struct PauseSpoke<'info> {
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    #[account(seeds=[b"zpx_config"], bump=config.bump)]
-    pub config: Account<'info, Config>,
-    #[account(mut, seeds=[b"hub_registry"], bump=registry.bump)]
-    pub registry: Account<'info, Registry>,
-    /// CHECK: admin PDA (optional)
-    pub admin: UncheckedAccount<'info>,
-}
+        // In-flight accounting per (mint, dst_chain); enforces the optional exposure cap
+        let in_flight = &mut ctx.accounts.in_flight;
+        let previous_in_flight = in_flight.amount;
+        in_flight.amount =
+            in_flight_after_forward(previous_in_flight, forward_amount, in_flight.exposure_cap)?;
 
-#[derive(Accounts)]
-pub struct ForwardViaSpoke<'info> {
-    #[account(mut)]
-    pub user: Signer<'info>,
-    /// CHECK: relayer EOA invoking the forward
-    pub relayer: Signer<'info>,
-    pub mint: Account<'info, Mint>,
-    #[account(mut, constraint = from.owner == user.key(), constraint = from.mint == mint.key())]
-    pub from: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub hub_protocol_vault: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub hub_relayer_vault: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub relayer_token_account: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub adapter_target_token_account: Account<'info, TokenAccount>,
-    #[account(mut, seeds=[b"hub_registry"], bump=registry.bump)]
-    pub registry: Account<'info, Registry>,
-    #[account(seeds=[b"zpx_config"], bump=config.bump)]
-    pub config: Account<'info, Config>,
-    #[account(mut)]
-    pub message_account: UncheckedAccount<'info>,
-    pub token_program: Program<'info, Token>,
-}
+        // The previous recipient's ATA stays valid during the rotation grace window
+        let fee_recipient = ctx.accounts.fee_recipient_ata.owner;
+        let fee_recipient_epoch = ctx
+            .accounts
+            .fee_recipient_history
+            .accepting_epoch(&fee_recipient, Clock::get()?.slot)
+            .ok_or_else(|| error!(ErrorCode::InvalidFeeRecipientAta))?;
+        verify_fee_recipient_ata(
+            &fee_recipient,
+            &ctx.accounts.mint.key(),
+            &ctx.accounts.token_program.key(),
+            &ctx.accounts.fee_recipient_ata,
+        )?;
+        // A referring partner's rebate comes off the protocol fee before any split
+        let partner = load_partner(&ctx.accounts.referral, ctx.program_id)?;
+        let rebate = partner.as_ref().map_or(0, |p| p.rebate_of(protocol_fee));
+        // Optional protocol fee split; share ATAs follow the oracle accounts
+        let fee_split = load_fee_split(&ctx.accounts.fee_split, ctx.program_id)?;
+        let split_offset = if cfg.usd_limits_enabled { 2 } else { 0 };
+        // Fixed-size share buffer keeps the hot path free of heap allocations
+        let share_buf;
+        let split_shares: &[u64] = match &fee_split {
+            Some(split) => {
+                let n = split.recipients_len as usize;
+                require!(
+                    ctx.remaining_accounts.len() >= split_offset + n,
+                    ErrorCode::InvalidFeeRecipientAta
+                );
+                for (recipient, ata) in split.recipients[..n]
+                    .iter()
+                    .zip(&ctx.remaining_accounts[split_offset..split_offset + n])
+                {
+                    verify_fee_share_ata(recipient, &ctx.accounts.mint.key(), ata)?;
+                }
+                share_buf = fee_split_shares(protocol_fee - rebate, &split.bps[..n]);
+                &share_buf[..n]
+            }
+            None => &[],
+        };
 
-#[derive(Accounts)]
-pub struct UniversalBridgeTransfer<'info> {
-    #[account(mut)]
-    pub user: Signer<'info>,
-    pub mint: Account<'info, Mint>,
-    #[account(mut, constraint = from.owner == user.key(), constraint = from.mint == mint.key())]
+        // Canonical hashes; routes with a max age wrap the payload hash in a validity window
+        let hash_version = cfg.message_hash_version();
+        let validity = message_validity_for(ctx.remaining_accounts, dst_chain_id, ctx.program_id)?;
+        // Normalized routes carry the amount with 18 decimals
+        let amount_decimals = route_amount_decimals(
+            ctx.remaining_accounts,
+            dst_chain_id,
+            &ctx.accounts.mint.key(),
+            ctx.program_id,
+        )?;
+        let (payload_hash, msg_hash, global_route) = source_leg_hashes(
+            hash_version,
+            cfg.src_chain_id,
+            dst_chain_id,
+            &ctx.accounts.target_adapter_program.key(),
+            &ctx.accounts.mint.key(),
+            &ctx.accounts.user.key(),
+            forward_amount,
+            amount_decimals,
+            &payload,
+            nonce,
+            validity.as_ref(),
+        )?;
+        if let Some(decimals) = amount_decimals {
+            emit!(AmountNormalized {
+                message_hash: msg_hash,
+                mint: ctx.accounts.mint.key(),
+                amount: forward_amount,
+                decimals,
+                amount_e18: amount::to_canonical(forward_amount, decimals)
+                    .map_err(ErrorCode::from)?,
+            });
+        }
+        if store_preimage {
+            store_message_preimage(
+                &ctx.accounts.rent_payer.to_account_info(),
+                &ctx.accounts.message.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                ctx.program_id,
+                MessagePreimage {
+                    src_chain_id: cfg.src_chain_id,
+                    dst_chain_id,
+                    target_adapter: ctx.accounts.target_adapter_program.key(),
+                    mint: ctx.accounts.mint.key(),
+                    initiator: ctx.accounts.user.key(),
+                    forward_amount,
+                    payload_hash,
+                    nonce,
+                    message_hash: msg_hash,
+                    hash_version,
+                    bump: 0,
+                    amount_decimals,
+                },
+            )?;
+            record_rent(
+                &ctx.accounts.rent_ledger.to_account_info(),
+                ctx.program_id,
+                RENT_KIND_MESSAGE,
+                MESSAGE_PREIMAGE_SPACE,
+                true,
+            )?;
+        }
+        if !skip_receipt {
+            record_transfer_receipt(
+                &ctx.accounts.rent_payer.to_account_info(),
+                &ctx.accounts.user_index.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                &ctx.accounts.user.key(),
+                TransferReceipt {
+                    message_hash: msg_hash,
+                    slot: Clock::get()?.slot,
+                    amount: forward_amount,
+                    dst_chain_id,
+                },
+                ctx.program_id,
+            )?;
+        }
+
+        record_router_stats(
+            &ctx.accounts.router_stats,
+            ctx.program_id,
+            &ctx.accounts.mint.key(),
+            forward_amount,
+            STATS_KIND_INITIATED,
+        )?;
+        let rebate_accrued = match &partner {
+            Some(p) if rebate > 0 => Some(accrue_partner_rebate(
+                p,
+                rebate,
+                &ctx.accounts.partner_rebate,
+                &ctx.accounts.partner_rebate_vault,
+                &ctx.accounts.mint.to_account_info(),
+                &ctx.accounts.rent_payer.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                &ctx.accounts.token_program.to_account_info(),
+                ctx.program_id,
+            )?),
+            _ => None,
+        };
+
+        // Interactions: token CPIs only after all state is written
+        // Transfer: user -> fee_recipient (fees); split shares and the partner rebate replace
+        // parts of the protocol fee
+        let split_total: u64 = split_shares.iter().sum();
+        let single_fee = total_fees - split_total - rebate;
+        if single_fee > 0 {
+            token::transfer(
+                CpiContext::new(
+                    token_cpi_program(&ctx.accounts.token_program)?,
+                    token::Transfer {
+                        from: ctx.accounts.from.to_account_info(),
+                        to: ctx.accounts.fee_recipient_ata.to_account_info(),
+                        authority: ctx.accounts.user.to_account_info(),
+                    },
+                ),
+                single_fee,
+            )?;
+        }
+        for (share, ata) in split_shares
+            .iter()
+            .zip(&ctx.remaining_accounts[split_offset..])
+        {
+            if *share > 0 {
+                token::transfer(
+                    CpiContext::new(
+                        token_cpi_program(&ctx.accounts.token_program)?,
+                        token::Transfer {
+                            from: ctx.accounts.from.to_account_info(),
+                            to: ata.clone(),
+                            authority: ctx.accounts.user.to_account_info(),
+                        },
+                    ),
+                    *share,
+                )?;
+            }
+        }
+        if rebate_accrued.is_some() {
+            token::transfer(
+                CpiContext::new(
+                    token_cpi_program(&ctx.accounts.token_program)?,
+                    token::Transfer {
+                        from: ctx.accounts.from.to_account_info(),
+                        to: ctx.accounts.partner_rebate_vault.to_account_info(),
+                        authority: ctx.accounts.user.to_account_info(),
+                    },
+                ),
+                rebate,
+            )?;
+        }
+
+        // Transfer: user -> target (forward amount)
+        if forward_amount > 0 {
+            token::transfer(
+                CpiContext::new(
+                    token_cpi_program(&ctx.accounts.token_program)?,
+                    token::Transfer {
+                        from: ctx.accounts.from.to_account_info(),
+                        to: ctx.accounts.target_token_account.to_account_info(),
+                        authority: ctx.accounts.user.to_account_info(),
+                    },
+                ),
+                forward_amount,
+            )?;
+        }
+
+        // Events per EVM schema
+        emit!(BridgeInitiated {
+            route_id: [0u8; 32],
+            user: ctx.accounts.user.key(),
+            token: ctx.accounts.mint.key(),
+            target: ctx.accounts.target_adapter_program.key(),
+            forwarded_amount: forward_amount,
+            protocol_fee,
+            relayer_fee,
+            payload_hash,
+            src_chain_id: cfg.src_chain_id as u16, // EVM uses u16; store u64 but emit lower 16 bits
+            dst_chain_id: dst_chain_id as u16,
+            nonce,
+        });
+        emit!(UniversalBridgeInitiated {
+            route_id: [0u8; 32],
+            payload_hash,
+            message_hash: msg_hash,
+            global_route_id: global_route,
+            user: ctx.accounts.user.key(),
+            token: ctx.accounts.mint.key(),
+            target: ctx.accounts.target_adapter_program.key(),
+            forwarded_amount: forward_amount,
+            protocol_fee,
+            relayer_fee,
+            src_chain_id: cfg.src_chain_id as u16,
+            dst_chain_id: dst_chain_id as u16,
+            nonce,
+        });
+        if let Some(c) = compression {
+            emit!(PayloadCompressed {
+                message_hash: msg_hash,
+                payload_hash,
+                algorithm: c.algorithm,
+                compressed_len: c.compressed_len,
+                decompressed_len: c.decompressed_len,
+            });
+        }
+        if let Some(validity) = validity {
+            emit!(MessageValidityEmbedded {
+                message_hash: msg_hash,
+                payload_hash: keccak256(&[&payload]),
+                sent_at: validity.sent_at,
+                expires_at: validity.expires_at,
+            });
+        }
+        emit!(InFlightUpdated {
+            mint: ctx.accounts.mint.key(),
+            dst_chain_id,
+            previous: previous_in_flight,
+            current: ctx.accounts.in_flight.amount,
+            exposure_cap: ctx.accounts.in_flight.exposure_cap,
+        });
+        if total_fees > 0 {
+            emit!(FeeAppliedSource {
+                message_hash: msg_hash,
+                asset: ctx.accounts.mint.key(),
+                payer: ctx.accounts.user.key(),
+                target: ctx.accounts.target_adapter_program.key(),
+                protocol_fee,
+                relayer_fee,
+                fee_recipient,
+                applied_at: Clock::get()?.unix_timestamp as u64,
+            });
+            if cfg.feature_enabled(FEATURE_V2_EVENTS) {
+                emit!(FeeAppliedSourceV2 {
+                    message_hash: msg_hash,
+                    asset: ctx.accounts.mint.key(),
+                    payer: ctx.accounts.user.key(),
+                    target: ctx.accounts.target_adapter_program.key(),
+                    protocol_fee,
+                    relayer_fee,
+                    fee_recipient,
+                    applied_at: Clock::get()?.unix_timestamp as u64,
+                    zpx_fee_burned: 0,
+                    fee_recipient_epoch,
+                });
+                emit!(FeeAppliedSourceV3 {
+                    message_hash: msg_hash,
+                    asset: ctx.accounts.mint.key(),
+                    payer: ctx.accounts.user.key(),
+                    target: ctx.accounts.target_adapter_program.key(),
+                    protocol_fee,
+                    relayer_fee,
+                    fee_recipient,
+                    applied_at: Clock::get()?.unix_timestamp as u64,
+                    zpx_fee_burned: 0,
+                    fee_recipient_epoch,
+                    config_version: cfg.config_version,
+                });
+            }
+        }
+        if let Some(split) = &fee_split {
+            for (i, share) in split_shares.iter().enumerate() {
+                emit!(FeeSplitApplied {
+                    message_hash: msg_hash,
+                    asset: ctx.accounts.mint.key(),
+                    index: i as u8,
+                    recipient: split.recipients[i],
+                    bps: split.bps[i],
+                    amount: *share,
+                });
+            }
+        }
+        if let (Some(p), Some(accrued)) = (&partner, rebate_accrued) {
+            emit!(PartnerRebateAccrued {
+                partner_id: p.partner_id,
+                mint: ctx.accounts.mint.key(),
+                user: ctx.accounts.user.key(),
+                message_hash: msg_hash,
+                rebate,
+                accrued,
+            });
+        }
+        // Partner hook runs last, once every transfer and event of the bridge is final
+        invoke_bridge_hook(
+            cfg,
+            &ctx.accounts.bridge_hook_program.to_account_info(),
+            &ctx.accounts.bridge_hook_authority.to_account_info(),
+            ctx.bumps.get("bridge_hook_authority").copied().unwrap(),
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.mint.to_account_info(),
+            &ctx.remaining_accounts[split_offset + split_shares.len()..],
+            &BridgeHookData {
+                message_hash: msg_hash,
+                global_route_id: global_route,
+                payload_hash,
+                user: ctx.accounts.user.key(),
+                mint: ctx.accounts.mint.key(),
+                target: ctx.accounts.target_adapter_program.key(),
+                forwarded_amount: forward_amount,
+                protocol_fee,
+                relayer_fee,
+                src_chain_id: cfg.src_chain_id,
+                dst_chain_id,
+                nonce,
+            },
+        )?;
+        Ok(())
+    }
+
+    /// Source-leg variant of universal_bridge_transfer where the protocol fee is paid in
+    /// ZPX at the keeper-published rate (minus the configured discount) and burned, instead
+    /// of being skimmed in-kind. The relayer fee is still taken in the bridged token.
+    /// Remaining accounts when `config.usd_limits_enabled`: [oracle_config, pyth_price];
+    /// anything after that is forwarded to the bridge hook.
+    pub fn universal_bridge_transfer_zpx_fee<'info>(
+        ctx: Context<'_, '_, '_, 'info, UniversalBridgeTransferZpxFee<'info>>,
+        amount: u64,
+        protocol_fee: u64,
+        relayer_fee: u64,
+        payload: Vec<u8>,
+        dst_chain_id: u64,
+        nonce: u64,
+        store_preimage: bool,
+        skip_receipt: bool,
+    ) -> Result<()> {
+        ctx.accounts
+            .config
+            .require_family_active(INSTRUCTION_FAMILY_BRIDGE)?;
+        let cfg = &ctx.accounts.config;
+        require!(
+            zpx_chains::fits_wire(cfg.src_chain_id) && zpx_chains::fits_wire(dst_chain_id),
+            ErrorCode::ChainIdOutOfRange
+        );
+        assert_token_program(&ctx.accounts.token_program)?;
+        validate_common(amount, payload.len(), cfg.paused, cfg.src_chain_id)?;
+        let compression = payload_compression(&payload)?;
+        require_mint_active(
+            &ctx.accounts.mint_pause,
+            &ctx.accounts.mint.key(),
+            ctx.program_id,
+        )?;
+        require_mint_allowed(
+            &ctx.accounts.mint_allowlist,
+            cfg,
+            &ctx.accounts.mint.key(),
+            ctx.program_id,
+        )?;
+        if cfg.usd_limits_enabled {
+            enforce_usd_min_forward(
+                ctx.remaining_accounts,
+                ctx.program_id,
+                &ctx.accounts.mint,
+                amount,
+            )?;
+        }
+        require!(
+            adapter_allowed(
+                cfg,
+                ctx.remaining_accounts,
+                &ctx.accounts.target_adapter_program.key(),
+                ctx.program_id,
+            ),
+            ErrorCode::AdapterNotAllowed
+        );
+        // Same fee checks as the in-kind path; only the relayer fee leaves the bridged amount
+        let fee_policy = load_fee_policy(
+            &ctx.accounts.fee_policy,
+            &ctx.accounts.mint.key(),
+            dst_chain_id,
+            ctx.program_id,
+        )?;
+        match fee_policy {
+            Some(policy) => policy_fees(&policy, amount, protocol_fee, relayer_fee)?,
+            None => {
+                compute_fees_and_forward(amount, protocol_fee, relayer_fee, cfg.relayer_fee_bps)?
+            }
+        };
+        let forward_amount = amount
+            .checked_sub(relayer_fee)
+            .ok_or(ErrorCode::FeesExceedAmount)?;
+
+        let rate = &ctx.accounts.zpx_fee_rate;
+        let slot = Clock::get()?.slot;
+        require!(
+            slot.saturating_sub(rate.updated_at_slot) <= rate.max_staleness_slots,
+            ErrorCode::StaleZpxRate
+        );
+        let zpx_fee = zpx_fee_for(protocol_fee, rate.zpx_per_token_e9, rate.discount_bps)?;
+        zpx_log!(
+            "fee_computed",
+            amount = amount,
+            protocol_fee = protocol_fee,
+            relayer_fee = relayer_fee,
+            forward_amount = forward_amount,
+            zpx_fee = zpx_fee,
+        );
+
+        let in_flight = &mut ctx.accounts.in_flight;
+        let previous_in_flight = in_flight.amount;
+        in_flight.amount =
+            in_flight_after_forward(previous_in_flight, forward_amount, in_flight.exposure_cap)?;
+
+        // The previous recipient's ATA stays valid during the rotation grace window
+        let fee_recipient = ctx.accounts.fee_recipient_ata.owner;
+        let fee_recipient_epoch = ctx
+            .accounts
+            .fee_recipient_history
+            .accepting_epoch(&fee_recipient, Clock::get()?.slot)
+            .ok_or_else(|| error!(ErrorCode::InvalidFeeRecipientAta))?;
+        verify_fee_recipient_ata(
+            &fee_recipient,
+            &ctx.accounts.mint.key(),
+            &ctx.accounts.token_program.key(),
+            &ctx.accounts.fee_recipient_ata,
+        )?;
+
+        // Canonical hashes; routes with a max age wrap the payload hash in a validity window
+        let hash_version = cfg.message_hash_version();
+        let validity = message_validity_for(ctx.remaining_accounts, dst_chain_id, ctx.program_id)?;
+        // Normalized routes carry the amount with 18 decimals
+        let amount_decimals = route_amount_decimals(
+            ctx.remaining_accounts,
+            dst_chain_id,
+            &ctx.accounts.mint.key(),
+            ctx.program_id,
+        )?;
+        let (payload_hash, msg_hash, global_route) = source_leg_hashes(
+            hash_version,
+            cfg.src_chain_id,
+            dst_chain_id,
+            &ctx.accounts.target_adapter_program.key(),
+            &ctx.accounts.mint.key(),
+            &ctx.accounts.user.key(),
+            forward_amount,
+            amount_decimals,
+            &payload,
+            nonce,
+            validity.as_ref(),
+        )?;
+        if let Some(decimals) = amount_decimals {
+            emit!(AmountNormalized {
+                message_hash: msg_hash,
+                mint: ctx.accounts.mint.key(),
+                amount: forward_amount,
+                decimals,
+                amount_e18: amount::to_canonical(forward_amount, decimals)
+                    .map_err(ErrorCode::from)?,
+            });
+        }
+        if store_preimage {
+            store_message_preimage(
+                &ctx.accounts.rent_payer.to_account_info(),
+                &ctx.accounts.message.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                ctx.program_id,
+                MessagePreimage {
+                    src_chain_id: cfg.src_chain_id,
+                    dst_chain_id,
+                    target_adapter: ctx.accounts.target_adapter_program.key(),
+                    mint: ctx.accounts.mint.key(),
+                    initiator: ctx.accounts.user.key(),
+                    forward_amount,
+                    payload_hash,
+                    nonce,
+                    message_hash: msg_hash,
+                    hash_version,
+                    bump: 0,
+                    amount_decimals,
+                },
+            )?;
+            record_rent(
+                &ctx.accounts.rent_ledger.to_account_info(),
+                ctx.program_id,
+                RENT_KIND_MESSAGE,
+                MESSAGE_PREIMAGE_SPACE,
+                true,
+            )?;
+        }
+        if !skip_receipt {
+            record_transfer_receipt(
+                &ctx.accounts.rent_payer.to_account_info(),
+                &ctx.accounts.user_index.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                &ctx.accounts.user.key(),
+                TransferReceipt {
+                    message_hash: msg_hash,
+                    slot: Clock::get()?.slot,
+                    amount: forward_amount,
+                    dst_chain_id,
+                },
+                ctx.program_id,
+            )?;
+        }
+
+        record_router_stats(
+            &ctx.accounts.router_stats,
+            ctx.program_id,
+            &ctx.accounts.mint.key(),
+            forward_amount,
+            STATS_KIND_INITIATED,
+        )?;
+
+        // Interactions: token CPIs only after all state is written
+        // Burn: user ZPX (discounted protocol fee)
+        if zpx_fee > 0 {
+            token::burn(
+                CpiContext::new(
+                    token_cpi_program(&ctx.accounts.token_program)?,
+                    token::Burn {
+                        mint: ctx.accounts.zpx_mint.to_account_info(),
+                        from: ctx.accounts.user_zpx_account.to_account_info(),
+                        authority: ctx.accounts.user.to_account_info(),
+                    },
+                ),
+                zpx_fee,
+            )?;
+        }
+
+        // Transfer: user -> fee_recipient (relayer fee only)
+        if relayer_fee > 0 {
+            token::transfer(
+                CpiContext::new(
+                    token_cpi_program(&ctx.accounts.token_program)?,
+                    token::Transfer {
+                        from: ctx.accounts.from.to_account_info(),
+                        to: ctx.accounts.fee_recipient_ata.to_account_info(),
+                        authority: ctx.accounts.user.to_account_info(),
+                    },
+                ),
+                relayer_fee,
+            )?;
+        }
+
+        // Transfer: user -> target (forward amount)
+        if forward_amount > 0 {
+            token::transfer(
+                CpiContext::new(
+                    token_cpi_program(&ctx.accounts.token_program)?,
+                    token::Transfer {
+                        from: ctx.accounts.from.to_account_info(),
+                        to: ctx.accounts.target_token_account.to_account_info(),
+                        authority: ctx.accounts.user.to_account_info(),
+                    },
+                ),
+                forward_amount,
+            )?;
+        }
+
+        emit!(BridgeInitiated {
+            route_id: [0u8; 32],
+            user: ctx.accounts.user.key(),
+            token: ctx.accounts.mint.key(),
+            target: ctx.accounts.target_adapter_program.key(),
+            forwarded_amount: forward_amount,
+            protocol_fee,
+            relayer_fee,
+            payload_hash,
+            src_chain_id: cfg.src_chain_id as u16,
+            dst_chain_id: dst_chain_id as u16,
+            nonce,
+        });
+        emit!(UniversalBridgeInitiated {
+            route_id: [0u8; 32],
+            payload_hash,
+            message_hash: msg_hash,
+            global_route_id: global_route,
+            user: ctx.accounts.user.key(),
+            token: ctx.accounts.mint.key(),
+            target: ctx.accounts.target_adapter_program.key(),
+            forwarded_amount: forward_amount,
+            protocol_fee,
+            relayer_fee,
+            src_chain_id: cfg.src_chain_id as u16,
+            dst_chain_id: dst_chain_id as u16,
+            nonce,
+        });
+        if let Some(c) = compression {
+            emit!(PayloadCompressed {
+                message_hash: msg_hash,
+                payload_hash,
+                algorithm: c.algorithm,
+                compressed_len: c.compressed_len,
+                decompressed_len: c.decompressed_len,
+            });
+        }
+        if let Some(validity) = validity {
+            emit!(MessageValidityEmbedded {
+                message_hash: msg_hash,
+                payload_hash: keccak256(&[&payload]),
+                sent_at: validity.sent_at,
+                expires_at: validity.expires_at,
+            });
+        }
+        emit!(InFlightUpdated {
+            mint: ctx.accounts.mint.key(),
+            dst_chain_id,
+            previous: previous_in_flight,
+            current: ctx.accounts.in_flight.amount,
+            exposure_cap: ctx.accounts.in_flight.exposure_cap,
+        });
+        emit!(FeeAppliedSourceV2 {
+            message_hash: msg_hash,
+            asset: ctx.accounts.mint.key(),
+            payer: ctx.accounts.user.key(),
+            target: ctx.accounts.target_adapter_program.key(),
+            protocol_fee,
+            relayer_fee,
+            fee_recipient,
+            applied_at: Clock::get()?.unix_timestamp as u64,
+            zpx_fee_burned: zpx_fee,
+            fee_recipient_epoch,
+        });
+        emit!(FeeAppliedSourceV3 {
+            message_hash: msg_hash,
+            asset: ctx.accounts.mint.key(),
+            payer: ctx.accounts.user.key(),
+            target: ctx.accounts.target_adapter_program.key(),
+            protocol_fee,
+            relayer_fee,
+            fee_recipient,
+            applied_at: Clock::get()?.unix_timestamp as u64,
+            zpx_fee_burned: zpx_fee,
+            fee_recipient_epoch,
+            config_version: cfg.config_version,
+        });
+        let oracle_accounts = if cfg.usd_limits_enabled { 2 } else { 0 };
+        invoke_bridge_hook(
+            cfg,
+            &ctx.accounts.bridge_hook_program.to_account_info(),
+            &ctx.accounts.bridge_hook_authority.to_account_info(),
+            ctx.bumps.get("bridge_hook_authority").copied().unwrap(),
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.mint.to_account_info(),
+            &ctx.remaining_accounts[oracle_accounts..],
+            &BridgeHookData {
+                message_hash: msg_hash,
+                global_route_id: global_route,
+                payload_hash,
+                user: ctx.accounts.user.key(),
+                mint: ctx.accounts.mint.key(),
+                target: ctx.accounts.target_adapter_program.key(),
+                forwarded_amount: forward_amount,
+                protocol_fee,
+                relayer_fee,
+                src_chain_id: cfg.src_chain_id,
+                dst_chain_id,
+                nonce,
+            },
+        )?;
+        Ok(())
+    }
+
+    /// Create the ZPX fee rate PDA for a bridged mint (admin-only).
+    pub fn initialize_zpx_fee_rate(
+        ctx: Context<InitializeZpxFeeRate>,
+        keeper: Pubkey,
+        zpx_per_token_e9: u64,
+        discount_bps: u16,
+        max_staleness_slots: u64,
+    ) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::InitializeZpxFeeRate {
+                keeper,
+                zpx_per_token_e9,
+                discount_bps,
+                max_staleness_slots,
+            },
+        )?;
+        require!(discount_bps <= 10_000, ErrorCode::InvalidZpxDiscount);
+        let rate = &mut ctx.accounts.zpx_fee_rate;
+        rate.mint = ctx.accounts.mint.key();
+        rate.zpx_mint = ctx.accounts.zpx_mint.key();
+        rate.keeper = keeper;
+        rate.zpx_per_token_e9 = zpx_per_token_e9;
+        rate.discount_bps = discount_bps;
+        rate.max_staleness_slots = max_staleness_slots;
+        rate.updated_at_slot = Clock::get()?.slot;
+        rate.bump = ctx.bumps.get("zpx_fee_rate").copied().unwrap();
+        emit!(ZpxFeeRateUpdated {
+            mint: rate.mint,
+            zpx_per_token_e9,
+            discount_bps,
+            updated_at_slot: rate.updated_at_slot,
+        });
+        Ok(())
+    }
+
+    /// Publish a fresh ZPX rate (keeper or admin).
+    pub fn update_zpx_fee_rate(
+        ctx: Context<UpdateZpxFeeRate>,
+        zpx_per_token_e9: u64,
+    ) -> Result<()> {
+        let rate = &mut ctx.accounts.zpx_fee_rate;
+        require!(
+            ctx.accounts.authority.key() == rate.keeper
+                || ctx.accounts.authority.key() == ctx.accounts.config.admin,
+            ErrorCode::Unauthorized
+        );
+        rate.zpx_per_token_e9 = zpx_per_token_e9;
+        rate.updated_at_slot = Clock::get()?.slot;
+        emit!(ZpxFeeRateUpdated {
+            mint: rate.mint,
+            zpx_per_token_e9,
+            discount_bps: rate.discount_bps,
+            updated_at_slot: rate.updated_at_slot,
+        });
+        Ok(())
+    }
+
+    /// Adjust keeper, discount and staleness bound of a ZPX fee rate (admin-only).
+    pub fn configure_zpx_fee_rate(
+        ctx: Context<ConfigureZpxFeeRate>,
+        keeper: Option<Pubkey>,
+        discount_bps: Option<u16>,
+        max_staleness_slots: Option<u64>,
+    ) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::ConfigureZpxFeeRate {
+                keeper,
+                discount_bps,
+                max_staleness_slots,
+            },
+        )?;
+        let rate = &mut ctx.accounts.zpx_fee_rate;
+        if let Some(k) = keeper {
+            rate.keeper = k;
+        }
+        if let Some(d) = discount_bps {
+            require!(d <= 10_000, ErrorCode::InvalidZpxDiscount);
+            rate.discount_bps = d;
+        }
+        if let Some(m) = max_staleness_slots {
+            rate.max_staleness_slots = m;
+        }
+        emit!(ZpxFeeRateUpdated {
+            mint: rate.mint,
+            zpx_per_token_e9: rate.zpx_per_token_e9,
+            discount_bps: rate.discount_bps,
+            updated_at_slot: rate.updated_at_slot,
+        });
+        Ok(())
+    }
+
+    // Test helper: perform a CPI to the provided adapter program. Used by program-tests
+    // to validate CPI failure handling and rollback semantics. Only live in builds with
+    // `test-instructions`, and even then only the admin may target an allowlisted adapter.
+    pub fn bridge_with_adapter_cpi(ctx: Context<BridgeWithAdapterCpi>) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.admin.key(),
+            &instruction::BridgeWithAdapterCpi {},
+        )?;
+        require!(
+            cfg!(feature = "test-instructions"),
+            ErrorCode::TestInstructionsDisabled
+        );
+        require!(
+            adapter_allowed(
+                &ctx.accounts.config,
+                ctx.remaining_accounts,
+                &ctx.accounts.adapter_program.key(),
+                ctx.program_id,
+            ),
+            ErrorCode::AdapterNotAllowed
+        );
+        // Build instruction data: adapter's `fail_now` has no args, instruction index 0
+        let ix = anchor_lang::solana_program::instruction::Instruction {
+            program_id: ctx.accounts.adapter_program.key(),
+            accounts: vec![],
+            data: vec![0u8],
+        };
+        // Perform CPI and propagate error. Pass the adapter account info so the runtime
+        // has ownership/context for the CPI.
+        anchor_lang::solana_program::program::invoke(
+            &ix,
+            &[ctx.accounts.adapter_program.to_account_info()],
+        )
+        .map_err(|_| error!(ErrorCode::Unauthorized))?;
+        Ok(())
+    }
+
+    /// Test-only: pin the slot / unix timestamp seen by time::now when the ClockOverride PDA
+    /// is passed as a remaining account (admin-only, `test-instructions` builds only).
+    pub fn set_clock_override(
+        ctx: Context<SetClockOverride>,
+        slot: u64,
+        unix_timestamp: i64,
+    ) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.admin.key(),
+            &instruction::SetClockOverride {
+                slot,
+                unix_timestamp,
+            },
+        )?;
+        require!(
+            cfg!(feature = "test-instructions"),
+            ErrorCode::TestInstructionsDisabled
+        );
+        let (expected, bump) =
+            Pubkey::find_program_address(&[time::CLOCK_OVERRIDE_SEED], ctx.program_id);
+        let override_ai = ctx.accounts.clock_override.to_account_info();
+        require_keys_eq!(override_ai.key(), expected, ErrorCode::Unauthorized);
+        if override_ai.owner != ctx.program_id {
+            create_pda_account(
+                &ctx.accounts.admin.to_account_info(),
+                &override_ai,
+                &ctx.accounts.system_program.to_account_info(),
+                CLOCK_OVERRIDE_SPACE,
+                &[time::CLOCK_OVERRIDE_SEED, &[bump]],
+                ctx.program_id,
+            )?;
+        }
+        ClockOverride {
+            slot,
+            unix_timestamp,
+            bump,
+        }
+        .try_serialize(&mut &mut override_ai.try_borrow_mut_data()?[..])?;
+        Ok(())
+    }
+
+    /// Break-glass: the build-time sentinel pauses config and every spoke at once. Until
+    /// release_global_kill runs, nothing can be unpaused. Re-running it cancels a scheduled
+    /// release.
+    pub fn global_kill(ctx: Context<GlobalKill>) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.sentinel.key(),
+            kill_sentinel(),
+            ErrorCode::NotKillSentinel
+        );
+        let cfg = &mut ctx.accounts.config;
+        cfg.paused = true;
+        cfg.killed = true;
+        cfg.kill_release_slot = 0;
+        bump_config_version(ctx.remaining_accounts, ctx.program_id, cfg, true)?;
+        let registry = &mut ctx.accounts.registry;
+        let len = registry.spokes_len as usize;
+        for spoke in registry.spokes[..len].iter_mut() {
+            spoke.paused = true;
+        }
+        emit!(GlobalKillActivated {
+            sentinel: ctx.accounts.sentinel.key(),
+            spokes_paused: registry.spokes_len,
+            slot: Clock::get()?.slot,
+        });
+        sync_route_availability(ctx.remaining_accounts, ctx.program_id, cfg, Some(registry))
+    }
+
+    /// Governance path out of a global kill, step 1 (admin-only): start the release timelock.
+    pub fn schedule_kill_release(ctx: Context<AdminConfig>) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::ScheduleKillRelease {},
+        )?;
+        let cfg = &mut ctx.accounts.config;
+        require!(cfg.killed, ErrorCode::NotKilled);
+        let release_slot = time::now(ctx.remaining_accounts, ctx.program_id)?
+            .slot
+            .checked_add(KILL_RELEASE_DELAY_SLOTS)
+            .ok_or(ErrorCode::MathOverflow)?;
+        cfg.kill_release_slot = release_slot;
+        bump_config_version(ctx.remaining_accounts, ctx.program_id, cfg, false)?;
+        emit!(KillReleaseScheduled { release_slot });
+        Ok(())
+    }
+
+    /// Step 2 (admin-only, after the timelock): clear the kill and unpause config. Spokes stay
+    /// paused and are re-enabled individually.
+    pub fn release_global_kill(ctx: Context<AdminConfig>) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::ReleaseGlobalKill {},
+        )?;
+        let cfg = &mut ctx.accounts.config;
+        require!(cfg.killed, ErrorCode::NotKilled);
+        let slot = time::now(ctx.remaining_accounts, ctx.program_id)?.slot;
+        require!(
+            kill_release_ready(cfg.kill_release_slot, slot),
+            ErrorCode::KillReleaseLocked
+        );
+        cfg.killed = false;
+        cfg.paused = false;
+        cfg.kill_release_slot = 0;
+        bump_config_version(ctx.remaining_accounts, ctx.program_id, cfg, false)?;
+        emit!(GlobalKillReleased {
+            admin: cfg.admin,
+            slot,
+        });
+        sync_route_availability(ctx.remaining_accounts, ctx.program_id, cfg, None)
+    }
+
+    /// Onboard a new mint route in one step (admin-only): create the mint's hub vaults,
+    /// allowlist the mint, create the spoke and set the route's fee policy, each as its own
+    /// instruction would. A failing step reverts the whole onboarding. Emits RouteOnboarded
+    /// instead of the per-step events.
+    pub fn onboard_route(ctx: Context<OnboardRoute>, route: RouteOnboarding) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::OnboardRoute {
+                route: route.clone(),
+            },
+        )?;
+        let accounts = &mut *ctx.accounts;
+        let mint = accounts.mint.key();
+        // Rejected before any account is created
+        let policy = FeePolicy::new(
+            mint,
+            route.dst_chain_id,
+            &route.fee_tiers,
+            route.protocol_min_fee,
+            route.protocol_max_fee,
+            route.relayer_min_fee,
+            route.relayer_max_fee,
+        )?;
+        init_hub_vaults(
+            &accounts.authority,
+            &accounts.mint,
+            &accounts.hub_protocol_vault,
+            &accounts.hub_relayer_vault,
+            &mut accounts.vault_registry,
+            ctx.bumps.get("vault_registry").copied().unwrap(),
+            &accounts.rent_ledger,
+            &accounts.token_program,
+            &accounts.system_program,
+            ctx.program_id,
+        )?;
+        accounts.mint_allowlist.add(mint)?;
+        insert_spoke(
+            &mut accounts.registry,
+            accounts.config.killed,
+            route.spoke_id,
+            route.adapter_program,
+            route.direct_relayer_payout,
+            route.version,
+            route.metadata,
+            route.payload_schema,
+        )?;
+        policy.store(
+            &accounts.authority,
+            &accounts.fee_policy,
+            &accounts.system_program,
+            ctx.program_id,
+        )?;
+        emit!(RouteOnboarded {
+            mint,
+            spoke_id: route.spoke_id,
+            adapter_program: route.adapter_program,
+            dst_chain_id: route.dst_chain_id,
+            protocol_vault: accounts.vault_registry.protocol_vault,
+            relayer_vault: accounts.vault_registry.relayer_vault,
+            fee_tiers: route.fee_tiers,
+        });
+        sync_route_availability(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &accounts.config,
+            Some(&accounts.registry),
+        )
+    }
+
+    /// Hub: create a new spoke registry entry (admin-only)
+    pub fn create_spoke(
+        ctx: Context<CreateSpoke>,
+        spoke_id: u32,
+        adapter_program: Pubkey,
+        direct_relayer_payout: bool,
+        version: u8,
+        metadata: Option<String>,
+        payload_schema: Option<PayloadSchema>,
+    ) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::CreateSpoke {
+                spoke_id,
+                adapter_program,
+                direct_relayer_payout,
+                version,
+                metadata: metadata.clone(),
+                payload_schema,
+            },
+        )?;
+        // Spokes created during a global kill start paused like every other spoke
+        let killed = ctx.accounts.config.killed;
+        insert_spoke(
+            &mut ctx.accounts.registry,
+            killed,
+            spoke_id,
+            adapter_program,
+            direct_relayer_payout,
+            version,
+            metadata,
+            payload_schema,
+        )?;
+        sync_route_availability(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            Some(&ctx.accounts.registry),
+        )
+    }
+
+    pub fn update_spoke(
+        ctx: Context<UpdateSpoke>,
+        spoke_id: u32,
+        adapter_program: Option<Pubkey>,
+        direct_relayer_payout: Option<bool>,
+        paused: Option<bool>,
+        metadata: Option<String>,
+        payload_schema: Option<PayloadSchema>,
+    ) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::UpdateSpoke {
+                spoke_id,
+                adapter_program,
+                direct_relayer_payout,
+                paused,
+                metadata: metadata.clone(),
+                payload_schema,
+            },
+        )?;
+        let registry = &mut ctx.accounts.registry;
+        let cfg = &ctx.accounts.config;
+        let len = registry.spokes_len as usize;
+        let mut idx = None;
+        for i in 0..len {
+            if registry.spokes[i].spoke_id == spoke_id {
+                idx = Some(i);
+                break;
+            }
+        }
+        let i = idx.ok_or_else(|| error!(ErrorCode::AdapterNotAllowed))?;
+        if let Some(p) = adapter_program {
+            if registry.spokes[i].adapter_program != p {
+                // A different program invalidates any existing pin; re-pin explicitly.
+                registry.spokes[i].adapter_program_data = Pubkey::default();
+                registry.spokes[i].adapter_deploy_slot = 0;
+                registry.spokes[i].adapter_upgrade_authority = Pubkey::default();
+            }
+            registry.spokes[i].adapter_program = p;
+        }
+        if let Some(d) = direct_relayer_payout {
+            registry.spokes[i].relayer_payout = relayer_payout_mode(d);
+        }
+        if let Some(p) = paused {
+            require!(p || !cfg.killed, ErrorCode::GlobalKilled);
+            registry.spokes[i].paused = p;
+        }
+        if let Some(m) = metadata {
+            let bytes = m.as_bytes();
+            let mut meta = [0u8; SPOKE_METADATA_LEN];
+            meta[..bytes.len().min(SPOKE_METADATA_LEN)]
+                .copy_from_slice(&bytes[..bytes.len().min(SPOKE_METADATA_LEN)]);
+            registry.spokes[i].metadata = meta;
+        }
+        if let Some(schema) = payload_schema {
+            check_payload_schema_config(&schema)?;
+            registry.spokes[i].payload_schema = schema;
+        }
+        sync_route_availability(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            Some(&ctx.accounts.registry),
+        )
+    }
+
+    /// Pause a single mint in both transfer paths (admin or guardian). Creates the
+    /// MintPause PDA on first use.
+    pub fn pause_mint(ctx: Context<SetMintPause>) -> Result<()> {
+        set_mint_pause(ctx, true)
+    }
+
+    /// Lift a per-mint pause (admin or guardian).
+    pub fn unpause_mint(ctx: Context<SetMintPause>) -> Result<()> {
+        set_mint_pause(ctx, false)
+    }
+
+    pub fn pause_spoke(ctx: Context<PauseSpoke>, spoke_id: u32) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::PauseSpoke { spoke_id },
+        )?;
+        let registry = &mut ctx.accounts.registry;
+        let len = registry.spokes_len as usize;
+        let mut idx = None;
+        for i in 0..len {
+            if registry.spokes[i].spoke_id == spoke_id {
+                idx = Some(i);
+                break;
+            }
+        }
+        let i = idx.ok_or_else(|| error!(ErrorCode::AdapterNotAllowed))?;
+        registry.spokes[i].paused = true;
+        sync_route_availability(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            Some(&ctx.accounts.registry),
+        )
+    }
+
+    pub fn enable_spoke(ctx: Context<PauseSpoke>, spoke_id: u32) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::EnableSpoke { spoke_id },
+        )?;
+        let registry = &mut ctx.accounts.registry;
+        let cfg = &ctx.accounts.config;
+        let len = registry.spokes_len as usize;
+        let mut idx = None;
+        for i in 0..len {
+            if registry.spokes[i].spoke_id == spoke_id {
+                idx = Some(i);
+                break;
+            }
+        }
+        let i = idx.ok_or_else(|| error!(ErrorCode::AdapterNotAllowed))?;
+        require!(!cfg.killed, ErrorCode::GlobalKilled);
+        registry.spokes[i].paused = false;
+        sync_route_availability(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            Some(&ctx.accounts.registry),
+        )
+    }
+
+    /// Tag a spoke with its upstream protocol family (admin-only).
+    pub fn set_spoke_category(ctx: Context<SpokeAdmin>, spoke_id: u32, category: u8) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::SetSpokeCategory { spoke_id, category },
+        )?;
+        let registry = &mut ctx.accounts.registry;
+        let len = registry.spokes_len as usize;
+        let entry = registry.spokes[..len]
+            .iter_mut()
+            .find(|s| s.spoke_id == spoke_id)
+            .ok_or_else(|| error!(ErrorCode::AdapterNotAllowed))?;
+        entry.category = category;
+        emit!(SpokeCategoryUpdated { spoke_id, category });
+        Ok(())
+    }
+
+    /// Choose how the spoke pays token relayer fees (admin-only): RELAYER_PAYOUT_DEFAULT
+    /// follows the config, RELAYER_PAYOUT_DIRECT pays the relayer's token account and
+    /// RELAYER_PAYOUT_VAULT turns direct payout off for a risky route.
+    pub fn set_spoke_relayer_payout(
+        ctx: Context<SpokeAdmin>,
+        spoke_id: u32,
+        relayer_payout: u8,
+    ) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::SetSpokeRelayerPayout {
+                spoke_id,
+                relayer_payout,
+            },
+        )?;
+        require!(
+            relayer_payout <= RELAYER_PAYOUT_VAULT,
+            ErrorCode::InvalidRelayerPayoutMode
+        );
+        let registry = &mut ctx.accounts.registry;
+        let len = registry.spokes_len as usize;
+        let entry = registry.spokes[..len]
+            .iter_mut()
+            .find(|s| s.spoke_id == spoke_id)
+            .ok_or_else(|| error!(ErrorCode::AdapterNotAllowed))?;
+        entry.relayer_payout = relayer_payout;
+        emit!(SpokeRelayerPayoutUpdated {
+            spoke_id,
+            relayer_payout,
+        });
+        Ok(())
+    }
+
+    /// Pause every spoke in `category` at once, e.g. all CCTP routes during a Circle outage.
+    pub fn pause_category(ctx: Context<PauseSpoke>, category: u8) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::PauseCategory { category },
+        )?;
+        require!(
+            category != SPOKE_CATEGORY_NONE,
+            ErrorCode::InvalidSpokeCategory
+        );
+        let registry = &mut ctx.accounts.registry;
+        let len = registry.spokes_len as usize;
+        let spokes = set_category_paused(&mut registry.spokes[..len], category, true);
+        emit!(CategoryPaused {
+            category,
+            paused: true,
+            spokes,
+            slot: Clock::get()?.slot,
+        });
+        sync_route_availability(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            Some(&ctx.accounts.registry),
+        )
+    }
+
+    /// Unpause every spoke in `category`, including spokes that were paused individually.
+    pub fn enable_category(ctx: Context<PauseSpoke>, category: u8) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::EnableCategory { category },
+        )?;
+        require!(
+            category != SPOKE_CATEGORY_NONE,
+            ErrorCode::InvalidSpokeCategory
+        );
+        require!(!ctx.accounts.config.killed, ErrorCode::GlobalKilled);
+        let registry = &mut ctx.accounts.registry;
+        let len = registry.spokes_len as usize;
+        let spokes = set_category_paused(&mut registry.spokes[..len], category, false);
+        emit!(CategoryPaused {
+            category,
+            paused: false,
+            spokes,
+            slot: Clock::get()?.slot,
+        });
+        sync_route_availability(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            Some(&ctx.accounts.registry),
+        )
+    }
+
+    /// Start a deprecation window for a spoke (admin-only). Forwards keep working but emit
+    /// SpokeDeprecationWarning until `sunset_slot`, after which they fail with SpokeDeprecated
+    /// pointing clients at `successor_spoke_id`.
+    pub fn deprecate_spoke(
+        ctx: Context<SpokeAdmin>,
+        spoke_id: u32,
+        successor_spoke_id: u32,
+        window_slots: u64,
+    ) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::DeprecateSpoke {
+                spoke_id,
+                successor_spoke_id,
+                window_slots,
+            },
+        )?;
+        require!(
+            successor_spoke_id != spoke_id,
+            ErrorCode::InvalidSuccessorSpoke
+        );
+        let registry = &mut ctx.accounts.registry;
+        let len = registry.spokes_len as usize;
+        let mut idx = None;
+        let mut successor_found = false;
+        for i in 0..len {
+            if registry.spokes[i].spoke_id == spoke_id {
+                idx = Some(i);
+            }
+            if registry.spokes[i].spoke_id == successor_spoke_id {
+                successor_found = true;
+            }
+        }
+        let i = idx.ok_or_else(|| error!(ErrorCode::AdapterNotAllowed))?;
+        require!(successor_found, ErrorCode::InvalidSuccessorSpoke);
+        let slot = Clock::get()?.slot;
+        let entry = &mut registry.spokes[i];
+        entry.deprecated_at_slot = slot;
+        entry.sunset_slot = slot
+            .checked_add(window_slots)
+            .ok_or(ErrorCode::MathOverflow)?;
+        entry.successor_spoke_id = successor_spoke_id;
+        emit!(SpokeDeprecated {
+            spoke_id,
+            successor_spoke_id,
+            deprecated_at_slot: entry.deprecated_at_slot,
+            sunset_slot: entry.sunset_slot,
+        });
+        sync_route_availability(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            Some(&ctx.accounts.registry),
+        )
+    }
+
+    /// Create or rewrite the RouteAvailability bitmap from the current config and registry
+    /// (permissionless; the payer funds the account on first use).
+    pub fn refresh_route_availability<'info>(
+        ctx: Context<'_, '_, '_, 'info, RefreshRouteAvailability<'info>>,
+    ) -> Result<()> {
+        let cfg = &ctx.accounts.config;
+        let (expected, bump) = Pubkey::find_program_address(
+            &[ROUTE_AVAILABILITY_SEED, cfg.namespace_seed()],
+            ctx.program_id,
+        );
+        let ai = ctx.accounts.route_availability.to_account_info();
+        require_keys_eq!(
+            ai.key(),
+            expected,
+            ErrorCode::InvalidRouteAvailabilityAccount
+        );
+        if ai.owner != ctx.program_id {
+            create_pda_account(
+                &ctx.accounts.payer.to_account_info(),
+                &ai,
+                &ctx.accounts.system_program.to_account_info(),
+                ROUTE_AVAILABILITY_SPACE,
+                &[ROUTE_AVAILABILITY_SEED, cfg.namespace_seed(), &[bump]],
+                ctx.program_id,
+            )?;
+            RouteAvailability {
+                flags: 0,
+                spokes_len: 0,
+                spoke_ids: [0; MAX_SPOKES],
+                spoke_bits: 0,
+                next_sunset_slot: 0,
+                updated_slot: 0,
+                bump,
+            }
+            .try_serialize(&mut &mut ai.try_borrow_mut_data()?[..])?;
+        }
+        // The sync helper finds the PDA (and any clock override) among the passed accounts
+        let mut accounts = vec![ai];
+        accounts.extend_from_slice(ctx.remaining_accounts);
+        sync_route_availability(&accounts, ctx.program_id, cfg, Some(&ctx.accounts.registry))
+    }
+
+    /// Assign a spoke to a route group with a load-balancing weight (admin-only).
+    /// route_group 0 removes the spoke from balancing.
+    pub fn set_spoke_weight(
+        ctx: Context<SpokeAdmin>,
+        spoke_id: u32,
+        route_group: u32,
+        weight: u16,
+    ) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::SetSpokeWeight {
+                spoke_id,
+                route_group,
+                weight,
+            },
+        )?;
+        let registry = &mut ctx.accounts.registry;
+        let len = registry.spokes_len as usize;
+        let entry = registry.spokes[..len]
+            .iter_mut()
+            .find(|s| s.spoke_id == spoke_id)
+            .ok_or_else(|| error!(ErrorCode::AdapterNotAllowed))?;
+        entry.route_group = route_group;
+        entry.weight = weight;
+        emit!(SpokeWeightUpdated {
+            spoke_id,
+            route_group,
+            weight,
+        });
+        Ok(())
+    }
+
+    /// Designate the spoke forward_auto reroutes to while `spoke_id` is paused (admin-only).
+    /// 0 clears the fallback.
+    pub fn set_spoke_fallback(
+        ctx: Context<SpokeAdmin>,
+        spoke_id: u32,
+        fallback_spoke_id: u32,
+    ) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::SetSpokeFallback {
+                spoke_id,
+                fallback_spoke_id,
+            },
+        )?;
+        require!(
+            fallback_spoke_id != spoke_id,
+            ErrorCode::InvalidSuccessorSpoke
+        );
+        let registry = &mut ctx.accounts.registry;
+        let len = registry.spokes_len as usize;
+        require!(
+            fallback_spoke_id == 0
+                || registry.spokes[..len]
+                    .iter()
+                    .any(|s| s.spoke_id == fallback_spoke_id),
+            ErrorCode::InvalidSuccessorSpoke
+        );
+        let entry = registry.spokes[..len]
+            .iter_mut()
+            .find(|s| s.spoke_id == spoke_id)
+            .ok_or_else(|| error!(ErrorCode::AdapterNotAllowed))?;
+        entry.fallback_spoke_id = fallback_spoke_id;
+        emit!(SpokeFallbackUpdated {
+            spoke_id,
+            fallback_spoke_id,
+        });
+        Ok(())
+    }
+
+    /// Register the owner the adapter's target token account must have (admin-only), e.g. the
+    /// adapter's custody PDA. forward_via_spoke and friends reject any other destination.
+    pub fn set_spoke_target_owner(
+        ctx: Context<SpokeAdmin>,
+        spoke_id: u32,
+        target_owner: Pubkey,
+    ) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::SetSpokeTargetOwner {
+                spoke_id,
+                target_owner,
+            },
+        )?;
+        require!(
+            target_owner != Pubkey::default(),
+            ErrorCode::InvalidAdapterTarget
+        );
+        let registry = &mut ctx.accounts.registry;
+        let len = registry.spokes_len as usize;
+        let entry = registry.spokes[..len]
+            .iter_mut()
+            .find(|s| s.spoke_id == spoke_id)
+            .ok_or_else(|| error!(ErrorCode::AdapterNotAllowed))?;
+        entry.target_owner = target_owner;
+        emit!(SpokeTargetOwnerUpdated {
+            spoke_id,
+            target_owner,
+        });
+        Ok(())
+    }
+
+    /// Set the outstanding-volume threshold above which SpokeImbalance fires (admin-only);
+    /// 0 disables the alert.
+    pub fn set_spoke_imbalance_threshold(
+        ctx: Context<SpokeAdmin>,
+        spoke_id: u32,
+        threshold: u64,
+    ) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::SetSpokeImbalanceThreshold {
+                spoke_id,
+                threshold,
+            },
+        )?;
+        let registry = &mut ctx.accounts.registry;
+        let len = registry.spokes_len as usize;
+        let entry = registry.spokes[..len]
+            .iter_mut()
+            .find(|s| s.spoke_id == spoke_id)
+            .ok_or_else(|| error!(ErrorCode::AdapterNotAllowed))?;
+        entry.imbalance_threshold = threshold;
+        emit!(SpokeImbalanceThresholdUpdated {
+            spoke_id,
+            threshold,
+        });
+        Ok(())
+    }
+
+    /// Set the relayer latency target (slots from escrow deposit to first dispatch) above
+    /// which forwards through the spoke emit SlaBreached (admin-only); 0 disables it.
+    pub fn set_spoke_sla_target(
+        ctx: Context<SpokeAdmin>,
+        spoke_id: u32,
+        target_slots: u64,
+    ) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::SetSpokeSlaTarget {
+                spoke_id,
+                target_slots,
+            },
+        )?;
+        let registry = &mut ctx.accounts.registry;
+        let len = registry.spokes_len as usize;
+        let entry = registry.spokes[..len]
+            .iter_mut()
+            .find(|s| s.spoke_id == spoke_id)
+            .ok_or_else(|| error!(ErrorCode::AdapterNotAllowed))?;
+        entry.sla_target_slots = target_slots;
+        emit!(SpokeSlaTargetUpdated {
+            spoke_id,
+            target_slots,
+        });
+        Ok(())
+    }
+
+    /// Cap the compute units the spoke's adapter may consume per router CPI (0 = no cap).
+    pub fn set_spoke_adapter_cu(
+        ctx: Context<SpokeAdmin>,
+        spoke_id: u32,
+        max_adapter_cu: u32,
+    ) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::SetSpokeAdapterCu {
+                spoke_id,
+                max_adapter_cu,
+            },
+        )?;
+        require!(
+            max_adapter_cu as u64 <= MAX_TX_COMPUTE_UNITS,
+            ErrorCode::AdapterBudgetExceeded
+        );
+        let registry = &mut ctx.accounts.registry;
+        let len = registry.spokes_len as usize;
+        let entry = registry.spokes[..len]
+            .iter_mut()
+            .find(|s| s.spoke_id == spoke_id)
+            .ok_or_else(|| error!(ErrorCode::AdapterNotAllowed))?;
+        entry.max_adapter_cu = max_adapter_cu;
+        emit!(SpokeAdapterCuUpdated {
+            spoke_id,
+            max_adapter_cu,
+        });
+        Ok(())
+    }
+
+    /// Record `amount` of a spoke's dispatched volume as finalized on the destination.
+    /// Callable by the configured relayer or admin.
+    pub fn ack_spoke_finalized(
+        ctx: Context<AckSpokeFinalized>,
+        spoke_id: u32,
+        amount: u64,
+    ) -> Result<()> {
+        let cfg = &ctx.accounts.config;
+        require!(
+            ctx.accounts.authority.key() == cfg.relayer_pubkey
+                || ctx.accounts.authority.key() == cfg.admin,
+            ErrorCode::Unauthorized
+        );
+        require!(amount > 0, ErrorCode::ZeroAmount);
+        let registry = &mut ctx.accounts.registry;
+        let len = registry.spokes_len as usize;
+        let entry = registry.spokes[..len]
+            .iter_mut()
+            .find(|s| s.spoke_id == spoke_id)
+            .ok_or_else(|| error!(ErrorCode::AdapterNotAllowed))?;
+        let was_imbalanced = entry.imbalanced();
+        let finalized = entry
+            .finalized_amount
+            .checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        require!(
+            finalized <= entry.dispatched_amount,
+            ErrorCode::FinalizedExceedsDispatched
+        );
+        entry.finalized_amount = finalized;
+        emit!(SpokeFinalizedAcked {
+            spoke_id,
+            amount,
+            dispatched_amount: entry.dispatched_amount,
+            finalized_amount: finalized,
+        });
+        if was_imbalanced && !entry.imbalanced() {
+            emit!(entry.imbalance_event());
+        }
+        Ok(())
+    }
+
+    /// Pin a spoke's adapter to its current program-data deployment (admin-only).
+    /// forward_via_spoke rejects the spoke with `AdapterCodeChanged` after any upgrade
+    /// or upgrade-authority change until the adapter is re-pinned.
+    pub fn pin_spoke_adapter(ctx: Context<PinSpokeAdapter>, spoke_id: u32) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::PinSpokeAdapter { spoke_id },
+        )?;
+        let registry = &mut ctx.accounts.registry;
+        let len = registry.spokes_len as usize;
+        let mut idx = None;
+        for i in 0..len {
+            if registry.spokes[i].spoke_id == spoke_id {
+                idx = Some(i);
+                break;
+            }
+        }
+        let i = idx.ok_or_else(|| error!(ErrorCode::AdapterNotAllowed))?;
+        let adapter = ctx.accounts.adapter_program.key();
+        require_keys_eq!(
+            registry.spokes[i].adapter_program,
+            adapter,
+            ErrorCode::AdapterNotAllowed
+        );
+        let (expected_program_data, _) = Pubkey::find_program_address(
+            &[adapter.as_ref()],
+            &anchor_lang::solana_program::bpf_loader_upgradeable::ID,
+        );
+        require_keys_eq!(
+            ctx.accounts.adapter_program_data.key(),
+            expected_program_data,
+            ErrorCode::AdapterCodeChanged
+        );
+        let program_data = &ctx.accounts.adapter_program_data;
+        let entry = &mut registry.spokes[i];
+        entry.adapter_program_data = expected_program_data;
+        entry.adapter_deploy_slot = program_data.slot;
+        entry.adapter_upgrade_authority =
+            program_data.upgrade_authority_address.unwrap_or_default();
+        emit!(AdapterPinned {
+            spoke_id,
+            adapter_program: adapter,
+            program_data: expected_program_data,
+            deploy_slot: entry.adapter_deploy_slot,
+            upgrade_authority: entry.adapter_upgrade_authority,
+        });
+        Ok(())
+    }
+
+    /// Source leg of the relayed forward path: the user deposits `amount` (plus an optional
+    /// lamport budget for a lamport relayer fee) into a per-(user, nonce) escrow owned by a
+    /// router PDA. forward_via_spoke later pulls from the escrow without the user's signature.
+    #[allow(clippy::too_many_arguments)]
+    pub fn deposit_forward_escrow(
+        ctx: Context<DepositForwardEscrow>,
+        nonce: u64,
+        spoke_id: u32,
+        amount: u64,
+        dst_domain: u32,
+        recipient: Recipient,
+        lamport_budget: u64,
+        relayer_tip: u64,
+        expiry_reminder_slot: u64,
+    ) -> Result<()> {
+        ctx.accounts
+            .config
+            .require_family_active(INSTRUCTION_FAMILY_BRIDGE)?;
+        require!(!ctx.accounts.config.paused, ErrorCode::Paused);
+        ctx.accounts.config.require_feature(FEATURE_ESCROW)?;
+        require!(amount > 0, ErrorCode::ZeroAmount);
+        let recipient = recipient.to_core();
+        recipient::check_for_domain(&recipient, dst_domain).map_err(ErrorCode::from)?;
+        let slot = Clock::get()?.slot;
+        require!(
+            expiry_reminder_slot == 0 || expiry_reminder_slot > slot,
+            ErrorCode::InvalidExpiryReminder
+        );
+        // Effects first: record the intent before moving funds
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.user = ctx.accounts.user.key();
+        escrow.mint = ctx.accounts.mint.key();
+        escrow.spoke_id = spoke_id;
+        escrow.amount = amount;
+        escrow.forwarded = 0;
+        escrow.dst_domain = dst_domain;
+        escrow.mint_recipient = recipient.word();
+        escrow.nonce = nonce;
+        escrow.lamport_budget = lamport_budget;
+        escrow.created_at_slot = slot;
+        escrow.bump = ctx.bumps.get("escrow").copied().unwrap();
+        escrow.cancelled = false;
+        escrow.relayer_tip = relayer_tip;
+        escrow.expiry_reminder_slot = expiry_reminder_slot;
+        escrow.held_protocol_fee = 0;
+        escrow.held_relayer_fee = 0;
+        escrow.fee_payee = Pubkey::default();
+        escrow.fee_payee_direct = false;
+        escrow.recipient_tag = recipient.tag();
+        let rent_ledger = ctx.accounts.rent_ledger.to_account_info();
+        record_rent(
+            &rent_ledger,
+            ctx.program_id,
+            RENT_KIND_ESCROW,
+            FORWARD_ESCROW_SPACE,
+            true,
+        )?;
+        record_rent(
+            &rent_ledger,
+            ctx.program_id,
+            RENT_KIND_VAULT,
+            TokenAccount::LEN,
+            true,
+        )?;
+        token::transfer(
+            CpiContext::new(
+                token_cpi_program(&ctx.accounts.token_program)?,
+                token::Transfer {
+                    from: ctx.accounts.from.to_account_info(),
+                    to: ctx.accounts.escrow_vault.to_account_info(),
+                    authority: ctx.accounts.user.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+        let escrowed_lamports = lamport_budget
+            .checked_add(relayer_tip)
+            .ok_or(ErrorCode::MathOverflow)?;
+        if escrowed_lamports > 0 {
+            anchor_lang::solana_program::program::invoke(
+                &system_instruction::transfer(
+                    &ctx.accounts.user.key(),
+                    &ctx.accounts.escrow.key(),
+                    escrowed_lamports,
+                ),
+                &[
+                    ctx.accounts.user.to_account_info(),
+                    ctx.accounts.escrow.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+            )?;
+        }
+        emit!(ForwardEscrowed {
+            user: ctx.accounts.user.key(),
+            mint: ctx.accounts.mint.key(),
+            spoke_id,
+            nonce,
+            amount,
+            lamport_budget,
+            escrow: ctx.accounts.escrow.key(),
+            relayer_tip,
+        });
+        Ok(())
+    }
+
+    /// Return an unforwarded escrow (tokens and lamport budget) to its depositor.
+    pub fn reclaim_forward_escrow(ctx: Context<ReclaimForwardEscrow>) -> Result<()> {
+        ctx.accounts
+            .config
+            .require_family_active(INSTRUCTION_FAMILY_WITHDRAW)?;
+        refund_forward_escrow(
+            &ctx.accounts.config,
+            &ctx.accounts.escrow,
+            &ctx.accounts.escrow_vault,
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.destination.to_account_info(),
+            &ctx.accounts.rent_ledger.to_account_info(),
+            &ctx.accounts.token_program,
+            ctx.program_id,
+        )
+    }
+
+    /// Permissionless crank for expiry notifications: every ForwardEscrow passed in
+    /// remaining_accounts past its reminder slot emits EscrowExpiringSoon or EscrowExpired.
+    /// Other accounts (and cancelled escrows) are skipped.
+    pub fn scan_escrow_expiry(ctx: Context<ScanEscrowExpiry>) -> Result<()> {
+        let slot = time::now(ctx.remaining_accounts, ctx.program_id)?.slot;
+        for ai in ctx.remaining_accounts.iter() {
+            if ai.owner != ctx.program_id {
+                continue;
+            }
+            let Ok(escrow) = ForwardEscrow::try_deserialize(&mut &ai.try_borrow_data()?[..]) else {
+                continue;
+            };
+            if escrow.cancelled {
+                continue;
+            }
+            let remaining = escrow.amount.saturating_sub(escrow.forwarded);
+            match escrow.expiry_at(slot) {
+                EscrowExpiry::Active => {}
+                EscrowExpiry::ExpiringSoon { expires_at_slot } => emit!(EscrowExpiringSoon {
+                    user: escrow.user,
+                    nonce: escrow.nonce,
+                    escrow: ai.key(),
+                    remaining,
+                    expires_at_slot,
+                }),
+                EscrowExpiry::Expired { expires_at_slot } => emit!(EscrowExpired {
+                    user: escrow.user,
+                    nonce: escrow.nonce,
+                    escrow: ai.key(),
+                    remaining,
+                    expires_at_slot,
+                }),
+            }
+        }
+        Ok(())
+    }
+
+    /// Refund an expired, unforwarded (or partially forwarded) escrow to the user's own token
+    /// account. Permissionless so notification bots can auto-refund; rent returns to the user.
+    pub fn refund_expired_escrow(ctx: Context<RefundExpiredEscrow>) -> Result<()> {
+        ctx.accounts
+            .config
+            .require_family_active(INSTRUCTION_FAMILY_WITHDRAW)?;
+        let escrow = &ctx.accounts.escrow;
+        require!(!escrow.cancelled, ErrorCode::MessageCancelled);
+        let slot = time::now(ctx.remaining_accounts, ctx.program_id)?.slot;
+        require!(
+            matches!(escrow.expiry_at(slot), EscrowExpiry::Expired { .. }),
+            ErrorCode::EscrowNotExpired
+        );
+        refund_forward_escrow(
+            &ctx.accounts.config,
+            escrow,
+            &ctx.accounts.escrow_vault,
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.cranker.to_account_info(),
+            &ctx.accounts.destination.to_account_info(),
+            &ctx.accounts.rent_ledger.to_account_info(),
+            &ctx.accounts.token_program,
+            ctx.program_id,
+        )
+    }
+
+    /// Raise the lamport tip on an escrow nobody has picked up yet. The whole tip goes to the
+    /// relayer that makes the first dispatch.
+    pub fn increase_relayer_tip(ctx: Context<IncreaseRelayerTip>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::ZeroAmount);
+        let escrow = &mut ctx.accounts.escrow;
+        require!(!escrow.cancelled, ErrorCode::MessageCancelled);
+        require!(escrow.forwarded == 0, ErrorCode::MessageAlreadyForwarded);
+        escrow.relayer_tip = escrow
+            .relayer_tip
+            .checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let (user, nonce, relayer_tip) = (escrow.user, escrow.nonce, escrow.relayer_tip);
+        anchor_lang::solana_program::program::invoke(
+            &system_instruction::transfer(&user, &ctx.accounts.escrow.key(), amount),
+            &[
+                ctx.accounts.user.to_account_info(),
+                ctx.accounts.escrow.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+        emit!(RelayerTipIncreased {
+            user,
+            nonce,
+            added: amount,
+            relayer_tip,
+        });
+        Ok(())
+    }
+
+    /// Cancel an escrowed message before any of it is forwarded (e.g. wrong destination).
+    /// Tokens and the lamport budget return to the user; the escrow stays behind as a
+    /// tombstone so the message cannot be forwarded and its nonce cannot be reused.
+    pub fn cancel_message(ctx: Context<CancelMessage>) -> Result<()> {
+        let escrow = &ctx.accounts.escrow;
+        require!(!escrow.cancelled, ErrorCode::MessageCancelled);
+        require!(escrow.forwarded == 0, ErrorCode::MessageAlreadyForwarded);
+        let (user, nonce, bump) = (escrow.user, escrow.nonce, escrow.bump);
+        let lamport_budget = escrow.lamport_budget;
+        let refund = lamport_budget + escrow.relayer_tip;
+        // Effects: tombstone before funds move
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.cancelled = true;
+        escrow.lamport_budget = 0;
+        escrow.relayer_tip = 0;
+        let nonce_le = nonce.to_le_bytes();
+        let seeds: &[&[u8]] = &[b"forward_escrow", user.as_ref(), &nonce_le, &[bump]];
+        let amount = ctx.accounts.escrow_vault.amount;
+        // The escrow stays as a tombstone; only its vault is closed
+        record_rent(
+            &ctx.accounts.rent_ledger.to_account_info(),
+            ctx.program_id,
+            RENT_KIND_VAULT,
+            TokenAccount::LEN,
+            false,
+        )?;
+        let escrow_ai = ctx.accounts.escrow.to_account_info();
+        let user_ai = ctx.accounts.user.to_account_info();
+        let vault_rent = ctx.accounts.escrow_vault.to_account_info().lamports();
+        drain_forward_escrow_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.escrow_vault,
+            &escrow_ai,
+            &escrow_ai,
+            Some((&ctx.accounts.destination.to_account_info(), amount)),
+            seeds,
+        )?;
+        // Lamport refund after the token CPI (see forward_from_escrow)
+        if refund > 0 {
+            **escrow_ai.try_borrow_mut_lamports()? -= refund;
+            **user_ai.try_borrow_mut_lamports()? += refund;
+        }
+        refund_escrow_rent(
+            &ctx.accounts.config,
+            &escrow_ai,
+            &user_ai,
+            &user_ai,
+            &ctx.accounts.rent_ledger.to_account_info(),
+            vault_rent,
+            ctx.program_id,
+        )?;
+        emit!(MessageCancelled {
+            user,
+            nonce,
+            spoke_id: ctx.accounts.escrow.spoke_id,
+            amount,
+            lamport_budget,
+        });
+        Ok(())
+    }
+
+    /// Pay out fees held by FEATURE_FEE_ESCROW once the adapter dispatch is confirmed: the
+    /// protocol fee to hub_protocol_vault, the relayer fee to its recorded payee. Closes the
+    /// escrow when every dispatch is done. Callable by the configured relayer or admin.
+    pub fn confirm_escrow_dispatch(ctx: Context<ConfirmEscrowDispatch>) -> Result<()> {
+        let cfg = &ctx.accounts.config;
+        require!(!cfg.killed, ErrorCode::GlobalKilled);
+        require!(
+            ctx.accounts.authority.key() == cfg.relayer_pubkey
+                || ctx.accounts.authority.key() == cfg.admin,
+            ErrorCode::Unauthorized
+        );
+        let escrow = &ctx.accounts.escrow;
+        require!(escrow.held_fees() > 0, ErrorCode::NoHeldFees);
+        let mint = escrow.mint;
+        let hub_protocol_vault =
+            Account::<TokenAccount>::try_from(&ctx.accounts.hub_protocol_vault)?;
+        let hub_relayer_vault = Account::<TokenAccount>::try_from(&ctx.accounts.hub_relayer_vault)?;
+        verify_hub_vault(
+            &hub_protocol_vault,
+            b"hub_protocol_vault",
+            &mint,
+            ctx.program_id,
+        )?;
+        verify_hub_vault(
+            &hub_relayer_vault,
+            b"hub_relayer_vault",
+            &mint,
+            ctx.program_id,
+        )?;
+        let (protocol_fee, relayer_fee) = (escrow.held_protocol_fee, escrow.held_relayer_fee);
+        let (fee_payee, direct_payout) = (escrow.fee_payee, escrow.fee_payee_direct);
+        if relayer_fee > 0 && direct_payout {
+            check_direct_payout_account(&ctx.accounts.relayer_token_account, &fee_payee, &mint)?;
+        }
+        let (user, nonce, bump) = (escrow.user, escrow.nonce, escrow.bump);
+        let closes = escrow.forwarded == escrow.amount;
+
+        // Effects
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.held_protocol_fee = 0;
+        escrow.held_relayer_fee = 0;
+        if relayer_fee > 0 && !direct_payout {
+            accrue_relayer_fee(
+                &ctx.accounts.authority.to_account_info(),
+                &fee_payee,
+                &ctx.accounts.relayer_vesting_config,
+                &ctx.accounts.relayer_vesting,
+                &ctx.accounts.system_program.to_account_info(),
+                &mint,
+                relayer_fee,
+                time::now(ctx.remaining_accounts, ctx.program_id)?.slot,
+                ctx.program_id,
+            )?;
+        }
+
+        // Interactions
+        let nonce_le = nonce.to_le_bytes();
+        let seeds: &[&[u8]] = &[b"forward_escrow", user.as_ref(), &nonce_le, &[bump]];
+        let relayer_to = if direct_payout {
+            ctx.accounts.relayer_token_account.to_account_info()
+        } else {
+            ctx.accounts.hub_relayer_vault.to_account_info()
+        };
+        if relayer_fee > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    token_cpi_program(&ctx.accounts.token_program)?,
+                    token::Transfer {
+                        from: ctx.accounts.escrow_vault.to_account_info(),
+                        to: relayer_to,
+                        authority: ctx.accounts.escrow.to_account_info(),
+                    },
+                    &[seeds],
+                ),
+                relayer_fee,
+            )?;
+        }
+        let protocol_to = ctx.accounts.hub_protocol_vault.to_account_info();
+        if closes {
+            // Tokens sent to the vault after the final dispatch go with the protocol fee
+            ctx.accounts.escrow_vault.reload()?;
+            let sweep = ctx.accounts.escrow_vault.amount;
+            close_settled_escrow(
+                &ctx.accounts.config,
+                &ctx.accounts.escrow,
+                &ctx.accounts.escrow_vault,
+                &ctx.accounts.user.to_account_info(),
+                &ctx.accounts.authority.to_account_info(),
+                &protocol_to,
+                sweep,
+                &ctx.accounts.rent_ledger.to_account_info(),
+                &ctx.accounts.token_program,
+                ctx.program_id,
+            )?;
+        } else if protocol_fee > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    token_cpi_program(&ctx.accounts.token_program)?,
+                    token::Transfer {
+                        from: ctx.accounts.escrow_vault.to_account_info(),
+                        to: protocol_to,
+                        authority: ctx.accounts.escrow.to_account_info(),
+                    },
+                    &[seeds],
+                ),
+                protocol_fee,
+            )?;
+        }
+        emit!(EscrowFeesSettled {
+            user,
+            nonce,
+            protocol_fee,
+            relayer_fee,
+            fee_payee,
+            closed: closes,
+        });
+        Ok(())
+    }
+
+    /// Return fees held by FEATURE_FEE_ESCROW to the user after the adapter leg failed (the
+    /// adapter refunds the net amount itself). Closes the escrow when every dispatch is done.
+    /// Callable by the configured relayer or admin.
+    pub fn refund_escrow_fees(ctx: Context<RefundEscrowFees>) -> Result<()> {
+        ctx.accounts
+            .config
+            .require_family_active(INSTRUCTION_FAMILY_WITHDRAW)?;
+        let cfg = &ctx.accounts.config;
+        require!(
+            ctx.accounts.authority.key() == cfg.relayer_pubkey
+                || ctx.accounts.authority.key() == cfg.admin,
+            ErrorCode::Unauthorized
+        );
+        let escrow = &ctx.accounts.escrow;
+        require!(escrow.held_fees() > 0, ErrorCode::NoHeldFees);
+        let (protocol_fee, relayer_fee) = (escrow.held_protocol_fee, escrow.held_relayer_fee);
+        let (user, nonce, bump) = (escrow.user, escrow.nonce, escrow.bump);
+        let closes = escrow.forwarded == escrow.amount;
+        let refund = escrow.held_fees();
+
+        // Effects
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.held_protocol_fee = 0;
+        escrow.held_relayer_fee = 0;
+
+        // Interactions
+        let destination = ctx.accounts.destination.to_account_info();
+        if closes {
+            // The closing refund also returns anything else left in the vault
+            let sweep = ctx.accounts.escrow_vault.amount;
+            close_settled_escrow(
+                &ctx.accounts.config,
+                &ctx.accounts.escrow,
+                &ctx.accounts.escrow_vault,
+                &ctx.accounts.user.to_account_info(),
+                &ctx.accounts.authority.to_account_info(),
+                &destination,
+                sweep,
+                &ctx.accounts.rent_ledger.to_account_info(),
+                &ctx.accounts.token_program,
+                ctx.program_id,
+            )?;
+        } else {
+            let nonce_le = nonce.to_le_bytes();
+            let seeds: &[&[u8]] = &[b"forward_escrow", user.as_ref(), &nonce_le, &[bump]];
+            token::transfer(
+                CpiContext::new_with_signer(
+                    token_cpi_program(&ctx.accounts.token_program)?,
+                    token::Transfer {
+                        from: ctx.accounts.escrow_vault.to_account_info(),
+                        to: destination,
+                        authority: ctx.accounts.escrow.to_account_info(),
+                    },
+                    &[seeds],
+                ),
+                refund,
+            )?;
+        }
+        emit!(EscrowFeesRefunded {
+            user,
+            nonce,
+            protocol_fee,
+            relayer_fee,
+            closed: closes,
+        });
+        Ok(())
+    }
+
+    /// Forward via spoke: hub-level fee skimming from the user's forward escrow and CPI into
+    /// the adapter. Relayer-only: the escrow PDA signs the token movements, so the user does
+    /// not co-sign. `amount` must equal the undispatched escrow balance and `dst_domain` /
+    /// `mint_recipient` must match the escrowed intent. A [ForwardSummary] is set as return
+    /// data for CPI callers.
+    #[allow(clippy::too_many_arguments)]
+    pub fn forward_via_spoke(
+        ctx: Context<ForwardViaSpoke>,
+        spoke_id: u32,
+        amount: u64,
+        dst_domain: u32,
+        mint_recipient: [u8; 32],
+        is_protocol_fee: bool,
+        is_relayer_fee: bool,
+        _nonce: u64,
+        relayer_fee_lamports: u64,
+        adapter_payload: Vec<u8>,
+    ) -> Result<()> {
+        forward_from_escrow(
+            ctx,
+            spoke_id,
+            amount,
+            dst_domain,
+            mint_recipient,
+            is_protocol_fee,
+            is_relayer_fee,
+            relayer_fee_lamports,
+            &adapter_payload,
+            false,
+            false,
+            false,
+        )
+    }
+
+    /// Resolve and validate the USDC fast path for `mint` on `spoke_id` (admin-only). The
+    /// spoke's registry slot and every account address forward_usdc_fast touches are cached
+    /// in the [USDC_FAST_PATH_SEED, mint] PDA, so the hot path compares keys instead of
+    /// scanning the registry and deriving PDAs. Re-run after moving the spoke in the registry.
+    pub fn configure_usdc_fast_path(
+        ctx: Context<ConfigureUsdcFastPath>,
+        spoke_id: u32,
+    ) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::ConfigureUsdcFastPath { spoke_id },
+        )?;
+        let cfg = &ctx.accounts.config;
+        let mint = ctx.accounts.mint.key();
+        require!(
+            cfg.accept_any_token || cfg.allowed_token_mint == mint,
+            ErrorCode::InvalidFastPath
+        );
+        let registry = &ctx.accounts.registry;
+        let index = registry.spokes[..registry.spokes_len as usize]
+            .iter()
+            .position(|s| s.spoke_id == spoke_id)
+            .ok_or_else(|| error!(ErrorCode::AdapterNotAllowed))?;
+        let spoke = &registry.spokes[index];
+        require!(spoke.enabled && !spoke.paused, ErrorCode::AdapterNotAllowed);
+        // Pinned adapters and vested relayer fees need accounts the fast path leaves out
+        require!(
+            spoke.adapter_program_data == Pubkey::default() && spoke.direct_payout(cfg),
+            ErrorCode::InvalidFastPath
+        );
+        let target = &ctx.accounts.adapter_target_token_account;
+        require!(
+            target.mint == mint && adapter_target_ok(spoke, &target.owner),
+            ErrorCode::InvalidAdapterTarget
+        );
+        verify_hub_vault(
+            &ctx.accounts.hub_protocol_vault,
+            b"hub_protocol_vault",
+            &mint,
+            ctx.program_id,
+        )?;
+        let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, ctx.program_id).0;
+        let (expected, bump) =
+            Pubkey::find_program_address(&[USDC_FAST_PATH_SEED, mint.as_ref()], ctx.program_id);
+        let fast_ai = ctx.accounts.fast_path.to_account_info();
+        require_keys_eq!(fast_ai.key(), expected, ErrorCode::InvalidFastPath);
+        if fast_ai.owner != ctx.program_id {
+            create_pda_account(
+                &ctx.accounts.authority.to_account_info(),
+                &fast_ai,
+                &ctx.accounts.system_program.to_account_info(),
+                USDC_FAST_PATH_SPACE,
+                &[USDC_FAST_PATH_SEED, mint.as_ref(), &[bump]],
+                ctx.program_id,
+            )?;
+        }
+        let fast_path = UsdcFastPath {
+            mint,
+            spoke_id,
+            spoke_index: index as u8,
+            config: cfg.key(),
+            registry: registry.key(),
+            hub_protocol_vault: ctx.accounts.hub_protocol_vault.key(),
+            adapter_target_token_account: target.key(),
+            mint_pause: pda(&[b"mint_pause", mint.as_ref()]),
+            spoke_relayers: pda(&[b"spoke_relayers", &spoke_id.to_le_bytes()]),
+            router_stats: pda(&[b"router_stats"]),
+            rent_ledger: pda(&[b"rent_ledger"]),
+            bump,
+        };
+        fast_path.try_serialize(&mut &mut fast_ai.try_borrow_mut_data()?[..])?;
+        emit!(UsdcFastPathConfigured {
+            mint,
+            spoke_id,
+            spoke_index: fast_path.spoke_index,
+            adapter_target_token_account: fast_path.adapter_target_token_account,
+        });
+        Ok(())
+    }
+
+    /// forward_via_spoke of a whole escrow over a configured USDC fast path: the spoke comes
+    /// from its cached registry slot and every other account is matched by cached address.
+    /// Live state (kill switch, mint pause, spoke pause and deprecation, relayer allowlist,
+    /// fee caps) is still checked on each call. Supports token relayer fees paid directly
+    /// to the relayer; escrows with lamport fees, held fees or partial dispatches take the
+    /// generic path.
+    pub fn forward_usdc_fast(
+        ctx: Context<ForwardUsdcFast>,
+        is_protocol_fee: bool,
+        is_relayer_fee: bool,
+    ) -> Result<()> {
+        ctx.accounts
+            .config
+            .require_family_active(INSTRUCTION_FAMILY_FORWARD)?;
+        forward_escrow_fast(ctx, is_protocol_fee, is_relayer_fee)
+    }
+
+    /// Batched forward_via_spoke for up to MAX_BATCH_ENTRIES escrows of one mint on one spoke
+    /// (relayer-only). Each entry must be the full undispatched escrow. Every escrow vault is
+    /// swept into hub_batch_vault, then one transfer per fee kind and one aggregated net
+    /// transfer leave it, followed by a single adapter `dispatch_batch` CPI. Remaining accounts:
+    /// (escrow, escrow_vault, user, fee_policy) per entry, then the accounts forwarded to the
+    /// adapter.
+    /// Per-entry Forwarded events are emitted as on the single path. A failing adapter
+    /// reverts the whole batch.
+    pub fn forward_batch_via_spoke<'info>(
+        ctx: Context<'_, '_, '_, 'info, ForwardBatchViaSpoke<'info>>,
+        spoke_id: u32,
+        entries: Vec<BatchEntry>,
+        is_protocol_fee: bool,
+        is_relayer_fee: bool,
+        adapter_payload: Vec<u8>,
+    ) -> Result<()> {
+        ctx.accounts
+            .config
+            .require_family_active(INSTRUCTION_FAMILY_FORWARD)?;
+        forward_batch_from_escrows(
+            ctx,
+            spoke_id,
+            &entries,
+            is_protocol_fee,
+            is_relayer_fee,
+            &adapter_payload,
+        )
+    }
+
+    /// forward_via_spoke for a mint whose hub vaults may not exist yet: the admin, acting as
+    /// relayer, pays for any missing vault and the VaultRegistry before the forward runs.
+    pub fn ensure_vaults_and_forward(
+        ctx: Context<ForwardViaSpoke>,
+        spoke_id: u32,
+        amount: u64,
+        dst_domain: u32,
+        mint_recipient: [u8; 32],
+        is_protocol_fee: bool,
+        is_relayer_fee: bool,
+        relayer_fee_lamports: u64,
+        adapter_payload: Vec<u8>,
+    ) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.relayer.key(),
+            ctx.accounts.config.admin,
+            ErrorCode::Unauthorized
+        );
+        ensure_hub_vaults(
+            &ctx.accounts.relayer.to_account_info(),
+            &ctx.accounts.mint.to_account_info(),
+            &ctx.accounts.hub_protocol_vault,
+            &ctx.accounts.hub_relayer_vault,
+            &ctx.accounts.vault_registry,
+            &ctx.accounts.rent_ledger,
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.token_program.to_account_info(),
+            ctx.program_id,
+        )?;
+        forward_from_escrow(
+            ctx,
+            spoke_id,
+            amount,
+            dst_domain,
+            mint_recipient,
+            is_protocol_fee,
+            is_relayer_fee,
+            relayer_fee_lamports,
+            &adapter_payload,
+            false,
+            false,
+            false,
+        )
+    }
+
+    /// Dispatch `portion` of an escrowed message (e.g. when destination limits force a
+    /// split). Cumulative progress is tracked on the escrow; the escrow is finalized and
+    /// closed by the dispatch that reaches the full amount.
+    #[allow(clippy::too_many_arguments)]
+    pub fn forward_partial(
+        ctx: Context<ForwardViaSpoke>,
+        spoke_id: u32,
+        portion: u64,
+        dst_domain: u32,
+        mint_recipient: [u8; 32],
+        is_protocol_fee: bool,
+        is_relayer_fee: bool,
+        relayer_fee_lamports: u64,
+        adapter_payload: Vec<u8>,
+    ) -> Result<()> {
+        forward_from_escrow(
+            ctx,
+            spoke_id,
+            portion,
+            dst_domain,
+            mint_recipient,
+            is_protocol_fee,
+            is_relayer_fee,
+            relayer_fee_lamports,
+            &adapter_payload,
+            true,
+            false,
+            false,
+        )
+    }
+
+    /// Forward the full escrowed amount through a spoke chosen by weight from the route group
+    /// of the spoke the user escrowed for (see `select_weighted_spoke`). The escrow nonce is
+    /// the selection input, so consecutive nonces rotate across the group in proportion to
+    /// the weights. `dst_domain` / `mint_recipient` must still match the escrow.
+    #[allow(clippy::too_many_arguments)]
+    pub fn forward_balanced(
+        ctx: Context<ForwardViaSpoke>,
+        dst_domain: u32,
+        mint_recipient: [u8; 32],
+        is_protocol_fee: bool,
+        is_relayer_fee: bool,
+        relayer_fee_lamports: u64,
+        adapter_payload: Vec<u8>,
+    ) -> Result<()> {
+        ctx.accounts.config.require_feature(FEATURE_AUTO_ROUTE)?;
+        let escrow = &ctx.accounts.escrow;
+        let nonce = escrow.nonce;
+        let amount = escrow
+            .amount
+            .checked_sub(escrow.forwarded)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let registry = &ctx.accounts.registry;
+        let spokes = &registry.spokes[..registry.spokes_len as usize];
+        let route_group = spokes
+            .iter()
+            .find(|s| s.spoke_id == escrow.spoke_id)
+            .map(|s| s.route_group)
+            .filter(|g| *g != 0)
+            .ok_or_else(|| error!(ErrorCode::NoBalancedRoute))?;
+        let slot = Clock::get()?.slot;
+        let mut candidates: Vec<(u32, u16)> = spokes
+            .iter()
+            .filter(|s| s.route_group == route_group && spoke_usable(s, slot))
+            .map(|s| (s.spoke_id, s.weight))
+            .collect();
+        candidates.sort_unstable_by_key(|(id, _)| *id);
+        let selection = select_weighted_spoke(&candidates, nonce)
+            .ok_or_else(|| error!(ErrorCode::NoBalancedRoute))?;
+        emit!(SpokeSelected {
+            route_group,
+            nonce,
+            requested_spoke_id: escrow.spoke_id,
+            spoke_id: selection.spoke_id,
+            weight: selection.weight,
+            ticket: selection.ticket,
+            total_weight: selection.total_weight,
+            candidates: candidates.len() as u8,
+        });
+        forward_from_escrow(
+            ctx,
+            selection.spoke_id,
+            amount,
+            dst_domain,
+            mint_recipient,
+            is_protocol_fee,
+            is_relayer_fee,
+            relayer_fee_lamports,
+            &adapter_payload,
+            false,
+            true,
+            false,
+        )
+    }
+
+    /// Forward the full escrowed amount through the escrow's spoke, or through its designated
+    /// fallback while the primary is paused or disabled (one hop; see resolve_spoke_route).
+    /// `dst_domain` / `mint_recipient` must still match the escrow.
+    #[allow(clippy::too_many_arguments)]
+    pub fn forward_auto(
+        ctx: Context<ForwardViaSpoke>,
+        dst_domain: u32,
+        mint_recipient: [u8; 32],
+        is_protocol_fee: bool,
+        is_relayer_fee: bool,
+        relayer_fee_lamports: u64,
+        adapter_payload: Vec<u8>,
+    ) -> Result<()> {
+        ctx.accounts.config.require_feature(FEATURE_AUTO_ROUTE)?;
+        let escrow = &ctx.accounts.escrow;
+        let primary_spoke_id = escrow.spoke_id;
+        let amount = escrow
+            .amount
+            .checked_sub(escrow.forwarded)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let registry = &ctx.accounts.registry;
+        let spoke_id = resolve_spoke_route(
+            &registry.spokes[..registry.spokes_len as usize],
+            primary_spoke_id,
+            Clock::get()?.slot,
+        )
+        .ok_or_else(|| error!(ErrorCode::AdapterNotAllowed))?;
+        if spoke_id != primary_spoke_id {
+            emit!(RoutedViaFallback {
+                nonce: escrow.nonce,
+                primary_spoke_id,
+                fallback_spoke_id: spoke_id,
+            });
+        }
+        forward_from_escrow(
+            ctx,
+            spoke_id,
+            amount,
+            dst_domain,
+            mint_recipient,
+            is_protocol_fee,
+            is_relayer_fee,
+            relayer_fee_lamports,
+            &adapter_payload,
+            false,
+            true,
+            false,
+        )
+    }
+
+    /// Forward the full escrowed amount as ordered by an EIP-712 bridge intent signed by a
+    /// registered intent signer (see `intent`). Anyone may submit: the signature replaces the
+    /// relayer check, the intent must name this escrow and the keccak of `adapter_payload`, and
+    /// the submitter earns the intent's lamport fee plus any tip. The protocol fee is always
+    /// charged and no token relayer fee is skimmed. The IntentSigners PDA is passed as a
+    /// remaining account.
+    pub fn forward_with_intent(
+        ctx: Context<ForwardViaSpoke>,
+        intent: BridgeIntent,
+        signature: [u8; 65],
+        adapter_payload: Vec<u8>,
+    ) -> Result<()> {
+        let escrow = &ctx.accounts.escrow;
+        require!(
+            intent.user == escrow.user
+                && intent.mint == escrow.mint
+                && intent.escrow_nonce == escrow.nonce,
+            ErrorCode::EscrowMismatch
+        );
+        require!(
+            keccak256(&[&adapter_payload]) == intent.payload_hash,
+            ErrorCode::IntentPayloadMismatch
+        );
+        let now = time::now(ctx.remaining_accounts, ctx.program_id)?.unix_timestamp;
+        require!(
+            u64::try_from(now).is_ok_and(|now| now <= intent.deadline),
+            ErrorCode::IntentExpired
+        );
+        let digest = intent::digest(
+            intent::domain_separator(ctx.accounts.config.src_chain_id, ctx.program_id.to_bytes()),
+            &intent,
+        );
+        let signer = intent::recover_signer(&digest, &signature)
+            .ok_or_else(|| error!(ErrorCode::InvalidIntentSignature))?;
+        require!(
+            intent_signer_registered(ctx.remaining_accounts, &signer, ctx.program_id)?,
+            ErrorCode::IntentSignerNotRegistered
+        );
+        emit!(BridgeIntentExecuted {
+            digest,
+            signer,
+            user: intent.user,
+            escrow_nonce: intent.escrow_nonce,
+            submitter: ctx.accounts.relayer.key(),
+        });
+        forward_from_escrow(
+            ctx,
+            intent.spoke_id,
+            intent.amount,
+            intent.dst_domain,
+            intent.mint_recipient,
+            true,
+            false,
+            intent.relayer_fee_lamports,
+            &adapter_payload,
+            false,
+            false,
+            true,
+        )
+    }
+
+    /// Dry run: CPI the spoke adapter's `validate_payload` with the caller-supplied
+    /// (simulated) accounts before any funds move. Intended as the first instruction of a
+    /// transaction whose next instruction performs the real dispatch; a rejection aborts
+    /// both. Return data is `[ok(1)]`.
+    pub fn dry_run_adapter<'info>(
+        ctx: Context<'_, '_, '_, 'info, DryRunAdapter<'info>>,
+        spoke_id: u32,
+        adapter_payload: Vec<u8>,
+    ) -> Result<()> {
+        let registry = &ctx.accounts.registry;
+        let spoke = registry.spokes[..registry.spokes_len as usize]
+            .iter()
+            .find(|s| s.spoke_id == spoke_id)
+            .ok_or_else(|| error!(ErrorCode::AdapterNotAllowed))?;
+        require!(spoke.enabled && !spoke.paused, ErrorCode::AdapterNotAllowed);
+        require_keys_eq!(
+            ctx.accounts.adapter_program.key(),
+            spoke.adapter_program,
+            ErrorCode::AdapterNotAllowed
+        );
+        validate_payload_len(adapter_payload.len())?;
+        validate_payload_schema(&spoke.payload_schema, &adapter_payload)?;
+
+        // Simulated accounts are forwarded read-only and never as signers, so the adapter
+        // cannot move funds while validating
+        let metas = ctx
+            .remaining_accounts
+            .iter()
+            .map(|a| AccountMeta::new_readonly(a.key(), false))
+            .collect();
+        let ix = anchor_lang::solana_program::instruction::Instruction {
+            program_id: spoke.adapter_program,
+            accounts: metas,
+            data: adapter_validate_payload_data(&adapter_payload)?,
+        };
+        let mut infos = ctx.remaining_accounts.to_vec();
+        infos.push(ctx.accounts.adapter_program.to_account_info());
+        let guard = AdapterCuGuard::enter(spoke_id, spoke.adapter_program, spoke.max_adapter_cu)?;
+        anchor_lang::solana_program::program::invoke(&ix, &infos)?;
+        guard.exit()?;
+        require!(
+            adapter_validation_passed(get_return_data(), &spoke.adapter_program),
+            ErrorCode::AdapterPayloadRejected
+        );
+        set_return_data(&[1u8]);
+        Ok(())
+    }
+
+    /// Destination finalize path (stateless): mark message replay and emit telemetry.
+    /// No token movement. Creates the replay PDA at seeds (b"replay", message_hash) owned by this
+    /// program, recording the finalizing relayer and the optional origin-chain tx hash.
+    #[allow(clippy::too_many_arguments)]
+    pub fn finalize_message_v1(
+        ctx: Context<FinalizeMessageV1>,
+        message_hash: [u8; 32],
+        src_chain_id: u64,
+        dst_chain_id: u64,
+        forwarded_amount: u64,
+        nonce: u64,
+        payload_hash: [u8; 32],
+        src_adapter: Pubkey,
+        asset_mint: Pubkey,
+        _initiator: Pubkey,
+        origin_tx_hash: Option<[u8; 32]>,
+        validity: Option<MessageValidity>,
+    ) -> Result<()> {
+        finalize_message(
+            ctx.accounts,
+            ctx.remaining_accounts,
+            ctx.program_id,
+            message_hash,
+            src_chain_id,
+            dst_chain_id,
+            forwarded_amount,
+            nonce,
+            payload_hash,
+            src_adapter,
+            asset_mint,
+            origin_tx_hash,
+            validity,
+        )
+    }
+
+    /// Permissionless crank for inbound messages the configured relayer has not finalized.
+    /// Runs exactly the finalize_message_v1 checks, then pays the caller the mint's
+    /// CrankReward out of hub_protocol_vault, capped by the remaining budget and the vault
+    /// balance. The configured relayer can crank too but earns no reward.
+    #[allow(clippy::too_many_arguments)]
+    pub fn crank_finalize_message_v1(
+        ctx: Context<CrankFinalizeMessageV1>,
+        message_hash: [u8; 32],
+        src_chain_id: u64,
+        dst_chain_id: u64,
+        forwarded_amount: u64,
+        nonce: u64,
+        payload_hash: [u8; 32],
+        src_adapter: Pubkey,
+        asset_mint: Pubkey,
+        _initiator: Pubkey,
+        origin_tx_hash: Option<[u8; 32]>,
+        validity: Option<MessageValidity>,
+    ) -> Result<()> {
+        finalize_message(
+            &ctx.accounts.finalize,
+            ctx.remaining_accounts,
+            ctx.program_id,
+            message_hash,
+            src_chain_id,
+            dst_chain_id,
+            forwarded_amount,
+            nonce,
+            payload_hash,
+            src_adapter,
+            asset_mint,
+            origin_tx_hash,
+            validity,
+        )?;
+        let cfg = &ctx.accounts.finalize.config;
+        require!(!cfg.killed, ErrorCode::GlobalKilled);
+        let mint = ctx.accounts.mint.key();
+        require_keys_eq!(mint, asset_mint, ErrorCode::InvalidCrankReward);
+        let cranker = ctx.accounts.finalize.relayer.key();
+        if cranker == cfg.relayer_pubkey {
+            return Ok(());
+        }
+        let bump = verify_hub_vault(
+            &ctx.accounts.hub_protocol_vault,
+            b"hub_protocol_vault",
+            &mint,
+            ctx.program_id,
+        )?;
+        let crank_reward = &mut ctx.accounts.crank_reward;
+        let amount = crank_reward
+            .amount
+            .min(crank_reward.budget)
+            .min(ctx.accounts.hub_protocol_vault.amount);
+        if amount == 0 {
+            return Ok(());
+        }
+        crank_reward.budget -= amount;
+        let budget_left = crank_reward.budget;
+        let signer_seeds: &[&[&[u8]]] = &[&[b"hub_protocol_vault", mint.as_ref(), &[bump]]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                token_cpi_program(&ctx.accounts.token_program)?,
+                token::Transfer {
+                    from: ctx.accounts.hub_protocol_vault.to_account_info(),
+                    to: ctx.accounts.crank_token_account.to_account_info(),
+                    authority: ctx.accounts.hub_protocol_vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+        emit!(CrankRewardPaid {
+            message_hash,
+            mint,
+            cranker,
+            amount,
+            budget_left,
+        });
+        Ok(())
+    }
+
+    /// Read-only audit snapshot of the materialized state (permissionless). Emits one
+    /// StateSnapshotConfig, one StateSnapshotSpokes, and a StateSnapshotVault per
+    /// VaultRegistry passed in remaining accounts.
+    pub fn emit_state_snapshot(ctx: Context<EmitStateSnapshot>) -> Result<()> {
+        let cfg = &ctx.accounts.config;
+        let registry = &ctx.accounts.registry;
+        let slot = Clock::get()?.slot;
+        emit!(StateSnapshotConfig {
+            slot,
+            admin: cfg.admin,
+            fee_recipient: cfg.fee_recipient,
+            src_chain_id: cfg.src_chain_id,
+            relayer_fee_bps: cfg.relayer_fee_bps,
+            protocol_fee_bps: cfg.protocol_fee_bps,
+            relayer_pubkey: cfg.relayer_pubkey,
+            accept_any_token: cfg.accept_any_token,
+            allowed_token_mint: cfg.allowed_token_mint,
+            direct_relayer_payout_default: cfg.direct_relayer_payout_default,
+            min_forward_amount: cfg.min_forward_amount,
+            adapters: cfg.adapters[..cfg.adapters_len as usize].to_vec(),
+            paused: cfg.paused,
+            max_relayer_fee_lamports: cfg.max_relayer_fee_lamports,
+            usd_limits_enabled: cfg.usd_limits_enabled,
+            strict_vaults: cfg.strict_vaults,
+            lp_fee_bps: cfg.lp_fee_bps,
+            lp_vault_program: cfg.lp_vault_program,
+            killed: cfg.killed,
+        });
+        emit!(StateSnapshotSpokes {
+            slot,
+            spokes_len: registry.spokes_len,
+            spokes: registry.spokes[..registry.spokes_len as usize]
+                .iter()
+                .map(SpokeSnapshot::from)
+                .collect(),
+        });
+        for ai in ctx.remaining_accounts.iter() {
+            require_keys_eq!(*ai.owner, *ctx.program_id, ErrorCode::InvalidVaultPda);
+            let vr = VaultRegistry::try_deserialize(&mut &ai.try_borrow_data()?[..])?;
+            emit!(StateSnapshotVault {
+                slot,
+                vault_registry: ai.key(),
+                mint: vr.mint,
+                protocol_vault: vr.protocol_vault,
+                relayer_vault: vr.relayer_vault,
+                version: vr.version,
+            });
+        }
+        Ok(())
+    }
+
+    /// Indexer resync (permissionless): emit a SpokeEnumerated event for `spoke_id`.
+    pub fn emit_spoke(ctx: Context<ExportRegistry>, spoke_id: u32) -> Result<()> {
+        let registry = &ctx.accounts.registry;
+        let len = registry.spokes_len as usize;
+        let index = registry.spokes[..len]
+            .iter()
+            .position(|s| s.spoke_id == spoke_id)
+            .ok_or_else(|| error!(ErrorCode::AdapterNotAllowed))?;
+        emit_spoke_enumerated(registry, index)
+    }
+
+    /// Indexer resync crank (permissionless): emit a SpokeEnumerated event for each registry
+    /// position in [from, from+count), clamped to the registry length. Page until
+    /// `index + 1 == spokes_len`.
+    pub fn emit_spokes(ctx: Context<ExportRegistry>, from: u8, count: u8) -> Result<()> {
+        let registry = &ctx.accounts.registry;
+        let len = registry.spokes_len as usize;
+        let from = from as usize;
+        let end = from.saturating_add(count as usize).min(len);
+        require!(from < end, ErrorCode::InvalidRegistryChunk);
+        for index in from..end {
+            emit_spoke_enumerated(registry, index)?;
+        }
+        Ok(())
+    }
+
+    /// Disaster-recovery export (permissionless): emit registry entries [start, start+count)
+    /// as one RegistryExportChunk (at most REGISTRY_CHUNK_MAX entries) with its chunk hash and
+    /// the hash of the whole registry, for replay through import_registry.
+    pub fn export_registry(ctx: Context<ExportRegistry>, start: u8, count: u8) -> Result<()> {
+        let registry = &ctx.accounts.registry;
+        let len = registry.spokes_len as usize;
+        let start_i = start as usize;
+        let end = start_i.saturating_add(count as usize).min(len);
+        require!(
+            start_i < end && end - start_i <= REGISTRY_CHUNK_MAX,
+            ErrorCode::InvalidRegistryChunk
+        );
+        let entries = registry.spokes[start_i..end].to_vec();
+        emit!(RegistryExportChunk {
+            slot: Clock::get()?.slot,
+            start,
+            spokes_len: registry.spokes_len,
+            chunk_hash: registry_chunk_hash(start, &entries)?,
+            registry_hash: registry_chunk_hash(0, &registry.spokes[..len])?,
+            entries,
+        });
+        Ok(())
+    }
+
+    /// Disaster-recovery import (admin-only): write `entries` at registry positions
+    /// [start, start+entries.len()) after checking them against `chunk_hash` from the export.
+    /// Chunks must be contiguous with the existing entries; spoke ids stay unique.
+    pub fn import_registry(
+        ctx: Context<SpokeAdmin>,
+        start: u8,
+        entries: Vec<SpokeEntry>,
+        chunk_hash: [u8; 32],
+    ) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::ImportRegistry {
+                start,
+                entries: entries.clone(),
+                chunk_hash,
+            },
+        )?;
+        let cfg = &ctx.accounts.config;
+        require!(
+            registry_chunk_hash(start, &entries)? == chunk_hash,
+            ErrorCode::RegistryChunkHashMismatch
+        );
+        let registry = &mut ctx.accounts.registry;
+        let len = registry.spokes_len as usize;
+        let start_i = start as usize;
+        let end = start_i + entries.len();
+        require!(
+            !entries.is_empty() && start_i <= len && end <= MAX_SPOKES,
+            ErrorCode::InvalidRegistryChunk
+        );
+        for (i, entry) in entries.iter().enumerate() {
+            check_payload_schema_config(&entry.payload_schema)?;
+            let clash = registry.spokes[..len.max(end)]
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j < start_i || *j >= end)
+                .any(|(_, s)| s.spoke_id == entry.spoke_id)
+                || entries[..i].iter().any(|e| e.spoke_id == entry.spoke_id);
+            require!(!clash, ErrorCode::AdapterAlreadyExists);
+        }
+        for (i, entry) in entries.iter().enumerate() {
+            let mut entry = *entry;
+            // As with create_spoke, spokes restored during a global kill stay paused
+            entry.paused |= cfg.killed;
+            registry.spokes[start_i + i] = entry;
+        }
+        registry.spokes_len = len.max(end) as u8;
+        let spokes_len = registry.spokes_len as usize;
+        emit!(RegistryImported {
+            start,
+            count: entries.len() as u8,
+            spokes_len: registry.spokes_len,
+            registry_hash: registry_chunk_hash(0, &registry.spokes[..spokes_len])?,
+        });
+        sync_route_availability(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            Some(&ctx.accounts.registry),
+        )
+    }
+
+    /// Open a multi-transaction route: lock `amount` in a session vault and record the plan
+    /// (`steps_expected` steps hashing to `plan_hash`, see route_plan_hash) and the token
+    /// account that receives the funds on commit. The plan must complete within
+    /// `window_slots` or the session refunds.
+    #[allow(clippy::too_many_arguments)]
+    pub fn begin_route_session(
+        ctx: Context<BeginRouteSession>,
+        nonce: u64,
+        amount: u64,
+        steps_expected: u8,
+        plan_hash: [u8; 32],
+        window_slots: u64,
+    ) -> Result<()> {
+        let cfg = &ctx.accounts.config;
+        require!(!cfg.paused, ErrorCode::Paused);
+        cfg.require_feature(FEATURE_ROUTE_SESSIONS)?;
+        require!(amount > 0, ErrorCode::ZeroAmount);
+        require!(
+            steps_expected > 0 && steps_expected as usize <= MAX_ROUTE_STEPS,
+            ErrorCode::RoutePlanMismatch
+        );
+        require!(
+            window_slots > 0 && window_slots <= MAX_ROUTE_SESSION_WINDOW_SLOTS,
+            ErrorCode::InvalidRouteWindow
+        );
+        let slot = time::now(ctx.remaining_accounts, ctx.program_id)?.slot;
+        let session = &mut ctx.accounts.session;
+        session.user = ctx.accounts.user.key();
+        session.mint = ctx.accounts.mint.key();
+        session.nonce = nonce;
+        session.amount = amount;
+        session.target_token_account = ctx.accounts.target_token_account.key();
+        session.plan_hash = plan_hash;
+        session.steps_expected = steps_expected;
+        session.steps_len = 0;
+        session.steps = [RouteStep::default(); MAX_ROUTE_STEPS];
+        session.begin_slot = slot;
+        session.expiry_slot = slot + window_slots;
+        session.bump = ctx.bumps.get("session").copied().unwrap();
+        token::transfer(
+            CpiContext::new(
+                token_cpi_program(&ctx.accounts.token_program)?,
+                token::Transfer {
+                    from: ctx.accounts.from.to_account_info(),
+                    to: ctx.accounts.session_vault.to_account_info(),
+                    authority: ctx.accounts.user.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+        emit!(RouteSessionBegun {
+            user: session.user,
+            nonce,
+            mint: session.mint,
+            amount,
+            steps_expected,
+            plan_hash,
+            expiry_slot: session.expiry_slot,
+        });
+        Ok(())
+    }
+
+    /// Record one executed step of an open route session (session owner only).
+    pub fn attach_route_step(
+        ctx: Context<AttachRouteStep>,
+        kind: u8,
+        program: Pubkey,
+        data_hash: [u8; 32],
+    ) -> Result<()> {
+        let session = &mut ctx.accounts.session;
+        require!(
+            time::now(ctx.remaining_accounts, ctx.program_id)?.slot <= session.expiry_slot,
+            ErrorCode::RouteSessionExpired
+        );
+        let len = session.steps_len as usize;
+        require!(
+            len < session.steps_expected as usize,
+            ErrorCode::RoutePlanMismatch
+        );
+        session.steps[len] = RouteStep {
+            kind,
+            program,
+            data_hash,
+        };
+        session.steps_len += 1;
+        emit!(RouteStepAttached {
+            user: session.user,
+            nonce: session.nonce,
+            index: len as u8,
+            kind,
+            program,
+        });
+        Ok(())
+    }
+
+    /// Settle a route session (permissionless). Inside the window the attached steps must
+    /// match the plan exactly and the locked funds go to the recorded target; once the
+    /// window has passed the funds return to the user instead.
+    pub fn commit_route_session(ctx: Context<CommitRouteSession>) -> Result<()> {
+        let session = &ctx.accounts.session;
+        let expired = time::now(ctx.remaining_accounts, ctx.program_id)?.slot > session.expiry_slot;
+        if !expired {
+            let steps = &session.steps[..session.steps_len as usize];
+            require!(
+                steps.len() == session.steps_expected as usize
+                    && route_plan_hash(steps) == session.plan_hash,
+                ErrorCode::RoutePlanMismatch
+            );
+        }
+        let (user, nonce, bump) = (session.user, session.nonce, session.bump);
+        let nonce_le = nonce.to_le_bytes();
+        let seeds: &[&[u8]] = &[b"route_session", user.as_ref(), &nonce_le, &[bump]];
+        let amount = ctx.accounts.session_vault.amount;
+        let to = if expired {
+            ctx.accounts.refund_token_account.to_account_info()
+        } else {
+            ctx.accounts.target_token_account.to_account_info()
+        };
+        drain_forward_escrow_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.session_vault,
+            &ctx.accounts.session.to_account_info(),
+            &ctx.accounts.user.to_account_info(),
+            Some((&to, amount)),
+            seeds,
+        )?;
+        if expired {
+            emit!(RouteSessionRefunded {
+                user,
+                nonce,
+                amount
+            });
+        } else {
+            emit!(RouteSessionCommitted {
+                user,
+                nonce,
+                amount,
+                target_token_account: ctx.accounts.target_token_account.key(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Escrow a lamport bounty for finalizing `message_hash` (typically in the same transaction
+    /// as the bridge transfer). Claimable by the finalizing relayer until `expiry_slot`.
+    pub fn post_finalization_bounty(
+        ctx: Context<PostFinalizationBounty>,
+        nonce: u64,
+        message_hash: [u8; 32],
+        amount: u64,
+        expiry_slot: u64,
+    ) -> Result<()> {
+        require!(amount > 0, ErrorCode::ZeroAmount);
+        require!(
+            expiry_slot > time::now(ctx.remaining_accounts, ctx.program_id)?.slot,
+            ErrorCode::BountyExpired
+        );
+        let bounty = &mut ctx.accounts.bounty;
+        bounty.user = ctx.accounts.user.key();
+        bounty.nonce = nonce;
+        bounty.message_hash = message_hash;
+        bounty.amount = amount;
+        bounty.expiry_slot = expiry_slot;
+        bounty.bump = ctx.bumps.get("bounty").copied().unwrap();
+        anchor_lang::solana_program::program::invoke(
+            &system_instruction::transfer(&ctx.accounts.user.key(), &bounty.key(), amount),
+            &[
+                ctx.accounts.user.to_account_info(),
+                bounty.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+        emit!(FinalizationBountyPosted {
+            user: ctx.accounts.user.key(),
+            nonce,
+            message_hash,
+            amount,
+            expiry_slot,
+        });
+        Ok(())
+    }
+
+    /// Pay the bounty to the relayer recorded as finalizer in the message's replay PDA.
+    /// The bounty account's rent returns to the user.
+    pub fn claim_finalization_bounty(ctx: Context<ClaimFinalizationBounty>) -> Result<()> {
+        let bounty = &ctx.accounts.bounty;
+        require!(
+            time::now(ctx.remaining_accounts, ctx.program_id)?.slot <= bounty.expiry_slot,
+            ErrorCode::BountyExpired
+        );
+        let replay_ai = ctx.accounts.replay.to_account_info();
+        replay_guard::verify_address(replay_ai.key, &bounty.message_hash, ctx.program_id)
+            .map_err(ErrorCode::from)?;
+        let finalizer = replay_guard::require_processed(&replay_ai, ctx.program_id)
+            .map_err(ErrorCode::from)?
+            .finalizer
+            .ok_or_else(|| error!(ErrorCode::MessageNotFinalized))?;
+        require_keys_eq!(
+            finalizer,
+            ctx.accounts.relayer.key(),
+            ErrorCode::Unauthorized
+        );
+        let amount = bounty.amount;
+        let (user, nonce, message_hash) = (bounty.user, bounty.nonce, bounty.message_hash);
+        let bounty_ai = ctx.accounts.bounty.to_account_info();
+        **bounty_ai.try_borrow_mut_lamports()? -= amount;
+        **ctx
+            .accounts
+            .relayer
+            .to_account_info()
+            .try_borrow_mut_lamports()? += amount;
+        emit!(FinalizationBountyClaimed {
+            user,
+            nonce,
+            message_hash,
+            relayer: finalizer,
+            amount,
+        });
+        Ok(())
+    }
+
+    /// Return an unclaimed bounty to the user once it has expired.
+    pub fn refund_finalization_bounty(ctx: Context<RefundFinalizationBounty>) -> Result<()> {
+        let bounty = &ctx.accounts.bounty;
+        require!(
+            time::now(ctx.remaining_accounts, ctx.program_id)?.slot > bounty.expiry_slot,
+            ErrorCode::BountyNotExpired
+        );
+        emit!(FinalizationBountyRefunded {
+            user: bounty.user,
+            nonce: bounty.nonce,
+            amount: bounty.amount,
+        });
+        Ok(())
+    }
+
+    /// Create the RouterStats PDA (admin-only). `keeper` may record off-chain observed
+    /// failure counts via record_router_failures.
+    pub fn initialize_router_stats(
+        ctx: Context<InitializeRouterStats>,
+        keeper: Pubkey,
+    ) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::InitializeRouterStats { keeper },
+        )?;
+        let stats = &mut ctx.accounts.router_stats;
+        stats.keeper = keeper;
+        stats.bump = ctx.bumps.get("router_stats").copied().unwrap();
+        Ok(())
+    }
+
+    /// Add `count` failures of error class `class` (keeper or admin).
+    pub fn record_router_failures(
+        ctx: Context<RecordRouterFailures>,
+        class: u8,
+        count: u64,
+    ) -> Result<()> {
+        let stats = &mut ctx.accounts.router_stats;
+        require!(
+            ctx.accounts.authority.key() == stats.keeper
+                || ctx.accounts.authority.key() == ctx.accounts.config.admin,
+            ErrorCode::Unauthorized
+        );
+        let slot = stats
+            .failures
+            .get_mut(class as usize)
+            .ok_or_else(|| error!(ErrorCode::InvalidFailureClass))?;
+        *slot = slot.saturating_add(count);
+        stats.updated_at_slot = Clock::get()?.slot;
+        Ok(())
+    }
+
+    /// Emit the current RouterStats as an event for cheap dashboard scraping (permissionless).
+    pub fn snapshot_router_stats(ctx: Context<SnapshotRouterStats>) -> Result<()> {
+        let stats = &ctx.accounts.router_stats;
+        emit!(RouterStatsSnapshot {
+            total_initiated: stats.total_initiated,
+            total_forwarded: stats.total_forwarded,
+            volumes: stats.volumes[..stats.volumes_len as usize].to_vec(),
+            days: stats.days,
+            failures: stats.failures,
+            updated_at_slot: stats.updated_at_slot,
+        });
+        Ok(())
+    }
+
+    /// Create the RentLedger PDA (admin-only). Accounts created before this are not counted.
+    pub fn initialize_rent_ledger(ctx: Context<InitializeRentLedger>) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::InitializeRentLedger {},
+        )?;
+        let ledger = &mut ctx.accounts.rent_ledger;
+        ledger.buckets = [RentBucket::default(); RENT_KINDS];
+        ledger.updated_at_slot = Clock::get()?.slot;
+        ledger.bump = ctx.bumps.get("rent_ledger").copied().unwrap();
+        Ok(())
+    }
+
+    /// Choose where rent reclaimed from closed escrows goes (admin-only): RENT_REFUND_PAYER,
+    /// RENT_REFUND_TREASURY (needs the rent ledger) or RENT_REFUND_CALLER_SPLIT with
+    /// `caller_bps` to the closing signer.
+    pub fn set_rent_refund_policy(
+        ctx: Context<SetRentRefundPolicy>,
+        policy: u8,
+        caller_bps: u16,
+    ) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::SetRentRefundPolicy { policy, caller_bps },
+        )?;
+        require!(
+            policy <= RENT_REFUND_CALLER_SPLIT && caller_bps <= 10_000,
+            ErrorCode::InvalidRentRefundPolicy
+        );
+        require!(
+            policy == RENT_REFUND_CALLER_SPLIT || caller_bps == 0,
+            ErrorCode::InvalidRentRefundPolicy
+        );
+        if policy == RENT_REFUND_TREASURY {
+            require_keys_eq!(
+                *ctx.accounts.rent_ledger.owner,
+                *ctx.program_id,
+                ErrorCode::InvalidRentLedger
+            );
+        }
+        let cfg = &mut ctx.accounts.config;
+        cfg.rent_refund_policy = policy;
+        cfg.rent_refund_caller_bps = caller_bps;
+        bump_config_version(ctx.remaining_accounts, ctx.program_id, cfg, false)?;
+        emit!(RentRefundPolicyUpdated { policy, caller_bps });
+        Ok(())
+    }
+
+    /// Move rent accrued under RENT_REFUND_TREASURY from the rent ledger to the fee recipient
+    /// (admin-only). The ledger keeps its own rent-exempt minimum.
+    pub fn sweep_rent_treasury(ctx: Context<SweepRentTreasury>) -> Result<()> {
+        ctx.accounts
+            .config
+            .require_family_active(INSTRUCTION_FAMILY_WITHDRAW)?;
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::SweepRentTreasury {},
+        )?;
+        let ledger = ctx.accounts.rent_ledger.to_account_info();
+        let amount = ledger
+            .lamports()
+            .saturating_sub(Rent::get()?.minimum_balance(RENT_LEDGER_SPACE));
+        if amount > 0 {
+            **ledger.try_borrow_mut_lamports()? -= amount;
+            **ctx
+                .accounts
+                .fee_recipient
+                .to_account_info()
+                .try_borrow_mut_lamports()? += amount;
+        }
+        emit!(RentTreasurySwept {
+            fee_recipient: ctx.accounts.fee_recipient.key(),
+            amount,
+        });
+        Ok(())
+    }
+
+    /// Create the AdminAuditLog PDA (admin-only) and make it mandatory for every admin-gated
+    /// instruction by setting FEATURE_ADMIN_AUDIT.
+    pub fn initialize_admin_audit_log(ctx: Context<InitializeAdminAuditLog>) -> Result<()> {
+        let log = &mut ctx.accounts.audit_log;
+        log.head = 0;
+        log.total = 0;
+        log.entries = [AdminAuditEntry::default(); ADMIN_AUDIT_LEN];
+        log.bump = ctx.bumps.get("audit_log").copied().unwrap();
+        log.push(AdminAuditEntry {
+            slot: time::now(ctx.remaining_accounts, ctx.program_id)?.slot,
+            instruction: instruction::InitializeAdminAuditLog::DISCRIMINATOR,
+            authority: ctx.accounts.authority.key(),
+            params_hash: anchor_lang::solana_program::hash::hash(&[]).to_bytes(),
+        });
+        let cfg = &mut ctx.accounts.config;
+        cfg.features |= 1 << FEATURE_ADMIN_AUDIT;
+        bump_config_version(ctx.remaining_accounts, ctx.program_id, cfg, false)?;
+        emit!(FeatureToggled {
+            feature: FEATURE_ADMIN_AUDIT,
+            enabled: true,
+            features: cfg.features,
+        });
+        Ok(())
+    }
+
+    /// Create the ConfigHistory PDA (admin-only) with the current fee parameters as its first
+    /// version, and make it mandatory for every config change by setting FEATURE_CONFIG_HISTORY.
+    pub fn initialize_config_history(ctx: Context<InitializeConfigHistory>) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::InitializeConfigHistory {},
+        )?;
+        let slot = time::now(ctx.remaining_accounts, ctx.program_id)?.slot;
+        let cfg = &mut ctx.accounts.config;
+        cfg.features |= 1 << FEATURE_CONFIG_HISTORY;
+        cfg.config_version = cfg
+            .config_version
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let history = &mut ctx.accounts.history;
+        history.head = 0;
+        history.total = 0;
+        history.entries = [ConfigVersionEntry::default(); CONFIG_HISTORY_LEN];
+        history.bump = ctx.bumps.get("history").copied().unwrap();
+        history.push(ConfigVersionEntry::of(cfg, slot));
+        emit!(FeatureToggled {
+            feature: FEATURE_CONFIG_HISTORY,
+            enabled: true,
+            features: cfg.features,
+        });
+        emit!(ConfigVersionBumped {
+            version: cfg.config_version,
+            slot,
+            recorded: true,
+        });
+        Ok(())
+    }
+
+    /// Emit the last `count` admin audit entries, newest first (permissionless).
+    pub fn emit_admin_audit(ctx: Context<EmitAdminAudit>, count: u8) -> Result<()> {
+        let log = &ctx.accounts.audit_log;
+        emit!(AdminAuditTrail {
+            entries: log.recent().take(count as usize).copied().collect(),
+            total: log.total,
+            slot: Clock::get()?.slot,
+        });
+        Ok(())
+    }
+
+    /// Emit the current rent ledger totals (permissionless).
+    pub fn reconcile_rent(ctx: Context<ReconcileRent>) -> Result<()> {
+        let ledger = &ctx.accounts.rent_ledger;
+        emit!(RentReconciled {
+            buckets: ledger.buckets,
+            total_accounts: ledger.buckets.iter().map(|b| b.accounts).sum(),
+            total_lamports: ledger.buckets.iter().map(|b| b.lamports).sum(),
+            updated_at_slot: ledger.updated_at_slot,
+            slot: Clock::get()?.slot,
+        });
+        Ok(())
+    }
+
+    /// Create the config attestation PDA (admin-only). Populated by attest_config.
+    pub fn initialize_config_attestation(ctx: Context<InitializeConfigAttestation>) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::InitializeConfigAttestation {},
+        )?;
+        let attestation = &mut ctx.accounts.attestation;
+        attestation.root = [0u8; 32];
+        attestation.sequence = 0;
+        attestation.updated_at_slot = 0;
+        attestation.bump = ctx.bumps.get("attestation").copied().unwrap();
+        Ok(())
+    }
+
+    /// Permissionless crank: recompute the keccak commitment over Config + spoke registry.
+    /// When the root differs from the stored one the sequence is bumped and ConfigAttested
+    /// is emitted so relayers can attest the new root to other chains.
+    pub fn attest_config(ctx: Context<AttestConfig>) -> Result<()> {
+        let root = config_commitment(&ctx.accounts.config, &ctx.accounts.registry)?;
+        let attestation = &mut ctx.accounts.attestation;
+        if attestation.root == root && attestation.sequence > 0 {
+            return Ok(());
+        }
+        attestation.root = root;
+        attestation.sequence += 1;
+        attestation.updated_at_slot = Clock::get()?.slot;
+        emit!(ConfigAttested {
+            root,
+            sequence: attestation.sequence,
+            updated_at_slot: attestation.updated_at_slot,
+            src_chain_id: ctx.accounts.config.src_chain_id,
+        });
+        Ok(())
+    }
+
+    /// Create the oracle config for a mint (admin-only). USD amounts use 6 decimals.
+    pub fn initialize_oracle_config(
+        ctx: Context<InitializeOracleConfig>,
+        oracle_program: Pubkey,
+        price_account: Pubkey,
+        max_staleness_slots: u64,
+        max_conf_bps: u16,
+        min_forward_usd_e6: u64,
+    ) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::InitializeOracleConfig {
+                oracle_program,
+                price_account,
+                max_staleness_slots,
+                max_conf_bps,
+                min_forward_usd_e6,
+            },
+        )?;
+        let oc = &mut ctx.accounts.oracle_config;
+        oc.mint = ctx.accounts.mint.key();
+        oc.decimals = ctx.accounts.mint.decimals;
+        oc.oracle_program = oracle_program;
+        oc.price_account = price_account;
+        oc.max_staleness_slots = max_staleness_slots;
+        oc.max_conf_bps = max_conf_bps;
+        oc.min_forward_usd_e6 = min_forward_usd_e6;
+        oc.bump = ctx.bumps.get("oracle_config").copied().unwrap();
+        Ok(())
+    }
+
+    pub fn update_oracle_config(
+        ctx: Context<UpdateOracleConfig>,
+        price_account: Option<Pubkey>,
+        max_staleness_slots: Option<u64>,
+        max_conf_bps: Option<u16>,
+        min_forward_usd_e6: Option<u64>,
+    ) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::UpdateOracleConfig {
+                price_account,
+                max_staleness_slots,
+                max_conf_bps,
+                min_forward_usd_e6,
+            },
+        )?;
+        let oc = &mut ctx.accounts.oracle_config;
+        if let Some(p) = price_account {
+            oc.price_account = p;
+        }
+        if let Some(m) = max_staleness_slots {
+            oc.max_staleness_slots = m;
+        }
+        if let Some(c) = max_conf_bps {
+            oc.max_conf_bps = c;
+        }
+        if let Some(u) = min_forward_usd_e6 {
+            oc.min_forward_usd_e6 = u;
+        }
+        Ok(())
+    }
+
+    /// Create the in-flight counter for a (mint, dst_chain) pair (admin-only).
+    /// `exposure_cap` of 0 means uncapped.
+    pub fn initialize_in_flight(
+        ctx: Context<InitializeInFlight>,
+        dst_chain_id: u64,
+        exposure_cap: u64,
+    ) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::InitializeInFlight {
+                dst_chain_id,
+                exposure_cap,
+            },
+        )?;
+        let in_flight = &mut ctx.accounts.in_flight;
+        in_flight.mint = ctx.accounts.mint.key();
+        in_flight.dst_chain_id = dst_chain_id;
+        in_flight.amount = 0;
+        in_flight.exposure_cap = exposure_cap;
+        in_flight.bump = ctx.bumps.get("in_flight").copied().unwrap();
+        Ok(())
+    }
+
+    /// Update the exposure cap for a (mint, dst_chain) pair (admin-only).
+    pub fn set_exposure_cap(ctx: Context<SetExposureCap>, exposure_cap: u64) -> Result<()> {
+        audit_admin_op(
+            ctx.remaining_accounts,
+            ctx.program_id,
+            &ctx.accounts.config,
+            ctx.accounts.authority.key(),
+            &instruction::SetExposureCap { exposure_cap },
+        )?;
+        let in_flight = &mut ctx.accounts.in_flight;
+        in_flight.exposure_cap = exposure_cap;
+        emit!(InFlightUpdated {
+            mint: in_flight.mint,
+            dst_chain_id: in_flight.dst_chain_id,
+            previous: in_flight.amount,
+            current: in_flight.amount,
+            exposure_cap,
+        });
+        Ok(())
+    }
+
+    /// Decrement the in-flight counter once a message is finalized on the destination
+    /// or refunded to the user. Callable by the configured relayer or admin.
+    pub fn settle_in_flight(ctx: Context<SettleInFlight>, amount: u64) -> Result<()> {
+        let cfg = &ctx.accounts.config;
+        require!(
+            ctx.accounts.authority.key() == cfg.relayer_pubkey
+                || ctx.accounts.authority.key() == cfg.admin,
+            ErrorCode::Unauthorized
+        );
+        let in_flight = &mut ctx.accounts.in_flight;
+        let previous = in_flight.amount;
+        in_flight.amount = previous
+            .checked_sub(amount)
+            .ok_or(ErrorCode::InFlightUnderflow)?;
+        emit!(InFlightUpdated {
+            mint: in_flight.mint,
+            dst_chain_id: in_flight.dst_chain_id,
+            previous,
+            current: in_flight.amount,
+            exposure_cap: in_flight.exposure_cap,
+        });
+        Ok(())
+    }
+}
+
+// ------------ Accounts / Config / Events / Errors ------------
+#[account]
+pub struct Config {
+    pub admin: Pubkey,
+    pub fee_recipient: Pubkey,
+    pub src_chain_id: u64,
+    pub relayer_fee_bps: u16,
+    pub protocol_fee_bps: u16,
+    pub relayer_pubkey: Pubkey,
+    pub accept_any_token: bool,
+    pub allowed_token_mint: Pubkey,
+    pub direct_relayer_payout_default: bool,
+    pub min_forward_amount: u64,
+    pub adapters_len: u8,
+    pub adapters: [Pubkey; 8],
+    pub paused: bool,
+    pub bump: u8,
+    /// Upper bound for lamport-denominated relayer fees in forward_via_spoke (0 disables)
+    pub max_relayer_fee_lamports: u64,
+    /// Enforce per-mint USD minimums from OracleConfig on the source leg
+    pub usd_limits_enabled: bool,
+    /// Only accept hub vaults recorded in a VaultRegistry (created via initialize_hub_vaults)
+    pub strict_vaults: bool,
+    /// Destination-leg LP fee skimmed into hub_lp_vault by settle_dest_lp_fee
+    pub lp_fee_bps: u16,
+    /// zpx_lp_vaults program whose [b"lp_authority"] PDA may withdraw from hub_lp_vault
+    pub lp_vault_program: Pubkey,
+    /// Set by the sentinel's global_kill; only release_global_kill (timelocked) clears it
+    pub killed: bool,
+    /// Slot from which release_global_kill may run; 0 when no release is scheduled
+    pub kill_release_slot: u64,
+    /// Operations key that may pause/unpause individual mints alongside the admin
+    pub guardian: Pubkey,
+    /// Ring buffer of recent admin idempotency keys (0 = empty slot)
+    pub admin_op_keys: [u64; ADMIN_OP_KEYS],
+    /// Next admin_op_keys slot to overwrite
+    pub admin_op_cursor: u8,
+    /// Hash messages without the domain separator while the EVM side migrates
+    pub legacy_message_hash: bool,
+    /// Rollout switches, one bit per FEATURE_* (set = enabled)
+    pub features: u64,
+    /// Partner program CPI'd after every successful source-leg bridge (default = none)
+    pub bridge_hook: Pubkey,
+    /// Skip (instead of failing) when the hook account passed is missing or not executable
+    pub bridge_hook_best_effort: bool,
+    /// Sandbox namespace length; 0 for the canonical instance
+    pub namespace_len: u8,
+    /// Extra seed of this config and its registry (`[b"zpx_config", namespace]`)
+    pub namespace: [u8; SANDBOX_NAMESPACE_LEN],
+    /// Layout version (CONFIG_SCHEMA_VERSION when written); see the `schema` module
+    pub schema_version: u8,
+    /// Where reclaimed escrow rent goes (RENT_REFUND_*)
+    pub rent_refund_policy: u8,
+    /// Caller's share of reclaimed rent under RENT_REFUND_CALLER_SPLIT
+    pub rent_refund_caller_bps: u16,
+    /// Bumped on every config change; carried by Forwarded and FeeAppliedSourceV3 and keyed
+    /// in the ConfigHistory PDA
+    pub config_version: u64,
+    /// Instruction families switched off by set_instruction_pause, one bit per
+    /// INSTRUCTION_FAMILY_* (set = paused)
+    pub paused_families: u8,
+}
+
+pub const ADMIN_OP_KEYS: usize = 8;
+/// Layout version written to new Config accounts. Bump it whenever the Config layout
+/// changes, appending new fields after `schema_version` so its offset stays put.
+pub const CONFIG_SCHEMA_VERSION: u8 = 4;
+/// Layout version written to new Registry accounts.
+pub const REGISTRY_SCHEMA_VERSION: u8 = 1;
+
+/// Longest namespace initialize_sandbox accepts.
+pub const SANDBOX_NAMESPACE_LEN: usize = 16;
+
+/// deposit_forward_escrow (new escrows; existing ones can still be forwarded or reclaimed)
+pub const FEATURE_ESCROW: u8 = 0;
+/// forward_balanced and forward_auto
+pub const FEATURE_AUTO_ROUTE: u8 = 1;
+/// FeeAppliedSourceV2 and FeeAppliedSourceV3 alongside FeeAppliedSource in
+/// universal_bridge_transfer
+pub const FEATURE_V2_EVENTS: u8 = 2;
+/// begin_route_session
+pub const FEATURE_ROUTE_SESSIONS: u8 = 3;
+/// Debug: ForwardBalances with token balances before/after every escrow forward
+pub const FEATURE_BALANCE_EVENTS: u8 = 4;
+/// Hold escrow-mode token fees in the escrow vault until confirm_escrow_dispatch (or return
+/// them with refund_escrow_fees when the adapter leg fails)
+pub const FEATURE_FEE_ESCROW: u8 = 5;
+/// Require the AdminAuditLog on every admin-gated instruction (set by
+/// initialize_admin_audit_log)
+pub const FEATURE_ADMIN_AUDIT: u8 = 6;
+/// Require the ConfigHistory PDA on every config change (set by initialize_config_history)
+pub const FEATURE_CONFIG_HISTORY: u8 = 7;
+/// Require a verified RemoteConfig for every inbound message's source chain at finalize
+pub const FEATURE_REMOTE_CONFIG: u8 = 8;
+pub const FEATURE_COUNT: u8 = 9;
+/// universal_bridge_transfer, universal_bridge_transfer_zpx_fee and deposit_forward_escrow
+pub const INSTRUCTION_FAMILY_BRIDGE: u8 = 0;
+/// Every forward out of an escrow: forward_via_spoke and its variants, the USDC fast path
+/// and batches
+pub const INSTRUCTION_FAMILY_FORWARD: u8 = 1;
+/// Funds leaving the hub outside a route: admin, LP and relayer fee withdrawals, vesting
+/// unlocks, rent sweeps and escrow refunds
+pub const INSTRUCTION_FAMILY_WITHDRAW: u8 = 2;
+/// finalize_message_v1, crank_finalize_message_v1 and settle_dest_lp_fee
+pub const INSTRUCTION_FAMILY_FINALIZE: u8 = 3;
+pub const INSTRUCTION_FAMILY_COUNT: u8 = 4;
+/// Features enabled in a fresh config. Instructions shipped dark leave their bit out.
+pub const FEATURES_DEFAULT: u64 = ((1 << FEATURE_COUNT) - 1)
+    & !(1 << FEATURE_BALANCE_EVENTS)
+    & !(1 << FEATURE_FEE_ESCROW)
+    & !(1 << FEATURE_ADMIN_AUDIT)
+    & !(1 << FEATURE_CONFIG_HISTORY)
+    & !(1 << FEATURE_REMOTE_CONFIG);
+
+impl Config {
+    /// Idempotency for retried admin ops: returns true (after emitting AdminOpReplayed) when
+    /// `key` was already applied, otherwise records it. `None` opts out; 0 is reserved.
+    pub fn replayed_admin_op(&mut self, key: Option<u64>) -> Result<bool> {
+        let Some(key) = key else {
+            return Ok(false);
+        };
+        require!(key != 0, ErrorCode::InvalidIdempotencyKey);
+        if self.admin_op_keys.contains(&key) {
+            emit!(AdminOpReplayed { key });
+            return Ok(true);
+        }
+        let slot = self.admin_op_cursor as usize % ADMIN_OP_KEYS;
+        self.admin_op_keys[slot] = key;
+        self.admin_op_cursor = ((slot + 1) % ADMIN_OP_KEYS) as u8;
+        Ok(false)
+    }
+
+    /// Fee policy of routes without a FeePolicy PDA: the configured bps at every amount.
+    pub fn flat_fee_policy(&self) -> fees::FeePolicy {
+        fees::FeePolicy::flat(self.protocol_fee_bps, self.relayer_fee_bps)
+    }
+
+    /// (payer, treasury, caller) shares of `rent` reclaimed from a closing escrow.
+    pub fn rent_refund_shares(&self, rent: u64) -> (u64, u64, u64) {
+        match self.rent_refund_policy {
+            RENT_REFUND_TREASURY => (0, rent, 0),
+            RENT_REFUND_CALLER_SPLIT => {
+                let caller = fees::bps_fee(rent, self.rent_refund_caller_bps);
+                (rent - caller, 0, caller)
+            }
+            _ => (rent, 0, 0),
+        }
+    }
+
+    pub fn feature_enabled(&self, feature: u8) -> bool {
+        feature < FEATURE_COUNT && self.features & (1 << feature) != 0
+    }
+
+    pub fn require_feature(&self, feature: u8) -> Result<()> {
+        require!(self.feature_enabled(feature), ErrorCode::FeatureDisabled);
+        Ok(())
+    }
+
+    pub fn family_paused(&self, family: u8) -> bool {
+        family < INSTRUCTION_FAMILY_COUNT && self.paused_families & (1 << family) != 0
+    }
+
+    pub fn require_family_active(&self, family: u8) -> Result<()> {
+        require!(
+            !self.family_paused(family),
+            ErrorCode::InstructionFamilyPaused
+        );
+        Ok(())
+    }
+
+    /// Namespace seed appended to the config and registry seeds. Empty for the canonical
+    /// instance, so `[b"zpx_config", namespace_seed()]` is the plain `[b"zpx_config"]` PDA.
+    pub fn namespace_seed(&self) -> &[u8] {
+        &self.namespace[..(self.namespace_len as usize).min(SANDBOX_NAMESPACE_LEN)]
+    }
+
+    /// Hash scheme version new messages are hashed with (0 while legacy_message_hash is set).
+    pub fn message_hash_version(&self) -> u8 {
+        if self.legacy_message_hash {
+            0
+        } else {
+            HASH_SCHEMA_VERSION
+        }
+    }
+}
+
+// space calc: discriminator(8) + admin(32) + fee_recipient(32) + src_chain_id(8) + relayer_fee_bps(2)
+// + protocol_fee_bps(2) + relayer_pubkey(32) + accept_any_token(1) + allowed_token_mint(32)
+// + direct_relayer_payout_default(1) + min_forward_amount(8) + adapters_len(1) + adapters(32*8) + paused(1) + bump(1)
+// + max_relayer_fee_lamports(8) + usd_limits_enabled(1) + strict_vaults(1) + lp_fee_bps(2)
+// + lp_vault_program(32) + killed(1) + kill_release_slot(8) + guardian(32)
+// + admin_op_keys(8*ADMIN_OP_KEYS) + admin_op_cursor(1) + legacy_message_hash(1) + features(8)
+// + bridge_hook(32) + bridge_hook_best_effort(1) + namespace_len(1) + namespace(SANDBOX_NAMESPACE_LEN)
+// + schema_version(1)
+const CONFIG_V1_SPACE: usize = 8
+    + 32
+    + 32
+    + 8
+    + 2
+    + 2
+    + 32
+    + 1
+    + 32
+    + 1
+    + 8
+    + 1
+    + (32 * 8)
+    + 1
+    + 1
+    + 8
+    + 1
+    + 1
+    + 2
+    + 32
+    + 1
+    + 8
+    + 32
+    + (8 * ADMIN_OP_KEYS)
+    + 1
+    + 1
+    + 8
+    + 32
+    + 1
+    + 1
+    + SANDBOX_NAMESPACE_LEN
+    + 1;
+
+// v2: + rent_refund_policy(1) + rent_refund_caller_bps(2)
+// v3: + config_version(8)
+// v4: + paused_families(1)
+const CONFIG_SPACE: usize = CONFIG_V1_SPACE + 1 + 2 + 8 + 1;
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: config PDA; created in the handler so an existing config fails with
+    /// ConfigAlreadyInitialized
+    #[account(mut, seeds = [b"zpx_config"], bump)]
+    pub config: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(namespace: String)]
+pub struct InitializeSandbox<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: namespaced config PDA; created in the handler like initialize_config's
+    #[account(mut, seeds = [b"zpx_config", namespace.as_bytes()], bump)]
+    pub config: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = REGISTRY_SPACE,
+        seeds = [b"hub_registry", namespace.as_bytes()],
+        bump
+    )]
+    pub registry: Box<Account<'info, Registry>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReinitializeConfigDevnet<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// CHECK: raw config PDA; owner and admin verified in handler so stale layouts can be reset
+    #[account(mut, seeds = [b"zpx_config"], bump)]
+    pub config: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AdminWithdraw<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub hub_protocol_vault: Account<'info, TokenAccount>,
+    pub mint: Account<'info, Mint>,
+    #[account(mut, constraint = destination.mint == mint.key())]
+    pub destination: Account<'info, TokenAccount>,
+    /// CHECK: VaultRegistry PDA for mint; may be uninitialized outside strict mode
+    pub vault_registry: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeHubLpVault<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    pub mint: Account<'info, Mint>,
+    /// CHECK: created in handler at the canonical PDA
+    #[account(mut)]
+    pub hub_lp_vault: UncheckedAccount<'info>,
+    /// CHECK: RentLedger PDA; only updated once it has been initialized
+    #[account(mut)]
+    pub rent_ledger: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(message_hash: [u8; 32])]
+pub struct SettleDestLpFee<'info> {
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+    #[account(seeds=[b"zpx_config", config.namespace_seed()], bump=config.bump)]
+    pub config: Account<'info, Config>,
+    pub mint: Account<'info, Mint>,
+    /// CHECK: replay PDA for message_hash; verified processed in handler
+    pub replay: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = relayer,
+        space = 8 + 1,
+        seeds = [b"lp_skim", message_hash.as_ref()],
+        bump
+    )]
+    pub lp_skim: Account<'info, LpFeeSkim>,
+    #[account(mut, constraint = relayer_token_account.mint == mint.key())]
+    pub relayer_token_account: Account<'info, TokenAccount>,
+    #[account(mut, constraint = recipient_token_account.mint == mint.key())]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub hub_lp_vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawLpFees<'info> {
+    /// zpx_lp_vaults [b"lp_authority"] PDA, signing via CPI
+    pub lp_authority: Signer<'info>,
+    #[account(seeds=[b"zpx_config", config.namespace_seed()], bump=config.bump)]
+    pub config: Account<'info, Config>,
+    pub mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub hub_lp_vault: Account<'info, TokenAccount>,
+    #[account(mut, constraint = destination.mint == mint.key())]
+    pub destination: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SetRelayerVesting<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    /// CHECK: RelayerVestingConfig PDA [b"relayer_vesting_config"]; created on first use
+    #[account(mut)]
+    pub vesting_config: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetRouteMaxAge<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    /// CHECK: RouteValidity PDA [b"route_validity", chain_id]; created on first use
+    #[account(mut)]
+    pub route_validity: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetRemoteConfig<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    /// CHECK: RemoteConfig PDA [REMOTE_CONFIG_SEED, src_chain_id]; created on first use
+    #[account(mut)]
+    pub remote_config: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPartner<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    /// CHECK: Partner PDA [b"partner", partner_id]; created on first use
+    #[account(mut)]
+    pub partner: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimPartnerRebate<'info> {
+    pub payout: Signer<'info>,
+    #[account(seeds=[b"zpx_config", config.namespace_seed()], bump=config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(
+        seeds=[b"partner", &partner.partner_id.to_le_bytes()],
+        bump=partner.bump,
+        constraint = partner.payout == payout.key() @ ErrorCode::Unauthorized
+    )]
+    pub partner: Account<'info, Partner>,
+    pub mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        seeds=[b"partner_rebate", &partner.partner_id.to_le_bytes(), mint.key().as_ref()],
+        bump=partner_rebate.bump
+    )]
+    pub partner_rebate: Account<'info, PartnerRebate>,
+    #[account(mut, seeds=[b"partner_rebate_vault", mint.key().as_ref()], bump)]
+    pub partner_rebate_vault: Account<'info, TokenAccount>,
+    #[account(mut, constraint = destination.mint == mint.key())]
+    pub destination: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(src_chain_id: u64)]
+pub struct VerifyRemoteConfig<'info> {
+    pub submitter: Signer<'info>,
+    #[account(
+        mut,
+        seeds=[REMOTE_CONFIG_SEED, &src_chain_id.to_le_bytes()],
+        bump=remote_config.bump
+    )]
+    pub remote_config: Account<'info, RemoteConfig>,
+    /// CHECK: Wormhole core GuardianSet; owner and address checked in load_guardian_set
+    pub guardian_set: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetFeePolicy<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    pub mint: Account<'info, Mint>,
+    /// CHECK: FeePolicy PDA [b"fee_policy", mint, dst_chain_id]; created on first use
+    #[account(mut)]
+    pub fee_policy: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetRelayerCosign<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    pub mint: Account<'info, Mint>,
+    /// CHECK: RelayerCosign PDA [b"relayer_cosign", mint]; created on first use
+    #[account(mut)]
+    pub relayer_cosign: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetRouteDecimals<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    pub mint: Account<'info, Mint>,
+    /// CHECK: RouteDecimals PDA [b"route_decimals", chain_id, mint]; created on first use
+    #[account(mut)]
+    pub route_decimals: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetCrankReward<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    pub mint: Account<'info, Mint>,
+    /// CHECK: CrankReward PDA [CRANK_REWARD_SEED, mint]; created on first use
+    #[account(mut)]
+    pub crank_reward: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureUsdcFastPath<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(seeds=[b"hub_registry", config.namespace_seed()], bump=registry.bump)]
+    pub registry: Box<Account<'info, Registry>>,
+    pub mint: Account<'info, Mint>,
+    pub hub_protocol_vault: Account<'info, TokenAccount>,
+    pub adapter_target_token_account: Account<'info, TokenAccount>,
+    /// CHECK: UsdcFastPath PDA [USDC_FAST_PATH_SEED, mint]; created on first use
+    #[account(mut)]
+    pub fast_path: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ForwardUsdcFast<'info> {
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+    /// CHECK: escrow depositor; receives the escrow and vault rent
+    #[account(mut, address = escrow.user)]
+    pub user: UncheckedAccount<'info>,
+    // Program-owned ForwardEscrow accounts only exist at their PDA, so no seeds check
+    #[account(mut)]
+    pub escrow: Box<Account<'info, ForwardEscrow>>,
+    #[account(mut, constraint = escrow_vault.owner == escrow.key() @ ErrorCode::EscrowMismatch)]
+    pub escrow_vault: Box<Account<'info, TokenAccount>>,
+    pub fast_path: Box<Account<'info, UsdcFastPath>>,
+    #[account(address = fast_path.config)]
+    pub config: Box<Account<'info, Config>>,
+    /// CHECK: the config's registry; only the cached spoke entry is read and written
+    #[account(mut, address = fast_path.registry)]
+    pub registry: UncheckedAccount<'info>,
+    /// CHECK: MintPause PDA for the mint; may be uninitialized (never paused)
+    #[account(address = fast_path.mint_pause)]
+    pub mint_pause: UncheckedAccount<'info>,
+    /// CHECK: SpokeRelayers PDA for the spoke; may be uninitialized (no allowlist)
+    #[account(address = fast_path.spoke_relayers)]
+    pub spoke_relayers: UncheckedAccount<'info>,
+    /// CHECK: canonical hub protocol vault of the mint, verified at configure time
+    #[account(mut, address = fast_path.hub_protocol_vault)]
+    pub hub_protocol_vault: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub relayer_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(mut, address = fast_path.adapter_target_token_account)]
+    pub adapter_target_token_account: Box<Account<'info, TokenAccount>>,
+    /// CHECK: RouterStats PDA; counters are only updated once it has been initialized
+    #[account(mut, address = fast_path.router_stats)]
+    pub router_stats: UncheckedAccount<'info>,
+    /// CHECK: RentLedger PDA; only updated once it has been initialized
+    #[account(mut, address = fast_path.rent_ledger)]
+    pub rent_ledger: UncheckedAccount<'info>,
+    /// CHECK: FeePolicy PDA of the escrow's route; may be uninitialized (config rates)
+    pub fee_policy: UncheckedAccount<'info>,
+    /// CHECK: RelayerCosign PDA [b"relayer_cosign", mint]; may be uninitialized (no co-signing)
+    pub relayer_cosign: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct RelayerClaimFees<'info> {
+    pub relayer: Signer<'info>,
+    #[account(seeds=[b"zpx_config", config.namespace_seed()], bump=config.bump)]
+    pub config: Account<'info, Config>,
+    pub mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        seeds=[b"relayer_vesting", relayer.key().as_ref(), mint.key().as_ref()],
+        bump=relayer_vesting.bump
+    )]
+    pub relayer_vesting: Account<'info, RelayerVesting>,
+    #[account(mut)]
+    pub hub_relayer_vault: Account<'info, TokenAccount>,
+    #[account(mut, constraint = destination.mint == mint.key())]
+    pub destination: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct UnlockRelayerVesting<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds=[b"relayer_vesting", relayer_vesting.relayer.as_ref(), relayer_vesting.mint.as_ref()],
+        bump=relayer_vesting.bump
+    )]
+    pub relayer_vesting: Account<'info, RelayerVesting>,
+}
+
+/// Read-only accounts for preflight. Everything past config and registry is whatever the
+/// client intends to pass to the bridge; wrong addresses are reported, not rejected.
+#[derive(Accounts)]
+pub struct Preflight<'info> {
+    #[account(seeds=[b"zpx_config", config.namespace_seed()], bump=config.bump)]
+    pub config: Box<Account<'info, Config>>,
+    #[account(seeds=[b"hub_registry", config.namespace_seed()], bump=registry.bump)]
+    pub registry: Box<Account<'info, Registry>>,
+    /// CHECK: reported as PREFLIGHT_MINT_INVALID unless an initialized SPL mint
+    pub mint: UncheckedAccount<'info>,
+    /// CHECK: expected to be the user's associated token account for the mint
+    pub from: UncheckedAccount<'info>,
+    /// CHECK: expected to be the (still unused) ForwardEscrow PDA for the route nonce
+    pub escrow: UncheckedAccount<'info>,
+    /// CHECK: MintPause PDA for the mint
+    pub mint_pause: UncheckedAccount<'info>,
+    /// CHECK: MintAllowlist PDA
+    pub mint_allowlist: UncheckedAccount<'info>,
+    /// CHECK: VaultRegistry PDA for the mint
+    pub vault_registry: UncheckedAccount<'info>,
+    /// CHECK: hub protocol fee vault for the mint
+    pub hub_protocol_vault: UncheckedAccount<'info>,
+    /// CHECK: hub relayer fee vault for the mint
+    pub hub_relayer_vault: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyPreimage<'info> {
+    #[account(
+        seeds=[b"message", message.initiator.as_ref(), &message.nonce.to_le_bytes()],
+        bump=message.bump
+    )]
+    pub message: Account<'info, MessagePreimage>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeHubVaults<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    pub mint: Account<'info, Mint>,
+    /// CHECK: created in handler at the canonical PDA
+    #[account(mut)]
+    pub hub_protocol_vault: UncheckedAccount<'info>,
+    /// CHECK: created in handler at the canonical PDA
+    #[account(mut)]
+    pub hub_relayer_vault: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = authority,
+        space = VAULT_REGISTRY_SPACE,
+        seeds = [b"vault_registry", mint.key().as_ref()],
+        bump
+    )]
+    pub vault_registry: Account<'info, VaultRegistry>,
+    /// CHECK: RentLedger PDA; only updated once it has been initialized
+    #[account(mut)]
+    pub rent_ledger: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeRegistry<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = REGISTRY_SPACE,
+        seeds = [b"hub_registry"],
+        bump
+    )]
+    pub registry: Box<Account<'info, Registry>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds=[b"fee_recipient_history"], bump=fee_recipient_history.bump)]
+    pub fee_recipient_history: Account<'info, FeeRecipientHistory>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeFeeRecipientHistory<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = authority,
+        // discriminator(8) + epoch(8) + entries((32 + 8) * N) + bump(1) + grace_slots(8)
+        space = 8 + 8 + (40 * FEE_RECIPIENT_HISTORY_LEN) + 1 + 8,
+        seeds = [b"fee_recipient_history"],
+        bump
+    )]
+    pub fee_recipient_history: Account<'info, FeeRecipientHistory>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AdminConfig<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct OnboardRoute<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    pub mint: Account<'info, Mint>,
+    /// CHECK: created in handler at the canonical PDA
+    #[account(mut)]
+    pub hub_protocol_vault: UncheckedAccount<'info>,
+    /// CHECK: created in handler at the canonical PDA
+    #[account(mut)]
+    pub hub_relayer_vault: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = authority,
+        space = VAULT_REGISTRY_SPACE,
+        seeds = [b"vault_registry", mint.key().as_ref()],
+        bump
+    )]
+    pub vault_registry: Box<Account<'info, VaultRegistry>>,
+    #[account(mut, seeds=[b"mint_allowlist"], bump=mint_allowlist.bump)]
+    pub mint_allowlist: Box<Account<'info, MintAllowlist>>,
+    #[account(mut, seeds=[b"hub_registry", config.namespace_seed()], bump=registry.bump)]
+    pub registry: Box<Account<'info, Registry>>,
+    /// CHECK: FeePolicy PDA [b"fee_policy", mint, route.dst_chain_id]; created in handler
+    #[account(mut)]
+    pub fee_policy: UncheckedAccount<'info>,
+    /// CHECK: RentLedger PDA; only updated once it has been initialized
+    #[account(mut)]
+    pub rent_ledger: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateSpoke<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds=[b"hub_registry", config.namespace_seed()], bump=registry.bump)]
+    pub registry: Box<Account<'info, Registry>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateSpoke<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds=[b"hub_registry", config.namespace_seed()], bump=registry.bump)]
+    pub registry: Box<Account<'info, Registry>>,
+}
+
+#[derive(Accounts)]
+pub struct SetFeature<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct SetMintPause<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(seeds=[b"zpx_config", config.namespace_seed()], bump=config.bump)]
+    pub config: Account<'info, Config>,
+    pub mint: Account<'info, Mint>,
+    /// CHECK: MintPause PDA [b"mint_pause", mint]; created on first pause
+    #[account(mut)]
+    pub mint_pause: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct GlobalKill<'info> {
+    pub sentinel: Signer<'info>,
+    #[account(mut, seeds=[b"zpx_config", config.namespace_seed()], bump=config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds=[b"hub_registry", config.namespace_seed()], bump=registry.bump)]
+    pub registry: Box<Account<'info, Registry>>,
+}
+
+#[derive(Accounts)]
+pub struct RefreshRouteAvailability<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(seeds=[b"zpx_config", config.namespace_seed()], bump=config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(seeds=[b"hub_registry", config.namespace_seed()], bump=registry.bump)]
+    pub registry: Box<Account<'info, Registry>>,
+    /// CHECK: PDA [b"route_availability", namespace]; created on first refresh
+    #[account(mut)]
+    pub route_availability: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PauseSpoke<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds=[b"hub_registry", config.namespace_seed()], bump=registry.bump)]
+    pub registry: Box<Account<'info, Registry>>,
+}
+
+#[derive(Accounts)]
+pub struct ForwardViaSpoke<'info> {
+    /// CHECK: escrow depositor; only receives the escrow rent and lamport remainder
+    #[account(mut, address = escrow.user)]
+    pub user: UncheckedAccount<'info>,
+    /// CHECK: relayer EOA invoking the forward
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+    #[account(address = escrow.mint)]
+    pub mint: Account<'info, Mint>,
+    /// Closed to `user` by the dispatch that completes the escrowed amount
+    #[account(
+        mut,
+        seeds=[b"forward_escrow", escrow.user.as_ref(), &escrow.nonce.to_le_bytes()],
+        bump=escrow.bump
+    )]
+    pub escrow: Box<Account<'info, ForwardEscrow>>,
+    #[account(mut, seeds=[b"forward_escrow_vault", escrow.key().as_ref()], bump)]
+    pub escrow_vault: Box<Account<'info, TokenAccount>>,
+    /// CHECK: canonical hub vault; deserialized in the handler after the VaultsNotInitialized
+    /// check (ensure_vaults_and_forward creates it)
+    #[account(mut)]
+    pub hub_protocol_vault: UncheckedAccount<'info>,
+    /// CHECK: as hub_protocol_vault
+    #[account(mut)]
+    pub hub_relayer_vault: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub relayer_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub adapter_target_token_account: Account<'info, TokenAccount>,
+    #[account(mut, seeds=[b"hub_registry", config.namespace_seed()], bump=registry.bump)]
+    pub registry: Box<Account<'info, Registry>>,
+    #[account(seeds=[b"zpx_config", config.namespace_seed()], bump=config.bump)]
+    pub config: Account<'info, Config>,
+    /// CHECK: adapter message account; blank or adapter-owned, checked in handler
+    #[account(mut)]
+    pub message_account: UncheckedAccount<'info>,
+    /// CHECK: adapter program-data; verified in handler only when the spoke is pinned
+    pub adapter_program_data: UncheckedAccount<'info>,
+    /// CHECK: VaultRegistry PDA for mint; may be uninitialized outside strict mode
+    #[account(mut)]
+    pub vault_registry: UncheckedAccount<'info>,
+    /// CHECK: SpokeRelayers PDA for spoke_id; may be uninitialized (no allowlist)
+    pub spoke_relayers: UncheckedAccount<'info>,
+    /// CHECK: RouterStats PDA; counters are only updated once it has been initialized
+    #[account(mut)]
+    pub router_stats: UncheckedAccount<'info>,
+    /// CHECK: MintPause PDA for mint; may be uninitialized (never paused)
+    pub mint_pause: UncheckedAccount<'info>,
+    /// CHECK: MintAllowlist PDA [b"mint_allowlist"]; consulted when accept_any_token is off
+    pub mint_allowlist: UncheckedAccount<'info>,
+    /// CHECK: RentLedger PDA; only updated once it has been initialized
+    #[account(mut)]
+    pub rent_ledger: UncheckedAccount<'info>,
+    /// CHECK: FeePolicy PDA [b"fee_policy", mint, fee_policy_chain_id(escrow.dst_domain)];
+    /// may be uninitialized (config rates)
+    pub fee_policy: UncheckedAccount<'info>,
+    /// CHECK: RelayerCosign PDA [b"relayer_cosign", mint]; may be uninitialized (no co-signing)
+    pub relayer_cosign: UncheckedAccount<'info>,
+    /// CHECK: ForwardState PDA [b"forward_state", escrow]; created by the relayer on a
+    /// message's first partial dispatch and closed by its last
+    #[account(mut)]
+    pub forward_state: UncheckedAccount<'info>,
+    /// CHECK: RelayerVestingConfig PDA; may be uninitialized (fees unlock immediately)
+    pub relayer_vesting_config: UncheckedAccount<'info>,
+    /// CHECK: RelayerVesting PDA [b"relayer_vesting", relayer, mint]; created by the relayer
+    /// on its first vault-bound fee
+    #[account(mut)]
+    pub relayer_vesting: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: RelayerActivity PDA [b"relayer_activity", relayer]; created by the relayer on
+    /// its first forward
+    #[account(mut)]
+    pub relayer_activity: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ForwardBatchViaSpoke<'info> {
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+    pub mint: Account<'info, Mint>,
+    /// CHECK: canonical hub vault; deserialized in the handler after the VaultsNotInitialized
+    /// check
+    #[account(mut)]
+    pub hub_protocol_vault: UncheckedAccount<'info>,
+    /// CHECK: as hub_protocol_vault
+    #[account(mut)]
+    pub hub_relayer_vault: UncheckedAccount<'info>,
+    /// CHECK: [b"hub_batch_vault", mint] staging token account; created on the mint's first
+    /// batch and verified in the handler
+    #[account(mut)]
+    pub hub_batch_vault: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub relayer_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub adapter_target_token_account: Account<'info, TokenAccount>,
+    #[account(mut, seeds=[b"hub_registry", config.namespace_seed()], bump=registry.bump)]
+    pub registry: Box<Account<'info, Registry>>,
+    #[account(seeds=[b"zpx_config", config.namespace_seed()], bump=config.bump)]
+    pub config: Account<'info, Config>,
+    /// CHECK: must be the spoke's adapter program (checked in handler)
+    pub adapter_program: UncheckedAccount<'info>,
+    /// CHECK: adapter program-data; verified in handler only when the spoke is pinned
+    pub adapter_program_data: UncheckedAccount<'info>,
+    /// CHECK: VaultRegistry PDA for mint; may be uninitialized outside strict mode
+    #[account(mut)]
+    pub vault_registry: UncheckedAccount<'info>,
+    /// CHECK: SpokeRelayers PDA for spoke_id; may be uninitialized (no allowlist)
+    pub spoke_relayers: UncheckedAccount<'info>,
+    /// CHECK: RouterStats PDA; counters are only updated once it has been initialized
+    #[account(mut)]
+    pub router_stats: UncheckedAccount<'info>,
+    /// CHECK: MintPause PDA for mint; may be uninitialized (never paused)
+    pub mint_pause: UncheckedAccount<'info>,
+    /// CHECK: MintAllowlist PDA [b"mint_allowlist"]; consulted when accept_any_token is off
+    pub mint_allowlist: UncheckedAccount<'info>,
+    /// CHECK: RentLedger PDA; only updated once it has been initialized
+    #[account(mut)]
+    pub rent_ledger: UncheckedAccount<'info>,
+    /// CHECK: RelayerCosign PDA [b"relayer_cosign", mint]; may be uninitialized (no co-signing)
+    pub relayer_cosign: UncheckedAccount<'info>,
+    /// CHECK: RelayerVestingConfig PDA; may be uninitialized (fees unlock immediately)
+    pub relayer_vesting_config: UncheckedAccount<'info>,
+    /// CHECK: RelayerVesting PDA [b"relayer_vesting", relayer, mint]
+    #[account(mut)]
+    pub relayer_vesting: UncheckedAccount<'info>,
+    /// CHECK: RelayerActivity PDA [b"relayer_activity", relayer]
+    #[account(mut)]
+    pub relayer_activity: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct PostFinalizationBounty<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        init,
+        payer = user,
+        // discriminator(8) + user(32) + nonce(8) + message_hash(32) + amount(8)
+        // + expiry_slot(8) + bump(1)
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"bounty", user.key().as_ref(), &nonce.to_le_bytes()],
+        bump
+    )]
+    pub bounty: Account<'info, FinalizationBounty>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimFinalizationBounty<'info> {
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+    /// CHECK: bounty poster; receives the bounty account rent
+    #[account(mut, address = bounty.user)]
+    pub user: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        close = user,
+        seeds=[b"bounty", bounty.user.as_ref(), &bounty.nonce.to_le_bytes()],
+        bump=bounty.bump
+    )]
+    pub bounty: Account<'info, FinalizationBounty>,
+    /// CHECK: replay PDA for bounty.message_hash; finalizer read in handler
+    pub replay: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RefundFinalizationBounty<'info> {
+    /// CHECK: bounty poster; receives the bounty and rent (callable by anyone after expiry)
+    #[account(mut, address = bounty.user)]
+    pub user: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        close = user,
+        seeds=[b"bounty", bounty.user.as_ref(), &bounty.nonce.to_le_bytes()],
+        bump=bounty.bump
+    )]
+    pub bounty: Account<'info, FinalizationBounty>,
+}
+
+#[derive(Accounts)]
+pub struct EmitStateSnapshot<'info> {
+    #[account(seeds=[b"zpx_config", config.namespace_seed()], bump=config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(seeds=[b"hub_registry", config.namespace_seed()], bump=registry.bump)]
+    pub registry: Box<Account<'info, Registry>>,
+}
+
+#[derive(Accounts)]
+pub struct SetBridgeHook<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct ExportRegistry<'info> {
+    #[account(seeds=[b"hub_registry"], bump=registry.bump)]
+    pub registry: Box<Account<'info, Registry>>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeRouterStats<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = authority,
+        space = ROUTER_STATS_SPACE,
+        seeds = [b"router_stats"],
+        bump
+    )]
+    pub router_stats: Box<Account<'info, RouterStats>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RecordRouterFailures<'info> {
+    pub authority: Signer<'info>,
+    #[account(seeds=[b"zpx_config", config.namespace_seed()], bump=config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds=[b"router_stats"], bump=router_stats.bump)]
+    pub router_stats: Box<Account<'info, RouterStats>>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeRentLedger<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = authority,
+        space = RENT_LEDGER_SPACE,
+        seeds = [b"rent_ledger"],
+        bump
+    )]
+    pub rent_ledger: Account<'info, RentLedger>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetRentRefundPolicy<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    /// CHECK: RentLedger PDA; must be initialized for RENT_REFUND_TREASURY
+    #[account(seeds = [b"rent_ledger"], bump)]
+    pub rent_ledger: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SweepRentTreasury<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds = [b"rent_ledger"], bump = rent_ledger.bump)]
+    pub rent_ledger: Account<'info, RentLedger>,
+    /// CHECK: lamport receiver; the configured fee recipient
+    #[account(mut, address = config.fee_recipient @ ErrorCode::Unauthorized)]
+    pub fee_recipient: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeAdminAuditLog<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = authority,
+        space = ADMIN_AUDIT_SPACE,
+        seeds = [ADMIN_AUDIT_SEED, config.namespace_seed()],
+        bump
+    )]
+    pub audit_log: Account<'info, AdminAuditLog>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfigHistory<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = authority,
+        space = CONFIG_HISTORY_SPACE,
+        seeds = [CONFIG_HISTORY_SEED, config.namespace_seed()],
+        bump
+    )]
+    pub history: Box<Account<'info, ConfigHistory>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct EmitAdminAudit<'info> {
+    #[account(seeds=[b"zpx_config", config.namespace_seed()], bump=config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(seeds = [ADMIN_AUDIT_SEED, config.namespace_seed()], bump = audit_log.bump)]
+    pub audit_log: Account<'info, AdminAuditLog>,
+}
+
+#[derive(Accounts)]
+pub struct ReconcileRent<'info> {
+    #[account(seeds=[b"rent_ledger"], bump=rent_ledger.bump)]
+    pub rent_ledger: Account<'info, RentLedger>,
+}
+
+#[derive(Accounts)]
+pub struct SnapshotRouterStats<'info> {
+    #[account(seeds=[b"router_stats"], bump=router_stats.bump)]
+    pub router_stats: Box<Account<'info, RouterStats>>,
+}
+
+#[derive(Accounts)]
+#[instruction(adapter: Pubkey)]
+pub struct ProposeAdapter<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = authority,
+        space = ADAPTER_PROPOSAL_SPACE,
+        seeds = [ADAPTER_PROPOSAL_SEED, adapter.as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, AdapterProposal>,
+    /// CHECK: AdapterEntry PDA; must not exist yet
+    #[account(seeds = [ADAPTER_SEED, adapter.as_ref()], bump)]
+    pub adapter_entry: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(adapter: Pubkey)]
+pub struct ActivateAdapter<'info> {
+    /// Pays rent for the AdapterEntry
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [ADAPTER_PROPOSAL_SEED, adapter.as_ref()],
+        bump = proposal.bump,
+        has_one = proposer,
+        close = proposer
+    )]
+    pub proposal: Account<'info, AdapterProposal>,
+    /// CHECK: rent receiver; must be the proposal's proposer
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+    /// CHECK: AdapterEntry PDA [ADAPTER_SEED, adapter]; created here
+    #[account(mut)]
+    pub adapter_entry: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(adapter: Pubkey)]
+pub struct RemoveAdapter<'info> {
+    /// Receives the AdapterEntry rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    /// CHECK: AdapterEntry PDA; uninitialized for adapters still in the legacy array
+    #[account(mut, seeds = [ADAPTER_SEED, adapter.as_ref()], bump)]
+    pub adapter_entry: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateAdapter<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    /// CHECK: AdapterEntry PDA [ADAPTER_SEED, adapter]; created here
+    #[account(mut)]
+    pub adapter_entry: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(adapter: Pubkey)]
+pub struct CancelAdapterProposal<'info> {
+    pub authority: Signer<'info>,
+    #[account(seeds=[b"zpx_config", config.namespace_seed()], bump=config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [ADAPTER_PROPOSAL_SEED, adapter.as_ref()],
+        bump = proposal.bump,
+        has_one = proposer,
+        close = proposer
+    )]
+    pub proposal: Account<'info, AdapterProposal>,
+    /// CHECK: rent receiver; must be the proposal's proposer
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(spoke_id: u32)]
+pub struct CreateSpokeRelayers<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = authority,
+        // discriminator(8) + spoke_id(4) + relayers_len(1) + relayers(32 * MAX) + bump(1)
+        space = 8 + 4 + 1 + (32 * MAX_SPOKE_RELAYERS) + 1,
+        seeds = [b"spoke_relayers".as_ref(), &spoke_id.to_le_bytes()],
+        bump
+    )]
+    pub spoke_relayers: Account<'info, SpokeRelayers>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeMintAllowlist<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = authority,
+        // discriminator(8) + mints_len(1) + mints(32 * MAX) + bump(1)
+        space = 8 + 1 + (32 * MAX_ALLOWED_MINTS) + 1,
+        seeds = [b"mint_allowlist"],
+        bump
+    )]
+    pub mint_allowlist: Account<'info, MintAllowlist>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MintAllowlistAdmin<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds=[b"mint_allowlist"], bump=mint_allowlist.bump)]
+    pub mint_allowlist: Account<'info, MintAllowlist>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeIntentSigners<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = authority,
+        // discriminator(8) + signers_len(1) + signers(20 * MAX) + bump(1)
+        space = 8 + 1 + (20 * MAX_INTENT_SIGNERS) + 1,
+        seeds = [b"intent_signers"],
+        bump
+    )]
+    pub intent_signers: Account<'info, IntentSigners>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct IntentSignersAdmin<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds=[b"intent_signers"], bump=intent_signers.bump)]
+    pub intent_signers: Account<'info, IntentSigners>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeFeeSplit<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = authority,
+        // discriminator(8) + recipients_len(1) + recipients(32 * MAX) + bps(2 * MAX) + bump(1)
+        space = 8 + 1 + (32 * MAX_FEE_SPLIT_RECIPIENTS) + (2 * MAX_FEE_SPLIT_RECIPIENTS) + 1,
+        seeds = [b"fee_split"],
+        bump
+    )]
+    pub fee_split: Account<'info, FeeSplit>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetFeeSplit<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds=[b"fee_split"], bump=fee_split.bump)]
+    pub fee_split: Account<'info, FeeSplit>,
+}
+
+#[derive(Accounts)]
+pub struct SpokeRelayersAdmin<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds=[b"spoke_relayers".as_ref(), &spoke_relayers.spoke_id.to_le_bytes()],
+        bump=spoke_relayers.bump
+    )]
+    pub spoke_relayers: Account<'info, SpokeRelayers>,
+}
+
+#[derive(Accounts)]
+pub struct CloseSpokeRelayers<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        close = authority,
+        seeds=[b"spoke_relayers".as_ref(), &spoke_relayers.spoke_id.to_le_bytes()],
+        bump=spoke_relayers.bump
+    )]
+    pub spoke_relayers: Account<'info, SpokeRelayers>,
+}
+
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct DepositForwardEscrow<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub mint: Account<'info, Mint>,
+    #[account(mut, constraint = from.owner == user.key(), constraint = from.mint == mint.key())]
+    pub from: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = user,
+        space = FORWARD_ESCROW_SPACE,
+        seeds = [b"forward_escrow", user.key().as_ref(), &nonce.to_le_bytes()],
+        bump
+    )]
+    pub escrow: Box<Account<'info, ForwardEscrow>>,
+    #[account(
+        init,
+        payer = user,
+        token::mint = mint,
+        token::authority = escrow,
+        seeds = [b"forward_escrow_vault", escrow.key().as_ref()],
+        bump
+    )]
+    pub escrow_vault: Box<Account<'info, TokenAccount>>,
+    #[account(seeds=[b"zpx_config", config.namespace_seed()], bump=config.bump)]
+    pub config: Account<'info, Config>,
+    /// CHECK: RentLedger PDA; only updated once it has been initialized
+    #[account(mut)]
+    pub rent_ledger: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct ReclaimForwardEscrow<'info> {
+    #[account(mut, address = escrow.user @ ErrorCode::Unauthorized)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        close = user,
+        seeds=[b"forward_escrow", escrow.user.as_ref(), &escrow.nonce.to_le_bytes()],
+        bump=escrow.bump
+    )]
+    pub escrow: Box<Account<'info, ForwardEscrow>>,
+    #[account(mut, seeds=[b"forward_escrow_vault", escrow.key().as_ref()], bump)]
+    pub escrow_vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut, constraint = destination.mint == escrow.mint)]
+    pub destination: Account<'info, TokenAccount>,
+    /// CHECK: RentLedger PDA; only updated once it has been initialized
+    #[account(mut)]
+    pub rent_ledger: UncheckedAccount<'info>,
+    #[account(seeds=[b"zpx_config", config.namespace_seed()], bump=config.bump)]
+    pub config: Box<Account<'info, Config>>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ConfirmEscrowDispatch<'info> {
+    /// Configured relayer or admin; pays for the payee's vesting record if it is new
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(seeds=[b"zpx_config", config.namespace_seed()], bump=config.bump)]
+    pub config: Account<'info, Config>,
+    /// CHECK: escrow depositor; receives its share of the escrow rent when it closes
+    #[account(mut, address = escrow.user)]
+    pub user: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds=[b"forward_escrow", escrow.user.as_ref(), &escrow.nonce.to_le_bytes()],
+        bump=escrow.bump
+    )]
+    pub escrow: Box<Account<'info, ForwardEscrow>>,
+    #[account(mut, seeds=[b"forward_escrow_vault", escrow.key().as_ref()], bump)]
+    pub escrow_vault: Box<Account<'info, TokenAccount>>,
+    /// CHECK: canonical hub vault for escrow.mint; verified in the handler
+    #[account(mut)]
+    pub hub_protocol_vault: UncheckedAccount<'info>,
+    /// CHECK: as hub_protocol_vault
+    #[account(mut)]
+    pub hub_relayer_vault: UncheckedAccount<'info>,
+    /// Direct-payout destination; checked against escrow.fee_payee only when used
+    #[account(mut)]
+    pub relayer_token_account: Account<'info, TokenAccount>,
+    /// CHECK: RelayerVestingConfig PDA; verified in accrue_relayer_fee
+    pub relayer_vesting_config: UncheckedAccount<'info>,
+    /// CHECK: RelayerVesting PDA for (escrow.fee_payee, mint); created on first accrual
+    #[account(mut)]
+    pub relayer_vesting: UncheckedAccount<'info>,
+    /// CHECK: RentLedger PDA; only updated once it has been initialized
+    #[account(mut)]
+    pub rent_ledger: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RefundEscrowFees<'info> {
+    /// Configured relayer or admin
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(seeds=[b"zpx_config", config.namespace_seed()], bump=config.bump)]
+    pub config: Account<'info, Config>,
+    /// CHECK: escrow depositor; receives its share of the escrow rent when it closes
+    #[account(mut, address = escrow.user)]
+    pub user: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds=[b"forward_escrow", escrow.user.as_ref(), &escrow.nonce.to_le_bytes()],
+        bump=escrow.bump
+    )]
+    pub escrow: Box<Account<'info, ForwardEscrow>>,
+    #[account(mut, seeds=[b"forward_escrow_vault", escrow.key().as_ref()], bump)]
+    pub escrow_vault: Box<Account<'info, TokenAccount>>,
+    /// Fees only ever go back to the depositor
+    #[account(
+        mut,
+        constraint = destination.mint == escrow.mint,
+        constraint = destination.owner == escrow.user @ ErrorCode::Unauthorized
+    )]
+    pub destination: Account<'info, TokenAccount>,
+    /// CHECK: RentLedger PDA; only updated once it has been initialized
+    #[account(mut)]
+    pub rent_ledger: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ScanEscrowExpiry<'info> {
+    pub cranker: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RefundExpiredEscrow<'info> {
+    /// Receives the caller share of the rent under RENT_REFUND_CALLER_SPLIT
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+    /// CHECK: escrow depositor; receives the lamport remainder and the payer share of the rent
+    #[account(mut, address = escrow.user)]
+    pub user: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        close = user,
+        seeds=[b"forward_escrow", escrow.user.as_ref(), &escrow.nonce.to_le_bytes()],
+        bump=escrow.bump
+    )]
+    pub escrow: Box<Account<'info, ForwardEscrow>>,
+    #[account(mut, seeds=[b"forward_escrow_vault", escrow.key().as_ref()], bump)]
+    pub escrow_vault: Box<Account<'info, TokenAccount>>,
+    /// Tokens only ever go back to the depositor
+    #[account(
+        mut,
+        constraint = destination.mint == escrow.mint,
+        constraint = destination.owner == escrow.user @ ErrorCode::Unauthorized
+    )]
+    pub destination: Account<'info, TokenAccount>,
+    /// CHECK: RentLedger PDA; only updated once it has been initialized
+    #[account(mut)]
+    pub rent_ledger: UncheckedAccount<'info>,
+    #[account(seeds=[b"zpx_config", config.namespace_seed()], bump=config.bump)]
+    pub config: Box<Account<'info, Config>>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct BeginRouteSession<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub mint: Account<'info, Mint>,
+    #[account(mut, constraint = from.owner == user.key(), constraint = from.mint == mint.key())]
+    pub from: Account<'info, TokenAccount>,
+    #[account(constraint = target_token_account.mint == mint.key())]
+    pub target_token_account: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = user,
+        space = ROUTE_SESSION_SPACE,
+        seeds = [b"route_session", user.key().as_ref(), &nonce.to_le_bytes()],
+        bump
+    )]
+    pub session: Box<Account<'info, RouteSession>>,
+    #[account(
+        init,
+        payer = user,
+        token::mint = mint,
+        token::authority = session,
+        seeds = [b"route_session_vault", session.key().as_ref()],
+        bump
+    )]
+    pub session_vault: Box<Account<'info, TokenAccount>>,
+    #[account(seeds=[b"zpx_config", config.namespace_seed()], bump=config.bump)]
+    pub config: Account<'info, Config>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct AttachRouteStep<'info> {
+    #[account(address = session.user @ ErrorCode::Unauthorized)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds=[b"route_session", session.user.as_ref(), &session.nonce.to_le_bytes()],
+        bump=session.bump
+    )]
+    pub session: Box<Account<'info, RouteSession>>,
+}
+
+#[derive(Accounts)]
+pub struct CommitRouteSession<'info> {
+    /// CHECK: session owner; receives the session and vault rent
+    #[account(mut, address = session.user)]
+    pub user: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        close = user,
+        seeds=[b"route_session", session.user.as_ref(), &session.nonce.to_le_bytes()],
+        bump=session.bump
+    )]
+    pub session: Box<Account<'info, RouteSession>>,
+    #[account(mut, seeds=[b"route_session_vault", session.key().as_ref()], bump)]
+    pub session_vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut, address = session.target_token_account)]
+    pub target_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = refund_token_account.owner == session.user,
+        constraint = refund_token_account.mint == session.mint
+    )]
+    pub refund_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct IncreaseRelayerTip<'info> {
+    #[account(mut, address = escrow.user @ ErrorCode::Unauthorized)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds=[b"forward_escrow", escrow.user.as_ref(), &escrow.nonce.to_le_bytes()],
+        bump=escrow.bump
+    )]
+    pub escrow: Box<Account<'info, ForwardEscrow>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelMessage<'info> {
+    #[account(mut, address = escrow.user @ ErrorCode::Unauthorized)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds=[b"forward_escrow", escrow.user.as_ref(), &escrow.nonce.to_le_bytes()],
+        bump=escrow.bump
+    )]
+    pub escrow: Box<Account<'info, ForwardEscrow>>,
+    #[account(mut, seeds=[b"forward_escrow_vault", escrow.key().as_ref()], bump)]
+    pub escrow_vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut, constraint = destination.mint == escrow.mint)]
+    pub destination: Account<'info, TokenAccount>,
+    /// CHECK: RentLedger PDA; only updated once it has been initialized
+    #[account(mut)]
+    pub rent_ledger: UncheckedAccount<'info>,
+    #[account(seeds=[b"zpx_config", config.namespace_seed()], bump=config.bump)]
+    pub config: Box<Account<'info, Config>>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SpokeAdmin<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds=[b"hub_registry", config.namespace_seed()], bump=registry.bump)]
+    pub registry: Box<Account<'info, Registry>>,
+}
+
+#[derive(Accounts)]
+pub struct AckSpokeFinalized<'info> {
+    pub authority: Signer<'info>,
+    #[account(seeds=[b"zpx_config", config.namespace_seed()], bump=config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds=[b"hub_registry", config.namespace_seed()], bump=registry.bump)]
+    pub registry: Box<Account<'info, Registry>>,
+}
+
+#[derive(Accounts)]
+pub struct PinSpokeAdapter<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds=[b"hub_registry", config.namespace_seed()], bump=registry.bump)]
+    pub registry: Box<Account<'info, Registry>>,
+    /// CHECK: must match the spoke's registered adapter program
+    #[account(executable)]
+    pub adapter_program: UncheckedAccount<'info>,
+    pub adapter_program_data: Account<'info, ProgramData>,
+}
+
+#[derive(Accounts)]
+#[instruction(amount: u64, protocol_fee: u64, relayer_fee: u64, payload: Vec<u8>, dst_chain_id: u64)]
+pub struct UniversalBridgeTransfer<'info> {
+    /// Token owner; only signs, so a sponsored transaction leaves it without SOL needs
+    pub user: Signer<'info>,
+    /// Pays rent for the optional message preimage. Pass `user` unless a partner sponsors
+    /// the transaction (see `sponsored::sponsored_message`).
+    #[account(mut)]
+    pub rent_payer: Signer<'info>,
+    pub mint: Account<'info, Mint>,
+    #[account(mut, constraint = from.owner == user.key(), constraint = from.mint == mint.key())]
+    pub from: Account<'info, TokenAccount>,
+    /// Owner checked in handler against the fee recipient history (grace window aware)
+    #[account(mut, constraint = fee_recipient_ata.mint == mint.key())]
+    pub fee_recipient_ata: Account<'info, TokenAccount>,
+    #[account(mut, constraint = target_token_account.mint == mint.key())]
+    pub target_token_account: Account<'info, TokenAccount>,
+    /// CHECK: adapter program (CPI target); we don’t execute it here, just emit identity
+    pub target_adapter_program: UncheckedAccount<'info>,
+    #[account(seeds=[b"zpx_config", config.namespace_seed()], bump=config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds=[b"in_flight", mint.key().as_ref(), &dst_chain_id.to_le_bytes()],
+        bump=in_flight.bump
+    )]
+    pub in_flight: Account<'info, InFlight>,
+    #[account(seeds=[b"fee_recipient_history"], bump=fee_recipient_history.bump)]
+    pub fee_recipient_history: Account<'info, FeeRecipientHistory>,
+    /// CHECK: MessagePreimage PDA [b"message", user, nonce]; only created when store_preimage
+    #[account(mut)]
+    pub message: UncheckedAccount<'info>,
+    /// CHECK: UserTransferIndex PDA [b"user_index", user]; created on first receipt
+    #[account(mut)]
+    pub user_index: UncheckedAccount<'info>,
+    /// CHECK: RouterStats PDA; counters are only updated once it has been initialized
+    #[account(mut)]
+    pub router_stats: UncheckedAccount<'info>,
+    /// CHECK: FeeSplit PDA; may be uninitialized (whole fee to fee_recipient_ata)
+    pub fee_split: UncheckedAccount<'info>,
+    /// CHECK: FeePolicy PDA [b"fee_policy", mint, dst_chain_id]; may be uninitialized
+    /// (caller-supplied fees within the caps)
+    pub fee_policy: UncheckedAccount<'info>,
+    /// CHECK: MintPause PDA for mint; may be uninitialized (never paused)
+    pub mint_pause: UncheckedAccount<'info>,
+    /// CHECK: MintAllowlist PDA [b"mint_allowlist"]; consulted when accept_any_token is off
+    pub mint_allowlist: UncheckedAccount<'info>,
+    /// CHECK: RentLedger PDA; only updated once it has been initialized
+    #[account(mut)]
+    pub rent_ledger: UncheckedAccount<'info>,
+    /// CHECK: compared with config.bridge_hook in the handler; ignored while none is set
+    pub bridge_hook_program: UncheckedAccount<'info>,
+    /// CHECK: signer PDA for hook CPIs; holds no data
+    #[account(seeds=[b"bridge_hook_authority"], bump)]
+    pub bridge_hook_authority: UncheckedAccount<'info>,
+    /// CHECK: referring Partner PDA; any account the program does not own means no referral
+    pub referral: UncheckedAccount<'info>,
+    /// CHECK: PartnerRebate PDA [b"partner_rebate", partner_id, mint]; ignored without referral
+    #[account(mut)]
+    pub partner_rebate: UncheckedAccount<'info>,
+    /// CHECK: rebate vault [b"partner_rebate_vault", mint]; ignored without referral
+    #[account(mut)]
+    pub partner_rebate_vault: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(amount: u64, protocol_fee: u64, relayer_fee: u64, payload: Vec<u8>, dst_chain_id: u64)]
+pub struct UniversalBridgeTransferZpxFee<'info> {
+    /// Token owner; only signs, so a sponsored transaction leaves it without SOL needs
+    pub user: Signer<'info>,
+    /// Pays rent for the optional message preimage. Pass `user` unless a partner sponsors
+    /// the transaction (see `sponsored::sponsored_message`).
+    #[account(mut)]
+    pub rent_payer: Signer<'info>,
+    pub mint: Account<'info, Mint>,
+    #[account(mut, constraint = from.owner == user.key(), constraint = from.mint == mint.key())]
     pub from: Account<'info, TokenAccount>,
+    /// Owner checked in handler against the fee recipient history (grace window aware)
+    #[account(mut, constraint = fee_recipient_ata.mint == mint.key())]
+    pub fee_recipient_ata: Account<'info, TokenAccount>,
+    #[account(mut, constraint = target_token_account.mint == mint.key())]
+    pub target_token_account: Account<'info, TokenAccount>,
+    /// CHECK: adapter program (CPI target); we don’t execute it here, just emit identity
+    pub target_adapter_program: UncheckedAccount<'info>,
+    #[account(seeds=[b"zpx_config", config.namespace_seed()], bump=config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds=[b"in_flight", mint.key().as_ref(), &dst_chain_id.to_le_bytes()],
+        bump=in_flight.bump
+    )]
+    pub in_flight: Account<'info, InFlight>,
+    #[account(seeds=[b"fee_recipient_history"], bump=fee_recipient_history.bump)]
+    pub fee_recipient_history: Account<'info, FeeRecipientHistory>,
+    #[account(seeds=[b"zpx_fee_rate", mint.key().as_ref()], bump=zpx_fee_rate.bump)]
+    pub zpx_fee_rate: Account<'info, ZpxFeeRate>,
+    #[account(mut, address = zpx_fee_rate.zpx_mint)]
+    pub zpx_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        constraint = user_zpx_account.owner == user.key(),
+        constraint = user_zpx_account.mint == zpx_mint.key()
+    )]
+    pub user_zpx_account: Account<'info, TokenAccount>,
+    /// CHECK: MessagePreimage PDA [b"message", user, nonce]; only created when store_preimage
+    #[account(mut)]
+    pub message: UncheckedAccount<'info>,
+    /// CHECK: UserTransferIndex PDA [b"user_index", user]; created on first receipt
+    #[account(mut)]
+    pub user_index: UncheckedAccount<'info>,
+    /// CHECK: RouterStats PDA; counters are only updated once it has been initialized
+    #[account(mut)]
+    pub router_stats: UncheckedAccount<'info>,
+    /// CHECK: FeePolicy PDA [b"fee_policy", mint, dst_chain_id]; may be uninitialized
+    /// (caller-supplied fees within the caps)
+    pub fee_policy: UncheckedAccount<'info>,
+    /// CHECK: MintPause PDA for mint; may be uninitialized (never paused)
+    pub mint_pause: UncheckedAccount<'info>,
+    /// CHECK: MintAllowlist PDA [b"mint_allowlist"]; consulted when accept_any_token is off
+    pub mint_allowlist: UncheckedAccount<'info>,
+    /// CHECK: RentLedger PDA; only updated once it has been initialized
+    #[account(mut)]
+    pub rent_ledger: UncheckedAccount<'info>,
+    /// CHECK: compared with config.bridge_hook in the handler; ignored while none is set
+    pub bridge_hook_program: UncheckedAccount<'info>,
+    /// CHECK: signer PDA for hook CPIs; holds no data
+    #[account(seeds=[b"bridge_hook_authority"], bump)]
+    pub bridge_hook_authority: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeZpxFeeRate<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    pub mint: Account<'info, Mint>,
+    pub zpx_mint: Account<'info, Mint>,
+    #[account(
+        init,
+        payer = authority,
+        // discriminator(8) + mint(32) + zpx_mint(32) + keeper(32) + zpx_per_token_e9(8)
+        // + discount_bps(2) + max_staleness_slots(8) + updated_at_slot(8) + bump(1)
+        space = 8 + 32 + 32 + 32 + 8 + 2 + 8 + 8 + 1,
+        seeds = [b"zpx_fee_rate", mint.key().as_ref()],
+        bump
+    )]
+    pub zpx_fee_rate: Account<'info, ZpxFeeRate>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateZpxFeeRate<'info> {
+    pub authority: Signer<'info>,
+    #[account(seeds=[b"zpx_config", config.namespace_seed()], bump=config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds=[b"zpx_fee_rate", zpx_fee_rate.mint.as_ref()], bump=zpx_fee_rate.bump)]
+    pub zpx_fee_rate: Account<'info, ZpxFeeRate>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureZpxFeeRate<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds=[b"zpx_fee_rate", zpx_fee_rate.mint.as_ref()], bump=zpx_fee_rate.bump)]
+    pub zpx_fee_rate: Account<'info, ZpxFeeRate>,
+}
+
+#[derive(Accounts)]
+pub struct DryRunAdapter<'info> {
+    #[account(seeds=[b"hub_registry"], bump=registry.bump)]
+    pub registry: Box<Account<'info, Registry>>,
+    /// CHECK: must be the spoke's registered adapter (checked in handler)
+    pub adapter_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetClockOverride<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == admin.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    /// CHECK: ClockOverride PDA [b"clock_override"]; created on first use
+    #[account(mut)]
+    pub clock_override: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BridgeWithAdapterCpi<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == admin.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    /// CHECK: must be in the config adapter allowlist (checked in handler)
+    pub adapter_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(message_hash: [u8; 32])]
+pub struct FinalizeMessageV1<'info> {
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+    #[account(seeds=[b"zpx_config", config.namespace_seed()], bump=config.bump)]
+    pub config: Account<'info, Config>,
+    /// CHECK: PDA verified & optionally created in handler
+    #[account(mut)]
+    pub replay: UncheckedAccount<'info>,
+    /// CHECK: RentLedger PDA; only updated once it has been initialized
+    #[account(mut)]
+    pub rent_ledger: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: RouteValidity PDA for src_chain_id; may be uninitialized (no max age)
+    pub route_validity: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CrankFinalizeMessageV1<'info> {
+    /// `relayer` is the cranker, any signer
+    pub finalize: FinalizeMessageV1<'info>,
+    pub mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        seeds=[CRANK_REWARD_SEED, mint.key().as_ref()],
+        bump=crank_reward.bump
+    )]
+    pub crank_reward: Account<'info, CrankReward>,
+    #[account(mut)]
+    pub hub_protocol_vault: Account<'info, TokenAccount>,
+    #[account(mut, constraint = crank_token_account.mint == mint.key())]
+    pub crank_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeOracleConfig<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    pub mint: Account<'info, Mint>,
+    #[account(
+        init,
+        payer = authority,
+        // discriminator(8) + mint(32) + decimals(1) + oracle_program(32) + price_account(32)
+        // + max_staleness_slots(8) + max_conf_bps(2) + min_forward_usd_e6(8) + bump(1)
+        space = 8 + 32 + 1 + 32 + 32 + 8 + 2 + 8 + 1,
+        seeds = [b"oracle", mint.key().as_ref()],
+        bump
+    )]
+    pub oracle_config: Account<'info, OracleConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateOracleConfig<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds=[b"oracle", oracle_config.mint.as_ref()], bump=oracle_config.bump)]
+    pub oracle_config: Account<'info, OracleConfig>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfigAttestation<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = authority,
+        // discriminator(8) + root(32) + sequence(8) + updated_at_slot(8) + bump(1)
+        space = 8 + 32 + 8 + 8 + 1,
+        seeds = [b"config_attestation"],
+        bump
+    )]
+    pub attestation: Account<'info, ConfigAttestation>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AttestConfig<'info> {
+    #[account(seeds=[b"zpx_config", config.namespace_seed()], bump=config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(seeds=[b"hub_registry", config.namespace_seed()], bump=registry.bump)]
+    pub registry: Box<Account<'info, Registry>>,
+    #[account(mut, seeds=[b"config_attestation"], bump=attestation.bump)]
+    pub attestation: Account<'info, ConfigAttestation>,
+}
+
+#[derive(Accounts)]
+#[instruction(dst_chain_id: u64)]
+pub struct InitializeInFlight<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    pub mint: Account<'info, Mint>,
+    #[account(
+        init,
+        payer = authority,
+        // discriminator(8) + mint(32) + dst_chain_id(8) + amount(8) + exposure_cap(8) + bump(1)
+        space = 8 + 32 + 8 + 8 + 8 + 1,
+        seeds = [b"in_flight", mint.key().as_ref(), &dst_chain_id.to_le_bytes()],
+        bump
+    )]
+    pub in_flight: Account<'info, InFlight>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetExposureCap<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        seeds=[b"zpx_config", config.namespace_seed()],
+        bump=config.bump,
+        constraint = config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
     #[account(
         mut,
-        constraint = fee_recipient_ata.mint == mint.key(),
-        constraint = fee_recipient_ata.owner == config.fee_recipient @ ErrorCode::InvalidFeeRecipientAta
+        seeds=[b"in_flight", in_flight.mint.as_ref(), &in_flight.dst_chain_id.to_le_bytes()],
+        bump=in_flight.bump
     )]
-    pub fee_recipient_ata: Account<'info, TokenAccount>,
-    #[account(mut, constraint = target_token_account.mint == mint.key())]
-    pub target_token_account: Account<'info, TokenAccount>,
-    /// CHECK: adapter program (CPI target); we don’t execute it here, just emit identity
-    pub target_adapter_program: UncheckedAccount<'info>,
-    #[account(seeds=[b"zpx_config"], bump=config.bump)]
+    pub in_flight: Account<'info, InFlight>,
+}
+
+#[derive(Accounts)]
+pub struct SettleInFlight<'info> {
+    pub authority: Signer<'info>,
+    #[account(seeds=[b"zpx_config", config.namespace_seed()], bump=config.bump)]
     pub config: Account<'info, Config>,
-    pub token_program: Program<'info, Token>,
+    #[account(
+        mut,
+        seeds=[b"in_flight", in_flight.mint.as_ref(), &in_flight.dst_chain_id.to_le_bytes()],
+        bump=in_flight.bump
+    )]
+    pub in_flight: Account<'info, InFlight>,
+}
+
+#[account]
+pub struct Replay {
+    pub processed: u8,
+    /// Relayer whose finalize_message_v1 created the marker (claims finalization bounties).
+    /// Absent on legacy 9-byte replay accounts.
+    pub finalizer: Pubkey,
+    /// Origin-chain (e.g. EVM) transaction hash supplied by the relayer; zero when unknown
+    pub origin_tx_hash: [u8; 32],
+}
+
+/// Relayer that finalized a message, read from a replay PDA's raw data; None when the
+/// marker is not processed or predates the finalizer field.
+pub fn replay_finalizer(data: &[u8]) -> Option<Pubkey> {
+    replay_guard::read(data)
+        .ok()
+        .filter(|marker| marker.processed)
+        .and_then(|marker| marker.finalizer)
+}
+
+/// Lamport bounty posted on the source leg for whichever relayer finalizes the message.
+#[account]
+pub struct FinalizationBounty {
+    pub user: Pubkey,
+    pub nonce: u64,
+    pub message_hash: [u8; 32],
+    pub amount: u64,
+    /// Last slot at which the bounty can be claimed; refundable afterwards
+    pub expiry_slot: u64,
+    pub bump: u8,
+}
+
+const FEE_RECIPIENT_HISTORY_LEN: usize = 8;
+
+/// Ring buffer of the last FEE_RECIPIENT_HISTORY_LEN fee recipients. `epoch` counts
+/// rotations since initialization; the active entry lives at `epoch % LEN`.
+#[account]
+pub struct FeeRecipientHistory {
+    pub epoch: u64,
+    pub entries: [FeeRecipientEpoch; FEE_RECIPIENT_HISTORY_LEN],
+    pub bump: u8,
+    /// Slots after a rotation during which the previous recipient's ATA is still accepted
+    pub grace_slots: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct FeeRecipientEpoch {
+    pub fee_recipient: Pubkey,
+    pub activated_at_slot: u64,
+}
+
+impl FeeRecipientHistory {
+    /// Ring index of the active (latest) entry.
+    pub fn head(&self) -> usize {
+        (self.epoch % FEE_RECIPIENT_HISTORY_LEN as u64) as usize
+    }
+
+    /// Epoch whose recipient is `owner` and may receive fees at `slot`: the active epoch, or
+    /// the previous one while inside the grace window after the latest rotation.
+    pub fn accepting_epoch(&self, owner: &Pubkey, slot: u64) -> Option<u64> {
+        let head = &self.entries[self.head()];
+        if head.fee_recipient == *owner {
+            return Some(self.epoch);
+        }
+        if self.epoch == 0 {
+            return None;
+        }
+        let prev_idx = ((self.epoch - 1) % FEE_RECIPIENT_HISTORY_LEN as u64) as usize;
+        let in_grace = slot < head.activated_at_slot.saturating_add(self.grace_slots);
+        (in_grace && self.entries[prev_idx].fee_recipient == *owner).then(|| self.epoch - 1)
+    }
+
+    /// Start a new epoch for `fee_recipient`, overwriting the oldest entry.
+    pub fn record(&mut self, fee_recipient: Pubkey, slot: u64) {
+        self.epoch += 1;
+        let head = self.head();
+        self.entries[head] = FeeRecipientEpoch {
+            fee_recipient,
+            activated_at_slot: slot,
+        };
+    }
+}
+
+/// Keeper-published ZPX price for paying protocol fees on a bridged mint in ZPX.
+#[account]
+pub struct ZpxFeeRate {
+    pub mint: Pubkey,
+    pub zpx_mint: Pubkey,
+    pub keeper: Pubkey,
+    /// ZPX base units per bridged-token base unit, scaled by 1e9
+    pub zpx_per_token_e9: u64,
+    pub discount_bps: u16,
+    pub max_staleness_slots: u64,
+    pub updated_at_slot: u64,
+    pub bump: u8,
+}
+
+pub const STATS_MAX_MINTS: usize = 8;
+pub const STATS_DAY_BUCKETS: usize = 7;
+pub const STATS_FAILURE_CLASSES: usize = 8;
+/// ~24h at 400ms slots; day buckets are keyed by `slot / STATS_SLOTS_PER_DAY`
+pub const STATS_SLOTS_PER_DAY: u64 = 216_000;
+const STATS_KIND_INITIATED: u8 = 0;
+const STATS_KIND_FORWARDED: u8 = 1;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct MintVolume {
+    pub mint: Pubkey,
+    pub volume: u128,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct DayBucket {
+    pub day: u64,
+    pub transfers: u64,
+}
+
+pub const RENT_KIND_REPLAY: u8 = 0;
+pub const RENT_KIND_MESSAGE: u8 = 1;
+pub const RENT_KIND_ESCROW: u8 = 2;
+pub const RENT_KIND_VAULT: u8 = 3;
+pub const RENT_KINDS: usize = 4;
+
+/// Config.rent_refund_policy: rent reclaimed from closed escrows (and their vaults) returns
+/// to the depositor that paid it,
+pub const RENT_REFUND_PAYER: u8 = 0;
+/// accrues in the RentLedger PDA until sweep_rent_treasury moves it to the fee recipient,
+pub const RENT_REFUND_TREASURY: u8 = 1;
+/// or is split: rent_refund_caller_bps to the signer that closed the escrow, the rest to the
+/// depositor.
+pub const RENT_REFUND_CALLER_SPLIT: u8 = 2;
+
+/// Rent held by one kind of program-owned account. `lamports` is the rent-exempt minimum of
+/// the live accounts; `created` / `closed` are cumulative.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct RentBucket {
+    pub accounts: u64,
+    pub lamports: u64,
+    pub created: u64,
+    pub closed: u64,
+}
+
+/// Rent locked in program-owned accounts, indexed by RENT_KIND_*.
+#[account]
+pub struct RentLedger {
+    pub buckets: [RentBucket; RENT_KINDS],
+    pub updated_at_slot: u64,
+    pub bump: u8,
+}
+
+// discriminator(8) + buckets(32 * RENT_KINDS) + updated_at_slot(8) + bump(1)
+const RENT_LEDGER_SPACE: usize = 8 + (32 * RENT_KINDS) + 8 + 1;
+
+impl RentLedger {
+    /// Account for one created (`opened`) or closed account holding `lamports` of rent.
+    pub fn record(&mut self, kind: u8, lamports: u64, opened: bool, slot: u64) {
+        let bucket = &mut self.buckets[kind as usize];
+        if opened {
+            bucket.accounts += 1;
+            bucket.created += 1;
+            bucket.lamports = bucket.lamports.saturating_add(lamports);
+        } else {
+            bucket.accounts = bucket.accounts.saturating_sub(1);
+            bucket.closed += 1;
+            bucket.lamports = bucket.lamports.saturating_sub(lamports);
+        }
+        self.updated_at_slot = slot;
+    }
+}
+
+/// Aggregate router counters for dashboards. Volume is tracked for the first
+/// STATS_MAX_MINTS mints seen; day buckets form a ring indexed by `day % STATS_DAY_BUCKETS`.
+#[account]
+pub struct RouterStats {
+    pub total_initiated: u64,
+    pub total_forwarded: u64,
+    pub volumes_len: u8,
+    pub volumes: [MintVolume; STATS_MAX_MINTS],
+    pub days: [DayBucket; STATS_DAY_BUCKETS],
+    /// Keeper-recorded failure counts by error class
+    pub failures: [u64; STATS_FAILURE_CLASSES],
+    pub keeper: Pubkey,
+    pub updated_at_slot: u64,
+    pub bump: u8,
+}
+
+// discriminator(8) + totals(16) + volumes_len(1) + volumes(48 * N) + days(16 * N)
+// + failures(8 * N) + keeper(32) + updated_at_slot(8) + bump(1)
+const ROUTER_STATS_SPACE: usize = 8
+    + 16
+    + 1
+    + (48 * STATS_MAX_MINTS)
+    + (16 * STATS_DAY_BUCKETS)
+    + (8 * STATS_FAILURE_CLASSES)
+    + 32
+    + 8
+    + 1;
+
+impl RouterStats {
+    /// Count one transfer (`kind` = STATS_KIND_*) of `amount` of `mint` at `slot`.
+    pub fn record(&mut self, mint: &Pubkey, amount: u64, kind: u8, slot: u64) {
+        if kind == STATS_KIND_INITIATED {
+            self.total_initiated = self.total_initiated.saturating_add(1);
+        } else {
+            self.total_forwarded = self.total_forwarded.saturating_add(1);
+        }
+        let len = self.volumes_len as usize;
+        match self.volumes[..len].iter_mut().find(|v| v.mint == *mint) {
+            Some(v) => v.volume = v.volume.saturating_add(amount as u128),
+            None if len < STATS_MAX_MINTS => {
+                self.volumes[len] = MintVolume {
+                    mint: *mint,
+                    volume: amount as u128,
+                };
+                self.volumes_len += 1;
+            }
+            None => {}
+        }
+        let day = slot / STATS_SLOTS_PER_DAY;
+        let bucket = &mut self.days[(day % STATS_DAY_BUCKETS as u64) as usize];
+        if bucket.day != day {
+            *bucket = DayBucket { day, transfers: 0 };
+        }
+        bucket.transfers = bucket.transfers.saturating_add(1);
+        self.updated_at_slot = slot;
+    }
+}
+
+pub const MAX_SPOKE_RELAYERS: usize = 8;
+
+/// Protocol fee split for the in-kind source skim: share `i` goes to the ATA of
+/// `recipients[i]`. Inactive while `recipients_len == 0`.
+#[account]
+pub struct FeeSplit {
+    pub recipients_len: u8,
+    pub recipients: [Pubkey; MAX_FEE_SPLIT_RECIPIENTS],
+    pub bps: [u16; MAX_FEE_SPLIT_RECIPIENTS],
+    pub bump: u8,
+}
+
+/// Per-spoke relayer allowlist for partner-operated routes, independent of Config.relayer_pubkey.
+#[account]
+pub struct SpokeRelayers {
+    pub spoke_id: u32,
+    pub relayers_len: u8,
+    pub relayers: [Pubkey; MAX_SPOKE_RELAYERS],
+    pub bump: u8,
+}
+
+pub const MAX_RELAYER_COSIGNERS: usize = 8;
+
+/// Fees staged for a message dispatched in several chunks, at [b"forward_state", escrow].
+/// Quoted once on the whole escrow by its first partial dispatch; every chunk then applies
+/// its pro-rata share, so per-chunk minimums and tiers cannot charge a message twice.
+/// Closed into the relayer by the final dispatch.
+#[account]
+pub struct ForwardState {
+    pub escrow: Pubkey,
+    pub protocol_fee: u64,
+    pub relayer_fee: u64,
+    pub protocol_fee_applied: u64,
+    pub relayer_fee_applied: u64,
+    pub bump: u8,
+}
+
+// discriminator(8) + escrow(32) + protocol_fee(8) + relayer_fee(8)
+// + protocol_fee_applied(8) + relayer_fee_applied(8) + bump(1)
+const FORWARD_STATE_SPACE: usize = 8 + 32 + 8 * 4 + 1;
+
+impl ForwardState {
+    /// Protocol and relayer fee shares for dispatching `amount` of an escrow of `total` with
+    /// `forwarded` already dispatched. Shares are cumulative floors, so the chunks of a
+    /// message always add up to the staged fees.
+    pub fn share(&self, total: u64, forwarded: u64, amount: u64) -> Result<(u64, u64)> {
+        let upto = |fee: u64, x: u64| -> Result<u64> {
+            require!(total > 0 && x <= total, ErrorCode::MathOverflow);
+            Ok((fee as u128 * x as u128 / total as u128) as u64)
+        };
+        let end = forwarded
+            .checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok((
+            upto(self.protocol_fee, end)? - upto(self.protocol_fee, forwarded)?,
+            upto(self.relayer_fee, end)? - upto(self.relayer_fee, forwarded)?,
+        ))
+    }
+}
+
+/// Co-signing policy for large forwards of `mint`, at [b"relayer_cosign", mint]. Forwards of
+/// escrows holding at least `threshold` need registered co-signers whose summed stake
+/// `weights` reach `required_weight` to sign the transaction. Absent or `threshold == 0` =
+/// the single-relayer path.
+#[account]
+pub struct RelayerCosign {
+    pub mint: Pubkey,
+    pub threshold: u64,
+    pub required_weight: u64,
+    pub cosigners_len: u8,
+    pub cosigners: [Pubkey; MAX_RELAYER_COSIGNERS],
+    pub weights: [u64; MAX_RELAYER_COSIGNERS],
+    pub bump: u8,
+}
+
+// discriminator(8) + mint(32) + threshold(8) + required_weight(8) + cosigners_len(1)
+// + cosigners(MAX_RELAYER_COSIGNERS * 32) + weights(MAX_RELAYER_COSIGNERS * 8) + bump(1)
+const RELAYER_COSIGN_SPACE: usize = 8 + 32 + 8 + 8 + 1 + MAX_RELAYER_COSIGNERS * (32 + 8) + 1;
+
+impl RelayerCosign {
+    /// Distinct, non-default co-signers with non-zero weights whose total can meet
+    /// `required_weight`. A disabled policy only needs a consistent length.
+    pub fn is_valid(&self) -> bool {
+        let n = self.cosigners_len as usize;
+        if n > MAX_RELAYER_COSIGNERS {
+            return false;
+        }
+        if self.threshold == 0 {
+            return true;
+        }
+        let cosigners = &self.cosigners[..n];
+        let distinct = cosigners
+            .iter()
+            .enumerate()
+            .all(|(i, c)| *c != Pubkey::default() && !cosigners[..i].contains(c));
+        let total = self.weights[..n].iter().try_fold(0u64, |acc, w| {
+            (*w > 0).then(|| acc.checked_add(*w)).flatten()
+        });
+        distinct && self.required_weight > 0 && total.is_some_and(|t| t >= self.required_weight)
+    }
+
+    /// Registered co-signers for which `signed` holds, with their summed weight.
+    pub fn signed_weight(&self, signed: impl Fn(&Pubkey) -> bool) -> (u64, Vec<Pubkey>) {
+        let n = self.cosigners_len as usize;
+        let mut weight = 0u64;
+        let mut signers = Vec::new();
+        for (cosigner, w) in self.cosigners[..n].iter().zip(&self.weights[..n]) {
+            if signed(cosigner) {
+                weight = weight.saturating_add(*w);
+                signers.push(*cosigner);
+            }
+        }
+        (weight, signers)
+    }
+}
+
+pub const MAX_ALLOWED_MINTS: usize = 16;
+
+/// Mints accepted by the transfer paths when `config.accept_any_token` is false, in addition
+/// to `config.allowed_token_mint`.
+#[account]
+pub struct MintAllowlist {
+    pub mints_len: u8,
+    pub mints: [Pubkey; MAX_ALLOWED_MINTS],
+    pub bump: u8,
+}
+
+impl MintAllowlist {
+    pub fn add(&mut self, mint: Pubkey) -> Result<()> {
+        let len = self.mints_len as usize;
+        require!(
+            !self.mints[..len].contains(&mint),
+            ErrorCode::MintAlreadyAllowed
+        );
+        require!(len < MAX_ALLOWED_MINTS, ErrorCode::AdapterListFull);
+        self.mints[len] = mint;
+        self.mints_len += 1;
+        Ok(())
+    }
+}
+
+pub const MAX_INTENT_SIGNERS: usize = 8;
+
+/// EVM addresses of the order-flow signers whose bridge intents forward_with_intent executes.
+#[account]
+pub struct IntentSigners {
+    pub signers_len: u8,
+    pub signers: [[u8; 20]; MAX_INTENT_SIGNERS],
+    pub bump: u8,
+}
+
+/// EVM-originated order to forward one forward escrow, signed as EIP-712 typed data
+/// (`BridgeIntent` in `intent::BRIDGE_INTENT_TYPE`). `deadline` is a unix timestamp.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BridgeIntent {
+    pub user: Pubkey,
+    pub mint: Pubkey,
+    pub escrow_nonce: u64,
+    pub spoke_id: u32,
+    pub amount: u64,
+    pub dst_domain: u32,
+    pub mint_recipient: [u8; 32],
+    pub relayer_fee_lamports: u64,
+    pub payload_hash: [u8; 32],
+    pub deadline: u64,
+}
+
+/// Instruction-side mirror of `recipient::Recipient`: the destination address tagged with
+/// its format, so a mis-encoded recipient is rejected at deposit instead of losing funds.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Recipient {
+    Evm20([u8; 20]),
+    Solana32([u8; 32]),
+    /// 32-byte hash of a Cosmos bech32 account's data part
+    Cosmos32([u8; 32]),
+    BtcScriptHash([u8; 32]),
+}
+
+impl Recipient {
+    pub fn to_core(&self) -> recipient::Recipient {
+        match *self {
+            Recipient::Evm20(a) => recipient::Recipient::Evm20(a),
+            Recipient::Solana32(a) => recipient::Recipient::Solana32(a),
+            Recipient::Cosmos32(a) => recipient::Recipient::Cosmos32(a),
+            Recipient::BtcScriptHash(a) => recipient::Recipient::BtcScriptHash(a),
+        }
+    }
+}
+
+/// Most escrows one forward_batch_via_spoke call dispatches
+pub const MAX_BATCH_ENTRIES: usize = 8;
+/// Remaining accounts per batch entry: escrow, escrow_vault, user, fee_policy
+pub const BATCH_ENTRY_ACCOUNTS: usize = 4;
+
+/// One escrowed message of a forward_batch_via_spoke call; must match the escrowed intent.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BatchEntry {
+    pub user: Pubkey,
+    pub nonce: u64,
+    pub amount: u64,
+    pub dst_domain: u32,
+    pub mint_recipient: [u8; 32],
+}
+
+/// Per-message part of the batched adapter payload: what the adapter sends where.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BatchDispatch {
+    pub user: Pubkey,
+    pub nonce: u64,
+    pub net_amount: u64,
+    pub dst_domain: u32,
+    pub mint_recipient: [u8; 32],
+}
+
+// user(32) + nonce(8) + net_amount(8) + dst_domain(4) + mint_recipient(32)
+pub const BATCH_DISPATCH_LEN: usize = 32 + 8 + 8 + 4 + 32;
+
+/// Per-(user, nonce) forward intent backing the relayer-only forward_via_spoke path. The
+/// escrowed tokens sit in a token account at [b"forward_escrow_vault", escrow] whose
+/// authority is this PDA; `lamport_budget` funds an optional lamport relayer fee.
+#[account]
+pub struct ForwardEscrow {
+    pub user: Pubkey,
+    pub mint: Pubkey,
+    pub spoke_id: u32,
+    pub amount: u64,
+    /// Cumulative amount dispatched so far (forward_partial)
+    pub forwarded: u64,
+    pub dst_domain: u32,
+    pub mint_recipient: [u8; 32],
+    pub nonce: u64,
+    pub lamport_budget: u64,
+    pub created_at_slot: u64,
+    pub bump: u8,
+    /// Tombstone set by cancel_message; the (user, nonce) can never be forwarded or reused
+    pub cancelled: bool,
+    /// Lamport bid paid to whichever relayer makes the first dispatch; the user may raise it
+    /// via increase_relayer_tip until then
+    pub relayer_tip: u64,
+    /// Slot from which scan_escrow_expiry reports the escrow as expiring; it expires (and
+    /// anyone may refund it to the user) ESCROW_EXPIRY_NOTICE_SLOTS later. 0 = never.
+    pub expiry_reminder_slot: u64,
+    /// Protocol fee kept in the escrow vault until confirm_escrow_dispatch (FEATURE_FEE_ESCROW)
+    pub held_protocol_fee: u64,
+    /// Token relayer fee kept in the escrow vault until confirm_escrow_dispatch
+    pub held_relayer_fee: u64,
+    /// Who is paid the held relayer fee: a relayer accruing vesting, or the owner of the
+    /// direct-payout token account when `fee_payee_direct`
+    pub fee_payee: Pubkey,
+    pub fee_payee_direct: bool,
+    /// recipient::TAG_* of the address `mint_recipient` encodes
+    pub recipient_tag: u8,
+}
+
+/// Notice period between an escrow's expiry reminder and its expiry (~1 day at 400ms slots)
+pub const ESCROW_EXPIRY_NOTICE_SLOTS: u64 = 216_000;
+
+/// Where an escrow stands relative to its expiry reminder.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EscrowExpiry {
+    Active,
+    ExpiringSoon { expires_at_slot: u64 },
+    Expired { expires_at_slot: u64 },
+}
+
+impl ForwardEscrow {
+    /// Token fees held in the vault pending dispatch confirmation.
+    pub fn held_fees(&self) -> u64 {
+        self.held_protocol_fee.saturating_add(self.held_relayer_fee)
+    }
+
+    /// Lamports in the escrow that belong to the user rather than to its rent: the unspent
+    /// lamport budget and an unclaimed relayer tip.
+    pub fn owed_lamports(&self) -> u64 {
+        self.lamport_budget.saturating_add(self.relayer_tip)
+    }
+
+    pub fn expiry_at(&self, slot: u64) -> EscrowExpiry {
+        if self.expiry_reminder_slot == 0 || slot < self.expiry_reminder_slot {
+            return EscrowExpiry::Active;
+        }
+        let expires_at_slot = self
+            .expiry_reminder_slot
+            .saturating_add(ESCROW_EXPIRY_NOTICE_SLOTS);
+        if slot < expires_at_slot {
+            EscrowExpiry::ExpiringSoon { expires_at_slot }
+        } else {
+            EscrowExpiry::Expired { expires_at_slot }
+        }
+    }
+}
+
+// discriminator(8) + user(32) + mint(32) + spoke_id(4) + amount(8) + forwarded(8)
+// + dst_domain(4) + mint_recipient(32) + nonce(8) + lamport_budget(8) + created_at_slot(8)
+// + bump(1) + cancelled(1) + relayer_tip(8) + expiry_reminder_slot(8) + held_protocol_fee(8)
+// + held_relayer_fee(8) + fee_payee(32) + fee_payee_direct(1) + recipient_tag(1)
+const FORWARD_ESCROW_SPACE: usize =
+    8 + 32 + 32 + 4 + 8 + 8 + 4 + 32 + 8 + 8 + 8 + 1 + 1 + 8 + 8 + 8 + 8 + 32 + 1 + 1;
+
+pub const MAX_ROUTE_STEPS: usize = 8;
+/// Longest window a route session may stay open (~1 minute at 400ms slots)
+pub const MAX_ROUTE_SESSION_WINDOW_SLOTS: u64 = 150;
+
+/// One executed step of a multi-transaction route (swap, bridge, memo, hook...). `kind` is
+/// client-defined; `data_hash` commits to the step's instruction data.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RouteStep {
+    pub kind: u8,
+    pub program: Pubkey,
+    pub data_hash: [u8; 32],
+}
+
+/// Funds and plan for a route that spans several transactions. PDA
+/// [b"route_session", user, nonce]; tokens sit in [b"route_session_vault", session].
+#[account]
+pub struct RouteSession {
+    pub user: Pubkey,
+    pub mint: Pubkey,
+    pub nonce: u64,
+    pub amount: u64,
+    pub target_token_account: Pubkey,
+    pub plan_hash: [u8; 32],
+    pub steps_expected: u8,
+    pub steps_len: u8,
+    pub steps: [RouteStep; MAX_ROUTE_STEPS],
+    pub begin_slot: u64,
+    pub expiry_slot: u64,
+    pub bump: u8,
+}
+
+// discriminator(8) + user(32) + mint(32) + nonce(8) + amount(8) + target_token_account(32)
+// + plan_hash(32) + steps_expected(1) + steps_len(1) + steps(65 * MAX) + begin_slot(8)
+// + expiry_slot(8) + bump(1)
+const ROUTE_SESSION_SPACE: usize =
+    8 + 32 + 32 + 8 + 8 + 32 + 32 + 1 + 1 + (65 * MAX_ROUTE_STEPS) + 8 + 8 + 1;
+
+/// Plan commitment for a route session: chained
+/// keccak256(prev || kind || program || data_hash) over the steps in order, from 32 zero bytes.
+pub fn route_plan_hash(steps: &[RouteStep]) -> [u8; 32] {
+    steps.iter().fold([0u8; 32], |acc, step| {
+        keccak256(&[&acc, &[step.kind], step.program.as_ref(), &step.data_hash])
+    })
+}
+
+pub const USER_INDEX_LEN: usize = 16;
+
+/// One bridge initiated by a user, as listed in their UserTransferIndex.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct TransferReceipt {
+    pub message_hash: [u8; 32],
+    pub slot: u64,
+    pub amount: u64,
+    pub dst_chain_id: u64,
+}
+
+/// Ring buffer of a user's most recent bridge transfers so wallets can list them without an
+/// indexer. PDA [b"user_index", user]; `head` is the next slot to overwrite.
+#[account]
+pub struct UserTransferIndex {
+    pub user: Pubkey,
+    pub head: u8,
+    pub total: u64,
+    pub receipts: [TransferReceipt; USER_INDEX_LEN],
+    pub bump: u8,
+}
+
+// discriminator(8) + user(32) + head(1) + total(8) + receipts(56 * USER_INDEX_LEN) + bump(1)
+const USER_INDEX_SPACE: usize = 8 + 32 + 1 + 8 + (56 * USER_INDEX_LEN) + 1;
+
+impl UserTransferIndex {
+    pub fn push(&mut self, receipt: TransferReceipt) {
+        self.receipts[self.head as usize % USER_INDEX_LEN] = receipt;
+        self.head = ((self.head as usize + 1) % USER_INDEX_LEN) as u8;
+        self.total += 1;
+    }
+
+    /// Receipts newest first.
+    pub fn recent(&self) -> impl Iterator<Item = &TransferReceipt> {
+        let n = (self.total as usize).min(USER_INDEX_LEN);
+        (1..=n).map(move |i| {
+            &self.receipts[(self.head as usize + USER_INDEX_LEN - i) % USER_INDEX_LEN]
+        })
+    }
+}
+
+pub const ADMIN_AUDIT_LEN: usize = 64;
+pub const ADMIN_AUDIT_SEED: &[u8] = b"admin_audit";
+
+/// One privileged operation as recorded in the AdminAuditLog.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct AdminAuditEntry {
+    pub slot: u64,
+    /// Anchor discriminator of the admin instruction
+    pub instruction: [u8; 8],
+    pub authority: Pubkey,
+    /// sha256 of the borsh-encoded instruction arguments
+    pub params_hash: [u8; 32],
+}
+
+/// Ring buffer of the last ADMIN_AUDIT_LEN admin-gated operations, so the trail survives
+/// log retention. PDA [b"admin_audit", namespace]; `head` is the next slot to overwrite.
+#[account]
+pub struct AdminAuditLog {
+    pub head: u8,
+    pub total: u64,
+    pub entries: [AdminAuditEntry; ADMIN_AUDIT_LEN],
+    pub bump: u8,
+}
+
+// discriminator(8) + head(1) + total(8) + entries(80 * ADMIN_AUDIT_LEN) + bump(1)
+const ADMIN_AUDIT_SPACE: usize = 8 + 1 + 8 + (80 * ADMIN_AUDIT_LEN) + 1;
+
+impl AdminAuditLog {
+    pub fn push(&mut self, entry: AdminAuditEntry) {
+        self.entries[self.head as usize % ADMIN_AUDIT_LEN] = entry;
+        self.head = ((self.head as usize + 1) % ADMIN_AUDIT_LEN) as u8;
+        self.total += 1;
+    }
+
+    /// Entries newest first.
+    pub fn recent(&self) -> impl Iterator<Item = &AdminAuditEntry> {
+        let n = (self.total as usize).min(ADMIN_AUDIT_LEN);
+        (1..=n).map(move |i| {
+            &self.entries[(self.head as usize + ADMIN_AUDIT_LEN - i) % ADMIN_AUDIT_LEN]
+        })
+    }
+}
+
+pub const CONFIG_HISTORY_LEN: usize = 16;
+pub const CONFIG_HISTORY_SEED: &[u8] = b"config_history";
+
+/// Fee parameters in force from `slot` on, under Config.config_version `version`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct ConfigVersionEntry {
+    pub version: u64,
+    pub slot: u64,
+    pub protocol_fee_bps: u16,
+    pub relayer_fee_bps: u16,
+    pub lp_fee_bps: u16,
+    pub fee_recipient: Pubkey,
+}
+
+/// Ring buffer of the last CONFIG_HISTORY_LEN config versions, for fee disputes: which
+/// parameters applied at a given slot. PDA [b"config_history", namespace].
+#[account]
+pub struct ConfigHistory {
+    pub head: u8,
+    pub total: u64,
+    pub entries: [ConfigVersionEntry; CONFIG_HISTORY_LEN],
+    pub bump: u8,
+}
+
+// discriminator(8) + head(1) + total(8) + entries(54 * CONFIG_HISTORY_LEN) + bump(1)
+const CONFIG_HISTORY_SPACE: usize = 8 + 1 + 8 + (54 * CONFIG_HISTORY_LEN) + 1;
+
+impl ConfigVersionEntry {
+    pub fn of(cfg: &Config, slot: u64) -> Self {
+        Self {
+            version: cfg.config_version,
+            slot,
+            protocol_fee_bps: cfg.protocol_fee_bps,
+            relayer_fee_bps: cfg.relayer_fee_bps,
+            lp_fee_bps: cfg.lp_fee_bps,
+            fee_recipient: cfg.fee_recipient,
+        }
+    }
+}
+
+impl ConfigHistory {
+    pub fn push(&mut self, entry: ConfigVersionEntry) {
+        self.entries[self.head as usize % CONFIG_HISTORY_LEN] = entry;
+        self.head = ((self.head as usize + 1) % CONFIG_HISTORY_LEN) as u8;
+        self.total += 1;
+    }
+
+    /// Entries newest first.
+    pub fn recent(&self) -> impl Iterator<Item = &ConfigVersionEntry> {
+        let n = (self.total as usize).min(CONFIG_HISTORY_LEN);
+        (1..=n).map(move |i| {
+            &self.entries[(self.head as usize + CONFIG_HISTORY_LEN - i) % CONFIG_HISTORY_LEN]
+        })
+    }
+
+    pub fn version(&self, version: u64) -> Option<&ConfigVersionEntry> {
+        self.recent().find(|e| e.version == version)
+    }
+
+    /// Newest version written at or before `slot`; None when it has rotated out.
+    pub fn at_slot(&self, slot: u64) -> Option<&ConfigVersionEntry> {
+        self.recent().find(|e| e.slot <= slot)
+    }
+}
+
+/// Packed message-hash preimage stored on request at bridge initiation, so relayers can
+/// prove the preimage on-chain later (see verify_preimage).
+#[account]
+pub struct MessagePreimage {
+    pub src_chain_id: u64,
+    pub dst_chain_id: u64,
+    pub target_adapter: Pubkey,
+    pub mint: Pubkey,
+    pub initiator: Pubkey,
+    pub forward_amount: u64,
+    pub payload_hash: [u8; 32],
+    pub nonce: u64,
+    pub message_hash: [u8; 32],
+    /// Hash scheme version the message was hashed with (0 = legacy, no separator)
+    pub hash_version: u8,
+    pub bump: u8,
+    /// Mint decimals the amount was normalized from (None = raw units)
+    pub amount_decimals: Option<u8>,
+}
+
+// discriminator(8) + src/dst chain(16) + target_adapter(32) + mint(32) + initiator(32)
+// + forward_amount(8) + payload_hash(32) + nonce(8) + message_hash(32) + hash_version(1)
+// + bump(1) + amount_decimals(2)
+const MESSAGE_PREIMAGE_SPACE: usize = 8 + 16 + 32 + 32 + 32 + 8 + 32 + 8 + 32 + 1 + 1 + 2;
+
+impl MessagePreimage {
+    pub fn recompute_message_hash(&self) -> Result<[u8; 32]> {
+        source_message_hash(
+            self.hash_version,
+            self.src_chain_id,
+            self.dst_chain_id,
+            &self.target_adapter,
+            &self.mint,
+            self.forward_amount,
+            self.amount_decimals,
+            self.payload_hash,
+            self.nonce,
+        )
+    }
+}
+
+/// Seed of the zpx_lp_vaults PDA allowed to call withdraw_lp_fees (see zpx_lp_vaults).
+pub const LP_AUTHORITY_SEED: &[u8] = b"lp_authority";
+
+/// One-per-message marker proving the destination LP fee was settled.
+#[account]
+pub struct LpFeeSkim {
+    pub bump: u8,
+}
+
+pub const VAULT_REGISTRY_VERSION: u8 = 1;
+// discriminator(8) + mint(32) + protocol_vault(32) + relayer_vault(32) + version(1)
+// + created_at_slot(8) + bump(1)
+const VAULT_REGISTRY_SPACE: usize = 8 + 32 + 32 + 32 + 1 + 8 + 1;
+
+/// Per-mint pause flag checked by universal_bridge_transfer(_zpx_fee) and the forward paths.
+#[account]
+pub struct MintPause {
+    pub mint: Pubkey,
+    pub paused: bool,
+    pub updated_at_slot: u64,
+    pub bump: u8,
+}
+
+// discriminator(8) + mint(32) + paused(1) + updated_at_slot(8) + bump(1)
+const MINT_PAUSE_SPACE: usize = 8 + 32 + 1 + 8 + 1;
+
+/// Test-only pinned time read by time::now (`test-instructions` builds).
+#[account]
+pub struct ClockOverride {
+    pub slot: u64,
+    pub unix_timestamp: i64,
+    pub bump: u8,
+}
+
+// discriminator(8) + slot(8) + unix_timestamp(8) + bump(1)
+const CLOCK_OVERRIDE_SPACE: usize = 8 + 8 + 8 + 1;
+
+/// Optional vesting schedule for relayer fees paid into hub_relayer_vault.
+#[account]
+pub struct RelayerVestingConfig {
+    pub period_slots: u64,
+    pub bump: u8,
+}
+
+// discriminator(8) + period_slots(8) + bump(1)
+const RELAYER_VESTING_CONFIG_SPACE: usize = 8 + 8 + 1;
+
+/// Max message age for the route to and from one remote chain, at
+/// [b"route_validity", chain_id]. Absent or 0 = messages never expire.
+#[account]
+pub struct RouteValidity {
+    pub chain_id: u64,
+    pub max_age_secs: u64,
+    pub bump: u8,
+}
+
+// discriminator(8) + chain_id(8) + max_age_secs(8) + bump(1)
+const ROUTE_VALIDITY_SPACE: usize = 8 + 8 + 8 + 1;
+
+pub const REMOTE_CONFIG_SEED: &[u8] = b"remote_config";
+
+/// One guardian's signature (r | s | v) over a Wormhole query response
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GuardianSignature {
+    pub index: u8,
+    pub signature: [u8; 65],
+}
+
+/// The EVM router on one source chain and the latest config root proven for it by a
+/// Wormhole query, at [REMOTE_CONFIG_SEED, src_chain_id]. Finalize of messages from that
+/// chain requires a proven root equal to `expected_root`, no older than `max_age_secs`.
+#[account]
+#[derive(Default)]
+pub struct RemoteConfig {
+    pub src_chain_id: u64,
+    pub wormhole_chain: u16,
+    pub evm_router: [u8; 20],
+    pub expected_root: [u8; 32],
+    pub max_age_secs: u64,
+    pub verified_root: [u8; 32],
+    pub verified_block: u64,
+    pub verified_block_time_us: u64,
+    pub verified_slot: u64,
+    pub bump: u8,
+}
+
+// discriminator(8) + src_chain_id(8) + wormhole_chain(2) + evm_router(20) + expected_root(32)
+// + max_age_secs(8) + verified_root(32) + verified_block(8) + verified_block_time_us(8)
+// + verified_slot(8) + bump(1)
+const REMOTE_CONFIG_SPACE: usize = 8 + 8 + 2 + 20 + 32 + 8 + 32 + 8 + 8 + 8 + 1;
+
+impl RemoteConfig {
+    pub fn clear_verified(&mut self) {
+        self.verified_root = [0; 32];
+        self.verified_block = 0;
+        self.verified_block_time_us = 0;
+        self.verified_slot = 0;
+    }
+
+    /// Whether a message finalized at unix time `now` may rely on the source's config
+    pub fn check(&self, now: u64) -> Result<()> {
+        require!(self.verified_slot != 0, ErrorCode::RemoteConfigUnverified);
+        require!(
+            self.verified_root == self.expected_root,
+            ErrorCode::RemoteConfigRootMismatch
+        );
+        require!(
+            now.saturating_sub(self.verified_block_time_us / 1_000_000) <= self.max_age_secs,
+            ErrorCode::RemoteConfigStale
+        );
+        Ok(())
+    }
+}
+
+/// Ecosystem partner at [b"partner", partner_id]. Bridges naming it as referral hold
+/// `rebate_bps` of their protocol fee in the mint's rebate vault for `payout` to claim.
+#[account]
+pub struct Partner {
+    pub partner_id: u32,
+    pub payout: Pubkey,
+    pub rebate_bps: u16,
+    pub bump: u8,
+}
+
+// discriminator(8) + partner_id(4) + payout(32) + rebate_bps(2) + bump(1)
+const PARTNER_SPACE: usize = 8 + 4 + 32 + 2 + 1;
+
+impl Partner {
+    /// Partner share of a protocol fee (rounded down).
+    pub fn rebate_of(&self, protocol_fee: u64) -> u64 {
+        (protocol_fee as u128 * self.rebate_bps as u128 / 10_000) as u64
+    }
+}
+
+/// Rebates of one partner in one mint, at [b"partner_rebate", partner_id, mint]. The tokens
+/// sit in the shared [b"partner_rebate_vault", mint]; `accrued - claimed` is this partner's.
+#[account]
+#[derive(Default)]
+pub struct PartnerRebate {
+    pub partner_id: u32,
+    pub mint: Pubkey,
+    pub accrued: u64,
+    pub claimed: u64,
+    pub bump: u8,
+}
+
+// discriminator(8) + partner_id(4) + mint(32) + accrued(8) + claimed(8) + bump(1)
+const PARTNER_REBATE_SPACE: usize = 8 + 4 + 32 + 8 + 8 + 1;
+
+impl PartnerRebate {
+    pub fn unclaimed(&self) -> u64 {
+        self.accrued - self.claimed
+    }
+}
+
+/// One amount bracket of a FeePolicy; mirrors `zpx_core::fees::FeeTier`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeeTier {
+    pub min_amount: u64,
+    pub protocol_bps: u16,
+    pub relayer_bps: u16,
+}
+
+/// Fee schedule of the (mint, dst_chain_id) route, at [b"fee_policy", mint, dst_chain_id].
+/// Absent = the config's flat protocol_fee_bps / relayer_fee_bps. Escrow forwards key the
+/// route by `fee_policy_chain_id(dst_domain)`. A zero max fee leaves that fee uncapped.
+#[account]
+pub struct FeePolicy {
+    pub mint: Pubkey,
+    pub dst_chain_id: u64,
+    pub tiers_len: u8,
+    pub tiers: [FeeTier; MAX_FEE_TIERS],
+    pub protocol_min_fee: u64,
+    pub protocol_max_fee: u64,
+    pub relayer_min_fee: u64,
+    pub relayer_max_fee: u64,
+    pub bump: u8,
+}
+
+// discriminator(8) + mint(32) + dst_chain_id(8) + tiers_len(1) + tiers(MAX_FEE_TIERS * 12)
+// + min/max fees(4 * 8) + bump(1)
+const FEE_POLICY_SPACE: usize = 8 + 32 + 8 + 1 + MAX_FEE_TIERS * (8 + 2 + 2) + 4 * 8 + 1;
+
+impl FeePolicy {
+    /// Policy of the (mint, dst_chain_id) route, checked against the fee caps.
+    pub fn new(
+        mint: Pubkey,
+        dst_chain_id: u64,
+        tiers: &[FeeTier],
+        protocol_min_fee: u64,
+        protocol_max_fee: u64,
+        relayer_min_fee: u64,
+        relayer_max_fee: u64,
+    ) -> Result<Self> {
+        require!(
+            zpx_chains::fits_wire(dst_chain_id),
+            ErrorCode::ChainIdOutOfRange
+        );
+        require!(
+            !tiers.is_empty() && tiers.len() <= MAX_FEE_TIERS,
+            ErrorCode::InvalidFeePolicy
+        );
+        let mut policy = FeePolicy {
+            mint,
+            dst_chain_id,
+            tiers_len: tiers.len() as u8,
+            tiers: [FeeTier::default(); MAX_FEE_TIERS],
+            protocol_min_fee,
+            protocol_max_fee,
+            relayer_min_fee,
+            relayer_max_fee,
+            bump: 0,
+        };
+        policy.tiers[..tiers.len()].copy_from_slice(tiers);
+        policy
+            .to_core()
+            .validate(FEE_CAP_BPS, RELAYER_FEE_CAP_BPS)
+            .map_err(ErrorCode::from)?;
+        Ok(policy)
+    }
+
+    /// Write the policy to its PDA, creating it (paid by `payer`) on first use.
+    pub fn store<'info>(
+        mut self,
+        payer: &AccountInfo<'info>,
+        policy_ai: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+        program_id: &Pubkey,
+    ) -> Result<()> {
+        let dst_chain_id = self.dst_chain_id.to_le_bytes();
+        let (expected, bump) = Pubkey::find_program_address(
+            &[b"fee_policy", self.mint.as_ref(), &dst_chain_id],
+            program_id,
+        );
+        require_keys_eq!(
+            policy_ai.key(),
+            expected,
+            ErrorCode::InvalidFeePolicyAccount
+        );
+        if policy_ai.owner != program_id {
+            create_pda_account(
+                payer,
+                policy_ai,
+                system_program,
+                FEE_POLICY_SPACE,
+                &[b"fee_policy", self.mint.as_ref(), &dst_chain_id, &[bump]],
+                program_id,
+            )?;
+        }
+        self.bump = bump;
+        self.try_serialize(&mut &mut policy_ai.try_borrow_mut_data()?[..])?;
+        Ok(())
+    }
+
+    /// The zpx-core policy `evaluate_fee_policy` runs on.
+    pub fn to_core(&self) -> fees::FeePolicy {
+        let mut tiers = [fees::FeeTier::default(); MAX_FEE_TIERS];
+        for (core, t) in tiers.iter_mut().zip(&self.tiers) {
+            *core = fees::FeeTier {
+                min_amount: t.min_amount,
+                protocol_bps: t.protocol_bps,
+                relayer_bps: t.relayer_bps,
+            };
+        }
+        fees::FeePolicy {
+            tiers_len: self.tiers_len,
+            tiers,
+            protocol: fees::FeeBounds {
+                min: self.protocol_min_fee,
+                max: self.protocol_max_fee,
+            },
+            relayer: fees::FeeBounds {
+                min: self.relayer_min_fee,
+                max: self.relayer_max_fee,
+            },
+        }
+    }
+}
+
+/// Declarative description of a new mint route for onboard_route: the spoke as create_spoke
+/// takes it and the route's fee policy as set_fee_policy takes it.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct RouteOnboarding {
+    pub spoke_id: u32,
+    pub adapter_program: Pubkey,
+    pub direct_relayer_payout: bool,
+    pub version: u8,
+    pub metadata: Option<String>,
+    pub payload_schema: Option<PayloadSchema>,
+    pub dst_chain_id: u64,
+    pub fee_tiers: Vec<FeeTier>,
+    pub protocol_min_fee: u64,
+    pub protocol_max_fee: u64,
+    pub relayer_min_fee: u64,
+    pub relayer_max_fee: u64,
+}
+
+/// Decimals metadata of a (chain, mint) route. PDA [b"route_decimals", chain_id, mint].
+/// With `normalize` set, messages carry the amount in the canonical 18-decimal
+/// representation (`amount::to_canonical`) instead of raw units.
+#[account]
+pub struct RouteDecimals {
+    pub chain_id: u64,
+    pub mint: Pubkey,
+    /// The mint's decimals when the route was configured
+    pub decimals: u8,
+    pub normalize: bool,
+    pub bump: u8,
+}
+
+// discriminator(8) + chain_id(8) + mint(32) + decimals(1) + normalize(1) + bump(1)
+const ROUTE_DECIMALS_SPACE: usize = 8 + 8 + 32 + 1 + 1 + 1;
+
+pub const CRANK_REWARD_SEED: &[u8] = b"crank_reward";
+
+/// Reward for finalizing messages of `mint` through crank_finalize_message_v1. PDA
+/// [CRANK_REWARD_SEED, mint]. Finalize trusts the source adapter allowlist rather than an
+/// attestation, so payouts are bounded by `budget`, which the admin tops up.
+#[account]
+pub struct CrankReward {
+    pub mint: Pubkey,
+    /// Paid per finalized message, in mint base units
+    pub amount: u64,
+    /// Still payable in total; each reward is deducted
+    pub budget: u64,
+    pub bump: u8,
+}
+
+// discriminator(8) + mint(32) + amount(8) + budget(8) + bump(1)
+const CRANK_REWARD_SPACE: usize = 8 + 32 + 8 + 8 + 1;
+
+/// Pending adapter addition, PDA [ADAPTER_PROPOSAL_SEED, adapter]; closed on activation or
+/// cancellation.
+#[account]
+pub struct AdapterProposal {
+    pub adapter: Pubkey,
+    pub proposer: Pubkey,
+    /// First slot activate_adapter accepts
+    pub activation_slot: u64,
+    pub bump: u8,
+}
+
+/// Allowlisted adapter program, PDA [ADAPTER_SEED, program]. Handlers that check the
+/// allowlist find it among their remaining accounts; remove_adapter closes it.
+#[account]
+pub struct AdapterEntry {
+    pub program: Pubkey,
+    pub activated_at_slot: u64,
+    pub bump: u8,
+}
+
+// discriminator(8) + program(32) + activated_at_slot(8) + bump(1)
+const ADAPTER_ENTRY_SPACE: usize = 8 + 32 + 8 + 1;
+
+// discriminator(8) + adapter(32) + proposer(32) + activation_slot(8) + bump(1)
+const ADAPTER_PROPOSAL_SPACE: usize = 8 + 32 + 32 + 8 + 1;
+
+pub const USDC_FAST_PATH_SEED: &[u8] = b"usdc_fast_path";
+
+/// Pre-resolved forward route for one mint, written by configure_usdc_fast_path. PDA
+/// [USDC_FAST_PATH_SEED, mint]. Addresses are the canonical PDAs at configure time, so
+/// forward_usdc_fast only compares keys.
+#[account]
+pub struct UsdcFastPath {
+    pub mint: Pubkey,
+    pub spoke_id: u32,
+    /// Slot of the spoke in Registry.spokes; forwards fail with FastPathStale if it moves
+    pub spoke_index: u8,
+    pub config: Pubkey,
+    pub registry: Pubkey,
+    pub hub_protocol_vault: Pubkey,
+    pub adapter_target_token_account: Pubkey,
+    pub mint_pause: Pubkey,
+    pub spoke_relayers: Pubkey,
+    pub router_stats: Pubkey,
+    pub rent_ledger: Pubkey,
+    pub bump: u8,
+}
+
+// discriminator(8) + mint(32) + spoke_id(4) + spoke_index(1) + 8 cached addresses(32 each)
+// + bump(1)
+const USDC_FAST_PATH_SPACE: usize = 8 + 32 + 4 + 1 + (32 * 8) + 1;
+
+/// Validity window (unix seconds) the source leg embeds on routes with a max age; the
+/// message commits to it through `hash::validity_payload_hash`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MessageValidity {
+    pub sent_at: u64,
+    pub expires_at: u64,
+}
+
+impl MessageValidity {
+    pub fn payload_hash(&self, payload_hash: [u8; 32]) -> [u8; 32] {
+        hash::validity_payload_hash(payload_hash, self.sent_at, self.expires_at)
+    }
+
+    /// Fails with MessageExpired past the embedded expiry, or once the message is older than
+    /// the destination's own `max_age_secs` (0 = no local bound).
+    pub fn check(&self, now: u64, max_age_secs: u64) -> Result<()> {
+        require!(
+            now <= self.expires_at
+                && (max_age_secs == 0 || now.saturating_sub(self.sent_at) <= max_age_secs),
+            ErrorCode::MessageExpired
+        );
+        Ok(())
+    }
+}
+
+/// A relayer's hub_relayer_vault balance for one mint. `locked` unlocks linearly until
+/// `end_slot`; each new fee re-spreads the remaining locked balance over a fresh period.
+#[account]
+pub struct RelayerVesting {
+    pub relayer: Pubkey,
+    pub mint: Pubkey,
+    pub locked: u64,
+    pub unlocked: u64,
+    pub start_slot: u64,
+    pub end_slot: u64,
+    pub claimed_total: u64,
+    pub bump: u8,
+}
+
+// discriminator(8) + relayer(32) + mint(32) + locked(8) + unlocked(8) + start_slot(8)
+// + end_slot(8) + claimed_total(8) + bump(1)
+const RELAYER_VESTING_SPACE: usize = 8 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 1;
+
+/// A relayer's forward latency record: slots from escrow deposit to first dispatch, summed
+/// over every message it picked up, for rotation decisions.
+#[account]
+pub struct RelayerActivity {
+    pub relayer: Pubkey,
+    pub forwards: u64,
+    pub total_latency_slots: u64,
+    pub max_latency_slots: u64,
+    /// Forwards slower than their spoke's sla_target_slots
+    pub sla_breaches: u64,
+    pub last_forward_slot: u64,
+    pub bump: u8,
+}
+
+// discriminator(8) + relayer(32) + forwards(8) + total_latency_slots(8) + max_latency_slots(8)
+// + sla_breaches(8) + last_forward_slot(8) + bump(1)
+const RELAYER_ACTIVITY_SPACE: usize = 8 + 32 + 8 + 8 + 8 + 8 + 8 + 1;
+
+impl RelayerActivity {
+    pub fn average_latency_slots(&self) -> u64 {
+        self.total_latency_slots
+            .checked_div(self.forwards)
+            .unwrap_or_default()
+    }
+
+    /// Add one forward; true when it breached `target_slots` (0 = no SLA).
+    pub fn record(&mut self, latency_slots: u64, target_slots: u64, slot: u64) -> bool {
+        self.forwards += 1;
+        self.total_latency_slots = self.total_latency_slots.saturating_add(latency_slots);
+        self.max_latency_slots = self.max_latency_slots.max(latency_slots);
+        self.last_forward_slot = slot;
+        let breached = target_slots != 0 && latency_slots > target_slots;
+        if breached {
+            self.sla_breaches += 1;
+        }
+        breached
+    }
+}
+
+impl RelayerVesting {
+    /// Portion of `locked` vested by `slot`.
+    pub fn vested_at(&self, slot: u64) -> u64 {
+        if slot >= self.end_slot {
+            return self.locked;
+        }
+        let elapsed = slot.saturating_sub(self.start_slot) as u128;
+        let span = (self.end_slot - self.start_slot) as u128;
+        (self.locked as u128 * elapsed / span) as u64
+    }
+
+    /// Move everything vested by `slot` into `unlocked`.
+    pub fn checkpoint(&mut self, slot: u64) {
+        let vested = self.vested_at(slot);
+        self.locked -= vested;
+        self.unlocked += vested;
+        self.start_slot = slot.max(self.start_slot);
+    }
+
+    pub fn accrue(&mut self, amount: u64, slot: u64, period_slots: u64) -> Result<()> {
+        self.checkpoint(slot);
+        if period_slots == 0 {
+            self.unlocked = self
+                .unlocked
+                .checked_add(amount)
+                .ok_or(ErrorCode::MathOverflow)?;
+            return Ok(());
+        }
+        self.locked = self
+            .locked
+            .checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        self.start_slot = slot;
+        self.end_slot = slot.saturating_add(period_slots);
+        Ok(())
+    }
+
+    /// Release the whole locked balance; returns the amount released.
+    pub fn unlock_all(&mut self) -> u64 {
+        let amount = std::mem::take(&mut self.locked);
+        self.unlocked += amount;
+        self.end_slot = self.start_slot;
+        amount
+    }
+}
+
+/// Per-mint record of the canonical hub vaults created by initialize_hub_vaults.
+#[account]
+pub struct VaultRegistry {
+    pub mint: Pubkey,
+    pub protocol_vault: Pubkey,
+    pub relayer_vault: Pubkey,
+    pub version: u8,
+    pub created_at_slot: u64,
+    pub bump: u8,
+}
+
+/// Per-mint oracle binding used to express limits in USD (6 decimals).
+#[account]
+pub struct OracleConfig {
+    pub mint: Pubkey,
+    pub decimals: u8,
+    /// Owner program expected for `price_account` (Pyth oracle program)
+    pub oracle_program: Pubkey,
+    pub price_account: Pubkey,
+    pub max_staleness_slots: u64,
+    /// Maximum confidence interval relative to price
+    pub max_conf_bps: u16,
+    /// Minimum forward value in USD (1e-6 units); 0 disables the check
+    pub min_forward_usd_e6: u64,
+    pub bump: u8,
+}
+
+/// Latest keccak commitment over the hub parameters (see `config_commitment`).
+#[account]
+pub struct ConfigAttestation {
+    pub root: [u8; 32],
+    pub sequence: u64,
+    pub updated_at_slot: u64,
+    pub bump: u8,
+}
+
+/// Value currently in flight towards a destination chain for a given mint.
+#[account]
+pub struct InFlight {
+    pub mint: Pubkey,
+    pub dst_chain_id: u64,
+    pub amount: u64,
+    pub exposure_cap: u64,
+    pub bump: u8,
+}
+
+/// SCHEMA FROZEN. Do not reorder/rename. Bump with V2 if changes are required.
+#[event]
+pub struct BridgeInitiated {
+    pub route_id: [u8; 32],
+    pub user: Pubkey,
+    pub token: Pubkey,
+    pub target: Pubkey,
+    pub forwarded_amount: u64,
+    pub protocol_fee: u64,
+    pub relayer_fee: u64,
+    pub payload_hash: [u8; 32],
+    pub src_chain_id: u16,
+    pub dst_chain_id: u16,
+    pub nonce: u64,
+}
+
+/// SCHEMA FROZEN. Do not reorder/rename. Bump with V2 if changes are required.
+#[event]
+pub struct UniversalBridgeInitiated {
+    pub route_id: [u8; 32],
+    pub payload_hash: [u8; 32],
+    pub message_hash: [u8; 32],
+    pub global_route_id: [u8; 32],
+    pub user: Pubkey,
+    pub token: Pubkey,
+    pub target: Pubkey,
+    pub forwarded_amount: u64,
+    pub protocol_fee: u64,
+    pub relayer_fee: u64,
+    pub src_chain_id: u16,
+    pub dst_chain_id: u16,
+    pub nonce: u64,
+}
+
+/// SCHEMA FROZEN. Do not reorder/rename. Bump with V2 if changes are required.
+#[event]
+pub struct FeeAppliedSource {
+    pub message_hash: [u8; 32],
+    pub asset: Pubkey,
+    pub payer: Pubkey,
+    pub target: Pubkey,
+    pub protocol_fee: u64,
+    pub relayer_fee: u64,
+    pub fee_recipient: Pubkey,
+    pub applied_at: u64,
+}
+
+/// Validity window embedded in an outbound message. `payload_hash` is the inner hash that
+/// finalize takes alongside the window; the message itself commits to the wrapped hash.
+#[event]
+pub struct MessageValidityEmbedded {
+    pub message_hash: [u8; 32],
+    pub payload_hash: [u8; 32],
+    pub sent_at: u64,
+    pub expires_at: u64,
+}
+
+/// Outbound payload carries the compressed header: `algorithm` (COMPRESSION_ZSTD /
+/// COMPRESSION_DEFLATE) tells the destination leg how to unpack it, and `decompressed_len`
+/// is the size it declared. `payload_hash` still commits to the bytes as sent.
+#[event]
+pub struct PayloadCompressed {
+    pub message_hash: [u8; 32],
+    pub payload_hash: [u8; 32],
+    pub algorithm: u8,
+    pub compressed_len: u32,
+    pub decompressed_len: u32,
+}
+
+/// Destination finalization record for cross-chain explorers; `origin_tx_hash` is the
+/// source transaction (e.g. the EVM tx) when the relayer supplied it.
+#[event]
+pub struct MessageFinalized {
+    pub message_hash: [u8; 32],
+    pub src_chain_id: u64,
+    pub dst_chain_id: u64,
+    pub asset: Pubkey,
+    pub amount: u64,
+    pub relayer: Pubkey,
+    pub origin_tx_hash: Option<[u8; 32]>,
+}
+
+/// SCHEMA FROZEN. Do not reorder/rename. Bump with V2 if changes are required.
+#[event]
+pub struct FeeAppliedDest {
+    pub message_hash: [u8; 32],
+    pub src_chain_id: u16,
+    pub dst_chain_id: u16,
+    pub router: Pubkey,
+    pub asset: Pubkey,
+    pub amount: u64,
+    pub protocol_bps: u16,
+    pub lp_bps: u16,
+    pub collector: Pubkey,
+    pub applied_at: u64,
+}
+
+/// SCHEMA FROZEN. Do not reorder/rename. Bump with V3 if changes are required.
+/// Same layout as FeeAppliedSource plus the ZPX amount burned in lieu of the in-kind
+/// protocol fee (0 when the protocol fee was skimmed from the bridged token) and the
+/// FeeRecipientHistory epoch that was active when the fee was collected.
+#[event]
+pub struct FeeAppliedSourceV2 {
+    pub message_hash: [u8; 32],
+    pub asset: Pubkey,
+    pub payer: Pubkey,
+    pub target: Pubkey,
+    pub protocol_fee: u64,
+    pub relayer_fee: u64,
+    pub fee_recipient: Pubkey,
+    pub applied_at: u64,
+    pub zpx_fee_burned: u64,
+    pub fee_recipient_epoch: u64,
+}
+
+/// SCHEMA FROZEN. Do not reorder/rename. Bump with V4 if changes are required.
+/// Same layout as FeeAppliedSourceV2 plus the Config.config_version the fees were computed
+/// under, to look up in the ConfigHistory PDA. Emitted alongside V2.
+#[event]
+pub struct FeeAppliedSourceV3 {
+    pub message_hash: [u8; 32],
+    pub asset: Pubkey,
+    pub payer: Pubkey,
+    pub target: Pubkey,
+    pub protocol_fee: u64,
+    pub relayer_fee: u64,
+    pub fee_recipient: Pubkey,
+    pub applied_at: u64,
+    pub zpx_fee_burned: u64,
+    pub fee_recipient_epoch: u64,
+    pub config_version: u64,
+}
+
+#[event]
+pub struct AdapterProposed {
+    pub admin: Pubkey,
+    pub program: Pubkey,
+    /// First slot activate_adapter accepts
+    pub activation_slot: u64,
+}
+#[event]
+pub struct AdapterActivated {
+    pub admin: Pubkey,
+    pub program: Pubkey,
+    pub activation_slot: u64,
+    pub slot: u64,
+}
+#[event]
+pub struct AdapterProposalCancelled {
+    pub authority: Pubkey,
+    pub program: Pubkey,
+}
+#[event]
+pub struct AdapterRemoved {
+    pub admin: Pubkey,
+    pub program: Pubkey,
+}
+/// A legacy Config.adapters entry moved to its AdapterEntry PDA; `legacy_left` entries remain.
+#[event]
+pub struct AdapterMigrated {
+    pub admin: Pubkey,
+    pub program: Pubkey,
+    pub legacy_left: u8,
+}
+#[event]
+pub struct ConfigUpdated {
+    pub admin: Pubkey,
+    pub fee_recipient: Pubkey,
+    pub src_chain_id: u64,
+    pub relayer_fee_bps: u16,
+}
+
+#[event]
+pub struct MessageCancelled {
+    pub user: Pubkey,
+    pub nonce: u64,
+    pub spoke_id: u32,
+    pub amount: u64,
+    pub lamport_budget: u64,
+}
+
+#[event]
+pub struct SpokeWeightUpdated {
+    pub spoke_id: u32,
+    pub route_group: u32,
+    pub weight: u16,
+}
+
+#[event]
+pub struct SpokeTargetOwnerUpdated {
+    pub spoke_id: u32,
+    pub target_owner: Pubkey,
+}
+
+#[event]
+pub struct SpokeRelayerPayoutUpdated {
+    pub spoke_id: u32,
+    pub relayer_payout: u8,
+}
+
+#[event]
+pub struct SpokeSlaTargetUpdated {
+    pub spoke_id: u32,
+    pub target_slots: u64,
+}
+
+#[event]
+pub struct SpokeAdapterCuUpdated {
+    pub spoke_id: u32,
+    pub max_adapter_cu: u32,
+}
+
+/// An adapter CPI consumed more compute than its spoke allows; the instruction fails with
+/// AdapterBudgetExceeded right after this event.
+#[event]
+pub struct AdapterComputeExceeded {
+    pub spoke_id: u32,
+    pub adapter: Pubkey,
+    pub max_adapter_cu: u32,
+    pub consumed_cu: u64,
+}
+
+/// A forward picked up later than its spoke's SLA target. The average and breach count are
+/// the relayer's RelayerActivity totals including this forward.
+#[event]
+pub struct SlaBreached {
+    pub relayer: Pubkey,
+    pub spoke_id: u32,
+    pub user: Pubkey,
+    pub nonce: u64,
+    pub latency_slots: u64,
+    pub target_slots: u64,
+    pub average_latency_slots: u64,
+    pub sla_breaches: u64,
+}
+
+#[event]
+pub struct SpokeImbalanceThresholdUpdated {
+    pub spoke_id: u32,
+    pub threshold: u64,
+}
+
+#[event]
+pub struct SpokeFinalizedAcked {
+    pub spoke_id: u32,
+    pub amount: u64,
+    pub dispatched_amount: u64,
+    pub finalized_amount: u64,
+}
+
+/// Emitted when a spoke's outstanding volume crosses its threshold: `imbalanced` is true on
+/// the forward that pushes it above, false on the ack that brings it back.
+#[event]
+pub struct SpokeImbalance {
+    pub spoke_id: u32,
+    pub dispatched_amount: u64,
+    pub finalized_amount: u64,
+    pub threshold: u64,
+    pub imbalanced: bool,
+}
+
+#[event]
+pub struct SpokeFallbackUpdated {
+    pub spoke_id: u32,
+    pub fallback_spoke_id: u32,
+}
+
+/// forward_auto dispatched through `fallback_spoke_id` because the escrowed spoke was paused.
+#[event]
+pub struct RoutedViaFallback {
+    pub nonce: u64,
+    pub primary_spoke_id: u32,
+    pub fallback_spoke_id: u32,
+}
+
+/// Selection rationale for forward_balanced: `ticket = nonce % total_weight` landed in
+/// `spoke_id`'s cumulative weight range.
+#[event]
+pub struct SpokeSelected {
+    pub route_group: u32,
+    pub nonce: u64,
+    pub requested_spoke_id: u32,
+    pub spoke_id: u32,
+    pub weight: u16,
+    pub ticket: u64,
+    pub total_weight: u64,
+    pub candidates: u8,
+}
+
+#[event]
+pub struct SpokeDeprecated {
+    pub spoke_id: u32,
+    pub successor_spoke_id: u32,
+    pub deprecated_at_slot: u64,
+    pub sunset_slot: u64,
+}
+
+#[event]
+pub struct SpokeDeprecationWarning {
+    pub spoke_id: u32,
+    pub successor_spoke_id: u32,
+    pub sunset_slot: u64,
+}
+
+#[event]
+pub struct AdapterPinned {
+    pub spoke_id: u32,
+    pub adapter_program: Pubkey,
+    pub program_data: Pubkey,
+    pub deploy_slot: u64,
+    pub upgrade_authority: Pubkey,
+}
+
+#[event]
+pub struct FinalizationBountyPosted {
+    pub user: Pubkey,
+    pub nonce: u64,
+    pub message_hash: [u8; 32],
+    pub amount: u64,
+    pub expiry_slot: u64,
+}
+
+#[event]
+pub struct FinalizationBountyClaimed {
+    pub user: Pubkey,
+    pub nonce: u64,
+    pub message_hash: [u8; 32],
+    pub relayer: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct FinalizationBountyRefunded {
+    pub user: Pubkey,
+    pub nonce: u64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct StateSnapshotConfig {
+    pub slot: u64,
+    pub admin: Pubkey,
+    pub fee_recipient: Pubkey,
+    pub src_chain_id: u64,
+    pub relayer_fee_bps: u16,
+    pub protocol_fee_bps: u16,
+    pub relayer_pubkey: Pubkey,
+    pub accept_any_token: bool,
+    pub allowed_token_mint: Pubkey,
+    pub direct_relayer_payout_default: bool,
+    pub min_forward_amount: u64,
+    pub adapters: Vec<Pubkey>,
+    pub paused: bool,
+    pub max_relayer_fee_lamports: u64,
+    pub usd_limits_enabled: bool,
+    pub strict_vaults: bool,
+    pub lp_fee_bps: u16,
+    pub lp_vault_program: Pubkey,
+    pub killed: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct SpokeSnapshot {
+    pub spoke_id: u32,
+    pub adapter_program: Pubkey,
+    pub enabled: bool,
+    pub paused: bool,
+    pub version: u8,
+    pub category: u8,
+    pub route_group: u32,
+    pub weight: u16,
+    pub fallback_spoke_id: u32,
+    pub dispatched_amount: u64,
+    pub finalized_amount: u64,
+}
+
+impl From<&SpokeEntry> for SpokeSnapshot {
+    fn from(s: &SpokeEntry) -> Self {
+        SpokeSnapshot {
+            spoke_id: s.spoke_id,
+            adapter_program: s.adapter_program,
+            enabled: s.enabled,
+            paused: s.paused,
+            version: s.version,
+            category: s.category,
+            route_group: s.route_group,
+            weight: s.weight,
+            fallback_spoke_id: s.fallback_spoke_id,
+            dispatched_amount: s.dispatched_amount,
+            finalized_amount: s.finalized_amount,
+        }
+    }
+}
+
+/// One registry position, emitted by emit_spoke / emit_spokes for log-only indexers.
+#[event]
+pub struct SpokeEnumerated {
+    pub slot: u64,
+    pub index: u8,
+    pub spokes_len: u8,
+    pub spoke: SpokeSnapshot,
+}
+
+#[event]
+pub struct StateSnapshotSpokes {
+    pub slot: u64,
+    pub spokes_len: u8,
+    pub spokes: Vec<SpokeSnapshot>,
+}
+
+#[event]
+pub struct RegistryExportChunk {
+    pub slot: u64,
+    pub start: u8,
+    pub spokes_len: u8,
+    pub chunk_hash: [u8; 32],
+    pub registry_hash: [u8; 32],
+    pub entries: Vec<SpokeEntry>,
+}
+
+#[event]
+pub struct RegistryImported {
+    pub start: u8,
+    pub count: u8,
+    pub spokes_len: u8,
+    pub registry_hash: [u8; 32],
+}
+
+#[event]
+pub struct StateSnapshotVault {
+    pub slot: u64,
+    pub vault_registry: Pubkey,
+    pub mint: Pubkey,
+    pub protocol_vault: Pubkey,
+    pub relayer_vault: Pubkey,
+    pub version: u8,
+}
+
+#[event]
+pub struct RouterStatsSnapshot {
+    pub total_initiated: u64,
+    pub total_forwarded: u64,
+    pub volumes: Vec<MintVolume>,
+    pub days: [DayBucket; STATS_DAY_BUCKETS],
+    pub failures: [u64; STATS_FAILURE_CLASSES],
+    pub updated_at_slot: u64,
+}
+
+#[event]
+pub struct SpokeRelayerAdded {
+    pub spoke_id: u32,
+    pub relayer: Pubkey,
+}
+
+#[event]
+pub struct AllowedMintAdded {
+    pub mint: Pubkey,
+}
+
+#[event]
+pub struct AllowedMintRemoved {
+    pub mint: Pubkey,
+}
+
+#[event]
+pub struct IntentSignerAdded {
+    pub signer: [u8; 20],
+}
+
+#[event]
+pub struct IntentSignerRemoved {
+    pub signer: [u8; 20],
+}
+
+#[event]
+pub struct BridgeIntentExecuted {
+    pub digest: [u8; 32],
+    pub signer: [u8; 20],
+    pub user: Pubkey,
+    pub escrow_nonce: u64,
+    pub submitter: Pubkey,
+}
+
+/// An admin instruction was retried with an already-applied idempotency key and skipped.
+#[event]
+pub struct AdminOpReplayed {
+    pub key: u64,
+}
+
+/// The last `entries.len()` admin operations, newest first, as read by emit_admin_audit.
+#[event]
+pub struct AdminAuditTrail {
+    pub entries: Vec<AdminAuditEntry>,
+    /// Operations recorded since the log was created
+    pub total: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RentReconciled {
+    pub buckets: [RentBucket; RENT_KINDS],
+    pub total_accounts: u64,
+    pub total_lamports: u64,
+    pub updated_at_slot: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RouteMaxAgeUpdated {
+    pub chain_id: u64,
+    pub max_age_secs: u64,
+}
+
+#[event]
+pub struct RemoteConfigSet {
+    pub src_chain_id: u64,
+    pub wormhole_chain: u16,
+    pub evm_router: [u8; 20],
+    pub expected_root: [u8; 32],
+    pub max_age_secs: u64,
+}
+
+/// A Wormhole query proved the source router's config root; `matches` = it equals the
+/// expected root, so finalize accepts messages from the chain again.
+#[event]
+pub struct RemoteConfigVerified {
+    pub src_chain_id: u64,
+    pub guardian_set_index: u32,
+    pub config_root: [u8; 32],
+    pub block_number: u64,
+    pub block_time_us: u64,
+    pub matches: bool,
+}
+
+#[event]
+pub struct PartnerSet {
+    pub partner_id: u32,
+    pub payout: Pubkey,
+    pub rebate_bps: u16,
+}
+
+/// A referred bridge held `rebate` of its protocol fee for the partner; `accrued` is the
+/// partner's running total in the mint.
+#[event]
+pub struct PartnerRebateAccrued {
+    pub partner_id: u32,
+    pub mint: Pubkey,
+    pub user: Pubkey,
+    pub message_hash: [u8; 32],
+    pub rebate: u64,
+    pub accrued: u64,
+}
+
+#[event]
+pub struct PartnerRebateClaimed {
+    pub partner_id: u32,
+    pub mint: Pubkey,
+    pub payout: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct FeePolicyUpdated {
+    pub mint: Pubkey,
+    pub dst_chain_id: u64,
+    pub tiers: Vec<FeeTier>,
+    pub protocol_min_fee: u64,
+    pub protocol_max_fee: u64,
+    pub relayer_min_fee: u64,
+    pub relayer_max_fee: u64,
+}
+
+#[event]
+pub struct RelayerCosignUpdated {
+    pub mint: Pubkey,
+    pub threshold: u64,
+    pub required_weight: u64,
+    pub cosigners: Vec<Pubkey>,
+    pub weights: Vec<u64>,
+}
+
+/// Forward of an escrow at or above its mint's co-signing threshold, with the registered
+/// relayers that signed it and their summed weight.
+#[event]
+pub struct ForwardCosigned {
+    pub user: Pubkey,
+    pub nonce: u64,
+    pub spoke_id: u32,
+    pub amount: u64,
+    pub weight: u64,
+    pub cosigners: Vec<Pubkey>,
+}
+
+#[event]
+pub struct RouteDecimalsUpdated {
+    pub chain_id: u64,
+    pub mint: Pubkey,
+    pub decimals: u8,
+    pub normalize: bool,
+}
+
+#[event]
+pub struct CrankRewardUpdated {
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub budget: u64,
+}
+
+#[event]
+pub struct UsdcFastPathConfigured {
+    pub mint: Pubkey,
+    pub spoke_id: u32,
+    pub spoke_index: u8,
+    pub adapter_target_token_account: Pubkey,
+}
+
+#[event]
+pub struct CrankRewardPaid {
+    pub message_hash: [u8; 32],
+    pub mint: Pubkey,
+    pub cranker: Pubkey,
+    pub amount: u64,
+    pub budget_left: u64,
+}
+
+/// A source leg on a normalized route hashed its amount as `amount_e18` rather than the raw
+/// `amount` of a mint with `decimals`.
+#[event]
+pub struct AmountNormalized {
+    pub message_hash: [u8; 32],
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub decimals: u8,
+    pub amount_e18: [u8; 32],
+}
+
+#[event]
+pub struct RelayerVestingUpdated {
+    pub period_slots: u64,
+}
+
+#[event]
+pub struct RelayerFeesClaimed {
+    pub relayer: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub still_locked: u64,
+}
+
+#[event]
+pub struct RelayerVestingUnlocked {
+    pub relayer: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ConfigVersionBumped {
+    pub version: u64,
+    pub slot: u64,
+    /// Whether the ConfigHistory PDA recorded this version
+    pub recorded: bool,
+}
+
+#[event]
+pub struct FeatureToggled {
+    pub feature: u8,
+    pub enabled: bool,
+    pub features: u64,
+}
+
+#[event]
+pub struct InstructionPauseUpdated {
+    pub family: u8,
+    pub paused: bool,
+    pub paused_families: u8,
+}
+
+#[event]
+pub struct BridgeHookUpdated {
+    pub hook: Pubkey,
+    pub best_effort: bool,
+}
+
+#[event]
+pub struct BridgeHookInvoked {
+    pub hook: Pubkey,
+    pub message_hash: [u8; 32],
+}
+
+#[event]
+pub struct BridgeHookSkipped {
+    pub hook: Pubkey,
+    pub message_hash: [u8; 32],
+}
+
+#[event]
+pub struct MintPaused {
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+    pub slot: u64,
+}
+
+#[event]
+pub struct MintUnpaused {
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+    pub slot: u64,
+}
+
+#[event]
+pub struct SpokeCategoryUpdated {
+    pub spoke_id: u32,
+    pub category: u8,
+}
+
+/// pause_category / enable_category result; `spokes` is how many entries were touched.
+#[event]
+pub struct CategoryPaused {
+    pub category: u8,
+    pub paused: bool,
+    pub spokes: u8,
+    pub slot: u64,
+}
+
+#[event]
+pub struct SandboxInitialized {
+    pub namespace: [u8; SANDBOX_NAMESPACE_LEN],
+    pub config: Pubkey,
+    pub registry: Pubkey,
+    pub admin: Pubkey,
+}
+
+#[event]
+pub struct GlobalKillActivated {
+    pub sentinel: Pubkey,
+    pub spokes_paused: u8,
+    pub slot: u64,
+}
+
+/// The adapter took more than the forwarded net amount out of its target token account, or
+/// left a new delegate on it. Logged right before the forward reverts.
+#[event]
+pub struct BalanceAssertionFailed {
+    pub spoke_id: u32,
+    pub adapter_program: Pubkey,
+    pub target: Pubkey,
+    pub net_amount: u64,
+    pub amount_before: u64,
+    pub amount_after: u64,
+    /// Delegate left on the target after the CPI (default = none)
+    pub delegate: Pubkey,
+}
+
+#[event]
+pub struct RouteAvailabilityUpdated {
+    pub flags: u8,
+    pub spoke_bits: u32,
+    pub slot: u64,
+}
+
+#[event]
+pub struct KillReleaseScheduled {
+    pub release_slot: u64,
+}
+
+#[event]
+pub struct GlobalKillReleased {
+    pub admin: Pubkey,
+    pub slot: u64,
+}
+
+#[event]
+pub struct FeeSplitApplied {
+    pub message_hash: [u8; 32],
+    pub asset: Pubkey,
+    pub index: u8,
+    pub recipient: Pubkey,
+    pub bps: u16,
+    pub amount: u64,
+}
+
+#[event]
+pub struct SpokeRelayerRemoved {
+    pub spoke_id: u32,
+    pub relayer: Pubkey,
+}
+
+#[event]
+pub struct ForwardEscrowed {
+    pub user: Pubkey,
+    pub mint: Pubkey,
+    pub spoke_id: u32,
+    pub nonce: u64,
+    pub amount: u64,
+    pub lamport_budget: u64,
+    pub escrow: Pubkey,
+    pub relayer_tip: u64,
+}
+
+#[event]
+pub struct RouteSessionBegun {
+    pub user: Pubkey,
+    pub nonce: u64,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub steps_expected: u8,
+    pub plan_hash: [u8; 32],
+    pub expiry_slot: u64,
+}
+
+#[event]
+pub struct RouteStepAttached {
+    pub user: Pubkey,
+    pub nonce: u64,
+    pub index: u8,
+    pub kind: u8,
+    pub program: Pubkey,
+}
+
+#[event]
+pub struct RouteSessionCommitted {
+    pub user: Pubkey,
+    pub nonce: u64,
+    pub amount: u64,
+    pub target_token_account: Pubkey,
+}
+
+#[event]
+pub struct RouteSessionRefunded {
+    pub user: Pubkey,
+    pub nonce: u64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct RelayerTipIncreased {
+    pub user: Pubkey,
+    pub nonce: u64,
+    pub added: u64,
+    pub relayer_tip: u64,
+}
+
+#[event]
+pub struct RelayerTipClaimed {
+    pub user: Pubkey,
+    pub nonce: u64,
+    pub relayer: Pubkey,
+    pub relayer_tip: u64,
+}
+
+#[event]
+pub struct PartialForwarded {
+    pub user: Pubkey,
+    pub nonce: u64,
+    pub spoke_id: u32,
+    pub portion: u64,
+    pub forwarded: u64,
+    pub total: u64,
+    pub complete: bool,
+}
+
+#[event]
+pub struct ForwardEscrowReclaimed {
+    pub user: Pubkey,
+    pub nonce: u64,
+    pub amount: u64,
+}
+
+/// Rent reclaimed from a closed escrow (or a cancelled message's vault), paid out under the
+/// Config.rent_refund_policy in force at the close.
+#[event]
+pub struct EscrowRentRefunded {
+    pub escrow: Pubkey,
+    pub user: Pubkey,
+    /// Signer of the closing instruction
+    pub caller: Pubkey,
+    pub policy: u8,
+    pub rent: u64,
+    pub payer_share: u64,
+    pub treasury_share: u64,
+    pub caller_share: u64,
+}
+
+#[event]
+pub struct RentRefundPolicyUpdated {
+    pub policy: u8,
+    pub caller_bps: u16,
+}
+
+#[event]
+pub struct RentTreasurySwept {
+    pub fee_recipient: Pubkey,
+    pub amount: u64,
+}
+
+/// A message's first partial dispatch staged its fees, quoted once on the whole escrow.
+#[event]
+pub struct ForwardFeesStaged {
+    pub user: Pubkey,
+    pub nonce: u64,
+    pub protocol_fee: u64,
+    pub relayer_fee: u64,
+}
+
+/// A dispatch left its token fees in the escrow vault; totals include earlier dispatches.
+#[event]
+pub struct EscrowFeesHeld {
+    pub user: Pubkey,
+    pub nonce: u64,
+    pub protocol_fee: u64,
+    pub relayer_fee: u64,
+    pub held_protocol_fee: u64,
+    pub held_relayer_fee: u64,
+}
+
+/// Held fees paid out to the hub vaults (or the relayer) once the dispatch was confirmed.
+#[event]
+pub struct EscrowFeesSettled {
+    pub user: Pubkey,
+    pub nonce: u64,
+    pub protocol_fee: u64,
+    pub relayer_fee: u64,
+    pub fee_payee: Pubkey,
+    /// The escrow and its vault were closed (every dispatch done)
+    pub closed: bool,
+}
+
+/// Held fees returned to the user after the adapter leg failed.
+#[event]
+pub struct EscrowFeesRefunded {
+    pub user: Pubkey,
+    pub nonce: u64,
+    pub protocol_fee: u64,
+    pub relayer_fee: u64,
+    pub closed: bool,
+}
+
+#[event]
+pub struct EscrowExpiringSoon {
+    pub user: Pubkey,
+    pub nonce: u64,
+    pub escrow: Pubkey,
+    /// Escrowed amount not yet forwarded
+    pub remaining: u64,
+    pub expires_at_slot: u64,
+}
+
+#[event]
+pub struct EscrowExpired {
+    pub user: Pubkey,
+    pub nonce: u64,
+    pub escrow: Pubkey,
+    pub remaining: u64,
+    pub expires_at_slot: u64,
+}
+
+#[event]
+pub struct LpFeeApplied {
+    pub message_hash: [u8; 32],
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub lp_fee: u64,
+    pub lp_bps: u16,
+    pub recipient: Pubkey,
+}
+
+#[event]
+pub struct LpFeesWithdrawn {
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub destination: Pubkey,
+}
+
+/// A mint route onboarded in one step: vaults, allowlist entry, spoke and fee policy.
+#[event]
+pub struct RouteOnboarded {
+    pub mint: Pubkey,
+    pub spoke_id: u32,
+    pub adapter_program: Pubkey,
+    pub dst_chain_id: u64,
+    pub protocol_vault: Pubkey,
+    pub relayer_vault: Pubkey,
+    pub fee_tiers: Vec<FeeTier>,
+}
+
+#[event]
+pub struct HubVaultsInitialized {
+    pub mint: Pubkey,
+    pub protocol_vault: Pubkey,
+    pub relayer_vault: Pubkey,
+    pub version: u8,
+}
+
+/// Which validation pattern a hub vault matched (VAULT_PATTERN_*), for migration monitoring.
+#[event]
+pub struct VaultPatternMatched {
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub pattern: u8,
+    pub strict: bool,
+}
+
+#[event]
+pub struct ConfigAttested {
+    pub root: [u8; 32],
+    pub sequence: u64,
+    pub updated_at_slot: u64,
+    pub src_chain_id: u64,
+}
+
+#[event]
+pub struct FeeRecipientRotated {
+    pub previous: Pubkey,
+    pub current: Pubkey,
+    pub epoch: u64,
+    pub activated_at_slot: u64,
+}
+
+#[event]
+pub struct ZpxFeeRateUpdated {
+    pub mint: Pubkey,
+    pub zpx_per_token_e9: u64,
+    pub discount_bps: u16,
+    pub updated_at_slot: u64,
+}
+
+#[event]
+pub struct InFlightUpdated {
+    pub mint: Pubkey,
+    pub dst_chain_id: u64,
+    pub previous: u64,
+    pub current: u64,
+    pub exposure_cap: u64,
+}
+
+/// Exposed schema snapshots (field names and order) for tests and tooling
+pub const BRIDGE_INITIATED_FIELDS: &[&str] = &[
+    "route_id",
+    "user",
+    "token",
+    "target",
+    "forwarded_amount",
+    "protocol_fee",
+    "relayer_fee",
+    "payload_hash",
+    "src_chain_id",
+    "dst_chain_id",
+    "nonce",
+];
+
+pub const UNIVERSAL_BRIDGE_INITIATED_FIELDS: &[&str] = &[
+    "route_id",
+    "payload_hash",
+    "message_hash",
+    "global_route_id",
+    "user",
+    "token",
+    "target",
+    "forwarded_amount",
+    "protocol_fee",
+    "relayer_fee",
+    "src_chain_id",
+    "dst_chain_id",
+    "nonce",
+];
+
+pub const FEE_APPLIED_SOURCE_FIELDS: &[&str] = &[
+    "message_hash",
+    "asset",
+    "payer",
+    "target",
+    "protocol_fee",
+    "relayer_fee",
+    "fee_recipient",
+    "applied_at",
+];
+
+pub const FEE_APPLIED_SOURCE_V2_FIELDS: &[&str] = &[
+    "message_hash",
+    "asset",
+    "payer",
+    "target",
+    "protocol_fee",
+    "relayer_fee",
+    "fee_recipient",
+    "applied_at",
+    "zpx_fee_burned",
+    "fee_recipient_epoch",
+];
+
+pub const FEE_APPLIED_SOURCE_V3_FIELDS: &[&str] = &[
+    "message_hash",
+    "asset",
+    "payer",
+    "target",
+    "protocol_fee",
+    "relayer_fee",
+    "fee_recipient",
+    "applied_at",
+    "zpx_fee_burned",
+    "fee_recipient_epoch",
+    "config_version",
+];
+
+pub const FEE_APPLIED_DEST_FIELDS: &[&str] = &[
+    "message_hash",
+    "src_chain_id",
+    "dst_chain_id",
+    "router",
+    "asset",
+    "amount",
+    "protocol_bps",
+    "lp_bps",
+    "collector",
+    "applied_at",
+];
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Unauthorized")]
+    Unauthorized,
+    #[msg("Paused")]
+    Paused,
+    #[msg("Source chain id not set")]
+    SrcChainNotSet,
+    #[msg("Zero-amount not allowed")]
+    ZeroAmount,
+    #[msg("Payload too large")]
+    PayloadTooLarge,
+    #[msg("Protocol fee too high")]
+    ProtocolFeeTooHigh,
+    #[msg("Relayer fee too high")]
+    RelayerFeeTooHigh,
+    #[msg("Fees exceed amount")]
+    FeesExceedAmount,
+    #[msg("Adapter already exists")]
+    AdapterAlreadyExists,
+    #[msg("Adapter not allowed")]
+    AdapterNotAllowed,
+    #[msg("Adapter list full")]
+    AdapterListFull,
+    #[msg("Math overflow")]
+    MathOverflow,
+    #[msg("Invalid token program")]
+    InvalidTokenProgram,
+    #[msg("Chain id out of range for u16 emission")]
+    ChainIdOutOfRange,
+    #[msg("Invalid fee recipient ATA")]
+    InvalidFeeRecipientAta,
+    #[msg("Placeholder program id used; replace with real id")]
+    PlaceholderProgramId,
+    // New replay-guard specific errors
+    #[msg("Replay PDA does not match expected seeds")]
+    InvalidReplayPda,
+    #[msg("Replay account not owned by program")]
+    InvalidReplayOwner,
+    #[msg("Replay account too small")]
+    ReplayAccountTooSmall,
+    #[msg("Message has already been finalized (replay)")]
+    ReplayAlreadyProcessed,
+    #[msg("Computed hash mismatch")]
+    HashMismatch,
+    #[msg("Vault PDA does not match expected seeds")]
+    InvalidVaultPda,
+    #[msg("Vault account not owned by program")]
+    InvalidVaultOwner,
+    #[msg("Exposure cap exceeded for destination")]
+    ExposureCapExceeded,
+    #[msg("In-flight amount underflow")]
+    InFlightUnderflow,
+    #[msg("Adapter program was upgraded or changed since it was pinned")]
+    AdapterCodeChanged,
+    #[msg("ZPX fee rate is stale")]
+    StaleZpxRate,
+    #[msg("ZPX discount must be at most 10000 bps")]
+    InvalidZpxDiscount,
+    #[msg("Config already initialized")]
+    ConfigAlreadyInitialized,
+    #[msg("Instruction only available in devnet-relaxed builds")]
+    DevnetOnly,
+    #[msg("Payload does not match spoke schema")]
+    PayloadSchemaMismatch,
+    #[msg("Spoke is deprecated; use its successor")]
+    SpokeDeprecated,
+    #[msg("Successor spoke invalid")]
+    InvalidSuccessorSpoke,
+    #[msg("Oracle price account invalid or not trading")]
+    InvalidOraclePrice,
+    #[msg("Oracle price is stale")]
+    StaleOraclePrice,
+    #[msg("Oracle confidence interval too wide")]
+    OracleConfidenceTooWide,
+    #[msg("Oracle accounts missing or mismatched")]
+    OracleAccountsMissing,
+    #[msg("Amount below USD minimum")]
+    BelowUsdMinimum,
+    #[msg("Vault not created via initialize_hub_vaults")]
+    VaultNotRegistered,
+    #[msg("Message account does not match the expected PDA")]
+    InvalidMessageAccount,
+    #[msg("Preimage already stored for this nonce")]
+    PreimageAlreadyStored,
+    #[msg("Forward arguments do not match the escrowed intent")]
+    EscrowMismatch,
+    #[msg("Relayer already on the spoke allowlist")]
+    RelayerAlreadyListed,
+    #[msg("Relayer not on the spoke allowlist")]
+    RelayerNotListed,
+    #[msg("Unknown failure class")]
+    InvalidFailureClass,
+    #[msg("Router stats account does not match the expected PDA")]
+    InvalidStatsAccount,
+    #[msg("Message has not been finalized on this chain")]
+    MessageNotFinalized,
+    #[msg("Finalization bounty expired")]
+    BountyExpired,
+    #[msg("Finalization bounty has not expired")]
+    BountyNotExpired,
+    #[msg("Adapter rejected payload in dry run")]
+    AdapterPayloadRejected,
+    #[msg("Fee split shares must sum to 10000 bps")]
+    InvalidFeeSplit,
+    #[msg("Router is globally killed; release via the timelocked governance path")]
+    GlobalKilled,
+    #[msg("Signer is not the kill sentinel")]
+    NotKillSentinel,
+    #[msg("Router is not globally killed")]
+    NotKilled,
+    #[msg("Kill release not scheduled or timelock not elapsed")]
+    KillReleaseLocked,
+    #[msg("No weighted spoke available in the route group")]
+    NoBalancedRoute,
+    #[msg("Message was cancelled by its sender")]
+    MessageCancelled,
+    #[msg("Message has already been (partially) forwarded")]
+    MessageAlreadyForwarded,
+    #[msg("Mint is paused")]
+    MintPaused,
+    #[msg("Invalid mint pause account")]
+    InvalidMintPauseAccount,
+    #[msg("Rent ledger account does not match its PDA")]
+    InvalidRentLedger,
+    #[msg("Recipient is not a valid address for the destination chain")]
+    InvalidRecipient,
+    #[msg("Idempotency key 0 is reserved")]
+    InvalidIdempotencyKey,
+    #[msg("Mint is not allowed")]
+    MintNotAllowed,
+    #[msg("Mint already allowed")]
+    MintAlreadyAllowed,
+    #[msg("Route session window has passed")]
+    RouteSessionExpired,
+    #[msg("Route steps do not match the session plan")]
+    RoutePlanMismatch,
+    #[msg("Route session window out of range")]
+    InvalidRouteWindow,
+    #[msg("Payload version not supported by this spoke")]
+    UnsupportedPayloadVersion,
+    #[msg("User transfer index account does not match its PDA")]
+    InvalidUserIndex,
+    #[msg("Instruction is only available in test-instructions builds")]
+    TestInstructionsDisabled,
+    #[msg("Relayer vesting account does not match its PDA")]
+    InvalidRelayerVesting,
+    #[msg("No vested relayer fees to claim")]
+    NothingVested,
+    #[msg("Registry chunk is empty, too large, or not contiguous")]
+    InvalidRegistryChunk,
+    #[msg("Registry chunk does not match its integrity hash")]
+    RegistryChunkHashMismatch,
+    #[msg("Instruction is disabled by a rollout feature switch")]
+    FeatureDisabled,
+    #[msg("Unknown rollout feature")]
+    UnknownFeature,
+    #[msg("Adapter target token account is not owned by the spoke's registered target")]
+    InvalidAdapterTarget,
+    #[msg("Bridge hook program mismatch or not executable")]
+    InvalidBridgeHook,
+    #[msg("Finalized volume would exceed dispatched volume")]
+    FinalizedExceedsDispatched,
+    #[msg("Hub vaults for this mint were never initialized (run initialize_hub_vaults)")]
+    VaultsNotInitialized,
+    #[msg("Escrow has not passed its expiry yet")]
+    EscrowNotExpired,
+    #[msg("Expiry reminder slot must be in the future")]
+    InvalidExpiryReminder,
+    #[msg("Bridge intent signature is invalid")]
+    InvalidIntentSignature,
+    #[msg("Intent signer is not registered")]
+    IntentSignerNotRegistered,
+    #[msg("Intent signer already registered")]
+    IntentSignerAlreadyRegistered,
+    #[msg("Bridge intent deadline has passed")]
+    IntentExpired,
+    #[msg("Adapter payload does not match the bridge intent")]
+    IntentPayloadMismatch,
+    #[msg("Message is past its validity window")]
+    MessageExpired,
+    #[msg("Route requires a message validity window")]
+    MessageValidityRequired,
+    #[msg("Invalid route validity account")]
+    InvalidRouteValidity,
+    #[msg("Invalid relayer activity account")]
+    InvalidRelayerActivity,
+    #[msg("Escrow fees are held until the dispatch is confirmed or refunded")]
+    FeesHeld,
+    #[msg("Escrow holds no fees")]
+    NoHeldFees,
+    #[msg("Adapter exceeded its compute or CPI depth allowance")]
+    AdapterBudgetExceeded,
+    #[msg("Spoke category 0 means uncategorized and cannot be paused as a group")]
+    InvalidSpokeCategory,
+    #[msg("Sandbox namespaces require a build with the sandbox feature")]
+    SandboxDisabled,
+    #[msg("Sandbox namespace must be 1..=SANDBOX_NAMESPACE_LEN bytes")]
+    InvalidSandboxNamespace,
+    #[msg("message_account must be blank or owned by the spoke's adapter program")]
+    MessageAccountOwnerMismatch,
+    #[msg("message_account is smaller than MIN_MESSAGE_ACCOUNT_LEN")]
+    MessageAccountTooSmall,
+    #[msg("Account holds less than its rent-exempt minimum")]
+    AccountNotRentExempt,
+    #[msg("Admin audit log PDA missing or not writable")]
+    AdminAuditLogMissing,
+    #[msg("Route decimals PDA mismatch")]
+    InvalidRouteDecimals,
+    #[msg("Mint has more decimals than the canonical 18")]
+    UnsupportedDecimals,
+    #[msg("Amount is not representable in the mint's units")]
+    AmountPrecisionLoss,
+    #[msg("Crank reward PDA or mint mismatch")]
+    InvalidCrankReward,
+    #[msg("Recipient address format does not match the destination chain")]
+    RecipientFormatMismatch,
+    #[msg("Batch is empty, too large, repeats an escrow or lacks entry accounts")]
+    InvalidBatch,
+    #[msg("Spoke or mint cannot use the USDC fast path")]
+    InvalidFastPath,
+    #[msg("USDC fast path no longer matches the registry; reconfigure it")]
+    FastPathStale,
+    #[msg("Adapter proposal timelock has not elapsed")]
+    AdapterActivationLocked,
+    #[msg("Unknown rent refund policy or caller share")]
+    InvalidRentRefundPolicy,
+    #[msg("Invalid fee policy")]
+    InvalidFeePolicy,
+    #[msg("Fees do not match the route fee policy")]
+    FeePolicyMismatch,
+    #[msg("Invalid fee policy account")]
+    InvalidFeePolicyAccount,
+    #[msg("Relayer token account is for a different mint")]
+    RelayerPayoutMintMismatch,
+    #[msg("Relayer token account is frozen")]
+    RelayerPayoutAccountFrozen,
+    #[msg("Relayer token account has a delegate")]
+    RelayerPayoutAccountDelegated,
+    #[msg("Unknown spoke relayer payout mode")]
+    InvalidRelayerPayoutMode,
+    #[msg("Unsupported payload compression algorithm")]
+    UnsupportedPayloadCompression,
+    #[msg("Declared decompressed payload size exceeds the limit")]
+    DecompressedPayloadTooLarge,
+    #[msg("Invalid relayer co-signing policy")]
+    InvalidRelayerCosign,
+    #[msg("Relayer co-signing PDA mismatch")]
+    InvalidRelayerCosignAccount,
+    #[msg("Registered relayer co-signatures below the required weight")]
+    CosignWeightNotMet,
+    #[msg("Route availability account does not match its PDA")]
+    InvalidRouteAvailabilityAccount,
+    #[msg("Adapter moved more than the net amount or set a delegate on its target")]
+    AdapterBalanceAssertionFailed,
+    #[msg("Config history account missing")]
+    ConfigHistoryMissing,
+    #[msg("Forward state account does not match the escrow")]
+    InvalidForwardStateAccount,
+    #[msg("Remote config parameters are invalid")]
+    InvalidRemoteConfig,
+    #[msg("Remote config account does not match the source chain")]
+    InvalidRemoteConfigAccount,
+    #[msg("Account is not a Wormhole guardian set")]
+    InvalidGuardianSet,
+    #[msg("Guardian set has expired")]
+    GuardianSetExpired,
+    #[msg("Query response lacks a guardian quorum")]
+    GuardianQuorumNotReached,
+    #[msg("Query response is not a single configRoot() eth_call")]
+    InvalidQueryResponse,
+    #[msg("Query response is for another chain or router")]
+    RemoteConfigQueryMismatch,
+    #[msg("Query response is not newer than the verified one")]
+    StaleRemoteConfigResponse,
+    #[msg("Remote config for the source chain is required")]
+    RemoteConfigMissing,
+    #[msg("Remote config has not been verified")]
+    RemoteConfigUnverified,
+    #[msg("Source router config root does not match the expected root")]
+    RemoteConfigRootMismatch,
+    #[msg("Verified remote config is too old")]
+    RemoteConfigStale,
+    #[msg("Instruction family is paused")]
+    InstructionFamilyPaused,
+    #[msg("Unknown instruction family")]
+    UnknownInstructionFamily,
+    #[msg("Partner accounts do not match their PDAs")]
+    InvalidPartnerAccount,
+    #[msg("Rebate bps exceed 10000 or payout is unset")]
+    InvalidRebateBps,
+    #[msg("No partner rebate to claim")]
+    NoPartnerRebate,
+    #[msg("Adapter is not in the legacy config allowlist")]
+    AdapterNotLegacy,
+}
+
+impl From<amount::AmountError> for ErrorCode {
+    fn from(e: amount::AmountError) -> Self {
+        match e {
+            amount::AmountError::UnsupportedDecimals => ErrorCode::UnsupportedDecimals,
+            amount::AmountError::PrecisionLoss => ErrorCode::AmountPrecisionLoss,
+            amount::AmountError::Overflow => ErrorCode::MathOverflow,
+        }
+    }
+}
+
+impl From<recipient::RecipientError> for ErrorCode {
+    fn from(e: recipient::RecipientError) -> Self {
+        match e {
+            recipient::RecipientError::FormatMismatch => ErrorCode::RecipientFormatMismatch,
+            _ => ErrorCode::InvalidRecipient,
+        }
+    }
+}
+
+impl From<fees::FeeError> for ErrorCode {
+    fn from(e: fees::FeeError) -> Self {
+        match e {
+            fees::FeeError::ZeroAmount => ErrorCode::ZeroAmount,
+            fees::FeeError::ProtocolFeeTooHigh => ErrorCode::ProtocolFeeTooHigh,
+            fees::FeeError::RelayerFeeTooHigh => ErrorCode::RelayerFeeTooHigh,
+            fees::FeeError::FeesExceedAmount => ErrorCode::FeesExceedAmount,
+            fees::FeeError::InvalidDiscount => ErrorCode::InvalidZpxDiscount,
+            fees::FeeError::MathOverflow => ErrorCode::MathOverflow,
+            fees::FeeError::InvalidPolicy => ErrorCode::InvalidFeePolicy,
+        }
+    }
+}
+
+impl From<ReplayError> for ErrorCode {
+    fn from(e: ReplayError) -> Self {
+        match e {
+            ReplayError::InvalidPda => ErrorCode::InvalidReplayPda,
+            ReplayError::InvalidOwner => ErrorCode::InvalidReplayOwner,
+            ReplayError::AccountTooSmall => ErrorCode::ReplayAccountTooSmall,
+            ReplayError::AlreadyProcessed => ErrorCode::ReplayAlreadyProcessed,
+            ReplayError::NotProcessed => ErrorCode::MessageNotFinalized,
+        }
+    }
+}
+
+impl From<payload::PayloadError> for ErrorCode {
+    fn from(e: payload::PayloadError) -> Self {
+        match e {
+            payload::PayloadError::TooLarge => ErrorCode::PayloadTooLarge,
+            payload::PayloadError::SchemaMismatch => ErrorCode::PayloadSchemaMismatch,
+            payload::PayloadError::UnsupportedVersion => ErrorCode::UnsupportedPayloadVersion,
+            payload::PayloadError::UnsupportedCompression => {
+                ErrorCode::UnsupportedPayloadCompression
+            }
+            payload::PayloadError::DecompressedTooLarge => ErrorCode::DecompressedPayloadTooLarge,
+        }
+    }
+}
+
+/// Exposed error table (custom code, name, message) mirroring `ErrorCode`, so clients can
+/// decode the numeric custom error codes surfaced in RPC responses. Keep in sync with the enum.
+pub const ERROR_CODES: &[(u32, &str, &str)] = &[
+    (6000, "Unauthorized", "Unauthorized"),
+    (6001, "Paused", "Paused"),
+    (6002, "SrcChainNotSet", "Source chain id not set"),
+    (6003, "ZeroAmount", "Zero-amount not allowed"),
+    (6004, "PayloadTooLarge", "Payload too large"),
+    (6005, "ProtocolFeeTooHigh", "Protocol fee too high"),
+    (6006, "RelayerFeeTooHigh", "Relayer fee too high"),
+    (6007, "FeesExceedAmount", "Fees exceed amount"),
+    (6008, "AdapterAlreadyExists", "Adapter already exists"),
+    (6009, "AdapterNotAllowed", "Adapter not allowed"),
+    (6010, "AdapterListFull", "Adapter list full"),
+    (6011, "MathOverflow", "Math overflow"),
+    (6012, "InvalidTokenProgram", "Invalid token program"),
+    (
+        6013,
+        "ChainIdOutOfRange",
+        "Chain id out of range for u16 emission",
+    ),
+    (6014, "InvalidFeeRecipientAta", "Invalid fee recipient ATA"),
+    (
+        6015,
+        "PlaceholderProgramId",
+        "Placeholder program id used; replace with real id",
+    ),
+    (
+        6016,
+        "InvalidReplayPda",
+        "Replay PDA does not match expected seeds",
+    ),
+    (
+        6017,
+        "InvalidReplayOwner",
+        "Replay account not owned by program",
+    ),
+    (6018, "ReplayAccountTooSmall", "Replay account too small"),
+    (
+        6019,
+        "ReplayAlreadyProcessed",
+        "Message has already been finalized (replay)",
+    ),
+    (6020, "HashMismatch", "Computed hash mismatch"),
+    (
+        6021,
+        "InvalidVaultPda",
+        "Vault PDA does not match expected seeds",
+    ),
+    (
+        6022,
+        "InvalidVaultOwner",
+        "Vault account not owned by program",
+    ),
+    (
+        6023,
+        "ExposureCapExceeded",
+        "Exposure cap exceeded for destination",
+    ),
+    (6024, "InFlightUnderflow", "In-flight amount underflow"),
+    (
+        6025,
+        "AdapterCodeChanged",
+        "Adapter program was upgraded or changed since it was pinned",
+    ),
+    (6026, "StaleZpxRate", "ZPX fee rate is stale"),
+    (
+        6027,
+        "InvalidZpxDiscount",
+        "ZPX discount must be at most 10000 bps",
+    ),
+    (
+        6028,
+        "ConfigAlreadyInitialized",
+        "Config already initialized",
+    ),
+    (
+        6029,
+        "DevnetOnly",
+        "Instruction only available in devnet-relaxed builds",
+    ),
+    (
+        6030,
+        "PayloadSchemaMismatch",
+        "Payload does not match spoke schema",
+    ),
+    (
+        6031,
+        "SpokeDeprecated",
+        "Spoke is deprecated; use its successor",
+    ),
+    (6032, "InvalidSuccessorSpoke", "Successor spoke invalid"),
+    (
+        6033,
+        "InvalidOraclePrice",
+        "Oracle price account invalid or not trading",
+    ),
+    (6034, "StaleOraclePrice", "Oracle price is stale"),
+    (
+        6035,
+        "OracleConfidenceTooWide",
+        "Oracle confidence interval too wide",
+    ),
+    (
+        6036,
+        "OracleAccountsMissing",
+        "Oracle accounts missing or mismatched",
+    ),
+    (6037, "BelowUsdMinimum", "Amount below USD minimum"),
+    (
+        6038,
+        "VaultNotRegistered",
+        "Vault not created via initialize_hub_vaults",
+    ),
+    (
+        6039,
+        "InvalidMessageAccount",
+        "Message account does not match the expected PDA",
+    ),
+    (
+        6040,
+        "PreimageAlreadyStored",
+        "Preimage already stored for this nonce",
+    ),
+    (
+        6041,
+        "EscrowMismatch",
+        "Forward arguments do not match the escrowed intent",
+    ),
+    (
+        6042,
+        "RelayerAlreadyListed",
+        "Relayer already on the spoke allowlist",
+    ),
+    (
+        6043,
+        "RelayerNotListed",
+        "Relayer not on the spoke allowlist",
+    ),
+    (6044, "InvalidFailureClass", "Unknown failure class"),
+    (
+        6045,
+        "InvalidStatsAccount",
+        "Router stats account does not match the expected PDA",
+    ),
+    (
+        6046,
+        "MessageNotFinalized",
+        "Message has not been finalized on this chain",
+    ),
+    (6047, "BountyExpired", "Finalization bounty expired"),
+    (
+        6048,
+        "BountyNotExpired",
+        "Finalization bounty has not expired",
+    ),
+    (
+        6049,
+        "AdapterPayloadRejected",
+        "Adapter rejected payload in dry run",
+    ),
+    (
+        6050,
+        "InvalidFeeSplit",
+        "Fee split shares must sum to 10000 bps",
+    ),
+    (
+        6051,
+        "GlobalKilled",
+        "Router is globally killed; release via the timelocked governance path",
+    ),
+    (6052, "NotKillSentinel", "Signer is not the kill sentinel"),
+    (6053, "NotKilled", "Router is not globally killed"),
+    (
+        6054,
+        "KillReleaseLocked",
+        "Kill release not scheduled or timelock not elapsed",
+    ),
+    (
+        6055,
+        "NoBalancedRoute",
+        "No weighted spoke available in the route group",
+    ),
+    (
+        6056,
+        "MessageCancelled",
+        "Message was cancelled by its sender",
+    ),
+    (
+        6057,
+        "MessageAlreadyForwarded",
+        "Message has already been (partially) forwarded",
+    ),
+    (6058, "MintPaused", "Mint is paused"),
+    (
+        6059,
+        "InvalidMintPauseAccount",
+        "Invalid mint pause account",
+    ),
+    (
+        6060,
+        "InvalidRentLedger",
+        "Rent ledger account does not match its PDA",
+    ),
+    (
+        6061,
+        "InvalidRecipient",
+        "Recipient is not a valid address for the destination chain",
+    ),
+    (
+        6062,
+        "InvalidIdempotencyKey",
+        "Idempotency key 0 is reserved",
+    ),
+    (6063, "MintNotAllowed", "Mint is not allowed"),
+    (6064, "MintAlreadyAllowed", "Mint already allowed"),
+    (
+        6065,
+        "RouteSessionExpired",
+        "Route session window has passed",
+    ),
+    (
+        6066,
+        "RoutePlanMismatch",
+        "Route steps do not match the session plan",
+    ),
+    (
+        6067,
+        "InvalidRouteWindow",
+        "Route session window out of range",
+    ),
+    (
+        6068,
+        "UnsupportedPayloadVersion",
+        "Payload version not supported by this spoke",
+    ),
+    (
+        6069,
+        "InvalidUserIndex",
+        "User transfer index account does not match its PDA",
+    ),
+    (
+        6070,
+        "TestInstructionsDisabled",
+        "Instruction is only available in test-instructions builds",
+    ),
+    (
+        6071,
+        "InvalidRelayerVesting",
+        "Relayer vesting account does not match its PDA",
+    ),
+    (6072, "NothingVested", "No vested relayer fees to claim"),
+    (
+        6073,
+        "InvalidRegistryChunk",
+        "Registry chunk is empty, too large, or not contiguous",
+    ),
+    (
+        6074,
+        "RegistryChunkHashMismatch",
+        "Registry chunk does not match its integrity hash",
+    ),
+    (
+        6075,
+        "FeatureDisabled",
+        "Instruction is disabled by a rollout feature switch",
+    ),
+    (6076, "UnknownFeature", "Unknown rollout feature"),
+    (
+        6077,
+        "InvalidAdapterTarget",
+        "Adapter target token account is not owned by the spoke's registered target",
+    ),
+    (
+        6078,
+        "InvalidBridgeHook",
+        "Bridge hook program mismatch or not executable",
+    ),
+    (
+        6079,
+        "FinalizedExceedsDispatched",
+        "Finalized volume would exceed dispatched volume",
+    ),
+    (
+        6080,
+        "VaultsNotInitialized",
+        "Hub vaults for this mint were never initialized (run initialize_hub_vaults)",
+    ),
+    (
+        6081,
+        "EscrowNotExpired",
+        "Escrow has not passed its expiry yet",
+    ),
+    (
+        6082,
+        "InvalidExpiryReminder",
+        "Expiry reminder slot must be in the future",
+    ),
+    (
+        6083,
+        "InvalidIntentSignature",
+        "Bridge intent signature is invalid",
+    ),
+    (
+        6084,
+        "IntentSignerNotRegistered",
+        "Intent signer is not registered",
+    ),
+    (
+        6085,
+        "IntentSignerAlreadyRegistered",
+        "Intent signer already registered",
+    ),
+    (6086, "IntentExpired", "Bridge intent deadline has passed"),
+    (
+        6087,
+        "IntentPayloadMismatch",
+        "Adapter payload does not match the bridge intent",
+    ),
+    (
+        6088,
+        "MessageExpired",
+        "Message is past its validity window",
+    ),
+    (
+        6089,
+        "MessageValidityRequired",
+        "Route requires a message validity window",
+    ),
+    (
+        6090,
+        "InvalidRouteValidity",
+        "Invalid route validity account",
+    ),
+    (
+        6091,
+        "InvalidRelayerActivity",
+        "Invalid relayer activity account",
+    ),
+    (
+        6092,
+        "FeesHeld",
+        "Escrow fees are held until the dispatch is confirmed or refunded",
+    ),
+    (6093, "NoHeldFees", "Escrow holds no fees"),
+    (
+        6094,
+        "AdapterBudgetExceeded",
+        "Adapter exceeded its compute or CPI depth allowance",
+    ),
+    (
+        6095,
+        "InvalidSpokeCategory",
+        "Spoke category 0 means uncategorized and cannot be paused as a group",
+    ),
+    (
+        6096,
+        "SandboxDisabled",
+        "Sandbox namespaces require a build with the sandbox feature",
+    ),
+    (
+        6097,
+        "InvalidSandboxNamespace",
+        "Sandbox namespace must be 1..=SANDBOX_NAMESPACE_LEN bytes",
+    ),
+    (
+        6098,
+        "MessageAccountOwnerMismatch",
+        "message_account must be blank or owned by the spoke's adapter program",
+    ),
+    (
+        6099,
+        "MessageAccountTooSmall",
+        "message_account is smaller than MIN_MESSAGE_ACCOUNT_LEN",
+    ),
+    (
+        6100,
+        "AccountNotRentExempt",
+        "Account holds less than its rent-exempt minimum",
+    ),
+    (
+        6101,
+        "AdminAuditLogMissing",
+        "Admin audit log PDA missing or not writable",
+    ),
+    (6102, "InvalidRouteDecimals", "Route decimals PDA mismatch"),
+    (
+        6103,
+        "UnsupportedDecimals",
+        "Mint has more decimals than the canonical 18",
+    ),
+    (
+        6104,
+        "AmountPrecisionLoss",
+        "Amount is not representable in the mint's units",
+    ),
+    (
+        6105,
+        "InvalidCrankReward",
+        "Crank reward PDA or mint mismatch",
+    ),
+    (
+        6106,
+        "RecipientFormatMismatch",
+        "Recipient address format does not match the destination chain",
+    ),
+    (
+        6107,
+        "InvalidBatch",
+        "Batch is empty, too large, repeats an escrow or lacks entry accounts",
+    ),
+    (
+        6108,
+        "InvalidFastPath",
+        "Spoke or mint cannot use the USDC fast path",
+    ),
+    (
+        6109,
+        "FastPathStale",
+        "USDC fast path no longer matches the registry; reconfigure it",
+    ),
+    (
+        6110,
+        "AdapterActivationLocked",
+        "Adapter proposal timelock has not elapsed",
+    ),
+    (
+        6111,
+        "InvalidRentRefundPolicy",
+        "Unknown rent refund policy or caller share",
+    ),
+    (6112, "InvalidFeePolicy", "Invalid fee policy"),
+    (
+        6113,
+        "FeePolicyMismatch",
+        "Fees do not match the route fee policy",
+    ),
+    (
+        6114,
+        "InvalidFeePolicyAccount",
+        "Invalid fee policy account",
+    ),
+    (
+        6115,
+        "RelayerPayoutMintMismatch",
+        "Relayer token account is for a different mint",
+    ),
+    (
+        6116,
+        "RelayerPayoutAccountFrozen",
+        "Relayer token account is frozen",
+    ),
+    (
+        6117,
+        "RelayerPayoutAccountDelegated",
+        "Relayer token account has a delegate",
+    ),
+    (
+        6118,
+        "InvalidRelayerPayoutMode",
+        "Unknown spoke relayer payout mode",
+    ),
+    (
+        6119,
+        "UnsupportedPayloadCompression",
+        "Unsupported payload compression algorithm",
+    ),
+    (
+        6120,
+        "DecompressedPayloadTooLarge",
+        "Declared decompressed payload size exceeds the limit",
+    ),
+    (
+        6121,
+        "InvalidRelayerCosign",
+        "Invalid relayer co-signing policy",
+    ),
+    (
+        6122,
+        "InvalidRelayerCosignAccount",
+        "Relayer co-signing PDA mismatch",
+    ),
+    (
+        6123,
+        "CosignWeightNotMet",
+        "Registered relayer co-signatures below the required weight",
+    ),
+    (
+        6124,
+        "InvalidRouteAvailabilityAccount",
+        "Route availability account does not match its PDA",
+    ),
+    (
+        6125,
+        "AdapterBalanceAssertionFailed",
+        "Adapter moved more than the net amount or set a delegate on its target",
+    ),
+    (
+        6126,
+        "ConfigHistoryMissing",
+        "Config history account missing",
+    ),
+    (
+        6127,
+        "InvalidForwardStateAccount",
+        "Forward state account does not match the escrow",
+    ),
+    (
+        6128,
+        "InvalidRemoteConfig",
+        "Remote config parameters are invalid",
+    ),
+    (
+        6129,
+        "InvalidRemoteConfigAccount",
+        "Remote config account does not match the source chain",
+    ),
+    (
+        6130,
+        "InvalidGuardianSet",
+        "Account is not a Wormhole guardian set",
+    ),
+    (6131, "GuardianSetExpired", "Guardian set has expired"),
+    (
+        6132,
+        "GuardianQuorumNotReached",
+        "Query response lacks a guardian quorum",
+    ),
+    (
+        6133,
+        "InvalidQueryResponse",
+        "Query response is not a single configRoot() eth_call",
+    ),
+    (
+        6134,
+        "RemoteConfigQueryMismatch",
+        "Query response is for another chain or router",
+    ),
+    (
+        6135,
+        "StaleRemoteConfigResponse",
+        "Query response is not newer than the verified one",
+    ),
+    (
+        6136,
+        "RemoteConfigMissing",
+        "Remote config for the source chain is required",
+    ),
+    (
+        6137,
+        "RemoteConfigUnverified",
+        "Remote config has not been verified",
+    ),
+    (
+        6138,
+        "RemoteConfigRootMismatch",
+        "Source router config root does not match the expected root",
+    ),
+    (
+        6139,
+        "RemoteConfigStale",
+        "Verified remote config is too old",
+    ),
+    (
+        6140,
+        "InstructionFamilyPaused",
+        "Instruction family is paused",
+    ),
+    (
+        6141,
+        "UnknownInstructionFamily",
+        "Unknown instruction family",
+    ),
+    (
+        6142,
+        "InvalidPartnerAccount",
+        "Partner accounts do not match their PDAs",
+    ),
+    (
+        6143,
+        "InvalidRebateBps",
+        "Rebate bps exceed 10000 or payout is unset",
+    ),
+    (6144, "NoPartnerRebate", "No partner rebate to claim"),
+    (
+        6145,
+        "AdapterNotLegacy",
+        "Adapter is not in the legacy config allowlist",
+    ),
+];
+
+/// Client-side lookup: resolve an Anchor custom error code to its (name, message).
+pub fn lookup_error_code(code: u32) -> Option<(&'static str, &'static str)> {
+    ERROR_CODES
+        .iter()
+        .find(|(c, _, _)| *c == code)
+        .map(|(_, name, msg)| (*name, *msg))
+}
+
+// Relayer fee denomination recorded in Forwarded
+pub const RELAYER_FEE_MODE_TOKEN: u8 = 0;
+pub const RELAYER_FEE_MODE_LAMPORTS: u8 = 1;
+
+// Hub-and-spoke constants
+pub const MAX_SPOKES: usize = 32;
+
+// Spoke categories: the upstream protocol a spoke's adapter relies on. Values above these
+// are free for new families.
+pub const SPOKE_CATEGORY_NONE: u8 = 0;
+pub const SPOKE_CATEGORY_CCTP: u8 = 1;
+pub const SPOKE_CATEGORY_WORMHOLE: u8 = 2;
+pub const SPOKE_CATEGORY_LAYERZERO: u8 = 3;
+pub const SPOKE_CATEGORY_HYPERLANE: u8 = 4;
+const SPOKE_METADATA_LEN: usize = 64;
+
+// SpokeEntry.relayer_payout. The byte held a bool before the vault-only mode existed, so
+// DEFAULT and DIRECT keep the old false / true encodings.
+/// Follow `config.direct_relayer_payout_default`
+pub const RELAYER_PAYOUT_DEFAULT: u8 = 0;
+/// Pay token relayer fees straight to the relayer's token account
+pub const RELAYER_PAYOUT_DIRECT: u8 = 1;
+/// Never pay directly; fees always accrue in hub_relayer_vault (risky routes)
+pub const RELAYER_PAYOUT_VAULT: u8 = 2;
+// spoke_id(4) + adapter_program(32) + enabled(1) + paused(1) + relayer_payout(1) + version(1)
+// + metadata(64) + created_at_slot(8) + adapter_program_data(32) + adapter_deploy_slot(8)
+// + adapter_upgrade_authority(32) + payload_schema(PAYLOAD_SCHEMA_LEN) + deprecated_at_slot(8)
+// + sunset_slot(8) + successor_spoke_id(4) + route_group(4) + weight(2) + fallback_spoke_id(4)
+// + target_owner(32) + dispatched_amount(8) + finalized_amount(8) + imbalance_threshold(8)
+// + sla_target_slots(8) + max_adapter_cu(4) + category(1)
+const SPOKE_ENTRY_LEN: usize = 4
+    + 32
+    + 1
+    + 1
+    + 1
+    + 1
+    + SPOKE_METADATA_LEN
+    + 8
+    + 32
+    + 8
+    + 32
+    + PAYLOAD_SCHEMA_LEN
+    + 8
+    + 8
+    + 4
+    + 4
+    + 2
+    + 4
+    + 32
+    + 8
+    + 8
+    + 8
+    + 8
+    + 4
+    + 1;
+// min_len(2) + max_len(2) + require_version(1) + version(1) + magic_len(1) + magic(4)
+// + max_version(1)
+const PAYLOAD_SCHEMA_LEN: usize = 2 + 2 + 1 + 1 + 1 + PAYLOAD_MAGIC_MAX_LEN + 1;
+
+/// Transaction-wide compute ceiling (ComputeBudget max).
+pub const MAX_TX_COMPUTE_UNITS: u64 = 1_400_000;
+/// Compute the router keeps back for its own accounting after an adapter CPI returns.
+pub const ROUTER_POST_CPI_RESERVE_CU: u64 = 25_000;
+/// Runtime limit on the instruction stack (transaction level = 1).
+pub const MAX_INVOKE_STACK_HEIGHT: usize = 5;
+/// Stack levels an adapter CPI needs below the router: the adapter plus one nested CPI
+/// (e.g. SPL Token).
+pub const ADAPTER_CPI_DEPTH: usize = 2;
+
+/// Pre-CPI guardrail: the adapter must fit under the stack limit and its compute allowance
+/// plus the router's reserve must still be available. `remaining_cu` / `stack_height` of 0
+/// mean the syscall is unavailable (native builds) and skip that check.
+pub fn check_adapter_budget(
+    remaining_cu: u64,
+    stack_height: usize,
+    max_adapter_cu: u32,
+) -> Result<()> {
+    require!(
+        stack_height == 0 || stack_height + ADAPTER_CPI_DEPTH <= MAX_INVOKE_STACK_HEIGHT,
+        ErrorCode::AdapterBudgetExceeded
+    );
+    require!(
+        remaining_cu == 0 || remaining_cu >= max_adapter_cu as u64 + ROUTER_POST_CPI_RESERVE_CU,
+        ErrorCode::AdapterBudgetExceeded
+    );
+    Ok(())
+}
+
+/// Compute metering around one adapter CPI: `enter` runs the pre-CPI checks, `exit` fails
+/// (after emitting AdapterComputeExceeded) when the adapter used more than its allowance.
+pub struct AdapterCuGuard {
+    spoke_id: u32,
+    adapter: Pubkey,
+    max_adapter_cu: u32,
+    before: u64,
+}
+
+impl AdapterCuGuard {
+    pub fn enter(spoke_id: u32, adapter: Pubkey, max_adapter_cu: u32) -> Result<Self> {
+        let before = anchor_lang::solana_program::compute_units::sol_remaining_compute_units();
+        let stack_height = anchor_lang::solana_program::instruction::get_stack_height();
+        let checked = check_adapter_budget(before, stack_height, max_adapter_cu);
+        if checked.is_err() {
+            zpx_log!(
+                "adapter_budget_rejected",
+                spoke_id = spoke_id,
+                remaining_cu = before,
+                stack_height = stack_height,
+                max_adapter_cu = max_adapter_cu,
+            );
+        }
+        checked?;
+        Ok(AdapterCuGuard {
+            spoke_id,
+            adapter,
+            max_adapter_cu,
+            before,
+        })
+    }
+
+    pub fn exit(self) -> Result<()> {
+        let after = anchor_lang::solana_program::compute_units::sol_remaining_compute_units();
+        if self.before == 0 || self.max_adapter_cu == 0 {
+            return Ok(());
+        }
+        let consumed_cu = self.before.saturating_sub(after);
+        if consumed_cu > self.max_adapter_cu as u64 {
+            zpx_log!(
+                "adapter_budget_exceeded",
+                spoke_id = self.spoke_id,
+                consumed_cu = consumed_cu,
+                max_adapter_cu = self.max_adapter_cu,
+            );
+            emit!(AdapterComputeExceeded {
+                spoke_id: self.spoke_id,
+                adapter: self.adapter,
+                max_adapter_cu: self.max_adapter_cu,
+                consumed_cu,
+            });
+            return err!(ErrorCode::AdapterBudgetExceeded);
+        }
+        Ok(())
+    }
+}
+
+/// create_spoke / update_spoke flag: force direct payout, or follow the config default.
+fn relayer_payout_mode(direct: bool) -> u8 {
+    if direct {
+        RELAYER_PAYOUT_DIRECT
+    } else {
+        RELAYER_PAYOUT_DEFAULT
+    }
+}
+
+/// A relayer token account may take a direct fee payout only when `payee` owns it, it holds
+/// `mint`, it is not frozen and no delegate could move the fee out.
+pub fn check_direct_payout_account(
+    account: &TokenAccount,
+    payee: &Pubkey,
+    mint: &Pubkey,
+) -> Result<()> {
+    require_keys_eq!(account.owner, *payee, ErrorCode::Unauthorized);
+    require_keys_eq!(account.mint, *mint, ErrorCode::RelayerPayoutMintMismatch);
+    require!(!account.is_frozen(), ErrorCode::RelayerPayoutAccountFrozen);
+    require!(
+        account.delegate.is_none(),
+        ErrorCode::RelayerPayoutAccountDelegated
+    );
+    Ok(())
+}
+
+/// Whether a token account owned by `owner` may receive a forward through `spoke`. A spoke
+/// with no registered target owner accepts nothing.
+pub fn adapter_target_ok(spoke: &SpokeEntry, owner: &Pubkey) -> bool {
+    spoke.target_owner != Pubkey::default() && *owner == spoke.target_owner
+}
+
+/// Compute and validate fees per caps (protocol fee capped at FEE_CAP_BPS); returns
+/// (forward_amount, total_fees)
+pub fn compute_fees_and_forward(
+    amount: u64,
+    protocol_fee: u64,
+    relayer_fee: u64,
+    relayer_bps_cap: u16,
+) -> Result<(u64, u64)> {
+    Ok(fees::compute_fees_and_forward(
+        amount,
+        protocol_fee,
+        relayer_fee,
+        FEE_CAP_BPS,
+        relayer_bps_cap,
+    )
+    .map_err(ErrorCode::from)?)
+}
+
+/// Check caller-supplied fees against a route fee policy: both must equal its quote (flat
+/// minimums may exceed the bps caps). Returns (forward_amount, total_fees).
+pub fn policy_fees(
+    policy: &fees::FeePolicy,
+    amount: u64,
+    protocol_fee: u64,
+    relayer_fee: u64,
+) -> Result<(u64, u64)> {
+    let quoted = fees::evaluate_fee_policy(policy, amount).map_err(ErrorCode::from)?;
+    require!(
+        quoted.protocol_fee == protocol_fee && quoted.relayer_fee == relayer_fee,
+        ErrorCode::FeePolicyMismatch
+    );
+    Ok((quoted.forward_amount, amount - quoted.forward_amount))
+}
+
+// Hub vault validation patterns reported in VaultPatternMatched
+/// Canonical PDA vault recorded in the mint's VaultRegistry
+pub const VAULT_PATTERN_REGISTERED: u8 = 0;
+/// Canonical PDA vault without a VaultRegistry record (rejected in strict mode)
+pub const VAULT_PATTERN_UNREGISTERED: u8 = 1;
+
+/// Create an SPL token account at the canonical hub vault PDA [seed, mint] whose token
+/// authority is the vault itself.
+/// Create both hub vaults of `mint` and fill in its VaultRegistry.
+#[allow(clippy::too_many_arguments)]
+fn init_hub_vaults<'info>(
+    authority: &AccountInfo<'info>,
+    mint: &Account<'info, Mint>,
+    hub_protocol_vault: &AccountInfo<'info>,
+    hub_relayer_vault: &AccountInfo<'info>,
+    vault_registry: &mut VaultRegistry,
+    vault_registry_bump: u8,
+    rent_ledger: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    program_id: &Pubkey,
+) -> Result<()> {
+    for (seed, vault) in [
+        (&b"hub_protocol_vault"[..], hub_protocol_vault),
+        (&b"hub_relayer_vault"[..], hub_relayer_vault),
+    ] {
+        create_hub_vault(
+            authority,
+            vault,
+            &mint.to_account_info(),
+            system_program,
+            token_program,
+            seed,
+            program_id,
+        )?;
+        record_rent(
+            rent_ledger,
+            program_id,
+            RENT_KIND_VAULT,
+            TokenAccount::LEN,
+            true,
+        )?;
+    }
+    vault_registry.mint = mint.key();
+    vault_registry.protocol_vault = hub_protocol_vault.key();
+    vault_registry.relayer_vault = hub_relayer_vault.key();
+    vault_registry.version = VAULT_REGISTRY_VERSION;
+    vault_registry.created_at_slot = Clock::get()?.slot;
+    vault_registry.bump = vault_registry_bump;
+    Ok(())
+}
+
+/// Append a new, enabled spoke to the registry; `killed` starts it paused.
+#[allow(clippy::too_many_arguments)]
+fn insert_spoke(
+    registry: &mut Registry,
+    killed: bool,
+    spoke_id: u32,
+    adapter_program: Pubkey,
+    direct_relayer_payout: bool,
+    version: u8,
+    metadata: Option<String>,
+    payload_schema: Option<PayloadSchema>,
+) -> Result<()> {
+    let len = registry.spokes_len as usize;
+    require!(len < MAX_SPOKES, ErrorCode::AdapterListFull);
+    // ensure unique spoke_id
+    for i in 0..len {
+        if registry.spokes[i].spoke_id == spoke_id {
+            return err!(ErrorCode::AdapterAlreadyExists);
+        }
+    }
+    let mut entry = SpokeEntry::default();
+    entry.spoke_id = spoke_id;
+    entry.adapter_program = adapter_program;
+    entry.enabled = true;
+    entry.paused = killed;
+    entry.relayer_payout = relayer_payout_mode(direct_relayer_payout);
+    entry.version = version;
+    if let Some(m) = metadata {
+        let bytes = m.as_bytes();
+        let mut meta = [0u8; SPOKE_METADATA_LEN];
+        meta[..bytes.len().min(SPOKE_METADATA_LEN)]
+            .copy_from_slice(&bytes[..bytes.len().min(SPOKE_METADATA_LEN)]);
+        entry.metadata = meta;
+    }
+    if let Some(schema) = payload_schema {
+        check_payload_schema_config(&schema)?;
+        entry.payload_schema = schema;
+    }
+    entry.created_at_slot = Clock::get()?.slot;
+    registry.spokes[len] = entry;
+    registry.spokes_len += 1;
+    Ok(())
+}
+
+/// SPL Token-2022 program id. Token CPIs accept it alongside SPL Token.
+pub const TOKEN_2022_PROGRAM_ID: Pubkey =
+    anchor_lang::solana_program::pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+/// Fail with InvalidTokenProgram unless `token_program` is the executable SPL Token or
+/// Token-2022 program. Checked by key on every token CPI instead of trusting the account's
+/// position, so a substituted program in a passthrough or remaining account is never invoked.
+pub fn assert_token_program(token_program: &AccountInfo) -> Result<()> {
+    require!(
+        (token_program.key() == Token::id() || token_program.key() == TOKEN_2022_PROGRAM_ID)
+            && token_program.executable,
+        ErrorCode::InvalidTokenProgram
+    );
+    Ok(())
+}
+
+/// `token_program` for a token CpiContext, once assert_token_program passes.
+fn token_cpi_program<'info>(token_program: &AccountInfo<'info>) -> Result<AccountInfo<'info>> {
+    assert_token_program(token_program)?;
+    Ok(token_program.clone())
+}
+
+fn create_hub_vault<'info>(
+    payer: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    seed: &[u8],
+    program_id: &Pubkey,
+) -> Result<()> {
+    let (expected, bump) = Pubkey::find_program_address(&[seed, mint.key.as_ref()], program_id);
+    require_keys_eq!(vault.key(), expected, ErrorCode::InvalidVaultPda);
+    create_pda_account(
+        payer,
+        vault,
+        system_program,
+        TokenAccount::LEN,
+        &[seed, mint.key.as_ref(), &[bump]],
+        &token::ID,
+    )?;
+    token::initialize_account3(CpiContext::new(
+        token_cpi_program(token_program)?,
+        token::InitializeAccount3 {
+            account: vault.clone(),
+            mint: mint.clone(),
+            authority: vault.clone(),
+        },
+    ))
+}
+
+/// Validate a hub vault token account: canonical PDA address for (seed, mint), token
+/// authority equal to that PDA, and owned by the SPL Token program. Returns the PDA bump.
+fn verify_hub_vault(
+    vault: &Account<TokenAccount>,
+    seed: &[u8],
+    mint: &Pubkey,
+    program_id: &Pubkey,
+) -> Result<u8> {
+    let (expected_vault, bump) = Pubkey::find_program_address(&[seed, mint.as_ref()], program_id);
+    require_keys_eq!(expected_vault, vault.key(), ErrorCode::InvalidVaultPda);
+    require_keys_eq!(vault.owner, expected_vault, ErrorCode::InvalidVaultOwner);
+    require!(
+        vault.to_account_info().owner == &token::ID,
+        ErrorCode::InvalidTokenProgram
+    );
+    Ok(bump)
+}
+
+/// Classify `vault` against the mint's VaultRegistry, emit the pattern, and reject
+/// unregistered vaults when `strict` is set.
+fn check_vault_pattern(
+    vault_registry: &AccountInfo,
+    mint: &Pubkey,
+    vault: &Pubkey,
+    strict: bool,
+    program_id: &Pubkey,
+) -> Result<u8> {
+    let (expected, _) =
+        Pubkey::find_program_address(&[b"vault_registry", mint.as_ref()], program_id);
+    require_keys_eq!(vault_registry.key(), expected, ErrorCode::InvalidVaultPda);
+    let registered = vault_registry.owner == program_id
+        && VaultRegistry::try_deserialize(&mut &vault_registry.try_borrow_data()?[..])
+            .map(|r| r.protocol_vault == *vault || r.relayer_vault == *vault)
+            .unwrap_or(false);
+    let pattern = if registered {
+        VAULT_PATTERN_REGISTERED
+    } else {
+        VAULT_PATTERN_UNREGISTERED
+    };
+    zpx_log!(
+        "vault_pattern_matched",
+        mint = mint,
+        vault = vault,
+        pattern = pattern,
+        strict = strict,
+    );
+    emit!(VaultPatternMatched {
+        mint: *mint,
+        vault: *vault,
+        pattern,
+        strict,
+    });
+    require!(
+        !strict || pattern == VAULT_PATTERN_REGISTERED,
+        ErrorCode::VaultNotRegistered
+    );
+    Ok(pattern)
+}
+
+/// Fail with VaultsNotInitialized unless `mint` has a VaultRegistry or, for mints provisioned
+/// before the registry existed, both hub vaults are live token accounts.
+fn require_vaults_initialized<'info>(
+    vault_registry: &AccountInfo<'info>,
+    protocol_vault: &AccountInfo<'info>,
+    relayer_vault: &AccountInfo<'info>,
+    mint: &Pubkey,
+    program_id: &Pubkey,
+) -> Result<()> {
+    let (expected, _) =
+        Pubkey::find_program_address(&[b"vault_registry", mint.as_ref()], program_id);
+    require_keys_eq!(vault_registry.key(), expected, ErrorCode::InvalidVaultPda);
+    let registered = vault_registry.owner == program_id && !vault_registry.data_is_empty();
+    let legacy = [protocol_vault, relayer_vault]
+        .iter()
+        .all(|v| v.owner == &token::ID && !v.data_is_empty());
+    require!(registered || legacy, ErrorCode::VaultsNotInitialized);
+    Ok(())
+}
+
+/// Smallest existing message_account forward_via_spoke accepts (an Anchor discriminator).
+pub const MIN_MESSAGE_ACCOUNT_LEN: usize = 8;
+
+/// Fail with AccountNotRentExempt when `ai` holds less than the rent-exempt minimum for its
+/// current size.
+fn require_rent_exempt(ai: &AccountInfo, rent: &Rent) -> Result<()> {
+    require!(
+        rent.is_exempt(ai.lamports(), ai.data_len()),
+        ErrorCode::AccountNotRentExempt
+    );
+    Ok(())
+}
+
+/// message_account is either still blank (system-owned, no data; the adapter creates it
+/// later) or an existing account of the spoke's adapter that is at least
+/// MIN_MESSAGE_ACCOUNT_LEN bytes and rent-exempt.
+fn check_message_account(ai: &AccountInfo, adapter_program: &Pubkey, rent: &Rent) -> Result<()> {
+    if ai.owner == &anchor_lang::solana_program::system_program::ID && ai.data_is_empty() {
+        return Ok(());
+    }
+    require_keys_eq!(
+        *ai.owner,
+        *adapter_program,
+        ErrorCode::MessageAccountOwnerMismatch
+    );
+    require!(
+        ai.data_len() >= MIN_MESSAGE_ACCOUNT_LEN,
+        ErrorCode::MessageAccountTooSmall
+    );
+    require_rent_exempt(ai, rent)
+}
+
+/// Create whichever canonical hub vaults of `mint` are missing and its VaultRegistry, paid by
+/// `payer`. Existing vaults and registries are left as they are.
+fn ensure_hub_vaults<'info>(
+    payer: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    protocol_vault: &AccountInfo<'info>,
+    relayer_vault: &AccountInfo<'info>,
+    vault_registry: &AccountInfo<'info>,
+    rent_ledger: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    program_id: &Pubkey,
+) -> Result<()> {
+    for (seed, vault) in [
+        (&b"hub_protocol_vault"[..], protocol_vault),
+        (&b"hub_relayer_vault"[..], relayer_vault),
+    ] {
+        if vault.data_is_empty() {
+            create_hub_vault(
+                payer,
+                vault,
+                mint,
+                system_program,
+                token_program,
+                seed,
+                program_id,
+            )?;
+            record_rent(
+                rent_ledger,
+                program_id,
+                RENT_KIND_VAULT,
+                TokenAccount::LEN,
+                true,
+            )?;
+        }
+    }
+    if vault_registry.owner == program_id {
+        return Ok(());
+    }
+    let (expected, bump) =
+        Pubkey::find_program_address(&[b"vault_registry", mint.key.as_ref()], program_id);
+    require_keys_eq!(vault_registry.key(), expected, ErrorCode::InvalidVaultPda);
+    create_pda_account(
+        payer,
+        vault_registry,
+        system_program,
+        VAULT_REGISTRY_SPACE,
+        &[b"vault_registry", mint.key.as_ref(), &[bump]],
+        program_id,
+    )?;
+    let registry = VaultRegistry {
+        mint: mint.key(),
+        protocol_vault: protocol_vault.key(),
+        relayer_vault: relayer_vault.key(),
+        version: VAULT_REGISTRY_VERSION,
+        created_at_slot: Clock::get()?.slot,
+        bump,
+    };
+    registry.try_serialize(&mut &mut vault_registry.try_borrow_mut_data()?[..])?;
+    emit!(HubVaultsInitialized {
+        mint: registry.mint,
+        protocol_vault: registry.protocol_vault,
+        relayer_vault: registry.relayer_vault,
+        version: registry.version,
+    });
+    Ok(())
+}
+
+/// Validate fee caps and assemble a fresh Config (no adapters, unpaused).
+fn build_config(
+    admin: Pubkey,
+    fee_recipient: Pubkey,
+    src_chain_id: u64,
+    relayer_fee_bps: u16,
+    protocol_fee_bps: u16,
+    relayer_pubkey: Pubkey,
+    accept_any_token: bool,
+    allowed_token_mint: Pubkey,
+    direct_relayer_payout_default: bool,
+    min_forward_amount: u64,
+    bump: u8,
+) -> Result<Config> {
+    require!(
+        relayer_fee_bps <= RELAYER_FEE_CAP_BPS,
+        ErrorCode::RelayerFeeTooHigh
+    );
+    require!(
+        protocol_fee_bps <= FEE_CAP_BPS,
+        ErrorCode::ProtocolFeeTooHigh
+    );
+    Ok(Config {
+        admin,
+        fee_recipient,
+        src_chain_id,
+        relayer_fee_bps,
+        protocol_fee_bps,
+        relayer_pubkey,
+        accept_any_token,
+        allowed_token_mint,
+        direct_relayer_payout_default,
+        min_forward_amount,
+        adapters_len: 0,
+        adapters: [Pubkey::default(); 8],
+        paused: false,
+        bump,
+        max_relayer_fee_lamports: 0,
+        usd_limits_enabled: false,
+        strict_vaults: false,
+        lp_fee_bps: 0,
+        lp_vault_program: Pubkey::default(),
+        killed: false,
+        kill_release_slot: 0,
+        guardian: Pubkey::default(),
+        admin_op_keys: [0; ADMIN_OP_KEYS],
+        admin_op_cursor: 0,
+        legacy_message_hash: false,
+        features: FEATURES_DEFAULT,
+        bridge_hook: Pubkey::default(),
+        bridge_hook_best_effort: false,
+        namespace_len: 0,
+        namespace: [0; SANDBOX_NAMESPACE_LEN],
+        schema_version: CONFIG_SCHEMA_VERSION,
+        rent_refund_policy: RENT_REFUND_PAYER,
+        rent_refund_caller_bps: 0,
+        config_version: 0,
+        paused_families: 0,
+    })
+}
+
+/// Create a program-owned PDA of `space` bytes, tolerating lamports pre-funded to the
+/// address (which would make a plain create_account fail).
+fn create_pda_account<'info>(
+    payer: &AccountInfo<'info>,
+    target: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+    signer_seeds: &[&[u8]],
+    program_id: &Pubkey,
+) -> Result<()> {
+    let required = Rent::get()?.minimum_balance(space);
+    let current = target.lamports();
+    if current == 0 {
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                target.key,
+                required,
+                space as u64,
+                program_id,
+            ),
+            &[payer.clone(), target.clone(), system_program.clone()],
+            &[signer_seeds],
+        )?;
+    } else {
+        let top_up = required.saturating_sub(current);
+        if top_up > 0 {
+            anchor_lang::solana_program::program::invoke(
+                &system_instruction::transfer(payer.key, target.key, top_up),
+                &[payer.clone(), target.clone(), system_program.clone()],
+            )?;
+        }
+        invoke_signed(
+            &system_instruction::allocate(target.key, space as u64),
+            &[target.clone(), system_program.clone()],
+            &[signer_seeds],
+        )?;
+        invoke_signed(
+            &system_instruction::assign(target.key, program_id),
+            &[target.clone(), system_program.clone()],
+            &[signer_seeds],
+        )?;
+    }
+    Ok(())
+}
+
+/// Strict ATA derivation: the fee account must be the associated token account of
+/// `fee_recipient` for `mint` under `token_program`, and be an SPL Token account.
+fn verify_fee_recipient_ata(
+    fee_recipient: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    fee_recipient_ata: &Account<TokenAccount>,
+) -> Result<()> {
+    // Expected = get_associated_token_address_with_program_id(fee_recipient, mint, token_program)
+    let ata_seeds: &[&[u8]] = &[
+        &fee_recipient.to_bytes(),
+        &token_program.to_bytes(),
+        &mint.to_bytes(),
+    ];
+    let (expected_fee_ata, _bump) =
+        Pubkey::find_program_address(ata_seeds, &anchor_spl::associated_token::ID);
+    require!(
+        fee_recipient_ata.key() == expected_fee_ata,
+        ErrorCode::InvalidFeeRecipientAta
+    );
+    // Extra checks for safety
+    require!(
+        fee_recipient_ata.to_account_info().owner == &token::ID,
+        ErrorCode::InvalidTokenProgram
+    );
+    require!(
+        fee_recipient_ata.mint == *mint,
+        ErrorCode::InvalidFeeRecipientAta
+    );
+    Ok(())
+}
+
+/// Canonical source-leg hashes: (payload_hash, message_hash, global_route_id).
+/// The target adapter program stands in for srcAdapter and the recipient is unknown
+/// on the source leg (resolved on destination).
+/// Shared body of forward_via_spoke / forward_partial: dispatch `amount` of the escrowed
+/// message. The escrow (and its vault) is closed once the full escrowed amount is dispatched.
+#[allow(clippy::too_many_arguments)]
+fn forward_from_escrow(
+    ctx: Context<ForwardViaSpoke>,
+    spoke_id: u32,
+    amount: u64,
+    dst_domain: u32,
+    mint_recipient: [u8; 32],
+    is_protocol_fee: bool,
+    is_relayer_fee: bool,
+    relayer_fee_lamports: u64,
+    adapter_payload: &[u8],
+    partial: bool,
+    rerouted: bool,
+    intent_signed: bool,
+) -> Result<()> {
+    // Validate caller is relayer or admin; a spoke relayer allowlist replaces the
+    // global relayer when present. A verified bridge intent authorizes any submitter.
+    let cfg = &ctx.accounts.config;
+    require!(!cfg.killed, ErrorCode::GlobalKilled);
+    cfg.require_family_active(INSTRUCTION_FAMILY_FORWARD)?;
+    let features = cfg.features;
+    let balance_events = cfg.feature_enabled(FEATURE_BALANCE_EVENTS);
+    // Once an escrow holds fees it keeps holding them, even if the switch is turned off
+    let held_before = ctx.accounts.escrow.held_fees();
+    let hold_fees = cfg.feature_enabled(FEATURE_FEE_ESCROW) || held_before > 0;
+    require_mint_active(
+        &ctx.accounts.mint_pause,
+        &ctx.accounts.mint.key(),
+        ctx.program_id,
+    )?;
+    require_mint_allowed(
+        &ctx.accounts.mint_allowlist,
+        cfg,
+        &ctx.accounts.mint.key(),
+        ctx.program_id,
+    )?;
+    // Name the real problem for never-provisioned mints instead of failing in vault checks
+    require_vaults_initialized(
+        &ctx.accounts.vault_registry,
+        &ctx.accounts.hub_protocol_vault,
+        &ctx.accounts.hub_relayer_vault,
+        &ctx.accounts.mint.key(),
+        ctx.program_id,
+    )?;
+    let hub_protocol_vault = Account::<TokenAccount>::try_from(&ctx.accounts.hub_protocol_vault)?;
+    let hub_relayer_vault = Account::<TokenAccount>::try_from(&ctx.accounts.hub_relayer_vault)?;
+    let spoke_relayers = load_spoke_relayers(
+        &ctx.accounts.spoke_relayers.to_account_info(),
+        spoke_id,
+        ctx.program_id,
+    )?;
+    require!(
+        intent_signed || relayer_allowed(&ctx.accounts.relayer.key(), cfg, spoke_relayers.as_ref()),
+        ErrorCode::Unauthorized
+    );
+    // The relayer may only execute the intent the user escrowed; a full forward must
+    // dispatch everything still undispatched. Rerouted forwards (forward_balanced,
+    // forward_auto) have already resolved spoke_id from the escrowed spoke.
+    let escrow = &ctx.accounts.escrow;
+    require!(!escrow.cancelled, ErrorCode::MessageCancelled);
+    let remaining = escrow
+        .amount
+        .checked_sub(escrow.forwarded)
+        .ok_or(ErrorCode::MathOverflow)?;
+    // Partial dispatches are measured against the whole escrow, so splitting cannot dodge it
+    let cosign = load_relayer_cosign(
+        &ctx.accounts.relayer_cosign,
+        &ctx.accounts.mint.key(),
+        ctx.program_id,
+    )?;
+    if let Some((weight, cosigners)) = check_relayer_cosign(
+        cosign.as_ref(),
+        escrow.amount,
+        &ctx.accounts.relayer.key(),
+        ctx.remaining_accounts,
+    )? {
+        emit!(ForwardCosigned {
+            user: escrow.user,
+            nonce: escrow.nonce,
+            spoke_id,
+            amount: escrow.amount,
+            weight,
+            cosigners,
+        });
+    }
+    require!(
+        (escrow.spoke_id == spoke_id || rerouted)
+            && escrow.dst_domain == dst_domain
+            && escrow.mint_recipient == mint_recipient
+            && amount <= remaining
+            && (partial || amount == remaining),
+        ErrorCode::EscrowMismatch
+    );
+    let completes = amount == remaining;
+    let (escrow_user, escrow_nonce, escrow_bump) = (escrow.user, escrow.nonce, escrow.bump);
+    let escrow_nonce_le = escrow_nonce.to_le_bytes();
+    let escrow_seeds: &[&[u8]] = &[
+        b"forward_escrow",
+        escrow_user.as_ref(),
+        &escrow_nonce_le,
+        &[escrow_bump],
+    ];
+    let escrow_signer: &[&[&[u8]]] = &[escrow_seeds];
+    // Lookup spoke
+    let registry = &ctx.accounts.registry;
+    let mut idx = None;
+    for i in 0..(registry.spokes_len as usize) {
+        if registry.spokes[i].spoke_id == spoke_id {
+            idx = Some(i);
+            break;
+        }
+    }
+    let i = idx.ok_or_else(|| error!(ErrorCode::AdapterNotAllowed))?;
+    let spoke = &registry.spokes[i];
+    require!(spoke.enabled && !spoke.paused, ErrorCode::AdapterNotAllowed);
+    zpx_log!(
+        "spoke_resolved",
+        spoke_id = spoke_id,
+        index = i,
+        adapter = spoke.adapter_program,
+    );
+    // Upgrade pinning: reject if the adapter was redeployed since it was pinned
+    if spoke.adapter_program_data != Pubkey::default() {
+        let pd_ai = ctx.accounts.adapter_program_data.to_account_info();
+        require!(
+            pd_ai.owner == &anchor_lang::solana_program::bpf_loader_upgradeable::ID,
+            ErrorCode::AdapterCodeChanged
+        );
+        let program_data = ProgramData::try_deserialize(&mut &pd_ai.try_borrow_data()?[..])
+            .map_err(|_| error!(ErrorCode::AdapterCodeChanged))?;
+        check_adapter_pin(spoke, &pd_ai.key(), &program_data)?;
+    }
+    // Deprecation: warn inside the window, hard-fail after sunset (event emitted last)
+    let deprecation_warning = spoke_deprecation_active(spoke, Clock::get()?.slot)?;
+    // Fail fast on malformed adapter payloads before any funds move
+    validate_payload_len(adapter_payload.len())?;
+    validate_payload_schema(&spoke.payload_schema, adapter_payload)?;
+    check_message_account(
+        &ctx.accounts.message_account.to_account_info(),
+        &spoke.adapter_program,
+        &Rent::get()?,
+    )?;
+
+    // Enforce hub-level fee caps (configured on init/update)
+    require!(
+        cfg.protocol_fee_bps <= FEE_CAP_BPS,
+        ErrorCode::ProtocolFeeTooHigh
+    );
+    require!(
+        cfg.relayer_fee_bps <= RELAYER_FEE_CAP_BPS,
+        ErrorCode::RelayerFeeTooHigh
+    );
+
+    // Fees follow the route's fee policy (the hub-configured bps without one); the flags
+    // waive either fee
+    require!(amount > 0, ErrorCode::ZeroAmount);
+    let fee_policy = load_fee_policy(
+        &ctx.accounts.fee_policy,
+        &ctx.accounts.mint.key(),
+        fee_policy_chain_id(ctx.accounts.escrow.dst_domain),
+        ctx.program_id,
+    )?
+    .unwrap_or_else(|| cfg.flat_fee_policy());
+    // A non-zero lamport fee replaces the token relayer-fee skim entirely
+    let relayer_fee_mode = if relayer_fee_lamports > 0 {
+        require!(
+            relayer_fee_lamports <= cfg.max_relayer_fee_lamports
+                && relayer_fee_lamports <= ctx.accounts.escrow.lamport_budget,
+            ErrorCode::RelayerFeeTooHigh
+        );
+        RELAYER_FEE_MODE_LAMPORTS
+    } else {
+        RELAYER_FEE_MODE_TOKEN
+    };
+    let waive_relayer = !is_relayer_fee || relayer_fee_mode != RELAYER_FEE_MODE_TOKEN;
+    // A message dispatched in chunks is quoted once, on the whole escrow, by its first
+    // partial dispatch; each chunk then applies its share of the staged fees. Escrows
+    // already mid-flight without a ForwardState keep the per-chunk quote.
+    let escrow_key = ctx.accounts.escrow.key();
+    let forwarded_before = ctx.accounts.escrow.forwarded;
+    let (forward_state, forward_state_bump) =
+        load_forward_state(&ctx.accounts.forward_state, &escrow_key, ctx.program_id)?;
+    let stages_fees = forward_state.is_none() && forwarded_before == 0 && !completes;
+    let mut forward_state = match forward_state {
+        Some(state) => Some(state),
+        None if stages_fees => {
+            let staged = fees::evaluate_fee_policy(&fee_policy, ctx.accounts.escrow.amount)
+                .map_err(ErrorCode::from)?;
+            Some(ForwardState {
+                escrow: escrow_key,
+                protocol_fee: staged.protocol_fee,
+                relayer_fee: staged.relayer_fee,
+                protocol_fee_applied: 0,
+                relayer_fee_applied: 0,
+                bump: forward_state_bump,
+            })
+        }
+        None => None,
+    };
+    let (proto_fee, relayer_fee) = match forward_state.as_mut() {
+        Some(state) => {
+            let (proto_share, relayer_share) =
+                state.share(ctx.accounts.escrow.amount, forwarded_before, amount)?;
+            let proto_fee = if is_protocol_fee { proto_share } else { 0 };
+            let relayer_fee = if waive_relayer { 0 } else { relayer_share };
+            require!(
+                proto_fee + relayer_fee < amount,
+                ErrorCode::FeesExceedAmount
+            );
+            state.protocol_fee_applied += proto_fee;
+            state.relayer_fee_applied += relayer_fee;
+            (proto_fee, relayer_fee)
+        }
+        None => {
+            let quoted = fees::evaluate_fee_policy(
+                &fee_policy.waive(!is_protocol_fee, waive_relayer),
+                amount,
+            )
+            .map_err(ErrorCode::from)?;
+            (quoted.protocol_fee, quoted.relayer_fee)
+        }
+    };
+    let total_fees = proto_fee + relayer_fee;
+    // The final dispatch also sweeps any stray tokens sent to the escrow vault (but not
+    // fees held from earlier dispatches)
+    let net_amount = if completes {
+        ctx.accounts
+            .escrow_vault
+            .amount
+            .checked_sub(total_fees)
+            .and_then(|n| n.checked_sub(held_before))
+            .ok_or(ErrorCode::MathOverflow)?
+    } else {
+        amount - total_fees
+    };
+    zpx_log!(
+        "fee_computed",
+        amount = amount,
+        protocol_fee = proto_fee,
+        relayer_fee = relayer_fee,
+        relayer_fee_lamports = relayer_fee_lamports,
+        net_amount = net_amount,
+    );
+
+    // Validate vault PDAs are correct. The token accounts provided must have
+    // their authority (owner field) set to the corresponding PDA and the
+    // account data must be owned by the SPL Token program.
+    let mint_key = ctx.accounts.mint.key();
+    verify_hub_vault(
+        &hub_protocol_vault,
+        b"hub_protocol_vault",
+        &mint_key,
+        ctx.program_id,
+    )?;
+    verify_hub_vault(
+        &hub_relayer_vault,
+        b"hub_relayer_vault",
+        &mint_key,
+        ctx.program_id,
+    )?;
+    check_vault_pattern(
+        &ctx.accounts.vault_registry,
+        &mint_key,
+        &hub_protocol_vault.key(),
+        cfg.strict_vaults,
+        ctx.program_id,
+    )?;
+    let direct_payout = spoke.direct_payout(cfg);
+    // Ensure relayer token account belongs to configured relayer pubkey (or to the
+    // calling partner relayer on spokes with a relayer allowlist)
+    let payout_owner = if spoke_relayers.is_some() {
+        ctx.accounts.relayer.key()
+    } else {
+        cfg.relayer_pubkey
+    };
+    if relayer_fee > 0 && direct_payout {
+        check_direct_payout_account(
+            &ctx.accounts.relayer_token_account,
+            &payout_owner,
+            &mint_key,
+        )?;
+    }
+    let fee_payee = if direct_payout {
+        payout_owner
+    } else {
+        ctx.accounts.relayer.key()
+    };
+    // Held relayer fees settle to a single payee
+    require!(
+        !hold_fees
+            || relayer_fee == 0
+            || ctx.accounts.escrow.held_relayer_fee == 0
+            || (ctx.accounts.escrow.fee_payee == fee_payee
+                && ctx.accounts.escrow.fee_payee_direct == direct_payout),
+        ErrorCode::FeesHeld
+    );
+    // The net amount may only land in a token account owned by the spoke's registered target
+    require!(
+        adapter_target_ok(spoke, &ctx.accounts.adapter_target_token_account.owner),
+        ErrorCode::InvalidAdapterTarget
+    );
+    let adapter_program = spoke.adapter_program;
+    let successor_spoke_id = spoke.successor_spoke_id;
+    let sunset_slot = spoke.sunset_slot;
+    let sla_target_slots = spoke.sla_target_slots;
+    let balances_before = balance_events.then(|| ForwardTokenBalances::read(ctx.accounts));
+
+    // Effects: escrow progress, lamport fee and stats are written before any CPI
+    let first_dispatch = ctx.accounts.escrow.forwarded == 0;
+    let forwarded = ctx.accounts.escrow.forwarded + amount;
+    ctx.accounts.escrow.forwarded = forwarded;
+    // The first relayer to dispatch wins the user's tip
+    let relayer_tip = if first_dispatch {
+        std::mem::take(&mut ctx.accounts.escrow.relayer_tip)
+    } else {
+        0
+    };
+    // Lamport relayer fee comes out of the escrowed lamport budget
+    ctx.accounts.escrow.lamport_budget -= relayer_fee_lamports;
+    // Held fees stay in the vault; the relayer fee accrues when the dispatch is confirmed
+    let fees_held = hold_fees && total_fees > 0;
+    if fees_held {
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.held_protocol_fee += proto_fee;
+        escrow.held_relayer_fee += relayer_fee;
+        if relayer_fee > 0 {
+            escrow.fee_payee = fee_payee;
+            escrow.fee_payee_direct = direct_payout;
+        }
+    }
+    // The escrow stays open until its held fees are settled
+    let closes = completes && ctx.accounts.escrow.held_fees() == 0;
+    if relayer_fee > 0 && !direct_payout && !fees_held {
+        accrue_relayer_fee(
+            &ctx.accounts.relayer.to_account_info(),
+            &ctx.accounts.relayer.key(),
+            &ctx.accounts.relayer_vesting_config,
+            &ctx.accounts.relayer_vesting,
+            &ctx.accounts.system_program.to_account_info(),
+            &mint_key,
+            relayer_fee,
+            time::now(ctx.remaining_accounts, ctx.program_id)?.slot,
+            ctx.program_id,
+        )?;
+    }
+    record_router_stats(
+        &ctx.accounts.router_stats,
+        ctx.program_id,
+        &mint_key,
+        amount,
+        STATS_KIND_FORWARDED,
+    )?;
+    // Relayer latency is measured once per message, from deposit to first dispatch
+    let sla_breach = if first_dispatch {
+        let slot = time::now(ctx.remaining_accounts, ctx.program_id)?.slot;
+        record_relayer_activity(
+            &ctx.accounts.relayer.to_account_info(),
+            &ctx.accounts.relayer_activity,
+            &ctx.accounts.system_program.to_account_info(),
+            slot.saturating_sub(ctx.accounts.escrow.created_at_slot),
+            sla_target_slots,
+            slot,
+            ctx.program_id,
+        )?
+        .map(|activity| SlaBreached {
+            relayer: activity.relayer,
+            spoke_id,
+            user: escrow_user,
+            nonce: escrow_nonce,
+            latency_slots: slot.saturating_sub(ctx.accounts.escrow.created_at_slot),
+            target_slots: sla_target_slots,
+            average_latency_slots: activity.average_latency_slots(),
+            sla_breaches: activity.sla_breaches,
+        })
+    } else {
+        None
+    };
+    // Staged fees are persisted with the chunk that applied them
+    if let Some(state) = forward_state.as_ref().filter(|_| !completes) {
+        let state_ai = ctx.accounts.forward_state.to_account_info();
+        if stages_fees {
+            create_pda_account(
+                &ctx.accounts.relayer.to_account_info(),
+                &state_ai,
+                &ctx.accounts.system_program.to_account_info(),
+                FORWARD_STATE_SPACE,
+                &[b"forward_state", escrow_key.as_ref(), &[forward_state_bump]],
+                ctx.program_id,
+            )?;
+        }
+        state.try_serialize(&mut &mut state_ai.try_borrow_mut_data()?[..])?;
+    }
+    let spoke = &mut ctx.accounts.registry.spokes[i];
+    let was_imbalanced = spoke.imbalanced();
+    spoke.dispatched_amount = spoke
+        .dispatched_amount
+        .checked_add(net_amount)
+        .ok_or(ErrorCode::MathOverflow)?;
+    let imbalance = (!was_imbalanced && spoke.imbalanced()).then(|| spoke.imbalance_event());
+
+    // Interactions: token CPIs signed by the escrow PDA
+    // Protocol fee -> hub_protocol_fee_vault (PDA)
+    if proto_fee > 0 && !fees_held {
+        token::transfer(
+            CpiContext::new_with_signer(
+                token_cpi_program(&ctx.accounts.token_program)?,
+                token::Transfer {
+                    from: ctx.accounts.escrow_vault.to_account_info(),
+                    to: ctx.accounts.hub_protocol_vault.to_account_info(),
+                    authority: ctx.accounts.escrow.to_account_info(),
+                },
+                escrow_signer,
+            ),
+            proto_fee,
+        )?;
+    }
+
+    // Relayer fee -> direct payout or hub_relayer_vault
+    if relayer_fee > 0 && !fees_held {
+        let to = if direct_payout {
+            ctx.accounts.relayer_token_account.to_account_info()
+        } else {
+            ctx.accounts.hub_relayer_vault.to_account_info()
+        };
+        token::transfer(
+            CpiContext::new_with_signer(
+                token_cpi_program(&ctx.accounts.token_program)?,
+                token::Transfer {
+                    from: ctx.accounts.escrow_vault.to_account_info(),
+                    to,
+                    authority: ctx.accounts.escrow.to_account_info(),
+                },
+                escrow_signer,
+            ),
+            relayer_fee,
+        )?;
+    }
+
+    if closes {
+        let rent_ledger = ctx.accounts.rent_ledger.to_account_info();
+        record_rent(
+            &rent_ledger,
+            ctx.program_id,
+            RENT_KIND_ESCROW,
+            FORWARD_ESCROW_SPACE,
+            false,
+        )?;
+        record_rent(
+            &rent_ledger,
+            ctx.program_id,
+            RENT_KIND_VAULT,
+            TokenAccount::LEN,
+            false,
+        )?;
+        // Final dispatch: transfer the net amount to the adapter target token account,
+        // then close the escrow vault into the escrow, whose rent is paid out on close
+        drain_forward_escrow_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.escrow_vault,
+            &ctx.accounts.escrow.to_account_info(),
+            &ctx.accounts.escrow.to_account_info(),
+            Some((
+                &ctx.accounts.adapter_target_token_account.to_account_info(),
+                net_amount,
+            )),
+            escrow_seeds,
+        )?;
+    } else {
+        token::transfer(
+            CpiContext::new_with_signer(
+                token_cpi_program(&ctx.accounts.token_program)?,
+                token::Transfer {
+                    from: ctx.accounts.escrow_vault.to_account_info(),
+                    to: ctx.accounts.adapter_target_token_account.to_account_info(),
+                    authority: ctx.accounts.escrow.to_account_info(),
+                },
+                escrow_signer,
+            ),
+            net_amount,
+        )?;
+    }
+
+    // CPI passthrough to adapter omitted in Phase 1 (TODO: add adapter CPI with explicit account layout)
+
+    // Lamport fee and tip -> relayer wallet. Moved after the token CPIs so the runtime's
+    // per-CPI lamport balance check only sees SPL Token's own changes.
+    let relayer_lamports = relayer_fee_lamports + relayer_tip;
+    if relayer_lamports > 0 {
+        let escrow_ai = ctx.accounts.escrow.to_account_info();
+        let relayer_ai = ctx.accounts.relayer.to_account_info();
+        **escrow_ai.try_borrow_mut_lamports()? -= relayer_lamports;
+        **relayer_ai.try_borrow_mut_lamports()? += relayer_lamports;
+    }
+    if completes && forward_state.is_some() {
+        close_program_account(
+            &ctx.accounts.forward_state.to_account_info(),
+            &ctx.accounts.relayer.to_account_info(),
+        )?;
+    }
+
+    if closes {
+        let escrow_ai = ctx.accounts.escrow.to_account_info();
+        let user = ctx.accounts.user.to_account_info();
+        let rent = escrow_ai
+            .lamports()
+            .saturating_sub(ctx.accounts.escrow.owed_lamports());
+        refund_escrow_rent(
+            &ctx.accounts.config,
+            &escrow_ai,
+            &user,
+            &ctx.accounts.relayer.to_account_info(),
+            &ctx.accounts.rent_ledger.to_account_info(),
+            rent,
+            ctx.program_id,
+        )?;
+        ctx.accounts.escrow.close(user)?;
+    }
+
+    // Events last, once every state change and CPI has succeeded
+    if relayer_tip > 0 {
+        emit!(RelayerTipClaimed {
+            user: escrow_user,
+            nonce: escrow_nonce,
+            relayer: ctx.accounts.relayer.key(),
+            relayer_tip,
+        });
+    }
+    if deprecation_warning {
+        emit!(SpokeDeprecationWarning {
+            spoke_id,
+            successor_spoke_id,
+            sunset_slot,
+        });
+    }
+    if let Some(imbalance) = imbalance {
+        emit!(imbalance);
+    }
+    if let Some(breach) = sla_breach {
+        emit!(breach);
+    }
+    if let Some(state) = forward_state.filter(|_| stages_fees) {
+        emit!(ForwardFeesStaged {
+            user: escrow_user,
+            nonce: escrow_nonce,
+            protocol_fee: state.protocol_fee,
+            relayer_fee: state.relayer_fee,
+        });
+    }
+    if fees_held {
+        emit!(EscrowFeesHeld {
+            user: escrow_user,
+            nonce: escrow_nonce,
+            protocol_fee: proto_fee,
+            relayer_fee,
+            held_protocol_fee: ctx.accounts.escrow.held_protocol_fee,
+            held_relayer_fee: ctx.accounts.escrow.held_relayer_fee,
+        });
+    }
+    emit!(Forwarded {
+        user: ctx.accounts.user.key(),
+        relayer: ctx.accounts.relayer.key(),
+        spoke_id,
+        adapter_program,
+        amount,
+        protocol_fee: proto_fee,
+        relayer_fee,
+        net_amount,
+        dst_domain,
+        message_account: ctx.accounts.message_account.key(),
+        relayer_fee_mode,
+        relayer_fee_lamports,
+        config_version: ctx.accounts.config.config_version,
+    });
+    if let Some(before) = balances_before {
+        emit!(ForwardBalances {
+            user: escrow_user,
+            nonce: escrow_nonce,
+            spoke_id,
+            features,
+            before,
+            after: ForwardTokenBalances::read(ctx.accounts),
+        });
+    }
+    if partial {
+        emit!(PartialForwarded {
+            user: escrow_user,
+            nonce: escrow_nonce,
+            spoke_id,
+            portion: amount,
+            forwarded,
+            total: ctx.accounts.escrow.amount,
+            complete: completes,
+        });
+    }
+    // last, so an adapter's own return data never shadows it for CPI callers
+    let summary = ForwardSummary {
+        proto_fee,
+        relayer_fee,
+        net: net_amount,
+        spoke_id,
+        message: ctx.accounts.message_account.key(),
+    };
+    set_return_data(&summary.try_to_vec()?);
+
+    Ok(())
+}
+
+/// Spoke entry at the fast path's cached registry slot, checked to still be its spoke.
+fn fast_path_spoke(registry: &AccountInfo, fast_path: &UsdcFastPath) -> Result<SpokeEntry> {
+    let data = registry.try_borrow_data()?;
+    let offset = 8 + 1 + fast_path.spoke_index as usize * SPOKE_ENTRY_LEN;
+    require!(
+        (fast_path.spoke_index as usize) < MAX_SPOKES && fast_path.spoke_index < data[8],
+        ErrorCode::FastPathStale
+    );
+    let spoke = SpokeEntry::deserialize(&mut &data[offset..offset + SPOKE_ENTRY_LEN])?;
+    require!(
+        spoke.spoke_id == fast_path.spoke_id,
+        ErrorCode::FastPathStale
+    );
+    Ok(spoke)
+}
+
+/// Body of forward_usdc_fast.
+fn forward_escrow_fast(
+    ctx: Context<ForwardUsdcFast>,
+    is_protocol_fee: bool,
+    is_relayer_fee: bool,
+) -> Result<()> {
+    let cfg = &ctx.accounts.config;
+    let fast_path = &ctx.accounts.fast_path;
+    require!(!cfg.killed, ErrorCode::GlobalKilled);
+    require!(
+        !cfg.feature_enabled(FEATURE_FEE_ESCROW),
+        ErrorCode::FeesHeld
+    );
+    require!(
+        cfg.accept_any_token || cfg.allowed_token_mint == fast_path.mint,
+        ErrorCode::MintNotAllowed
+    );
+    require_mint_unpaused(&ctx.accounts.mint_pause, ctx.program_id)?;
+    let spoke_relayers = read_spoke_relayers(&ctx.accounts.spoke_relayers, ctx.program_id)?;
+    require!(
+        relayer_allowed(&ctx.accounts.relayer.key(), cfg, spoke_relayers.as_ref()),
+        ErrorCode::Unauthorized
+    );
+    let registry_ai = ctx.accounts.registry.to_account_info();
+    let mut spoke = fast_path_spoke(&registry_ai, fast_path)?;
+    require!(spoke.enabled && !spoke.paused, ErrorCode::AdapterNotAllowed);
+    require!(
+        spoke.adapter_program_data == Pubkey::default() && spoke.direct_payout(cfg),
+        ErrorCode::FastPathStale
+    );
+    let deprecation_warning = spoke_deprecation_active(&spoke, Clock::get()?.slot)?;
+    validate_payload_schema(&spoke.payload_schema, &[])?;
+    require!(
+        adapter_target_ok(&spoke, &ctx.accounts.adapter_target_token_account.owner),
+        ErrorCode::InvalidAdapterTarget
+    );
+    require!(
+        cfg.protocol_fee_bps <= FEE_CAP_BPS,
+        ErrorCode::ProtocolFeeTooHigh
+    );
+    require!(
+        cfg.relayer_fee_bps <= RELAYER_FEE_CAP_BPS,
+        ErrorCode::RelayerFeeTooHigh
+    );
+
+    // Only a whole, never-dispatched escrow on this spoke and mint
+    let escrow = &ctx.accounts.escrow;
+    require!(!escrow.cancelled, ErrorCode::MessageCancelled);
+    require!(
+        escrow.mint == fast_path.mint
+            && escrow.spoke_id == fast_path.spoke_id
+            && escrow.forwarded == 0
+            && escrow.held_fees() == 0,
+        ErrorCode::EscrowMismatch
+    );
+    let amount = escrow.amount;
+    let cosign = load_relayer_cosign(
+        &ctx.accounts.relayer_cosign,
+        &fast_path.mint,
+        ctx.program_id,
+    )?;
+    if let Some((weight, cosigners)) = check_relayer_cosign(
+        cosign.as_ref(),
+        amount,
+        &ctx.accounts.relayer.key(),
+        ctx.remaining_accounts,
+    )? {
+        emit!(ForwardCosigned {
+            user: escrow.user,
+            nonce: escrow.nonce,
+            spoke_id: fast_path.spoke_id,
+            amount,
+            weight,
+            cosigners,
+        });
+    }
+    let fee_policy = load_fee_policy(
+        &ctx.accounts.fee_policy,
+        &fast_path.mint,
+        fee_policy_chain_id(escrow.dst_domain),
+        ctx.program_id,
+    )?
+    .unwrap_or_else(|| cfg.flat_fee_policy());
+    let quoted =
+        fees::evaluate_fee_policy(&fee_policy.waive(!is_protocol_fee, !is_relayer_fee), amount)
+            .map_err(ErrorCode::from)?;
+    let (proto_fee, relayer_fee) = (quoted.protocol_fee, quoted.relayer_fee);
+    let total_fees = proto_fee + relayer_fee;
+    let net_amount = ctx
+        .accounts
+        .escrow_vault
+        .amount
+        .checked_sub(total_fees)
+        .ok_or(ErrorCode::MathOverflow)?;
+    let payout_owner = if spoke_relayers.is_some() {
+        ctx.accounts.relayer.key()
+    } else {
+        cfg.relayer_pubkey
+    };
+    if relayer_fee > 0 {
+        check_direct_payout_account(
+            &ctx.accounts.relayer_token_account,
+            &payout_owner,
+            &fast_path.mint,
+        )?;
+    }
+
+    // Effects
+    let escrow = &mut ctx.accounts.escrow;
+    escrow.forwarded = amount;
+    let relayer_tip = std::mem::take(&mut escrow.relayer_tip);
+    let (escrow_user, escrow_nonce, escrow_bump) = (escrow.user, escrow.nonce, escrow.bump);
+    let dst_domain = escrow.dst_domain;
+    write_router_stats(
+        &ctx.accounts.router_stats,
+        ctx.program_id,
+        &fast_path.mint,
+        amount,
+        STATS_KIND_FORWARDED,
+    )?;
+    let was_imbalanced = spoke.imbalanced();
+    spoke.dispatched_amount = spoke
+        .dispatched_amount
+        .checked_add(net_amount)
+        .ok_or(ErrorCode::MathOverflow)?;
+    let imbalance = (!was_imbalanced && spoke.imbalanced()).then(|| spoke.imbalance_event());
+    {
+        let offset = 8 + 1 + fast_path.spoke_index as usize * SPOKE_ENTRY_LEN;
+        let mut data = registry_ai.try_borrow_mut_data()?;
+        spoke.serialize(&mut &mut data[offset..offset + SPOKE_ENTRY_LEN])?;
+    }
+
+    // Interactions
+    let nonce_le = escrow_nonce.to_le_bytes();
+    let escrow_seeds: &[&[u8]] = &[
+        b"forward_escrow",
+        escrow_user.as_ref(),
+        &nonce_le,
+        &[escrow_bump],
+    ];
+    let escrow_ai = ctx.accounts.escrow.to_account_info();
+    for (to, fee) in [
+        (ctx.accounts.hub_protocol_vault.to_account_info(), proto_fee),
+        (
+            ctx.accounts.relayer_token_account.to_account_info(),
+            relayer_fee,
+        ),
+    ] {
+        if fee > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    token_cpi_program(&ctx.accounts.token_program)?,
+                    token::Transfer {
+                        from: ctx.accounts.escrow_vault.to_account_info(),
+                        to,
+                        authority: escrow_ai.clone(),
+                    },
+                    &[escrow_seeds],
+                ),
+                fee,
+            )?;
+        }
+    }
+    drain_forward_escrow_vault(
+        &ctx.accounts.token_program,
+        &ctx.accounts.escrow_vault,
+        &escrow_ai,
+        &escrow_ai,
+        Some((
+            &ctx.accounts.adapter_target_token_account.to_account_info(),
+            net_amount,
+        )),
+        escrow_seeds,
+    )?;
+
+    // Tip and escrow rent after the token CPIs, as on the generic path
+    let relayer_ai = ctx.accounts.relayer.to_account_info();
+    if relayer_tip > 0 {
+        **escrow_ai.try_borrow_mut_lamports()? -= relayer_tip;
+        **relayer_ai.try_borrow_mut_lamports()? += relayer_tip;
+    }
+    let rent_ledger = ctx.accounts.rent_ledger.to_account_info();
+    let user = ctx.accounts.user.to_account_info();
+    let rent = escrow_ai
+        .lamports()
+        .saturating_sub(ctx.accounts.escrow.owed_lamports());
+    refund_escrow_rent(
+        &ctx.accounts.config,
+        &escrow_ai,
+        &user,
+        &relayer_ai,
+        &rent_ledger,
+        rent,
+        ctx.program_id,
+    )?;
+    ctx.accounts.escrow.close(user)?;
+    write_rent(
+        &rent_ledger,
+        ctx.program_id,
+        RENT_KIND_ESCROW,
+        FORWARD_ESCROW_SPACE,
+        false,
+    )?;
+    write_rent(
+        &rent_ledger,
+        ctx.program_id,
+        RENT_KIND_VAULT,
+        TokenAccount::LEN,
+        false,
+    )?;
+
+    // Events last
+    let relayer = ctx.accounts.relayer.key();
+    if relayer_tip > 0 {
+        emit!(RelayerTipClaimed {
+            user: escrow_user,
+            nonce: escrow_nonce,
+            relayer,
+            relayer_tip,
+        });
+    }
+    if deprecation_warning {
+        emit!(SpokeDeprecationWarning {
+            spoke_id: spoke.spoke_id,
+            successor_spoke_id: spoke.successor_spoke_id,
+            sunset_slot: spoke.sunset_slot,
+        });
+    }
+    if let Some(imbalance) = imbalance {
+        emit!(imbalance);
+    }
+    emit!(Forwarded {
+        user: escrow_user,
+        relayer,
+        spoke_id: spoke.spoke_id,
+        adapter_program: spoke.adapter_program,
+        amount,
+        protocol_fee: proto_fee,
+        relayer_fee,
+        net_amount,
+        dst_domain,
+        message_account: Pubkey::default(),
+        relayer_fee_mode: RELAYER_FEE_MODE_TOKEN,
+        relayer_fee_lamports: 0,
+        config_version: ctx.accounts.config.config_version,
+    });
+    Ok(())
+}
+
+/// Escrow accounts of one batch entry and what it dispatches.
+struct BatchLeg<'info> {
+    escrow: Account<'info, ForwardEscrow>,
+    user: AccountInfo<'info>,
+    proto_fee: u64,
+    relayer_fee: u64,
+    net_amount: u64,
+    sla_breach: Option<SlaBreached>,
+}
+
+/// Body of forward_batch_via_spoke.
+fn forward_batch_from_escrows<'info>(
+    ctx: Context<'_, '_, '_, 'info, ForwardBatchViaSpoke<'info>>,
+    spoke_id: u32,
+    entries: &[BatchEntry],
+    is_protocol_fee: bool,
+    is_relayer_fee: bool,
+    adapter_payload: &[u8],
+) -> Result<()> {
+    let cfg = &ctx.accounts.config;
+    require!(!cfg.killed, ErrorCode::GlobalKilled);
+    require!(
+        !entries.is_empty() && entries.len() <= MAX_BATCH_ENTRIES,
+        ErrorCode::InvalidBatch
+    );
+    let (leg_accounts, adapter_accounts) = ctx
+        .remaining_accounts
+        .split_at_checked(entries.len() * BATCH_ENTRY_ACCOUNTS)
+        .ok_or_else(|| error!(ErrorCode::InvalidBatch))?;
+    // Held fees settle per escrow through confirm_escrow_dispatch, which a batch cannot do
+    require!(
+        !cfg.feature_enabled(FEATURE_FEE_ESCROW),
+        ErrorCode::FeesHeld
+    );
+    let mint_key = ctx.accounts.mint.key();
+    require_mint_active(&ctx.accounts.mint_pause, &mint_key, ctx.program_id)?;
+    require_mint_allowed(&ctx.accounts.mint_allowlist, cfg, &mint_key, ctx.program_id)?;
+    require_vaults_initialized(
+        &ctx.accounts.vault_registry,
+        &ctx.accounts.hub_protocol_vault,
+        &ctx.accounts.hub_relayer_vault,
+        &mint_key,
+        ctx.program_id,
+    )?;
+    let hub_protocol_vault = Account::<TokenAccount>::try_from(&ctx.accounts.hub_protocol_vault)?;
+    let hub_relayer_vault = Account::<TokenAccount>::try_from(&ctx.accounts.hub_relayer_vault)?;
+    let spoke_relayers = load_spoke_relayers(
+        &ctx.accounts.spoke_relayers.to_account_info(),
+        spoke_id,
+        ctx.program_id,
+    )?;
+    require!(
+        relayer_allowed(&ctx.accounts.relayer.key(), cfg, spoke_relayers.as_ref()),
+        ErrorCode::Unauthorized
+    );
+
+    let registry = &ctx.accounts.registry;
+    let i = registry.spokes[..registry.spokes_len as usize]
+        .iter()
+        .position(|s| s.spoke_id == spoke_id)
+        .ok_or_else(|| error!(ErrorCode::AdapterNotAllowed))?;
+    let spoke = &registry.spokes[i];
+    require!(spoke.enabled && !spoke.paused, ErrorCode::AdapterNotAllowed);
+    require_keys_eq!(
+        ctx.accounts.adapter_program.key(),
+        spoke.adapter_program,
+        ErrorCode::AdapterNotAllowed
+    );
+    if spoke.adapter_program_data != Pubkey::default() {
+        let pd_ai = ctx.accounts.adapter_program_data.to_account_info();
+        require!(
+            pd_ai.owner == &anchor_lang::solana_program::bpf_loader_upgradeable::ID,
+            ErrorCode::AdapterCodeChanged
+        );
+        let program_data = ProgramData::try_deserialize(&mut &pd_ai.try_borrow_data()?[..])
+            .map_err(|_| error!(ErrorCode::AdapterCodeChanged))?;
+        check_adapter_pin(spoke, &pd_ai.key(), &program_data)?;
+    }
+    let deprecation_warning = spoke_deprecation_active(spoke, Clock::get()?.slot)?;
+    validate_payload_len(adapter_payload.len())?;
+    validate_payload_schema(&spoke.payload_schema, adapter_payload)?;
+    require!(
+        cfg.protocol_fee_bps <= FEE_CAP_BPS,
+        ErrorCode::ProtocolFeeTooHigh
+    );
+    require!(
+        cfg.relayer_fee_bps <= RELAYER_FEE_CAP_BPS,
+        ErrorCode::RelayerFeeTooHigh
+    );
+    require!(
+        adapter_target_ok(spoke, &ctx.accounts.adapter_target_token_account.owner),
+        ErrorCode::InvalidAdapterTarget
+    );
+    verify_hub_vault(
+        &hub_protocol_vault,
+        b"hub_protocol_vault",
+        &mint_key,
+        ctx.program_id,
+    )?;
+    verify_hub_vault(
+        &hub_relayer_vault,
+        b"hub_relayer_vault",
+        &mint_key,
+        ctx.program_id,
+    )?;
+    check_vault_pattern(
+        &ctx.accounts.vault_registry,
+        &mint_key,
+        &hub_protocol_vault.key(),
+        cfg.strict_vaults,
+        ctx.program_id,
+    )?;
+    let direct_payout = spoke.direct_payout(cfg);
+    let payout_owner = if spoke_relayers.is_some() {
+        ctx.accounts.relayer.key()
+    } else {
+        cfg.relayer_pubkey
+    };
+    let adapter_program = spoke.adapter_program;
+    let (successor_spoke_id, sunset_slot) = (spoke.successor_spoke_id, spoke.sunset_slot);
+    let (sla_target_slots, max_adapter_cu) = (spoke.sla_target_slots, spoke.max_adapter_cu);
+    let flat_fee_policy = cfg.flat_fee_policy();
+    let cosign = load_relayer_cosign(&ctx.accounts.relayer_cosign, &mint_key, ctx.program_id)?;
+
+    // The batch vault is a router-owned staging account, created on the mint's first batch
+    let batch_vault_ai = ctx.accounts.hub_batch_vault.to_account_info();
+    if batch_vault_ai.owner != &token::ID {
+        create_hub_vault(
+            &ctx.accounts.relayer.to_account_info(),
+            &batch_vault_ai,
+            &ctx.accounts.mint.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.token_program.to_account_info(),
+            b"hub_batch_vault",
+            ctx.program_id,
+        )?;
+        record_rent(
+            &ctx.accounts.rent_ledger.to_account_info(),
+            ctx.program_id,
+            RENT_KIND_VAULT,
+            TokenAccount::LEN,
+            true,
+        )?;
+    }
+    let hub_batch_vault = Account::<TokenAccount>::try_from(&batch_vault_ai)?;
+    let batch_bump = verify_hub_vault(
+        &hub_batch_vault,
+        b"hub_batch_vault",
+        &mint_key,
+        ctx.program_id,
+    )?;
+
+    // Checks and per-entry effects
+    let slot = time::now(ctx.remaining_accounts, ctx.program_id)?.slot;
+    let mut legs: Vec<BatchLeg> = Vec::with_capacity(entries.len());
+    let mut dispatches = Vec::with_capacity(entries.len());
+    for (entry, accounts) in entries
+        .iter()
+        .zip(leg_accounts.chunks_exact(BATCH_ENTRY_ACCOUNTS))
+    {
+        let escrow = Account::<ForwardEscrow>::try_from(&accounts[0])?;
+        let (expected, _) = Pubkey::find_program_address(
+            &[
+                b"forward_escrow",
+                entry.user.as_ref(),
+                &entry.nonce.to_le_bytes(),
+            ],
+            ctx.program_id,
+        );
+        require_keys_eq!(escrow.key(), expected, ErrorCode::EscrowMismatch);
+        require!(
+            legs.iter().all(|leg| leg.escrow.key() != expected),
+            ErrorCode::InvalidBatch
+        );
+        let escrow_vault = Account::<TokenAccount>::try_from(&accounts[1])?;
+        require!(
+            escrow_vault.owner == expected && escrow_vault.mint == mint_key,
+            ErrorCode::EscrowMismatch
+        );
+        require_keys_eq!(accounts[2].key(), escrow.user, ErrorCode::EscrowMismatch);
+        // Only full, never-dispatched escrows without held fees can join a batch
+        require!(!escrow.cancelled, ErrorCode::MessageCancelled);
+        require!(
+            escrow.user == entry.user
+                && escrow.nonce == entry.nonce
+                && escrow.mint == mint_key
+                && escrow.spoke_id == spoke_id
+                && escrow.dst_domain == entry.dst_domain
+                && escrow.mint_recipient == entry.mint_recipient
+                && escrow.forwarded == 0
+                && escrow.held_fees() == 0
+                && escrow.amount == entry.amount,
+            ErrorCode::EscrowMismatch
+        );
+        if let Some((weight, cosigners)) = check_relayer_cosign(
+            cosign.as_ref(),
+            entry.amount,
+            &ctx.accounts.relayer.key(),
+            ctx.remaining_accounts,
+        )? {
+            emit!(ForwardCosigned {
+                user: entry.user,
+                nonce: entry.nonce,
+                spoke_id,
+                amount: entry.amount,
+                weight,
+                cosigners,
+            });
+        }
+        // Each entry pays its own route's fee policy
+        let fee_policy = load_fee_policy(
+            &accounts[3],
+            &mint_key,
+            fee_policy_chain_id(entry.dst_domain),
+            ctx.program_id,
+        )?
+        .unwrap_or(flat_fee_policy);
+        let quoted = fees::evaluate_fee_policy(
+            &fee_policy.waive(!is_protocol_fee, !is_relayer_fee),
+            entry.amount,
+        )
+        .map_err(ErrorCode::from)?;
+        let (proto_fee, relayer_fee) = (quoted.protocol_fee, quoted.relayer_fee);
+        let total_fees = proto_fee + relayer_fee;
+        // As on a completing single forward, stray tokens in the vault travel with the net
+        let net_amount = escrow_vault
+            .amount
+            .checked_sub(total_fees)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        let latency_slots = slot.saturating_sub(escrow.created_at_slot);
+        let sla_breach = record_relayer_activity(
+            &ctx.accounts.relayer.to_account_info(),
+            &ctx.accounts.relayer_activity,
+            &ctx.accounts.system_program.to_account_info(),
+            latency_slots,
+            sla_target_slots,
+            slot,
+            ctx.program_id,
+        )?
+        .map(|activity| SlaBreached {
+            relayer: activity.relayer,
+            spoke_id,
+            user: escrow.user,
+            nonce: escrow.nonce,
+            latency_slots,
+            target_slots: sla_target_slots,
+            average_latency_slots: activity.average_latency_slots(),
+            sla_breaches: activity.sla_breaches,
+        });
+
+        // Sweep the whole escrow vault into the batch vault and close it into the escrow,
+        // whose rent is paid out after the adapter CPI
+        let nonce_le = escrow.nonce.to_le_bytes();
+        let escrow_seeds: &[&[u8]] = &[
+            b"forward_escrow",
+            escrow.user.as_ref(),
+            &nonce_le,
+            &[escrow.bump],
+        ];
+        drain_forward_escrow_vault(
+            &ctx.accounts.token_program,
+            &escrow_vault,
+            &escrow.to_account_info(),
+            &escrow.to_account_info(),
+            Some((&batch_vault_ai, escrow_vault.amount)),
+            escrow_seeds,
+        )?;
+        dispatches.push(BatchDispatch {
+            user: escrow.user,
+            nonce: escrow.nonce,
+            net_amount,
+            dst_domain: escrow.dst_domain,
+            mint_recipient: escrow.mint_recipient,
+        });
+        legs.push(BatchLeg {
+            escrow,
+            user: accounts[2].clone(),
+            proto_fee,
+            relayer_fee,
+            net_amount,
+            sla_breach,
+        });
+    }
+    let sum = |f: fn(&BatchLeg) -> u64| {
+        legs.iter()
+            .try_fold(0u64, |acc, leg| acc.checked_add(f(leg)))
+            .ok_or(ErrorCode::MathOverflow)
+    };
+    let total_amount = sum(|leg| leg.escrow.amount)?;
+    let total_proto_fee = sum(|leg| leg.proto_fee)?;
+    let total_relayer_fee = sum(|leg| leg.relayer_fee)?;
+    let total_net = sum(|leg| leg.net_amount)?;
+    if total_relayer_fee > 0 && direct_payout {
+        check_direct_payout_account(
+            &ctx.accounts.relayer_token_account,
+            &payout_owner,
+            &mint_key,
+        )?;
+    }
+
+    // Aggregate effects
+    if total_relayer_fee > 0 && !direct_payout {
+        accrue_relayer_fee(
+            &ctx.accounts.relayer.to_account_info(),
+            &ctx.accounts.relayer.key(),
+            &ctx.accounts.relayer_vesting_config,
+            &ctx.accounts.relayer_vesting,
+            &ctx.accounts.system_program.to_account_info(),
+            &mint_key,
+            total_relayer_fee,
+            slot,
+            ctx.program_id,
+        )?;
+    }
+    record_router_stats(
+        &ctx.accounts.router_stats,
+        ctx.program_id,
+        &mint_key,
+        total_amount,
+        STATS_KIND_FORWARDED,
+    )?;
+    let spoke = &mut ctx.accounts.registry.spokes[i];
+    let was_imbalanced = spoke.imbalanced();
+    spoke.dispatched_amount = spoke
+        .dispatched_amount
+        .checked_add(total_net)
+        .ok_or(ErrorCode::MathOverflow)?;
+    let imbalance = (!was_imbalanced && spoke.imbalanced()).then(|| spoke.imbalance_event());
+
+    // Interactions: one transfer per destination out of the batch vault
+    let batch_seeds: &[&[&[u8]]] = &[&[b"hub_batch_vault", mint_key.as_ref(), &[batch_bump]]];
+    let relayer_fee_to = if direct_payout {
+        ctx.accounts.relayer_token_account.to_account_info()
+    } else {
+        hub_relayer_vault.to_account_info()
+    };
+    for (to, amount) in [
+        (hub_protocol_vault.to_account_info(), total_proto_fee),
+        (relayer_fee_to, total_relayer_fee),
+        (
+            ctx.accounts.adapter_target_token_account.to_account_info(),
+            total_net,
+        ),
+    ] {
+        if amount > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    token_cpi_program(&ctx.accounts.token_program)?,
+                    token::Transfer {
+                        from: batch_vault_ai.clone(),
+                        to,
+                        authority: batch_vault_ai.clone(),
+                    },
+                    batch_seeds,
+                ),
+                amount,
+            )?;
+        }
+    }
+
+    // One adapter CPI for the whole batch; the router signs for none of its accounts
+    let ix = anchor_lang::solana_program::instruction::Instruction {
+        program_id: adapter_program,
+        accounts: adapter_accounts
+            .iter()
+            .map(|a| AccountMeta {
+                pubkey: a.key(),
+                is_signer: false,
+                is_writable: a.is_writable,
+            })
+            .collect(),
+        data: adapter_dispatch_batch_data(&dispatches, adapter_payload)?,
+    };
+    let mut infos = adapter_accounts.to_vec();
+    infos.push(ctx.accounts.adapter_program.to_account_info());
+    let target_ai = ctx.accounts.adapter_target_token_account.to_account_info();
+    let target_before = AdapterTargetSnapshot::read(&target_ai);
+    let guard = AdapterCuGuard::enter(spoke_id, adapter_program, max_adapter_cu)?;
+    anchor_lang::solana_program::program::invoke(&ix, &infos)?;
+    guard.exit()?;
+    target_before.assert_after_cpi(
+        &AdapterTargetSnapshot::read(&target_ai),
+        total_net,
+        spoke_id,
+        adapter_program,
+        target_ai.key(),
+    )?;
+
+    // Tips and escrow rent move after every CPI, as on the single path
+    let rent_ledger = ctx.accounts.rent_ledger.to_account_info();
+    let relayer_ai = ctx.accounts.relayer.to_account_info();
+    let mut tips = Vec::with_capacity(legs.len());
+    for leg in &legs {
+        let tip = leg.escrow.relayer_tip;
+        if tip > 0 {
+            **leg.escrow.to_account_info().try_borrow_mut_lamports()? -= tip;
+            **relayer_ai.try_borrow_mut_lamports()? += tip;
+        }
+        tips.push(tip);
+        let escrow_ai = leg.escrow.to_account_info();
+        // The tip field is not cleared on this path, so only the budget is still owed
+        let rent = escrow_ai
+            .lamports()
+            .saturating_sub(leg.escrow.lamport_budget);
+        refund_escrow_rent(
+            &ctx.accounts.config,
+            &escrow_ai,
+            &leg.user,
+            &relayer_ai,
+            &rent_ledger,
+            rent,
+            ctx.program_id,
+        )?;
+        leg.escrow.close(leg.user.clone())?;
+        record_rent(
+            &rent_ledger,
+            ctx.program_id,
+            RENT_KIND_ESCROW,
+            FORWARD_ESCROW_SPACE,
+            false,
+        )?;
+        record_rent(
+            &rent_ledger,
+            ctx.program_id,
+            RENT_KIND_VAULT,
+            TokenAccount::LEN,
+            false,
+        )?;
+    }
+
+    // Events last
+    let relayer = ctx.accounts.relayer.key();
+    if deprecation_warning {
+        emit!(SpokeDeprecationWarning {
+            spoke_id,
+            successor_spoke_id,
+            sunset_slot,
+        });
+    }
+    if let Some(imbalance) = imbalance {
+        emit!(imbalance);
+    }
+    for (leg, tip) in legs.into_iter().zip(tips) {
+        if tip > 0 {
+            emit!(RelayerTipClaimed {
+                user: leg.escrow.user,
+                nonce: leg.escrow.nonce,
+                relayer,
+                relayer_tip: tip,
+            });
+        }
+        if let Some(breach) = leg.sla_breach {
+            emit!(breach);
+        }
+        emit!(Forwarded {
+            user: leg.escrow.user,
+            relayer,
+            spoke_id,
+            adapter_program,
+            amount: leg.escrow.amount,
+            protocol_fee: leg.proto_fee,
+            relayer_fee: leg.relayer_fee,
+            net_amount: leg.net_amount,
+            dst_domain: leg.escrow.dst_domain,
+            message_account: Pubkey::default(),
+            relayer_fee_mode: RELAYER_FEE_MODE_TOKEN,
+            relayer_fee_lamports: 0,
+            config_version: ctx.accounts.config.config_version,
+        });
+    }
+    emit!(BatchForwarded {
+        relayer,
+        spoke_id,
+        adapter_program,
+        mint: mint_key,
+        entries: entries.len() as u8,
+        amount: total_amount,
+        protocol_fee: total_proto_fee,
+        relayer_fee: total_relayer_fee,
+        net_amount: total_net,
+    });
+    Ok(())
+}
+
+/// SPL token balance from raw account data, so reads after a CPI see the new amount without
+/// a reload. Closed or non-token accounts read as 0.
+fn raw_token_amount(ai: &AccountInfo) -> u64 {
+    ai.try_borrow_data()
+        .ok()
+        .and_then(|data| {
+            data.get(64..72)
+                .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+        })
+        .unwrap_or(0)
+}
+
+/// SPL token delegate from raw account data (COption tag at 72, key at 76..108).
+fn raw_token_delegate(ai: &AccountInfo) -> Option<Pubkey> {
+    let data = ai.try_borrow_data().ok()?;
+    match data.get(72..108)? {
+        [1, 0, 0, 0, key @ ..] => Some(Pubkey::new_from_array(key.try_into().unwrap())),
+        _ => None,
+    }
+}
+
+/// Adapter target token account state read just before and just after the adapter CPI.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AdapterTargetSnapshot {
+    pub amount: u64,
+    pub delegate: Option<Pubkey>,
+}
+
+impl AdapterTargetSnapshot {
+    pub fn read(ai: &AccountInfo) -> Self {
+        Self {
+            amount: raw_token_amount(ai),
+            delegate: raw_token_delegate(ai),
+        }
+    }
+
+    /// The adapter may move at most the forwarded `net` out of the target and may not set a
+    /// delegate that was not there before (revoking is fine).
+    pub fn allows(&self, after: &Self, net: u64) -> bool {
+        self.amount.saturating_sub(after.amount) <= net
+            && (after.delegate.is_none() || after.delegate == self.delegate)
+    }
+
+    /// Emit BalanceAssertionFailed and revert when `after` breaks `allows`.
+    fn assert_after_cpi(
+        &self,
+        after: &Self,
+        net: u64,
+        spoke_id: u32,
+        adapter_program: Pubkey,
+        target: Pubkey,
+    ) -> Result<()> {
+        if self.allows(after, net) {
+            return Ok(());
+        }
+        emit!(BalanceAssertionFailed {
+            spoke_id,
+            adapter_program,
+            target,
+            net_amount: net,
+            amount_before: self.amount,
+            amount_after: after.amount,
+            delegate: after.delegate.unwrap_or_default(),
+        });
+        err!(ErrorCode::AdapterBalanceAssertionFailed)
+    }
+}
+
+impl ForwardTokenBalances {
+    fn read(accounts: &ForwardViaSpoke) -> Self {
+        Self {
+            escrow_vault: raw_token_amount(&accounts.escrow_vault.to_account_info()),
+            hub_protocol_vault: raw_token_amount(&accounts.hub_protocol_vault),
+            hub_relayer_vault: raw_token_amount(&accounts.hub_relayer_vault),
+            relayer_token_account: raw_token_amount(
+                &accounts.relayer_token_account.to_account_info(),
+            ),
+            adapter_target: raw_token_amount(
+                &accounts.adapter_target_token_account.to_account_info(),
+            ),
+        }
+    }
+}
+
+/// Shared body of finalize_message_v1 and crank_finalize_message_v1: every check depends only
+/// on the message fields, never on who signs as `relayer`.
+#[allow(clippy::too_many_arguments)]
+fn finalize_message(
+    accounts: &FinalizeMessageV1,
+    remaining: &[AccountInfo],
+    program_id: &Pubkey,
+    message_hash: [u8; 32],
+    src_chain_id: u64,
+    dst_chain_id: u64,
+    forwarded_amount: u64,
+    nonce: u64,
+    payload_hash: [u8; 32],
+    src_adapter: Pubkey,
+    asset_mint: Pubkey,
+    origin_tx_hash: Option<[u8; 32]>,
+    validity: Option<MessageValidity>,
+) -> Result<()> {
+    // Messages from routes with a max age must carry the source leg's validity window;
+    // `payload_hash` is then the inner hash and the envelope is hashed in below
+    let max_age_secs = route_max_age(&accounts.route_validity, src_chain_id, program_id)?;
+    let payload_hash = match validity {
+        Some(v) => {
+            let now = time::now(remaining, program_id)?.unix_timestamp;
+            v.check(u64::try_from(now).unwrap_or_default(), max_age_secs)?;
+            v.payload_hash(payload_hash)
+        }
+        None => {
+            require!(max_age_secs == 0, ErrorCode::MessageValidityRequired);
+            payload_hash
+        }
+    };
+    // Build canonical message hash matching source-leg schema
+    let src_adapter_32 = src_adapter.to_bytes();
+    let recipient_32 = [0u8; 32];
+    let asset_32 = asset_mint.to_bytes();
+    // Normalized routes carry the amount with 18 decimals; forwarded_amount is in the
+    // local mint's units either way
+    let amount_decimals = route_amount_decimals(remaining, src_chain_id, &asset_mint, program_id)?;
+    let amount_be =
+        amount::amount_word(forwarded_amount, amount_decimals).map_err(ErrorCode::from)?;
+    let computed_hash = message_hash_be(
+        message_hash_scheme(accounts.config.message_hash_version()),
+        src_chain_id,
+        src_adapter_32,
+        recipient_32,
+        asset_32,
+        amount_be,
+        payload_hash,
+        nonce,
+        dst_chain_id,
+    );
+
+    // Chain id width guard to avoid truncation when emitting u16
+    require!(
+        zpx_chains::fits_wire(src_chain_id) && zpx_chains::fits_wire(dst_chain_id),
+        ErrorCode::ChainIdOutOfRange
+    );
+
+    // Ensure router is not paused at destination finalize
+    require!(!accounts.config.paused, ErrorCode::Paused);
+    accounts
+        .config
+        .require_family_active(INSTRUCTION_FAMILY_FINALIZE)?;
+    // The source router must be proven to run the fee config the hub expects
+    require_remote_config(remaining, &accounts.config, src_chain_id, program_id)?;
+
+    // Auth gate: make sure the declared source adapter is in the configured allowlist.
+    // This prevents arbitrary callers from forging finalize events for adapters that are
+    // not known/approved by the router config.
+    require!(
+        adapter_allowed(&accounts.config, remaining, &src_adapter, program_id),
+        ErrorCode::AdapterNotAllowed
+    );
+
+    // 1) Hash parity enforcement
+    require!(computed_hash == message_hash, ErrorCode::HashMismatch);
+
+    // 2) Replay PDA enforcement + stateful replay guard
+    let replay_ai = &accounts.replay.to_account_info();
+    let bump = replay_guard::verify_address(replay_ai.key, &message_hash, program_id)
+        .map_err(ErrorCode::from)?;
+    let finalizer = accounts.relayer.key();
+    let origin_tx = origin_tx_hash.unwrap_or_default();
+    if replay_ai.data_len() == 0 {
+        // First use: create the marker already processed, with finalizer + origin tx
+        replay_guard::create_processed(
+            &accounts.relayer.to_account_info(),
+            replay_ai,
+            &accounts.system_program.to_account_info(),
+            &message_hash,
+            bump,
+            program_id,
+            &finalizer,
+            &origin_tx,
+        )?;
+        zpx_log!("replay_created", relayer = accounts.relayer.key);
+        record_rent(
+            &accounts.rent_ledger.to_account_info(),
+            program_id,
+            RENT_KIND_REPLAY,
+            REPLAY_SPACE,
+            true,
+        )?;
+    } else {
+        // Pre-created marker (possibly legacy 9 bytes): verify owner, layout, rent and
+        // processed flag, then mark it
+        require_rent_exempt(replay_ai, &Rent::get()?)?;
+        replay_guard::check_and_mark(replay_ai, program_id, &finalizer, &origin_tx)
+            .map_err(ErrorCode::from)?;
+        zpx_log!("replay_marked");
+    }
+
+    // Emit telemetry event (no fee movement in v1)
+    emit!(FeeAppliedDest {
+        message_hash,
+        src_chain_id: src_chain_id as u16,
+        dst_chain_id: dst_chain_id as u16,
+        router: crate::ID,
+        asset: asset_mint,
+        amount: forwarded_amount,
+        protocol_bps: 0,
+        lp_bps: accounts.config.lp_fee_bps,
+        collector: accounts.config.fee_recipient,
+        applied_at: Clock::get()?.unix_timestamp as u64,
+    });
+    // FeeAppliedDest is frozen; explorers join the origin tx through MessageFinalized
+    emit!(MessageFinalized {
+        message_hash,
+        src_chain_id,
+        dst_chain_id,
+        asset: asset_mint,
+        amount: forwarded_amount,
+        relayer: accounts.relayer.key(),
+        origin_tx_hash,
+    });
+
+    Ok(())
+}
+
+/// max_age_secs of the RouteValidity PDA for `chain_id`; 0 while it is uninitialized.
+fn route_max_age(ai: &AccountInfo, chain_id: u64, program_id: &Pubkey) -> Result<u64> {
+    let (expected, _) =
+        Pubkey::find_program_address(&[b"route_validity", &chain_id.to_le_bytes()], program_id);
+    require_keys_eq!(ai.key(), expected, ErrorCode::InvalidRouteValidity);
+    if ai.owner != program_id {
+        return Ok(0);
+    }
+    Ok(RouteValidity::try_deserialize(&mut &ai.try_borrow_data()?[..])?.max_age_secs)
+}
+
+/// Fee policy of the (mint, dst_chain_id) route; None while its FeePolicy PDA is uninitialized.
+fn load_fee_policy(
+    ai: &AccountInfo,
+    mint: &Pubkey,
+    dst_chain_id: u64,
+    program_id: &Pubkey,
+) -> Result<Option<fees::FeePolicy>> {
+    let (expected, _) = Pubkey::find_program_address(
+        &[b"fee_policy", mint.as_ref(), &dst_chain_id.to_le_bytes()],
+        program_id,
+    );
+    require_keys_eq!(ai.key(), expected, ErrorCode::InvalidFeePolicyAccount);
+    if ai.owner != program_id {
+        return Ok(None);
+    }
+    Ok(Some(
+        FeePolicy::try_deserialize(&mut &ai.try_borrow_data()?[..])?.to_core(),
+    ))
+}
+
+/// The referring partner of a bridge; None for any account the program does not own
+/// (callers without a referral pass e.g. the system program).
+fn load_partner(ai: &AccountInfo, program_id: &Pubkey) -> Result<Option<Partner>> {
+    if ai.owner != program_id {
+        return Ok(None);
+    }
+    let partner = Partner::try_deserialize(&mut &ai.try_borrow_data()?[..])?;
+    let (expected, _) =
+        Pubkey::find_program_address(&[b"partner", &partner.partner_id.to_le_bytes()], program_id);
+    require_keys_eq!(ai.key(), expected, ErrorCode::InvalidPartnerAccount);
+    Ok(Some(partner))
+}
+
+/// Credit `rebate` to the partner's PartnerRebate entry for `mint`, creating the entry and
+/// the mint's rebate vault on first use. Returns the partner's new accrued total.
+#[allow(clippy::too_many_arguments)]
+fn accrue_partner_rebate<'info>(
+    partner: &Partner,
+    rebate: u64,
+    entry: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    program_id: &Pubkey,
+) -> Result<u64> {
+    let partner_id = partner.partner_id.to_le_bytes();
+    let (expected, bump) = Pubkey::find_program_address(
+        &[b"partner_rebate", &partner_id, mint.key.as_ref()],
+        program_id,
+    );
+    require_keys_eq!(entry.key(), expected, ErrorCode::InvalidPartnerAccount);
+    let mut state = if entry.owner != program_id {
+        create_pda_account(
+            payer,
+            entry,
+            system_program,
+            PARTNER_REBATE_SPACE,
+            &[b"partner_rebate", &partner_id, mint.key.as_ref(), &[bump]],
+            program_id,
+        )?;
+        PartnerRebate {
+            partner_id: partner.partner_id,
+            mint: mint.key(),
+            bump,
+            ..PartnerRebate::default()
+        }
+    } else {
+        PartnerRebate::try_deserialize(&mut &entry.try_borrow_data()?[..])?
+    };
+    if vault.owner == &token::ID {
+        let (expected_vault, _) =
+            Pubkey::find_program_address(&[b"partner_rebate_vault", mint.key.as_ref()], program_id);
+        require_keys_eq!(
+            vault.key(),
+            expected_vault,
+            ErrorCode::InvalidPartnerAccount
+        );
+    } else {
+        create_hub_vault(
+            payer,
+            vault,
+            mint,
+            system_program,
+            token_program,
+            b"partner_rebate_vault",
+            program_id,
+        )?;
+    }
+    state.accrued = state
+        .accrued
+        .checked_add(rebate)
+        .ok_or(ErrorCode::MathOverflow)?;
+    state.try_serialize(&mut &mut entry.try_borrow_mut_data()?[..])?;
+    Ok(state.accrued)
+}
+
+/// Co-signing policy of `mint`; None while its RelayerCosign PDA is uninitialized.
+fn load_relayer_cosign(
+    ai: &AccountInfo,
+    mint: &Pubkey,
+    program_id: &Pubkey,
+) -> Result<Option<RelayerCosign>> {
+    let (expected, _) =
+        Pubkey::find_program_address(&[b"relayer_cosign", mint.as_ref()], program_id);
+    require_keys_eq!(ai.key(), expected, ErrorCode::InvalidRelayerCosignAccount);
+    if ai.owner != program_id {
+        return Ok(None);
+    }
+    Ok(Some(RelayerCosign::try_deserialize(
+        &mut &ai.try_borrow_data()?[..],
+    )?))
+}
+
+fn load_forward_state(
+    ai: &AccountInfo,
+    escrow: &Pubkey,
+    program_id: &Pubkey,
+) -> Result<(Option<ForwardState>, u8)> {
+    let (expected, bump) =
+        Pubkey::find_program_address(&[b"forward_state", escrow.as_ref()], program_id);
+    require_keys_eq!(ai.key(), expected, ErrorCode::InvalidForwardStateAccount);
+    if ai.owner != program_id {
+        return Ok((None, bump));
+    }
+    let state = ForwardState::try_deserialize(&mut &ai.try_borrow_data()?[..])?;
+    require_keys_eq!(state.escrow, *escrow, ErrorCode::InvalidForwardStateAccount);
+    Ok((Some(state), bump))
+}
+
+/// Close a program-owned PDA held as an UncheckedAccount (a finished ForwardState, a removed
+/// AdapterEntry) into `to`.
+fn close_program_account<'info>(ai: &AccountInfo<'info>, to: &AccountInfo<'info>) -> Result<()> {
+    let lamports = ai.lamports();
+    **ai.try_borrow_mut_lamports()? -= lamports;
+    **to.try_borrow_mut_lamports()? += lamports;
+    ai.assign(&anchor_lang::solana_program::system_program::ID);
+    ai.realloc(0, false)?;
+    Ok(())
+}
+
+/// Enforce the mint's co-signing policy on a forward of an escrow holding `escrowed`. A
+/// co-signer counts when it is the submitting `relayer` or signs among `remaining`. Returns
+/// the co-signers and their weight when the policy applied.
+fn check_relayer_cosign(
+    cosign: Option<&RelayerCosign>,
+    escrowed: u64,
+    relayer: &Pubkey,
+    remaining: &[AccountInfo],
+) -> Result<Option<(u64, Vec<Pubkey>)>> {
+    let Some(cosign) = cosign.filter(|c| c.threshold > 0 && escrowed >= c.threshold) else {
+        return Ok(None);
+    };
+    let (weight, cosigners) = cosign.signed_weight(|key| {
+        key == relayer || remaining.iter().any(|ai| ai.is_signer && ai.key == key)
+    });
+    require!(
+        weight >= cosign.required_weight,
+        ErrorCode::CosignWeightNotMet
+    );
+    Ok(Some((weight, cosigners)))
+}
+
+/// Route key of an escrow forward to CCTP `dst_domain`: its EVM chain id, or 0 for domains
+/// without one (Solana, Noble).
+pub fn fee_policy_chain_id(dst_domain: u32) -> u64 {
+    zpx_chains::chain_for_cctp_domain(zpx_chains::CctpDomain(dst_domain)).map_or(0, |c| c.0)
+}
+
+/// Validity window for a new message to `dst_chain_id`, when the route's RouteValidity PDA is
+/// among `remaining` with a non-zero max age.
+fn message_validity_for(
+    remaining: &[AccountInfo],
+    dst_chain_id: u64,
+    program_id: &Pubkey,
+) -> Result<Option<MessageValidity>> {
+    let (expected, _) = Pubkey::find_program_address(
+        &[b"route_validity", &dst_chain_id.to_le_bytes()],
+        program_id,
+    );
+    let Some(ai) = remaining.iter().find(|ai| ai.key() == expected) else {
+        return Ok(None);
+    };
+    let max_age_secs = route_max_age(ai, dst_chain_id, program_id)?;
+    if max_age_secs == 0 {
+        return Ok(None);
+    }
+    let sent_at =
+        u64::try_from(time::now(remaining, program_id)?.unix_timestamp).unwrap_or_default();
+    Ok(Some(MessageValidity {
+        sent_at,
+        expires_at: sent_at.saturating_add(max_age_secs),
+    }))
+}
+
+/// Decimals the amount of `mint` is normalized from on the route to/from `chain_id`, when
+/// the route's RouteDecimals PDA is among `remaining` with `normalize` set.
+fn route_amount_decimals(
+    remaining: &[AccountInfo],
+    chain_id: u64,
+    mint: &Pubkey,
+    program_id: &Pubkey,
+) -> Result<Option<u8>> {
+    let (expected, _) = Pubkey::find_program_address(
+        &[b"route_decimals", &chain_id.to_le_bytes(), mint.as_ref()],
+        program_id,
+    );
+    let Some(ai) = remaining
+        .iter()
+        .find(|ai| ai.key() == expected && ai.owner == program_id)
+    else {
+        return Ok(None);
+    };
+    let route = RouteDecimals::try_deserialize(&mut &ai.try_borrow_data()?[..])?;
+    Ok(route.normalize.then_some(route.decimals))
+}
+
+fn load_guardian_set(ai: &AccountInfo) -> Result<ccq::GuardianSet> {
+    require_keys_eq!(
+        *ai.owner,
+        ccq::WORMHOLE_CORE_PROGRAM_ID,
+        ErrorCode::InvalidGuardianSet
+    );
+    let set = ccq::GuardianSet::deserialize(&mut &ai.try_borrow_data()?[..])
+        .map_err(|_| error!(ErrorCode::InvalidGuardianSet))?;
+    let (expected, _) = Pubkey::find_program_address(
+        &[b"GuardianSet", &set.index.to_be_bytes()],
+        &ccq::WORMHOLE_CORE_PROGRAM_ID,
+    );
+    require_keys_eq!(ai.key(), expected, ErrorCode::InvalidGuardianSet);
+    Ok(set)
+}
+
+/// Gate inbound messages from `chain_id` on the source router's proven config. The
+/// RemoteConfig PDA is read from `remaining`; once registered it is always enforced, and
+/// FEATURE_REMOTE_CONFIG makes it mandatory for every source chain.
+fn require_remote_config(
+    remaining: &[AccountInfo],
+    cfg: &Config,
+    chain_id: u64,
+    program_id: &Pubkey,
+) -> Result<()> {
+    let (expected, _) =
+        Pubkey::find_program_address(&[REMOTE_CONFIG_SEED, &chain_id.to_le_bytes()], program_id);
+    let Some(ai) = remaining
+        .iter()
+        .find(|ai| ai.key() == expected && ai.owner == program_id)
+    else {
+        require!(
+            !cfg.feature_enabled(FEATURE_REMOTE_CONFIG),
+            ErrorCode::RemoteConfigMissing
+        );
+        return Ok(());
+    };
+    let remote = RemoteConfig::try_deserialize(&mut &ai.try_borrow_data()?[..])?;
+    let now = time::now(remaining, program_id)?.unix_timestamp;
+    remote.check(u64::try_from(now).unwrap_or_default())
+}
+
+/// Update RouterStats in a hot path. The account must be the canonical [b"router_stats"]
+/// PDA; counting is skipped until initialize_router_stats has run.
+fn record_router_stats(
+    ai: &AccountInfo,
+    program_id: &Pubkey,
+    mint: &Pubkey,
+    amount: u64,
+    kind: u8,
+) -> Result<()> {
+    let (expected, _) = Pubkey::find_program_address(&[b"router_stats"], program_id);
+    require_keys_eq!(ai.key(), expected, ErrorCode::InvalidStatsAccount);
+    write_router_stats(ai, program_id, mint, amount, kind)
+}
+
+/// record_router_stats for an `ai` the caller already matched against the canonical PDA.
+fn write_router_stats(
+    ai: &AccountInfo,
+    program_id: &Pubkey,
+    mint: &Pubkey,
+    amount: u64,
+    kind: u8,
+) -> Result<()> {
+    if ai.owner != program_id {
+        return Ok(());
+    }
+    let mut stats = RouterStats::try_deserialize(&mut &ai.try_borrow_data()?[..])?;
+    stats.record(mint, amount, kind, Clock::get()?.slot);
+    stats.try_serialize(&mut &mut ai.try_borrow_mut_data()?[..])?;
+    Ok(())
+}
+
+/// Track rent for an account of `space` bytes created (`opened`) or closed by the program.
+/// The account must be the canonical [b"rent_ledger"] PDA; skipped until
+/// initialize_rent_ledger has run.
+fn record_rent(
+    ai: &AccountInfo,
+    program_id: &Pubkey,
+    kind: u8,
+    space: usize,
+    opened: bool,
+) -> Result<()> {
+    let (expected, _) = Pubkey::find_program_address(&[b"rent_ledger"], program_id);
+    require_keys_eq!(ai.key(), expected, ErrorCode::InvalidRentLedger);
+    write_rent(ai, program_id, kind, space, opened)
+}
+
+/// record_rent for an `ai` the caller already matched against the canonical PDA.
+fn write_rent(
+    ai: &AccountInfo,
+    program_id: &Pubkey,
+    kind: u8,
+    space: usize,
+    opened: bool,
+) -> Result<()> {
+    if ai.owner != program_id {
+        return Ok(());
+    }
+    let mut ledger = RentLedger::try_deserialize(&mut &ai.try_borrow_data()?[..])?;
+    ledger.record(
+        kind,
+        Rent::get()?.minimum_balance(space),
+        opened,
+        Clock::get()?.slot,
+    );
+    ledger.try_serialize(&mut &mut ai.try_borrow_mut_data()?[..])?;
+    Ok(())
+}
+
+/// Load the spoke's relayer allowlist if one exists. The account must be the canonical
+/// [b"spoke_relayers", spoke_id] PDA; an uninitialized PDA or an empty list yields None.
+fn load_spoke_relayers(
+    ai: &AccountInfo,
+    spoke_id: u32,
+    program_id: &Pubkey,
+) -> Result<Option<SpokeRelayers>> {
+    let (expected, _) =
+        Pubkey::find_program_address(&[b"spoke_relayers", &spoke_id.to_le_bytes()], program_id);
+    require_keys_eq!(ai.key(), expected, ErrorCode::Unauthorized);
+    read_spoke_relayers(ai, program_id)
+}
+
+/// load_spoke_relayers for an `ai` the caller already matched against the canonical PDA.
+fn read_spoke_relayers(ai: &AccountInfo, program_id: &Pubkey) -> Result<Option<SpokeRelayers>> {
+    if ai.owner != program_id {
+        return Ok(None);
+    }
+    let list = SpokeRelayers::try_deserialize(&mut &ai.try_borrow_data()?[..])?;
+    Ok(if list.relayers_len > 0 {
+        Some(list)
+    } else {
+        None
+    })
+}
+
+/// Whether `signer` is in the IntentSigners set, read from the canonical PDA among
+/// `remaining`. A missing or uninitialized set registers nobody.
+fn intent_signer_registered(
+    remaining: &[AccountInfo],
+    signer: &[u8; 20],
+    program_id: &Pubkey,
+) -> Result<bool> {
+    let (expected, _) = Pubkey::find_program_address(&[b"intent_signers"], program_id);
+    let Some(ai) = remaining
+        .iter()
+        .find(|ai| ai.key() == expected && ai.owner == program_id)
+    else {
+        return Ok(false);
+    };
+    let set = IntentSigners::try_deserialize(&mut &ai.try_borrow_data()?[..])?;
+    Ok(set.signers[..set.signers_len as usize].contains(signer))
+}
+
+/// Append an admin operation to the AdminAuditLog when the PDA is among `remaining`. Once
+/// FEATURE_ADMIN_AUDIT is set (initialize_admin_audit_log sets it) the log is mandatory.
+fn audit_admin_op<I: Discriminator + AnchorSerialize>(
+    remaining: &[AccountInfo],
+    program_id: &Pubkey,
+    cfg: &Config,
+    authority: Pubkey,
+    ix: &I,
+) -> Result<()> {
+    let (expected, _) =
+        Pubkey::find_program_address(&[ADMIN_AUDIT_SEED, cfg.namespace_seed()], program_id);
+    let Some(ai) = remaining
+        .iter()
+        .find(|ai| ai.key() == expected && ai.owner == program_id)
+    else {
+        require!(
+            !cfg.feature_enabled(FEATURE_ADMIN_AUDIT),
+            ErrorCode::AdminAuditLogMissing
+        );
+        return Ok(());
+    };
+    require!(ai.is_writable, ErrorCode::AdminAuditLogMissing);
+    let mut log = AdminAuditLog::try_deserialize(&mut &ai.try_borrow_data()?[..])?;
+    log.push(AdminAuditEntry {
+        slot: time::now(remaining, program_id)?.slot,
+        instruction: I::DISCRIMINATOR,
+        authority,
+        params_hash: anchor_lang::solana_program::hash::hash(&ix.try_to_vec()?).to_bytes(),
+    });
+    log.try_serialize(&mut &mut ai.try_borrow_mut_data()?[..])?;
+    Ok(())
+}
+
+/// Bump Config.config_version after a config change and append the new version to the
+/// ConfigHistory PDA when it is among `remaining`. With FEATURE_CONFIG_HISTORY set the PDA
+/// is mandatory unless `best_effort` (global_kill must never fail on a missing account).
+fn bump_config_version(
+    remaining: &[AccountInfo],
+    program_id: &Pubkey,
+    cfg: &mut Config,
+    best_effort: bool,
+) -> Result<()> {
+    cfg.config_version = cfg
+        .config_version
+        .checked_add(1)
+        .ok_or(ErrorCode::MathOverflow)?;
+    let slot = time::now(remaining, program_id)?.slot;
+    let (expected, _) =
+        Pubkey::find_program_address(&[CONFIG_HISTORY_SEED, cfg.namespace_seed()], program_id);
+    let history = remaining
+        .iter()
+        .find(|ai| ai.key() == expected && ai.owner == program_id && ai.is_writable);
+    match history {
+        Some(ai) => {
+            let mut history = ConfigHistory::try_deserialize(&mut &ai.try_borrow_data()?[..])?;
+            history.push(ConfigVersionEntry::of(cfg, slot));
+            history.try_serialize(&mut &mut ai.try_borrow_mut_data()?[..])?;
+        }
+        None => require!(
+            best_effort || !cfg.feature_enabled(FEATURE_CONFIG_HISTORY),
+            ErrorCode::ConfigHistoryMissing
+        ),
+    }
+    emit!(ConfigVersionBumped {
+        version: cfg.config_version,
+        slot,
+        recorded: history.is_some(),
+    });
+    Ok(())
+}
+
+/// Rewrite the RouteAvailability PDA when the caller passed it in remaining accounts; a
+/// `None` registry only refreshes the config flags. Absent or uninitialized is a no-op.
+fn sync_route_availability(
+    remaining: &[AccountInfo],
+    program_id: &Pubkey,
+    cfg: &Config,
+    registry: Option<&Registry>,
+) -> Result<()> {
+    let (expected, _) =
+        Pubkey::find_program_address(&[ROUTE_AVAILABILITY_SEED, cfg.namespace_seed()], program_id);
+    let Some(ai) = remaining
+        .iter()
+        .find(|ai| ai.key() == expected && ai.owner == program_id)
+    else {
+        return Ok(());
+    };
+    require!(ai.is_writable, ErrorCode::InvalidRouteAvailabilityAccount);
+    let mut avail = RouteAvailability::try_deserialize(&mut &ai.try_borrow_data()?[..])?;
+    let slot = time::now(remaining, program_id)?.slot;
+    avail.set_flags(cfg);
+    if let Some(registry) = registry {
+        avail.set_spokes(&registry.spokes[..registry.spokes_len as usize], slot);
+    }
+    avail.updated_slot = slot;
+    avail.try_serialize(&mut &mut ai.try_borrow_mut_data()?[..])?;
+    emit!(RouteAvailabilityUpdated {
+        flags: avail.flags,
+        spoke_bits: avail.spoke_bits,
+        slot,
+    });
+    Ok(())
+}
+
+/// Outcome of `select_weighted_spoke`, emitted as the SpokeSelected rationale.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WeightedSelection {
+    pub spoke_id: u32,
+    pub weight: u16,
+    pub ticket: u64,
+    pub total_weight: u64,
+}
+
+/// Deterministic weighted selection: with candidates in ascending spoke_id order and
+/// `total_weight = sum(weight)`, `ticket = nonce % total_weight` picks the first candidate
+/// whose cumulative weight exceeds the ticket. Zero-weight candidates are never chosen;
+/// None when every weight is zero.
+pub fn select_weighted_spoke(candidates: &[(u32, u16)], nonce: u64) -> Option<WeightedSelection> {
+    let total_weight: u64 = candidates.iter().map(|(_, w)| *w as u64).sum();
+    if total_weight == 0 {
+        return None;
+    }
+    let ticket = nonce % total_weight;
+    let mut cumulative = 0u64;
+    for (spoke_id, weight) in candidates {
+        cumulative += *weight as u64;
+        if ticket < cumulative {
+            return Some(WeightedSelection {
+                spoke_id: *spoke_id,
+                weight: *weight,
+                ticket,
+                total_weight,
+            });
+        }
+    }
+    None
+}
+
+/// Set `paused` on every spoke in `category`; returns how many spokes matched.
+pub fn set_category_paused(spokes: &mut [SpokeEntry], category: u8, paused: bool) -> u8 {
+    let mut matched = 0u8;
+    for spoke in spokes.iter_mut().filter(|s| s.category == category) {
+        spoke.paused = paused;
+        matched += 1;
+    }
+    matched
+}
+
+fn spoke_usable(spoke: &SpokeEntry, slot: u64) -> bool {
+    spoke.enabled && !spoke.paused && (spoke.deprecated_at_slot == 0 || slot < spoke.sunset_slot)
+}
+
+/// Spoke forward_auto dispatches through: the primary while usable, otherwise its fallback
+/// if that one is usable. Fallbacks are not chained. None when neither can take traffic.
+pub fn resolve_spoke_route(spokes: &[SpokeEntry], primary_spoke_id: u32, slot: u64) -> Option<u32> {
+    let primary = spokes.iter().find(|s| s.spoke_id == primary_spoke_id)?;
+    if spoke_usable(primary, slot) {
+        return Some(primary.spoke_id);
+    }
+    if primary.fallback_spoke_id == 0 {
+        return None;
+    }
+    spokes
+        .iter()
+        .find(|s| s.spoke_id == primary.fallback_spoke_id && spoke_usable(s, slot))
+        .map(|s| s.spoke_id)
+}
+
+/// A kill can be released only once a release was scheduled and its slot has passed.
+pub fn kill_release_ready(kill_release_slot: u64, slot: u64) -> bool {
+    kill_release_slot != 0 && slot >= kill_release_slot
+}
+
+/// Shared body of pause_mint / unpause_mint.
+fn set_mint_pause(ctx: Context<SetMintPause>, paused: bool) -> Result<()> {
+    let cfg = &ctx.accounts.config;
+    let authority = ctx.accounts.authority.key();
+    require!(
+        authority == cfg.admin || (cfg.guardian != Pubkey::default() && authority == cfg.guardian),
+        ErrorCode::Unauthorized
+    );
+    let mint = ctx.accounts.mint.key();
+    let (expected, bump) =
+        Pubkey::find_program_address(&[b"mint_pause", mint.as_ref()], ctx.program_id);
+    let pause_ai = ctx.accounts.mint_pause.to_account_info();
+    require_keys_eq!(pause_ai.key(), expected, ErrorCode::InvalidMintPauseAccount);
+    if pause_ai.owner != ctx.program_id {
+        create_pda_account(
+            &ctx.accounts.authority.to_account_info(),
+            &pause_ai,
+            &ctx.accounts.system_program.to_account_info(),
+            MINT_PAUSE_SPACE,
+            &[b"mint_pause", mint.as_ref(), &[bump]],
+            ctx.program_id,
+        )?;
+    }
+    let slot = Clock::get()?.slot;
+    MintPause {
+        mint,
+        paused,
+        updated_at_slot: slot,
+        bump,
+    }
+    .try_serialize(&mut &mut pause_ai.try_borrow_mut_data()?[..])?;
+    if paused {
+        emit!(MintPaused {
+            mint,
+            authority,
+            slot
+        });
+    } else {
+        emit!(MintUnpaused {
+            mint,
+            authority,
+            slot
+        });
+    }
+    Ok(())
+}
+
+/// Credit a vault-bound relayer fee to the relayer's RelayerVesting PDA (created on first
+/// use, paid by the relayer) under the current RelayerVestingConfig period.
+fn accrue_relayer_fee<'info>(
+    payer: &AccountInfo<'info>,
+    relayer: &Pubkey,
+    vesting_config: &AccountInfo<'info>,
+    vesting: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    mint: &Pubkey,
+    amount: u64,
+    slot: u64,
+    program_id: &Pubkey,
+) -> Result<()> {
+    let (expected_config, _) =
+        Pubkey::find_program_address(&[b"relayer_vesting_config"], program_id);
+    require_keys_eq!(
+        vesting_config.key(),
+        expected_config,
+        ErrorCode::InvalidRelayerVesting
+    );
+    let period_slots = if vesting_config.owner == program_id {
+        RelayerVestingConfig::try_deserialize(&mut &vesting_config.try_borrow_data()?[..])?
+            .period_slots
+    } else {
+        0
+    };
+    let (expected, bump) = Pubkey::find_program_address(
+        &[b"relayer_vesting", relayer.as_ref(), mint.as_ref()],
+        program_id,
+    );
+    require_keys_eq!(vesting.key(), expected, ErrorCode::InvalidRelayerVesting);
+    let mut state = if vesting.owner == program_id {
+        RelayerVesting::try_deserialize(&mut &vesting.try_borrow_data()?[..])?
+    } else {
+        create_pda_account(
+            payer,
+            vesting,
+            system_program,
+            RELAYER_VESTING_SPACE,
+            &[b"relayer_vesting", relayer.as_ref(), mint.as_ref(), &[bump]],
+            program_id,
+        )?;
+        RelayerVesting {
+            relayer: *relayer,
+            mint: *mint,
+            locked: 0,
+            unlocked: 0,
+            start_slot: slot,
+            end_slot: slot,
+            claimed_total: 0,
+            bump,
+        }
+    };
+    state.accrue(amount, slot, period_slots)?;
+    state.try_serialize(&mut &mut vesting.try_borrow_mut_data()?[..])?;
+    Ok(())
+}
+
+/// Fold one forward's latency into the relayer's RelayerActivity PDA (created on first use,
+/// paid by the relayer). Returns the updated activity when the forward breached `target_slots`.
+#[allow(clippy::too_many_arguments)]
+fn record_relayer_activity<'info>(
+    relayer: &AccountInfo<'info>,
+    activity: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    latency_slots: u64,
+    target_slots: u64,
+    slot: u64,
+    program_id: &Pubkey,
+) -> Result<Option<RelayerActivity>> {
+    let (expected, bump) =
+        Pubkey::find_program_address(&[b"relayer_activity", relayer.key.as_ref()], program_id);
+    require_keys_eq!(activity.key(), expected, ErrorCode::InvalidRelayerActivity);
+    let mut state = if activity.owner == program_id {
+        RelayerActivity::try_deserialize(&mut &activity.try_borrow_data()?[..])?
+    } else {
+        create_pda_account(
+            relayer,
+            activity,
+            system_program,
+            RELAYER_ACTIVITY_SPACE,
+            &[b"relayer_activity", relayer.key.as_ref(), &[bump]],
+            program_id,
+        )?;
+        RelayerActivity {
+            relayer: relayer.key(),
+            forwards: 0,
+            total_latency_slots: 0,
+            max_latency_slots: 0,
+            sla_breaches: 0,
+            last_forward_slot: 0,
+            bump,
+        }
+    };
+    let breached = state.record(latency_slots, target_slots, slot);
+    state.try_serialize(&mut &mut activity.try_borrow_mut_data()?[..])?;
+    Ok(breached.then_some(state))
+}
+
+/// Reject transfers of a paused mint. The account must be the canonical MintPause PDA;
+/// an uninitialized PDA means the mint was never paused.
+fn require_mint_active(ai: &AccountInfo, mint: &Pubkey, program_id: &Pubkey) -> Result<()> {
+    let (expected, _) = Pubkey::find_program_address(&[b"mint_pause", mint.as_ref()], program_id);
+    require_keys_eq!(ai.key(), expected, ErrorCode::InvalidMintPauseAccount);
+    require_mint_unpaused(ai, program_id)
+}
+
+/// require_mint_active for an `ai` the caller already matched against the canonical PDA.
+fn require_mint_unpaused(ai: &AccountInfo, program_id: &Pubkey) -> Result<()> {
+    if ai.owner != program_id {
+        return Ok(());
+    }
+    let state = MintPause::try_deserialize(&mut &ai.try_borrow_data()?[..])?;
+    require!(!state.paused, ErrorCode::MintPaused);
+    Ok(())
+}
+
+/// Token gate for the transfer paths: any mint when `accept_any_token`, otherwise
+/// `allowed_token_mint` or a mint listed in the [b"mint_allowlist"] PDA (if initialized).
+fn require_mint_allowed(
+    ai: &AccountInfo,
+    cfg: &Config,
+    mint: &Pubkey,
+    program_id: &Pubkey,
+) -> Result<()> {
+    let (expected, _) = Pubkey::find_program_address(&[b"mint_allowlist"], program_id);
+    require_keys_eq!(ai.key(), expected, ErrorCode::MintNotAllowed);
+    if cfg.accept_any_token || *mint == cfg.allowed_token_mint {
+        return Ok(());
+    }
+    let listed = ai.owner == program_id && {
+        let list = MintAllowlist::try_deserialize(&mut &ai.try_borrow_data()?[..])?;
+        list.mints[..list.mints_len as usize].contains(mint)
+    };
+    require!(listed, ErrorCode::MintNotAllowed);
+    Ok(())
+}
+
+/// Load the FeeSplit PDA; None when uninitialized or empty.
+fn load_fee_split(ai: &AccountInfo, program_id: &Pubkey) -> Result<Option<FeeSplit>> {
+    let (expected, _) = Pubkey::find_program_address(&[b"fee_split"], program_id);
+    require_keys_eq!(ai.key(), expected, ErrorCode::InvalidFeeRecipientAta);
+    if ai.owner != program_id {
+        return Ok(None);
+    }
+    let split = FeeSplit::try_deserialize(&mut &ai.try_borrow_data()?[..])?;
+    Ok(if split.recipients_len > 0 {
+        Some(split)
+    } else {
+        None
+    })
+}
+
+/// A split table holds 1..=MAX_FEE_SPLIT_RECIPIENTS shares summing to 10_000 bps (or is empty).
+pub fn check_fee_split(recipients: &[Pubkey], bps: &[u16]) -> Result<()> {
+    require!(
+        recipients.len() == bps.len() && recipients.len() <= MAX_FEE_SPLIT_RECIPIENTS,
+        ErrorCode::InvalidFeeSplit
+    );
+    if recipients.is_empty() {
+        return Ok(());
+    }
+    let total: u32 = bps.iter().map(|b| *b as u32).sum();
+    require!(total == 10_000, ErrorCode::InvalidFeeSplit);
+    Ok(())
+}
+
+/// Share ATAs must be the canonical SPL Token ATA of the configured recipient for `mint`.
+fn verify_fee_share_ata(recipient: &Pubkey, mint: &Pubkey, ata: &AccountInfo) -> Result<()> {
+    let (expected, _) = Pubkey::find_program_address(
+        &[recipient.as_ref(), token::ID.as_ref(), mint.as_ref()],
+        &anchor_spl::associated_token::ID,
+    );
+    require_keys_eq!(ata.key(), expected, ErrorCode::InvalidFeeRecipientAta);
+    require_keys_eq!(*ata.owner, token::ID, ErrorCode::InvalidTokenProgram);
+    Ok(())
+}
+
+/// Admin may always relay; otherwise the spoke allowlist (if any) or the global relayer.
+fn relayer_allowed(relayer: &Pubkey, cfg: &Config, spoke_relayers: Option<&SpokeRelayers>) -> bool {
+    if *relayer == cfg.admin {
+        return true;
+    }
+    match spoke_relayers {
+        Some(list) => list.relayers[..list.relayers_len as usize].contains(relayer),
+        None => *relayer == cfg.relayer_pubkey,
+    }
+}
+
+/// Return everything left in a forward escrow's vault to `destination`, close the vault and
+/// pay out the reclaimed rent (the escrow account itself is closed by the caller's
+/// `close = user` constraint).
+#[allow(clippy::too_many_arguments)]
+fn refund_forward_escrow<'info>(
+    cfg: &Config,
+    escrow: &Account<'info, ForwardEscrow>,
+    escrow_vault: &Account<'info, TokenAccount>,
+    user: &AccountInfo<'info>,
+    caller: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+    rent_ledger: &AccountInfo<'info>,
+    token_program: &Program<'info, Token>,
+    program_id: &Pubkey,
+) -> Result<()> {
+    // Held fees are settled (or refunded) by the relayer/admin, never reclaimed
+    require!(escrow.held_fees() == 0, ErrorCode::FeesHeld);
+    let nonce_le = escrow.nonce.to_le_bytes();
+    let seeds: &[&[u8]] = &[
+        b"forward_escrow",
+        escrow.user.as_ref(),
+        &nonce_le,
+        &[escrow.bump],
+    ];
+    let amount = escrow_vault.amount;
+    record_rent(
+        rent_ledger,
+        program_id,
+        RENT_KIND_ESCROW,
+        FORWARD_ESCROW_SPACE,
+        false,
+    )?;
+    record_rent(
+        rent_ledger,
+        program_id,
+        RENT_KIND_VAULT,
+        TokenAccount::LEN,
+        false,
+    )?;
+    let escrow_ai = escrow.to_account_info();
+    drain_forward_escrow_vault(
+        token_program,
+        escrow_vault,
+        &escrow_ai,
+        &escrow_ai,
+        Some((destination, amount)),
+        seeds,
+    )?;
+    // The lamport budget and tip are the user's own funds, not rent
+    let rent = escrow_ai.lamports().saturating_sub(escrow.owed_lamports());
+    refund_escrow_rent(cfg, &escrow_ai, user, caller, rent_ledger, rent, program_id)?;
+    emit!(ForwardEscrowReclaimed {
+        user: escrow.user,
+        nonce: escrow.nonce,
+        amount,
+    });
+    Ok(())
+}
+
+/// Pay out `rent` lamports reclaimed into `escrow` (its own rent and, when its vault was
+/// closed into it, the vault's) under the config's rent refund policy: the payer share to
+/// `user`, the treasury share to the rent ledger PDA and the caller share to `caller`. The
+/// treasury share stays with the payer until the rent ledger is initialized. Lamports left in
+/// the escrow go to `user` when it is closed.
+#[allow(clippy::too_many_arguments)]
+fn refund_escrow_rent<'info>(
+    cfg: &Config,
+    escrow: &AccountInfo<'info>,
+    user: &AccountInfo<'info>,
+    caller: &AccountInfo<'info>,
+    rent_ledger: &AccountInfo<'info>,
+    rent: u64,
+    program_id: &Pubkey,
+) -> Result<()> {
+    let (mut payer_share, mut treasury_share, caller_share) = cfg.rent_refund_shares(rent);
+    if rent_ledger.owner != program_id {
+        payer_share += treasury_share;
+        treasury_share = 0;
+    }
+    **escrow.try_borrow_mut_lamports()? -= payer_share + treasury_share + caller_share;
+    **user.try_borrow_mut_lamports()? += payer_share;
+    **rent_ledger.try_borrow_mut_lamports()? += treasury_share;
+    **caller.try_borrow_mut_lamports()? += caller_share;
+    emit!(EscrowRentRefunded {
+        escrow: escrow.key(),
+        user: user.key(),
+        caller: caller.key(),
+        policy: cfg.rent_refund_policy,
+        rent,
+        payer_share,
+        treasury_share,
+        caller_share,
+    });
+    Ok(())
+}
+
+/// Close a fully dispatched escrow whose held fees were just settled: move what is left in
+/// the vault (`amount`) to `to`, close the vault and the escrow and pay out their rent.
+#[allow(clippy::too_many_arguments)]
+fn close_settled_escrow<'info>(
+    cfg: &Config,
+    escrow: &Account<'info, ForwardEscrow>,
+    escrow_vault: &Account<'info, TokenAccount>,
+    user: &AccountInfo<'info>,
+    caller: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    amount: u64,
+    rent_ledger: &AccountInfo<'info>,
+    token_program: &Program<'info, Token>,
+    program_id: &Pubkey,
+) -> Result<()> {
+    let nonce_le = escrow.nonce.to_le_bytes();
+    let seeds: &[&[u8]] = &[
+        b"forward_escrow",
+        escrow.user.as_ref(),
+        &nonce_le,
+        &[escrow.bump],
+    ];
+    record_rent(
+        rent_ledger,
+        program_id,
+        RENT_KIND_ESCROW,
+        FORWARD_ESCROW_SPACE,
+        false,
+    )?;
+    record_rent(
+        rent_ledger,
+        program_id,
+        RENT_KIND_VAULT,
+        TokenAccount::LEN,
+        false,
+    )?;
+    let escrow_ai = escrow.to_account_info();
+    drain_forward_escrow_vault(
+        token_program,
+        escrow_vault,
+        &escrow_ai,
+        &escrow_ai,
+        Some((to, amount)),
+        seeds,
+    )?;
+    let rent = escrow_ai.lamports().saturating_sub(escrow.owed_lamports());
+    refund_escrow_rent(cfg, &escrow_ai, user, caller, rent_ledger, rent, program_id)?;
+    escrow.close(user.clone())
+}
+
+/// Optionally move `amount` out of a forward escrow vault to `to`, then close the vault with
+/// rent to `rent_to`. Callers must empty the vault: SPL close requires a zero balance.
+fn drain_forward_escrow_vault<'info>(
+    token_program: &Program<'info, Token>,
+    vault: &Account<'info, TokenAccount>,
+    escrow: &AccountInfo<'info>,
+    rent_to: &AccountInfo<'info>,
+    transfer: Option<(&AccountInfo<'info>, u64)>,
+    escrow_seeds: &[&[u8]],
+) -> Result<()> {
+    let signer: &[&[&[u8]]] = &[escrow_seeds];
+    if let Some((to, amount)) = transfer {
+        if amount > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    token_cpi_program(token_program)?,
+                    token::Transfer {
+                        from: vault.to_account_info(),
+                        to: to.clone(),
+                        authority: escrow.clone(),
+                    },
+                    signer,
+                ),
+                amount,
+            )?;
+        }
+    }
+    token::close_account(CpiContext::new_with_signer(
+        token_cpi_program(token_program)?,
+        token::CloseAccount {
+            account: vault.to_account_info(),
+            destination: rent_to.clone(),
+            authority: escrow.clone(),
+        },
+        signer,
+    ))
+}
+
+/// Environment mixed into this build's message-hash domain separator.
+const DEPLOYMENT_ENV: u8 = if cfg!(feature = "mainnet") {
+    hash::ENV_MAINNET
+} else if cfg!(feature = "devnet-relaxed") {
+    hash::ENV_DEVNET
+} else {
+    hash::ENV_LOCAL
+};
+
+/// Hash scheme for `version`: 0 is the legacy packing, anything else is separated by this
+/// hub program's id, the deployment environment and `version`.
+pub fn message_hash_scheme(version: u8) -> HashScheme {
+    HashScheme::for_version(crate::ID.to_bytes(), DEPLOYMENT_ENV, version)
+}
+
+/// Source-leg message hash over the packed preimage fields (shared with verify_preimage).
+/// `amount_decimals` is set on routes that normalize amounts to 18 decimals.
+#[allow(clippy::too_many_arguments)]
+fn source_message_hash(
+    hash_version: u8,
+    src_chain_id: u64,
+    dst_chain_id: u64,
+    target_adapter: &Pubkey,
+    mint: &Pubkey,
+    forward_amount: u64,
+    amount_decimals: Option<u8>,
+    payload_hash: [u8; 32],
+    nonce: u64,
+) -> Result<[u8; 32]> {
+    Ok(hash::source_message_hash_word(
+        message_hash_scheme(hash_version),
+        src_chain_id,
+        dst_chain_id,
+        target_adapter.to_bytes(),
+        mint.to_bytes(),
+        amount::amount_word(forward_amount, amount_decimals).map_err(ErrorCode::from)?,
+        payload_hash,
+        nonce,
+    ))
+}
+
+/// Create the caller-funded MessagePreimage PDA [b"message", initiator, nonce] and write
+/// `preimage` into it. Fails if a preimage for this (initiator, nonce) already exists.
+fn store_message_preimage<'info>(
+    payer: &AccountInfo<'info>,
+    message: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    program_id: &Pubkey,
+    mut preimage: MessagePreimage,
+) -> Result<()> {
+    let nonce_le = preimage.nonce.to_le_bytes();
+    let (expected, bump) = Pubkey::find_program_address(
+        &[b"message", preimage.initiator.as_ref(), &nonce_le],
+        program_id,
+    );
+    require_keys_eq!(message.key(), expected, ErrorCode::InvalidMessageAccount);
+    require!(
+        message.owner != program_id,
+        ErrorCode::PreimageAlreadyStored
+    );
+    create_pda_account(
+        payer,
+        message,
+        system_program,
+        MESSAGE_PREIMAGE_SPACE,
+        &[b"message", preimage.initiator.as_ref(), &nonce_le, &[bump]],
+        program_id,
+    )?;
+    preimage.bump = bump;
+    preimage.try_serialize(&mut &mut message.try_borrow_mut_data()?[..])?;
+    Ok(())
+}
+
+/// Append `receipt` to the user's transfer index, creating the PDA (funded by `payer`) on
+/// first use.
+fn record_transfer_receipt<'info>(
+    payer: &AccountInfo<'info>,
+    index: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    user: &Pubkey,
+    receipt: TransferReceipt,
+    program_id: &Pubkey,
+) -> Result<()> {
+    let (expected, bump) =
+        Pubkey::find_program_address(&[b"user_index", user.as_ref()], program_id);
+    require_keys_eq!(index.key(), expected, ErrorCode::InvalidUserIndex);
+    let mut state = if index.owner == program_id {
+        UserTransferIndex::try_deserialize(&mut &index.try_borrow_data()?[..])?
+    } else {
+        create_pda_account(
+            payer,
+            index,
+            system_program,
+            USER_INDEX_SPACE,
+            &[b"user_index", user.as_ref(), &[bump]],
+            program_id,
+        )?;
+        UserTransferIndex {
+            user: *user,
+            head: 0,
+            total: 0,
+            receipts: [TransferReceipt::default(); USER_INDEX_LEN],
+            bump,
+        }
+    };
+    state.push(receipt);
+    state.try_serialize(&mut &mut index.try_borrow_mut_data()?[..])?;
+    Ok(())
+}
+
+fn source_leg_hashes(
+    hash_version: u8,
+    src_chain_id: u64,
+    dst_chain_id: u64,
+    target_adapter: &Pubkey,
+    mint: &Pubkey,
+    initiator: &Pubkey,
+    forward_amount: u64,
+    amount_decimals: Option<u8>,
+    payload: &[u8],
+    nonce: u64,
+    validity: Option<&MessageValidity>,
+) -> Result<([u8; 32], [u8; 32], [u8; 32])> {
+    let payload_hash = payload::payload_hash(payload, validity.map(|v| (v.sent_at, v.expires_at)));
+    let msg_hash = source_message_hash(
+        hash_version,
+        src_chain_id,
+        dst_chain_id,
+        target_adapter,
+        mint,
+        forward_amount,
+        amount_decimals,
+        payload_hash,
+        nonce,
+    )?;
+    let global_route = global_route_id(
+        message_hash_scheme(hash_version),
+        src_chain_id,
+        dst_chain_id,
+        initiator.to_bytes(),
+        msg_hash,
+        nonce,
+    );
+    Ok((payload_hash, msg_hash, global_route))
+}
+
+/// ZPX owed for an in-kind `protocol_fee` at `zpx_per_token_e9`, after `discount_bps`.
+pub fn zpx_fee_for(protocol_fee: u64, zpx_per_token_e9: u64, discount_bps: u16) -> Result<u64> {
+    Ok(fees::zpx_fee_for(protocol_fee, zpx_per_token_e9, discount_bps).map_err(ErrorCode::from)?)
+}
+
+/// Reject prices that are stale, not trading, non-positive, or too uncertain.
+pub fn check_oracle_price(
+    price: &PythPrice,
+    slot: u64,
+    max_staleness_slots: u64,
+    max_conf_bps: u16,
+) -> Result<()> {
+    require!(
+        price.status == oracle::PYTH_STATUS_TRADING && price.price > 0,
+        ErrorCode::InvalidOraclePrice
+    );
+    require!(
+        slot.saturating_sub(price.pub_slot) <= max_staleness_slots,
+        ErrorCode::StaleOraclePrice
+    );
+    require!(
+        (price.conf as u128) * 10_000u128 <= (price.price as u128) * (max_conf_bps as u128),
+        ErrorCode::OracleConfidenceTooWide
+    );
+    Ok(())
+}
+
+/// USD value (6 decimals) of `amount` base units of a mint with `decimals`.
+pub fn usd_value_e6(amount: u64, decimals: u8, price: &PythPrice) -> Result<u64> {
+    // value = amount * price * 10^(expo + 6 - decimals)
+    let scale = price.expo as i64 + 6 - decimals as i64;
+    let raw = (amount as u128)
+        .checked_mul(price.price as u128)
+        .ok_or(ErrorCode::MathOverflow)?;
+    let pow = 10u128
+        .checked_pow(scale.unsigned_abs() as u32)
+        .ok_or(ErrorCode::MathOverflow)?;
+    let value = if scale >= 0 {
+        raw.checked_mul(pow).ok_or(ErrorCode::MathOverflow)?
+    } else {
+        raw / pow
+    };
+    u64::try_from(value).map_err(|_| error!(ErrorCode::MathOverflow))
+}
+
+/// Enforce OracleConfig.min_forward_usd_e6 using [oracle_config, pyth_price] remaining accounts.
+fn enforce_usd_min_forward(
+    remaining: &[AccountInfo],
+    program_id: &Pubkey,
+    mint: &Account<Mint>,
+    amount: u64,
+) -> Result<()> {
+    require!(remaining.len() >= 2, ErrorCode::OracleAccountsMissing);
+    let (oc_ai, price_ai) = (&remaining[0], &remaining[1]);
+    let (expected_oc, _) =
+        Pubkey::find_program_address(&[b"oracle", mint.key().as_ref()], program_id);
+    require_keys_eq!(oc_ai.key(), expected_oc, ErrorCode::OracleAccountsMissing);
+    require_keys_eq!(*oc_ai.owner, *program_id, ErrorCode::OracleAccountsMissing);
+    let oc = OracleConfig::try_deserialize(&mut &oc_ai.try_borrow_data()?[..])?;
+    if oc.min_forward_usd_e6 == 0 {
+        return Ok(());
+    }
+    require_keys_eq!(
+        price_ai.key(),
+        oc.price_account,
+        ErrorCode::InvalidOraclePrice
+    );
+    require_keys_eq!(
+        *price_ai.owner,
+        oc.oracle_program,
+        ErrorCode::InvalidOraclePrice
+    );
+    let price = oracle::parse_pyth_price(&price_ai.try_borrow_data()?)
+        .ok_or_else(|| error!(ErrorCode::InvalidOraclePrice))?;
+    check_oracle_price(
+        &price,
+        Clock::get()?.slot,
+        oc.max_staleness_slots,
+        oc.max_conf_bps,
+    )?;
+    require!(
+        usd_value_e6(amount, oc.decimals, &price)? >= oc.min_forward_usd_e6,
+        ErrorCode::BelowUsdMinimum
+    );
+    Ok(())
+}
+
+/// Domain tag prefixed to the config commitment preimage.
+pub const CONFIG_COMMITMENT_TAG: &[u8] = b"zpx_config_attestation_v1";
+
+/// Canonical config root: keccak256(TAG || borsh(Config) || spokes_len || borsh(spoke_i)...)
+/// over the active spokes only. Off-chain relayers recompute this from account data.
+pub fn config_commitment(cfg: &Config, registry: &Registry) -> Result<[u8; 32]> {
+    let cfg_bytes = cfg.try_to_vec()?;
+    let len = registry.spokes_len as usize;
+    let mut spokes_bytes = Vec::with_capacity(len * SPOKE_ENTRY_LEN);
+    for spoke in registry.spokes[..len].iter() {
+        spokes_bytes.extend_from_slice(&spoke.try_to_vec()?);
+    }
+    Ok(keccak256(&[
+        CONFIG_COMMITMENT_TAG,
+        &cfg_bytes,
+        &[registry.spokes_len],
+        &spokes_bytes,
+    ]))
+}
+
+fn emit_spoke_enumerated(registry: &Registry, index: usize) -> Result<()> {
+    emit!(SpokeEnumerated {
+        slot: Clock::get()?.slot,
+        index: index as u8,
+        spokes_len: registry.spokes_len,
+        spoke: SpokeSnapshot::from(&registry.spokes[index]),
+    });
+    Ok(())
+}
+
+/// Domain tag prefixed to registry export/import chunk hashes.
+pub const REGISTRY_EXPORT_TAG: &[u8] = b"zpx_registry_export_v1";
+/// Entries per RegistryExportChunk, keeping the event well under the log size limit.
+pub const REGISTRY_CHUNK_MAX: usize = 8;
+
+/// keccak256(TAG || start || borsh(entry_i)...). With start 0 over all active entries this
+/// is the registry hash reported by export_registry and import_registry.
+pub fn registry_chunk_hash(start: u8, entries: &[SpokeEntry]) -> Result<[u8; 32]> {
+    let mut bytes = Vec::with_capacity(entries.len() * SPOKE_ENTRY_LEN);
+    for entry in entries {
+        bytes.extend_from_slice(&entry.try_to_vec()?);
+    }
+    Ok(keccak256(&[REGISTRY_EXPORT_TAG, &[start], &bytes]))
+}
+
+/// Add `amount` to the in-flight counter, enforcing `exposure_cap` when non-zero.
+pub fn in_flight_after_forward(current: u64, amount: u64, exposure_cap: u64) -> Result<u64> {
+    let next = current.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+    require!(
+        exposure_cap == 0 || next <= exposure_cap,
+        ErrorCode::ExposureCapExceeded
+    );
+    Ok(next)
+}
+
+/// Spoke registry stored separately from Config. Fixed-size array-based registry for simplicity.
+#[account]
+pub struct Registry {
+    pub spokes_len: u8,
+    pub spokes: [SpokeEntry; MAX_SPOKES],
+    pub bump: u8,
+    /// Layout version (REGISTRY_SCHEMA_VERSION when written); see the `schema` module
+    pub schema_version: u8,
+}
+
+// discriminator(8) + spokes_len(1) + spokes + bump(1) + schema_version(1)
+pub const REGISTRY_SPACE: usize = 8 + 1 + (SPOKE_ENTRY_LEN * MAX_SPOKES) + 1 + 1;
+
+pub const ROUTE_AVAILABILITY_SEED: &[u8] = b"route_availability";
+/// RouteAvailability::flags bit 0: the router is neither paused nor globally killed.
+pub const ROUTE_AVAILABLE_LIVE: u8 = 1 << 0;
+/// RouteAvailability::flags bit 1: any mint is accepted (no allowlist check).
+pub const ROUTE_AVAILABLE_ANY_TOKEN: u8 = 1 << 1;
+// spoke_bits holds one bit per registry slot
+const _: () = assert!(MAX_SPOKES <= u32::BITS as usize);
+
+/// Compact availability view of the registry for wallets, PDA [b"route_availability", namespace].
+/// Bit i of `spoke_bits` is set when `spoke_ids[i]` (registry position i) is enabled, not
+/// paused and not past its sunset. Rewritten by every spoke or pause change that passes it in
+/// remaining accounts, and by refresh_route_availability. Per-mint pauses stay in mint_pause.
+#[account]
+pub struct RouteAvailability {
+    pub flags: u8,
+    pub spokes_len: u8,
+    pub spoke_ids: [u32; MAX_SPOKES],
+    pub spoke_bits: u32,
+    /// Earliest sunset among set bits (0 = none); the bitmap is stale from that slot on
+    pub next_sunset_slot: u64,
+    pub updated_slot: u64,
+    pub bump: u8,
+}
+
+// discriminator(8) + flags(1) + spokes_len(1) + spoke_ids(4 * MAX_SPOKES) + spoke_bits(4)
+// + next_sunset_slot(8) + updated_slot(8) + bump(1)
+pub const ROUTE_AVAILABILITY_SPACE: usize = 8 + 1 + 1 + (4 * MAX_SPOKES) + 4 + 8 + 8 + 1;
+
+impl RouteAvailability {
+    pub fn set_flags(&mut self, cfg: &Config) {
+        self.flags = 0;
+        if !cfg.paused
+            && !cfg.killed
+            && !cfg.family_paused(INSTRUCTION_FAMILY_BRIDGE)
+            && !cfg.family_paused(INSTRUCTION_FAMILY_FORWARD)
+        {
+            self.flags |= ROUTE_AVAILABLE_LIVE;
+        }
+        if cfg.accept_any_token {
+            self.flags |= ROUTE_AVAILABLE_ANY_TOKEN;
+        }
+    }
+
+    pub fn set_spokes(&mut self, spokes: &[SpokeEntry], slot: u64) {
+        self.spokes_len = spokes.len() as u8;
+        self.spoke_ids = [0; MAX_SPOKES];
+        self.spoke_bits = 0;
+        self.next_sunset_slot = 0;
+        for (i, spoke) in spokes.iter().enumerate() {
+            self.spoke_ids[i] = spoke.spoke_id;
+            if !spoke_usable(spoke, slot) {
+                continue;
+            }
+            self.spoke_bits |= 1 << i;
+            if spoke.deprecated_at_slot != 0
+                && (self.next_sunset_slot == 0 || spoke.sunset_slot < self.next_sunset_slot)
+            {
+                self.next_sunset_slot = spoke.sunset_slot;
+            }
+        }
+    }
+
+    /// Whether `spoke_id` could take traffic as of `updated_slot`; callers past
+    /// `next_sunset_slot` should refresh first.
+    pub fn is_available(&self, spoke_id: u32) -> bool {
+        if self.flags & ROUTE_AVAILABLE_LIVE == 0 {
+            return false;
+        }
+        self.spoke_ids[..self.spokes_len as usize]
+            .iter()
+            .position(|id| *id == spoke_id)
+            .is_some_and(|i| self.spoke_bits & (1 << i) != 0)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct SpokeEntry {
+    pub spoke_id: u32,
+    pub adapter_program: Pubkey,
+    pub enabled: bool,
+    pub paused: bool,
+    /// RELAYER_PAYOUT_*: whether token relayer fees go straight to the relayer's account
+    pub relayer_payout: u8,
+    pub version: u8,
+    pub metadata: [u8; SPOKE_METADATA_LEN],
+    pub created_at_slot: u64,
+    /// Pinned program-data account of the adapter; default means not pinned.
+    pub adapter_program_data: Pubkey,
+    /// Program-data deployment slot at pin time (changes on every upgrade).
+    pub adapter_deploy_slot: u64,
+    /// Upgrade authority at pin time; default means immutable.
+    pub adapter_upgrade_authority: Pubkey,
+    pub payload_schema: PayloadSchema,
+    /// Slot the deprecation window opened; 0 when the spoke is not deprecated.
+    pub deprecated_at_slot: u64,
+    /// Forwards fail with SpokeDeprecated from this slot on.
+    pub sunset_slot: u64,
+    pub successor_spoke_id: u32,
+    /// Spokes sharing a non-zero route group serve the same destination and are
+    /// interchangeable for forward_balanced.
+    pub route_group: u32,
+    /// Relative share of forward_balanced traffic within the route group; 0 = never selected.
+    pub weight: u16,
+    /// Spoke forward_auto reroutes to while this one is paused or disabled; 0 = none.
+    pub fallback_spoke_id: u32,
+    /// Required owner of adapter_target_token_account; forwards fail until it is set.
+    pub target_owner: Pubkey,
+    /// Net amount forwarded to the adapter over the spoke's lifetime.
+    pub dispatched_amount: u64,
+    /// Amount acknowledged as finalized on the destination via ack_spoke_finalized.
+    pub finalized_amount: u64,
+    /// Outstanding (dispatched - finalized) volume above which SpokeImbalance fires; 0 = off.
+    pub imbalance_threshold: u64,
+    /// Deposit-to-dispatch latency (slots) above which SlaBreached fires; 0 = no SLA.
+    pub sla_target_slots: u64,
+    /// Compute units the adapter may consume per router CPI; 0 = only the router reserve
+    /// is checked
+    pub max_adapter_cu: u32,
+    /// Upstream bridge family (SPOKE_CATEGORY_*); pause_category acts on all spokes sharing it.
+    pub category: u8,
+}
+
+/// Per-spoke adapter payload descriptor checked by the router before dispatch.
+/// Layout expected when enabled: `magic[..magic_len] || version (if required) || body`.
+/// A zeroed schema (max_len == 0) disables validation.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct PayloadSchema {
+    pub min_len: u16,
+    pub max_len: u16,
+    pub require_version: bool,
+    /// Oldest supported payload version
+    pub version: u8,
+    pub magic_len: u8,
+    pub magic: [u8; PAYLOAD_MAGIC_MAX_LEN],
+    /// Newest supported payload version; 0 accepts exactly `version`
+    pub max_version: u8,
+}
+
+impl PayloadSchema {
+    /// Inclusive range of payload versions this spoke accepts.
+    pub fn supported_versions(&self) -> core::ops::RangeInclusive<u8> {
+        self.to_core().supported_versions()
+    }
+
+    /// The zpx-core descriptor the payload checks run on.
+    pub fn to_core(&self) -> payload::PayloadSchema {
+        payload::PayloadSchema {
+            min_len: self.min_len,
+            max_len: self.max_len,
+            require_version: self.require_version,
+            version: self.version,
+            magic_len: self.magic_len,
+            magic: self.magic,
+            max_version: self.max_version,
+        }
+    }
+}
+
+impl SpokeEntry {
+    /// Whether this spoke pays token relayer fees straight to the relayer's token account.
+    pub fn direct_payout(&self, cfg: &Config) -> bool {
+        match self.relayer_payout {
+            RELAYER_PAYOUT_DIRECT => true,
+            RELAYER_PAYOUT_VAULT => false,
+            _ => cfg.direct_relayer_payout_default,
+        }
+    }
+}
+
+impl Default for SpokeEntry {
+    fn default() -> Self {
+        SpokeEntry {
+            spoke_id: 0,
+            adapter_program: Pubkey::default(),
+            enabled: false,
+            paused: false,
+            relayer_payout: RELAYER_PAYOUT_DEFAULT,
+            version: 0,
+            metadata: [0u8; SPOKE_METADATA_LEN],
+            created_at_slot: 0,
+            adapter_program_data: Pubkey::default(),
+            adapter_deploy_slot: 0,
+            adapter_upgrade_authority: Pubkey::default(),
+            payload_schema: PayloadSchema::default(),
+            deprecated_at_slot: 0,
+            sunset_slot: 0,
+            successor_spoke_id: 0,
+            route_group: 0,
+            weight: 0,
+            fallback_spoke_id: 0,
+            target_owner: Pubkey::default(),
+            dispatched_amount: 0,
+            finalized_amount: 0,
+            imbalance_threshold: 0,
+            sla_target_slots: 0,
+            max_adapter_cu: 0,
+            category: SPOKE_CATEGORY_NONE,
+        }
+    }
+}
+
+impl SpokeEntry {
+    /// Dispatched volume not yet acknowledged as finalized.
+    pub fn outstanding(&self) -> u64 {
+        self.dispatched_amount.saturating_sub(self.finalized_amount)
+    }
+
+    /// Whether the outstanding volume is above the spoke's imbalance threshold.
+    pub fn imbalanced(&self) -> bool {
+        self.imbalance_threshold != 0 && self.outstanding() > self.imbalance_threshold
+    }
+
+    fn imbalance_event(&self) -> SpokeImbalance {
+        SpokeImbalance {
+            spoke_id: self.spoke_id,
+            dispatched_amount: self.dispatched_amount,
+            finalized_amount: self.finalized_amount,
+            threshold: self.imbalance_threshold,
+            imbalanced: self.imbalanced(),
+        }
+    }
+}
+
+/// Event emitted whenever a forward is executed via a spoke
+#[event]
+pub struct Forwarded {
+    pub user: Pubkey,
+    pub relayer: Pubkey,
+    pub spoke_id: u32,
+    pub adapter_program: Pubkey,
+    pub amount: u64,
+    pub protocol_fee: u64,
+    pub relayer_fee: u64,
+    pub net_amount: u64,
+    pub dst_domain: u32,
+    pub message_account: Pubkey,
+    /// RELAYER_FEE_MODE_TOKEN or RELAYER_FEE_MODE_LAMPORTS
+    pub relayer_fee_mode: u8,
+    pub relayer_fee_lamports: u64,
+    /// Config.config_version the fees were computed under (see ConfigHistory)
+    pub config_version: u64,
+}
+
+/// Totals of a forward_batch_via_spoke call; each entry also emits Forwarded.
+#[event]
+pub struct BatchForwarded {
+    pub relayer: Pubkey,
+    pub spoke_id: u32,
+    pub adapter_program: Pubkey,
+    pub mint: Pubkey,
+    pub entries: u8,
+    pub amount: u64,
+    pub protocol_fee: u64,
+    pub relayer_fee: u64,
+    pub net_amount: u64,
+}
+
+/// Result of an escrow forward, written as Borsh return data so CPI composers can read the
+/// fees actually taken without parsing logs.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ForwardSummary {
+    pub proto_fee: u64,
+    pub relayer_fee: u64,
+    /// Amount handed to the adapter after fees.
+    pub net: u64,
+    pub spoke_id: u32,
+    /// The escrowed message account that was forwarded.
+    pub message: Pubkey,
+}
+
+/// Token balances of the accounts an escrow forward touches (escrow_vault reads 0 once closed)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ForwardTokenBalances {
+    pub escrow_vault: u64,
+    pub hub_protocol_vault: u64,
+    pub hub_relayer_vault: u64,
+    pub relayer_token_account: u64,
+    pub adapter_target: u64,
+}
+
+/// Dispute evidence for one escrow forward, emitted last while FEATURE_BALANCE_EVENTS is on.
+/// `features` is the config bitmask at emission, so the debug flag state is explicit.
+#[event]
+pub struct ForwardBalances {
+    pub user: Pubkey,
+    pub nonce: u64,
+    pub spoke_id: u32,
+    pub features: u64,
+    pub before: ForwardTokenBalances,
+    pub after: ForwardTokenBalances,
+}
+
+/// A bridge a client is about to submit, as checked by preflight.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RouteDescriptor {
+    pub user: Pubkey,
+    pub mint: Pubkey,
+    pub spoke_id: u32,
+    pub dst_domain: u32,
+    pub amount: u64,
+    /// Escrow nonce the deposit will use.
+    pub nonce: u64,
+}
+
+/// preflight problem bits (PreflightReport::problems)
+pub const PREFLIGHT_WRONG_ACCOUNT: u32 = 1 << 0;
+pub const PREFLIGHT_ROUTER_PAUSED: u32 = 1 << 1;
+pub const PREFLIGHT_FAMILY_PAUSED: u32 = 1 << 2;
+pub const PREFLIGHT_ESCROW_DISABLED: u32 = 1 << 3;
+pub const PREFLIGHT_ZERO_AMOUNT: u32 = 1 << 4;
+pub const PREFLIGHT_MINT_INVALID: u32 = 1 << 5;
+pub const PREFLIGHT_MINT_PAUSED: u32 = 1 << 6;
+pub const PREFLIGHT_MINT_NOT_ALLOWED: u32 = 1 << 7;
+pub const PREFLIGHT_VAULTS_MISSING: u32 = 1 << 8;
+pub const PREFLIGHT_SPOKE_UNKNOWN: u32 = 1 << 9;
+pub const PREFLIGHT_SPOKE_PAUSED: u32 = 1 << 10;
+pub const PREFLIGHT_SPOKE_SUNSET: u32 = 1 << 11;
+pub const PREFLIGHT_MISSING_ATA: u32 = 1 << 12;
+pub const PREFLIGHT_ATA_FROZEN: u32 = 1 << 13;
+pub const PREFLIGHT_INSUFFICIENT_BALANCE: u32 = 1 << 14;
+pub const PREFLIGHT_NONCE_USED: u32 = 1 << 15;
+
+/// preflight result, written as Borsh return data.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PreflightReport {
+    /// PREFLIGHT_* bits; 0 when nothing would block the bridge.
+    pub problems: u32,
+    /// The user's ATA balance (0 when missing).
+    pub balance: u64,
+    /// Derived addresses the bridge must use.
+    pub from: Pubkey,
+    pub escrow: Pubkey,
+}
+
+/// Evaluate every preflight check; checks depending on a wrong account are skipped and
+/// only PREFLIGHT_WRONG_ACCOUNT is reported for them.
+fn preflight_route(
+    accounts: &Preflight,
+    route: &RouteDescriptor,
+    program_id: &Pubkey,
+    slot: u64,
+) -> Result<PreflightReport> {
+    let cfg = &accounts.config;
+    let mut problems = 0u32;
+    let mut flag = |bit: u32, hit: bool| {
+        if hit {
+            problems |= bit;
+        }
+    };
+    flag(PREFLIGHT_ROUTER_PAUSED, cfg.paused || cfg.killed);
+    flag(
+        PREFLIGHT_FAMILY_PAUSED,
+        cfg.family_paused(INSTRUCTION_FAMILY_BRIDGE)
+            || cfg.family_paused(INSTRUCTION_FAMILY_FORWARD),
+    );
+    flag(
+        PREFLIGHT_ESCROW_DISABLED,
+        !cfg.feature_enabled(FEATURE_ESCROW),
+    );
+    flag(PREFLIGHT_ZERO_AMOUNT, route.amount == 0);
+
+    let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, program_id).0;
+    let mint = route.mint;
+    let (from, _) = Pubkey::find_program_address(
+        &[route.user.as_ref(), token::ID.as_ref(), mint.as_ref()],
+        &anchor_spl::associated_token::ID,
+    );
+    let escrow = pda(&[
+        b"forward_escrow",
+        route.user.as_ref(),
+        &route.nonce.to_le_bytes(),
+    ]);
+    let expected = [
+        (accounts.mint.key(), mint),
+        (accounts.from.key(), from),
+        (accounts.escrow.key(), escrow),
+        (
+            accounts.mint_pause.key(),
+            pda(&[b"mint_pause", mint.as_ref()]),
+        ),
+        (accounts.mint_allowlist.key(), pda(&[b"mint_allowlist"])),
+        (
+            accounts.vault_registry.key(),
+            pda(&[b"vault_registry", mint.as_ref()]),
+        ),
+        (
+            accounts.hub_protocol_vault.key(),
+            pda(&[b"hub_protocol_vault", mint.as_ref()]),
+        ),
+        (
+            accounts.hub_relayer_vault.key(),
+            pda(&[b"hub_relayer_vault", mint.as_ref()]),
+        ),
+    ];
+    let report = |problems: u32, balance: u64| PreflightReport {
+        problems,
+        balance,
+        from,
+        escrow,
+    };
+    if expected.iter().any(|(passed, want)| passed != want) {
+        return Ok(report(problems | PREFLIGHT_WRONG_ACCOUNT, 0));
+    }
+
+    let mint_ai = accounts.mint.to_account_info();
+    let mint_ok = mint_ai.owner == &token::ID
+        && Mint::try_deserialize(&mut &mint_ai.try_borrow_data()?[..])
+            .is_ok_and(|m| m.is_initialized);
+    flag(PREFLIGHT_MINT_INVALID, !mint_ok);
+    flag(
+        PREFLIGHT_MINT_PAUSED,
+        require_mint_unpaused(&accounts.mint_pause, program_id).is_err(),
+    );
+    flag(
+        PREFLIGHT_MINT_NOT_ALLOWED,
+        require_mint_allowed(&accounts.mint_allowlist, cfg, &mint, program_id).is_err(),
+    );
+    flag(
+        PREFLIGHT_VAULTS_MISSING,
+        require_vaults_initialized(
+            &accounts.vault_registry,
+            &accounts.hub_protocol_vault,
+            &accounts.hub_relayer_vault,
+            &mint,
+            program_id,
+        )
+        .is_err(),
+    );
+
+    let registry = &accounts.registry;
+    match registry.spokes[..registry.spokes_len as usize]
+        .iter()
+        .find(|s| s.spoke_id == route.spoke_id)
+    {
+        None => flag(PREFLIGHT_SPOKE_UNKNOWN, true),
+        Some(spoke) => {
+            flag(PREFLIGHT_SPOKE_PAUSED, !spoke.enabled || spoke.paused);
+            flag(
+                PREFLIGHT_SPOKE_SUNSET,
+                spoke_deprecation_active(spoke, slot).is_err(),
+            );
+        }
+    }
+
+    let from_ai = accounts.from.to_account_info();
+    let token_account = if from_ai.owner == &token::ID {
+        TokenAccount::try_deserialize(&mut &from_ai.try_borrow_data()?[..]).ok()
+    } else {
+        None
+    };
+    let balance = token_account.as_ref().map_or(0, |t| t.amount);
+    match token_account {
+        None => flag(PREFLIGHT_MISSING_ATA, true),
+        Some(t) => {
+            flag(PREFLIGHT_ATA_FROZEN, t.is_frozen());
+            flag(PREFLIGHT_INSUFFICIENT_BALANCE, t.amount < route.amount);
+        }
+    }
+    let escrow_ai = accounts.escrow.to_account_info();
+    flag(
+        PREFLIGHT_NONCE_USED,
+        escrow_ai.owner != &System::id() || !escrow_ai.data_is_empty(),
+    );
+    Ok(report(problems, balance))
+}
+
+/// Ok(true) while a spoke is inside its deprecation window, Ok(false) when not deprecated,
+/// SpokeDeprecated once the sunset slot is reached.
+pub fn spoke_deprecation_active(spoke: &SpokeEntry, slot: u64) -> Result<bool> {
+    if spoke.deprecated_at_slot == 0 {
+        return Ok(false);
+    }
+    if slot >= spoke.sunset_slot {
+        zpx_log!(
+            "spoke_deprecated",
+            spoke_id = spoke.spoke_id,
+            successor = spoke.successor_spoke_id,
+        );
+        return err!(ErrorCode::SpokeDeprecated);
+    }
+    Ok(true)
+}
+
+/// Compare the live program-data state against the pin recorded on the spoke.
+pub fn check_adapter_pin(
+    spoke: &SpokeEntry,
+    program_data_key: &Pubkey,
+    program_data: &ProgramData,
+) -> Result<()> {
+    require_keys_eq!(
+        *program_data_key,
+        spoke.adapter_program_data,
+        ErrorCode::AdapterCodeChanged
+    );
+    require!(
+        program_data.slot == spoke.adapter_deploy_slot
+            && program_data.upgrade_authority_address.unwrap_or_default()
+                == spoke.adapter_upgrade_authority,
+        ErrorCode::AdapterCodeChanged
+    );
+    Ok(())
+}
+
+/// Adapter allowlist gate used by the handlers; `devnet-relaxed` builds accept any adapter.
+/// An adapter is allowlisted when its AdapterEntry PDA is among `remaining`, or while it is
+/// still in the legacy Config.adapters array (until migrate_adapter moves it).
+fn adapter_allowed(
+    cfg: &Config,
+    remaining: &[AccountInfo],
+    program: &Pubkey,
+    program_id: &Pubkey,
+) -> bool {
+    cfg!(feature = "devnet-relaxed")
+        || is_allowed_adapter_cfg(cfg, program)
+        || adapter_entry_live(remaining, program, program_id)
+}
+
+/// Whether `remaining` holds the initialized AdapterEntry PDA of `program`.
+fn adapter_entry_live(remaining: &[AccountInfo], program: &Pubkey, program_id: &Pubkey) -> bool {
+    let (expected, _) = Pubkey::find_program_address(&[ADAPTER_SEED, program.as_ref()], program_id);
+    remaining
+        .iter()
+        .any(|ai| ai.key() == expected && ai.owner == program_id && !ai.data_is_empty())
+}
+
+fn is_allowed_adapter_cfg(cfg: &Config, program: &Pubkey) -> bool {
+    let len = cfg.adapters_len as usize;
+    for i in 0..len {
+        if cfg.adapters[i] == *program {
+            return true;
+        }
+    }
+    false
+}
+
+/// Swap-remove `program` from the legacy Config.adapters array; false when absent.
+fn remove_legacy_adapter(cfg: &mut Config, program: &Pubkey) -> bool {
+    let len = cfg.adapters_len as usize;
+    let Some(i) = cfg.adapters[..len].iter().position(|a| a == program) else {
+        return false;
+    };
+    let last = len - 1;
+    cfg.adapters[i] = cfg.adapters[last];
+    cfg.adapters[last] = Pubkey::default();
+    cfg.adapters_len -= 1;
+    true
+}
+
+/// Create the AdapterEntry PDA of `program`; AdapterAlreadyExists if it is already live.
+fn create_adapter_entry<'info>(
+    payer: &AccountInfo<'info>,
+    entry: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    program: &Pubkey,
+    slot: u64,
+    program_id: &Pubkey,
+) -> Result<()> {
+    let (expected, bump) =
+        Pubkey::find_program_address(&[ADAPTER_SEED, program.as_ref()], program_id);
+    require_keys_eq!(entry.key(), expected, ErrorCode::AdapterNotAllowed);
+    require!(entry.owner != program_id, ErrorCode::AdapterAlreadyExists);
+    create_pda_account(
+        payer,
+        entry,
+        system_program,
+        ADAPTER_ENTRY_SPACE,
+        &[ADAPTER_SEED, program.as_ref(), &[bump]],
+        program_id,
+    )?;
+    AdapterEntry {
+        program: *program,
+        activated_at_slot: slot,
+        bump,
+    }
+    .try_serialize(&mut &mut entry.try_borrow_mut_data()?[..])
+}
+
+/// Validate common preconditions used by UBT
+pub fn validate_common(
+    amount: u64,
+    payload_len: usize,
+    paused: bool,
+    src_chain_id: u64,
+) -> Result<()> {
+    require!(!paused, ErrorCode::Paused);
+    require!(src_chain_id != 0, ErrorCode::SrcChainNotSet);
+    require!(amount > 0, ErrorCode::ZeroAmount);
+    validate_payload_len(payload_len)
+}
+
+/// Reject schema descriptors that could never match a payload.
+fn check_payload_schema_config(schema: &PayloadSchema) -> Result<()> {
+    Ok(schema.to_core().check_config().map_err(ErrorCode::from)?)
+}
+
+/// Compression metadata of a bridge payload, checked against the decompressed size limit.
+pub fn payload_compression(payload: &[u8]) -> Result<Option<CompressedPayload>> {
+    Ok(payload::compression_header(payload).map_err(ErrorCode::from)?)
+}
+
+/// Validate an adapter payload against a spoke's schema descriptor.
+pub fn validate_payload_schema(schema: &PayloadSchema, payload: &[u8]) -> Result<()> {
+    Ok(schema
+        .to_core()
+        .validate(payload)
+        .map_err(ErrorCode::from)?)
+}
+
+/// Adapter interface: instruction name of the view-style payload check. Adapters return
+/// `[1]` via return data when the payload parses and the route is live.
+pub const ADAPTER_VALIDATE_PAYLOAD_IX: &str = "validate_payload";
+
+/// Anchor-encoded `validate_payload(payload: Vec<u8>)` instruction data.
+pub fn adapter_validate_payload_data(payload: &[u8]) -> Result<Vec<u8>> {
+    let discriminator = anchor_lang::solana_program::hash::hashv(&[
+        b"global:",
+        ADAPTER_VALIDATE_PAYLOAD_IX.as_bytes(),
+    ]);
+    // One allocation: discriminator(8) + borsh Vec<u8> (u32 length + bytes)
+    let mut data = Vec::with_capacity(8 + 4 + payload.len());
+    data.extend_from_slice(&discriminator.to_bytes()[..8]);
+    payload.serialize(&mut data)?;
+    Ok(data)
+}
+
+/// Adapter interface: instruction name of the batched dispatch forward_batch_via_spoke calls,
+/// `dispatch_batch(entries: Vec<BatchDispatch>, payload: Vec<u8>)`.
+pub const ADAPTER_DISPATCH_BATCH_IX: &str = "dispatch_batch";
+
+/// Anchor-encoded `dispatch_batch(entries, payload)` instruction data.
+pub fn adapter_dispatch_batch_data(entries: &[BatchDispatch], payload: &[u8]) -> Result<Vec<u8>> {
+    let discriminator = anchor_lang::solana_program::hash::hashv(&[
+        b"global:",
+        ADAPTER_DISPATCH_BATCH_IX.as_bytes(),
+    ]);
+    let mut data =
+        Vec::with_capacity(8 + 4 + entries.len() * BATCH_DISPATCH_LEN + 4 + payload.len());
+    data.extend_from_slice(&discriminator.to_bytes()[..8]);
+    entries.serialize(&mut data)?;
+    payload.serialize(&mut data)?;
+    Ok(data)
+}
+
+/// True when the adapter itself (not a nested CPI) set return data starting with 1.
+pub fn adapter_validation_passed(ret: Option<(Pubkey, Vec<u8>)>, adapter: &Pubkey) -> bool {
+    matches!(ret, Some((program, data)) if program == *adapter && data.first() == Some(&1))
+}
+
+/// Hook interface: instruction name the router calls on `config.bridge_hook` after a
+/// successful source-leg bridge.
+pub const BRIDGE_HOOK_IX: &str = "on_bridge_initiated";
+
+/// Arguments of the hook call, mirroring UniversalBridgeInitiated. The accounts are
+/// `[bridge_hook_authority (signer), user, mint]` followed by any trailing remaining accounts
+/// of the bridge, all forwarded without signer privileges.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BridgeHookData {
+    pub message_hash: [u8; 32],
+    pub global_route_id: [u8; 32],
+    pub payload_hash: [u8; 32],
+    pub user: Pubkey,
+    pub mint: Pubkey,
+    pub target: Pubkey,
+    pub forwarded_amount: u64,
+    pub protocol_fee: u64,
+    pub relayer_fee: u64,
+    pub src_chain_id: u64,
+    pub dst_chain_id: u64,
+    pub nonce: u64,
+}
+
+/// Borsh size of BridgeHookData: three hashes, three pubkeys, six u64s.
+pub const BRIDGE_HOOK_DATA_LEN: usize = 32 * 3 + 32 * 3 + 8 * 6;
+
+/// Anchor-encoded `on_bridge_initiated(data: BridgeHookData)` instruction data.
+pub fn bridge_hook_ix_data(data: &BridgeHookData) -> Result<Vec<u8>> {
+    let discriminator =
+        anchor_lang::solana_program::hash::hashv(&[b"global:", BRIDGE_HOOK_IX.as_bytes()]);
+    let mut ix_data = Vec::with_capacity(8 + BRIDGE_HOOK_DATA_LEN);
+    ix_data.extend_from_slice(&discriminator.to_bytes()[..8]);
+    data.serialize(&mut ix_data)?;
+    Ok(ix_data)
 }
 
-#[derive(Accounts)]
-pub struct BridgeWithAdapterCpi<'info> {
-    /// CHECK: adapter program to CPI into
-    pub adapter_program: UncheckedAccount<'info>,
-}
+/// CPI the registered bridge hook, signed by the [b"bridge_hook_authority"] PDA. A wrong or
+/// non-executable hook account is skipped in best-effort mode; an error returned by the hook
+/// itself still reverts the bridge (the runtime cannot isolate a failed CPI).
+fn invoke_bridge_hook<'info>(
+    cfg: &Config,
+    hook_program: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    authority_bump: u8,
+    user: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    extra: &[AccountInfo<'info>],
+    data: &BridgeHookData,
+) -> Result<()> {
+    if cfg.bridge_hook == Pubkey::default() {
+        return Ok(());
+    }
+    if hook_program.key() != cfg.bridge_hook || !hook_program.executable {
+        require!(cfg.bridge_hook_best_effort, ErrorCode::InvalidBridgeHook);
+        emit!(BridgeHookSkipped {
+            hook: cfg.bridge_hook,
+            message_hash: data.message_hash,
+        });
+        return Ok(());
+    }
+    let mut metas = Vec::with_capacity(3 + extra.len());
+    metas.push(AccountMeta::new_readonly(authority.key(), true));
+    metas.push(AccountMeta::new_readonly(user.key(), false));
+    metas.push(AccountMeta::new_readonly(mint.key(), false));
+    metas.extend(extra.iter().map(|ai| AccountMeta {
+        pubkey: ai.key(),
+        is_signer: false,
+        is_writable: ai.is_writable,
+    }));
+    let mut infos = Vec::with_capacity(4 + extra.len());
+    infos.extend([authority.clone(), user.clone(), mint.clone()]);
+    infos.extend_from_slice(extra);
+    infos.push(hook_program.clone());
+    let ix = anchor_lang::solana_program::instruction::Instruction {
+        program_id: cfg.bridge_hook,
+        accounts: metas,
+        data: bridge_hook_ix_data(data)?,
+    };
+    invoke_signed(
+        &ix,
+        &infos,
+        &[&[b"bridge_hook_authority", &[authority_bump]]],
+    )?;
+    emit!(BridgeHookInvoked {
+        hook: cfg.bridge_hook,
+        message_hash: data.message_hash,
+    });
+    Ok(())
+}
+
+/// Validate payload size only (exposed for tests)
+pub fn validate_payload_len(payload_len: usize) -> Result<()> {
+    Ok(payload::check_len(payload_len).map_err(ErrorCode::from)?)
+}
+
+// Extended unit tests to increase coverage for fee logic, PDA derivation, and validators.
+#[cfg(test)]
+mod extended_tests {
+    use super::*;
+    use anchor_lang::solana_program::pubkey::Pubkey;
+
+    #[test]
+    fn compute_fees_and_forward_ok() {
+        let amount = 100_000u64;
+        let protocol_fee = 5u64;
+        let relayer_fee = 50u64;
+        let (forward, total) =
+            compute_fees_and_forward(amount, protocol_fee, relayer_fee, 1000).unwrap();
+        assert_eq!(total, protocol_fee + relayer_fee);
+        assert_eq!(forward, amount - total);
+    }
+
+    #[test]
+    fn compute_fees_and_forward_protocol_too_high() {
+        let amount = 10_000u64;
+        // Make protocol_fee exceed the allowed cap by computation
+        let protocol_fee = ((amount as u128) * (FEE_CAP_BPS as u128) / 10_000u128) as u64 + 1;
+        let res = compute_fees_and_forward(amount, protocol_fee, 0, RELAYER_FEE_CAP_BPS);
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn adapter_budget_checks() {
+        // Syscalls unavailable: nothing to check
+        assert!(check_adapter_budget(0, 0, 200_000).is_ok());
+        assert!(check_adapter_budget(200_000 + ROUTER_POST_CPI_RESERVE_CU, 1, 200_000).is_ok());
+        assert!(
+            check_adapter_budget(200_000 + ROUTER_POST_CPI_RESERVE_CU - 1, 1, 200_000).is_err()
+        );
+        // Uncapped spokes still need the router reserve
+        assert!(check_adapter_budget(ROUTER_POST_CPI_RESERVE_CU - 1, 1, 0).is_err());
+        assert!(
+            check_adapter_budget(1_000_000, MAX_INVOKE_STACK_HEIGHT - ADAPTER_CPI_DEPTH, 0).is_ok()
+        );
+        assert!(check_adapter_budget(1_000_000, MAX_INVOKE_STACK_HEIGHT - 1, 0).is_err());
+    }
+
+    #[test]
+    fn payload_len_validation() {
+        assert!(validate_payload_len(0).is_ok());
+        assert!(validate_payload_len(512).is_ok());
+        assert!(validate_payload_len(513).is_err());
+    }
+
+    #[test]
+    fn adapter_allowlist_behavior() {
+        let program = Pubkey::new_unique();
+        let mut cfg = Config {
+            admin: Pubkey::default(),
+            fee_recipient: Pubkey::default(),
+            src_chain_id: 1,
+            relayer_fee_bps: 0,
+            protocol_fee_bps: 0,
+            relayer_pubkey: Pubkey::default(),
+            accept_any_token: false,
+            allowed_token_mint: Pubkey::default(),
+            direct_relayer_payout_default: false,
+            min_forward_amount: 0,
+            adapters_len: 0,
+            adapters: [Pubkey::default(); 8],
+            paused: false,
+            bump: 0,
+            max_relayer_fee_lamports: 0,
+            usd_limits_enabled: false,
+            strict_vaults: false,
+            lp_fee_bps: 0,
+            lp_vault_program: Pubkey::default(),
+            killed: false,
+            kill_release_slot: 0,
+            guardian: Pubkey::default(),
+            admin_op_keys: [0; ADMIN_OP_KEYS],
+            admin_op_cursor: 0,
+            legacy_message_hash: false,
+            features: FEATURES_DEFAULT,
+            bridge_hook: Pubkey::default(),
+            bridge_hook_best_effort: false,
+            namespace_len: 0,
+            namespace: [0; SANDBOX_NAMESPACE_LEN],
+            schema_version: CONFIG_SCHEMA_VERSION,
+            rent_refund_policy: RENT_REFUND_PAYER,
+            rent_refund_caller_bps: 0,
+            config_version: 0,
+            paused_families: 0,
+        };
+        assert!(!is_allowed_adapter_cfg(&cfg, &program));
+        cfg.adapters[0] = program;
+        cfg.adapters_len = 1;
+        assert!(is_allowed_adapter_cfg(&cfg, &program));
+    }
+
+    #[test]
+    fn legacy_adapters_swap_remove() {
+        let k = Pubkey::new_unique();
+        let mut cfg = build_config(k, k, 1, 0, 0, k, true, k, false, 0, 255).unwrap();
+        let [a, b, c] = [(); 3].map(|_| Pubkey::new_unique());
+        cfg.adapters[..3].copy_from_slice(&[a, b, c]);
+        cfg.adapters_len = 3;
+        assert!(remove_legacy_adapter(&mut cfg, &a));
+        assert_eq!(&cfg.adapters[..3], &[c, b, Pubkey::default()]);
+        assert_eq!(cfg.adapters_len, 2);
+        assert!(!remove_legacy_adapter(&mut cfg, &a));
+        assert!(remove_legacy_adapter(&mut cfg, &b));
+        assert!(remove_legacy_adapter(&mut cfg, &c));
+        assert_eq!(cfg.adapters_len, 0);
+        assert!(!is_allowed_adapter_cfg(&cfg, &c));
+    }
+
+    #[test]
+    fn rent_refund_shares_follow_the_policy() {
+        let k = Pubkey::new_unique();
+        let mut cfg = build_config(k, k, 1, 0, 0, k, true, k, false, 0, 255).unwrap();
+        assert_eq!(cfg.rent_refund_shares(2_039_280), (2_039_280, 0, 0));
+        cfg.rent_refund_policy = RENT_REFUND_TREASURY;
+        assert_eq!(cfg.rent_refund_shares(2_039_280), (0, 2_039_280, 0));
+        cfg.rent_refund_policy = RENT_REFUND_CALLER_SPLIT;
+        cfg.rent_refund_caller_bps = 2_500;
+        assert_eq!(cfg.rent_refund_shares(1_001), (751, 0, 250));
+        cfg.rent_refund_caller_bps = 10_000;
+        assert_eq!(cfg.rent_refund_shares(1_001), (0, 0, 1_001));
+    }
+
+    #[test]
+    fn versioned_accounts_reject_other_layouts() {
+        use schema::{Migration, SchemaError, VersionedAccount};
+        let k = Pubkey::new_unique();
+        let cfg = build_config(k, k, 1, 0, 0, k, true, k, false, 0, 255).unwrap();
+        let mut data = Vec::new();
+        cfg.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), CONFIG_SPACE);
+        assert_eq!(Config::migration(&data), Ok(Migration::Current));
+        assert_eq!(Config::try_from_account_data(&data).unwrap().admin, k);
+
+        // A pre-versioning deployment's layout ends before the version byte
+        let legacy = &data[..CONFIG_V1_SPACE - 1];
+        assert_eq!(
+            Config::migration(legacy),
+            Ok(Migration::Outdated { found: 0 })
+        );
+        assert_eq!(
+            Config::try_from_account_data(legacy).err(),
+            Some(SchemaError::UnsupportedVersion {
+                found: 0,
+                expected: CONFIG_SCHEMA_VERSION
+            })
+        );
+        data[CONFIG_V1_SPACE - 1] = CONFIG_SCHEMA_VERSION + 1;
+        assert_eq!(
+            Config::migration(&data),
+            Ok(Migration::Newer {
+                found: CONFIG_SCHEMA_VERSION + 1
+            })
+        );
+        // Config bytes are never read as a Registry
+        assert_eq!(
+            Registry::schema_version(&data),
+            Err(SchemaError::DiscriminatorMismatch)
+        );
+
+        let registry = Registry {
+            spokes_len: 0,
+            spokes: [SpokeEntry::default(); MAX_SPOKES],
+            bump: 0,
+            schema_version: REGISTRY_SCHEMA_VERSION,
+        };
+        let mut data = Vec::new();
+        registry.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), REGISTRY_SPACE);
+        assert!(Registry::try_from_account_data(&data).is_ok());
+        data.truncate(REGISTRY_SPACE - 1);
+        assert_eq!(
+            Registry::migration(&data),
+            Ok(Migration::Outdated { found: 0 })
+        );
+    }
+
+    #[test]
+    fn in_flight_exposure_cap() {
+        assert_eq!(in_flight_after_forward(10, 5, 0).unwrap(), 15);
+        assert_eq!(in_flight_after_forward(10, 5, 15).unwrap(), 15);
+        assert!(in_flight_after_forward(10, 6, 15).is_err());
+        assert!(in_flight_after_forward(u64::MAX, 1, 0).is_err());
+    }
+
+    #[test]
+    fn adapter_pin_detects_upgrade() {
+        let pd_key = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let mut spoke = SpokeEntry::default();
+        spoke.adapter_program_data = pd_key;
+        spoke.adapter_deploy_slot = 42;
+        spoke.adapter_upgrade_authority = authority;
+        let mut pd = ProgramData {
+            slot: 42,
+            upgrade_authority_address: Some(authority),
+        };
+        assert!(check_adapter_pin(&spoke, &pd_key, &pd).is_ok());
+        assert!(check_adapter_pin(&spoke, &Pubkey::new_unique(), &pd).is_err());
+        pd.slot = 43;
+        assert!(check_adapter_pin(&spoke, &pd_key, &pd).is_err());
+        pd.slot = 42;
+        pd.upgrade_authority_address = None;
+        assert!(check_adapter_pin(&spoke, &pd_key, &pd).is_err());
+    }
+
+    #[test]
+    fn zpx_fee_discount() {
+        // 1 ZPX base unit per 2 token base units, 20% discount
+        assert_eq!(zpx_fee_for(1_000, 500_000_000, 2_000).unwrap(), 400);
+        assert_eq!(zpx_fee_for(1_000, 500_000_000, 10_000).unwrap(), 0);
+        assert!(zpx_fee_for(1_000, 500_000_000, 10_001).is_err());
+        assert!(zpx_fee_for(u64::MAX, u64::MAX, 0).is_err());
+    }
 
-#[derive(Accounts)]
-#[instruction(message_hash: [u8; 32])]
-pub struct FinalizeMessageV1<'info> {
-    #[account(mut)]
-    pub relayer: Signer<'info>,
-    #[account(seeds=[b"zpx_config"], bump=config.bump)]
-    pub config: Account<'info, Config>,
-    /// CHECK: PDA verified & optionally created in handler
-    #[account(mut)]
-    pub replay: UncheckedAccount<'info>,
-    pub system_program: Program<'info, System>,
-}
+    #[test]
+    fn fee_recipient_history_wraps() {
+        let mut history = FeeRecipientHistory {
+            epoch: 0,
+            entries: [FeeRecipientEpoch::default(); FEE_RECIPIENT_HISTORY_LEN],
+            bump: 0,
+            grace_slots: 0,
+        };
+        let mut last = Pubkey::default();
+        for slot in 1..=(FEE_RECIPIENT_HISTORY_LEN as u64 + 2) {
+            last = Pubkey::new_unique();
+            history.record(last, slot);
+        }
+        assert_eq!(history.epoch, FEE_RECIPIENT_HISTORY_LEN as u64 + 2);
+        assert_eq!(history.head(), 2);
+        assert_eq!(history.entries[history.head()].fee_recipient, last);
+    }
 
-#[account]
-pub struct Replay {
-    pub processed: u8,
-}
+    #[test]
+    fn fee_recipient_grace_window() {
+        let (old, new) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut history = FeeRecipientHistory {
+            epoch: 0,
+            entries: [FeeRecipientEpoch::default(); FEE_RECIPIENT_HISTORY_LEN],
+            bump: 0,
+            grace_slots: 100,
+        };
+        history.entries[0].fee_recipient = old;
+        history.record(new, 1_000);
+        assert_eq!(history.accepting_epoch(&new, 1_000), Some(1));
+        assert_eq!(history.accepting_epoch(&old, 1_099), Some(0));
+        assert_eq!(history.accepting_epoch(&old, 1_100), None);
+        assert_eq!(history.accepting_epoch(&Pubkey::new_unique(), 1_000), None);
+    }
 
-/// SCHEMA FROZEN. Do not reorder/rename. Bump with V2 if changes are required.
-#[event]
-pub struct BridgeInitiated {
-    pub route_id: [u8; 32],
-    pub user: Pubkey,
-    pub token: Pubkey,
-    pub target: Pubkey,
-    pub forwarded_amount: u64,
-    pub protocol_fee: u64,
-    pub relayer_fee: u64,
-    pub payload_hash: [u8; 32],
-    pub src_chain_id: u16,
-    pub dst_chain_id: u16,
-    pub nonce: u64,
-}
+    #[test]
+    fn router_stats_buckets_and_mint_cap() {
+        let mut stats = RouterStats {
+            total_initiated: 0,
+            total_forwarded: 0,
+            volumes_len: 0,
+            volumes: [MintVolume::default(); STATS_MAX_MINTS],
+            days: [DayBucket::default(); STATS_DAY_BUCKETS],
+            failures: [0; STATS_FAILURE_CLASSES],
+            keeper: Pubkey::default(),
+            updated_at_slot: 0,
+            bump: 0,
+        };
+        let mint = Pubkey::new_unique();
+        stats.record(&mint, 10, STATS_KIND_INITIATED, 5);
+        stats.record(&mint, 15, STATS_KIND_FORWARDED, 6);
+        assert_eq!((stats.total_initiated, stats.total_forwarded), (1, 1));
+        assert_eq!(stats.volumes_len, 1);
+        assert_eq!(stats.volumes[0].volume, 25);
+        assert_eq!(stats.days[0].transfers, 2);
+        // Same ring slot a week later resets the bucket
+        let later = STATS_SLOTS_PER_DAY * STATS_DAY_BUCKETS as u64;
+        stats.record(&mint, 1, STATS_KIND_INITIATED, later);
+        assert_eq!(stats.days[0].day, STATS_DAY_BUCKETS as u64);
+        assert_eq!(stats.days[0].transfers, 1);
+        for _ in 0..STATS_MAX_MINTS + 2 {
+            stats.record(&Pubkey::new_unique(), 1, STATS_KIND_INITIATED, later);
+        }
+        assert_eq!(stats.volumes_len as usize, STATS_MAX_MINTS);
+    }
 
-/// SCHEMA FROZEN. Do not reorder/rename. Bump with V2 if changes are required.
-#[event]
-pub struct UniversalBridgeInitiated {
-    pub route_id: [u8; 32],
-    pub payload_hash: [u8; 32],
-    pub message_hash: [u8; 32],
-    pub global_route_id: [u8; 32],
-    pub user: Pubkey,
-    pub token: Pubkey,
-    pub target: Pubkey,
-    pub forwarded_amount: u64,
-    pub protocol_fee: u64,
-    pub relayer_fee: u64,
-    pub src_chain_id: u16,
-    pub dst_chain_id: u16,
-    pub nonce: u64,
-}
+    #[test]
+    fn replay_finalizer_layout() {
+        let relayer = Pubkey::new_unique();
+        let mut data = vec![0u8; REPLAY_SPACE];
+        // The shared guard writes markers the Anchor account type still reads
+        assert_eq!(Replay::DISCRIMINATOR, replay_guard::REPLAY_DISCRIMINATOR);
+        data[0..8].copy_from_slice(&Replay::DISCRIMINATOR);
+        data[replay_guard::REPLAY_FINALIZER].copy_from_slice(relayer.as_ref());
+        assert_eq!(replay_finalizer(&data), None);
+        data[8] = 1;
+        assert_eq!(replay_finalizer(&data), Some(relayer));
+        // Markers without the origin tx hash still carry the finalizer
+        assert_eq!(replay_finalizer(&data[..41]), Some(relayer));
+        // Legacy 9-byte markers carry no finalizer
+        assert_eq!(replay_finalizer(&data[..9]), None);
+    }
 
-/// SCHEMA FROZEN. Do not reorder/rename. Bump with V2 if changes are required.
-#[event]
-pub struct FeeAppliedSource {
-    pub message_hash: [u8; 32],
-    pub asset: Pubkey,
-    pub payer: Pubkey,
-    pub target: Pubkey,
-    pub protocol_fee: u64,
-    pub relayer_fee: u64,
-    pub fee_recipient: Pubkey,
-    pub applied_at: u64,
-}
+    #[test]
+    fn adapter_dry_run_encoding() {
+        let data = adapter_validate_payload_data(&[7, 8]).unwrap();
+        assert_eq!(data.len(), 8 + 4 + 2);
+        assert_eq!(&data[8..], &[2, 0, 0, 0, 7, 8]);
+        let adapter = Pubkey::new_unique();
+        assert!(adapter_validation_passed(
+            Some((adapter, vec![1])),
+            &adapter
+        ));
+        assert!(!adapter_validation_passed(
+            Some((adapter, vec![0])),
+            &adapter
+        ));
+        assert!(!adapter_validation_passed(
+            Some((Pubkey::new_unique(), vec![1])),
+            &adapter
+        ));
+        assert!(!adapter_validation_passed(None, &adapter));
+    }
 
-/// SCHEMA FROZEN. Do not reorder/rename. Bump with V2 if changes are required.
-#[event]
-pub struct FeeAppliedDest {
-    pub message_hash: [u8; 32],
-    pub src_chain_id: u16,
-    pub dst_chain_id: u16,
-    pub router: Pubkey,
-    pub asset: Pubkey,
-    pub amount: u64,
-    pub protocol_bps: u16,
-    pub lp_bps: u16,
-    pub collector: Pubkey,
-    pub applied_at: u64,
-}
+    #[test]
+    fn fee_split_shares_sum_to_fee() {
+        assert!(check_fee_split(&[], &[]).is_ok());
+        let r = [Pubkey::new_unique(), Pubkey::new_unique()];
+        assert!(check_fee_split(&r, &[7_000, 3_000]).is_ok());
+        assert!(check_fee_split(&r, &[7_000, 2_999]).is_err());
+        assert!(check_fee_split(&r, &[10_000]).is_err());
+        assert!(check_fee_split(&[r[0]; 5], &[2_000; 5]).is_err());
+        assert_eq!(fee_split_shares(101, &[7_000, 3_000])[..2], [70, 31]);
+        assert_eq!(fee_split_shares(0, &[5_000, 5_000])[..2], [0, 0]);
+        assert_eq!(fee_split_shares(u64::MAX, &[10_000])[..1], [u64::MAX]);
+        assert!(fee_split_shares(101, &[10_000])[1..]
+            .iter()
+            .all(|s| *s == 0));
+    }
 
-#[event]
-pub struct AdapterAdded {
-    pub admin: Pubkey,
-    pub program: Pubkey,
-}
-#[event]
-pub struct AdapterRemoved {
-    pub admin: Pubkey,
-    pub program: Pubkey,
-}
-#[event]
-pub struct ConfigUpdated {
-    pub admin: Pubkey,
-    pub fee_recipient: Pubkey,
-    pub src_chain_id: u64,
-    pub relayer_fee_bps: u16,
-}
+    #[test]
+    fn kill_release_requires_schedule_and_delay() {
+        assert!(!kill_release_ready(0, u64::MAX));
+        assert!(!kill_release_ready(100, 99));
+        assert!(kill_release_ready(100, 100));
+        // Unset sentinel falls back to a key that can never sign
+        if option_env!("ZPX_KILL_SENTINEL").is_none() {
+            assert_eq!(kill_sentinel(), Pubkey::default());
+        }
+    }
 
-/// Exposed schema snapshots (field names and order) for tests and tooling
-pub const BRIDGE_INITIATED_FIELDS: &[&str] = &[
-    "route_id",
-    "user",
-    "token",
-    "target",
-    "forwarded_amount",
-    "protocol_fee",
-    "relayer_fee",
-    "payload_hash",
-    "src_chain_id",
-    "dst_chain_id",
-    "nonce",
-];
+    #[test]
+    fn fallback_route_used_only_while_primary_unusable() {
+        let spoke = |spoke_id, fallback_spoke_id| SpokeEntry {
+            spoke_id,
+            enabled: true,
+            fallback_spoke_id,
+            ..SpokeEntry::default()
+        };
+        let mut spokes = [spoke(1, 2), spoke(2, 3), spoke(3, 0)];
+        assert_eq!(resolve_spoke_route(&spokes, 1, 10), Some(1));
+        spokes[0].paused = true;
+        assert_eq!(resolve_spoke_route(&spokes, 1, 10), Some(2));
+        // Fallbacks are not chained
+        spokes[1].paused = true;
+        assert_eq!(resolve_spoke_route(&spokes, 1, 10), None);
+        spokes[1].paused = false;
+        spokes[1].deprecated_at_slot = 5;
+        spokes[1].sunset_slot = 8;
+        assert_eq!(resolve_spoke_route(&spokes, 1, 10), None);
+        assert_eq!(resolve_spoke_route(&spokes, 3, 10), Some(3));
+        assert_eq!(resolve_spoke_route(&spokes, 9, 10), None);
+    }
 
-pub const UNIVERSAL_BRIDGE_INITIATED_FIELDS: &[&str] = &[
-    "route_id",
-    "payload_hash",
-    "message_hash",
-    "global_route_id",
-    "user",
-    "token",
-    "target",
-    "forwarded_amount",
-    "protocol_fee",
-    "relayer_fee",
-    "src_chain_id",
-    "dst_chain_id",
-    "nonce",
-];
+    #[test]
+    fn adapter_target_snapshot_bounds_outflow_and_delegate() {
+        let snap = |amount, delegate| AdapterTargetSnapshot { amount, delegate };
+        let thief = Some(Pubkey::new_unique());
+        assert!(snap(100, None).allows(&snap(40, None), 60));
+        assert!(snap(100, None).allows(&snap(150, None), 60));
+        assert!(!snap(100, None).allows(&snap(39, None), 60));
+        assert!(!snap(100, None).allows(&snap(100, thief), 60));
+        assert!(snap(100, thief).allows(&snap(100, thief), 60));
+        assert!(snap(100, thief).allows(&snap(100, None), 60));
+    }
 
-pub const FEE_APPLIED_SOURCE_FIELDS: &[&str] = &[
-    "message_hash",
-    "asset",
-    "payer",
-    "target",
-    "protocol_fee",
-    "relayer_fee",
-    "fee_recipient",
-    "applied_at",
-];
+    #[test]
+    fn route_availability_bits_follow_registry_position() {
+        let spoke = |spoke_id| SpokeEntry {
+            spoke_id,
+            enabled: true,
+            ..SpokeEntry::default()
+        };
+        let mut spokes = [spoke(7), spoke(3), spoke(9)];
+        spokes[1].paused = true;
+        spokes[2].deprecated_at_slot = 5;
+        spokes[2].sunset_slot = 20;
+        let mut avail = RouteAvailability {
+            flags: ROUTE_AVAILABLE_LIVE,
+            spokes_len: 0,
+            spoke_ids: [0; MAX_SPOKES],
+            spoke_bits: 0,
+            next_sunset_slot: 0,
+            updated_slot: 0,
+            bump: 0,
+        };
+        avail.set_spokes(&spokes, 10);
+        assert_eq!(avail.spoke_bits, 0b101);
+        assert_eq!(avail.next_sunset_slot, 20);
+        assert!(avail.is_available(7));
+        assert!(!avail.is_available(3));
+        assert!(!avail.is_available(42));
+        avail.set_spokes(&spokes, 20);
+        assert_eq!(avail.spoke_bits, 0b001);
+        assert_eq!(avail.next_sunset_slot, 0);
+        avail.flags = 0;
+        assert!(!avail.is_available(7));
+    }
 
-pub const FEE_APPLIED_DEST_FIELDS: &[&str] = &[
-    "message_hash",
-    "src_chain_id",
-    "dst_chain_id",
-    "router",
-    "asset",
-    "amount",
-    "protocol_bps",
-    "lp_bps",
-    "collector",
-    "applied_at",
-];
+    #[test]
+    fn category_pause_touches_only_matching_spokes() {
+        let mut spokes = [SpokeEntry::default(); 3];
+        spokes[0].category = SPOKE_CATEGORY_CCTP;
+        spokes[1].category = SPOKE_CATEGORY_WORMHOLE;
+        spokes[2].category = SPOKE_CATEGORY_CCTP;
+        assert_eq!(
+            set_category_paused(&mut spokes, SPOKE_CATEGORY_CCTP, true),
+            2
+        );
+        assert!(spokes[0].paused && !spokes[1].paused && spokes[2].paused);
+        assert_eq!(
+            set_category_paused(&mut spokes, SPOKE_CATEGORY_LAYERZERO, true),
+            0
+        );
+        assert_eq!(
+            set_category_paused(&mut spokes, SPOKE_CATEGORY_CCTP, false),
+            2
+        );
+        assert!(spokes.iter().all(|s| !s.paused));
+    }
 
-#[error_code]
-pub enum ErrorCode {
-    #[msg("Unauthorized")]
-    Unauthorized,
-    #[msg("Paused")]
-    Paused,
-    #[msg("Source chain id not set")]
-    SrcChainNotSet,
-    #[msg("Zero-amount not allowed")]
-    ZeroAmount,
-    #[msg("Payload too large")]
-    PayloadTooLarge,
-    #[msg("Protocol fee too high")]
-    ProtocolFeeTooHigh,
-    #[msg("Relayer fee too high")]
-    RelayerFeeTooHigh,
-    #[msg("Fees exceed amount")]
-    FeesExceedAmount,
-    #[msg("Adapter already exists")]
-    AdapterAlreadyExists,
-    #[msg("Adapter not allowed")]
-    AdapterNotAllowed,
-    #[msg("Adapter list full")]
-    AdapterListFull,
-    #[msg("Math overflow")]
-    MathOverflow,
-    #[msg("Invalid token program")]
-    InvalidTokenProgram,
-    #[msg("Chain id out of range for u16 emission")]
-    ChainIdOutOfRange,
-    #[msg("Invalid fee recipient ATA")]
-    InvalidFeeRecipientAta,
-    #[msg("Placeholder program id used; replace with real id")]
-    PlaceholderProgramId,
-    // New replay-guard specific errors
-    #[msg("Replay PDA does not match expected seeds")]
-    InvalidReplayPda,
-    #[msg("Replay account not owned by program")]
-    InvalidReplayOwner,
-    #[msg("Replay account too small")]
-    ReplayAccountTooSmall,
-    #[msg("Message has already been finalized (replay)")]
-    ReplayAlreadyProcessed,
-    #[msg("Computed hash mismatch")]
-    HashMismatch,
-    #[msg("Vault PDA does not match expected seeds")]
-    InvalidVaultPda,
-    #[msg("Vault account not owned by program")]
-    InvalidVaultOwner,
-}
+    #[test]
+    fn weighted_spoke_selection_rotates_by_nonce() {
+        let candidates = [(1u32, 3u16), (2, 0), (5, 1)];
+        let picks: Vec<u32> = (0..8)
+            .map(|n| select_weighted_spoke(&candidates, n).unwrap().spoke_id)
+            .collect();
+        assert_eq!(picks, vec![1, 1, 1, 5, 1, 1, 1, 5]);
+        let sel = select_weighted_spoke(&candidates, 3).unwrap();
+        assert_eq!((sel.ticket, sel.total_weight, sel.weight), (3, 4, 1));
+        assert_eq!(select_weighted_spoke(&[(1, 0)], 7), None);
+        assert_eq!(select_weighted_spoke(&[], 0), None);
+    }
+
+    #[test]
+    fn recipients_checked_against_destination_format() {
+        let check = |r: Recipient, domain: u32| {
+            recipient::check_for_domain(&r.to_core(), domain).map_err(|e| ErrorCode::from(e) as u32)
+        };
+        let mut evm = [0u8; 20];
+        evm[19] = 1;
+        assert_eq!(
+            check(Recipient::Evm20(evm), zpx_chains::CCTP_BASE.0),
+            Ok(())
+        );
+        assert_eq!(
+            check(Recipient::Evm20([0; 20]), zpx_chains::CCTP_BASE.0),
+            Err(ErrorCode::InvalidRecipient as u32)
+        );
+        // A Solana key sent to an EVM chain is the mis-encoding the raw word allowed
+        assert_eq!(
+            check(Recipient::Solana32([7; 32]), zpx_chains::CCTP_ETHEREUM.0),
+            Err(ErrorCode::RecipientFormatMismatch as u32)
+        );
+        assert_eq!(
+            check(Recipient::Evm20(evm), zpx_chains::CCTP_SOLANA.0),
+            Err(ErrorCode::RecipientFormatMismatch as u32)
+        );
+        assert_eq!(
+            check(Recipient::Solana32([7; 32]), zpx_chains::CCTP_SOLANA.0),
+            Ok(())
+        );
+        assert_eq!(check(Recipient::BtcScriptHash([9; 32]), 999), Ok(()));
+    }
+
+    #[test]
+    fn hash_backends_agree_and_pack_big_endian() {
+        use hash::{HashBackend, PureRust, Syscall};
+        let empty: [u8; 32] = [
+            0xc5, 0xd2, 0x46, 0x01, 0x86, 0xf7, 0x23, 0x3c, 0x92, 0x7e, 0x7d, 0xb2, 0xdc, 0xc7,
+            0x03, 0xc0, 0xe5, 0x00, 0xb6, 0x53, 0xca, 0x82, 0x27, 0x3b, 0x7b, 0xfa, 0xd8, 0x04,
+            0x5d, 0x85, 0xa4, 0x70,
+        ];
+        assert_eq!(PureRust::keccak256(&[]), empty);
+        assert_eq!(Syscall::keccak256(&[]), empty);
+        assert_eq!(
+            PureRust::keccak256(&[b"zoop", b"x"]),
+            Syscall::keccak256(&[b"zoopx"])
+        );
 
-// Hub-and-spoke constants
-const MAX_SPOKES: usize = 32;
-const SPOKE_METADATA_LEN: usize = 64;
+        let (adapter, recipient, asset, amount, payload) =
+            ([1u8; 32], [2u8; 32], [3u8; 32], [4u8; 32], [5u8; 32]);
+        let mut packed = Vec::new();
+        packed.extend_from_slice(&10u64.to_be_bytes());
+        for w in [adapter, recipient, asset, amount, payload] {
+            packed.extend_from_slice(&w);
+        }
+        packed.extend_from_slice(&7u64.to_be_bytes());
+        packed.extend_from_slice(&8453u64.to_be_bytes());
+        let legacy = HashScheme::Legacy;
+        let msg = message_hash_be(
+            legacy, 10, adapter, recipient, asset, amount, payload, 7, 8453,
+        );
+        assert_eq!(msg, keccak256(&[&packed]));
+        assert_ne!(
+            msg,
+            message_hash_be(legacy, 8453, adapter, recipient, asset, amount, payload, 7, 10)
+        );
+        assert_ne!(
+            global_route_id(legacy, 10, 8453, [9; 32], msg, 7),
+            global_route_id(legacy, 10, 8453, [9; 32], msg, 8)
+        );
 
-/// Compute and validate fees per caps; returns (forward_amount, total_fees)
-pub fn compute_fees_and_forward(
-    amount: u64,
-    protocol_fee: u64,
-    relayer_fee: u64,
-    relayer_bps_cap: u16,
-) -> Result<(u64, u64)> {
-    require!(amount > 0, ErrorCode::ZeroAmount);
-    // Protocol fee cap: 5 bps of amount
-    require!(
-        (protocol_fee as u128) * 10_000u128 <= (amount as u128) * (FEE_CAP_BPS as u128),
-        ErrorCode::ProtocolFeeTooHigh
-    );
-    if relayer_bps_cap > 0 {
-        require!(
-            (relayer_fee as u128) * 10_000u128 <= (amount as u128) * (relayer_bps_cap as u128),
-            ErrorCode::RelayerFeeTooHigh
+        // The domain-separated scheme prefixes the same packing with the separator
+        let scheme = HashScheme::domain([7; 32], hash::ENV_MAINNET, HASH_SCHEMA_VERSION);
+        let HashScheme::Domain(separator) = scheme else {
+            panic!("expected a domain scheme");
+        };
+        assert_eq!(
+            separator,
+            keccak256(&[
+                hash::DOMAIN_TAG,
+                &[7; 32],
+                &[hash::ENV_MAINNET, HASH_SCHEMA_VERSION]
+            ])
+        );
+        let separated = message_hash_be(
+            scheme, 10, adapter, recipient, asset, amount, payload, 7, 8453,
         );
+        assert_eq!(separated, keccak256(&[&separator, &packed]));
+        assert_ne!(separated, msg);
+        // Environments, hubs and versions never share hashes
+        for other in [
+            HashScheme::domain([7; 32], hash::ENV_DEVNET, HASH_SCHEMA_VERSION),
+            HashScheme::domain([8; 32], hash::ENV_MAINNET, HASH_SCHEMA_VERSION),
+            HashScheme::domain([7; 32], hash::ENV_MAINNET, HASH_SCHEMA_VERSION + 1),
+        ] {
+            assert_ne!(
+                message_hash_be(other, 10, adapter, recipient, asset, amount, payload, 7, 8453),
+                separated
+            );
+        }
+        assert_eq!(message_hash_scheme(0), HashScheme::Legacy);
     }
-    let total_fees = protocol_fee
-        .checked_add(relayer_fee)
-        .ok_or(ErrorCode::MathOverflow)?;
-    require!(total_fees <= amount, ErrorCode::FeesExceedAmount);
-    let forward_amount = amount - total_fees;
-    Ok((forward_amount, total_fees))
-}
 
-/// Spoke registry stored separately from Config. Fixed-size array-based registry for simplicity.
-#[account]
-pub struct Registry {
-    pub spokes_len: u8,
-    pub spokes: [SpokeEntry; MAX_SPOKES],
-    pub bump: u8,
-}
+    #[test]
+    fn route_plan_hash_commits_to_order_and_fields() {
+        let swap = RouteStep {
+            kind: 1,
+            program: Pubkey::new_unique(),
+            data_hash: [1; 32],
+        };
+        let bridge = RouteStep {
+            kind: 2,
+            program: Pubkey::new_unique(),
+            data_hash: [2; 32],
+        };
+        assert_eq!(route_plan_hash(&[]), [0u8; 32]);
+        let plan = route_plan_hash(&[swap, bridge]);
+        assert_eq!(plan, route_plan_hash(&[swap, bridge]));
+        assert_ne!(plan, route_plan_hash(&[bridge, swap]));
+        assert_ne!(plan, route_plan_hash(&[swap]));
+        let tampered = RouteStep {
+            data_hash: [3; 32],
+            ..bridge
+        };
+        assert_ne!(plan, route_plan_hash(&[swap, tampered]));
+        assert_eq!(
+            ROUTE_SESSION_SPACE,
+            8 + RouteSession {
+                user: Pubkey::default(),
+                mint: Pubkey::default(),
+                nonce: 0,
+                amount: 0,
+                target_token_account: Pubkey::default(),
+                plan_hash: [0; 32],
+                steps_expected: 0,
+                steps_len: 0,
+                steps: [RouteStep::default(); MAX_ROUTE_STEPS],
+                begin_slot: 0,
+                expiry_slot: 0,
+                bump: 0,
+            }
+            .try_to_vec()
+            .unwrap()
+            .len()
+        );
+    }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
-pub struct SpokeEntry {
-    pub spoke_id: u32,
-    pub adapter_program: Pubkey,
-    pub enabled: bool,
-    pub paused: bool,
-    pub direct_relayer_payout: bool,
-    pub version: u8,
-    pub metadata: [u8; SPOKE_METADATA_LEN],
-    pub created_at_slot: u64,
-}
+    #[test]
+    fn mint_allowlist_gate() {
+        let (usdc, usdt, other) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let mut cfg = build_config(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            1,
+            0,
+            0,
+            Pubkey::default(),
+            false,
+            usdc,
+            false,
+            0,
+            255,
+        )
+        .unwrap();
+        let (key, _) = Pubkey::find_program_address(&[b"mint_allowlist"], &crate::ID);
+        let mut list = MintAllowlist {
+            mints_len: 1,
+            mints: [Pubkey::default(); MAX_ALLOWED_MINTS],
+            bump: 0,
+        };
+        list.mints[0] = usdt;
+        let mut data = Vec::new();
+        list.try_serialize(&mut data).unwrap();
+        let mut lamports = 1;
+        let owner = crate::ID;
+        let ai = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+        assert!(require_mint_allowed(&ai, &cfg, &usdc, &crate::ID).is_ok());
+        assert!(require_mint_allowed(&ai, &cfg, &usdt, &crate::ID).is_ok());
+        assert!(require_mint_allowed(&ai, &cfg, &other, &crate::ID).is_err());
+        cfg.accept_any_token = true;
+        assert!(require_mint_allowed(&ai, &cfg, &other, &crate::ID).is_ok());
+    }
 
-impl Default for SpokeEntry {
-    fn default() -> Self {
-        SpokeEntry {
-            spoke_id: 0,
-            adapter_program: Pubkey::default(),
-            enabled: false,
-            paused: false,
-            direct_relayer_payout: false,
-            version: 0,
-            metadata: [0u8; SPOKE_METADATA_LEN],
+    #[test]
+    fn admin_idempotency_keys_ring() {
+        let k = Pubkey::default();
+        let mut cfg = build_config(k, k, 1, 0, 0, k, true, k, false, 0, 255).unwrap();
+        assert!(!cfg.replayed_admin_op(None).unwrap());
+        assert!(!cfg.replayed_admin_op(None).unwrap());
+        assert!(cfg.replayed_admin_op(Some(0)).is_err());
+        assert!(!cfg.replayed_admin_op(Some(42)).unwrap());
+        assert!(cfg.replayed_admin_op(Some(42)).unwrap());
+        // Once ADMIN_OP_KEYS newer keys are recorded the oldest is forgotten
+        for key in 100..100 + ADMIN_OP_KEYS as u64 {
+            assert!(!cfg.replayed_admin_op(Some(key)).unwrap());
+        }
+        assert!(!cfg.replayed_admin_op(Some(42)).unwrap());
+    }
+
+    #[test]
+    fn adapter_target_must_match_registered_owner() {
+        let custody = Pubkey::new_unique();
+        let mut spoke = SpokeEntry::default();
+        assert!(!adapter_target_ok(&spoke, &custody));
+        assert!(!adapter_target_ok(&spoke, &Pubkey::default()));
+        spoke.target_owner = custody;
+        assert!(adapter_target_ok(&spoke, &custody));
+        assert!(!adapter_target_ok(&spoke, &Pubkey::new_unique()));
+        assert_eq!(spoke.try_to_vec().unwrap().len(), SPOKE_ENTRY_LEN);
+    }
+
+    #[test]
+    fn escrow_expiry_follows_reminder_and_notice_period() {
+        let mut escrow = ForwardEscrow {
+            user: Pubkey::default(),
+            mint: Pubkey::default(),
+            spoke_id: 1,
+            amount: 10,
+            forwarded: 0,
+            dst_domain: 0,
+            mint_recipient: [0; 32],
+            nonce: 0,
+            lamport_budget: 0,
             created_at_slot: 0,
+            bump: 255,
+            cancelled: false,
+            relayer_tip: 0,
+            expiry_reminder_slot: 0,
+            held_protocol_fee: 0,
+            held_relayer_fee: 0,
+            fee_payee: Pubkey::default(),
+            fee_payee_direct: false,
+            recipient_tag: 0,
+        };
+        // No reminder: never expires
+        assert_eq!(escrow.expiry_at(u64::MAX), EscrowExpiry::Active);
+        escrow.expiry_reminder_slot = 100;
+        let expires_at_slot = 100 + ESCROW_EXPIRY_NOTICE_SLOTS;
+        assert_eq!(escrow.expiry_at(99), EscrowExpiry::Active);
+        assert_eq!(
+            escrow.expiry_at(100),
+            EscrowExpiry::ExpiringSoon { expires_at_slot }
+        );
+        assert_eq!(
+            escrow.expiry_at(expires_at_slot),
+            EscrowExpiry::Expired { expires_at_slot }
+        );
+        let mut data = Vec::new();
+        escrow.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), FORWARD_ESCROW_SPACE);
+    }
+
+    #[test]
+    fn spoke_imbalance_follows_outstanding_volume() {
+        let mut spoke = SpokeEntry {
+            dispatched_amount: 1_000,
+            ..SpokeEntry::default()
+        };
+        // No threshold: never imbalanced however far finalization lags
+        assert_eq!(spoke.outstanding(), 1_000);
+        assert!(!spoke.imbalanced());
+        spoke.imbalance_threshold = 500;
+        assert!(spoke.imbalanced());
+        spoke.finalized_amount = 500;
+        assert!(!spoke.imbalanced());
+        let event = spoke.imbalance_event();
+        assert_eq!((event.threshold, event.imbalanced), (500, false));
+        // Acks never push outstanding below zero
+        spoke.finalized_amount = 2_000;
+        assert_eq!(spoke.outstanding(), 0);
+    }
+
+    #[test]
+    fn feature_switches_gate_by_bit() {
+        let k = Pubkey::default();
+        let mut cfg = build_config(k, k, 1, 0, 0, k, true, k, false, 0, 255).unwrap();
+        for feature in 0..FEATURE_BALANCE_EVENTS {
+            assert!(cfg.require_feature(feature).is_ok());
         }
+        // Debug events ship dark
+        assert!(!cfg.feature_enabled(FEATURE_BALANCE_EVENTS));
+        cfg.features &= !(1 << FEATURE_AUTO_ROUTE);
+        assert!(cfg.require_feature(FEATURE_AUTO_ROUTE).is_err());
+        assert!(cfg.feature_enabled(FEATURE_ESCROW));
+        // Bits beyond the known features never enable anything
+        cfg.features = u64::MAX;
+        assert!(!cfg.feature_enabled(FEATURE_COUNT));
     }
-}
 
-/// Event emitted whenever a forward is executed via a spoke
-#[event]
-pub struct Forwarded {
-    pub user: Pubkey,
-    pub relayer: Pubkey,
-    pub spoke_id: u32,
-    pub adapter_program: Pubkey,
-    pub amount: u64,
-    pub protocol_fee: u64,
-    pub relayer_fee: u64,
-    pub net_amount: u64,
-    pub dst_domain: u32,
-    pub message_account: Pubkey,
-}
+    #[test]
+    fn relayer_activity_averages_latency_and_counts_breaches() {
+        let mut activity = RelayerActivity {
+            relayer: Pubkey::new_unique(),
+            forwards: 0,
+            total_latency_slots: 0,
+            max_latency_slots: 0,
+            sla_breaches: 0,
+            last_forward_slot: 0,
+            bump: 255,
+        };
+        assert_eq!(activity.average_latency_slots(), 0);
+        // No SLA configured: never a breach
+        assert!(!activity.record(500, 0, 1_000));
+        assert!(!activity.record(100, 150, 1_100));
+        assert!(activity.record(300, 150, 1_200));
+        assert_eq!(activity.forwards, 3);
+        assert_eq!(activity.average_latency_slots(), 300);
+        assert_eq!(activity.max_latency_slots, 500);
+        assert_eq!(activity.sla_breaches, 1);
+        assert_eq!(activity.last_forward_slot, 1_200);
+    }
 
-fn is_allowed_adapter_cfg(cfg: &Config, program: &Pubkey) -> bool {
-    let len = cfg.adapters_len as usize;
-    for i in 0..len {
-        if cfg.adapters[i] == *program {
-            return true;
+    #[test]
+    fn raw_token_amount_reads_packed_accounts() {
+        use anchor_lang::solana_program::program_pack::Pack;
+        let key = Pubkey::new_unique();
+        let owner = anchor_spl::token::ID;
+        let mut data = vec![0u8; anchor_spl::token::spl_token::state::Account::LEN];
+        anchor_spl::token::spl_token::state::Account {
+            mint: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            amount: 42,
+            state: anchor_spl::token::spl_token::state::AccountState::Initialized,
+            ..Default::default()
         }
+        .pack_into_slice(&mut data);
+        let mut lamports = 1;
+        let ai = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+        assert_eq!(raw_token_amount(&ai), 42);
+        // Closed accounts have no data left
+        let mut empty = Vec::new();
+        let mut lamports = 0;
+        let ai = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut empty,
+            &owner,
+            false,
+            0,
+        );
+        assert_eq!(raw_token_amount(&ai), 0);
     }
-    false
-}
 
-/// Validate common preconditions used by UBT
-pub fn validate_common(
-    amount: u64,
-    payload_len: usize,
-    paused: bool,
-    src_chain_id: u64,
-) -> Result<()> {
-    require!(!paused, ErrorCode::Paused);
-    require!(src_chain_id != 0, ErrorCode::SrcChainNotSet);
-    require!(amount > 0, ErrorCode::ZeroAmount);
-    require!(payload_len <= 512, ErrorCode::PayloadTooLarge);
-    Ok(())
-}
+    #[test]
+    fn token_program_checked_by_key() {
+        let loader = anchor_lang::solana_program::bpf_loader::ID;
+        let fake = Pubkey::new_unique();
+        for (key, executable, ok) in [
+            (Token::id(), true, true),
+            (TOKEN_2022_PROGRAM_ID, true, true),
+            (fake, true, false),
+            // A data account parked at the real address is not a program
+            (Token::id(), false, false),
+        ] {
+            let (mut lamports, mut data) = (1, Vec::new());
+            let ai = AccountInfo::new(
+                &key,
+                false,
+                false,
+                &mut lamports,
+                &mut data,
+                &loader,
+                executable,
+                0,
+            );
+            assert_eq!(assert_token_program(&ai).is_ok(), ok);
+            if !ok {
+                assert_eq!(
+                    token_cpi_program(&ai).unwrap_err(),
+                    anchor_lang::error::Error::from(ErrorCode::InvalidTokenProgram)
+                );
+            }
+        }
+    }
 
-/// Validate payload size only (exposed for tests)
-pub fn validate_payload_len(payload_len: usize) -> Result<()> {
-    require!(payload_len <= 512, ErrorCode::PayloadTooLarge);
-    Ok(())
-}
+    #[test]
+    fn user_transfer_index_wraps_newest_first() {
+        let mut index = UserTransferIndex {
+            user: Pubkey::new_unique(),
+            head: 0,
+            total: 0,
+            receipts: [TransferReceipt::default(); USER_INDEX_LEN],
+            bump: 0,
+        };
+        assert_eq!(index.recent().count(), 0);
+        for slot in 0..(USER_INDEX_LEN as u64 + 3) {
+            index.push(TransferReceipt {
+                slot,
+                ..TransferReceipt::default()
+            });
+        }
+        assert_eq!(index.total, USER_INDEX_LEN as u64 + 3);
+        let slots: Vec<u64> = index.recent().map(|r| r.slot).collect();
+        assert_eq!(slots.len(), USER_INDEX_LEN);
+        assert_eq!(slots[0], USER_INDEX_LEN as u64 + 2);
+        assert_eq!(*slots.last().unwrap(), 3);
+        let mut data = Vec::new();
+        index.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), USER_INDEX_SPACE);
+    }
 
-// Extended unit tests to increase coverage for fee logic, PDA derivation, and validators.
-#[cfg(test)]
-mod extended_tests {
-    use super::*;
-    use anchor_lang::solana_program::pubkey::Pubkey;
+    #[test]
+    fn admin_audit_log_wraps_newest_first() {
+        let mut log = AdminAuditLog {
+            head: 0,
+            total: 0,
+            entries: [AdminAuditEntry::default(); ADMIN_AUDIT_LEN],
+            bump: 0,
+        };
+        for slot in 0..(ADMIN_AUDIT_LEN as u64 + 5) {
+            log.push(AdminAuditEntry {
+                slot,
+                ..AdminAuditEntry::default()
+            });
+        }
+        let slots: Vec<u64> = log.recent().map(|e| e.slot).collect();
+        assert_eq!(slots.len(), ADMIN_AUDIT_LEN);
+        assert_eq!(slots[0], ADMIN_AUDIT_LEN as u64 + 4);
+        assert_eq!(*slots.last().unwrap(), 5);
+        let mut data = Vec::new();
+        log.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), ADMIN_AUDIT_SPACE);
+    }
 
     #[test]
-    fn compute_fees_and_forward_ok() {
-        let amount = 100_000u64;
-        let protocol_fee = 5u64;
-        let relayer_fee = 50u64;
-        let (forward, total) =
-            compute_fees_and_forward(amount, protocol_fee, relayer_fee, 1000).unwrap();
-        assert_eq!(total, protocol_fee + relayer_fee);
-        assert_eq!(forward, amount - total);
+    fn forward_state_shares_add_up_to_staged_fees() {
+        let state = ForwardState {
+            escrow: Pubkey::new_unique(),
+            protocol_fee: 500,
+            relayer_fee: 7,
+            protocol_fee_applied: 0,
+            relayer_fee_applied: 0,
+            bump: 0,
+        };
+        let total = 1_000_000;
+        let mut forwarded = 0;
+        let (mut protocol, mut relayer) = (0, 0);
+        for chunk in [333_333, 333_333, 333_334] {
+            let (p, r) = state.share(total, forwarded, chunk).unwrap();
+            protocol += p;
+            relayer += r;
+            forwarded += chunk;
+        }
+        assert_eq!((protocol, relayer), (500, 7));
+        assert_eq!(state.share(total, 0, total).unwrap(), (500, 7));
+        assert!(state.share(total, 1, total).is_err());
     }
 
     #[test]
-    fn compute_fees_and_forward_protocol_too_high() {
-        let amount = 10_000u64;
-        // Make protocol_fee exceed the allowed cap by computation
-        let protocol_fee = ((amount as u128) * (FEE_CAP_BPS as u128) / 10_000u128) as u64 + 1;
-        let res = compute_fees_and_forward(amount, protocol_fee, 0, RELAYER_FEE_CAP_BPS);
-        assert!(res.is_err());
+    fn partner_rebate_is_a_floored_share_of_the_protocol_fee() {
+        let partner = Partner {
+            partner_id: 1,
+            payout: Pubkey::new_unique(),
+            rebate_bps: 2_500,
+            bump: 0,
+        };
+        assert_eq!(partner.rebate_of(500), 125);
+        assert_eq!(partner.rebate_of(3), 0);
+        assert_eq!(partner.rebate_of(u64::MAX), u64::MAX / 4);
+        let full = Partner {
+            rebate_bps: 10_000,
+            ..partner
+        };
+        assert_eq!(full.rebate_of(999), 999);
     }
 
     #[test]
-    fn payload_len_validation() {
-        assert!(validate_payload_len(0).is_ok());
-        assert!(validate_payload_len(512).is_ok());
-        assert!(validate_payload_len(513).is_err());
+    fn remote_config_needs_a_fresh_matching_root() {
+        let mut remote = RemoteConfig {
+            expected_root: [1; 32],
+            max_age_secs: 60,
+            ..RemoteConfig::default()
+        };
+        assert!(remote.check(1_000).is_err());
+        remote.verified_root = [2; 32];
+        remote.verified_block_time_us = 1_000 * 1_000_000;
+        remote.verified_slot = 5;
+        assert!(remote.check(1_000).is_err());
+        remote.verified_root = [1; 32];
+        assert!(remote.check(1_060).is_ok());
+        assert!(remote.check(1_061).is_err());
+        remote.clear_verified();
+        assert!(remote.check(1_000).is_err());
+
+        let set = ccq::GuardianSet {
+            index: 4,
+            keys: vec![[0; 20]; 19],
+            creation_time: 0,
+            expiration_time: 500,
+        };
+        assert_eq!(set.quorum(), 13);
+        assert!(set.active(499) && !set.active(500));
+        assert!(ccq::parse_eth_call(&[1, 0, 0], ccq::config_root_selector()).is_none());
     }
 
     #[test]
-    fn adapter_allowlist_behavior() {
-        let program = Pubkey::new_unique();
-        let mut cfg = Config {
-            admin: Pubkey::default(),
-            fee_recipient: Pubkey::default(),
-            src_chain_id: 1,
-            relayer_fee_bps: 0,
-            protocol_fee_bps: 0,
-            relayer_pubkey: Pubkey::default(),
-            accept_any_token: false,
-            allowed_token_mint: Pubkey::default(),
-            direct_relayer_payout_default: false,
-            min_forward_amount: 0,
-            adapters_len: 0,
-            adapters: [Pubkey::default(); 8],
-            paused: false,
+    fn config_history_resolves_version_at_slot() {
+        let mut history = ConfigHistory {
+            head: 0,
+            total: 0,
+            entries: [ConfigVersionEntry::default(); CONFIG_HISTORY_LEN],
             bump: 0,
         };
-        assert!(!is_allowed_adapter_cfg(&cfg, &program));
-        cfg.adapters[0] = program;
-        cfg.adapters_len = 1;
-        assert!(is_allowed_adapter_cfg(&cfg, &program));
+        // Version v written at slot 10 * v with v bps
+        for version in 1..=(CONFIG_HISTORY_LEN as u64 + 3) {
+            history.push(ConfigVersionEntry {
+                version,
+                slot: 10 * version,
+                protocol_fee_bps: version as u16,
+                ..ConfigVersionEntry::default()
+            });
+        }
+        assert_eq!(history.at_slot(55).unwrap().version, 5);
+        assert_eq!(history.at_slot(60).unwrap().version, 6);
+        assert_eq!(history.at_slot(u64::MAX).unwrap().protocol_fee_bps, 19);
+        // Versions 1..=3 have rotated out
+        assert!(history.at_slot(35).is_none());
+        assert!(history.version(3).is_none());
+        assert_eq!(history.version(4).unwrap().slot, 40);
+        let mut data = Vec::new();
+        history.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), CONFIG_HISTORY_SPACE);
+    }
+
+    #[test]
+    fn relayer_fees_vest_linearly_with_admin_override() {
+        let mut v = RelayerVesting {
+            relayer: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            locked: 0,
+            unlocked: 0,
+            start_slot: 100,
+            end_slot: 100,
+            claimed_total: 0,
+            bump: 0,
+        };
+        // No schedule: fees are claimable at once
+        v.accrue(50, 100, 0).unwrap();
+        assert_eq!((v.locked, v.unlocked), (0, 50));
+
+        v.accrue(1_000, 100, 100).unwrap();
+        assert_eq!(v.vested_at(100), 0);
+        assert_eq!(v.vested_at(150), 500);
+        assert_eq!(v.vested_at(10_000), 1_000);
+        v.checkpoint(125);
+        assert_eq!((v.locked, v.unlocked), (750, 300));
+
+        // A new fee re-spreads the remaining locked balance over a fresh period
+        v.accrue(250, 150, 100).unwrap();
+        assert_eq!((v.locked, v.unlocked), (750, 550));
+        assert_eq!((v.start_slot, v.end_slot), (150, 250));
+        assert_eq!(v.vested_at(200), 375);
+
+        assert_eq!(v.unlock_all(), 750);
+        assert_eq!((v.locked, v.unlocked), (0, 1_300));
+        assert_eq!(v.vested_at(151), 0);
+
+        let mut data = Vec::new();
+        v.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), RELAYER_VESTING_SPACE);
+    }
+
+    #[test]
+    fn rent_ledger_tracks_open_and_close() {
+        let mut ledger = RentLedger {
+            buckets: [RentBucket::default(); RENT_KINDS],
+            updated_at_slot: 0,
+            bump: 0,
+        };
+        ledger.record(RENT_KIND_ESCROW, 100, true, 1);
+        ledger.record(RENT_KIND_ESCROW, 100, true, 2);
+        ledger.record(RENT_KIND_ESCROW, 100, false, 3);
+        let b = ledger.buckets[RENT_KIND_ESCROW as usize];
+        assert_eq!(
+            (b.accounts, b.lamports, b.created, b.closed),
+            (1, 100, 2, 1)
+        );
+        // Closing an account opened before the ledger existed never underflows
+        ledger.record(RENT_KIND_VAULT, 50, false, 4);
+        let b = ledger.buckets[RENT_KIND_VAULT as usize];
+        assert_eq!((b.accounts, b.lamports, b.closed), (0, 0, 1));
+        assert_eq!(ledger.updated_at_slot, 4);
+        assert_eq!(RENT_LEDGER_SPACE, 8 + RENT_KINDS * 32 + 8 + 1);
+    }
+
+    #[test]
+    fn error_table_matches_enum() {
+        // Parse the #[error_code] enum from source so new variants can't be left out.
+        let src = include_str!("lib.rs");
+        let start = src.find("pub enum ErrorCode {").unwrap();
+        let body = &src[start..start + src[start..].find("\n}").unwrap()];
+        let mut parsed = Vec::new();
+        let mut msg = None;
+        for line in body.lines().map(str::trim) {
+            if let Some(m) = line.strip_prefix("#[msg(\"") {
+                msg = Some(m.trim_end_matches("\")]").to_string());
+            } else if let Some(name) = line.strip_suffix(',') {
+                parsed.push((name.to_string(), msg.take().unwrap()));
+            }
+        }
+        assert_eq!(parsed.len(), ERROR_CODES.len());
+        for (i, ((name, msg), (code, t_name, t_msg))) in
+            parsed.iter().zip(ERROR_CODES.iter()).enumerate()
+        {
+            assert_eq!(*code, anchor_lang::error::ERROR_CODE_OFFSET + i as u32);
+            assert_eq!(name, t_name);
+            assert_eq!(msg, t_msg);
+        }
+        assert_eq!(
+            u32::from(ErrorCode::Unauthorized),
+            ERROR_CODES[0].0,
+            "table codes must match Anchor's numbering"
+        );
+        assert_eq!(lookup_error_code(6001), Some(("Paused", "Paused")));
+        assert_eq!(lookup_error_code(42), None);
+    }
+
+    #[test]
+    fn payload_schema_validation() {
+        let open = PayloadSchema::default();
+        assert!(validate_payload_schema(&open, &[]).is_ok());
+
+        let schema = PayloadSchema {
+            min_len: 4,
+            max_len: 8,
+            require_version: true,
+            version: 2,
+            magic_len: 2,
+            magic: *b"ZP\0\0",
+            max_version: 0,
+        };
+        assert!(check_payload_schema_config(&schema).is_ok());
+        assert!(validate_payload_schema(&schema, b"ZP\x02x").is_ok());
+        assert!(validate_payload_schema(&schema, b"ZP\x02").is_err()); // too short
+        assert!(validate_payload_schema(&schema, b"ZP\x02xxxxxx").is_err()); // too long
+        assert!(validate_payload_schema(&schema, b"ZQ\x02x").is_err()); // bad magic
+        assert!(validate_payload_schema(&schema, b"ZP\x01x").is_err()); // bad version
+
+        // A version range accepts every version in between and rejects the rest
+        let ranged = PayloadSchema {
+            max_version: 4,
+            ..schema
+        };
+        assert!(check_payload_schema_config(&ranged).is_ok());
+        assert!(validate_payload_schema(&ranged, b"ZP\x04x").is_ok());
+        let unsupported = anchor_lang::error::Error::from(ErrorCode::UnsupportedPayloadVersion);
+        assert_eq!(
+            validate_payload_schema(&ranged, b"ZP\x05x").unwrap_err(),
+            unsupported
+        );
+        assert_eq!(
+            validate_payload_schema(&ranged, b"ZP\x01x").unwrap_err(),
+            unsupported
+        );
+        assert!(check_payload_schema_config(&PayloadSchema {
+            max_version: 1,
+            ..schema
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn compressed_payload_limits() {
+        assert_eq!(payload_compression(b"calldata").unwrap(), None);
+        let mut payload = COMPRESSED_PAYLOAD_MAGIC.to_vec();
+        payload.push(COMPRESSION_ZSTD);
+        payload.extend_from_slice(&MAX_DECOMPRESSED_PAYLOAD_LEN.to_le_bytes());
+        payload.extend_from_slice(&[0xaa; 12]);
+        let header = payload_compression(&payload).unwrap().unwrap();
+        assert_eq!(
+            (header.compressed_len, header.decompressed_len),
+            (12, MAX_DECOMPRESSED_PAYLOAD_LEN)
+        );
+        payload[5..9].copy_from_slice(&(MAX_DECOMPRESSED_PAYLOAD_LEN + 1).to_le_bytes());
+        assert_eq!(
+            payload_compression(&payload).unwrap_err(),
+            anchor_lang::error::Error::from(ErrorCode::DecompressedPayloadTooLarge)
+        );
+        payload[4] = 0;
+        assert_eq!(
+            payload_compression(&payload).unwrap_err(),
+            anchor_lang::error::Error::from(ErrorCode::UnsupportedPayloadCompression)
+        );
+    }
+
+    #[test]
+    fn spoke_deprecation_window() {
+        let mut spoke = SpokeEntry::default();
+        assert!(!spoke_deprecation_active(&spoke, 100).unwrap());
+        spoke.deprecated_at_slot = 100;
+        spoke.sunset_slot = 200;
+        spoke.successor_spoke_id = 7;
+        assert!(spoke_deprecation_active(&spoke, 150).unwrap());
+        assert!(spoke_deprecation_active(&spoke, 200).is_err());
+    }
+
+    #[test]
+    fn config_commitment_tracks_changes() {
+        let mut cfg = build_config(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            1,
+            0,
+            0,
+            Pubkey::default(),
+            false,
+            Pubkey::default(),
+            false,
+            0,
+            255,
+        )
+        .unwrap();
+        let mut registry = Registry {
+            spokes_len: 0,
+            spokes: [SpokeEntry::default(); MAX_SPOKES],
+            bump: 0,
+            schema_version: REGISTRY_SCHEMA_VERSION,
+        };
+        let a = config_commitment(&cfg, &registry).unwrap();
+        assert_eq!(a, config_commitment(&cfg, &registry).unwrap());
+        cfg.relayer_fee_bps = 10;
+        let b = config_commitment(&cfg, &registry).unwrap();
+        assert_ne!(a, b);
+        // Inactive slots beyond spokes_len don't affect the root
+        registry.spokes[3].spoke_id = 9;
+        assert_eq!(b, config_commitment(&cfg, &registry).unwrap());
+        registry.spokes_len = 1;
+        assert_ne!(b, config_commitment(&cfg, &registry).unwrap());
+    }
+
+    #[test]
+    fn spoke_relayer_allowlist_overrides_global_relayer() {
+        let (admin, global, partner) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let cfg = build_config(
+            admin,
+            Pubkey::new_unique(),
+            1,
+            0,
+            0,
+            global,
+            false,
+            Pubkey::default(),
+            false,
+            0,
+            255,
+        )
+        .unwrap();
+        assert!(relayer_allowed(&global, &cfg, None));
+        assert!(!relayer_allowed(&partner, &cfg, None));
+        let mut list = SpokeRelayers {
+            spoke_id: 1,
+            relayers_len: 1,
+            relayers: [Pubkey::default(); MAX_SPOKE_RELAYERS],
+            bump: 0,
+        };
+        list.relayers[0] = partner;
+        assert!(relayer_allowed(&partner, &cfg, Some(&list)));
+        assert!(!relayer_allowed(&global, &cfg, Some(&list)));
+        assert!(relayer_allowed(&admin, &cfg, Some(&list)));
+    }
+
+    #[test]
+    fn pyth_price_parsing_and_usd_conversion() {
+        let mut data = vec![0u8; 240];
+        data[0..4].copy_from_slice(&oracle::PYTH_MAGIC.to_le_bytes());
+        data[4..8].copy_from_slice(&oracle::PYTH_VERSION_2.to_le_bytes());
+        data[8..12].copy_from_slice(&oracle::PYTH_ATYPE_PRICE.to_le_bytes());
+        data[20..24].copy_from_slice(&(-8i32).to_le_bytes());
+        data[208..216].copy_from_slice(&100_000_000i64.to_le_bytes()); // $1.00
+        data[216..224].copy_from_slice(&50_000u64.to_le_bytes());
+        data[224..228].copy_from_slice(&oracle::PYTH_STATUS_TRADING.to_le_bytes());
+        data[232..240].copy_from_slice(&1_000u64.to_le_bytes());
+        let price = oracle::parse_pyth_price(&data).unwrap();
+        assert_eq!(price.expo, -8);
+        assert!(check_oracle_price(&price, 1_010, 20, 10).is_ok());
+        assert!(check_oracle_price(&price, 1_030, 20, 10).is_err()); // stale
+        assert!(check_oracle_price(&price, 1_010, 20, 1).is_err()); // conf 5 bps > 1
+                                                                    // 2.5 tokens with 6 decimals at $1.00 -> 2_500_000 micro-USD
+        assert_eq!(usd_value_e6(2_500_000, 6, &price).unwrap(), 2_500_000);
+        // 9-decimals mint
+        assert_eq!(usd_value_e6(1_000_000_000, 9, &price).unwrap(), 1_000_000);
+        data[0] = 0;
+        assert!(oracle::parse_pyth_price(&data).is_none());
     }
 
     #[test]
@@ -0,0 +1,410 @@
+//! Unchecked accounts the router accepts from relayers are checked for owner, size and rent
+//! before use: forward_via_spoke's message_account and pre-created replay markers.
+
+use anchor_lang::{AccountSerialize, Discriminator, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::{Instruction, InstructionError},
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    sysvar,
+    transaction::{Transaction, TransactionError},
+};
+use zpx_router::hash::message_hash_be;
+use zpx_router::{
+    message_hash_scheme, Config, ErrorCode, Registry, Replay, SpokeEntry, MAX_SPOKES,
+};
+
+fn anchor_account<T: AccountSerialize>(value: &T) -> Account {
+    let mut data = Vec::new();
+    value.try_serialize(&mut data).unwrap();
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: zpx_router::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn packed_account<T: Pack>(value: T) -> Account {
+    let mut data = vec![0u8; T::LEN];
+    value.pack_into_slice(&mut data);
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: spl_token::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> Account {
+    packed_account(spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        delegate: COption::None,
+        state: spl_token::state::AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    })
+}
+
+fn raw_account(owner: Pubkey, len: usize, lamports: Option<u64>) -> Account {
+    Account {
+        lamports: lamports.unwrap_or_else(|| Rent::default().minimum_balance(len)),
+        data: vec![1u8; len],
+        owner,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn config(admin: Pubkey, relayer: Pubkey, adapters: [Pubkey; 8], bump: u8) -> Config {
+    Config {
+        admin,
+        fee_recipient: Pubkey::new_unique(),
+        src_chain_id: 1,
+        relayer_fee_bps: 0,
+        protocol_fee_bps: 0,
+        relayer_pubkey: relayer,
+        accept_any_token: true,
+        allowed_token_mint: Pubkey::default(),
+        direct_relayer_payout_default: false,
+        min_forward_amount: 0,
+        adapters_len: adapters.iter().filter(|a| **a != Pubkey::default()).count() as u8,
+        adapters,
+        paused: false,
+        bump,
+        max_relayer_fee_lamports: 0,
+        usd_limits_enabled: false,
+        strict_vaults: false,
+        lp_fee_bps: 0,
+        lp_vault_program: Pubkey::default(),
+        killed: false,
+        kill_release_slot: 0,
+        guardian: Pubkey::default(),
+        admin_op_keys: [0; 8],
+        admin_op_cursor: 0,
+        legacy_message_hash: false,
+        features: zpx_router::FEATURES_DEFAULT,
+        bridge_hook: Pubkey::default(),
+        bridge_hook_best_effort: false,
+        namespace_len: 0,
+        namespace: [0; zpx_router::SANDBOX_NAMESPACE_LEN],
+        schema_version: zpx_router::CONFIG_SCHEMA_VERSION,
+        rent_refund_policy: zpx_router::RENT_REFUND_PAYER,
+        rent_refund_caller_bps: 0,
+        config_version: 0,
+        paused_families: 0,
+    }
+}
+
+fn custom(code: ErrorCode) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(code.into()))
+}
+
+#[tokio::test]
+async fn forward_checks_the_message_account() {
+    let program_id = zpx_router::ID;
+    let mut program_test =
+        ProgramTest::new("zpx_router", program_id, processor!(zpx_router::entry));
+
+    let admin = Keypair::new();
+    let relayer = Keypair::new();
+    let user = Keypair::new();
+    let adapter = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let from = Pubkey::new_unique();
+    let custody = Pubkey::new_unique();
+    let adapter_target = Pubkey::new_unique();
+    let spoke_id = 1u32;
+    let nonce = 1u64;
+    let mut evm_recipient = [0u8; 20];
+    evm_recipient[19] = 1;
+    let mut mint_recipient = [0u8; 32];
+    mint_recipient[12..].copy_from_slice(&evm_recipient);
+
+    let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &program_id);
+    let (config_key, config_bump) = pda(&[b"zpx_config"]);
+    let (registry, registry_bump) = pda(&[b"hub_registry"]);
+    let (hub_protocol_vault, _) = pda(&[b"hub_protocol_vault", mint.as_ref()]);
+    let (hub_relayer_vault, _) = pda(&[b"hub_relayer_vault", mint.as_ref()]);
+    let (rent_ledger, _) = pda(&[b"rent_ledger"]);
+    let (escrow, _) = pda(&[
+        b"forward_escrow",
+        user.pubkey().as_ref(),
+        &nonce.to_le_bytes(),
+    ]);
+    let (escrow_vault, _) = pda(&[b"forward_escrow_vault", escrow.as_ref()]);
+
+    program_test.add_account(
+        config_key,
+        anchor_account(&config(
+            admin.pubkey(),
+            relayer.pubkey(),
+            [Pubkey::default(); 8],
+            config_bump,
+        )),
+    );
+    let mut spokes = [SpokeEntry::default(); MAX_SPOKES];
+    spokes[0] = SpokeEntry {
+        spoke_id,
+        adapter_program: adapter,
+        enabled: true,
+        target_owner: custody,
+        ..SpokeEntry::default()
+    };
+    program_test.add_account(
+        registry,
+        anchor_account(&Registry {
+            spokes_len: 1,
+            spokes,
+            bump: registry_bump,
+            schema_version: zpx_router::REGISTRY_SCHEMA_VERSION,
+        }),
+    );
+    program_test.add_account(
+        mint,
+        packed_account(spl_token::state::Mint {
+            mint_authority: COption::None,
+            supply: 1_000_000,
+            decimals: 6,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        }),
+    );
+    program_test.add_account(from, token_account(mint, user.pubkey(), 1_000_000));
+    program_test.add_account(adapter_target, token_account(mint, custody, 0));
+    program_test.add_account(
+        hub_protocol_vault,
+        token_account(mint, hub_protocol_vault, 0),
+    );
+    program_test.add_account(hub_relayer_vault, token_account(mint, hub_relayer_vault, 0));
+    for wallet in [&user, &relayer] {
+        program_test.add_account(
+            wallet.pubkey(),
+            Account::new(10_000_000_000, 0, &solana_sdk::system_program::ID),
+        );
+    }
+    let undersized = Pubkey::new_unique();
+    let foreign = Pubkey::new_unique();
+    let underfunded = Pubkey::new_unique();
+    let adapter_message = Pubkey::new_unique();
+    program_test.add_account(undersized, raw_account(adapter, 4, None));
+    program_test.add_account(foreign, raw_account(Pubkey::new_unique(), 64, None));
+    program_test.add_account(underfunded, raw_account(adapter, 64, Some(1)));
+    program_test.add_account(adapter_message, raw_account(adapter, 64, None));
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let tx = |ix: Instruction, signer: &Keypair| {
+        Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&payer.pubkey()),
+            &[&payer, signer],
+            recent_blockhash,
+        )
+    };
+
+    let deposit = Instruction {
+        program_id,
+        accounts: zpx_router::accounts::DepositForwardEscrow {
+            user: user.pubkey(),
+            mint,
+            from,
+            escrow,
+            escrow_vault,
+            config: config_key,
+            rent_ledger,
+            token_program: spl_token::ID,
+            system_program: solana_sdk::system_program::ID,
+            rent: sysvar::rent::ID,
+        }
+        .to_account_metas(None),
+        data: zpx_router::instruction::DepositForwardEscrow {
+            nonce,
+            spoke_id,
+            amount: 1_000_000,
+            dst_domain: zpx_chains::CCTP_BASE.0,
+            recipient: zpx_router::Recipient::Evm20(evm_recipient),
+            lamport_budget: 0,
+            relayer_tip: 0,
+            expiry_reminder_slot: 0,
+        }
+        .data(),
+    };
+    banks_client
+        .process_transaction(tx(deposit, &user))
+        .await
+        .unwrap();
+
+    let forward = |message_account: Pubkey| {
+        let r = relayer.pubkey();
+        Instruction {
+            program_id,
+            accounts: zpx_router::accounts::ForwardViaSpoke {
+                user: user.pubkey(),
+                relayer: r,
+                mint,
+                escrow,
+                escrow_vault,
+                hub_protocol_vault,
+                hub_relayer_vault,
+                relayer_token_account: adapter_target,
+                adapter_target_token_account: adapter_target,
+                registry,
+                config: config_key,
+                message_account,
+                adapter_program_data: Pubkey::new_unique(),
+                vault_registry: pda(&[b"vault_registry", mint.as_ref()]).0,
+                spoke_relayers: pda(&[b"spoke_relayers", &spoke_id.to_le_bytes()]).0,
+                router_stats: pda(&[b"router_stats"]).0,
+                mint_pause: pda(&[b"mint_pause", mint.as_ref()]).0,
+                mint_allowlist: pda(&[b"mint_allowlist"]).0,
+                rent_ledger,
+                fee_policy: pda(&[
+                    b"fee_policy",
+                    mint.as_ref(),
+                    &zpx_chains::BASE.0.to_le_bytes(),
+                ])
+                .0,
+                relayer_cosign: pda(&[b"relayer_cosign", mint.as_ref()]).0,
+                forward_state: pda(&[b"forward_state", escrow.as_ref()]).0,
+                relayer_vesting_config: pda(&[b"relayer_vesting_config"]).0,
+                relayer_vesting: pda(&[b"relayer_vesting", r.as_ref(), mint.as_ref()]).0,
+                token_program: spl_token::ID,
+                system_program: solana_sdk::system_program::ID,
+                relayer_activity: pda(&[b"relayer_activity", r.as_ref()]).0,
+            }
+            .to_account_metas(None),
+            data: zpx_router::instruction::ForwardViaSpoke {
+                spoke_id,
+                amount: 1_000_000,
+                dst_domain: zpx_chains::CCTP_BASE.0,
+                mint_recipient,
+                is_protocol_fee: false,
+                is_relayer_fee: false,
+                _nonce: nonce,
+                relayer_fee_lamports: 0,
+                adapter_payload: vec![],
+            }
+            .data(),
+        }
+    };
+
+    for (message_account, code) in [
+        (undersized, ErrorCode::MessageAccountTooSmall),
+        (foreign, ErrorCode::MessageAccountOwnerMismatch),
+        (underfunded, ErrorCode::AccountNotRentExempt),
+    ] {
+        let err = banks_client
+            .process_transaction(tx(forward(message_account), &relayer))
+            .await
+            .unwrap_err()
+            .unwrap();
+        assert_eq!(err, custom(code));
+    }
+    banks_client
+        .process_transaction(tx(forward(adapter_message), &relayer))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn finalize_rejects_an_underfunded_replay_marker() {
+    let program_id = zpx_router::ID;
+    let mut program_test =
+        ProgramTest::new("zpx_router", program_id, processor!(zpx_router::entry));
+
+    let relayer = Keypair::new();
+    let src_adapter = Pubkey::new_unique();
+    let asset_mint = Pubkey::new_unique();
+    let (src_chain_id, dst_chain_id) = (8453u64, 1u64);
+    let payload_hash = [7u8; 32];
+    let (config_key, config_bump) = Pubkey::find_program_address(&[b"zpx_config"], &program_id);
+    let mut adapters = [Pubkey::default(); 8];
+    adapters[0] = src_adapter;
+    program_test.add_account(
+        config_key,
+        anchor_account(&config(
+            Pubkey::new_unique(),
+            relayer.pubkey(),
+            adapters,
+            config_bump,
+        )),
+    );
+    program_test.add_account(
+        relayer.pubkey(),
+        Account::new(10_000_000_000, 0, &solana_sdk::system_program::ID),
+    );
+
+    let mut amount_be = [0u8; 32];
+    amount_be[24..].copy_from_slice(&1_000u64.to_be_bytes());
+    let message_hash = message_hash_be(
+        message_hash_scheme(zpx_router::hash::HASH_SCHEMA_VERSION),
+        src_chain_id,
+        src_adapter.to_bytes(),
+        [0u8; 32],
+        asset_mint.to_bytes(),
+        amount_be,
+        payload_hash,
+        1,
+        dst_chain_id,
+    );
+    let (replay, _) = Pubkey::find_program_address(&[b"replay", &message_hash], &program_id);
+    // A pre-created, unprocessed marker that does not cover its own rent
+    let mut marker = raw_account(program_id, 9, Some(1));
+    marker.data[..8].copy_from_slice(&Replay::DISCRIMINATOR);
+    marker.data[8] = 0;
+    program_test.add_account(replay, marker);
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let finalize = Instruction {
+        program_id,
+        accounts: zpx_router::accounts::FinalizeMessageV1 {
+            relayer: relayer.pubkey(),
+            config: config_key,
+            replay,
+            rent_ledger: Pubkey::find_program_address(&[b"rent_ledger"], &program_id).0,
+            system_program: solana_sdk::system_program::ID,
+            route_validity: Pubkey::find_program_address(
+                &[b"route_validity", &src_chain_id.to_le_bytes()],
+                &program_id,
+            )
+            .0,
+        }
+        .to_account_metas(None),
+        data: zpx_router::instruction::FinalizeMessageV1 {
+            message_hash,
+            src_chain_id,
+            dst_chain_id,
+            forwarded_amount: 1_000,
+            nonce: 1,
+            payload_hash,
+            src_adapter,
+            asset_mint,
+            _initiator: Pubkey::default(),
+            origin_tx_hash: None,
+            validity: None,
+        }
+        .data(),
+    };
+    let err = banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[finalize],
+            Some(&payer.pubkey()),
+            &[&payer, &relayer],
+            recent_blockhash,
+        ))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::AccountNotRentExempt));
+}
@@ -0,0 +1,309 @@
+//! Adapter registry: allowlisted adapters live in AdapterEntry PDAs at [ADAPTER_SEED, program]
+//! that callers pass as remaining accounts. migrate_adapter moves legacy Config.adapters entries
+//! over, which stay allowlisted until then.
+
+use anchor_lang::{AccountDeserialize, AccountSerialize, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use zpx_router::{
+    AdapterEntry, Config, ErrorCode, FeeRecipientEpoch, FeeRecipientHistory, InFlight, ADAPTER_SEED,
+};
+
+fn anchor_account<T: AccountSerialize>(value: &T) -> Account {
+    let mut data = Vec::new();
+    value.try_serialize(&mut data).unwrap();
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: zpx_router::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn packed_account<T: Pack>(value: T) -> Account {
+    let mut data = vec![0u8; T::LEN];
+    value.pack_into_slice(&mut data);
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: spl_token::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> Account {
+    packed_account(spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        delegate: COption::None,
+        state: spl_token::state::AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    })
+}
+
+fn custom(code: ErrorCode) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(code.into()))
+}
+
+#[tokio::test]
+async fn legacy_adapters_migrate_to_pdas() {
+    let program_id = zpx_router::ID;
+    let mut program_test =
+        ProgramTest::new("zpx_router", program_id, processor!(zpx_router::entry));
+
+    let admin = Keypair::new();
+    let user = Keypair::new();
+    let mint = Pubkey::new_unique();
+    let from = Pubkey::new_unique();
+    let fee_recipient = Pubkey::new_unique();
+    let fee_recipient_ata =
+        anchor_spl::associated_token::get_associated_token_address(&fee_recipient, &mint);
+    let target_token_account = Pubkey::new_unique();
+    let target_adapter = Pubkey::new_unique();
+    let dst_chain_id = 10u64;
+
+    let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &program_id);
+    let (config, config_bump) = pda(&[b"zpx_config"]);
+    let (in_flight, in_flight_bump) =
+        pda(&[b"in_flight", mint.as_ref(), &dst_chain_id.to_le_bytes()]);
+    let (history, history_bump) = pda(&[b"fee_recipient_history"]);
+    let (bridge_hook_authority, _) = pda(&[b"bridge_hook_authority"]);
+    let (adapter_entry, _) = pda(&[ADAPTER_SEED, target_adapter.as_ref()]);
+
+    let mut adapters = [Pubkey::default(); 8];
+    adapters[0] = target_adapter;
+    program_test.add_account(
+        config,
+        anchor_account(&Config {
+            admin: admin.pubkey(),
+            fee_recipient,
+            src_chain_id: 1,
+            relayer_fee_bps: 0,
+            protocol_fee_bps: 5,
+            relayer_pubkey: Pubkey::default(),
+            accept_any_token: true,
+            allowed_token_mint: Pubkey::default(),
+            direct_relayer_payout_default: false,
+            min_forward_amount: 0,
+            adapters_len: 1,
+            adapters,
+            paused: false,
+            bump: config_bump,
+            max_relayer_fee_lamports: 0,
+            usd_limits_enabled: false,
+            strict_vaults: false,
+            lp_fee_bps: 0,
+            lp_vault_program: Pubkey::default(),
+            killed: false,
+            kill_release_slot: 0,
+            guardian: Pubkey::default(),
+            admin_op_keys: [0; 8],
+            admin_op_cursor: 0,
+            legacy_message_hash: false,
+            features: zpx_router::FEATURES_DEFAULT,
+            bridge_hook: Pubkey::default(),
+            bridge_hook_best_effort: false,
+            namespace_len: 0,
+            namespace: [0; zpx_router::SANDBOX_NAMESPACE_LEN],
+            schema_version: zpx_router::CONFIG_SCHEMA_VERSION,
+            rent_refund_policy: zpx_router::RENT_REFUND_PAYER,
+            rent_refund_caller_bps: 0,
+            config_version: 0,
+            paused_families: 0,
+        }),
+    );
+    program_test.add_account(
+        in_flight,
+        anchor_account(&InFlight {
+            mint,
+            dst_chain_id,
+            amount: 0,
+            exposure_cap: 0,
+            bump: in_flight_bump,
+        }),
+    );
+    let mut entries = [FeeRecipientEpoch::default(); 8];
+    entries[0].fee_recipient = fee_recipient;
+    program_test.add_account(
+        history,
+        anchor_account(&FeeRecipientHistory {
+            epoch: 0,
+            entries,
+            bump: history_bump,
+            grace_slots: 0,
+        }),
+    );
+    program_test.add_account(
+        mint,
+        packed_account(spl_token::state::Mint {
+            mint_authority: COption::None,
+            supply: 10_000_000,
+            decimals: 6,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        }),
+    );
+    program_test.add_account(from, token_account(mint, user.pubkey(), 10_000_000));
+    program_test.add_account(fee_recipient_ata, token_account(mint, fee_recipient, 0));
+    program_test.add_account(target_token_account, token_account(mint, target_adapter, 0));
+
+    program_test.add_account(
+        admin.pubkey(),
+        Account::new(10_000_000_000, 0, &solana_sdk::system_program::ID),
+    );
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let send = |ix: Instruction, signer: &Keypair| {
+        Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&payer.pubkey()),
+            &[&payer, signer],
+            recent_blockhash,
+        )
+    };
+    let bridge = |nonce: u64, with_entry: bool| {
+        let mut accounts = zpx_router::accounts::UniversalBridgeTransfer {
+            user: user.pubkey(),
+            rent_payer: payer.pubkey(),
+            mint,
+            from,
+            fee_recipient_ata,
+            target_token_account,
+            target_adapter_program: target_adapter,
+            config,
+            in_flight,
+            fee_recipient_history: history,
+            message: pda(&[b"message", user.pubkey().as_ref(), &nonce.to_le_bytes()]).0,
+            user_index: pda(&[b"user_index", user.pubkey().as_ref()]).0,
+            router_stats: pda(&[b"router_stats"]).0,
+            fee_split: pda(&[b"fee_split"]).0,
+            fee_policy: pda(&[b"fee_policy", mint.as_ref(), &dst_chain_id.to_le_bytes()]).0,
+            mint_pause: pda(&[b"mint_pause", mint.as_ref()]).0,
+            mint_allowlist: pda(&[b"mint_allowlist"]).0,
+            rent_ledger: pda(&[b"rent_ledger"]).0,
+            bridge_hook_program: Pubkey::default(),
+            bridge_hook_authority,
+            referral: Pubkey::default(),
+            partner_rebate: Pubkey::new_unique(),
+            partner_rebate_vault: Pubkey::new_unique(),
+            token_program: spl_token::ID,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None);
+        if with_entry {
+            accounts.push(AccountMeta::new_readonly(adapter_entry, false));
+        }
+        Instruction {
+            program_id,
+            accounts,
+            data: zpx_router::instruction::UniversalBridgeTransfer {
+                amount: 1_000_000,
+                protocol_fee: 500,
+                relayer_fee: 0,
+                payload: vec![],
+                dst_chain_id,
+                nonce,
+                store_preimage: false,
+                skip_receipt: true,
+            }
+            .data(),
+        }
+    };
+    let migrate = |adapter: Pubkey| Instruction {
+        program_id,
+        accounts: zpx_router::accounts::MigrateAdapter {
+            authority: admin.pubkey(),
+            config,
+            adapter_entry: pda(&[ADAPTER_SEED, adapter.as_ref()]).0,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zpx_router::instruction::MigrateAdapter { adapter }.data(),
+    };
+    let remove = Instruction {
+        program_id,
+        accounts: zpx_router::accounts::RemoveAdapter {
+            authority: admin.pubkey(),
+            config,
+            adapter_entry,
+        }
+        .to_account_metas(None),
+        data: zpx_router::instruction::RemoveAdapter {
+            adapter: target_adapter,
+            idempotency_key: None,
+        }
+        .data(),
+    };
+
+    // Still in the legacy array: no PDA needed
+    banks_client
+        .process_transaction(send(bridge(1, false), &user))
+        .await
+        .unwrap();
+
+    banks_client
+        .process_transaction(send(migrate(target_adapter), &admin))
+        .await
+        .unwrap();
+    let account = banks_client.get_account(config).await.unwrap().unwrap();
+    let cfg = Config::try_deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(cfg.adapters_len, 0);
+    let account = banks_client
+        .get_account(adapter_entry)
+        .await
+        .unwrap()
+        .unwrap();
+    let entry = AdapterEntry::try_deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(entry.program, target_adapter);
+    let err = banks_client
+        .process_transaction(send(migrate(Pubkey::new_unique()), &admin))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::AdapterNotLegacy));
+
+    // Migrated: the bridge must now pass the adapter's PDA
+    let err = banks_client
+        .process_transaction(send(bridge(2, false), &user))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::AdapterNotAllowed));
+    banks_client
+        .process_transaction(send(bridge(3, true), &user))
+        .await
+        .unwrap();
+
+    // Removal closes the PDA and returns its rent to the admin
+    let admin_before = banks_client.get_balance(admin.pubkey()).await.unwrap();
+    banks_client
+        .process_transaction(send(remove, &admin))
+        .await
+        .unwrap();
+    assert!(banks_client
+        .get_account(adapter_entry)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(banks_client.get_balance(admin.pubkey()).await.unwrap() > admin_before);
+    let err = banks_client
+        .process_transaction(send(bridge(4, true), &user))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::AdapterNotAllowed));
+}
@@ -0,0 +1,227 @@
+//! Adapter additions: propose_adapter records a pending adapter, activate_adapter allowlists it
+//! (creates its AdapterEntry PDA) only after ADAPTER_ACTIVATION_DELAY_SLOTS, and the guardian
+//! can veto a pending proposal.
+
+use anchor_lang::{AccountDeserialize, AccountSerialize, InstructionData, ToAccountMetas};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use zpx_router::{
+    AdapterEntry, Config, ErrorCode, ADAPTER_ACTIVATION_DELAY_SLOTS, ADAPTER_PROPOSAL_SEED,
+    ADAPTER_SEED,
+};
+
+fn anchor_account<T: AccountSerialize>(value: &T) -> Account {
+    let mut data = Vec::new();
+    value.try_serialize(&mut data).unwrap();
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: zpx_router::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn admin_config(admin: Pubkey, guardian: Pubkey, bump: u8) -> Config {
+    Config {
+        admin,
+        fee_recipient: Pubkey::new_unique(),
+        src_chain_id: 1,
+        relayer_fee_bps: 0,
+        protocol_fee_bps: 0,
+        relayer_pubkey: Pubkey::default(),
+        accept_any_token: true,
+        allowed_token_mint: Pubkey::default(),
+        direct_relayer_payout_default: false,
+        min_forward_amount: 0,
+        adapters_len: 0,
+        adapters: [Pubkey::default(); 8],
+        paused: false,
+        bump,
+        max_relayer_fee_lamports: 0,
+        usd_limits_enabled: false,
+        strict_vaults: false,
+        lp_fee_bps: 0,
+        lp_vault_program: Pubkey::default(),
+        killed: false,
+        kill_release_slot: 0,
+        guardian,
+        admin_op_keys: [0; 8],
+        admin_op_cursor: 0,
+        legacy_message_hash: false,
+        features: zpx_router::FEATURES_DEFAULT,
+        bridge_hook: Pubkey::default(),
+        bridge_hook_best_effort: false,
+        namespace_len: 0,
+        namespace: [0; zpx_router::SANDBOX_NAMESPACE_LEN],
+        schema_version: zpx_router::CONFIG_SCHEMA_VERSION,
+        rent_refund_policy: zpx_router::RENT_REFUND_PAYER,
+        rent_refund_caller_bps: 0,
+        config_version: 0,
+        paused_families: 0,
+    }
+}
+
+fn custom(code: ErrorCode) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(code.into()))
+}
+
+#[tokio::test]
+async fn adapter_additions_wait_out_the_timelock() {
+    let program_id = zpx_router::ID;
+    let mut program_test =
+        ProgramTest::new("zpx_router", program_id, processor!(zpx_router::entry));
+
+    let admin = Keypair::new();
+    let guardian = Keypair::new();
+    let stranger = Keypair::new();
+    let adapter = Pubkey::new_unique();
+    let vetoed = Pubkey::new_unique();
+    let (config, config_bump) = Pubkey::find_program_address(&[b"zpx_config"], &program_id);
+    let proposal_pda = |adapter: Pubkey| {
+        Pubkey::find_program_address(&[ADAPTER_PROPOSAL_SEED, adapter.as_ref()], &program_id).0
+    };
+    let entry_pda = |adapter: Pubkey| {
+        Pubkey::find_program_address(&[ADAPTER_SEED, adapter.as_ref()], &program_id).0
+    };
+    program_test.add_account(
+        config,
+        anchor_account(&admin_config(
+            admin.pubkey(),
+            guardian.pubkey(),
+            config_bump,
+        )),
+    );
+    for signer in [&admin, &guardian, &stranger] {
+        program_test.add_account(
+            signer.pubkey(),
+            Account::new(1_000_000_000, 0, &solana_sdk::system_program::ID),
+        );
+    }
+
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.insecure_clone();
+    let propose = |authority: Pubkey, adapter: Pubkey| Instruction {
+        program_id,
+        accounts: zpx_router::accounts::ProposeAdapter {
+            authority,
+            config,
+            proposal: proposal_pda(adapter),
+            adapter_entry: entry_pda(adapter),
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zpx_router::instruction::ProposeAdapter { adapter }.data(),
+    };
+    let activate = Instruction {
+        program_id,
+        accounts: zpx_router::accounts::ActivateAdapter {
+            authority: admin.pubkey(),
+            config,
+            proposal: proposal_pda(adapter),
+            proposer: admin.pubkey(),
+            adapter_entry: entry_pda(adapter),
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zpx_router::instruction::ActivateAdapter { adapter }.data(),
+    };
+    let send = |ix: Instruction, signer: &Keypair, blockhash| {
+        Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&payer.pubkey()),
+            &[&payer, signer],
+            blockhash,
+        )
+    };
+
+    // Only the admin proposes
+    let blockhash = context.last_blockhash;
+    let err = context
+        .banks_client
+        .process_transaction(send(
+            propose(stranger.pubkey(), adapter),
+            &stranger,
+            blockhash,
+        ))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::Unauthorized));
+
+    for pending in [adapter, vetoed] {
+        context
+            .banks_client
+            .process_transaction(send(propose(admin.pubkey(), pending), &admin, blockhash))
+            .await
+            .unwrap();
+    }
+    let err = context
+        .banks_client
+        .process_transaction(send(activate.clone(), &admin, blockhash))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::AdapterActivationLocked));
+
+    // The guardian vetoes the second proposal during the delay
+    context
+        .banks_client
+        .process_transaction(send(
+            Instruction {
+                program_id,
+                accounts: zpx_router::accounts::CancelAdapterProposal {
+                    authority: guardian.pubkey(),
+                    config,
+                    proposal: proposal_pda(vetoed),
+                    proposer: admin.pubkey(),
+                }
+                .to_account_metas(None),
+                data: zpx_router::instruction::CancelAdapterProposal { adapter: vetoed }.data(),
+            },
+            &guardian,
+            blockhash,
+        ))
+        .await
+        .unwrap();
+    assert!(context
+        .banks_client
+        .get_account(proposal_pda(vetoed))
+        .await
+        .unwrap()
+        .is_none());
+
+    context
+        .warp_to_slot(ADAPTER_ACTIVATION_DELAY_SLOTS + 2)
+        .unwrap();
+    let blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    context
+        .banks_client
+        .process_transaction(send(activate, &admin, blockhash))
+        .await
+        .unwrap();
+
+    let banks = &mut context.banks_client;
+    let account = banks.get_account(config).await.unwrap().unwrap();
+    let cfg = Config::try_deserialize(&mut &account.data[..]).unwrap();
+    // Activation creates the adapter's PDA; the legacy config array stays untouched
+    assert_eq!(cfg.adapters_len, 0);
+    let account = banks
+        .get_account(entry_pda(adapter))
+        .await
+        .unwrap()
+        .unwrap();
+    let entry = AdapterEntry::try_deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(entry.program, adapter);
+    assert!(banks
+        .get_account(proposal_pda(adapter))
+        .await
+        .unwrap()
+        .is_none());
+}
@@ -0,0 +1,197 @@
+//! Admin audit trail: once initialize_admin_audit_log has run, every admin-gated instruction
+//! must pass the AdminAuditLog PDA and appends (slot, discriminator, authority, params hash).
+
+use anchor_lang::{
+    AccountDeserialize, AccountSerialize, AnchorSerialize, Discriminator, InstructionData,
+    ToAccountMetas,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use zpx_router::{AdminAuditLog, Config, ErrorCode, ADMIN_AUDIT_SEED, FEATURE_ADMIN_AUDIT};
+
+fn anchor_account<T: AccountSerialize>(value: &T) -> Account {
+    let mut data = Vec::new();
+    value.try_serialize(&mut data).unwrap();
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: zpx_router::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn admin_config(admin: Pubkey, bump: u8) -> Config {
+    Config {
+        admin,
+        fee_recipient: Pubkey::new_unique(),
+        src_chain_id: 1,
+        relayer_fee_bps: 0,
+        protocol_fee_bps: 0,
+        relayer_pubkey: Pubkey::default(),
+        accept_any_token: true,
+        allowed_token_mint: Pubkey::default(),
+        direct_relayer_payout_default: false,
+        min_forward_amount: 0,
+        adapters_len: 0,
+        adapters: [Pubkey::default(); 8],
+        paused: false,
+        bump,
+        max_relayer_fee_lamports: 0,
+        usd_limits_enabled: false,
+        strict_vaults: false,
+        lp_fee_bps: 0,
+        lp_vault_program: Pubkey::default(),
+        killed: false,
+        kill_release_slot: 0,
+        guardian: Pubkey::default(),
+        admin_op_keys: [0; 8],
+        admin_op_cursor: 0,
+        legacy_message_hash: false,
+        features: zpx_router::FEATURES_DEFAULT,
+        bridge_hook: Pubkey::default(),
+        bridge_hook_best_effort: false,
+        namespace_len: 0,
+        namespace: [0; zpx_router::SANDBOX_NAMESPACE_LEN],
+        schema_version: zpx_router::CONFIG_SCHEMA_VERSION,
+        rent_refund_policy: zpx_router::RENT_REFUND_PAYER,
+        rent_refund_caller_bps: 0,
+        config_version: 0,
+        paused_families: 0,
+    }
+}
+
+fn custom(code: ErrorCode) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(code.into()))
+}
+
+#[tokio::test]
+async fn admin_ops_append_to_mandatory_audit_log() {
+    let program_id = zpx_router::ID;
+    let mut program_test =
+        ProgramTest::new("zpx_router", program_id, processor!(zpx_router::entry));
+
+    let admin = Keypair::new();
+    let (config, config_bump) = Pubkey::find_program_address(&[b"zpx_config"], &program_id);
+    let (audit_log, _) = Pubkey::find_program_address(&[ADMIN_AUDIT_SEED], &program_id);
+    program_test.add_account(
+        config,
+        anchor_account(&admin_config(admin.pubkey(), config_bump)),
+    );
+    program_test.add_account(
+        admin.pubkey(),
+        Account {
+            lamports: 1_000_000_000,
+            ..Account::default()
+        },
+    );
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let tx = |ix: Instruction| {
+        Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&payer.pubkey()),
+            &[&payer, &admin],
+            recent_blockhash,
+        )
+    };
+    let set_feature = |args: &zpx_router::instruction::SetFeature, with_log: bool| {
+        let mut accounts = zpx_router::accounts::SetFeature {
+            authority: admin.pubkey(),
+            config,
+        }
+        .to_account_metas(None);
+        if with_log {
+            accounts.push(AccountMeta::new(audit_log, false));
+        }
+        Instruction {
+            program_id,
+            accounts,
+            data: args.data(),
+        }
+    };
+
+    // Before the log exists admin instructions run without it
+    let disable_v2 = zpx_router::instruction::SetFeature {
+        feature: zpx_router::FEATURE_V2_EVENTS,
+        enabled: false,
+    };
+    banks_client
+        .process_transaction(tx(set_feature(&disable_v2, false)))
+        .await
+        .unwrap();
+
+    banks_client
+        .process_transaction(tx(Instruction {
+            program_id,
+            accounts: zpx_router::accounts::InitializeAdminAuditLog {
+                authority: admin.pubkey(),
+                config,
+                audit_log,
+                system_program: solana_sdk::system_program::ID,
+            }
+            .to_account_metas(None),
+            data: zpx_router::instruction::InitializeAdminAuditLog {}.data(),
+        }))
+        .await
+        .unwrap();
+    let account = banks_client.get_account(config).await.unwrap().unwrap();
+    let cfg = Config::try_deserialize(&mut &account.data[..]).unwrap();
+    assert!(cfg.feature_enabled(FEATURE_ADMIN_AUDIT));
+
+    // From now on the log is mandatory
+    let enable_v2 = zpx_router::instruction::SetFeature {
+        feature: zpx_router::FEATURE_V2_EVENTS,
+        enabled: true,
+    };
+    let err = banks_client
+        .process_transaction(tx(set_feature(&enable_v2, false)))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::AdminAuditLogMissing));
+
+    banks_client
+        .process_transaction(tx(set_feature(&enable_v2, true)))
+        .await
+        .unwrap();
+    let account = banks_client.get_account(audit_log).await.unwrap().unwrap();
+    let log = AdminAuditLog::try_deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(log.total, 2);
+    let entries: Vec<_> = log.recent().collect();
+    assert_eq!(
+        entries[0].instruction,
+        zpx_router::instruction::SetFeature::DISCRIMINATOR
+    );
+    assert_eq!(entries[0].authority, admin.pubkey());
+    assert_eq!(
+        entries[0].params_hash,
+        solana_sdk::hash::hash(&enable_v2.try_to_vec().unwrap()).to_bytes()
+    );
+    assert_eq!(
+        entries[1].instruction,
+        zpx_router::instruction::InitializeAdminAuditLog::DISCRIMINATOR
+    );
+
+    // Reading the trail is permissionless
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[Instruction {
+                program_id,
+                accounts: zpx_router::accounts::EmitAdminAudit { config, audit_log }
+                    .to_account_metas(None),
+                data: zpx_router::instruction::EmitAdminAudit { count: 8 }.data(),
+            }],
+            Some(&payer.pubkey()),
+            &[&payer],
+            recent_blockhash,
+        ))
+        .await
+        .unwrap();
+}
@@ -0,0 +1,81 @@
+//! Golden canonical-amount vectors shared with the EVM router. Routes with decimals
+//! normalization carry amounts as 18-decimal uint256s; the hex fixtures in
+//! tests/fixtures/amounts are the words both sides must produce, and each must convert back
+//! to the same raw SPL amount.
+//!
+//! Regenerate fixtures only for an intentional encoding change: `ZPX_BLESS=1 cargo test`.
+
+use zpx_router::hash::{message_hash_be, HashScheme, ENV_MAINNET, HASH_SCHEMA_VERSION};
+use zpx_router::zpx_core::amount::{from_canonical, to_canonical, AmountError};
+
+/// (fixture, raw amount, mint decimals)
+const VECTORS: &[(&str, u64, u8)] = &[
+    ("usdc_1", 1_000_000, 6),
+    ("sol_1_5", 1_500_000_000, 9),
+    ("dust_6", 1, 6),
+    ("zero_decimals_max", u64::MAX, 0),
+    ("eighteen_decimals", 123_456_789, 18),
+];
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn unhex(s: &str) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap();
+    }
+    out
+}
+
+/// The fixture's bytes, after checking (or with ZPX_BLESS, writing) it against `actual`.
+fn check(name: &str, actual: [u8; 32]) -> [u8; 32] {
+    let path = format!(
+        "{}/tests/fixtures/amounts/{name}.hex",
+        env!("CARGO_MANIFEST_DIR")
+    );
+    let actual = hex(&actual);
+    if std::env::var_os("ZPX_BLESS").is_some() {
+        std::fs::create_dir_all(std::path::Path::new(&path).parent().unwrap()).unwrap();
+        std::fs::write(&path, format!("{actual}\n")).unwrap();
+    }
+    let golden = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(actual, golden.trim(), "{name} vector changed");
+    unhex(golden.trim())
+}
+
+#[test]
+fn canonical_amounts_round_trip() {
+    for &(name, raw, decimals) in VECTORS {
+        let golden = check(name, to_canonical(raw, decimals).unwrap());
+        assert_eq!(from_canonical(&golden, decimals), Ok(raw), "{name}");
+    }
+}
+
+#[test]
+fn lossy_canonical_amounts_are_rejected() {
+    // 1 wei of an 18-decimal token is below a 6-decimal mint's unit
+    let one_wei = unhex(&format!("{:064x}", 1));
+    assert_eq!(from_canonical(&one_wei, 6), Err(AmountError::PrecisionLoss));
+    let max = check("zero_decimals_max", to_canonical(u64::MAX, 0).unwrap());
+    assert_eq!(from_canonical(&max, 1), Err(AmountError::Overflow));
+    assert_eq!(to_canonical(1, 19), Err(AmountError::UnsupportedDecimals));
+}
+
+#[test]
+fn normalized_message_hash_vector() {
+    let scheme = HashScheme::domain(zpx_router::ID.to_bytes(), ENV_MAINNET, HASH_SCHEMA_VERSION);
+    let msg = message_hash_be(
+        scheme,
+        10,
+        [1; 32],
+        [2; 32],
+        [3; 32],
+        to_canonical(1_000_000, 6).unwrap(),
+        [5; 32],
+        7,
+        8453,
+    );
+    check("mainnet_v1_message_e18", msg);
+}
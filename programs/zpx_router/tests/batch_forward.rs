@@ -0,0 +1,407 @@
+//! Batched forward: several escrows of one mint go out through a single aggregated transfer
+//! and one `dispatch_batch` CPI to the zpx_adapter_chaos adapter. Fees and net amounts sum
+//! the per-entry values, and an adapter failure reverts every entry.
+
+use anchor_lang::{AccountSerialize, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    sysvar,
+    transaction::{Transaction, TransactionError},
+};
+use zpx_adapter_chaos::{CHAOS_CONFIG_SEED, MODE_FAIL, MODE_PASS};
+use zpx_router::{BatchEntry, Config, ErrorCode, Registry, SpokeEntry, MAX_SPOKES};
+
+const AMOUNT: u64 = 1_000_000;
+const CHAOS_ERROR: u32 = 4242;
+
+fn anchor_account<T: AccountSerialize>(value: &T) -> Account {
+    let mut data = Vec::new();
+    value.try_serialize(&mut data).unwrap();
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: zpx_router::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn packed_account<T: Pack>(value: T) -> Account {
+    let mut data = vec![0u8; T::LEN];
+    value.pack_into_slice(&mut data);
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: spl_token::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> Account {
+    packed_account(spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        delegate: COption::None,
+        state: spl_token::state::AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    })
+}
+
+fn custom(code: u32) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(code))
+}
+
+async fn token_balance(banks_client: &mut BanksClient, account: Pubkey) -> u64 {
+    let account = banks_client.get_account(account).await.unwrap().unwrap();
+    spl_token::state::Account::unpack(&account.data)
+        .unwrap()
+        .amount
+}
+
+#[tokio::test]
+async fn batch_forwards_escrows_through_one_adapter_cpi() {
+    let program_id = zpx_router::ID;
+    let chaos = zpx_adapter_chaos::ID;
+    let mut program_test =
+        ProgramTest::new("zpx_router", program_id, processor!(zpx_router::entry));
+    program_test.add_program(
+        "zpx_adapter_chaos",
+        chaos,
+        processor!(zpx_adapter_chaos::entry),
+    );
+
+    let relayer = Keypair::new();
+    let user = Keypair::new();
+    let mint = Pubkey::new_unique();
+    let from = Pubkey::new_unique();
+    let custody = Pubkey::new_unique();
+    let adapter_target = Pubkey::new_unique();
+    let spoke_id = 1u32;
+    let mut evm_recipient = [0u8; 20];
+    evm_recipient[19] = 1;
+    let mut mint_recipient = [0u8; 32];
+    mint_recipient[12..].copy_from_slice(&evm_recipient);
+
+    let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &program_id);
+    let (config, config_bump) = pda(&[b"zpx_config"]);
+    let (registry, registry_bump) = pda(&[b"hub_registry"]);
+    let (hub_protocol_vault, _) = pda(&[b"hub_protocol_vault", mint.as_ref()]);
+    let (hub_relayer_vault, _) = pda(&[b"hub_relayer_vault", mint.as_ref()]);
+    let (hub_batch_vault, _) = pda(&[b"hub_batch_vault", mint.as_ref()]);
+    let (rent_ledger, _) = pda(&[b"rent_ledger"]);
+    let (chaos_config, _) = Pubkey::find_program_address(&[CHAOS_CONFIG_SEED], &chaos);
+    let escrow_of = |nonce: u64| {
+        let escrow = pda(&[
+            b"forward_escrow",
+            user.pubkey().as_ref(),
+            &nonce.to_le_bytes(),
+        ])
+        .0;
+        (escrow, pda(&[b"forward_escrow_vault", escrow.as_ref()]).0)
+    };
+
+    program_test.add_account(
+        config,
+        anchor_account(&Config {
+            admin: Pubkey::new_unique(),
+            fee_recipient: Pubkey::new_unique(),
+            src_chain_id: 1,
+            relayer_fee_bps: 100,
+            protocol_fee_bps: 5,
+            relayer_pubkey: relayer.pubkey(),
+            accept_any_token: true,
+            allowed_token_mint: Pubkey::default(),
+            direct_relayer_payout_default: false,
+            min_forward_amount: 0,
+            adapters_len: 0,
+            adapters: [Pubkey::default(); 8],
+            paused: false,
+            bump: config_bump,
+            max_relayer_fee_lamports: 0,
+            usd_limits_enabled: false,
+            strict_vaults: false,
+            lp_fee_bps: 0,
+            lp_vault_program: Pubkey::default(),
+            killed: false,
+            kill_release_slot: 0,
+            guardian: Pubkey::default(),
+            admin_op_keys: [0; 8],
+            admin_op_cursor: 0,
+            legacy_message_hash: false,
+            features: zpx_router::FEATURES_DEFAULT,
+            bridge_hook: Pubkey::default(),
+            bridge_hook_best_effort: false,
+            namespace_len: 0,
+            namespace: [0; zpx_router::SANDBOX_NAMESPACE_LEN],
+            schema_version: zpx_router::CONFIG_SCHEMA_VERSION,
+            rent_refund_policy: zpx_router::RENT_REFUND_PAYER,
+            rent_refund_caller_bps: 0,
+            config_version: 0,
+            paused_families: 0,
+        }),
+    );
+    let mut spokes = [SpokeEntry::default(); MAX_SPOKES];
+    spokes[0] = SpokeEntry {
+        spoke_id,
+        adapter_program: chaos,
+        enabled: true,
+        target_owner: custody,
+        ..SpokeEntry::default()
+    };
+    program_test.add_account(
+        registry,
+        anchor_account(&Registry {
+            spokes_len: 1,
+            spokes,
+            bump: registry_bump,
+            schema_version: zpx_router::REGISTRY_SCHEMA_VERSION,
+        }),
+    );
+    program_test.add_account(
+        mint,
+        packed_account(spl_token::state::Mint {
+            mint_authority: COption::None,
+            supply: 3 * AMOUNT,
+            decimals: 6,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        }),
+    );
+    program_test.add_account(from, token_account(mint, user.pubkey(), 3 * AMOUNT));
+    program_test.add_account(adapter_target, token_account(mint, custody, 0));
+    program_test.add_account(
+        hub_protocol_vault,
+        token_account(mint, hub_protocol_vault, 0),
+    );
+    program_test.add_account(hub_relayer_vault, token_account(mint, hub_relayer_vault, 0));
+    for wallet in [&user, &relayer] {
+        program_test.add_account(
+            wallet.pubkey(),
+            Account::new(10_000_000_000, 0, &solana_sdk::system_program::ID),
+        );
+    }
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let tx = |ix: Instruction, signer: &Keypair| {
+        Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&payer.pubkey()),
+            &[&payer, signer],
+            recent_blockhash,
+        )
+    };
+
+    banks_client
+        .process_transaction(tx(
+            Instruction {
+                program_id: chaos,
+                accounts: zpx_adapter_chaos::accounts::Initialize {
+                    authority: relayer.pubkey(),
+                    config: chaos_config,
+                    system_program: solana_sdk::system_program::ID,
+                }
+                .to_account_metas(None),
+                data: zpx_adapter_chaos::instruction::Initialize {}.data(),
+            },
+            &relayer,
+        ))
+        .await
+        .unwrap();
+    let configure = |mode: u8| {
+        tx(
+            Instruction {
+                program_id: chaos,
+                accounts: zpx_adapter_chaos::accounts::Configure {
+                    authority: relayer.pubkey(),
+                    config: chaos_config,
+                }
+                .to_account_metas(None),
+                data: zpx_adapter_chaos::instruction::Configure {
+                    mode,
+                    error_code: CHAOS_ERROR,
+                    burn_cu: 0,
+                    return_data_len: 0,
+                    succeed_after: 0,
+                }
+                .data(),
+            },
+            &relayer,
+        )
+    };
+
+    for nonce in 1..=3u64 {
+        let (escrow, escrow_vault) = escrow_of(nonce);
+        banks_client
+            .process_transaction(tx(
+                Instruction {
+                    program_id,
+                    accounts: zpx_router::accounts::DepositForwardEscrow {
+                        user: user.pubkey(),
+                        mint,
+                        from,
+                        escrow,
+                        escrow_vault,
+                        config,
+                        rent_ledger,
+                        token_program: spl_token::ID,
+                        system_program: solana_sdk::system_program::ID,
+                        rent: sysvar::rent::ID,
+                    }
+                    .to_account_metas(None),
+                    data: zpx_router::instruction::DepositForwardEscrow {
+                        nonce,
+                        spoke_id,
+                        amount: AMOUNT,
+                        dst_domain: zpx_chains::CCTP_BASE.0,
+                        recipient: zpx_router::Recipient::Evm20(evm_recipient),
+                        lamport_budget: 0,
+                        relayer_tip: 0,
+                        expiry_reminder_slot: 0,
+                    }
+                    .data(),
+                },
+                &user,
+            ))
+            .await
+            .unwrap();
+    }
+
+    let batch = |nonces: &[u64], amount: u64| {
+        let r = relayer.pubkey();
+        let mut accounts = zpx_router::accounts::ForwardBatchViaSpoke {
+            relayer: r,
+            mint,
+            hub_protocol_vault,
+            hub_relayer_vault,
+            hub_batch_vault,
+            relayer_token_account: adapter_target,
+            adapter_target_token_account: adapter_target,
+            registry,
+            config,
+            adapter_program: chaos,
+            adapter_program_data: Pubkey::new_unique(),
+            vault_registry: pda(&[b"vault_registry", mint.as_ref()]).0,
+            spoke_relayers: pda(&[b"spoke_relayers", &spoke_id.to_le_bytes()]).0,
+            router_stats: pda(&[b"router_stats"]).0,
+            mint_pause: pda(&[b"mint_pause", mint.as_ref()]).0,
+            mint_allowlist: pda(&[b"mint_allowlist"]).0,
+            rent_ledger,
+            relayer_cosign: pda(&[b"relayer_cosign", mint.as_ref()]).0,
+            relayer_vesting_config: pda(&[b"relayer_vesting_config"]).0,
+            relayer_vesting: pda(&[b"relayer_vesting", r.as_ref(), mint.as_ref()]).0,
+            relayer_activity: pda(&[b"relayer_activity", r.as_ref()]).0,
+            token_program: spl_token::ID,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None);
+        let mut entries = Vec::new();
+        for &nonce in nonces {
+            let (escrow, escrow_vault) = escrow_of(nonce);
+            accounts.push(AccountMeta::new(escrow, false));
+            accounts.push(AccountMeta::new(escrow_vault, false));
+            accounts.push(AccountMeta::new(user.pubkey(), false));
+            accounts.push(AccountMeta::new_readonly(
+                pda(&[
+                    b"fee_policy",
+                    mint.as_ref(),
+                    &zpx_chains::BASE.0.to_le_bytes(),
+                ])
+                .0,
+                false,
+            ));
+            entries.push(BatchEntry {
+                user: user.pubkey(),
+                nonce,
+                amount,
+                dst_domain: zpx_chains::CCTP_BASE.0,
+                mint_recipient,
+            });
+        }
+        accounts.push(AccountMeta::new_readonly(chaos_config, false));
+        tx(
+            Instruction {
+                program_id,
+                accounts,
+                data: zpx_router::instruction::ForwardBatchViaSpoke {
+                    spoke_id,
+                    entries,
+                    is_protocol_fee: true,
+                    is_relayer_fee: true,
+                    adapter_payload: vec![],
+                }
+                .data(),
+            },
+            &relayer,
+        )
+    };
+
+    // An entry that does not match its escrow is rejected
+    let err = banks_client
+        .process_transaction(batch(&[1, 2], AMOUNT - 1))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::EscrowMismatch.into()));
+
+    // A failing adapter reverts the whole batch: every escrow keeps its tokens
+    banks_client
+        .process_transaction(configure(MODE_FAIL))
+        .await
+        .unwrap();
+    let err = banks_client
+        .process_transaction(batch(&[1, 2, 3], AMOUNT))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(CHAOS_ERROR));
+    for nonce in 1..=3 {
+        assert_eq!(
+            token_balance(&mut banks_client, escrow_of(nonce).1).await,
+            AMOUNT
+        );
+    }
+
+    // 5 bps protocol + 100 bps relayer on each entry, moved in one transfer per destination
+    banks_client
+        .process_transaction(configure(MODE_PASS))
+        .await
+        .unwrap();
+    banks_client
+        .process_transaction(batch(&[1, 2, 3], AMOUNT))
+        .await
+        .unwrap();
+    let (protocol_fee, relayer_fee) = (500u64, 10_000u64);
+    assert_eq!(
+        token_balance(&mut banks_client, adapter_target).await,
+        3 * (AMOUNT - protocol_fee - relayer_fee)
+    );
+    assert_eq!(
+        token_balance(&mut banks_client, hub_protocol_vault).await,
+        3 * protocol_fee
+    );
+    assert_eq!(
+        token_balance(&mut banks_client, hub_relayer_vault).await,
+        3 * relayer_fee
+    );
+    assert_eq!(token_balance(&mut banks_client, hub_batch_vault).await, 0);
+    for nonce in 1..=3 {
+        let (escrow, escrow_vault) = escrow_of(nonce);
+        assert!(banks_client.get_account(escrow).await.unwrap().is_none());
+        assert!(banks_client
+            .get_account(escrow_vault)
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
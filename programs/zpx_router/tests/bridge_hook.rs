@@ -0,0 +1,299 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use anchor_lang::{AccountSerialize, AnchorDeserialize, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    program_error::ProgramError,
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use zpx_router::{
+    BridgeHookData, Config, ErrorCode, FeeRecipientEpoch, FeeRecipientHistory, InFlight,
+    BRIDGE_HOOK_DATA_LEN,
+};
+
+static HOOK_CALLS: AtomicU64 = AtomicU64::new(0);
+static HOOK_LAST_AMOUNT: AtomicU64 = AtomicU64::new(0);
+
+/// Partner hook: accepts only router-signed calls with the documented account layout.
+fn hook_processor(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let (authority, _) = Pubkey::find_program_address(&[b"bridge_hook_authority"], &zpx_router::ID);
+    let [signer, user, _mint, extra] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if *signer.key != authority || !signer.is_signer || user.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !extra.is_writable || extra.is_signer {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if data.len() != 8 + BRIDGE_HOOK_DATA_LEN {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let args = BridgeHookData::try_from_slice(&data[8..])?;
+    if args.user != *user.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    HOOK_CALLS.fetch_add(1, Ordering::SeqCst);
+    HOOK_LAST_AMOUNT.store(args.forwarded_amount, Ordering::SeqCst);
+    Ok(())
+}
+
+fn anchor_account<T: AccountSerialize>(value: &T) -> Account {
+    let mut data = Vec::new();
+    value.try_serialize(&mut data).unwrap();
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: zpx_router::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn packed_account<T: Pack>(value: T) -> Account {
+    let mut data = vec![0u8; T::LEN];
+    value.pack_into_slice(&mut data);
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: spl_token::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> Account {
+    packed_account(spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        delegate: COption::None,
+        state: spl_token::state::AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    })
+}
+
+fn custom(code: ErrorCode) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(code.into()))
+}
+
+#[tokio::test]
+async fn hook_is_called_after_bridge_and_skipped_in_best_effort_mode() {
+    let program_id = zpx_router::ID;
+    let hook = Pubkey::new_unique();
+    let mut program_test =
+        ProgramTest::new("zpx_router", program_id, processor!(zpx_router::entry));
+    program_test.add_program("bridge_hook", hook, processor!(hook_processor));
+
+    let admin = Keypair::new();
+    let user = Keypair::new();
+    let mint = Pubkey::new_unique();
+    let from = Pubkey::new_unique();
+    let fee_recipient = Pubkey::new_unique();
+    let fee_recipient_ata =
+        anchor_spl::associated_token::get_associated_token_address(&fee_recipient, &mint);
+    let target_token_account = Pubkey::new_unique();
+    let target_adapter = Pubkey::new_unique();
+    let hook_state = Pubkey::new_unique();
+    let dst_chain_id = 10u64;
+
+    let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &program_id);
+    let (config, config_bump) = pda(&[b"zpx_config"]);
+    let (in_flight, in_flight_bump) =
+        pda(&[b"in_flight", mint.as_ref(), &dst_chain_id.to_le_bytes()]);
+    let (history, history_bump) = pda(&[b"fee_recipient_history"]);
+    let (bridge_hook_authority, _) = pda(&[b"bridge_hook_authority"]);
+
+    let mut adapters = [Pubkey::default(); 8];
+    adapters[0] = target_adapter;
+    program_test.add_account(
+        config,
+        anchor_account(&Config {
+            admin: admin.pubkey(),
+            fee_recipient,
+            src_chain_id: 1,
+            relayer_fee_bps: 0,
+            protocol_fee_bps: 5,
+            relayer_pubkey: Pubkey::default(),
+            accept_any_token: true,
+            allowed_token_mint: Pubkey::default(),
+            direct_relayer_payout_default: false,
+            min_forward_amount: 0,
+            adapters_len: 1,
+            adapters,
+            paused: false,
+            bump: config_bump,
+            max_relayer_fee_lamports: 0,
+            usd_limits_enabled: false,
+            strict_vaults: false,
+            lp_fee_bps: 0,
+            lp_vault_program: Pubkey::default(),
+            killed: false,
+            kill_release_slot: 0,
+            guardian: Pubkey::default(),
+            admin_op_keys: [0; 8],
+            admin_op_cursor: 0,
+            legacy_message_hash: false,
+            features: zpx_router::FEATURES_DEFAULT,
+            bridge_hook: Pubkey::default(),
+            bridge_hook_best_effort: false,
+            namespace_len: 0,
+            namespace: [0; zpx_router::SANDBOX_NAMESPACE_LEN],
+            schema_version: zpx_router::CONFIG_SCHEMA_VERSION,
+            rent_refund_policy: zpx_router::RENT_REFUND_PAYER,
+            rent_refund_caller_bps: 0,
+            config_version: 0,
+            paused_families: 0,
+        }),
+    );
+    program_test.add_account(
+        in_flight,
+        anchor_account(&InFlight {
+            mint,
+            dst_chain_id,
+            amount: 0,
+            exposure_cap: 0,
+            bump: in_flight_bump,
+        }),
+    );
+    let mut entries = [FeeRecipientEpoch::default(); 8];
+    entries[0].fee_recipient = fee_recipient;
+    program_test.add_account(
+        history,
+        anchor_account(&FeeRecipientHistory {
+            epoch: 0,
+            entries,
+            bump: history_bump,
+            grace_slots: 0,
+        }),
+    );
+    program_test.add_account(
+        mint,
+        packed_account(spl_token::state::Mint {
+            mint_authority: COption::None,
+            supply: 10_000_000,
+            decimals: 6,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        }),
+    );
+    program_test.add_account(from, token_account(mint, user.pubkey(), 10_000_000));
+    program_test.add_account(fee_recipient_ata, token_account(mint, fee_recipient, 0));
+    program_test.add_account(target_token_account, token_account(mint, target_adapter, 0));
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let send = |ix: Instruction, signer: &Keypair| {
+        Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&payer.pubkey()),
+            &[&payer, signer],
+            recent_blockhash,
+        )
+    };
+    let set_hook = |hook: Pubkey, best_effort: bool| Instruction {
+        program_id,
+        accounts: zpx_router::accounts::SetBridgeHook {
+            authority: admin.pubkey(),
+            config,
+        }
+        .to_account_metas(None),
+        data: zpx_router::instruction::SetBridgeHook { hook, best_effort }.data(),
+    };
+    let bridge = |hook_program: Pubkey, nonce: u64| {
+        let mut accounts = zpx_router::accounts::UniversalBridgeTransfer {
+            user: user.pubkey(),
+            rent_payer: payer.pubkey(),
+            mint,
+            from,
+            fee_recipient_ata,
+            target_token_account,
+            target_adapter_program: target_adapter,
+            config,
+            in_flight,
+            fee_recipient_history: history,
+            message: pda(&[b"message", user.pubkey().as_ref(), &nonce.to_le_bytes()]).0,
+            user_index: pda(&[b"user_index", user.pubkey().as_ref()]).0,
+            router_stats: pda(&[b"router_stats"]).0,
+            fee_split: pda(&[b"fee_split"]).0,
+            fee_policy: pda(&[b"fee_policy", mint.as_ref(), &dst_chain_id.to_le_bytes()]).0,
+            mint_pause: pda(&[b"mint_pause", mint.as_ref()]).0,
+            mint_allowlist: pda(&[b"mint_allowlist"]).0,
+            rent_ledger: pda(&[b"rent_ledger"]).0,
+            bridge_hook_program: hook_program,
+            bridge_hook_authority,
+            // No referral
+            referral: Pubkey::default(),
+            partner_rebate: Pubkey::new_unique(),
+            partner_rebate_vault: Pubkey::new_unique(),
+            token_program: spl_token::ID,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None);
+        // Partner state the hook wants to touch rides along as a remaining account
+        accounts.push(AccountMeta::new(hook_state, false));
+        Instruction {
+            program_id,
+            accounts,
+            data: zpx_router::instruction::UniversalBridgeTransfer {
+                amount: 1_000_000,
+                protocol_fee: 500,
+                relayer_fee: 0,
+                payload: vec![],
+                dst_chain_id,
+                nonce,
+                store_preimage: false,
+                skip_receipt: true,
+            }
+            .data(),
+        }
+    };
+
+    // The router refuses to register itself as its own hook
+    let err = banks_client
+        .process_transaction(send(set_hook(program_id, false), &admin))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::InvalidBridgeHook));
+
+    banks_client
+        .process_transaction(send(set_hook(hook, false), &admin))
+        .await
+        .unwrap();
+    banks_client
+        .process_transaction(send(bridge(hook, 1), &user))
+        .await
+        .unwrap();
+    assert_eq!(HOOK_CALLS.load(Ordering::SeqCst), 1);
+    assert_eq!(HOOK_LAST_AMOUNT.load(Ordering::SeqCst), 999_500);
+
+    // Strict mode: a bridge that omits the registered hook fails
+    let err = banks_client
+        .process_transaction(send(bridge(Pubkey::default(), 2), &user))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::InvalidBridgeHook));
+
+    // Best effort: the same bridge goes through without calling the hook
+    banks_client
+        .process_transaction(send(set_hook(hook, true), &admin))
+        .await
+        .unwrap();
+    banks_client
+        .process_transaction(send(bridge(Pubkey::default(), 3), &user))
+        .await
+        .unwrap();
+    assert_eq!(HOOK_CALLS.load(Ordering::SeqCst), 1);
+}
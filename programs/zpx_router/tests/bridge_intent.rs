@@ -0,0 +1,392 @@
+use anchor_lang::{AccountSerialize, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    sysvar,
+    transaction::{Transaction, TransactionError},
+};
+use zpx_router::hash::keccak256;
+use zpx_router::{
+    intent, BridgeIntent, Config, ErrorCode, IntentSigners, Registry, SpokeEntry,
+    MAX_INTENT_SIGNERS, MAX_SPOKES,
+};
+
+fn anchor_account<T: AccountSerialize>(value: &T) -> Account {
+    let mut data = Vec::new();
+    value.try_serialize(&mut data).unwrap();
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: zpx_router::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn packed_account<T: Pack>(value: T) -> Account {
+    let mut data = vec![0u8; T::LEN];
+    value.pack_into_slice(&mut data);
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: spl_token::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> Account {
+    packed_account(spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        delegate: COption::None,
+        state: spl_token::state::AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    })
+}
+
+fn custom(code: ErrorCode) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(code.into()))
+}
+
+/// The EVM address of `key`: the last 20 bytes of keccak256 over the uncompressed point
+fn evm_address(key: &libsecp256k1::SecretKey) -> [u8; 20] {
+    let public = libsecp256k1::PublicKey::from_secret_key(key).serialize();
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&keccak256(&[&public[1..]])[12..]);
+    address
+}
+
+/// eth_signTypedData-style signature: r | s | v with v in {27, 28}
+fn sign(digest: &[u8; 32], key: &libsecp256k1::SecretKey) -> [u8; 65] {
+    let (signature, recovery_id) = libsecp256k1::sign(&libsecp256k1::Message::parse(digest), key);
+    let mut out = [0u8; 65];
+    out[..64].copy_from_slice(&signature.serialize());
+    out[64] = recovery_id.serialize() + 27;
+    out
+}
+
+#[test]
+fn recover_signer_rejects_malleable_and_malformed_signatures() {
+    let key = libsecp256k1::SecretKey::parse(&[7u8; 32]).unwrap();
+    let digest = keccak256(&[b"intent"]);
+    let signature = sign(&digest, &key);
+    assert_eq!(
+        intent::recover_signer(&digest, &signature),
+        Some(evm_address(&key))
+    );
+    let mut raw = signature;
+    raw[64] -= 27;
+    assert_eq!(
+        intent::recover_signer(&digest, &raw),
+        Some(evm_address(&key))
+    );
+
+    let mut bad_v = signature;
+    bad_v[64] = 29;
+    assert_eq!(intent::recover_signer(&digest, &bad_v), None);
+    // (r, n - s) with the flipped parity recovers the same key; it must not be accepted
+    let mut sig = libsecp256k1::Signature::parse_standard_slice(&signature[..64]).unwrap();
+    sig.s = -sig.s;
+    let mut high_s = signature;
+    high_s[..64].copy_from_slice(&sig.serialize());
+    high_s[64] ^= 1;
+    assert_eq!(intent::recover_signer(&digest, &high_s), None);
+}
+
+#[tokio::test]
+async fn registered_intent_forwards_without_a_relayer() {
+    let program_id = zpx_router::ID;
+    let mut program_test =
+        ProgramTest::new("zpx_router", program_id, processor!(zpx_router::entry));
+
+    let admin = Keypair::new();
+    let submitter = Keypair::new();
+    let user = Keypair::new();
+    let mint = Pubkey::new_unique();
+    let from = Pubkey::new_unique();
+    let custody = Pubkey::new_unique();
+    let adapter_target = Pubkey::new_unique();
+    let nonce = 1u64;
+    let spoke_id = 1u32;
+    let mut evm_recipient = [0u8; 20];
+    evm_recipient[19] = 1;
+    let mut mint_recipient = [0u8; 32];
+    mint_recipient[12..].copy_from_slice(&evm_recipient);
+    let order_signer = libsecp256k1::SecretKey::parse(&[3u8; 32]).unwrap();
+    let stranger = libsecp256k1::SecretKey::parse(&[4u8; 32]).unwrap();
+
+    let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &program_id);
+    let (config, config_bump) = pda(&[b"zpx_config"]);
+    let (registry, registry_bump) = pda(&[b"hub_registry"]);
+    let (escrow, _) = pda(&[
+        b"forward_escrow",
+        user.pubkey().as_ref(),
+        &nonce.to_le_bytes(),
+    ]);
+    let (escrow_vault, _) = pda(&[b"forward_escrow_vault", escrow.as_ref()]);
+    let (hub_protocol_vault, _) = pda(&[b"hub_protocol_vault", mint.as_ref()]);
+    let (hub_relayer_vault, _) = pda(&[b"hub_relayer_vault", mint.as_ref()]);
+    let (rent_ledger, _) = pda(&[b"rent_ledger"]);
+    let (intent_signers, intent_signers_bump) = pda(&[b"intent_signers"]);
+
+    program_test.add_account(
+        config,
+        anchor_account(&Config {
+            admin: admin.pubkey(),
+            fee_recipient: Pubkey::new_unique(),
+            src_chain_id: 1,
+            relayer_fee_bps: 0,
+            protocol_fee_bps: 0,
+            relayer_pubkey: Pubkey::new_unique(),
+            accept_any_token: true,
+            allowed_token_mint: Pubkey::default(),
+            direct_relayer_payout_default: false,
+            min_forward_amount: 0,
+            adapters_len: 0,
+            adapters: [Pubkey::default(); 8],
+            paused: false,
+            bump: config_bump,
+            max_relayer_fee_lamports: 0,
+            usd_limits_enabled: false,
+            strict_vaults: false,
+            lp_fee_bps: 0,
+            lp_vault_program: Pubkey::default(),
+            killed: false,
+            kill_release_slot: 0,
+            guardian: Pubkey::default(),
+            admin_op_keys: [0; 8],
+            admin_op_cursor: 0,
+            legacy_message_hash: false,
+            features: zpx_router::FEATURES_DEFAULT,
+            bridge_hook: Pubkey::default(),
+            bridge_hook_best_effort: false,
+            namespace_len: 0,
+            namespace: [0; zpx_router::SANDBOX_NAMESPACE_LEN],
+            schema_version: zpx_router::CONFIG_SCHEMA_VERSION,
+            rent_refund_policy: zpx_router::RENT_REFUND_PAYER,
+            rent_refund_caller_bps: 0,
+            config_version: 0,
+            paused_families: 0,
+        }),
+    );
+    let mut spokes = [SpokeEntry::default(); MAX_SPOKES];
+    spokes[0] = SpokeEntry {
+        spoke_id,
+        adapter_program: Pubkey::new_unique(),
+        enabled: true,
+        target_owner: custody,
+        ..SpokeEntry::default()
+    };
+    program_test.add_account(
+        registry,
+        anchor_account(&Registry {
+            spokes_len: 1,
+            spokes,
+            bump: registry_bump,
+            schema_version: zpx_router::REGISTRY_SCHEMA_VERSION,
+        }),
+    );
+    program_test.add_account(
+        intent_signers,
+        anchor_account(&IntentSigners {
+            signers_len: 0,
+            signers: [[0u8; 20]; MAX_INTENT_SIGNERS],
+            bump: intent_signers_bump,
+        }),
+    );
+    program_test.add_account(
+        mint,
+        packed_account(spl_token::state::Mint {
+            mint_authority: COption::None,
+            supply: 1_000_000,
+            decimals: 6,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        }),
+    );
+    program_test.add_account(from, token_account(mint, user.pubkey(), 1_000_000));
+    program_test.add_account(adapter_target, token_account(mint, custody, 0));
+    program_test.add_account(
+        hub_protocol_vault,
+        token_account(mint, hub_protocol_vault, 0),
+    );
+    program_test.add_account(hub_relayer_vault, token_account(mint, hub_relayer_vault, 0));
+    for wallet in [&user, &admin, &submitter] {
+        program_test.add_account(
+            wallet.pubkey(),
+            Account::new(10_000_000_000, 0, &solana_sdk::system_program::ID),
+        );
+    }
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let tx = |ix: Instruction, signer: &Keypair| {
+        Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&payer.pubkey()),
+            &[&payer, signer],
+            recent_blockhash,
+        )
+    };
+
+    let add_signer = Instruction {
+        program_id,
+        accounts: zpx_router::accounts::IntentSignersAdmin {
+            authority: admin.pubkey(),
+            config,
+            intent_signers,
+        }
+        .to_account_metas(None),
+        data: zpx_router::instruction::AddIntentSigner {
+            signer: evm_address(&order_signer),
+        }
+        .data(),
+    };
+    banks_client
+        .process_transaction(tx(add_signer, &admin))
+        .await
+        .unwrap();
+
+    let deposit = Instruction {
+        program_id,
+        accounts: zpx_router::accounts::DepositForwardEscrow {
+            user: user.pubkey(),
+            mint,
+            from,
+            escrow,
+            escrow_vault,
+            config,
+            rent_ledger,
+            token_program: spl_token::ID,
+            system_program: solana_sdk::system_program::ID,
+            rent: sysvar::rent::ID,
+        }
+        .to_account_metas(None),
+        data: zpx_router::instruction::DepositForwardEscrow {
+            nonce,
+            spoke_id,
+            amount: 1_000_000,
+            dst_domain: zpx_chains::CCTP_BASE.0,
+            recipient: zpx_router::Recipient::Evm20(evm_recipient),
+            lamport_budget: 0,
+            relayer_tip: 0,
+            expiry_reminder_slot: 0,
+        }
+        .data(),
+    };
+    banks_client
+        .process_transaction(tx(deposit, &user))
+        .await
+        .unwrap();
+
+    let order = BridgeIntent {
+        user: user.pubkey(),
+        mint,
+        escrow_nonce: nonce,
+        spoke_id,
+        amount: 1_000_000,
+        dst_domain: zpx_chains::CCTP_BASE.0,
+        mint_recipient,
+        relayer_fee_lamports: 0,
+        payload_hash: keccak256(&[&[]]),
+        deadline: u64::MAX,
+    };
+    let digest = intent::digest(intent::domain_separator(1, program_id.to_bytes()), &order);
+    let forward = |signature: [u8; 65], adapter_payload: Vec<u8>| {
+        let mut accounts = zpx_router::accounts::ForwardViaSpoke {
+            user: user.pubkey(),
+            relayer: submitter.pubkey(),
+            mint,
+            escrow,
+            escrow_vault,
+            hub_protocol_vault,
+            hub_relayer_vault,
+            relayer_token_account: adapter_target,
+            adapter_target_token_account: adapter_target,
+            registry,
+            config,
+            message_account: Pubkey::new_unique(),
+            adapter_program_data: Pubkey::new_unique(),
+            vault_registry: pda(&[b"vault_registry", mint.as_ref()]).0,
+            spoke_relayers: pda(&[b"spoke_relayers", &spoke_id.to_le_bytes()]).0,
+            router_stats: pda(&[b"router_stats"]).0,
+            mint_pause: pda(&[b"mint_pause", mint.as_ref()]).0,
+            mint_allowlist: pda(&[b"mint_allowlist"]).0,
+            rent_ledger,
+            fee_policy: pda(&[
+                b"fee_policy",
+                mint.as_ref(),
+                &zpx_chains::BASE.0.to_le_bytes(),
+            ])
+            .0,
+            relayer_cosign: pda(&[b"relayer_cosign", mint.as_ref()]).0,
+            forward_state: pda(&[b"forward_state", escrow.as_ref()]).0,
+            relayer_vesting_config: pda(&[b"relayer_vesting_config"]).0,
+            relayer_vesting: pda(&[
+                b"relayer_vesting",
+                submitter.pubkey().as_ref(),
+                mint.as_ref(),
+            ])
+            .0,
+            token_program: spl_token::ID,
+            system_program: solana_sdk::system_program::ID,
+            relayer_activity: pda(&[b"relayer_activity", submitter.pubkey().as_ref()]).0,
+        }
+        .to_account_metas(None);
+        accounts.push(AccountMeta::new_readonly(intent_signers, false));
+        Instruction {
+            program_id,
+            accounts,
+            data: zpx_router::instruction::ForwardWithIntent {
+                intent: order,
+                signature,
+                adapter_payload,
+            }
+            .data(),
+        }
+    };
+
+    // An unregistered key cannot authorize the forward
+    let err = banks_client
+        .process_transaction(tx(forward(sign(&digest, &stranger), vec![]), &submitter))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::IntentSignerNotRegistered));
+    // The signature covers the adapter payload
+    let err = banks_client
+        .process_transaction(tx(
+            forward(sign(&digest, &order_signer), vec![1]),
+            &submitter,
+        ))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::IntentPayloadMismatch));
+
+    // Any submitter may execute a registered signer's intent
+    banks_client
+        .process_transaction(tx(
+            forward(sign(&digest, &order_signer), vec![]),
+            &submitter,
+        ))
+        .await
+        .unwrap();
+    let target = banks_client
+        .get_account(adapter_target)
+        .await
+        .unwrap()
+        .unwrap();
+    let target = spl_token::state::Account::unpack(&target.data).unwrap();
+    assert_eq!(target.amount, 1_000_000);
+    assert!(banks_client.get_account(escrow).await.unwrap().is_none());
+}
@@ -0,0 +1,196 @@
+//! Router resilience against a misbehaving adapter: dry_run_adapter against the
+//! zpx_adapter_chaos program in each failure mode.
+
+use anchor_lang::{AccountSerialize, InstructionData, ToAccountMetas};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::Signer,
+    transaction::{Transaction, TransactionError},
+};
+use zpx_adapter_chaos::{
+    CHAOS_CONFIG_SEED, MODE_BURN_CU, MODE_FAIL, MODE_FLAKY, MODE_LARGE_RETURN, MODE_PASS,
+};
+use zpx_router::{ErrorCode, Registry, SpokeEntry, MAX_SPOKES};
+
+const SPOKE_ID: u32 = 7;
+const CHAOS_ERROR: u32 = 4242;
+
+fn anchor_account<T: AccountSerialize>(value: &T, owner: Pubkey) -> Account {
+    let mut data = Vec::new();
+    value.try_serialize(&mut data).unwrap();
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn custom(code: u32) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(code))
+}
+
+#[tokio::test]
+async fn dry_run_survives_every_chaos_mode() {
+    let program_id = zpx_router::ID;
+    let chaos = zpx_adapter_chaos::ID;
+    let mut program_test =
+        ProgramTest::new("zpx_router", program_id, processor!(zpx_router::entry));
+    program_test.add_program(
+        "zpx_adapter_chaos",
+        chaos,
+        processor!(zpx_adapter_chaos::entry),
+    );
+
+    let (registry, registry_bump) = Pubkey::find_program_address(&[b"hub_registry"], &program_id);
+    let (chaos_config, _) = Pubkey::find_program_address(&[CHAOS_CONFIG_SEED], &chaos);
+    let mut spokes = [SpokeEntry::default(); MAX_SPOKES];
+    spokes[0] = SpokeEntry {
+        spoke_id: SPOKE_ID,
+        adapter_program: chaos,
+        enabled: true,
+        ..SpokeEntry::default()
+    };
+    program_test.add_account(
+        registry,
+        anchor_account(
+            &Registry {
+                spokes_len: 1,
+                spokes,
+                bump: registry_bump,
+                schema_version: zpx_router::REGISTRY_SCHEMA_VERSION,
+            },
+            program_id,
+        ),
+    );
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[Instruction {
+                program_id: chaos,
+                accounts: zpx_adapter_chaos::accounts::Initialize {
+                    authority: payer.pubkey(),
+                    config: chaos_config,
+                    system_program: solana_sdk::system_program::ID,
+                }
+                .to_account_metas(None),
+                data: zpx_adapter_chaos::instruction::Initialize {}.data(),
+            }],
+            Some(&payer.pubkey()),
+            &[&payer],
+            recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    let configure = |mode: u8, burn_cu: u32, return_data_len: u16, succeed_after: u32| {
+        Transaction::new_signed_with_payer(
+            &[Instruction {
+                program_id: chaos,
+                accounts: zpx_adapter_chaos::accounts::Configure {
+                    authority: payer.pubkey(),
+                    config: chaos_config,
+                }
+                .to_account_metas(None),
+                data: zpx_adapter_chaos::instruction::Configure {
+                    mode,
+                    error_code: CHAOS_ERROR,
+                    burn_cu,
+                    return_data_len,
+                    succeed_after,
+                }
+                .data(),
+            }],
+            Some(&payer.pubkey()),
+            &[&payer],
+            recent_blockhash,
+        )
+    };
+    // A throwaway trailing account keeps otherwise identical dry runs from being deduped
+    let dry_run = |attempt: u32| {
+        let mut accounts = zpx_router::accounts::DryRunAdapter {
+            registry,
+            adapter_program: chaos,
+        }
+        .to_account_metas(None);
+        accounts.push(AccountMeta::new_readonly(chaos_config, false));
+        accounts.push(AccountMeta::new_readonly(Pubkey::new_unique(), false));
+        Transaction::new_signed_with_payer(
+            &[Instruction {
+                program_id,
+                accounts,
+                data: zpx_router::instruction::DryRunAdapter {
+                    spoke_id: SPOKE_ID,
+                    adapter_payload: attempt.to_le_bytes().to_vec(),
+                }
+                .data(),
+            }],
+            Some(&payer.pubkey()),
+            &[&payer],
+            recent_blockhash,
+        )
+    };
+
+    // Healthy adapter
+    banks_client
+        .process_transaction(configure(MODE_PASS, 0, 0, 0))
+        .await
+        .unwrap();
+    banks_client.process_transaction(dry_run(0)).await.unwrap();
+
+    // The adapter's own error code surfaces unchanged and nothing is left half-applied
+    banks_client
+        .process_transaction(configure(MODE_FAIL, 0, 0, 0))
+        .await
+        .unwrap();
+    let err = banks_client
+        .process_transaction(dry_run(0))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(CHAOS_ERROR));
+
+    // Heavy adapters still complete within the router's budget checks
+    banks_client
+        .process_transaction(configure(MODE_BURN_CU, 50_000, 0, 0))
+        .await
+        .unwrap();
+    banks_client.process_transaction(dry_run(0)).await.unwrap();
+
+    // Oversized, garbage return data is a rejection rather than a pass
+    banks_client
+        .process_transaction(configure(
+            MODE_LARGE_RETURN,
+            0,
+            solana_sdk::program::MAX_RETURN_DATA as u16,
+            0,
+        ))
+        .await
+        .unwrap();
+    let err = banks_client
+        .process_transaction(dry_run(0))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::AdapterPayloadRejected.into()));
+
+    // A flaky adapter fails the first three attempts, then validates
+    banks_client
+        .process_transaction(configure(MODE_FLAKY, 0, 0, 3))
+        .await
+        .unwrap();
+    for attempt in 0..3 {
+        let err = banks_client
+            .process_transaction(dry_run(attempt))
+            .await
+            .unwrap_err()
+            .unwrap();
+        assert_eq!(err, custom(CHAOS_ERROR), "attempt {attempt}");
+    }
+    banks_client.process_transaction(dry_run(3)).await.unwrap();
+}
@@ -0,0 +1,183 @@
+//! Config versions: every config change bumps Config.config_version, and once
+//! initialize_config_history has run the ConfigHistory PDA is mandatory and records the fee
+//! parameters of each version with the slot it took effect.
+
+use anchor_lang::{AccountDeserialize, AccountSerialize, InstructionData, ToAccountMetas};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use zpx_router::{
+    Config, ConfigHistory, ErrorCode, CONFIG_HISTORY_SEED, FEATURE_CONFIG_HISTORY,
+    FEATURE_V2_EVENTS,
+};
+
+fn anchor_account<T: AccountSerialize>(value: &T) -> Account {
+    let mut data = Vec::new();
+    value.try_serialize(&mut data).unwrap();
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: zpx_router::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn admin_config(admin: Pubkey, bump: u8) -> Config {
+    Config {
+        admin,
+        fee_recipient: Pubkey::new_unique(),
+        src_chain_id: 1,
+        relayer_fee_bps: 0,
+        protocol_fee_bps: 5,
+        relayer_pubkey: Pubkey::default(),
+        accept_any_token: true,
+        allowed_token_mint: Pubkey::default(),
+        direct_relayer_payout_default: false,
+        min_forward_amount: 0,
+        adapters_len: 0,
+        adapters: [Pubkey::default(); 8],
+        paused: false,
+        bump,
+        max_relayer_fee_lamports: 0,
+        usd_limits_enabled: false,
+        strict_vaults: false,
+        lp_fee_bps: 0,
+        lp_vault_program: Pubkey::default(),
+        killed: false,
+        kill_release_slot: 0,
+        guardian: Pubkey::default(),
+        admin_op_keys: [0; 8],
+        admin_op_cursor: 0,
+        legacy_message_hash: false,
+        features: zpx_router::FEATURES_DEFAULT,
+        bridge_hook: Pubkey::default(),
+        bridge_hook_best_effort: false,
+        namespace_len: 0,
+        namespace: [0; zpx_router::SANDBOX_NAMESPACE_LEN],
+        schema_version: zpx_router::CONFIG_SCHEMA_VERSION,
+        rent_refund_policy: zpx_router::RENT_REFUND_PAYER,
+        rent_refund_caller_bps: 0,
+        config_version: 0,
+        paused_families: 0,
+    }
+}
+
+fn custom(code: ErrorCode) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(code.into()))
+}
+
+async fn load_config(banks_client: &mut BanksClient, config: Pubkey) -> Config {
+    let account = banks_client.get_account(config).await.unwrap().unwrap();
+    Config::try_deserialize(&mut &account.data[..]).unwrap()
+}
+
+#[tokio::test]
+async fn config_changes_bump_version_and_record_history() {
+    let program_id = zpx_router::ID;
+    let mut program_test =
+        ProgramTest::new("zpx_router", program_id, processor!(zpx_router::entry));
+
+    let admin = Keypair::new();
+    let (config, config_bump) = Pubkey::find_program_address(&[b"zpx_config"], &program_id);
+    let (history, _) = Pubkey::find_program_address(&[CONFIG_HISTORY_SEED], &program_id);
+    program_test.add_account(
+        config,
+        anchor_account(&admin_config(admin.pubkey(), config_bump)),
+    );
+    program_test.add_account(
+        admin.pubkey(),
+        Account {
+            lamports: 1_000_000_000,
+            ..Account::default()
+        },
+    );
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let tx = |ix: Instruction| {
+        Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&payer.pubkey()),
+            &[&payer, &admin],
+            recent_blockhash,
+        )
+    };
+    let set_feature = |enabled: bool, with_history: bool| {
+        let mut accounts = zpx_router::accounts::SetFeature {
+            authority: admin.pubkey(),
+            config,
+        }
+        .to_account_metas(None);
+        if with_history {
+            accounts.push(AccountMeta::new(history, false));
+        }
+        tx(Instruction {
+            program_id,
+            accounts,
+            data: zpx_router::instruction::SetFeature {
+                feature: FEATURE_V2_EVENTS,
+                enabled,
+            }
+            .data(),
+        })
+    };
+
+    // Before the history exists changes only bump the counter
+    banks_client
+        .process_transaction(set_feature(false, false))
+        .await
+        .unwrap();
+    assert_eq!(
+        load_config(&mut banks_client, config).await.config_version,
+        1
+    );
+
+    banks_client
+        .process_transaction(tx(Instruction {
+            program_id,
+            accounts: zpx_router::accounts::InitializeConfigHistory {
+                authority: admin.pubkey(),
+                config,
+                history,
+                system_program: solana_sdk::system_program::ID,
+            }
+            .to_account_metas(None),
+            data: zpx_router::instruction::InitializeConfigHistory {}.data(),
+        }))
+        .await
+        .unwrap();
+    let cfg = load_config(&mut banks_client, config).await;
+    assert_eq!(cfg.config_version, 2);
+    assert!(cfg.feature_enabled(FEATURE_CONFIG_HISTORY));
+
+    // From now on every change must record into the history
+    let err = banks_client
+        .process_transaction(set_feature(true, false))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::ConfigHistoryMissing));
+    banks_client
+        .process_transaction(set_feature(true, true))
+        .await
+        .unwrap();
+    assert_eq!(
+        load_config(&mut banks_client, config).await.config_version,
+        3
+    );
+
+    let account = banks_client.get_account(history).await.unwrap().unwrap();
+    let history = ConfigHistory::try_deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(history.total, 2);
+    let versions: Vec<u64> = history.recent().map(|e| e.version).collect();
+    assert_eq!(versions, [3, 2]);
+    let v2 = history.version(2).unwrap();
+    assert_eq!(v2.protocol_fee_bps, 5);
+    assert_eq!(v2.fee_recipient, cfg.fee_recipient);
+    assert_eq!(history.at_slot(u64::MAX).unwrap().version, 3);
+}
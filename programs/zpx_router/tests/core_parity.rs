@@ -0,0 +1,141 @@
+//! zpx-core is what browsers and tooling run (natively or as wasm); these checks pin it to
+//! the program's own derivations so a client can never compute a different address, hash or
+//! fee than the router.
+
+use solana_sdk::pubkey::Pubkey;
+use zpx_router::zpx_core::{fees, hash, payload, pda};
+use zpx_router::{compute_fees_and_forward, validate_payload_schema, ErrorCode, PayloadSchema};
+
+fn same(core: ([u8; 32], u8), seeds: &[&[u8]]) {
+    let (address, bump) = Pubkey::find_program_address(seeds, &zpx_router::ID);
+    assert_eq!(core, (address.to_bytes(), bump), "seeds {seeds:?}");
+}
+
+#[test]
+fn pda_derivation_matches_the_runtime() {
+    let program = zpx_router::ID.to_bytes();
+    for i in 0..32u64 {
+        let key = Pubkey::new_unique();
+        let k = key.to_bytes();
+        same(pda::config(&program), &[b"zpx_config"]);
+        same(
+            pda::forward_escrow(&program, &k, i),
+            &[b"forward_escrow", key.as_ref(), &i.to_le_bytes()],
+        );
+        same(
+            pda::forward_escrow_vault(&program, &k),
+            &[b"forward_escrow_vault", key.as_ref()],
+        );
+        same(
+            pda::hub_protocol_vault(&program, &k),
+            &[b"hub_protocol_vault", key.as_ref()],
+        );
+        same(
+            pda::hub_relayer_vault(&program, &k),
+            &[b"hub_relayer_vault", key.as_ref()],
+        );
+        same(pda::replay(&program, &k), &[b"replay", key.as_ref()]);
+        same(
+            pda::message_preimage(&program, &k, i),
+            &[b"message", key.as_ref(), &i.to_le_bytes()],
+        );
+        same(
+            pda::route_validity(&program, i),
+            &[b"route_validity", &i.to_le_bytes()],
+        );
+        same(
+            pda::relayer_activity(&program, &k),
+            &[b"relayer_activity", key.as_ref()],
+        );
+        same(
+            pda::spoke_relayers(&program, i as u32),
+            &[b"spoke_relayers", &(i as u32).to_le_bytes()],
+        );
+    }
+    same(pda::hub_registry(&program), &[b"hub_registry"]);
+    same(pda::rent_ledger(&program), &[b"rent_ledger"]);
+    // Over-long seeds are rejected like Pubkey::create_program_address
+    assert_eq!(pda::create_program_address(&[&[0; 33]], &program), None);
+}
+
+#[test]
+fn hashes_match_the_program_and_syscall_backend() {
+    use hash::HashBackend;
+    for len in [0usize, 1, 31, 32, 33, 136, 512] {
+        let data = vec![0xa5u8; len];
+        assert_eq!(
+            hash::PureRust::keccak256(&[&data]),
+            hash::Syscall::keccak256(&[&data])
+        );
+    }
+    let scheme = zpx_router::message_hash_scheme(hash::HASH_SCHEMA_VERSION);
+    assert_eq!(
+        scheme,
+        hash::HashScheme::for_version(
+            zpx_router::ID.to_bytes(),
+            hash::ENV_LOCAL,
+            hash::HASH_SCHEMA_VERSION
+        )
+    );
+    assert_eq!(zpx_router::message_hash_scheme(0), hash::HashScheme::Legacy);
+    let payload_hash = payload::payload_hash(b"body", Some((10, 20)));
+    assert_eq!(
+        payload_hash,
+        hash::validity_payload_hash(hash::keccak256(&[b"body"]), 10, 20)
+    );
+    assert_eq!(
+        hash::source_message_hash(scheme, 1, 8453, [1; 32], [3; 32], 999, payload_hash, 7),
+        hash::message_hash_be(
+            scheme,
+            1,
+            [1; 32],
+            [0; 32],
+            [3; 32],
+            hash::amount_be(999),
+            payload_hash,
+            7,
+            8453
+        )
+    );
+}
+
+#[test]
+fn fees_and_payload_checks_match_the_program() {
+    for amount in [1u64, 10, 9_999, 10_000, 1_000_001, u64::MAX / 3] {
+        let q = fees::quote(amount, 5, 1_000);
+        let program = compute_fees_and_forward(
+            amount,
+            fees::bps_fee(amount, 5),
+            fees::bps_fee(amount, 1_000),
+            1_000,
+        );
+        match (q, program) {
+            (Ok(q), Ok((forward, total))) => {
+                assert_eq!(q.forward_amount, forward);
+                assert_eq!(q.protocol_fee + q.relayer_fee, total);
+            }
+            // A quote also refuses to forward nothing, like forward_via_spoke
+            (Err(fees::FeeError::ZeroAmount), Ok((0, _))) => {}
+            (q, program) => panic!("{amount}: {q:?} vs {program:?}"),
+        }
+    }
+
+    let schema = PayloadSchema {
+        min_len: 3,
+        max_len: 16,
+        require_version: true,
+        version: 2,
+        magic_len: 2,
+        magic: *b"ZX\0\0",
+        max_version: 3,
+    };
+    for body in [&b"ZX\x02hi"[..], b"ZX\x04", b"ZY\x02", b"Z", &[0u8; 17]] {
+        let core = schema.to_core().validate(body).map_err(ErrorCode::from);
+        let program = validate_payload_schema(&schema, body);
+        match (core, program) {
+            (Ok(()), Ok(())) => {}
+            (Err(code), Err(err)) => assert_eq!(err, code.into()),
+            (core, program) => panic!("{body:?}: {core:?} vs {program:?}"),
+        }
+    }
+}
@@ -0,0 +1,317 @@
+//! Permissionless finalize crank: any signer can finalize an inbound message with the same
+//! checks as finalize_message_v1 and is paid the mint's CrankReward from hub_protocol_vault
+//! until the admin-set budget runs out. The configured relayer earns nothing from it.
+
+use anchor_lang::{AccountDeserialize, AccountSerialize, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use zpx_router::hash::message_hash_be;
+use zpx_router::{message_hash_scheme, Config, CrankReward, ErrorCode, CRANK_REWARD_SEED};
+
+const VAULT_BALANCE: u64 = 10_000;
+
+fn anchor_account<T: AccountSerialize>(value: &T) -> Account {
+    let mut data = Vec::new();
+    value.try_serialize(&mut data).unwrap();
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: zpx_router::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn packed_account<T: Pack>(value: T) -> Account {
+    let mut data = vec![0u8; T::LEN];
+    value.pack_into_slice(&mut data);
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: spl_token::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> Account {
+    packed_account(spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        delegate: COption::None,
+        state: spl_token::state::AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    })
+}
+
+fn custom(code: ErrorCode) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(code.into()))
+}
+
+async fn token_balance(banks_client: &mut BanksClient, account: Pubkey) -> u64 {
+    let account = banks_client.get_account(account).await.unwrap().unwrap();
+    spl_token::state::Account::unpack(&account.data)
+        .unwrap()
+        .amount
+}
+
+#[tokio::test]
+async fn anyone_can_crank_finalize_for_a_budgeted_reward() {
+    let program_id = zpx_router::ID;
+    let mut program_test =
+        ProgramTest::new("zpx_router", program_id, processor!(zpx_router::entry));
+
+    let admin = Keypair::new();
+    let relayer = Keypair::new();
+    let cranker = Keypair::new();
+    let src_adapter = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let src_chain_id = 8453u64;
+    let dst_chain_id = 1u64;
+    let (config, config_bump) = Pubkey::find_program_address(&[b"zpx_config"], &program_id);
+    let (route_validity, _) = Pubkey::find_program_address(
+        &[b"route_validity", &src_chain_id.to_le_bytes()],
+        &program_id,
+    );
+    let (rent_ledger, _) = Pubkey::find_program_address(&[b"rent_ledger"], &program_id);
+    let (crank_reward, _) =
+        Pubkey::find_program_address(&[CRANK_REWARD_SEED, mint.as_ref()], &program_id);
+    let (hub_protocol_vault, _) =
+        Pubkey::find_program_address(&[b"hub_protocol_vault", mint.as_ref()], &program_id);
+    let cranker_ata = Pubkey::new_unique();
+    let relayer_ata = Pubkey::new_unique();
+    let mut adapters = [Pubkey::default(); 8];
+    adapters[0] = src_adapter;
+    program_test.add_account(
+        config,
+        anchor_account(&Config {
+            admin: admin.pubkey(),
+            fee_recipient: Pubkey::new_unique(),
+            src_chain_id: dst_chain_id,
+            relayer_fee_bps: 0,
+            protocol_fee_bps: 0,
+            relayer_pubkey: relayer.pubkey(),
+            accept_any_token: true,
+            allowed_token_mint: Pubkey::default(),
+            direct_relayer_payout_default: false,
+            min_forward_amount: 0,
+            adapters_len: 1,
+            adapters,
+            paused: false,
+            bump: config_bump,
+            max_relayer_fee_lamports: 0,
+            usd_limits_enabled: false,
+            strict_vaults: false,
+            lp_fee_bps: 0,
+            lp_vault_program: Pubkey::default(),
+            killed: false,
+            kill_release_slot: 0,
+            guardian: Pubkey::default(),
+            admin_op_keys: [0; 8],
+            admin_op_cursor: 0,
+            legacy_message_hash: false,
+            features: zpx_router::FEATURES_DEFAULT,
+            bridge_hook: Pubkey::default(),
+            bridge_hook_best_effort: false,
+            namespace_len: 0,
+            namespace: [0; zpx_router::SANDBOX_NAMESPACE_LEN],
+            schema_version: zpx_router::CONFIG_SCHEMA_VERSION,
+            rent_refund_policy: zpx_router::RENT_REFUND_PAYER,
+            rent_refund_caller_bps: 0,
+            config_version: 0,
+            paused_families: 0,
+        }),
+    );
+    program_test.add_account(
+        mint,
+        packed_account(spl_token::state::Mint {
+            mint_authority: COption::None,
+            supply: VAULT_BALANCE,
+            decimals: 6,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        }),
+    );
+    program_test.add_account(
+        hub_protocol_vault,
+        token_account(mint, hub_protocol_vault, VAULT_BALANCE),
+    );
+    program_test.add_account(cranker_ata, token_account(mint, cranker.pubkey(), 0));
+    program_test.add_account(relayer_ata, token_account(mint, relayer.pubkey(), 0));
+    for wallet in [&admin, &relayer, &cranker] {
+        program_test.add_account(
+            wallet.pubkey(),
+            Account::new(10_000_000_000, 0, &solana_sdk::system_program::ID),
+        );
+    }
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let tx = |ix: Instruction, signer: &Keypair| {
+        Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&payer.pubkey()),
+            &[&payer, signer],
+            recent_blockhash,
+        )
+    };
+
+    banks_client
+        .process_transaction(tx(
+            Instruction {
+                program_id,
+                accounts: zpx_router::accounts::SetCrankReward {
+                    authority: admin.pubkey(),
+                    config,
+                    mint,
+                    crank_reward,
+                    system_program: solana_sdk::system_program::ID,
+                }
+                .to_account_metas(None),
+                data: zpx_router::instruction::SetCrankReward {
+                    amount: 100,
+                    budget: 150,
+                }
+                .data(),
+            },
+            &admin,
+        ))
+        .await
+        .unwrap();
+
+    let payload_hash = [7u8; 32];
+    let crank = |nonce: u64, caller: &Keypair, destination: Pubkey| {
+        let mut amount_be = [0u8; 32];
+        amount_be[24..].copy_from_slice(&1_000u64.to_be_bytes());
+        let message_hash = message_hash_be(
+            message_hash_scheme(zpx_router::hash::HASH_SCHEMA_VERSION),
+            src_chain_id,
+            src_adapter.to_bytes(),
+            [0u8; 32],
+            mint.to_bytes(),
+            amount_be,
+            payload_hash,
+            nonce,
+            dst_chain_id,
+        );
+        Instruction {
+            program_id,
+            accounts: zpx_router::accounts::CrankFinalizeMessageV1 {
+                finalize: zpx_router::accounts::FinalizeMessageV1 {
+                    relayer: caller.pubkey(),
+                    config,
+                    replay: Pubkey::find_program_address(&[b"replay", &message_hash], &program_id)
+                        .0,
+                    rent_ledger,
+                    system_program: solana_sdk::system_program::ID,
+                    route_validity,
+                },
+                mint,
+                crank_reward,
+                hub_protocol_vault,
+                crank_token_account: destination,
+                token_program: spl_token::ID,
+            }
+            .to_account_metas(None),
+            data: zpx_router::instruction::CrankFinalizeMessageV1 {
+                message_hash,
+                src_chain_id,
+                dst_chain_id,
+                forwarded_amount: 1_000,
+                nonce,
+                payload_hash,
+                src_adapter,
+                asset_mint: mint,
+                _initiator: Pubkey::default(),
+                origin_tx_hash: None,
+                validity: None,
+            }
+            .data(),
+        }
+    };
+
+    // A stranger finalizes a stuck message and is paid the full reward
+    banks_client
+        .process_transaction(tx(crank(1, &cranker, cranker_ata), &cranker))
+        .await
+        .unwrap();
+    assert_eq!(token_balance(&mut banks_client, cranker_ata).await, 100);
+
+    // The same message cannot be cranked twice (the extra account keeps the retry from
+    // being deduplicated as an identical transaction)
+    let mut retry = crank(1, &cranker, cranker_ata);
+    retry
+        .accounts
+        .push(AccountMeta::new_readonly(Pubkey::new_unique(), false));
+    let err = banks_client
+        .process_transaction(tx(retry, &cranker))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::ReplayAlreadyProcessed));
+
+    // The next reward is capped by what is left of the budget
+    banks_client
+        .process_transaction(tx(crank(2, &cranker, cranker_ata), &cranker))
+        .await
+        .unwrap();
+    assert_eq!(token_balance(&mut banks_client, cranker_ata).await, 150);
+    let account = banks_client
+        .get_account(crank_reward)
+        .await
+        .unwrap()
+        .unwrap();
+    let reward = CrankReward::try_deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(reward.budget, 0);
+
+    // Once the budget is spent cranking still finalizes, without a payout
+    banks_client
+        .process_transaction(tx(crank(3, &cranker, cranker_ata), &cranker))
+        .await
+        .unwrap();
+    assert_eq!(token_balance(&mut banks_client, cranker_ata).await, 150);
+
+    // The configured relayer can use the crank but earns no reward
+    banks_client
+        .process_transaction(tx(
+            Instruction {
+                program_id,
+                accounts: zpx_router::accounts::SetCrankReward {
+                    authority: admin.pubkey(),
+                    config,
+                    mint,
+                    crank_reward,
+                    system_program: solana_sdk::system_program::ID,
+                }
+                .to_account_metas(None),
+                data: zpx_router::instruction::SetCrankReward {
+                    amount: 100,
+                    budget: 1_000,
+                }
+                .data(),
+            },
+            &admin,
+        ))
+        .await
+        .unwrap();
+    banks_client
+        .process_transaction(tx(crank(4, &relayer, relayer_ata), &relayer))
+        .await
+        .unwrap();
+    assert_eq!(token_balance(&mut banks_client, relayer_ata).await, 0);
+    assert_eq!(
+        token_balance(&mut banks_client, hub_protocol_vault).await,
+        VAULT_BALANCE - 150
+    );
+}
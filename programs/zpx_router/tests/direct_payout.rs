@@ -0,0 +1,386 @@
+//! Direct relayer payout: forward_via_spoke pays the token relayer fee straight into
+//! relayer_token_account only when the payee owns it, it holds the forwarded mint, it is not
+//! frozen and it has no delegate. RELAYER_PAYOUT_VAULT turns direct payout off per spoke.
+
+use anchor_lang::{AccountSerialize, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::{Instruction, InstructionError},
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    sysvar,
+    transaction::{Transaction, TransactionError},
+};
+use zpx_router::{Config, ErrorCode, Registry, SpokeEntry, MAX_SPOKES, RELAYER_PAYOUT_VAULT};
+
+const AMOUNT: u64 = 1_000_000;
+const RELAYER_FEE: u64 = AMOUNT / 100;
+
+fn anchor_account<T: AccountSerialize>(value: &T) -> Account {
+    let mut data = Vec::new();
+    value.try_serialize(&mut data).unwrap();
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: zpx_router::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn packed_account<T: Pack>(value: T) -> Account {
+    let mut data = vec![0u8; T::LEN];
+    value.pack_into_slice(&mut data);
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: spl_token::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn token_state(mint: Pubkey, owner: Pubkey, amount: u64) -> spl_token::state::Account {
+    spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        delegate: COption::None,
+        state: spl_token::state::AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    }
+}
+
+fn custom(code: ErrorCode) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(code.into()))
+}
+
+async fn token_balance(banks_client: &mut BanksClient, account: Pubkey) -> u64 {
+    let account = banks_client.get_account(account).await.unwrap().unwrap();
+    spl_token::state::Account::unpack(&account.data)
+        .unwrap()
+        .amount
+}
+
+#[tokio::test]
+async fn direct_payout_rejects_unsafe_relayer_accounts() {
+    let program_id = zpx_router::ID;
+    let mut program_test =
+        ProgramTest::new("zpx_router", program_id, processor!(zpx_router::entry));
+
+    let admin = Keypair::new();
+    let relayer = Keypair::new();
+    let user = Keypair::new();
+    let mint = Pubkey::new_unique();
+    let from = Pubkey::new_unique();
+    let custody = Pubkey::new_unique();
+    let adapter_target = Pubkey::new_unique();
+    let spoke_id = 1u32;
+    let mut evm_recipient = [0u8; 20];
+    evm_recipient[19] = 1;
+    let mut mint_recipient = [0u8; 32];
+    mint_recipient[12..].copy_from_slice(&evm_recipient);
+
+    let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &program_id);
+    let (config, config_bump) = pda(&[b"zpx_config"]);
+    let (registry, registry_bump) = pda(&[b"hub_registry"]);
+    let (hub_protocol_vault, _) = pda(&[b"hub_protocol_vault", mint.as_ref()]);
+    let (hub_relayer_vault, _) = pda(&[b"hub_relayer_vault", mint.as_ref()]);
+    let (rent_ledger, _) = pda(&[b"rent_ledger"]);
+    let escrow_of = |nonce: u64| {
+        let escrow = pda(&[
+            b"forward_escrow",
+            user.pubkey().as_ref(),
+            &nonce.to_le_bytes(),
+        ])
+        .0;
+        (escrow, pda(&[b"forward_escrow_vault", escrow.as_ref()]).0)
+    };
+
+    program_test.add_account(
+        config,
+        anchor_account(&Config {
+            admin: admin.pubkey(),
+            fee_recipient: Pubkey::new_unique(),
+            src_chain_id: 1,
+            relayer_fee_bps: 100,
+            protocol_fee_bps: 0,
+            relayer_pubkey: relayer.pubkey(),
+            accept_any_token: true,
+            allowed_token_mint: Pubkey::default(),
+            direct_relayer_payout_default: true,
+            min_forward_amount: 0,
+            adapters_len: 0,
+            adapters: [Pubkey::default(); 8],
+            paused: false,
+            bump: config_bump,
+            max_relayer_fee_lamports: 0,
+            usd_limits_enabled: false,
+            strict_vaults: false,
+            lp_fee_bps: 0,
+            lp_vault_program: Pubkey::default(),
+            killed: false,
+            kill_release_slot: 0,
+            guardian: Pubkey::default(),
+            admin_op_keys: [0; 8],
+            admin_op_cursor: 0,
+            legacy_message_hash: false,
+            features: zpx_router::FEATURES_DEFAULT,
+            bridge_hook: Pubkey::default(),
+            bridge_hook_best_effort: false,
+            namespace_len: 0,
+            namespace: [0; zpx_router::SANDBOX_NAMESPACE_LEN],
+            schema_version: zpx_router::CONFIG_SCHEMA_VERSION,
+            rent_refund_policy: zpx_router::RENT_REFUND_PAYER,
+            rent_refund_caller_bps: 0,
+            config_version: 0,
+            paused_families: 0,
+        }),
+    );
+    let mut spokes = [SpokeEntry::default(); MAX_SPOKES];
+    spokes[0] = SpokeEntry {
+        spoke_id,
+        adapter_program: Pubkey::new_unique(),
+        enabled: true,
+        target_owner: custody,
+        ..SpokeEntry::default()
+    };
+    program_test.add_account(
+        registry,
+        anchor_account(&Registry {
+            spokes_len: 1,
+            spokes,
+            bump: registry_bump,
+            schema_version: zpx_router::REGISTRY_SCHEMA_VERSION,
+        }),
+    );
+    program_test.add_account(
+        mint,
+        packed_account(spl_token::state::Mint {
+            mint_authority: COption::None,
+            supply: 2 * AMOUNT,
+            decimals: 6,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        }),
+    );
+    program_test.add_account(
+        from,
+        packed_account(token_state(mint, user.pubkey(), 2 * AMOUNT)),
+    );
+    program_test.add_account(
+        adapter_target,
+        packed_account(token_state(mint, custody, 0)),
+    );
+    for vault in [hub_protocol_vault, hub_relayer_vault] {
+        program_test.add_account(vault, packed_account(token_state(mint, vault, 0)));
+    }
+
+    // One acceptable payout account and one per rejection
+    let relayer_ata = Pubkey::new_unique();
+    let other_mint_ata = Pubkey::new_unique();
+    let frozen_ata = Pubkey::new_unique();
+    let delegated_ata = Pubkey::new_unique();
+    program_test.add_account(
+        relayer_ata,
+        packed_account(token_state(mint, relayer.pubkey(), 0)),
+    );
+    program_test.add_account(
+        other_mint_ata,
+        packed_account(token_state(Pubkey::new_unique(), relayer.pubkey(), 0)),
+    );
+    program_test.add_account(
+        frozen_ata,
+        packed_account(spl_token::state::Account {
+            state: spl_token::state::AccountState::Frozen,
+            ..token_state(mint, relayer.pubkey(), 0)
+        }),
+    );
+    program_test.add_account(
+        delegated_ata,
+        packed_account(spl_token::state::Account {
+            delegate: COption::Some(Pubkey::new_unique()),
+            delegated_amount: u64::MAX,
+            ..token_state(mint, relayer.pubkey(), 0)
+        }),
+    );
+    for wallet in [&admin, &user, &relayer] {
+        program_test.add_account(
+            wallet.pubkey(),
+            Account::new(10_000_000_000, 0, &solana_sdk::system_program::ID),
+        );
+    }
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let tx = |ix: Instruction, signer: &Keypair| {
+        Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&payer.pubkey()),
+            &[&payer, signer],
+            recent_blockhash,
+        )
+    };
+
+    for nonce in 1..=2u64 {
+        let (escrow, escrow_vault) = escrow_of(nonce);
+        let deposit = Instruction {
+            program_id,
+            accounts: zpx_router::accounts::DepositForwardEscrow {
+                user: user.pubkey(),
+                mint,
+                from,
+                escrow,
+                escrow_vault,
+                config,
+                rent_ledger,
+                token_program: spl_token::ID,
+                system_program: solana_sdk::system_program::ID,
+                rent: sysvar::rent::ID,
+            }
+            .to_account_metas(None),
+            data: zpx_router::instruction::DepositForwardEscrow {
+                nonce,
+                spoke_id,
+                amount: AMOUNT,
+                dst_domain: zpx_chains::CCTP_BASE.0,
+                recipient: zpx_router::Recipient::Evm20(evm_recipient),
+                lamport_budget: 0,
+                relayer_tip: 0,
+                expiry_reminder_slot: 0,
+            }
+            .data(),
+        };
+        banks_client
+            .process_transaction(tx(deposit, &user))
+            .await
+            .unwrap();
+    }
+
+    let forward = |nonce: u64, relayer_token_account: Pubkey| {
+        let (escrow, escrow_vault) = escrow_of(nonce);
+        let r = relayer.pubkey();
+        let ix = Instruction {
+            program_id,
+            accounts: zpx_router::accounts::ForwardViaSpoke {
+                user: user.pubkey(),
+                relayer: r,
+                mint,
+                escrow,
+                escrow_vault,
+                hub_protocol_vault,
+                hub_relayer_vault,
+                relayer_token_account,
+                adapter_target_token_account: adapter_target,
+                registry,
+                config,
+                message_account: Pubkey::new_unique(),
+                adapter_program_data: Pubkey::new_unique(),
+                vault_registry: pda(&[b"vault_registry", mint.as_ref()]).0,
+                spoke_relayers: pda(&[b"spoke_relayers", &spoke_id.to_le_bytes()]).0,
+                router_stats: pda(&[b"router_stats"]).0,
+                mint_pause: pda(&[b"mint_pause", mint.as_ref()]).0,
+                mint_allowlist: pda(&[b"mint_allowlist"]).0,
+                rent_ledger,
+                fee_policy: pda(&[
+                    b"fee_policy",
+                    mint.as_ref(),
+                    &zpx_chains::BASE.0.to_le_bytes(),
+                ])
+                .0,
+                relayer_cosign: pda(&[b"relayer_cosign", mint.as_ref()]).0,
+                forward_state: pda(&[b"forward_state", escrow.as_ref()]).0,
+                relayer_vesting_config: pda(&[b"relayer_vesting_config"]).0,
+                relayer_vesting: pda(&[b"relayer_vesting", r.as_ref(), mint.as_ref()]).0,
+                token_program: spl_token::ID,
+                system_program: solana_sdk::system_program::ID,
+                relayer_activity: pda(&[b"relayer_activity", r.as_ref()]).0,
+            }
+            .to_account_metas(None),
+            data: zpx_router::instruction::ForwardViaSpoke {
+                spoke_id,
+                amount: AMOUNT,
+                dst_domain: zpx_chains::CCTP_BASE.0,
+                mint_recipient,
+                is_protocol_fee: false,
+                is_relayer_fee: true,
+                _nonce: nonce,
+                relayer_fee_lamports: 0,
+                adapter_payload: vec![],
+            }
+            .data(),
+        };
+        tx(ix, &relayer)
+    };
+
+    for (account, code) in [
+        (adapter_target, ErrorCode::Unauthorized),
+        (other_mint_ata, ErrorCode::RelayerPayoutMintMismatch),
+        (frozen_ata, ErrorCode::RelayerPayoutAccountFrozen),
+        (delegated_ata, ErrorCode::RelayerPayoutAccountDelegated),
+    ] {
+        let err = banks_client
+            .process_transaction(forward(1, account))
+            .await
+            .unwrap_err()
+            .unwrap();
+        assert_eq!(err, custom(code));
+    }
+    banks_client
+        .process_transaction(forward(1, relayer_ata))
+        .await
+        .unwrap();
+    assert_eq!(
+        token_balance(&mut banks_client, relayer_ata).await,
+        RELAYER_FEE
+    );
+
+    // A risky route opts out of direct payout even though the config defaults to it
+    let set_payout = |relayer_payout: u8| {
+        tx(
+            Instruction {
+                program_id,
+                accounts: zpx_router::accounts::SpokeAdmin {
+                    authority: admin.pubkey(),
+                    config,
+                    registry,
+                }
+                .to_account_metas(None),
+                data: zpx_router::instruction::SetSpokeRelayerPayout {
+                    spoke_id,
+                    relayer_payout,
+                }
+                .data(),
+            },
+            &admin,
+        )
+    };
+    let err = banks_client
+        .process_transaction(set_payout(RELAYER_PAYOUT_VAULT + 1))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::InvalidRelayerPayoutMode));
+    banks_client
+        .process_transaction(set_payout(RELAYER_PAYOUT_VAULT))
+        .await
+        .unwrap();
+    // The payout account is no longer used, so even the frozen one passes
+    banks_client
+        .process_transaction(forward(2, frozen_ata))
+        .await
+        .unwrap();
+    assert_eq!(
+        token_balance(&mut banks_client, hub_relayer_vault).await,
+        RELAYER_FEE
+    );
+    assert_eq!(
+        token_balance(&mut banks_client, relayer_ata).await,
+        RELAYER_FEE
+    );
+}
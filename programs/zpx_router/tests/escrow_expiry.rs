@@ -0,0 +1,264 @@
+use anchor_lang::{AccountSerialize, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    sysvar,
+    transaction::{Transaction, TransactionError},
+};
+use zpx_router::{Config, ErrorCode, ESCROW_EXPIRY_NOTICE_SLOTS};
+
+fn anchor_account<T: AccountSerialize>(value: &T) -> Account {
+    let mut data = Vec::new();
+    value.try_serialize(&mut data).unwrap();
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: zpx_router::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn packed_account<T: Pack>(value: T) -> Account {
+    let mut data = vec![0u8; T::LEN];
+    value.pack_into_slice(&mut data);
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: spl_token::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> Account {
+    packed_account(spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        delegate: COption::None,
+        state: spl_token::state::AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    })
+}
+
+fn token_amount(account: &Account) -> u64 {
+    spl_token::state::Account::unpack(&account.data)
+        .unwrap()
+        .amount
+}
+
+fn custom(code: ErrorCode) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(code.into()))
+}
+
+#[tokio::test]
+async fn expired_escrow_is_refunded_to_user_by_anyone() {
+    let program_id = zpx_router::ID;
+    let mut program_test =
+        ProgramTest::new("zpx_router", program_id, processor!(zpx_router::entry));
+
+    let user = Keypair::new();
+    let bot = Keypair::new();
+    let mint = Pubkey::new_unique();
+    let from = Pubkey::new_unique();
+    let stranger_ata = Pubkey::new_unique();
+    let nonce = 1u64;
+    let reminder_slot = 100u64;
+    let (config, config_bump) = Pubkey::find_program_address(&[b"zpx_config"], &program_id);
+    let (escrow, _) = Pubkey::find_program_address(
+        &[
+            b"forward_escrow",
+            user.pubkey().as_ref(),
+            &nonce.to_le_bytes(),
+        ],
+        &program_id,
+    );
+    let (escrow_vault, _) =
+        Pubkey::find_program_address(&[b"forward_escrow_vault", escrow.as_ref()], &program_id);
+    let (rent_ledger, _) = Pubkey::find_program_address(&[b"rent_ledger"], &program_id);
+
+    program_test.add_account(
+        config,
+        anchor_account(&Config {
+            admin: Pubkey::new_unique(),
+            fee_recipient: Pubkey::new_unique(),
+            src_chain_id: 1,
+            relayer_fee_bps: 0,
+            protocol_fee_bps: 0,
+            relayer_pubkey: Pubkey::default(),
+            accept_any_token: true,
+            allowed_token_mint: Pubkey::default(),
+            direct_relayer_payout_default: false,
+            min_forward_amount: 0,
+            adapters_len: 0,
+            adapters: [Pubkey::default(); 8],
+            paused: false,
+            bump: config_bump,
+            max_relayer_fee_lamports: 0,
+            usd_limits_enabled: false,
+            strict_vaults: false,
+            lp_fee_bps: 0,
+            lp_vault_program: Pubkey::default(),
+            killed: false,
+            kill_release_slot: 0,
+            guardian: Pubkey::default(),
+            admin_op_keys: [0; 8],
+            admin_op_cursor: 0,
+            legacy_message_hash: false,
+            features: zpx_router::FEATURES_DEFAULT,
+            bridge_hook: Pubkey::default(),
+            bridge_hook_best_effort: false,
+            namespace_len: 0,
+            namespace: [0; zpx_router::SANDBOX_NAMESPACE_LEN],
+            schema_version: zpx_router::CONFIG_SCHEMA_VERSION,
+            rent_refund_policy: zpx_router::RENT_REFUND_PAYER,
+            rent_refund_caller_bps: 0,
+            config_version: 0,
+            paused_families: 0,
+        }),
+    );
+    program_test.add_account(
+        mint,
+        packed_account(spl_token::state::Mint {
+            mint_authority: COption::None,
+            supply: 1_000_000,
+            decimals: 6,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        }),
+    );
+    program_test.add_account(from, token_account(mint, user.pubkey(), 1_000_000));
+    program_test.add_account(stranger_ata, token_account(mint, bot.pubkey(), 0));
+    program_test.add_account(
+        user.pubkey(),
+        Account::new(10_000_000_000, 0, &solana_sdk::system_program::ID),
+    );
+
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.insecure_clone();
+    let mut evm_recipient = [0u8; 20];
+    evm_recipient[19] = 1;
+    let mut mint_recipient = [0u8; 32];
+    mint_recipient[12..].copy_from_slice(&evm_recipient);
+    let deposit = Instruction {
+        program_id,
+        accounts: zpx_router::accounts::DepositForwardEscrow {
+            user: user.pubkey(),
+            mint,
+            from,
+            escrow,
+            escrow_vault,
+            config,
+            rent_ledger,
+            token_program: spl_token::ID,
+            system_program: solana_sdk::system_program::ID,
+            rent: sysvar::rent::ID,
+        }
+        .to_account_metas(None),
+        data: zpx_router::instruction::DepositForwardEscrow {
+            nonce,
+            spoke_id: 1,
+            amount: 1_000_000,
+            dst_domain: zpx_chains::CCTP_BASE.0,
+            recipient: zpx_router::Recipient::Evm20(evm_recipient),
+            lamport_budget: 0,
+            relayer_tip: 0,
+            expiry_reminder_slot: reminder_slot,
+        }
+        .data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit],
+        Some(&payer.pubkey()),
+        &[&payer, &user],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    let refund = |destination: Pubkey| Instruction {
+        program_id,
+        accounts: zpx_router::accounts::RefundExpiredEscrow {
+            cranker: bot.pubkey(),
+            user: user.pubkey(),
+            escrow,
+            escrow_vault,
+            destination,
+            rent_ledger,
+            config,
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None),
+        data: zpx_router::instruction::RefundExpiredEscrow {}.data(),
+    };
+    let scan = Instruction {
+        program_id,
+        accounts: {
+            let mut metas = zpx_router::accounts::ScanEscrowExpiry {
+                cranker: bot.pubkey(),
+            }
+            .to_account_metas(None);
+            // Escrows ride along as remaining accounts; unrelated accounts are skipped
+            metas.push(AccountMeta::new_readonly(escrow, false));
+            metas.push(AccountMeta::new_readonly(config, false));
+            metas
+        },
+        data: zpx_router::instruction::ScanEscrowExpiry {}.data(),
+    };
+
+    // Inside the notice period the escrow is only "expiring soon"
+    context.warp_to_slot(reminder_slot + 1).unwrap();
+    let blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    let send = |ix: Instruction, blockhash| {
+        Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[&payer, &bot], blockhash)
+    };
+    context
+        .banks_client
+        .process_transaction(send(scan.clone(), blockhash))
+        .await
+        .unwrap();
+    let err = context
+        .banks_client
+        .process_transaction(send(refund(from), blockhash))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::EscrowNotExpired));
+
+    context
+        .warp_to_slot(reminder_slot + ESCROW_EXPIRY_NOTICE_SLOTS)
+        .unwrap();
+    let blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    context
+        .banks_client
+        .process_transaction(send(scan, blockhash))
+        .await
+        .unwrap();
+    // Refunds never go anywhere but the depositor's own token account
+    let err = context
+        .banks_client
+        .process_transaction(send(refund(stranger_ata), blockhash))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::Unauthorized));
+    context
+        .banks_client
+        .process_transaction(send(refund(from), blockhash))
+        .await
+        .unwrap();
+
+    let banks = &mut context.banks_client;
+    let from_account = banks.get_account(from).await.unwrap().unwrap();
+    assert_eq!(token_amount(&from_account), 1_000_000);
+    assert!(banks.get_account(escrow).await.unwrap().is_none());
+    assert!(banks.get_account(escrow_vault).await.unwrap().is_none());
+}
@@ -0,0 +1,152 @@
+//! Binary layout conformance for the frozen events. Each event is built from fixed field
+//! values and its discriminator + Borsh encoding compared with the golden hex fixture in
+//! tests/fixtures/events, so a reordered field or changed type fails even when the names
+//! (the *_FIELDS snapshots) are unchanged.
+//!
+//! Regenerate fixtures only for an intentional schema bump: `ZPX_BLESS=1 cargo test`.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::Event;
+use zpx_router::{
+    BridgeInitiated, FeeAppliedDest, FeeAppliedSource, FeeAppliedSourceV2, FeeAppliedSourceV3,
+    UniversalBridgeInitiated,
+};
+
+fn key(b: u8) -> Pubkey {
+    Pubkey::new_from_array([b; 32])
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn check(name: &str, event: &impl Event) {
+    let path = format!(
+        "{}/tests/fixtures/events/{name}.hex",
+        env!("CARGO_MANIFEST_DIR")
+    );
+    let actual = hex(&event.data());
+    if std::env::var_os("ZPX_BLESS").is_some() {
+        std::fs::write(&path, format!("{actual}\n")).unwrap();
+        return;
+    }
+    let golden = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(actual, golden.trim(), "{name} binary layout changed");
+}
+
+#[test]
+fn bridge_initiated_layout() {
+    check(
+        "bridge_initiated",
+        &BridgeInitiated {
+            route_id: [1; 32],
+            user: key(2),
+            token: key(3),
+            target: key(4),
+            forwarded_amount: 5,
+            protocol_fee: 6,
+            relayer_fee: 7,
+            payload_hash: [8; 32],
+            src_chain_id: 9,
+            dst_chain_id: 10,
+            nonce: 11,
+        },
+    );
+}
+
+#[test]
+fn universal_bridge_initiated_layout() {
+    check(
+        "universal_bridge_initiated",
+        &UniversalBridgeInitiated {
+            route_id: [1; 32],
+            payload_hash: [2; 32],
+            message_hash: [3; 32],
+            global_route_id: [4; 32],
+            user: key(5),
+            token: key(6),
+            target: key(7),
+            forwarded_amount: 8,
+            protocol_fee: 9,
+            relayer_fee: 10,
+            src_chain_id: 11,
+            dst_chain_id: 12,
+            nonce: 13,
+        },
+    );
+}
+
+#[test]
+fn fee_applied_source_layout() {
+    check(
+        "fee_applied_source",
+        &FeeAppliedSource {
+            message_hash: [1; 32],
+            asset: key(2),
+            payer: key(3),
+            target: key(4),
+            protocol_fee: 5,
+            relayer_fee: 6,
+            fee_recipient: key(7),
+            applied_at: 8,
+        },
+    );
+}
+
+#[test]
+fn fee_applied_source_v2_layout() {
+    check(
+        "fee_applied_source_v2",
+        &FeeAppliedSourceV2 {
+            message_hash: [1; 32],
+            asset: key(2),
+            payer: key(3),
+            target: key(4),
+            protocol_fee: 5,
+            relayer_fee: 6,
+            fee_recipient: key(7),
+            applied_at: 8,
+            zpx_fee_burned: 9,
+            fee_recipient_epoch: 10,
+        },
+    );
+}
+
+#[test]
+fn fee_applied_source_v3_layout() {
+    check(
+        "fee_applied_source_v3",
+        &FeeAppliedSourceV3 {
+            message_hash: [1; 32],
+            asset: key(2),
+            payer: key(3),
+            target: key(4),
+            protocol_fee: 5,
+            relayer_fee: 6,
+            fee_recipient: key(7),
+            applied_at: 8,
+            zpx_fee_burned: 9,
+            fee_recipient_epoch: 10,
+            config_version: 11,
+        },
+    );
+}
+
+#[test]
+fn fee_applied_dest_layout() {
+    check(
+        "fee_applied_dest",
+        &FeeAppliedDest {
+            message_hash: [1; 32],
+            src_chain_id: 2,
+            dst_chain_id: 3,
+            router: key(4),
+            asset: key(5),
+            amount: 6,
+            protocol_bps: 7,
+            lp_bps: 8,
+            collector: key(9),
+            applied_at: 10,
+        },
+    );
+}
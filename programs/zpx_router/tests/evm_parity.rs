@@ -0,0 +1,44 @@
+//! Parity with the EVM router over tests/fixtures/hashes.evm.json, the vector file written by
+//! the EVM repo's generator (not by this crate): domain separators, message hashes, route
+//! ids, payload hashes and recipient encodings must match byte for byte. Refresh the fixture
+//! with `evm-vectors refresh` (src/bin/evm_vectors) after the EVM side regenerates it.
+
+#[path = "../src/bin/evm_vectors/parity.rs"]
+mod parity;
+
+#[test]
+fn evm_vectors_match() {
+    let doc = parity::load(&parity::fixture_path()).unwrap();
+    let found = parity::mismatches(&doc).unwrap();
+    let report: Vec<String> = found.iter().map(ToString::to_string).collect();
+    assert!(
+        report.is_empty(),
+        "EVM parity broken:\n{}",
+        report.join("\n")
+    );
+    for section in ["domains", "messages", "payloads", "recipients"] {
+        assert!(
+            !doc[section].as_array().unwrap().is_empty(),
+            "{section} has no vectors"
+        );
+    }
+}
+
+#[test]
+fn mismatches_name_the_vector_and_field() {
+    let mut doc = parity::load(&parity::fixture_path()).unwrap();
+    doc["messages"][0]["routeId"] = format!("0x{}", "00".repeat(32)).into();
+    doc["payloads"][1]["payloadHash"] = format!("0x{}", "00".repeat(32)).into();
+    let found = parity::mismatches(&doc).unwrap();
+    let fields: Vec<_> = found.iter().map(|m| (m.vector.as_str(), m.field)).collect();
+    assert_eq!(
+        fields,
+        [
+            ("messages/base_usdc_legacy", "routeId"),
+            ("payloads/versioned_body", "payloadHash"),
+        ]
+    );
+
+    doc["version"] = (parity::VECTORS_VERSION + 1).into();
+    assert!(parity::mismatches(&doc).is_err());
+}
@@ -0,0 +1,440 @@
+use anchor_lang::{AccountDeserialize, AccountSerialize, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::{Instruction, InstructionError},
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    sysvar,
+    transaction::{Transaction, TransactionError},
+};
+use zpx_router::{
+    Config, ErrorCode, ForwardEscrow, Registry, RelayerVesting, SpokeEntry, MAX_SPOKES,
+};
+
+fn anchor_account<T: AccountSerialize>(value: &T) -> Account {
+    let mut data = Vec::new();
+    value.try_serialize(&mut data).unwrap();
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: zpx_router::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn packed_account<T: Pack>(value: T) -> Account {
+    let mut data = vec![0u8; T::LEN];
+    value.pack_into_slice(&mut data);
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: spl_token::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> Account {
+    packed_account(spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        delegate: COption::None,
+        state: spl_token::state::AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    })
+}
+
+fn custom(code: ErrorCode) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(code.into()))
+}
+
+async fn token_balance(banks_client: &mut BanksClient, account: Pubkey) -> u64 {
+    let account = banks_client.get_account(account).await.unwrap().unwrap();
+    spl_token::state::Account::unpack(&account.data)
+        .unwrap()
+        .amount
+}
+
+#[tokio::test]
+async fn held_fees_are_refunded_on_failure_and_settled_on_confirmation() {
+    let program_id = zpx_router::ID;
+    let mut program_test =
+        ProgramTest::new("zpx_router", program_id, processor!(zpx_router::entry));
+
+    let admin = Keypair::new();
+    let relayer = Keypair::new();
+    let user = Keypair::new();
+    let mint = Pubkey::new_unique();
+    let from = Pubkey::new_unique();
+    let custody = Pubkey::new_unique();
+    let adapter_target = Pubkey::new_unique();
+    let spoke_id = 1u32;
+    let mut evm_recipient = [0u8; 20];
+    evm_recipient[19] = 1;
+    let mut mint_recipient = [0u8; 32];
+    mint_recipient[12..].copy_from_slice(&evm_recipient);
+
+    let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &program_id);
+    let (config, config_bump) = pda(&[b"zpx_config"]);
+    let (registry, registry_bump) = pda(&[b"hub_registry"]);
+    let (hub_protocol_vault, _) = pda(&[b"hub_protocol_vault", mint.as_ref()]);
+    let (hub_relayer_vault, _) = pda(&[b"hub_relayer_vault", mint.as_ref()]);
+    let (rent_ledger, _) = pda(&[b"rent_ledger"]);
+    let escrow_of = |nonce: u64| {
+        let escrow = pda(&[
+            b"forward_escrow",
+            user.pubkey().as_ref(),
+            &nonce.to_le_bytes(),
+        ])
+        .0;
+        (escrow, pda(&[b"forward_escrow_vault", escrow.as_ref()]).0)
+    };
+
+    program_test.add_account(
+        config,
+        anchor_account(&Config {
+            admin: admin.pubkey(),
+            fee_recipient: Pubkey::new_unique(),
+            src_chain_id: 1,
+            relayer_fee_bps: 100,
+            protocol_fee_bps: 5,
+            relayer_pubkey: relayer.pubkey(),
+            accept_any_token: true,
+            allowed_token_mint: Pubkey::default(),
+            direct_relayer_payout_default: false,
+            min_forward_amount: 0,
+            adapters_len: 0,
+            adapters: [Pubkey::default(); 8],
+            paused: false,
+            bump: config_bump,
+            max_relayer_fee_lamports: 0,
+            usd_limits_enabled: false,
+            strict_vaults: false,
+            lp_fee_bps: 0,
+            lp_vault_program: Pubkey::default(),
+            killed: false,
+            kill_release_slot: 0,
+            guardian: Pubkey::default(),
+            admin_op_keys: [0; 8],
+            admin_op_cursor: 0,
+            legacy_message_hash: false,
+            features: zpx_router::FEATURES_DEFAULT | 1 << zpx_router::FEATURE_FEE_ESCROW,
+            bridge_hook: Pubkey::default(),
+            bridge_hook_best_effort: false,
+            namespace_len: 0,
+            namespace: [0; zpx_router::SANDBOX_NAMESPACE_LEN],
+            schema_version: zpx_router::CONFIG_SCHEMA_VERSION,
+            rent_refund_policy: zpx_router::RENT_REFUND_PAYER,
+            rent_refund_caller_bps: 0,
+            config_version: 0,
+            paused_families: 0,
+        }),
+    );
+    let mut spokes = [SpokeEntry::default(); MAX_SPOKES];
+    spokes[0] = SpokeEntry {
+        spoke_id,
+        adapter_program: Pubkey::new_unique(),
+        enabled: true,
+        target_owner: custody,
+        ..SpokeEntry::default()
+    };
+    program_test.add_account(
+        registry,
+        anchor_account(&Registry {
+            spokes_len: 1,
+            spokes,
+            bump: registry_bump,
+            schema_version: zpx_router::REGISTRY_SCHEMA_VERSION,
+        }),
+    );
+    program_test.add_account(
+        mint,
+        packed_account(spl_token::state::Mint {
+            mint_authority: COption::None,
+            supply: 2_000_000,
+            decimals: 6,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        }),
+    );
+    program_test.add_account(from, token_account(mint, user.pubkey(), 2_000_000));
+    program_test.add_account(adapter_target, token_account(mint, custody, 0));
+    program_test.add_account(
+        hub_protocol_vault,
+        token_account(mint, hub_protocol_vault, 0),
+    );
+    program_test.add_account(hub_relayer_vault, token_account(mint, hub_relayer_vault, 0));
+    for wallet in [&user, &admin, &relayer] {
+        program_test.add_account(
+            wallet.pubkey(),
+            Account::new(10_000_000_000, 0, &solana_sdk::system_program::ID),
+        );
+    }
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let tx = |ix: Instruction, signer: &Keypair| {
+        Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&payer.pubkey()),
+            &[&payer, signer],
+            recent_blockhash,
+        )
+    };
+
+    let deposit = |nonce: u64| {
+        let (escrow, escrow_vault) = escrow_of(nonce);
+        Instruction {
+            program_id,
+            accounts: zpx_router::accounts::DepositForwardEscrow {
+                user: user.pubkey(),
+                mint,
+                from,
+                escrow,
+                escrow_vault,
+                config,
+                rent_ledger,
+                token_program: spl_token::ID,
+                system_program: solana_sdk::system_program::ID,
+                rent: sysvar::rent::ID,
+            }
+            .to_account_metas(None),
+            data: zpx_router::instruction::DepositForwardEscrow {
+                nonce,
+                spoke_id,
+                amount: 1_000_000,
+                dst_domain: zpx_chains::CCTP_BASE.0,
+                recipient: zpx_router::Recipient::Evm20(evm_recipient),
+                lamport_budget: 0,
+                relayer_tip: 0,
+                expiry_reminder_slot: 0,
+            }
+            .data(),
+        }
+    };
+    let forward = |nonce: u64| {
+        let (escrow, escrow_vault) = escrow_of(nonce);
+        let r = relayer.pubkey();
+        Instruction {
+            program_id,
+            accounts: zpx_router::accounts::ForwardViaSpoke {
+                user: user.pubkey(),
+                relayer: r,
+                mint,
+                escrow,
+                escrow_vault,
+                hub_protocol_vault,
+                hub_relayer_vault,
+                relayer_token_account: adapter_target,
+                adapter_target_token_account: adapter_target,
+                registry,
+                config,
+                message_account: Pubkey::new_unique(),
+                adapter_program_data: Pubkey::new_unique(),
+                vault_registry: pda(&[b"vault_registry", mint.as_ref()]).0,
+                spoke_relayers: pda(&[b"spoke_relayers", &spoke_id.to_le_bytes()]).0,
+                router_stats: pda(&[b"router_stats"]).0,
+                mint_pause: pda(&[b"mint_pause", mint.as_ref()]).0,
+                mint_allowlist: pda(&[b"mint_allowlist"]).0,
+                rent_ledger,
+                fee_policy: pda(&[
+                    b"fee_policy",
+                    mint.as_ref(),
+                    &zpx_chains::BASE.0.to_le_bytes(),
+                ])
+                .0,
+                relayer_cosign: pda(&[b"relayer_cosign", mint.as_ref()]).0,
+                forward_state: pda(&[b"forward_state", escrow.as_ref()]).0,
+                relayer_vesting_config: pda(&[b"relayer_vesting_config"]).0,
+                relayer_vesting: pda(&[b"relayer_vesting", r.as_ref(), mint.as_ref()]).0,
+                token_program: spl_token::ID,
+                system_program: solana_sdk::system_program::ID,
+                relayer_activity: pda(&[b"relayer_activity", r.as_ref()]).0,
+            }
+            .to_account_metas(None),
+            data: zpx_router::instruction::ForwardViaSpoke {
+                spoke_id,
+                amount: 1_000_000,
+                dst_domain: zpx_chains::CCTP_BASE.0,
+                mint_recipient,
+                is_protocol_fee: true,
+                is_relayer_fee: true,
+                _nonce: nonce,
+                relayer_fee_lamports: 0,
+                adapter_payload: vec![],
+            }
+            .data(),
+        }
+    };
+    let refund = |nonce: u64, authority: Pubkey| {
+        let (escrow, escrow_vault) = escrow_of(nonce);
+        Instruction {
+            program_id,
+            accounts: zpx_router::accounts::RefundEscrowFees {
+                authority,
+                config,
+                user: user.pubkey(),
+                escrow,
+                escrow_vault,
+                destination: from,
+                rent_ledger,
+                token_program: spl_token::ID,
+            }
+            .to_account_metas(None),
+            data: zpx_router::instruction::RefundEscrowFees {}.data(),
+        }
+    };
+    let confirm = |nonce: u64, authority: Pubkey| {
+        let (escrow, escrow_vault) = escrow_of(nonce);
+        let r = relayer.pubkey();
+        Instruction {
+            program_id,
+            accounts: zpx_router::accounts::ConfirmEscrowDispatch {
+                authority,
+                config,
+                user: user.pubkey(),
+                escrow,
+                escrow_vault,
+                hub_protocol_vault,
+                hub_relayer_vault,
+                relayer_token_account: adapter_target,
+                relayer_vesting_config: pda(&[b"relayer_vesting_config"]).0,
+                relayer_vesting: pda(&[b"relayer_vesting", r.as_ref(), mint.as_ref()]).0,
+                rent_ledger,
+                token_program: spl_token::ID,
+                system_program: solana_sdk::system_program::ID,
+            }
+            .to_account_metas(None),
+            data: zpx_router::instruction::ConfirmEscrowDispatch {}.data(),
+        }
+    };
+
+    // 5 bps protocol + 100 bps relayer on 1_000_000
+    let (protocol_fee, relayer_fee) = (500u64, 10_000u64);
+    let net = 1_000_000 - protocol_fee - relayer_fee;
+
+    // Adapter leg fails: the fees go back to the user
+    banks_client
+        .process_transaction(tx(deposit(1), &user))
+        .await
+        .unwrap();
+    banks_client
+        .process_transaction(tx(forward(1), &relayer))
+        .await
+        .unwrap();
+    let (escrow, escrow_vault) = escrow_of(1);
+    assert_eq!(token_balance(&mut banks_client, adapter_target).await, net);
+    assert_eq!(
+        token_balance(&mut banks_client, escrow_vault).await,
+        protocol_fee + relayer_fee
+    );
+    assert_eq!(
+        token_balance(&mut banks_client, hub_protocol_vault).await,
+        0
+    );
+    let state = banks_client.get_account(escrow).await.unwrap().unwrap();
+    let state = ForwardEscrow::try_deserialize(&mut &state.data[..]).unwrap();
+    assert_eq!(
+        (
+            state.held_protocol_fee,
+            state.held_relayer_fee,
+            state.fee_payee
+        ),
+        (protocol_fee, relayer_fee, relayer.pubkey())
+    );
+
+    // The user cannot pull held fees out through a reclaim
+    let reclaim = Instruction {
+        program_id,
+        accounts: zpx_router::accounts::ReclaimForwardEscrow {
+            user: user.pubkey(),
+            escrow,
+            escrow_vault,
+            destination: from,
+            rent_ledger,
+            config,
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None),
+        data: zpx_router::instruction::ReclaimForwardEscrow {}.data(),
+    };
+    let err = banks_client
+        .process_transaction(tx(reclaim, &user))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::FeesHeld));
+    let err = banks_client
+        .process_transaction(tx(refund(1, user.pubkey()), &user))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::Unauthorized));
+
+    banks_client
+        .process_transaction(tx(refund(1, relayer.pubkey()), &relayer))
+        .await
+        .unwrap();
+    assert_eq!(
+        token_balance(&mut banks_client, from).await,
+        1_000_000 + protocol_fee + relayer_fee
+    );
+    assert!(banks_client.get_account(escrow).await.unwrap().is_none());
+    assert!(banks_client
+        .get_account(escrow_vault)
+        .await
+        .unwrap()
+        .is_none());
+
+    // Dispatch confirmed: the fees settle to the hub vaults and the relayer's vesting
+    banks_client
+        .process_transaction(tx(deposit(2), &user))
+        .await
+        .unwrap();
+    banks_client
+        .process_transaction(tx(forward(2), &relayer))
+        .await
+        .unwrap();
+    banks_client
+        .process_transaction(tx(confirm(2, admin.pubkey()), &admin))
+        .await
+        .unwrap();
+    assert_eq!(
+        token_balance(&mut banks_client, hub_protocol_vault).await,
+        protocol_fee
+    );
+    assert_eq!(
+        token_balance(&mut banks_client, hub_relayer_vault).await,
+        relayer_fee
+    );
+    let vesting = banks_client
+        .get_account(pda(&[b"relayer_vesting", relayer.pubkey().as_ref(), mint.as_ref()]).0)
+        .await
+        .unwrap()
+        .unwrap();
+    let vesting = RelayerVesting::try_deserialize(&mut &vesting.data[..]).unwrap();
+    assert_eq!(vesting.relayer, relayer.pubkey());
+    assert_eq!(vesting.locked + vesting.unlocked, relayer_fee);
+    let (escrow, _) = escrow_of(2);
+    assert!(banks_client.get_account(escrow).await.unwrap().is_none());
+    // Nothing is left to settle twice
+    let err = banks_client
+        .process_transaction(tx(confirm(2, relayer.pubkey()), &relayer))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(anchor_lang::error::ErrorCode::AccountNotInitialized.into())
+        )
+    );
+}
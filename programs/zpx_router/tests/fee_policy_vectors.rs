@@ -0,0 +1,125 @@
+//! Golden fee quotes over a policy matrix. Every (policy, amount) pair is priced through the
+//! on-chain FeePolicy account's `to_core` and `evaluate_fee_policy`, and the result must match
+//! the line recorded in tests/fixtures/fee_policies.txt, so a change to bracket selection,
+//! rounding, minimums or caps shows up as a diff.
+//!
+//! Regenerate the fixture only for an intentional pricing change: `ZPX_BLESS=1 cargo test`.
+
+use anchor_lang::prelude::Pubkey;
+use zpx_router::{evaluate_fee_policy, FeePolicy, FeeTier, MAX_FEE_TIERS};
+
+const AMOUNTS: &[u64] = &[
+    1,
+    1_000,
+    999_999,
+    1_000_000,
+    50_000_000,
+    100_000_000,
+    10_000_000_000_000,
+    u64::MAX,
+];
+
+/// (name, tiers as (min_amount, protocol_bps, relayer_bps), protocol min/max, relayer min/max)
+type PolicyRow = (
+    &'static str,
+    &'static [(u64, u16, u16)],
+    (u64, u64),
+    (u64, u64),
+);
+
+const POLICIES: &[PolicyRow] = &[
+    ("flat", &[(0, 5, 100)], (0, 0), (0, 0)),
+    (
+        "tiered",
+        &[(0, 5, 50), (1_000_000, 3, 25), (100_000_000, 1, 10)],
+        (0, 0),
+        (0, 0),
+    ),
+    ("floored", &[(0, 2, 10)], (500, 0), (1_000, 0)),
+    ("capped", &[(0, 5, 1_000)], (0, 10_000), (0, 250_000)),
+    (
+        "floor_and_cap",
+        &[(0, 5, 100), (10_000_000, 0, 50)],
+        (100, 1_000),
+        (2_000, 50_000),
+    ),
+    ("relayer_only", &[(0, 0, 1_000)], (0, 0), (0, 0)),
+];
+
+fn policy(row: &PolicyRow) -> FeePolicy {
+    let (_, tiers, protocol, relayer) = *row;
+    let mut policy = FeePolicy {
+        mint: Pubkey::new_unique(),
+        dst_chain_id: zpx_chains::BASE.0,
+        tiers_len: tiers.len() as u8,
+        tiers: [FeeTier::default(); MAX_FEE_TIERS],
+        protocol_min_fee: protocol.0,
+        protocol_max_fee: protocol.1,
+        relayer_min_fee: relayer.0,
+        relayer_max_fee: relayer.1,
+        bump: 255,
+    };
+    for (slot, &(min_amount, protocol_bps, relayer_bps)) in policy.tiers.iter_mut().zip(tiers) {
+        *slot = FeeTier {
+            min_amount,
+            protocol_bps,
+            relayer_bps,
+        };
+    }
+    policy
+}
+
+/// One fixture line per (policy, amount): the three quote fields or the rejection.
+fn quote_lines() -> String {
+    let mut out = String::new();
+    for row in POLICIES {
+        let core = policy(row).to_core();
+        assert_eq!(core.validate(u16::MAX, 0), Ok(()), "{} is malformed", row.0);
+        for &amount in AMOUNTS {
+            let line = match evaluate_fee_policy(&core, amount) {
+                Ok(q) => format!(
+                    "{} {amount} {} {} {}",
+                    row.0, q.protocol_fee, q.relayer_fee, q.forward_amount
+                ),
+                Err(e) => format!("{} {amount} {e:?}", row.0),
+            };
+            out.push_str(&line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+#[test]
+fn policy_matrix_matches_golden_quotes() {
+    let path = format!(
+        "{}/tests/fixtures/fee_policies.txt",
+        env!("CARGO_MANIFEST_DIR")
+    );
+    let actual = quote_lines();
+    if std::env::var_os("ZPX_BLESS").is_some() {
+        std::fs::write(&path, &actual).unwrap();
+    }
+    let golden = std::fs::read_to_string(&path).unwrap();
+    for (a, g) in actual.lines().zip(golden.lines()) {
+        assert_eq!(a, g, "fee policy quote changed");
+    }
+    assert_eq!(actual.lines().count(), golden.lines().count());
+}
+
+#[test]
+fn quotes_conserve_the_amount() {
+    for row in POLICIES {
+        let core = policy(row).to_core();
+        for &amount in AMOUNTS {
+            if let Ok(q) = evaluate_fee_policy(&core, amount) {
+                assert_eq!(
+                    q.protocol_fee as u128 + q.relayer_fee as u128 + q.forward_amount as u128,
+                    amount as u128,
+                    "{} {amount}",
+                    row.0
+                );
+            }
+        }
+    }
+}
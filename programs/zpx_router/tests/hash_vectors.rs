@@ -0,0 +1,58 @@
+//! Golden message-hash / route-id vectors shared with the EVM router. Inputs are fixed; the
+//! hex fixtures in tests/fixtures/hashes are what both sides must produce, under the legacy
+//! packing and under the domain-separated scheme for this hub on mainnet and devnet.
+//!
+//! Regenerate fixtures only for an intentional scheme change: `ZPX_BLESS=1 cargo test`.
+
+use zpx_router::hash::{
+    global_route_id, message_hash_be, HashScheme, ENV_DEVNET, ENV_MAINNET, HASH_SCHEMA_VERSION,
+};
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn check(name: &str, actual: [u8; 32]) {
+    let path = format!(
+        "{}/tests/fixtures/hashes/{name}.hex",
+        env!("CARGO_MANIFEST_DIR")
+    );
+    let actual = hex(&actual);
+    if std::env::var_os("ZPX_BLESS").is_some() {
+        std::fs::write(&path, format!("{actual}\n")).unwrap();
+        return;
+    }
+    let golden = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(actual, golden.trim(), "{name} vector changed");
+}
+
+fn vectors(name: &str, scheme: HashScheme) {
+    let mut amount_be = [0u8; 32];
+    amount_be[16..].copy_from_slice(&1_000_000u128.to_be_bytes());
+    let msg = message_hash_be(
+        scheme, 10, [1; 32], [2; 32], [3; 32], amount_be, [5; 32], 7, 8453,
+    );
+    check(&format!("{name}_message"), msg);
+    check(
+        &format!("{name}_route"),
+        global_route_id(scheme, 10, 8453, [9; 32], msg, 7),
+    );
+}
+
+#[test]
+fn legacy_vectors() {
+    vectors("legacy", HashScheme::Legacy);
+}
+
+#[test]
+fn domain_separated_vectors() {
+    let hub = zpx_router::ID.to_bytes();
+    vectors(
+        "mainnet_v1",
+        HashScheme::domain(hub, ENV_MAINNET, HASH_SCHEMA_VERSION),
+    );
+    vectors(
+        "devnet_v1",
+        HashScheme::domain(hub, ENV_DEVNET, HASH_SCHEMA_VERSION),
+    );
+}
@@ -0,0 +1,92 @@
+//! Heap budget of the universal_bridge_transfer / forward hot-path helpers. The on-chain heap
+//! is a bump allocator that never frees, so every allocation here is permanent for the
+//! transaction; these helpers must stay allocation-free (the adapter instruction data is the
+//! one deliberate, exactly-sized allocation).
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use zpx_router::hash::{
+    global_route_id, keccak256, message_hash_be, HashScheme, ENV_MAINNET, HASH_SCHEMA_VERSION,
+};
+use zpx_router::{
+    adapter_validate_payload_data, compute_fees_and_forward, fee_split_shares,
+    in_flight_after_forward, validate_common, validate_payload_len, validate_payload_schema,
+    PayloadSchema,
+};
+
+struct Counting;
+
+thread_local! {
+    static ALLOCS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCS.with(|n| n.set(n.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn allocations<T>(f: impl FnOnce() -> T) -> (usize, T) {
+    let before = ALLOCS.with(Cell::get);
+    let out = f();
+    (ALLOCS.with(Cell::get) - before, out)
+}
+
+#[test]
+fn source_leg_helpers_do_not_allocate() {
+    let payload = [0xabu8; 300];
+    let (n, _) = allocations(|| {
+        validate_common(1_000_000, payload.len(), false, 10).unwrap();
+        validate_payload_len(payload.len()).unwrap();
+        let (forward, fees) = compute_fees_and_forward(1_000_000, 500, 100, 1_000).unwrap();
+        assert_eq!(forward + fees, 1_000_000);
+        in_flight_after_forward(5, forward, 0).unwrap();
+        let shares = fee_split_shares(500, &[6_000, 4_000]);
+        assert_eq!(shares[..2], [300, 200]);
+
+        let scheme = HashScheme::domain([7; 32], ENV_MAINNET, HASH_SCHEMA_VERSION);
+        let payload_hash = keccak256(&[&payload]);
+        let msg = message_hash_be(
+            scheme,
+            10,
+            [1; 32],
+            [0; 32],
+            [3; 32],
+            [4; 32],
+            payload_hash,
+            7,
+            8453,
+        );
+        global_route_id(scheme, 10, 8453, [9; 32], msg, 7)
+    });
+    assert_eq!(n, 0, "source-leg helpers allocated {n} times");
+}
+
+#[test]
+fn forward_helpers_allocate_only_instruction_data() {
+    let schema = PayloadSchema {
+        min_len: 4,
+        max_len: 64,
+        require_version: true,
+        version: 1,
+        magic_len: 2,
+        magic: [0x5a, 0x50, 0, 0],
+        max_version: 2,
+    };
+    let payload = [0x5a, 0x50, 2, 9, 9, 9];
+    let (n, _) = allocations(|| validate_payload_schema(&schema, &payload).unwrap());
+    assert_eq!(n, 0);
+
+    let (n, data) = allocations(|| adapter_validate_payload_data(&payload).unwrap());
+    assert_eq!(n, 1);
+    assert_eq!(data.len(), data.capacity());
+}
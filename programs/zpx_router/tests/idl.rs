@@ -0,0 +1,107 @@
+//! The TS-client IDL (src/bin/idl_extra) checked against the program's own source of
+//! truth: every #[event] is exported with its docs, the frozen events keep their *_FIELDS
+//! order, and the error table matches ERROR_CODES.
+
+#[path = "../src/bin/idl_extra/idl.rs"]
+mod idl;
+
+use serde_json::Value;
+
+fn event<'a>(idl: &'a Value, name: &str) -> &'a Value {
+    idl["events"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|e| e["name"] == name)
+        .unwrap_or_else(|| panic!("{name} missing from the IDL"))
+}
+
+fn field_names(event: &Value) -> Vec<&str> {
+    event["fields"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| f["name"].as_str().unwrap())
+        .collect()
+}
+
+#[test]
+fn idl_exports_every_event_with_docs_and_hex_hints() {
+    let idl = idl::build(&idl::lib_path()).unwrap();
+    let source = std::fs::read_to_string(idl::lib_path()).unwrap();
+    assert_eq!(
+        idl["events"].as_array().unwrap().len(),
+        source.matches("\n#[event]\n").count()
+    );
+    assert_eq!(idl["metadata"]["schemaVersion"], idl::IDL_SCHEMA_VERSION);
+    assert_eq!(idl["metadata"]["address"], zpx_router::ID.to_string());
+
+    for (name, fields) in [
+        ("BridgeInitiated", zpx_router::BRIDGE_INITIATED_FIELDS),
+        (
+            "UniversalBridgeInitiated",
+            zpx_router::UNIVERSAL_BRIDGE_INITIATED_FIELDS,
+        ),
+        ("FeeAppliedSource", zpx_router::FEE_APPLIED_SOURCE_FIELDS),
+        (
+            "FeeAppliedSourceV2",
+            zpx_router::FEE_APPLIED_SOURCE_V2_FIELDS,
+        ),
+        (
+            "FeeAppliedSourceV3",
+            zpx_router::FEE_APPLIED_SOURCE_V3_FIELDS,
+        ),
+        ("FeeAppliedDest", zpx_router::FEE_APPLIED_DEST_FIELDS),
+    ] {
+        let fields: Vec<String> = fields.iter().map(|f| idl::camel(f)).collect();
+        assert_eq!(field_names(event(&idl, name)), fields, "{name}");
+    }
+
+    // [u8; 32] fields carry the hex hint, other fields do not
+    let bridged = event(&idl, "BridgeInitiated");
+    for field in bridged["fields"].as_array().unwrap() {
+        let hex = field["encoding"] == "hex";
+        assert_eq!(
+            hex,
+            field["name"] == "routeId" || field["name"] == "payloadHash",
+            "{}",
+            field["name"]
+        );
+    }
+
+    // Event and field doc comments survive
+    let held = event(&idl, "AdapterComputeExceeded");
+    assert!(held["docs"][0]
+        .as_str()
+        .unwrap()
+        .starts_with("An adapter CPI consumed more compute"));
+    let forwarded = event(&idl, "Forwarded");
+    let mode = forwarded["fields"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|f| f["name"] == "relayerFeeMode")
+        .unwrap();
+    assert_eq!(
+        mode["docs"][0],
+        "RELAYER_FEE_MODE_TOKEN or RELAYER_FEE_MODE_LAMPORTS"
+    );
+
+    let errors: Vec<(u64, &str, &str)> = idl["errors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| {
+            (
+                e["code"].as_u64().unwrap(),
+                e["name"].as_str().unwrap(),
+                e["msg"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    let table: Vec<(u64, &str, &str)> = zpx_router::ERROR_CODES
+        .iter()
+        .map(|(code, name, msg)| (*code as u64, *name, *msg))
+        .collect();
+    assert_eq!(errors, table);
+}
@@ -0,0 +1,376 @@
+//! Instruction pause: set_instruction_pause switches one instruction family off while the
+//! others keep running, e.g. withdrawals frozen during an investigation while users bridge.
+
+use anchor_lang::{AccountDeserialize, AccountSerialize, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::{Instruction, InstructionError},
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    sysvar,
+    transaction::{Transaction, TransactionError},
+};
+use zpx_router::{
+    Config, ErrorCode, Registry, SpokeEntry, INSTRUCTION_FAMILY_BRIDGE, INSTRUCTION_FAMILY_COUNT,
+    INSTRUCTION_FAMILY_FORWARD, INSTRUCTION_FAMILY_WITHDRAW, MAX_SPOKES,
+};
+
+const AMOUNT: u64 = 1_000_000;
+
+fn anchor_account<T: AccountSerialize>(value: &T) -> Account {
+    let mut data = Vec::new();
+    value.try_serialize(&mut data).unwrap();
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: zpx_router::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn packed_account<T: Pack>(value: T) -> Account {
+    let mut data = vec![0u8; T::LEN];
+    value.pack_into_slice(&mut data);
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: spl_token::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn token_state(mint: Pubkey, owner: Pubkey, amount: u64) -> spl_token::state::Account {
+    spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        delegate: COption::None,
+        state: spl_token::state::AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    }
+}
+
+fn custom(code: ErrorCode) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(code.into()))
+}
+
+#[tokio::test]
+async fn paused_families_fail_while_the_others_run() {
+    let program_id = zpx_router::ID;
+    let mut program_test =
+        ProgramTest::new("zpx_router", program_id, processor!(zpx_router::entry));
+
+    let admin = Keypair::new();
+    let relayer = Keypair::new();
+    let user = Keypair::new();
+    let mint = Pubkey::new_unique();
+    let from = Pubkey::new_unique();
+    let custody = Pubkey::new_unique();
+    let adapter_target = Pubkey::new_unique();
+    let spoke_id = 1u32;
+    let mut evm_recipient = [0u8; 20];
+    evm_recipient[19] = 1;
+    let mut mint_recipient = [0u8; 32];
+    mint_recipient[12..].copy_from_slice(&evm_recipient);
+
+    let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &program_id);
+    let (config, config_bump) = pda(&[b"zpx_config"]);
+    let (registry, registry_bump) = pda(&[b"hub_registry"]);
+    let (hub_protocol_vault, _) = pda(&[b"hub_protocol_vault", mint.as_ref()]);
+    let (hub_relayer_vault, _) = pda(&[b"hub_relayer_vault", mint.as_ref()]);
+    let (rent_ledger, _) = pda(&[b"rent_ledger"]);
+    let escrow_of = |nonce: u64| {
+        let escrow = pda(&[
+            b"forward_escrow",
+            user.pubkey().as_ref(),
+            &nonce.to_le_bytes(),
+        ])
+        .0;
+        (escrow, pda(&[b"forward_escrow_vault", escrow.as_ref()]).0)
+    };
+
+    program_test.add_account(
+        config,
+        anchor_account(&Config {
+            admin: admin.pubkey(),
+            fee_recipient: Pubkey::new_unique(),
+            src_chain_id: 1,
+            relayer_fee_bps: 0,
+            protocol_fee_bps: 5,
+            relayer_pubkey: relayer.pubkey(),
+            accept_any_token: true,
+            allowed_token_mint: Pubkey::default(),
+            direct_relayer_payout_default: true,
+            min_forward_amount: 0,
+            adapters_len: 0,
+            adapters: [Pubkey::default(); 8],
+            paused: false,
+            bump: config_bump,
+            max_relayer_fee_lamports: 0,
+            usd_limits_enabled: false,
+            strict_vaults: false,
+            lp_fee_bps: 0,
+            lp_vault_program: Pubkey::default(),
+            killed: false,
+            kill_release_slot: 0,
+            guardian: Pubkey::default(),
+            admin_op_keys: [0; 8],
+            admin_op_cursor: 0,
+            legacy_message_hash: false,
+            features: zpx_router::FEATURES_DEFAULT,
+            bridge_hook: Pubkey::default(),
+            bridge_hook_best_effort: false,
+            namespace_len: 0,
+            namespace: [0; zpx_router::SANDBOX_NAMESPACE_LEN],
+            schema_version: zpx_router::CONFIG_SCHEMA_VERSION,
+            rent_refund_policy: zpx_router::RENT_REFUND_PAYER,
+            rent_refund_caller_bps: 0,
+            config_version: 0,
+            paused_families: 0,
+        }),
+    );
+    let mut spokes = [SpokeEntry::default(); MAX_SPOKES];
+    spokes[0] = SpokeEntry {
+        spoke_id,
+        adapter_program: Pubkey::new_unique(),
+        enabled: true,
+        target_owner: custody,
+        ..SpokeEntry::default()
+    };
+    program_test.add_account(
+        registry,
+        anchor_account(&Registry {
+            spokes_len: 1,
+            spokes,
+            bump: registry_bump,
+            schema_version: zpx_router::REGISTRY_SCHEMA_VERSION,
+        }),
+    );
+    program_test.add_account(
+        mint,
+        packed_account(spl_token::state::Mint {
+            mint_authority: COption::None,
+            supply: 3 * AMOUNT,
+            decimals: 6,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        }),
+    );
+    program_test.add_account(
+        from,
+        packed_account(token_state(mint, user.pubkey(), 3 * AMOUNT)),
+    );
+    program_test.add_account(
+        adapter_target,
+        packed_account(token_state(mint, custody, 0)),
+    );
+    let relayer_ata = Pubkey::new_unique();
+    program_test.add_account(
+        relayer_ata,
+        packed_account(token_state(mint, relayer.pubkey(), 0)),
+    );
+    for vault in [hub_protocol_vault, hub_relayer_vault] {
+        program_test.add_account(vault, packed_account(token_state(mint, vault, 0)));
+    }
+
+    for wallet in [&admin, &user, &relayer] {
+        program_test.add_account(
+            wallet.pubkey(),
+            Account::new(10_000_000_000, 0, &solana_sdk::system_program::ID),
+        );
+    }
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let tx = |ix: Instruction, signer: &Keypair| {
+        Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&payer.pubkey()),
+            &[&payer, signer],
+            recent_blockhash,
+        )
+    };
+
+    let deposit = |nonce: u64| {
+        let (escrow, escrow_vault) = escrow_of(nonce);
+        Instruction {
+            program_id,
+            accounts: zpx_router::accounts::DepositForwardEscrow {
+                user: user.pubkey(),
+                mint,
+                from,
+                escrow,
+                escrow_vault,
+                config,
+                rent_ledger,
+                token_program: spl_token::ID,
+                system_program: solana_sdk::system_program::ID,
+                rent: sysvar::rent::ID,
+            }
+            .to_account_metas(None),
+            data: zpx_router::instruction::DepositForwardEscrow {
+                nonce,
+                spoke_id,
+                amount: AMOUNT,
+                dst_domain: zpx_chains::CCTP_BASE.0,
+                recipient: zpx_router::Recipient::Evm20(evm_recipient),
+                lamport_budget: 0,
+                relayer_tip: 0,
+                expiry_reminder_slot: 0,
+            }
+            .data(),
+        }
+    };
+    let forward = |nonce: u64| {
+        let (escrow, escrow_vault) = escrow_of(nonce);
+        let r = relayer.pubkey();
+        Instruction {
+            program_id,
+            accounts: zpx_router::accounts::ForwardViaSpoke {
+                user: user.pubkey(),
+                relayer: r,
+                mint,
+                escrow,
+                escrow_vault,
+                hub_protocol_vault,
+                hub_relayer_vault,
+                relayer_token_account: relayer_ata,
+                adapter_target_token_account: adapter_target,
+                registry,
+                config,
+                message_account: Pubkey::new_unique(),
+                adapter_program_data: Pubkey::new_unique(),
+                vault_registry: pda(&[b"vault_registry", mint.as_ref()]).0,
+                spoke_relayers: pda(&[b"spoke_relayers", &spoke_id.to_le_bytes()]).0,
+                router_stats: pda(&[b"router_stats"]).0,
+                mint_pause: pda(&[b"mint_pause", mint.as_ref()]).0,
+                mint_allowlist: pda(&[b"mint_allowlist"]).0,
+                rent_ledger,
+                fee_policy: pda(&[
+                    b"fee_policy",
+                    mint.as_ref(),
+                    &zpx_chains::BASE.0.to_le_bytes(),
+                ])
+                .0,
+                relayer_cosign: pda(&[b"relayer_cosign", mint.as_ref()]).0,
+                forward_state: pda(&[b"forward_state", escrow.as_ref()]).0,
+                relayer_vesting_config: pda(&[b"relayer_vesting_config"]).0,
+                relayer_vesting: pda(&[b"relayer_vesting", r.as_ref(), mint.as_ref()]).0,
+                token_program: spl_token::ID,
+                system_program: solana_sdk::system_program::ID,
+                relayer_activity: pda(&[b"relayer_activity", r.as_ref()]).0,
+            }
+            .to_account_metas(None),
+            data: zpx_router::instruction::ForwardViaSpoke {
+                spoke_id,
+                amount: AMOUNT,
+                dst_domain: zpx_chains::CCTP_BASE.0,
+                mint_recipient,
+                is_protocol_fee: true,
+                is_relayer_fee: false,
+                _nonce: nonce,
+                relayer_fee_lamports: 0,
+                adapter_payload: vec![],
+            }
+            .data(),
+        }
+    };
+    let reclaim = |nonce: u64| {
+        let (escrow, escrow_vault) = escrow_of(nonce);
+        Instruction {
+            program_id,
+            accounts: zpx_router::accounts::ReclaimForwardEscrow {
+                user: user.pubkey(),
+                escrow,
+                escrow_vault,
+                destination: from,
+                rent_ledger,
+                config,
+                token_program: spl_token::ID,
+            }
+            .to_account_metas(None),
+            data: zpx_router::instruction::ReclaimForwardEscrow {}.data(),
+        }
+    };
+    let set_pause_by = |authority: &Keypair, family: u8, paused: bool| {
+        tx(
+            Instruction {
+                program_id,
+                accounts: zpx_router::accounts::SetFeature {
+                    authority: authority.pubkey(),
+                    config,
+                }
+                .to_account_metas(None),
+                data: zpx_router::instruction::SetInstructionPause { family, paused }.data(),
+            },
+            authority,
+        )
+    };
+    let set_pause = |family: u8, paused: bool| set_pause_by(&admin, family, paused);
+
+    let err = banks_client
+        .process_transaction(set_pause(INSTRUCTION_FAMILY_COUNT, true))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::UnknownInstructionFamily));
+    let err = banks_client
+        .process_transaction(set_pause_by(&relayer, INSTRUCTION_FAMILY_WITHDRAW, true))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::Unauthorized));
+
+    // Withdrawals frozen: users still bridge and relayers still forward
+    banks_client
+        .process_transaction(set_pause(INSTRUCTION_FAMILY_WITHDRAW, true))
+        .await
+        .unwrap();
+    for nonce in 1..=3 {
+        banks_client
+            .process_transaction(tx(deposit(nonce), &user))
+            .await
+            .unwrap();
+    }
+    let err = banks_client
+        .process_transaction(tx(reclaim(2), &user))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::InstructionFamilyPaused));
+    banks_client
+        .process_transaction(tx(forward(1), &relayer))
+        .await
+        .unwrap();
+
+    // Forwarding frozen instead: the reclaim goes through, the forward does not
+    banks_client
+        .process_transaction(set_pause(INSTRUCTION_FAMILY_FORWARD, true))
+        .await
+        .unwrap();
+    banks_client
+        .process_transaction(set_pause(INSTRUCTION_FAMILY_WITHDRAW, false))
+        .await
+        .unwrap();
+    banks_client
+        .process_transaction(tx(reclaim(3), &user))
+        .await
+        .unwrap();
+    let err = banks_client
+        .process_transaction(tx(forward(2), &relayer))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::InstructionFamilyPaused));
+    let account = banks_client.get_account(config).await.unwrap().unwrap();
+    let cfg = Config::try_deserialize(&mut account.data.as_slice()).unwrap();
+    assert_eq!(cfg.paused_families, 1 << INSTRUCTION_FAMILY_FORWARD);
+    assert!(!cfg.family_paused(INSTRUCTION_FAMILY_BRIDGE));
+}
@@ -0,0 +1,221 @@
+use anchor_lang::{AccountSerialize, InstructionData, ToAccountMetas};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use zpx_router::{Config, ErrorCode, FeeRecipientEpoch, FeeRecipientHistory, Registry, SpokeEntry};
+
+fn anchor_account<T: AccountSerialize>(value: &T) -> Account {
+    let mut data = Vec::new();
+    value.try_serialize(&mut data).unwrap();
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: zpx_router::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn killed_config(admin: Pubkey, bump: u8) -> Config {
+    Config {
+        admin,
+        fee_recipient: Pubkey::new_unique(),
+        src_chain_id: 1,
+        relayer_fee_bps: 0,
+        protocol_fee_bps: 0,
+        relayer_pubkey: Pubkey::default(),
+        accept_any_token: true,
+        allowed_token_mint: Pubkey::default(),
+        direct_relayer_payout_default: false,
+        min_forward_amount: 0,
+        adapters_len: 0,
+        adapters: [Pubkey::default(); 8],
+        paused: true,
+        bump,
+        max_relayer_fee_lamports: 0,
+        usd_limits_enabled: false,
+        strict_vaults: false,
+        lp_fee_bps: 0,
+        lp_vault_program: Pubkey::default(),
+        killed: true,
+        kill_release_slot: 0,
+        guardian: Pubkey::default(),
+        admin_op_keys: [0; 8],
+        admin_op_cursor: 0,
+        legacy_message_hash: false,
+        features: zpx_router::FEATURES_DEFAULT,
+        bridge_hook: Pubkey::default(),
+        bridge_hook_best_effort: false,
+        namespace_len: 0,
+        namespace: [0; zpx_router::SANDBOX_NAMESPACE_LEN],
+        schema_version: zpx_router::CONFIG_SCHEMA_VERSION,
+        rent_refund_policy: zpx_router::RENT_REFUND_PAYER,
+        rent_refund_caller_bps: 0,
+        config_version: 0,
+        paused_families: 0,
+    }
+}
+
+fn custom(code: ErrorCode) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(code.into()))
+}
+
+#[tokio::test]
+async fn killed_router_stays_paused_until_timelocked_release() {
+    let program_id = zpx_router::ID;
+    let mut program_test =
+        ProgramTest::new("zpx_router", program_id, processor!(zpx_router::entry));
+
+    let admin = Keypair::new();
+    let (config, config_bump) = Pubkey::find_program_address(&[b"zpx_config"], &program_id);
+    let (registry, registry_bump) = Pubkey::find_program_address(&[b"hub_registry"], &program_id);
+    let (history, history_bump) =
+        Pubkey::find_program_address(&[b"fee_recipient_history"], &program_id);
+
+    program_test.add_account(
+        config,
+        anchor_account(&killed_config(admin.pubkey(), config_bump)),
+    );
+    let mut spokes = [SpokeEntry::default(); zpx_router::MAX_SPOKES];
+    spokes[0].spoke_id = 7;
+    spokes[0].enabled = true;
+    program_test.add_account(
+        registry,
+        anchor_account(&Registry {
+            spokes_len: 1,
+            spokes,
+            bump: registry_bump,
+            schema_version: zpx_router::REGISTRY_SCHEMA_VERSION,
+        }),
+    );
+    program_test.add_account(
+        history,
+        anchor_account(&FeeRecipientHistory {
+            epoch: 0,
+            entries: [FeeRecipientEpoch::default(); 8],
+            bump: history_bump,
+            grace_slots: 0,
+        }),
+    );
+    program_test.add_account(
+        admin.pubkey(),
+        Account {
+            lamports: 1_000_000_000,
+            ..Account::default()
+        },
+    );
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let tx = |ix: Instruction, signer: &Keypair| {
+        Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&payer.pubkey()),
+            &[&payer, signer],
+            recent_blockhash,
+        )
+    };
+
+    // Only the build-time sentinel may trigger the kill
+    let impostor = Keypair::new();
+    let kill = Instruction {
+        program_id,
+        accounts: zpx_router::accounts::GlobalKill {
+            sentinel: impostor.pubkey(),
+            config,
+            registry,
+        }
+        .to_account_metas(None),
+        data: zpx_router::instruction::GlobalKill {}.data(),
+    };
+    let err = banks_client
+        .process_transaction(tx(kill, &impostor))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::NotKillSentinel));
+
+    // The admin cannot unpause a killed config through update_config
+    let unpause = Instruction {
+        program_id,
+        accounts: zpx_router::accounts::UpdateConfig {
+            authority: admin.pubkey(),
+            config,
+            fee_recipient_history: history,
+        }
+        .to_account_metas(None),
+        data: zpx_router::instruction::UpdateConfig {
+            fee_recipient: None,
+            src_chain_id: None,
+            relayer_fee_bps: None,
+            protocol_fee_bps: None,
+            relayer_pubkey: None,
+            accept_any_token: None,
+            allowed_token_mint: None,
+            direct_relayer_payout_default: None,
+            min_forward_amount: None,
+            paused: Some(false),
+            max_relayer_fee_lamports: None,
+            usd_limits_enabled: None,
+            strict_vaults: None,
+            fee_recipient_grace_slots: None,
+            lp_fee_bps: None,
+            lp_vault_program: None,
+            guardian: None,
+            idempotency_key: None,
+            legacy_message_hash: None,
+        }
+        .data(),
+    };
+    let err = banks_client
+        .process_transaction(tx(unpause, &admin))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::GlobalKilled));
+
+    // Release needs a schedule and then the full timelock
+    let admin_config = zpx_router::accounts::AdminConfig {
+        authority: admin.pubkey(),
+        config,
+    };
+    let release = Instruction {
+        program_id,
+        accounts: admin_config.to_account_metas(None),
+        data: zpx_router::instruction::ReleaseGlobalKill {}.data(),
+    };
+    let err = banks_client
+        .process_transaction(tx(release.clone(), &admin))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::KillReleaseLocked));
+
+    let schedule = Instruction {
+        program_id,
+        accounts: admin_config.to_account_metas(None),
+        data: zpx_router::instruction::ScheduleKillRelease {}.data(),
+    };
+    banks_client
+        .process_transaction(tx(schedule, &admin))
+        .await
+        .unwrap();
+    let mut early_release = release;
+    // Distinct message so the runtime does not dedupe the earlier failed transaction
+    early_release
+        .accounts
+        .push(solana_sdk::instruction::AccountMeta::new_readonly(
+            Pubkey::new_unique(),
+            false,
+        ));
+    let err = banks_client
+        .process_transaction(tx(early_release, &admin))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::KillReleaseLocked));
+}
@@ -0,0 +1,218 @@
+use anchor_lang::{AccountSerialize, InstructionData, ToAccountMetas};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    clock::Clock,
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use zpx_router::hash::message_hash_be;
+use zpx_router::{message_hash_scheme, Config, ErrorCode, MessageValidity};
+
+fn anchor_account<T: AccountSerialize>(value: &T) -> Account {
+    let mut data = Vec::new();
+    value.try_serialize(&mut data).unwrap();
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: zpx_router::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn custom(code: ErrorCode) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(code.into()))
+}
+
+#[tokio::test]
+async fn finalize_enforces_the_route_validity_window() {
+    let program_id = zpx_router::ID;
+    let mut program_test =
+        ProgramTest::new("zpx_router", program_id, processor!(zpx_router::entry));
+
+    let admin = Keypair::new();
+    let relayer = Keypair::new();
+    let src_adapter = Pubkey::new_unique();
+    let asset_mint = Pubkey::new_unique();
+    let src_chain_id = 8453u64;
+    let dst_chain_id = 1u64;
+    let max_age_secs = 3_600u64;
+    let (config, config_bump) = Pubkey::find_program_address(&[b"zpx_config"], &program_id);
+    let (route_validity, _) = Pubkey::find_program_address(
+        &[b"route_validity", &src_chain_id.to_le_bytes()],
+        &program_id,
+    );
+    let (rent_ledger, _) = Pubkey::find_program_address(&[b"rent_ledger"], &program_id);
+    let mut adapters = [Pubkey::default(); 8];
+    adapters[0] = src_adapter;
+    program_test.add_account(
+        config,
+        anchor_account(&Config {
+            admin: admin.pubkey(),
+            fee_recipient: Pubkey::new_unique(),
+            src_chain_id: dst_chain_id,
+            relayer_fee_bps: 0,
+            protocol_fee_bps: 0,
+            relayer_pubkey: relayer.pubkey(),
+            accept_any_token: true,
+            allowed_token_mint: Pubkey::default(),
+            direct_relayer_payout_default: false,
+            min_forward_amount: 0,
+            adapters_len: 1,
+            adapters,
+            paused: false,
+            bump: config_bump,
+            max_relayer_fee_lamports: 0,
+            usd_limits_enabled: false,
+            strict_vaults: false,
+            lp_fee_bps: 0,
+            lp_vault_program: Pubkey::default(),
+            killed: false,
+            kill_release_slot: 0,
+            guardian: Pubkey::default(),
+            admin_op_keys: [0; 8],
+            admin_op_cursor: 0,
+            legacy_message_hash: false,
+            features: zpx_router::FEATURES_DEFAULT,
+            bridge_hook: Pubkey::default(),
+            bridge_hook_best_effort: false,
+            namespace_len: 0,
+            namespace: [0; zpx_router::SANDBOX_NAMESPACE_LEN],
+            schema_version: zpx_router::CONFIG_SCHEMA_VERSION,
+            rent_refund_policy: zpx_router::RENT_REFUND_PAYER,
+            rent_refund_caller_bps: 0,
+            config_version: 0,
+            paused_families: 0,
+        }),
+    );
+    for wallet in [&admin, &relayer] {
+        program_test.add_account(
+            wallet.pubkey(),
+            Account::new(10_000_000_000, 0, &solana_sdk::system_program::ID),
+        );
+    }
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let tx = |ix: Instruction, signer: &Keypair| {
+        Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&payer.pubkey()),
+            &[&payer, signer],
+            recent_blockhash,
+        )
+    };
+
+    let set_max_age = Instruction {
+        program_id,
+        accounts: zpx_router::accounts::SetRouteMaxAge {
+            authority: admin.pubkey(),
+            config,
+            route_validity,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zpx_router::instruction::SetRouteMaxAge {
+            chain_id: src_chain_id,
+            max_age_secs,
+        }
+        .data(),
+    };
+    banks_client
+        .process_transaction(tx(set_max_age, &admin))
+        .await
+        .unwrap();
+
+    let now = banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap()
+        .unix_timestamp as u64;
+    let payload_hash = [7u8; 32];
+    // Each case uses its own nonce, so every message gets a fresh replay PDA
+    let finalize = |nonce: u64, validity: Option<MessageValidity>| {
+        let committed = validity.map_or(payload_hash, |v| v.payload_hash(payload_hash));
+        let mut amount_be = [0u8; 32];
+        amount_be[24..].copy_from_slice(&1_000u64.to_be_bytes());
+        let message_hash = message_hash_be(
+            message_hash_scheme(zpx_router::hash::HASH_SCHEMA_VERSION),
+            src_chain_id,
+            src_adapter.to_bytes(),
+            [0u8; 32],
+            asset_mint.to_bytes(),
+            amount_be,
+            committed,
+            nonce,
+            dst_chain_id,
+        );
+        Instruction {
+            program_id,
+            accounts: zpx_router::accounts::FinalizeMessageV1 {
+                relayer: relayer.pubkey(),
+                config,
+                replay: Pubkey::find_program_address(&[b"replay", &message_hash], &program_id).0,
+                rent_ledger,
+                system_program: solana_sdk::system_program::ID,
+                route_validity,
+            }
+            .to_account_metas(None),
+            data: zpx_router::instruction::FinalizeMessageV1 {
+                message_hash,
+                src_chain_id,
+                dst_chain_id,
+                forwarded_amount: 1_000,
+                nonce,
+                payload_hash,
+                src_adapter,
+                asset_mint,
+                _initiator: Pubkey::default(),
+                origin_tx_hash: None,
+                validity,
+            }
+            .data(),
+        }
+    };
+
+    // The route has a max age, so unwrapped messages are refused
+    let err = banks_client
+        .process_transaction(tx(finalize(1, None), &relayer))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::MessageValidityRequired));
+
+    // Past the embedded expiry
+    let expired = MessageValidity {
+        sent_at: now - 120,
+        expires_at: now - 60,
+    };
+    let err = banks_client
+        .process_transaction(tx(finalize(2, Some(expired)), &relayer))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::MessageExpired));
+
+    // A generous source expiry does not outlive the destination's own max age
+    let stale = MessageValidity {
+        sent_at: now - max_age_secs - 1,
+        expires_at: now + 86_400,
+    };
+    let err = banks_client
+        .process_transaction(tx(finalize(3, Some(stale)), &relayer))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::MessageExpired));
+
+    let fresh = MessageValidity {
+        sent_at: now - 10,
+        expires_at: now + max_age_secs,
+    };
+    banks_client
+        .process_transaction(tx(finalize(4, Some(fresh)), &relayer))
+        .await
+        .unwrap();
+}
@@ -0,0 +1,357 @@
+//! Partner rebates: a bridge naming a Partner as referral holds the partner's share of the
+//! protocol fee in the mint's rebate vault until the partner's payout address claims it.
+
+use anchor_lang::{AccountDeserialize, AccountSerialize, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::{Instruction, InstructionError},
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use zpx_router::{
+    Config, ErrorCode, FeeRecipientEpoch, FeeRecipientHistory, InFlight, Partner, PartnerRebate,
+};
+
+fn anchor_account<T: AccountSerialize>(value: &T) -> Account {
+    let mut data = Vec::new();
+    value.try_serialize(&mut data).unwrap();
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: zpx_router::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn packed_account<T: Pack>(value: T) -> Account {
+    let mut data = vec![0u8; T::LEN];
+    value.pack_into_slice(&mut data);
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: spl_token::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> Account {
+    packed_account(spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        delegate: COption::None,
+        state: spl_token::state::AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    })
+}
+
+fn custom(code: ErrorCode) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(code.into()))
+}
+
+async fn token_balance(banks_client: &mut BanksClient, account: Pubkey) -> u64 {
+    let account = banks_client.get_account(account).await.unwrap().unwrap();
+    spl_token::state::Account::unpack(&account.data)
+        .unwrap()
+        .amount
+}
+
+#[tokio::test]
+async fn referred_bridges_accrue_a_rebate_the_partner_claims() {
+    let program_id = zpx_router::ID;
+    let mut program_test =
+        ProgramTest::new("zpx_router", program_id, processor!(zpx_router::entry));
+
+    let admin = Keypair::new();
+    let user = Keypair::new();
+    let partner_payout = Keypair::new();
+    let mint = Pubkey::new_unique();
+    let from = Pubkey::new_unique();
+    let fee_recipient = Pubkey::new_unique();
+    let fee_recipient_ata =
+        anchor_spl::associated_token::get_associated_token_address(&fee_recipient, &mint);
+    let target_token_account = Pubkey::new_unique();
+    let target_adapter = Pubkey::new_unique();
+    let payout_ata = Pubkey::new_unique();
+    let dst_chain_id = 10u64;
+    let partner_id = 7u32;
+
+    let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &program_id);
+    let (config, config_bump) = pda(&[b"zpx_config"]);
+    let (in_flight, in_flight_bump) =
+        pda(&[b"in_flight", mint.as_ref(), &dst_chain_id.to_le_bytes()]);
+    let (history, history_bump) = pda(&[b"fee_recipient_history"]);
+    let (bridge_hook_authority, _) = pda(&[b"bridge_hook_authority"]);
+    let (partner, _) = pda(&[b"partner", &partner_id.to_le_bytes()]);
+    let (partner_rebate, _) = pda(&[b"partner_rebate", &partner_id.to_le_bytes(), mint.as_ref()]);
+    let (partner_rebate_vault, _) = pda(&[b"partner_rebate_vault", mint.as_ref()]);
+
+    let mut adapters = [Pubkey::default(); 8];
+    adapters[0] = target_adapter;
+    program_test.add_account(
+        config,
+        anchor_account(&Config {
+            admin: admin.pubkey(),
+            fee_recipient,
+            src_chain_id: 1,
+            relayer_fee_bps: 0,
+            protocol_fee_bps: 5,
+            relayer_pubkey: Pubkey::default(),
+            accept_any_token: true,
+            allowed_token_mint: Pubkey::default(),
+            direct_relayer_payout_default: false,
+            min_forward_amount: 0,
+            adapters_len: 1,
+            adapters,
+            paused: false,
+            bump: config_bump,
+            max_relayer_fee_lamports: 0,
+            usd_limits_enabled: false,
+            strict_vaults: false,
+            lp_fee_bps: 0,
+            lp_vault_program: Pubkey::default(),
+            killed: false,
+            kill_release_slot: 0,
+            guardian: Pubkey::default(),
+            admin_op_keys: [0; 8],
+            admin_op_cursor: 0,
+            legacy_message_hash: false,
+            features: zpx_router::FEATURES_DEFAULT,
+            bridge_hook: Pubkey::default(),
+            bridge_hook_best_effort: false,
+            namespace_len: 0,
+            namespace: [0; zpx_router::SANDBOX_NAMESPACE_LEN],
+            schema_version: zpx_router::CONFIG_SCHEMA_VERSION,
+            rent_refund_policy: zpx_router::RENT_REFUND_PAYER,
+            rent_refund_caller_bps: 0,
+            config_version: 0,
+            paused_families: 0,
+        }),
+    );
+    program_test.add_account(
+        in_flight,
+        anchor_account(&InFlight {
+            mint,
+            dst_chain_id,
+            amount: 0,
+            exposure_cap: 0,
+            bump: in_flight_bump,
+        }),
+    );
+    let mut entries = [FeeRecipientEpoch::default(); 8];
+    entries[0].fee_recipient = fee_recipient;
+    program_test.add_account(
+        history,
+        anchor_account(&FeeRecipientHistory {
+            epoch: 0,
+            entries,
+            bump: history_bump,
+            grace_slots: 0,
+        }),
+    );
+    program_test.add_account(
+        mint,
+        packed_account(spl_token::state::Mint {
+            mint_authority: COption::None,
+            supply: 10_000_000,
+            decimals: 6,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        }),
+    );
+    program_test.add_account(from, token_account(mint, user.pubkey(), 10_000_000));
+    program_test.add_account(fee_recipient_ata, token_account(mint, fee_recipient, 0));
+    program_test.add_account(target_token_account, token_account(mint, target_adapter, 0));
+
+    program_test.add_account(payout_ata, token_account(mint, partner_payout.pubkey(), 0));
+    for wallet in [&admin, &partner_payout] {
+        program_test.add_account(
+            wallet.pubkey(),
+            Account::new(10_000_000_000, 0, &solana_sdk::system_program::ID),
+        );
+    }
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let send = |ix: Instruction, signer: &Keypair| {
+        Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&payer.pubkey()),
+            &[&payer, signer],
+            recent_blockhash,
+        )
+    };
+    let bridge = |referral: Pubkey, nonce: u64| Instruction {
+        program_id,
+        accounts: zpx_router::accounts::UniversalBridgeTransfer {
+            user: user.pubkey(),
+            rent_payer: payer.pubkey(),
+            mint,
+            from,
+            fee_recipient_ata,
+            target_token_account,
+            target_adapter_program: target_adapter,
+            config,
+            in_flight,
+            fee_recipient_history: history,
+            message: pda(&[b"message", user.pubkey().as_ref(), &nonce.to_le_bytes()]).0,
+            user_index: pda(&[b"user_index", user.pubkey().as_ref()]).0,
+            router_stats: pda(&[b"router_stats"]).0,
+            fee_split: pda(&[b"fee_split"]).0,
+            fee_policy: pda(&[b"fee_policy", mint.as_ref(), &dst_chain_id.to_le_bytes()]).0,
+            mint_pause: pda(&[b"mint_pause", mint.as_ref()]).0,
+            mint_allowlist: pda(&[b"mint_allowlist"]).0,
+            rent_ledger: pda(&[b"rent_ledger"]).0,
+            bridge_hook_program: Pubkey::default(),
+            bridge_hook_authority,
+            referral,
+            partner_rebate,
+            partner_rebate_vault,
+            token_program: spl_token::ID,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zpx_router::instruction::UniversalBridgeTransfer {
+            amount: 1_000_000,
+            protocol_fee: 500,
+            relayer_fee: 0,
+            payload: vec![],
+            dst_chain_id,
+            nonce,
+            store_preimage: false,
+            skip_receipt: true,
+        }
+        .data(),
+    };
+    let claim = |payout: Pubkey| Instruction {
+        program_id,
+        accounts: zpx_router::accounts::ClaimPartnerRebate {
+            payout,
+            config,
+            partner,
+            mint,
+            partner_rebate,
+            partner_rebate_vault,
+            destination: payout_ata,
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None),
+        data: zpx_router::instruction::ClaimPartnerRebate {}.data(),
+    };
+    let set_partner = |rebate_bps: u16| Instruction {
+        program_id,
+        accounts: zpx_router::accounts::SetPartner {
+            authority: admin.pubkey(),
+            config,
+            partner,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zpx_router::instruction::SetPartner {
+            partner_id,
+            payout: partner_payout.pubkey(),
+            rebate_bps,
+        }
+        .data(),
+    };
+
+    let err = banks_client
+        .process_transaction(send(set_partner(10_001), &admin))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::InvalidRebateBps));
+    banks_client
+        .process_transaction(send(set_partner(2_000), &admin))
+        .await
+        .unwrap();
+    let account = banks_client.get_account(partner).await.unwrap().unwrap();
+    let stored = Partner::try_deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(
+        (stored.partner_id, stored.payout, stored.rebate_bps),
+        (partner_id, partner_payout.pubkey(), 2_000)
+    );
+
+    // Without a referral the whole protocol fee goes to the fee recipient
+    banks_client
+        .process_transaction(send(bridge(Pubkey::default(), 1), &user))
+        .await
+        .unwrap();
+    assert_eq!(
+        token_balance(&mut banks_client, fee_recipient_ata).await,
+        500
+    );
+    assert!(banks_client
+        .get_account(partner_rebate_vault)
+        .await
+        .unwrap()
+        .is_none());
+
+    // Two referred bridges: 20% of each 500 protocol fee is held for the partner
+    for nonce in [2, 3] {
+        banks_client
+            .process_transaction(send(bridge(partner, nonce), &user))
+            .await
+            .unwrap();
+    }
+    assert_eq!(
+        token_balance(&mut banks_client, fee_recipient_ata).await,
+        1_300
+    );
+    assert_eq!(
+        token_balance(&mut banks_client, partner_rebate_vault).await,
+        200
+    );
+    assert_eq!(
+        token_balance(&mut banks_client, target_token_account).await,
+        2_998_500
+    );
+    let account = banks_client
+        .get_account(partner_rebate)
+        .await
+        .unwrap()
+        .unwrap();
+    let entry = PartnerRebate::try_deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!((entry.accrued, entry.claimed), (200, 0));
+
+    // Only the registered payout address claims, and only once
+    let err = banks_client
+        .process_transaction(send(claim(user.pubkey()), &user))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::Unauthorized));
+    banks_client
+        .process_transaction(send(claim(partner_payout.pubkey()), &partner_payout))
+        .await
+        .unwrap();
+    assert_eq!(token_balance(&mut banks_client, payout_ata).await, 200);
+    assert_eq!(
+        token_balance(&mut banks_client, partner_rebate_vault).await,
+        0
+    );
+    // A fresh blockhash, or the bank would return the identical first claim's result
+    let blockhash = banks_client
+        .get_new_latest_blockhash(&recent_blockhash)
+        .await
+        .unwrap();
+    let err = banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[claim(partner_payout.pubkey())],
+            Some(&payer.pubkey()),
+            &[&payer, &partner_payout],
+            blockhash,
+        ))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::NoPartnerRebate));
+}
@@ -0,0 +1,279 @@
+//! preflight: a read-only simulation of a proposed escrow bridge that reports every
+//! problem at once as a bitmask instead of failing on the first one.
+
+use anchor_lang::{AccountSerialize, AnchorDeserialize, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::Instruction,
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use zpx_router::{
+    Config, MintPause, PreflightReport, Registry, RouteDescriptor, SpokeEntry, MAX_SPOKES,
+    PREFLIGHT_INSUFFICIENT_BALANCE, PREFLIGHT_MINT_PAUSED, PREFLIGHT_MISSING_ATA,
+    PREFLIGHT_NONCE_USED, PREFLIGHT_SPOKE_PAUSED, PREFLIGHT_SPOKE_UNKNOWN,
+    PREFLIGHT_VAULTS_MISSING, PREFLIGHT_WRONG_ACCOUNT,
+};
+
+const AMOUNT: u64 = 1_000_000;
+
+fn anchor_account<T: AccountSerialize>(value: &T) -> Account {
+    let mut data = Vec::new();
+    value.try_serialize(&mut data).unwrap();
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: zpx_router::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn packed_account<T: Pack>(value: T) -> Account {
+    let mut data = vec![0u8; T::LEN];
+    value.pack_into_slice(&mut data);
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: spl_token::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn token_state(mint: Pubkey, owner: Pubkey, amount: u64) -> spl_token::state::Account {
+    spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        delegate: COption::None,
+        state: spl_token::state::AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    }
+}
+
+fn ata(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[owner.as_ref(), spl_token::ID.as_ref(), mint.as_ref()],
+        &anchor_spl::associated_token::ID,
+    )
+    .0
+}
+
+#[tokio::test]
+async fn preflight_reports_every_problem_of_a_route() {
+    let program_id = zpx_router::ID;
+    let mut program_test =
+        ProgramTest::new("zpx_router", program_id, processor!(zpx_router::entry));
+
+    let admin = Keypair::new();
+    let user = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let paused_mint = Pubkey::new_unique();
+
+    let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &program_id);
+    let (config, config_bump) = pda(&[b"zpx_config"]);
+    let (registry, registry_bump) = pda(&[b"hub_registry"]);
+    let escrow_of = |nonce: u64| pda(&[b"forward_escrow", user.as_ref(), &nonce.to_le_bytes()]).0;
+
+    program_test.add_account(
+        config,
+        anchor_account(&Config {
+            admin: admin.pubkey(),
+            fee_recipient: Pubkey::new_unique(),
+            src_chain_id: 1,
+            relayer_fee_bps: 0,
+            protocol_fee_bps: 5,
+            relayer_pubkey: Pubkey::new_unique(),
+            accept_any_token: true,
+            allowed_token_mint: Pubkey::default(),
+            direct_relayer_payout_default: true,
+            min_forward_amount: 0,
+            adapters_len: 0,
+            adapters: [Pubkey::default(); 8],
+            paused: false,
+            bump: config_bump,
+            max_relayer_fee_lamports: 0,
+            usd_limits_enabled: false,
+            strict_vaults: false,
+            lp_fee_bps: 0,
+            lp_vault_program: Pubkey::default(),
+            killed: false,
+            kill_release_slot: 0,
+            guardian: Pubkey::default(),
+            admin_op_keys: [0; 8],
+            admin_op_cursor: 0,
+            legacy_message_hash: false,
+            features: zpx_router::FEATURES_DEFAULT,
+            bridge_hook: Pubkey::default(),
+            bridge_hook_best_effort: false,
+            namespace_len: 0,
+            namespace: [0; zpx_router::SANDBOX_NAMESPACE_LEN],
+            schema_version: zpx_router::CONFIG_SCHEMA_VERSION,
+            rent_refund_policy: zpx_router::RENT_REFUND_PAYER,
+            rent_refund_caller_bps: 0,
+            config_version: 0,
+            paused_families: 0,
+        }),
+    );
+    let mut spokes = [SpokeEntry::default(); MAX_SPOKES];
+    spokes[0] = SpokeEntry {
+        spoke_id: 1,
+        adapter_program: Pubkey::new_unique(),
+        enabled: true,
+        ..SpokeEntry::default()
+    };
+    spokes[1] = SpokeEntry {
+        spoke_id: 2,
+        adapter_program: Pubkey::new_unique(),
+        enabled: true,
+        paused: true,
+        ..SpokeEntry::default()
+    };
+    program_test.add_account(
+        registry,
+        anchor_account(&Registry {
+            spokes_len: 2,
+            spokes,
+            bump: registry_bump,
+            schema_version: zpx_router::REGISTRY_SCHEMA_VERSION,
+        }),
+    );
+    for m in [mint, paused_mint] {
+        program_test.add_account(
+            m,
+            packed_account(spl_token::state::Mint {
+                mint_authority: COption::None,
+                supply: AMOUNT,
+                decimals: 6,
+                is_initialized: true,
+                freeze_authority: COption::None,
+            }),
+        );
+    }
+    program_test.add_account(
+        ata(&user, &mint),
+        packed_account(token_state(mint, user, AMOUNT)),
+    );
+    for seed in [&b"hub_protocol_vault"[..], &b"hub_relayer_vault"[..]] {
+        let vault = pda(&[seed, mint.as_ref()]).0;
+        program_test.add_account(vault, packed_account(token_state(mint, vault, 0)));
+    }
+    let (mint_pause, mint_pause_bump) = pda(&[b"mint_pause", paused_mint.as_ref()]);
+    program_test.add_account(
+        mint_pause,
+        anchor_account(&MintPause {
+            mint: paused_mint,
+            paused: true,
+            updated_at_slot: 0,
+            bump: mint_pause_bump,
+        }),
+    );
+    // Nonce 7 already has an escrow
+    program_test.add_account(escrow_of(7), Account::new(1_000_000_000, 8, &program_id));
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    let preflight = |route: RouteDescriptor, from: Pubkey| {
+        let m = route.mint;
+        let ix = Instruction {
+            program_id,
+            accounts: zpx_router::accounts::Preflight {
+                config,
+                registry,
+                mint: m,
+                from,
+                escrow: escrow_of(route.nonce),
+                mint_pause: pda(&[b"mint_pause", m.as_ref()]).0,
+                mint_allowlist: pda(&[b"mint_allowlist"]).0,
+                vault_registry: pda(&[b"vault_registry", m.as_ref()]).0,
+                hub_protocol_vault: pda(&[b"hub_protocol_vault", m.as_ref()]).0,
+                hub_relayer_vault: pda(&[b"hub_relayer_vault", m.as_ref()]).0,
+            }
+            .to_account_metas(None),
+            data: zpx_router::instruction::Preflight { route }.data(),
+        };
+        let tx = Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&payer.pubkey()),
+            &[&payer],
+            recent_blockhash,
+        );
+        let mut client = banks_client.clone();
+        async move {
+            let outcome = client.simulate_transaction(tx).await.unwrap();
+            outcome.result.unwrap().unwrap();
+            let data = outcome
+                .simulation_details
+                .unwrap()
+                .return_data
+                .unwrap()
+                .data;
+            PreflightReport::try_from_slice(&data).unwrap()
+        }
+    };
+    let route = RouteDescriptor {
+        user,
+        mint,
+        spoke_id: 1,
+        dst_domain: zpx_chains::CCTP_BASE.0,
+        amount: AMOUNT,
+        nonce: 1,
+    };
+
+    // A healthy route has no problems and echoes the derived accounts
+    let report = preflight(route, ata(&user, &mint)).await;
+    assert_eq!(
+        report,
+        PreflightReport {
+            problems: 0,
+            balance: AMOUNT,
+            from: ata(&user, &mint),
+            escrow: escrow_of(1),
+        }
+    );
+
+    // Every problem is reported at once
+    let report = preflight(
+        RouteDescriptor {
+            spoke_id: 9,
+            amount: AMOUNT + 1,
+            nonce: 7,
+            ..route
+        },
+        ata(&user, &mint),
+    )
+    .await;
+    assert_eq!(
+        report.problems,
+        PREFLIGHT_SPOKE_UNKNOWN | PREFLIGHT_INSUFFICIENT_BALANCE | PREFLIGHT_NONCE_USED
+    );
+
+    let report = preflight(
+        RouteDescriptor {
+            mint: paused_mint,
+            spoke_id: 2,
+            ..route
+        },
+        ata(&user, &paused_mint),
+    )
+    .await;
+    assert_eq!(
+        report.problems,
+        PREFLIGHT_MINT_PAUSED
+            | PREFLIGHT_VAULTS_MISSING
+            | PREFLIGHT_SPOKE_PAUSED
+            | PREFLIGHT_MISSING_ATA
+    );
+    assert_eq!(report.balance, 0);
+
+    // A token account other than the user's ATA is a wrong account, not a balance problem
+    let report = preflight(route, Pubkey::new_unique()).await;
+    assert_eq!(report.problems, PREFLIGHT_WRONG_ACCOUNT);
+}
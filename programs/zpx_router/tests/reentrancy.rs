@@ -0,0 +1,188 @@
+use anchor_lang::{AccountSerialize, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{Instruction, InstructionError},
+    program::invoke,
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::Signer,
+    transaction::{Transaction, TransactionError},
+};
+
+/// Token-program stand-in that immediately tries to re-enter the router. It must never run:
+/// the router pins `token_program` to SPL Token before any CPI is attempted.
+fn reentrant_token_processor(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    let reenter = Instruction {
+        program_id: zpx_router::ID,
+        accounts: vec![],
+        data: zpx_router::instruction::SnapshotRouterStats {}.data(),
+    };
+    invoke(&reenter, accounts)
+}
+
+fn anchor_account<T: AccountSerialize>(value: &T) -> Account {
+    let mut data = Vec::new();
+    value.try_serialize(&mut data).unwrap();
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: zpx_router::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> Account {
+    let mut data = vec![0u8; spl_token::state::Account::LEN];
+    spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        delegate: COption::None,
+        state: spl_token::state::AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    }
+    .pack_into_slice(&mut data);
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: spl_token::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn mint_account() -> Account {
+    let mut data = vec![0u8; spl_token::state::Mint::LEN];
+    spl_token::state::Mint {
+        mint_authority: COption::None,
+        supply: 1_000_000,
+        decimals: 6,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }
+    .pack_into_slice(&mut data);
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: spl_token::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+#[tokio::test]
+async fn reentrant_token_program_is_rejected_before_any_cpi() {
+    let program_id = zpx_router::ID;
+    let mock_token = Pubkey::new_unique();
+    let mut program_test =
+        ProgramTest::new("zpx_router", program_id, processor!(zpx_router::entry));
+    program_test.add_program(
+        "reentrant_token",
+        mock_token,
+        processor!(reentrant_token_processor),
+    );
+
+    let admin = solana_sdk::signature::Keypair::new();
+    let mint = Pubkey::new_unique();
+    let (config, config_bump) = Pubkey::find_program_address(&[b"zpx_config"], &program_id);
+    let (vault, _) =
+        Pubkey::find_program_address(&[b"hub_protocol_vault", mint.as_ref()], &program_id);
+    let (vault_registry, _) =
+        Pubkey::find_program_address(&[b"vault_registry", mint.as_ref()], &program_id);
+    let destination = Pubkey::new_unique();
+
+    let cfg = zpx_router::Config {
+        admin: admin.pubkey(),
+        fee_recipient: Pubkey::new_unique(),
+        src_chain_id: 1,
+        relayer_fee_bps: 0,
+        protocol_fee_bps: 0,
+        relayer_pubkey: Pubkey::default(),
+        accept_any_token: true,
+        allowed_token_mint: Pubkey::default(),
+        direct_relayer_payout_default: false,
+        min_forward_amount: 0,
+        adapters_len: 0,
+        adapters: [Pubkey::default(); 8],
+        paused: false,
+        bump: config_bump,
+        max_relayer_fee_lamports: 0,
+        usd_limits_enabled: false,
+        strict_vaults: false,
+        lp_fee_bps: 0,
+        lp_vault_program: Pubkey::default(),
+        killed: false,
+        kill_release_slot: 0,
+        guardian: Pubkey::default(),
+        admin_op_keys: [0; 8],
+        admin_op_cursor: 0,
+        legacy_message_hash: false,
+        features: zpx_router::FEATURES_DEFAULT,
+        bridge_hook: Pubkey::default(),
+        bridge_hook_best_effort: false,
+        namespace_len: 0,
+        namespace: [0; zpx_router::SANDBOX_NAMESPACE_LEN],
+        schema_version: zpx_router::CONFIG_SCHEMA_VERSION,
+        rent_refund_policy: zpx_router::RENT_REFUND_PAYER,
+        rent_refund_caller_bps: 0,
+        config_version: 0,
+        paused_families: 0,
+    };
+    program_test.add_account(config, anchor_account(&cfg));
+    program_test.add_account(mint, mint_account());
+    program_test.add_account(vault, token_account(mint, vault, 1_000));
+    program_test.add_account(destination, token_account(mint, admin.pubkey(), 0));
+    program_test.add_account(
+        admin.pubkey(),
+        Account {
+            lamports: 1_000_000_000,
+            ..Account::default()
+        },
+    );
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    let ix = Instruction {
+        program_id,
+        accounts: zpx_router::accounts::AdminWithdraw {
+            authority: admin.pubkey(),
+            config,
+            hub_protocol_vault: vault,
+            mint,
+            destination,
+            vault_registry,
+            token_program: mock_token,
+        }
+        .to_account_metas(None),
+        data: zpx_router::instruction::AdminWithdraw { amount: 500 }.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &[&payer, &admin],
+        recent_blockhash,
+    );
+    let err = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    // anchor_lang::error::ErrorCode::InvalidProgramId: account validation stops the
+    // instruction before the mock program could be invoked (and re-enter)
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::Custom(3008))
+    );
+}
@@ -0,0 +1,211 @@
+use anchor_lang::{
+    AccountDeserialize, AccountSerialize, AnchorSerialize, InstructionData, ToAccountMetas,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use zpx_router::{registry_chunk_hash, Config, ErrorCode, Registry, SpokeEntry, MAX_SPOKES};
+
+fn anchor_account<T: AccountSerialize>(value: &T) -> Account {
+    let mut data = Vec::new();
+    value.try_serialize(&mut data).unwrap();
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: zpx_router::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn admin_config(admin: Pubkey, bump: u8) -> Config {
+    Config {
+        admin,
+        fee_recipient: Pubkey::new_unique(),
+        src_chain_id: 1,
+        relayer_fee_bps: 0,
+        protocol_fee_bps: 0,
+        relayer_pubkey: Pubkey::default(),
+        accept_any_token: true,
+        allowed_token_mint: Pubkey::default(),
+        direct_relayer_payout_default: false,
+        min_forward_amount: 0,
+        adapters_len: 0,
+        adapters: [Pubkey::default(); 8],
+        paused: false,
+        bump,
+        max_relayer_fee_lamports: 0,
+        usd_limits_enabled: false,
+        strict_vaults: false,
+        lp_fee_bps: 0,
+        lp_vault_program: Pubkey::default(),
+        killed: false,
+        kill_release_slot: 0,
+        guardian: Pubkey::default(),
+        admin_op_keys: [0; 8],
+        admin_op_cursor: 0,
+        legacy_message_hash: false,
+        features: zpx_router::FEATURES_DEFAULT,
+        bridge_hook: Pubkey::default(),
+        bridge_hook_best_effort: false,
+        namespace_len: 0,
+        namespace: [0; zpx_router::SANDBOX_NAMESPACE_LEN],
+        schema_version: zpx_router::CONFIG_SCHEMA_VERSION,
+        rent_refund_policy: zpx_router::RENT_REFUND_PAYER,
+        rent_refund_caller_bps: 0,
+        config_version: 0,
+        paused_families: 0,
+    }
+}
+
+fn custom(code: ErrorCode) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(code.into()))
+}
+
+/// The registry as it stood before the incident, as recovered from export_registry events.
+fn exported_spokes() -> Vec<SpokeEntry> {
+    (0..5u32)
+        .map(|i| SpokeEntry {
+            spoke_id: 100 + i,
+            adapter_program: Pubkey::new_unique(),
+            enabled: true,
+            paused: i == 3,
+            route_group: i % 2,
+            weight: 10 * i as u16,
+            ..SpokeEntry::default()
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn import_rebuilds_exported_registry_chunk_by_chunk() {
+    let program_id = zpx_router::ID;
+    let mut program_test =
+        ProgramTest::new("zpx_router", program_id, processor!(zpx_router::entry));
+
+    let admin = Keypair::new();
+    let (config, config_bump) = Pubkey::find_program_address(&[b"zpx_config"], &program_id);
+    let (registry, registry_bump) = Pubkey::find_program_address(&[b"hub_registry"], &program_id);
+    program_test.add_account(
+        config,
+        anchor_account(&admin_config(admin.pubkey(), config_bump)),
+    );
+    program_test.add_account(
+        registry,
+        anchor_account(&Registry {
+            spokes_len: 0,
+            spokes: [SpokeEntry::default(); MAX_SPOKES],
+            bump: registry_bump,
+            schema_version: zpx_router::REGISTRY_SCHEMA_VERSION,
+        }),
+    );
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let import = |start: u8, entries: &[SpokeEntry], chunk_hash: [u8; 32]| {
+        let ix = Instruction {
+            program_id,
+            accounts: zpx_router::accounts::SpokeAdmin {
+                authority: admin.pubkey(),
+                config,
+                registry,
+            }
+            .to_account_metas(None),
+            data: zpx_router::instruction::ImportRegistry {
+                start,
+                entries: entries.to_vec(),
+                chunk_hash,
+            }
+            .data(),
+        };
+        Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&payer.pubkey()),
+            &[&payer, &admin],
+            recent_blockhash,
+        )
+    };
+
+    let spokes = exported_spokes();
+    let (first, rest) = spokes.split_at(3);
+    let first_hash = registry_chunk_hash(0, first).unwrap();
+    let rest_hash = registry_chunk_hash(3, rest).unwrap();
+
+    // A chunk altered in transit (or replayed at another offset) is rejected
+    let mut tampered = first.to_vec();
+    tampered[1].adapter_program = Pubkey::new_unique();
+    let err = banks_client
+        .process_transaction(import(0, &tampered, first_hash))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::RegistryChunkHashMismatch));
+    // Chunks cannot leave a gap after the current entries
+    let tail = &spokes[4..];
+    let err = banks_client
+        .process_transaction(import(4, tail, registry_chunk_hash(4, tail).unwrap()))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::InvalidRegistryChunk));
+
+    banks_client
+        .process_transaction(import(0, first, first_hash))
+        .await
+        .unwrap();
+    banks_client
+        .process_transaction(import(3, rest, rest_hash))
+        .await
+        .unwrap();
+
+    let account = banks_client.get_account(registry).await.unwrap().unwrap();
+    let restored = Registry::try_deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(restored.spokes_len, 5);
+    let restored = &restored.spokes[..5];
+    assert_eq!(
+        registry_chunk_hash(0, restored).unwrap(),
+        registry_chunk_hash(0, &spokes).unwrap()
+    );
+    for (got, want) in restored.iter().zip(&spokes) {
+        assert_eq!(got.try_to_vec().unwrap(), want.try_to_vec().unwrap());
+    }
+
+    // Indexers can page the restored registry from logs
+    let enumerate = |data: Vec<u8>| {
+        let ix = Instruction {
+            program_id,
+            accounts: zpx_router::accounts::ExportRegistry { registry }.to_account_metas(None),
+            data,
+        };
+        Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&payer.pubkey()),
+            &[&payer],
+            recent_blockhash,
+        )
+    };
+    banks_client
+        .process_transaction(enumerate(
+            zpx_router::instruction::EmitSpokes { from: 3, count: 10 }.data(),
+        ))
+        .await
+        .unwrap();
+    banks_client
+        .process_transaction(enumerate(
+            zpx_router::instruction::EmitSpoke { spoke_id: 102 }.data(),
+        ))
+        .await
+        .unwrap();
+    let err = banks_client
+        .process_transaction(enumerate(
+            zpx_router::instruction::EmitSpokes { from: 5, count: 1 }.data(),
+        ))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::InvalidRegistryChunk));
+}
@@ -0,0 +1,367 @@
+//! Relayer co-signing: forwards of escrows at or above the mint's threshold need registered
+//! co-signers whose stake weights reach the required weight to sign; smaller escrows keep the
+//! single-relayer path.
+
+use anchor_lang::{AccountSerialize, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    sysvar,
+    transaction::{Transaction, TransactionError},
+};
+use zpx_router::{Config, ErrorCode, Registry, SpokeEntry, MAX_SPOKES};
+
+const AMOUNT: u64 = 1_000_000;
+
+fn anchor_account<T: AccountSerialize>(value: &T) -> Account {
+    let mut data = Vec::new();
+    value.try_serialize(&mut data).unwrap();
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: zpx_router::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn packed_account<T: Pack>(value: T) -> Account {
+    let mut data = vec![0u8; T::LEN];
+    value.pack_into_slice(&mut data);
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: spl_token::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn token_state(mint: Pubkey, owner: Pubkey, amount: u64) -> spl_token::state::Account {
+    spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        delegate: COption::None,
+        state: spl_token::state::AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    }
+}
+
+fn custom(code: ErrorCode) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(code.into()))
+}
+
+#[tokio::test]
+async fn large_forwards_need_weighted_cosigners() {
+    let program_id = zpx_router::ID;
+    let mut program_test =
+        ProgramTest::new("zpx_router", program_id, processor!(zpx_router::entry));
+
+    let admin = Keypair::new();
+    let relayer = Keypair::new();
+    let user = Keypair::new();
+    // Stake weights 2, 1, 1 with a required weight of 3
+    let whale = Keypair::new();
+    let small_a = Keypair::new();
+    let small_b = Keypair::new();
+    let mint = Pubkey::new_unique();
+    let from = Pubkey::new_unique();
+    let custody = Pubkey::new_unique();
+    let adapter_target = Pubkey::new_unique();
+    let spoke_id = 1u32;
+    let mut evm_recipient = [0u8; 20];
+    evm_recipient[19] = 1;
+    let mut mint_recipient = [0u8; 32];
+    mint_recipient[12..].copy_from_slice(&evm_recipient);
+
+    let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &program_id);
+    let (config, config_bump) = pda(&[b"zpx_config"]);
+    let (registry, registry_bump) = pda(&[b"hub_registry"]);
+    let (hub_protocol_vault, _) = pda(&[b"hub_protocol_vault", mint.as_ref()]);
+    let (hub_relayer_vault, _) = pda(&[b"hub_relayer_vault", mint.as_ref()]);
+    let (rent_ledger, _) = pda(&[b"rent_ledger"]);
+    let escrow_of = |nonce: u64| {
+        let escrow = pda(&[
+            b"forward_escrow",
+            user.pubkey().as_ref(),
+            &nonce.to_le_bytes(),
+        ])
+        .0;
+        (escrow, pda(&[b"forward_escrow_vault", escrow.as_ref()]).0)
+    };
+
+    program_test.add_account(
+        config,
+        anchor_account(&Config {
+            admin: admin.pubkey(),
+            fee_recipient: Pubkey::new_unique(),
+            src_chain_id: 1,
+            relayer_fee_bps: 100,
+            protocol_fee_bps: 0,
+            relayer_pubkey: relayer.pubkey(),
+            accept_any_token: true,
+            allowed_token_mint: Pubkey::default(),
+            direct_relayer_payout_default: true,
+            min_forward_amount: 0,
+            adapters_len: 0,
+            adapters: [Pubkey::default(); 8],
+            paused: false,
+            bump: config_bump,
+            max_relayer_fee_lamports: 0,
+            usd_limits_enabled: false,
+            strict_vaults: false,
+            lp_fee_bps: 0,
+            lp_vault_program: Pubkey::default(),
+            killed: false,
+            kill_release_slot: 0,
+            guardian: Pubkey::default(),
+            admin_op_keys: [0; 8],
+            admin_op_cursor: 0,
+            legacy_message_hash: false,
+            features: zpx_router::FEATURES_DEFAULT,
+            bridge_hook: Pubkey::default(),
+            bridge_hook_best_effort: false,
+            namespace_len: 0,
+            namespace: [0; zpx_router::SANDBOX_NAMESPACE_LEN],
+            schema_version: zpx_router::CONFIG_SCHEMA_VERSION,
+            rent_refund_policy: zpx_router::RENT_REFUND_PAYER,
+            rent_refund_caller_bps: 0,
+            config_version: 0,
+            paused_families: 0,
+        }),
+    );
+    let mut spokes = [SpokeEntry::default(); MAX_SPOKES];
+    spokes[0] = SpokeEntry {
+        spoke_id,
+        adapter_program: Pubkey::new_unique(),
+        enabled: true,
+        target_owner: custody,
+        ..SpokeEntry::default()
+    };
+    program_test.add_account(
+        registry,
+        anchor_account(&Registry {
+            spokes_len: 1,
+            spokes,
+            bump: registry_bump,
+            schema_version: zpx_router::REGISTRY_SCHEMA_VERSION,
+        }),
+    );
+    program_test.add_account(
+        mint,
+        packed_account(spl_token::state::Mint {
+            mint_authority: COption::None,
+            supply: 3 * AMOUNT,
+            decimals: 6,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        }),
+    );
+    program_test.add_account(
+        from,
+        packed_account(token_state(mint, user.pubkey(), 3 * AMOUNT)),
+    );
+    program_test.add_account(
+        adapter_target,
+        packed_account(token_state(mint, custody, 0)),
+    );
+    for vault in [hub_protocol_vault, hub_relayer_vault] {
+        program_test.add_account(vault, packed_account(token_state(mint, vault, 0)));
+    }
+
+    let relayer_ata = Pubkey::new_unique();
+    program_test.add_account(
+        relayer_ata,
+        packed_account(token_state(mint, relayer.pubkey(), 0)),
+    );
+    for wallet in [&admin, &user, &relayer] {
+        program_test.add_account(
+            wallet.pubkey(),
+            Account::new(10_000_000_000, 0, &solana_sdk::system_program::ID),
+        );
+    }
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let tx = |ix: Instruction, signers: &[&Keypair]| {
+        let mut all = vec![&payer];
+        all.extend_from_slice(signers);
+        Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &all, recent_blockhash)
+    };
+
+    // Escrows 1 and 2 hold the threshold amount, escrow 3 one unit less
+    for (nonce, amount) in [(1u64, AMOUNT), (2, AMOUNT), (3, AMOUNT - 1)] {
+        let (escrow, escrow_vault) = escrow_of(nonce);
+        let deposit = Instruction {
+            program_id,
+            accounts: zpx_router::accounts::DepositForwardEscrow {
+                user: user.pubkey(),
+                mint,
+                from,
+                escrow,
+                escrow_vault,
+                config,
+                rent_ledger,
+                token_program: spl_token::ID,
+                system_program: solana_sdk::system_program::ID,
+                rent: sysvar::rent::ID,
+            }
+            .to_account_metas(None),
+            data: zpx_router::instruction::DepositForwardEscrow {
+                nonce,
+                spoke_id,
+                amount,
+                dst_domain: zpx_chains::CCTP_BASE.0,
+                recipient: zpx_router::Recipient::Evm20(evm_recipient),
+                lamport_budget: 0,
+                relayer_tip: 0,
+                expiry_reminder_slot: 0,
+            }
+            .data(),
+        };
+        banks_client
+            .process_transaction(tx(deposit, &[&user]))
+            .await
+            .unwrap();
+    }
+
+    let relayer_cosign = pda(&[b"relayer_cosign", mint.as_ref()]).0;
+    let set_cosign = |cosigners: Vec<Pubkey>, weights: Vec<u64>| {
+        tx(
+            Instruction {
+                program_id,
+                accounts: zpx_router::accounts::SetRelayerCosign {
+                    authority: admin.pubkey(),
+                    config,
+                    mint,
+                    relayer_cosign,
+                    system_program: solana_sdk::system_program::ID,
+                }
+                .to_account_metas(None),
+                data: zpx_router::instruction::SetRelayerCosign {
+                    threshold: AMOUNT,
+                    required_weight: 3,
+                    cosigners,
+                    weights,
+                }
+                .data(),
+            },
+            &[&admin],
+        )
+    };
+    // Duplicate co-signers, or weights that can never reach the requirement, are rejected
+    for (cosigners, weights) in [
+        (vec![whale.pubkey(), whale.pubkey()], vec![2, 1]),
+        (vec![whale.pubkey(), small_a.pubkey()], vec![1, 1]),
+    ] {
+        let err = banks_client
+            .process_transaction(set_cosign(cosigners, weights))
+            .await
+            .unwrap_err()
+            .unwrap();
+        assert_eq!(err, custom(ErrorCode::InvalidRelayerCosign));
+    }
+    banks_client
+        .process_transaction(set_cosign(
+            vec![whale.pubkey(), small_a.pubkey(), small_b.pubkey()],
+            vec![2, 1, 1],
+        ))
+        .await
+        .unwrap();
+
+    let forward = |nonce: u64, amount: u64, cosigners: &[&Keypair]| {
+        let (escrow, escrow_vault) = escrow_of(nonce);
+        let r = relayer.pubkey();
+        let mut accounts = zpx_router::accounts::ForwardViaSpoke {
+            user: user.pubkey(),
+            relayer: r,
+            mint,
+            escrow,
+            escrow_vault,
+            hub_protocol_vault,
+            hub_relayer_vault,
+            relayer_token_account: relayer_ata,
+            adapter_target_token_account: adapter_target,
+            registry,
+            config,
+            message_account: Pubkey::new_unique(),
+            adapter_program_data: Pubkey::new_unique(),
+            vault_registry: pda(&[b"vault_registry", mint.as_ref()]).0,
+            spoke_relayers: pda(&[b"spoke_relayers", &spoke_id.to_le_bytes()]).0,
+            router_stats: pda(&[b"router_stats"]).0,
+            mint_pause: pda(&[b"mint_pause", mint.as_ref()]).0,
+            mint_allowlist: pda(&[b"mint_allowlist"]).0,
+            rent_ledger,
+            fee_policy: pda(&[
+                b"fee_policy",
+                mint.as_ref(),
+                &zpx_chains::BASE.0.to_le_bytes(),
+            ])
+            .0,
+            relayer_cosign,
+            forward_state: pda(&[b"forward_state", escrow.as_ref()]).0,
+            relayer_vesting_config: pda(&[b"relayer_vesting_config"]).0,
+            relayer_vesting: pda(&[b"relayer_vesting", r.as_ref(), mint.as_ref()]).0,
+            token_program: spl_token::ID,
+            system_program: solana_sdk::system_program::ID,
+            relayer_activity: pda(&[b"relayer_activity", r.as_ref()]).0,
+        }
+        .to_account_metas(None);
+        accounts.extend(
+            cosigners
+                .iter()
+                .map(|c| AccountMeta::new_readonly(c.pubkey(), true)),
+        );
+        let ix = Instruction {
+            program_id,
+            accounts,
+            data: zpx_router::instruction::ForwardViaSpoke {
+                spoke_id,
+                amount,
+                dst_domain: zpx_chains::CCTP_BASE.0,
+                mint_recipient,
+                is_protocol_fee: false,
+                is_relayer_fee: true,
+                _nonce: nonce,
+                relayer_fee_lamports: 0,
+                adapter_payload: vec![],
+            }
+            .data(),
+        };
+        let mut signers = vec![&relayer];
+        signers.extend_from_slice(cosigners);
+        tx(ix, &signers)
+    };
+
+    // Alone, or with co-signers weighing only 2, the relayer cannot move a large escrow
+    for cosigners in [vec![], vec![&small_a, &small_b]] {
+        let err = banks_client
+            .process_transaction(forward(1, AMOUNT, &cosigners))
+            .await
+            .unwrap_err()
+            .unwrap();
+        assert_eq!(err, custom(ErrorCode::CosignWeightNotMet));
+    }
+    banks_client
+        .process_transaction(forward(1, AMOUNT, &[&whale, &small_b]))
+        .await
+        .unwrap();
+    // Any combination reaching the weight works
+    banks_client
+        .process_transaction(forward(2, AMOUNT, &[&whale, &small_a, &small_b]))
+        .await
+        .unwrap();
+    // Below the threshold the single relayer suffices
+    banks_client
+        .process_transaction(forward(3, AMOUNT - 1, &[]))
+        .await
+        .unwrap();
+}
@@ -0,0 +1,235 @@
+use anchor_lang::{AccountDeserialize, AccountSerialize, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::{Instruction, InstructionError},
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    sysvar,
+    transaction::{Transaction, TransactionError},
+};
+use zpx_router::{Config, ErrorCode, ForwardEscrow};
+
+fn anchor_account<T: AccountSerialize>(value: &T) -> Account {
+    let mut data = Vec::new();
+    value.try_serialize(&mut data).unwrap();
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: zpx_router::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn packed_account<T: Pack>(value: T) -> Account {
+    let mut data = vec![0u8; T::LEN];
+    value.pack_into_slice(&mut data);
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: spl_token::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> Account {
+    packed_account(spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        delegate: COption::None,
+        state: spl_token::state::AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    })
+}
+
+fn open_config(bump: u8) -> Config {
+    Config {
+        admin: Pubkey::new_unique(),
+        fee_recipient: Pubkey::new_unique(),
+        src_chain_id: 1,
+        relayer_fee_bps: 0,
+        protocol_fee_bps: 0,
+        relayer_pubkey: Pubkey::default(),
+        accept_any_token: true,
+        allowed_token_mint: Pubkey::default(),
+        direct_relayer_payout_default: false,
+        min_forward_amount: 0,
+        adapters_len: 0,
+        adapters: [Pubkey::default(); 8],
+        paused: false,
+        bump,
+        max_relayer_fee_lamports: 0,
+        usd_limits_enabled: false,
+        strict_vaults: false,
+        lp_fee_bps: 0,
+        lp_vault_program: Pubkey::default(),
+        killed: false,
+        kill_release_slot: 0,
+        guardian: Pubkey::default(),
+        admin_op_keys: [0; 8],
+        admin_op_cursor: 0,
+        legacy_message_hash: false,
+        features: zpx_router::FEATURES_DEFAULT,
+        bridge_hook: Pubkey::default(),
+        bridge_hook_best_effort: false,
+        namespace_len: 0,
+        namespace: [0; zpx_router::SANDBOX_NAMESPACE_LEN],
+        schema_version: zpx_router::CONFIG_SCHEMA_VERSION,
+        rent_refund_policy: zpx_router::RENT_REFUND_PAYER,
+        rent_refund_caller_bps: 0,
+        config_version: 0,
+        paused_families: 0,
+    }
+}
+
+fn custom(code: ErrorCode) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(code.into()))
+}
+
+#[tokio::test]
+async fn tip_is_raised_while_unclaimed_and_refunded_on_cancel() {
+    let program_id = zpx_router::ID;
+    let mut program_test =
+        ProgramTest::new("zpx_router", program_id, processor!(zpx_router::entry));
+
+    let user = Keypair::new();
+    let mint = Pubkey::new_unique();
+    let from = Pubkey::new_unique();
+    let nonce = 1u64;
+    let (config, config_bump) = Pubkey::find_program_address(&[b"zpx_config"], &program_id);
+    let (escrow, _) = Pubkey::find_program_address(
+        &[
+            b"forward_escrow",
+            user.pubkey().as_ref(),
+            &nonce.to_le_bytes(),
+        ],
+        &program_id,
+    );
+    let (escrow_vault, _) =
+        Pubkey::find_program_address(&[b"forward_escrow_vault", escrow.as_ref()], &program_id);
+    let (rent_ledger, _) = Pubkey::find_program_address(&[b"rent_ledger"], &program_id);
+
+    program_test.add_account(config, anchor_account(&open_config(config_bump)));
+    program_test.add_account(
+        mint,
+        packed_account(spl_token::state::Mint {
+            mint_authority: COption::None,
+            supply: 1_000_000,
+            decimals: 6,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        }),
+    );
+    program_test.add_account(from, token_account(mint, user.pubkey(), 1_000_000));
+    program_test.add_account(
+        user.pubkey(),
+        Account::new(10_000_000_000, 0, &solana_sdk::system_program::ID),
+    );
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let tx = |ix: Instruction| {
+        Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&payer.pubkey()),
+            &[&payer, &user],
+            recent_blockhash,
+        )
+    };
+
+    let mut evm_recipient = [0u8; 20];
+    evm_recipient[19] = 1;
+    let mut mint_recipient = [0u8; 32];
+    mint_recipient[12..].copy_from_slice(&evm_recipient);
+    let deposit = Instruction {
+        program_id,
+        accounts: zpx_router::accounts::DepositForwardEscrow {
+            user: user.pubkey(),
+            mint,
+            from,
+            escrow,
+            escrow_vault,
+            config,
+            rent_ledger,
+            token_program: spl_token::ID,
+            system_program: solana_sdk::system_program::ID,
+            rent: sysvar::rent::ID,
+        }
+        .to_account_metas(None),
+        data: zpx_router::instruction::DepositForwardEscrow {
+            nonce,
+            spoke_id: 1,
+            amount: 1_000_000,
+            dst_domain: zpx_chains::CCTP_BASE.0,
+            recipient: zpx_router::Recipient::Evm20(evm_recipient),
+            lamport_budget: 0,
+            relayer_tip: 1_000,
+            expiry_reminder_slot: 0,
+        }
+        .data(),
+    };
+    banks_client.process_transaction(tx(deposit)).await.unwrap();
+
+    let increase = |amount: u64| Instruction {
+        program_id,
+        accounts: zpx_router::accounts::IncreaseRelayerTip {
+            user: user.pubkey(),
+            escrow,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zpx_router::instruction::IncreaseRelayerTip { amount }.data(),
+    };
+    banks_client
+        .process_transaction(tx(increase(500)))
+        .await
+        .unwrap();
+
+    let account = banks_client.get_account(escrow).await.unwrap().unwrap();
+    let state = ForwardEscrow::try_deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(state.relayer_tip, 1_500);
+    let rent_exempt = banks_client
+        .get_rent()
+        .await
+        .unwrap()
+        .minimum_balance(account.data.len());
+    assert_eq!(account.lamports, rent_exempt + 1_500);
+
+    let user_before = banks_client.get_balance(user.pubkey()).await.unwrap();
+    let cancel = Instruction {
+        program_id,
+        accounts: zpx_router::accounts::CancelMessage {
+            user: user.pubkey(),
+            escrow,
+            escrow_vault,
+            destination: from,
+            rent_ledger,
+            config,
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None),
+        data: zpx_router::instruction::CancelMessage {}.data(),
+    };
+    banks_client.process_transaction(tx(cancel)).await.unwrap();
+    let account = banks_client.get_account(escrow).await.unwrap().unwrap();
+    let state = ForwardEscrow::try_deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(state.relayer_tip, 0);
+    assert_eq!(account.lamports, rent_exempt);
+    // Tip and vault rent come back to the user (the payer covers the fee)
+    assert!(banks_client.get_balance(user.pubkey()).await.unwrap() > user_before + 1_500);
+
+    // A cancelled message can no longer be bid on
+    let err = banks_client
+        .process_transaction(tx(increase(1)))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::MessageCancelled));
+}
@@ -0,0 +1,421 @@
+//! Remote config verification: a Wormhole query response signed by a guardian quorum proves
+//! the EVM router's configRoot(), and finalize of messages from that chain requires the
+//! proven root to match the expected one and be fresh.
+
+use anchor_lang::{AccountDeserialize, AccountSerialize, InstructionData, ToAccountMetas};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    clock::Clock,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use zpx_router::ccq::{self, WORMHOLE_CORE_PROGRAM_ID};
+use zpx_router::hash::{keccak256, message_hash_be};
+use zpx_router::{
+    message_hash_scheme, Config, ErrorCode, GuardianSignature, RemoteConfig, REMOTE_CONFIG_SEED,
+};
+
+const WORMHOLE_BASE: u16 = 30;
+const EVM_ROUTER: [u8; 20] = [0xaa; 20];
+const EXPECTED_ROOT: [u8; 32] = [0x11; 32];
+const MAX_AGE_SECS: u64 = 3_600;
+
+fn anchor_account<T: AccountSerialize>(value: &T) -> Account {
+    let mut data = Vec::new();
+    value.try_serialize(&mut data).unwrap();
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: zpx_router::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn custom(code: ErrorCode) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(code.into()))
+}
+
+fn evm_address(key: &libsecp256k1::SecretKey) -> [u8; 20] {
+    let public = libsecp256k1::PublicKey::from_secret_key(key).serialize();
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&keccak256(&[&public[1..]])[12..]);
+    address
+}
+
+/// Wormhole core GuardianSet account: index, keys, creation_time, expiration_time (Borsh)
+fn guardian_set_account(keys: &[[u8; 20]]) -> Account {
+    let mut data = 0u32.to_le_bytes().to_vec();
+    data.extend_from_slice(&(keys.len() as u32).to_le_bytes());
+    for key in keys {
+        data.extend_from_slice(key);
+    }
+    data.extend_from_slice(&0u32.to_le_bytes());
+    data.extend_from_slice(&0u32.to_le_bytes());
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: WORMHOLE_CORE_PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn with_len(out: &mut Vec<u8>, bytes: &[u8]) {
+    out.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+    out.extend_from_slice(bytes);
+}
+
+/// Query response to one off-chain request for configRoot() on `router`
+fn config_root_response(
+    router: [u8; 20],
+    block_number: u64,
+    block_time_us: u64,
+    root: [u8; 32],
+) -> Vec<u8> {
+    let mut call = Vec::new();
+    with_len(&mut call, b"latest");
+    call.push(1);
+    call.extend_from_slice(&router);
+    with_len(&mut call, &ccq::config_root_selector());
+    let mut request = vec![ccq::QUERY_VERSION];
+    request.extend_from_slice(&7u32.to_be_bytes());
+    request.push(1);
+    request.extend_from_slice(&WORMHOLE_BASE.to_be_bytes());
+    request.push(ccq::QUERY_TYPE_ETH_CALL);
+    with_len(&mut request, &call);
+
+    let mut result = block_number.to_be_bytes().to_vec();
+    result.extend_from_slice(&[0xbb; 32]);
+    result.extend_from_slice(&block_time_us.to_be_bytes());
+    result.push(1);
+    with_len(&mut result, &root);
+
+    let mut response = vec![ccq::QUERY_VERSION];
+    response.extend_from_slice(&0u16.to_be_bytes());
+    response.extend_from_slice(&[0u8; 65]);
+    with_len(&mut response, &request);
+    response.push(1);
+    response.extend_from_slice(&WORMHOLE_BASE.to_be_bytes());
+    response.push(ccq::QUERY_TYPE_ETH_CALL);
+    with_len(&mut response, &result);
+    response
+}
+
+fn sign(response: &[u8], guardians: &[(u8, &libsecp256k1::SecretKey)]) -> Vec<GuardianSignature> {
+    let digest = ccq::digest(response);
+    guardians
+        .iter()
+        .map(|(index, key)| {
+            let (sig, recovery_id) =
+                libsecp256k1::sign(&libsecp256k1::Message::parse(&digest), key);
+            let mut signature = [0u8; 65];
+            signature[..64].copy_from_slice(&sig.serialize());
+            signature[64] = recovery_id.serialize();
+            GuardianSignature {
+                index: *index,
+                signature,
+            }
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn finalize_requires_a_verified_source_config_root() {
+    let program_id = zpx_router::ID;
+    let mut program_test =
+        ProgramTest::new("zpx_router", program_id, processor!(zpx_router::entry));
+
+    let admin = Keypair::new();
+    let relayer = Keypair::new();
+    let src_adapter = Pubkey::new_unique();
+    let asset_mint = Pubkey::new_unique();
+    let src_chain_id = 8453u64;
+    let dst_chain_id = 1u64;
+    let (config, config_bump) = Pubkey::find_program_address(&[b"zpx_config"], &program_id);
+    let (route_validity, _) = Pubkey::find_program_address(
+        &[b"route_validity", &src_chain_id.to_le_bytes()],
+        &program_id,
+    );
+    let (remote_config, _) = Pubkey::find_program_address(
+        &[REMOTE_CONFIG_SEED, &src_chain_id.to_le_bytes()],
+        &program_id,
+    );
+    let (rent_ledger, _) = Pubkey::find_program_address(&[b"rent_ledger"], &program_id);
+    let mut adapters = [Pubkey::default(); 8];
+    adapters[0] = src_adapter;
+    program_test.add_account(
+        config,
+        anchor_account(&Config {
+            admin: admin.pubkey(),
+            fee_recipient: Pubkey::new_unique(),
+            src_chain_id: dst_chain_id,
+            relayer_fee_bps: 0,
+            protocol_fee_bps: 0,
+            relayer_pubkey: relayer.pubkey(),
+            accept_any_token: true,
+            allowed_token_mint: Pubkey::default(),
+            direct_relayer_payout_default: false,
+            min_forward_amount: 0,
+            adapters_len: 1,
+            adapters,
+            paused: false,
+            bump: config_bump,
+            max_relayer_fee_lamports: 0,
+            usd_limits_enabled: false,
+            strict_vaults: false,
+            lp_fee_bps: 0,
+            lp_vault_program: Pubkey::default(),
+            killed: false,
+            kill_release_slot: 0,
+            guardian: Pubkey::default(),
+            admin_op_keys: [0; 8],
+            admin_op_cursor: 0,
+            legacy_message_hash: false,
+            features: zpx_router::FEATURES_DEFAULT,
+            bridge_hook: Pubkey::default(),
+            bridge_hook_best_effort: false,
+            namespace_len: 0,
+            namespace: [0; zpx_router::SANDBOX_NAMESPACE_LEN],
+            schema_version: zpx_router::CONFIG_SCHEMA_VERSION,
+            rent_refund_policy: zpx_router::RENT_REFUND_PAYER,
+            rent_refund_caller_bps: 0,
+            config_version: 0,
+            paused_families: 0,
+        }),
+    );
+    let guardians: Vec<_> = (1..=3u8)
+        .map(|i| libsecp256k1::SecretKey::parse(&[i; 32]).unwrap())
+        .collect();
+    let (guardian_set, _) = Pubkey::find_program_address(
+        &[b"GuardianSet", &0u32.to_be_bytes()],
+        &WORMHOLE_CORE_PROGRAM_ID,
+    );
+    program_test.add_account(
+        guardian_set,
+        guardian_set_account(&guardians.iter().map(evm_address).collect::<Vec<_>>()),
+    );
+    for wallet in [&admin, &relayer] {
+        program_test.add_account(
+            wallet.pubkey(),
+            Account::new(10_000_000_000, 0, &solana_sdk::system_program::ID),
+        );
+    }
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let tx = |ix: Instruction, signer: &Keypair| {
+        Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&payer.pubkey()),
+            &[&payer, signer],
+            recent_blockhash,
+        )
+    };
+
+    let set_remote = Instruction {
+        program_id,
+        accounts: zpx_router::accounts::SetRemoteConfig {
+            authority: admin.pubkey(),
+            config,
+            remote_config,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zpx_router::instruction::SetRemoteConfig {
+            src_chain_id,
+            wormhole_chain: WORMHOLE_BASE,
+            evm_router: EVM_ROUTER,
+            expected_root: EXPECTED_ROOT,
+            max_age_secs: MAX_AGE_SECS,
+        }
+        .data(),
+    };
+    banks_client
+        .process_transaction(tx(set_remote, &admin))
+        .await
+        .unwrap();
+
+    let now = banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap()
+        .unix_timestamp as u64;
+    let verify = |response: &[u8], signatures: Vec<GuardianSignature>| Instruction {
+        program_id,
+        accounts: zpx_router::accounts::VerifyRemoteConfig {
+            submitter: relayer.pubkey(),
+            remote_config,
+            guardian_set,
+        }
+        .to_account_metas(None),
+        data: zpx_router::instruction::VerifyRemoteConfig {
+            src_chain_id,
+            response: response.to_vec(),
+            signatures,
+        }
+        .data(),
+    };
+    // Each case uses its own nonce, so every message gets a fresh replay PDA
+    let finalize = |nonce: u64, with_remote: bool| {
+        let payload_hash = [7u8; 32];
+        let mut amount_be = [0u8; 32];
+        amount_be[24..].copy_from_slice(&1_000u64.to_be_bytes());
+        let message_hash = message_hash_be(
+            message_hash_scheme(zpx_router::hash::HASH_SCHEMA_VERSION),
+            src_chain_id,
+            src_adapter.to_bytes(),
+            [0u8; 32],
+            asset_mint.to_bytes(),
+            amount_be,
+            payload_hash,
+            nonce,
+            dst_chain_id,
+        );
+        let mut accounts = zpx_router::accounts::FinalizeMessageV1 {
+            relayer: relayer.pubkey(),
+            config,
+            replay: Pubkey::find_program_address(&[b"replay", &message_hash], &program_id).0,
+            rent_ledger,
+            system_program: solana_sdk::system_program::ID,
+            route_validity,
+        }
+        .to_account_metas(None);
+        if with_remote {
+            accounts.push(AccountMeta::new_readonly(remote_config, false));
+        }
+        Instruction {
+            program_id,
+            accounts,
+            data: zpx_router::instruction::FinalizeMessageV1 {
+                message_hash,
+                src_chain_id,
+                dst_chain_id,
+                forwarded_amount: 1_000,
+                nonce,
+                payload_hash,
+                src_adapter,
+                asset_mint,
+                _initiator: Pubkey::default(),
+                origin_tx_hash: None,
+                validity: None,
+            }
+            .data(),
+        }
+    };
+    let all: Vec<_> = guardians
+        .iter()
+        .enumerate()
+        .map(|(i, k)| (i as u8, k))
+        .collect();
+
+    // Registered but never proven
+    let err = banks_client
+        .process_transaction(tx(finalize(1, true), &relayer))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::RemoteConfigUnverified));
+
+    // Two of three guardians are short of quorum; duplicates do not count twice
+    let fresh = config_root_response(EVM_ROUTER, 100, now * 1_000_000, EXPECTED_ROOT);
+    for signatures in [
+        sign(&fresh, &all[..2]),
+        sign(&fresh, &[all[0], all[0], all[1]]),
+    ] {
+        let err = banks_client
+            .process_transaction(tx(verify(&fresh, signatures), &relayer))
+            .await
+            .unwrap_err()
+            .unwrap();
+        assert_eq!(err, custom(ErrorCode::GuardianQuorumNotReached));
+    }
+
+    // The source runs a different fee config
+    let drifted = config_root_response(EVM_ROUTER, 99, now * 1_000_000, [0x22; 32]);
+    banks_client
+        .process_transaction(tx(verify(&drifted, sign(&drifted, &all)), &relayer))
+        .await
+        .unwrap();
+    let err = banks_client
+        .process_transaction(tx(finalize(2, true), &relayer))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::RemoteConfigRootMismatch));
+
+    // Another contract's answer, or an older block, is refused
+    let elsewhere = config_root_response([0xcc; 20], 101, now * 1_000_000, EXPECTED_ROOT);
+    let err = banks_client
+        .process_transaction(tx(verify(&elsewhere, sign(&elsewhere, &all)), &relayer))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::RemoteConfigQueryMismatch));
+    let older = config_root_response(EVM_ROUTER, 98, now * 1_000_000, EXPECTED_ROOT);
+    let err = banks_client
+        .process_transaction(tx(verify(&older, sign(&older, &all)), &relayer))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::StaleRemoteConfigResponse));
+
+    // Proven and matching: finalize goes through
+    banks_client
+        .process_transaction(tx(verify(&fresh, sign(&fresh, &all)), &relayer))
+        .await
+        .unwrap();
+    let account = banks_client
+        .get_account(remote_config)
+        .await
+        .unwrap()
+        .unwrap();
+    let remote = RemoteConfig::try_deserialize(&mut account.data.as_slice()).unwrap();
+    assert_eq!(remote.verified_root, EXPECTED_ROOT);
+    assert_eq!(remote.verified_block, 100);
+    banks_client
+        .process_transaction(tx(finalize(3, true), &relayer))
+        .await
+        .unwrap();
+
+    // A proof of an EVM block older than the max age no longer vouches for the source
+    let aged = (now - MAX_AGE_SECS - 1) * 1_000_000;
+    let stale = config_root_response(EVM_ROUTER, 102, aged, EXPECTED_ROOT);
+    banks_client
+        .process_transaction(tx(verify(&stale, sign(&stale, &all)), &relayer))
+        .await
+        .unwrap();
+    let err = banks_client
+        .process_transaction(tx(finalize(4, true), &relayer))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::RemoteConfigStale));
+
+    // With the feature on, leaving the RemoteConfig out is no longer an option
+    let enable = Instruction {
+        program_id,
+        accounts: zpx_router::accounts::SetFeature {
+            authority: admin.pubkey(),
+            config,
+        }
+        .to_account_metas(None),
+        data: zpx_router::instruction::SetFeature {
+            feature: zpx_router::FEATURE_REMOTE_CONFIG,
+            enabled: true,
+        }
+        .data(),
+    };
+    banks_client
+        .process_transaction(tx(enable, &admin))
+        .await
+        .unwrap();
+    let err = banks_client
+        .process_transaction(tx(finalize(5, false), &relayer))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::RemoteConfigMissing));
+}
@@ -0,0 +1,362 @@
+//! Rent refund policy: rent reclaimed when an escrow and its vault close goes to the depositor,
+//! the treasury (via the rent ledger and sweep_rent_treasury) or is split with the closer.
+
+use anchor_lang::{AccountSerialize, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::{Instruction, InstructionError},
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    sysvar,
+    transaction::{Transaction, TransactionError},
+};
+use zpx_router::{
+    Config, ErrorCode, RentBucket, RentLedger, ESCROW_EXPIRY_NOTICE_SLOTS,
+    RENT_REFUND_CALLER_SPLIT, RENT_REFUND_PAYER, RENT_REFUND_TREASURY,
+};
+
+fn anchor_account<T: AccountSerialize>(value: &T, lamports: u64) -> Account {
+    let mut data = Vec::new();
+    value.try_serialize(&mut data).unwrap();
+    Account {
+        lamports,
+        data,
+        owner: zpx_router::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn packed_account<T: Pack>(value: T) -> Account {
+    let mut data = vec![0u8; T::LEN];
+    value.pack_into_slice(&mut data);
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: spl_token::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn custom(code: ErrorCode) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(code.into()))
+}
+
+struct Setup {
+    context: ProgramTestContext,
+    admin: Keypair,
+    user: Keypair,
+    cranker: Keypair,
+    fee_recipient: Pubkey,
+    config: Pubkey,
+    rent_ledger: Pubkey,
+    escrow: Pubkey,
+    escrow_vault: Pubkey,
+    from: Pubkey,
+}
+
+/// An expired escrow from `user`, with the config on `policy` / `caller_bps`.
+async fn expired_escrow(policy: u8, caller_bps: u16) -> Setup {
+    let program_id = zpx_router::ID;
+    let mut program_test =
+        ProgramTest::new("zpx_router", program_id, processor!(zpx_router::entry));
+    let admin = Keypair::new();
+    let user = Keypair::new();
+    let cranker = Keypair::new();
+    let fee_recipient = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let from = Pubkey::new_unique();
+    let nonce = 1u64;
+    let (config, config_bump) = Pubkey::find_program_address(&[b"zpx_config"], &program_id);
+    let (escrow, _) = Pubkey::find_program_address(
+        &[
+            b"forward_escrow",
+            user.pubkey().as_ref(),
+            &nonce.to_le_bytes(),
+        ],
+        &program_id,
+    );
+    let (escrow_vault, _) =
+        Pubkey::find_program_address(&[b"forward_escrow_vault", escrow.as_ref()], &program_id);
+    let (rent_ledger, ledger_bump) = Pubkey::find_program_address(&[b"rent_ledger"], &program_id);
+
+    program_test.add_account(
+        config,
+        anchor_account(
+            &Config {
+                admin: admin.pubkey(),
+                fee_recipient,
+                src_chain_id: 1,
+                relayer_fee_bps: 0,
+                protocol_fee_bps: 0,
+                relayer_pubkey: Pubkey::default(),
+                accept_any_token: true,
+                allowed_token_mint: Pubkey::default(),
+                direct_relayer_payout_default: false,
+                min_forward_amount: 0,
+                adapters_len: 0,
+                adapters: [Pubkey::default(); 8],
+                paused: false,
+                bump: config_bump,
+                max_relayer_fee_lamports: 0,
+                usd_limits_enabled: false,
+                strict_vaults: false,
+                lp_fee_bps: 0,
+                lp_vault_program: Pubkey::default(),
+                killed: false,
+                kill_release_slot: 0,
+                guardian: Pubkey::default(),
+                admin_op_keys: [0; 8],
+                admin_op_cursor: 0,
+                legacy_message_hash: false,
+                features: zpx_router::FEATURES_DEFAULT,
+                bridge_hook: Pubkey::default(),
+                bridge_hook_best_effort: false,
+                namespace_len: 0,
+                namespace: [0; zpx_router::SANDBOX_NAMESPACE_LEN],
+                schema_version: zpx_router::CONFIG_SCHEMA_VERSION,
+                rent_refund_policy: policy,
+                rent_refund_caller_bps: caller_bps,
+                config_version: 0,
+                paused_families: 0,
+            },
+            1_000_000_000,
+        ),
+    );
+    let ledger = RentLedger {
+        buckets: [RentBucket::default(); zpx_router::RENT_KINDS],
+        updated_at_slot: 0,
+        bump: ledger_bump,
+    };
+    let mut data = Vec::new();
+    ledger.try_serialize(&mut data).unwrap();
+    // Exactly rent-exempt, so anything above the minimum is swept treasury rent
+    let ledger_rent = Rent::default().minimum_balance(data.len());
+    program_test.add_account(rent_ledger, anchor_account(&ledger, ledger_rent));
+    program_test.add_account(
+        mint,
+        packed_account(spl_token::state::Mint {
+            mint_authority: COption::None,
+            supply: 1_000_000,
+            decimals: 6,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        }),
+    );
+    program_test.add_account(
+        from,
+        packed_account(spl_token::state::Account {
+            mint,
+            owner: user.pubkey(),
+            amount: 1_000_000,
+            delegate: COption::None,
+            state: spl_token::state::AccountState::Initialized,
+            is_native: COption::None,
+            delegated_amount: 0,
+            close_authority: COption::None,
+        }),
+    );
+    for key in [
+        user.pubkey(),
+        cranker.pubkey(),
+        admin.pubkey(),
+        fee_recipient,
+    ] {
+        program_test.add_account(
+            key,
+            Account::new(10_000_000_000, 0, &solana_sdk::system_program::ID),
+        );
+    }
+
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.insecure_clone();
+    let mut evm_recipient = [0u8; 20];
+    evm_recipient[19] = 1;
+    let deposit = Instruction {
+        program_id,
+        accounts: zpx_router::accounts::DepositForwardEscrow {
+            user: user.pubkey(),
+            mint,
+            from,
+            escrow,
+            escrow_vault,
+            config,
+            rent_ledger,
+            token_program: spl_token::ID,
+            system_program: solana_sdk::system_program::ID,
+            rent: sysvar::rent::ID,
+        }
+        .to_account_metas(None),
+        data: zpx_router::instruction::DepositForwardEscrow {
+            nonce,
+            spoke_id: 1,
+            amount: 1_000_000,
+            dst_domain: zpx_chains::CCTP_BASE.0,
+            recipient: zpx_router::Recipient::Evm20(evm_recipient),
+            lamport_budget: 5_000,
+            relayer_tip: 0,
+            expiry_reminder_slot: 10,
+        }
+        .data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit],
+        Some(&payer.pubkey()),
+        &[&payer, &user],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+    context
+        .warp_to_slot(10 + ESCROW_EXPIRY_NOTICE_SLOTS)
+        .unwrap();
+    Setup {
+        context,
+        admin,
+        user,
+        cranker,
+        fee_recipient,
+        config,
+        rent_ledger,
+        escrow,
+        escrow_vault,
+        from,
+    }
+}
+
+impl Setup {
+    async fn balance(&mut self, key: Pubkey) -> u64 {
+        self.context.banks_client.get_balance(key).await.unwrap()
+    }
+
+    /// Rent held by the escrow and its vault, net of the escrowed lamport budget.
+    async fn escrow_rent(&mut self) -> u64 {
+        self.balance(self.escrow).await - 5_000 + self.balance(self.escrow_vault).await
+    }
+
+    async fn send(&mut self, ix: Instruction, signer: &Keypair) -> Result<(), TransactionError> {
+        let payer = self.context.payer.insecure_clone();
+        let blockhash = self
+            .context
+            .banks_client
+            .get_latest_blockhash()
+            .await
+            .unwrap();
+        let tx = Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&payer.pubkey()),
+            &[&payer, signer],
+            blockhash,
+        );
+        self.context
+            .banks_client
+            .process_transaction(tx)
+            .await
+            .map_err(|e| e.unwrap())
+    }
+
+    async fn refund_expired(&mut self) {
+        let ix = Instruction {
+            program_id: zpx_router::ID,
+            accounts: zpx_router::accounts::RefundExpiredEscrow {
+                cranker: self.cranker.pubkey(),
+                user: self.user.pubkey(),
+                escrow: self.escrow,
+                escrow_vault: self.escrow_vault,
+                destination: self.from,
+                rent_ledger: self.rent_ledger,
+                config: self.config,
+                token_program: spl_token::ID,
+            }
+            .to_account_metas(None),
+            data: zpx_router::instruction::RefundExpiredEscrow {}.data(),
+        };
+        let cranker = self.cranker.insecure_clone();
+        self.send(ix, &cranker).await.unwrap();
+    }
+
+    fn set_policy(&self, policy: u8, caller_bps: u16) -> Instruction {
+        Instruction {
+            program_id: zpx_router::ID,
+            accounts: zpx_router::accounts::SetRentRefundPolicy {
+                authority: self.admin.pubkey(),
+                config: self.config,
+                rent_ledger: self.rent_ledger,
+            }
+            .to_account_metas(None),
+            data: zpx_router::instruction::SetRentRefundPolicy { policy, caller_bps }.data(),
+        }
+    }
+}
+
+#[tokio::test]
+async fn caller_split_pays_the_cranker_its_share() {
+    let mut s = expired_escrow(RENT_REFUND_CALLER_SPLIT, 2_500).await;
+    let rent = s.escrow_rent().await;
+    let user_before = s.balance(s.user.pubkey()).await;
+    let cranker_before = s.balance(s.cranker.pubkey()).await;
+    s.refund_expired().await;
+
+    let caller_share = rent / 4;
+    assert_eq!(
+        s.balance(s.cranker.pubkey()).await - cranker_before,
+        caller_share
+    );
+    // The lamport budget is the user's own money and always goes back in full
+    assert_eq!(
+        s.balance(s.user.pubkey()).await - user_before,
+        rent - caller_share + 5_000
+    );
+    assert!(s
+        .context
+        .banks_client
+        .get_account(s.escrow)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn treasury_rent_accrues_in_the_ledger_until_swept() {
+    let mut s = expired_escrow(RENT_REFUND_PAYER, 0).await;
+    let admin = s.admin.insecure_clone();
+    // The caller share only exists under the split policy
+    let err = s
+        .send(s.set_policy(RENT_REFUND_TREASURY, 100), &admin)
+        .await
+        .unwrap_err();
+    assert_eq!(err, custom(ErrorCode::InvalidRentRefundPolicy));
+    s.send(s.set_policy(RENT_REFUND_TREASURY, 0), &admin)
+        .await
+        .unwrap();
+
+    let rent = s.escrow_rent().await;
+    let ledger_before = s.balance(s.rent_ledger).await;
+    let user_before = s.balance(s.user.pubkey()).await;
+    let cranker_before = s.balance(s.cranker.pubkey()).await;
+    s.refund_expired().await;
+    assert_eq!(s.balance(s.rent_ledger).await - ledger_before, rent);
+    assert_eq!(s.balance(s.user.pubkey()).await - user_before, 5_000);
+    assert_eq!(s.balance(s.cranker.pubkey()).await, cranker_before);
+
+    let recipient_before = s.balance(s.fee_recipient).await;
+    let sweep = Instruction {
+        program_id: zpx_router::ID,
+        accounts: zpx_router::accounts::SweepRentTreasury {
+            authority: admin.pubkey(),
+            config: s.config,
+            rent_ledger: s.rent_ledger,
+            fee_recipient: s.fee_recipient,
+        }
+        .to_account_metas(None),
+        data: zpx_router::instruction::SweepRentTreasury {}.data(),
+    };
+    s.send(sweep, &admin).await.unwrap();
+    assert_eq!(s.balance(s.fee_recipient).await - recipient_before, rent);
+    assert_eq!(s.balance(s.rent_ledger).await, ledger_before);
+}
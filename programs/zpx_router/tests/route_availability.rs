@@ -0,0 +1,168 @@
+//! Route availability bitmap: refresh_route_availability creates the PDA from the registry,
+//! and spoke pauses that pass it in remaining accounts keep the bits current.
+
+use anchor_lang::{AccountDeserialize, AccountSerialize, InstructionData, ToAccountMetas};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use zpx_router::{
+    Config, Registry, RouteAvailability, SpokeEntry, MAX_SPOKES, ROUTE_AVAILABILITY_SEED,
+    ROUTE_AVAILABLE_ANY_TOKEN, ROUTE_AVAILABLE_LIVE,
+};
+
+fn anchor_account<T: AccountSerialize>(value: &T) -> Account {
+    let mut data = Vec::new();
+    value.try_serialize(&mut data).unwrap();
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: zpx_router::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn admin_config(admin: Pubkey, bump: u8) -> Config {
+    Config {
+        admin,
+        fee_recipient: Pubkey::new_unique(),
+        src_chain_id: 1,
+        relayer_fee_bps: 0,
+        protocol_fee_bps: 0,
+        relayer_pubkey: Pubkey::default(),
+        accept_any_token: true,
+        allowed_token_mint: Pubkey::default(),
+        direct_relayer_payout_default: false,
+        min_forward_amount: 0,
+        adapters_len: 0,
+        adapters: [Pubkey::default(); 8],
+        paused: false,
+        bump,
+        max_relayer_fee_lamports: 0,
+        usd_limits_enabled: false,
+        strict_vaults: false,
+        lp_fee_bps: 0,
+        lp_vault_program: Pubkey::default(),
+        killed: false,
+        kill_release_slot: 0,
+        guardian: Pubkey::default(),
+        admin_op_keys: [0; 8],
+        admin_op_cursor: 0,
+        legacy_message_hash: false,
+        features: zpx_router::FEATURES_DEFAULT,
+        bridge_hook: Pubkey::default(),
+        bridge_hook_best_effort: false,
+        namespace_len: 0,
+        namespace: [0; zpx_router::SANDBOX_NAMESPACE_LEN],
+        schema_version: zpx_router::CONFIG_SCHEMA_VERSION,
+        rent_refund_policy: zpx_router::RENT_REFUND_PAYER,
+        rent_refund_caller_bps: 0,
+        config_version: 0,
+        paused_families: 0,
+    }
+}
+
+#[tokio::test]
+async fn pause_spoke_flips_availability_bit() {
+    let program_id = zpx_router::ID;
+    let mut program_test =
+        ProgramTest::new("zpx_router", program_id, processor!(zpx_router::entry));
+
+    let admin = Keypair::new();
+    let (config, config_bump) = Pubkey::find_program_address(&[b"zpx_config"], &program_id);
+    let (registry, registry_bump) = Pubkey::find_program_address(&[b"hub_registry"], &program_id);
+    let (availability, _) = Pubkey::find_program_address(&[ROUTE_AVAILABILITY_SEED], &program_id);
+    program_test.add_account(
+        config,
+        anchor_account(&admin_config(admin.pubkey(), config_bump)),
+    );
+    let mut spokes = [SpokeEntry::default(); MAX_SPOKES];
+    for (i, spoke_id) in [11u32, 22].into_iter().enumerate() {
+        spokes[i].spoke_id = spoke_id;
+        spokes[i].adapter_program = Pubkey::new_unique();
+        spokes[i].enabled = true;
+    }
+    program_test.add_account(
+        registry,
+        anchor_account(&Registry {
+            spokes_len: 2,
+            spokes,
+            bump: registry_bump,
+            schema_version: zpx_router::REGISTRY_SCHEMA_VERSION,
+        }),
+    );
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let tx = |ix: Instruction| {
+        Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&payer.pubkey()),
+            &[&payer, &admin],
+            recent_blockhash,
+        )
+    };
+
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[Instruction {
+                program_id,
+                accounts: zpx_router::accounts::RefreshRouteAvailability {
+                    payer: payer.pubkey(),
+                    config,
+                    registry,
+                    route_availability: availability,
+                    system_program: solana_sdk::system_program::ID,
+                }
+                .to_account_metas(None),
+                data: zpx_router::instruction::RefreshRouteAvailability {}.data(),
+            }],
+            Some(&payer.pubkey()),
+            &[&payer],
+            recent_blockhash,
+        ))
+        .await
+        .unwrap();
+    let account = banks_client
+        .get_account(availability)
+        .await
+        .unwrap()
+        .unwrap();
+    let avail = RouteAvailability::try_deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(
+        avail.flags,
+        ROUTE_AVAILABLE_LIVE | ROUTE_AVAILABLE_ANY_TOKEN
+    );
+    assert_eq!(avail.spokes_len, 2);
+    assert_eq!(avail.spoke_bits, 0b11);
+    assert!(avail.is_available(22));
+
+    let mut accounts = zpx_router::accounts::PauseSpoke {
+        authority: admin.pubkey(),
+        config,
+        registry,
+    }
+    .to_account_metas(None);
+    accounts.push(AccountMeta::new(availability, false));
+    banks_client
+        .process_transaction(tx(Instruction {
+            program_id,
+            accounts,
+            data: zpx_router::instruction::PauseSpoke { spoke_id: 22 }.data(),
+        }))
+        .await
+        .unwrap();
+    let account = banks_client
+        .get_account(availability)
+        .await
+        .unwrap()
+        .unwrap();
+    let avail = RouteAvailability::try_deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(avail.spoke_bits, 0b01);
+    assert!(avail.is_available(11));
+    assert!(!avail.is_available(22));
+}
@@ -0,0 +1,243 @@
+//! onboard_route: vaults, mint allowlist entry, spoke and fee policy of a new mint route in
+//! one admin instruction that reverts wholesale when any step fails.
+
+use anchor_lang::{AccountDeserialize, AccountSerialize, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::{Instruction, InstructionError},
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use zpx_router::{
+    Config, ErrorCode, FeePolicy, FeeTier, MintAllowlist, Registry, RouteOnboarding, SpokeEntry,
+    VaultRegistry, MAX_ALLOWED_MINTS, MAX_SPOKES,
+};
+
+fn anchor_account<T: AccountSerialize>(value: &T) -> Account {
+    let mut data = Vec::new();
+    value.try_serialize(&mut data).unwrap();
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: zpx_router::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn custom(code: ErrorCode) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(code.into()))
+}
+
+async fn anchor_state<T: AccountDeserialize>(banks_client: &mut BanksClient, key: Pubkey) -> T {
+    let account = banks_client.get_account(key).await.unwrap().unwrap();
+    T::try_deserialize(&mut &account.data[..]).unwrap()
+}
+
+#[tokio::test]
+async fn onboard_route_is_atomic() {
+    let program_id = zpx_router::ID;
+    let mut program_test =
+        ProgramTest::new("zpx_router", program_id, processor!(zpx_router::entry));
+
+    let admin = Keypair::new();
+    let mint = Pubkey::new_unique();
+    let dst_chain_id = zpx_chains::BASE.0;
+
+    let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &program_id);
+    let (config, config_bump) = pda(&[b"zpx_config"]);
+    let (registry, registry_bump) = pda(&[b"hub_registry"]);
+    let (mint_allowlist, allowlist_bump) = pda(&[b"mint_allowlist"]);
+    let hub_protocol_vault = pda(&[b"hub_protocol_vault", mint.as_ref()]).0;
+    let hub_relayer_vault = pda(&[b"hub_relayer_vault", mint.as_ref()]).0;
+    let vault_registry = pda(&[b"vault_registry", mint.as_ref()]).0;
+    let fee_policy = pda(&[b"fee_policy", mint.as_ref(), &dst_chain_id.to_le_bytes()]).0;
+
+    program_test.add_account(
+        config,
+        anchor_account(&Config {
+            admin: admin.pubkey(),
+            fee_recipient: Pubkey::new_unique(),
+            src_chain_id: 1,
+            relayer_fee_bps: 0,
+            protocol_fee_bps: 0,
+            relayer_pubkey: Pubkey::new_unique(),
+            accept_any_token: false,
+            allowed_token_mint: Pubkey::default(),
+            direct_relayer_payout_default: true,
+            min_forward_amount: 0,
+            adapters_len: 0,
+            adapters: [Pubkey::default(); 8],
+            paused: false,
+            bump: config_bump,
+            max_relayer_fee_lamports: 0,
+            usd_limits_enabled: false,
+            strict_vaults: false,
+            lp_fee_bps: 0,
+            lp_vault_program: Pubkey::default(),
+            killed: false,
+            kill_release_slot: 0,
+            guardian: Pubkey::default(),
+            admin_op_keys: [0; 8],
+            admin_op_cursor: 0,
+            legacy_message_hash: false,
+            features: zpx_router::FEATURES_DEFAULT,
+            bridge_hook: Pubkey::default(),
+            bridge_hook_best_effort: false,
+            namespace_len: 0,
+            namespace: [0; zpx_router::SANDBOX_NAMESPACE_LEN],
+            schema_version: zpx_router::CONFIG_SCHEMA_VERSION,
+            rent_refund_policy: zpx_router::RENT_REFUND_PAYER,
+            rent_refund_caller_bps: 0,
+            config_version: 0,
+            paused_families: 0,
+        }),
+    );
+    // Spoke 9 is already taken
+    let mut spokes = [SpokeEntry::default(); MAX_SPOKES];
+    spokes[0] = SpokeEntry {
+        spoke_id: 9,
+        adapter_program: Pubkey::new_unique(),
+        enabled: true,
+        ..SpokeEntry::default()
+    };
+    program_test.add_account(
+        registry,
+        anchor_account(&Registry {
+            spokes_len: 1,
+            spokes,
+            bump: registry_bump,
+            schema_version: zpx_router::REGISTRY_SCHEMA_VERSION,
+        }),
+    );
+    program_test.add_account(
+        mint_allowlist,
+        anchor_account(&MintAllowlist {
+            mints_len: 0,
+            mints: [Pubkey::default(); MAX_ALLOWED_MINTS],
+            bump: allowlist_bump,
+        }),
+    );
+    let mut mint_data = vec![0u8; spl_token::state::Mint::LEN];
+    spl_token::state::Mint {
+        mint_authority: COption::None,
+        supply: 0,
+        decimals: 6,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }
+    .pack_into_slice(&mut mint_data);
+    program_test.add_account(
+        mint,
+        Account {
+            lamports: 1_000_000_000,
+            data: mint_data,
+            owner: spl_token::ID,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    program_test.add_account(
+        admin.pubkey(),
+        Account::new(10_000_000_000, 0, &solana_sdk::system_program::ID),
+    );
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let adapter_program = Pubkey::new_unique();
+    let tier = FeeTier {
+        min_amount: 0,
+        protocol_bps: 0,
+        relayer_bps: 50,
+    };
+    let onboard = |spoke_id: u32, fee_tiers: Vec<FeeTier>| {
+        Transaction::new_signed_with_payer(
+            &[Instruction {
+                program_id,
+                accounts: zpx_router::accounts::OnboardRoute {
+                    authority: admin.pubkey(),
+                    config,
+                    mint,
+                    hub_protocol_vault,
+                    hub_relayer_vault,
+                    vault_registry,
+                    mint_allowlist,
+                    registry,
+                    fee_policy,
+                    rent_ledger: pda(&[b"rent_ledger"]).0,
+                    token_program: spl_token::ID,
+                    system_program: solana_sdk::system_program::ID,
+                }
+                .to_account_metas(None),
+                data: zpx_router::instruction::OnboardRoute {
+                    route: RouteOnboarding {
+                        spoke_id,
+                        adapter_program,
+                        direct_relayer_payout: false,
+                        version: 1,
+                        metadata: Some("usdc-base".to_string()),
+                        payload_schema: None,
+                        dst_chain_id,
+                        fee_tiers,
+                        protocol_min_fee: 0,
+                        protocol_max_fee: 0,
+                        relayer_min_fee: 0,
+                        relayer_max_fee: 0,
+                    },
+                }
+                .data(),
+            }],
+            Some(&payer.pubkey()),
+            &[&payer, &admin],
+            recent_blockhash,
+        )
+    };
+
+    // A bad fee policy fails up front; a taken spoke id fails after the vaults were created
+    for (spoke_id, tiers, code) in [
+        (1, vec![], ErrorCode::InvalidFeePolicy),
+        (9, vec![tier], ErrorCode::AdapterAlreadyExists),
+    ] {
+        let err = banks_client
+            .process_transaction(onboard(spoke_id, tiers))
+            .await
+            .unwrap_err()
+            .unwrap();
+        assert_eq!(err, custom(code));
+        for key in [hub_protocol_vault, vault_registry, fee_policy] {
+            assert!(banks_client.get_account(key).await.unwrap().is_none());
+        }
+    }
+
+    banks_client
+        .process_transaction(onboard(1, vec![tier]))
+        .await
+        .unwrap();
+    let vaults: VaultRegistry = anchor_state(&mut banks_client, vault_registry).await;
+    assert_eq!(
+        (vaults.mint, vaults.protocol_vault, vaults.relayer_vault),
+        (mint, hub_protocol_vault, hub_relayer_vault)
+    );
+    for vault in [hub_protocol_vault, hub_relayer_vault] {
+        let account = banks_client.get_account(vault).await.unwrap().unwrap();
+        let state = spl_token::state::Account::unpack(&account.data).unwrap();
+        assert_eq!((state.mint, state.owner), (mint, vault));
+    }
+    let allowlist: MintAllowlist = anchor_state(&mut banks_client, mint_allowlist).await;
+    assert_eq!(&allowlist.mints[..allowlist.mints_len as usize], &[mint]);
+    let registry: Registry = anchor_state(&mut banks_client, registry).await;
+    assert_eq!(registry.spokes_len, 2);
+    let spoke = registry.spokes[1];
+    assert_eq!(
+        (spoke.spoke_id, spoke.adapter_program),
+        (1, adapter_program)
+    );
+    assert!(spoke.enabled && !spoke.paused);
+    let policy: FeePolicy = anchor_state(&mut banks_client, fee_policy).await;
+    assert_eq!((policy.mint, policy.dst_chain_id), (mint, dst_chain_id));
+    assert_eq!(&policy.tiers[..policy.tiers_len as usize], &[tier]);
+}
@@ -0,0 +1,230 @@
+//! Sandbox namespaces: a config at `[b"zpx_config", namespace]` drives its own
+//! `[b"hub_registry", namespace]` registry and never the canonical one. Creating a namespace
+//! needs the `sandbox` build; the default build rejects it.
+
+use anchor_lang::{AccountDeserialize, AccountSerialize, InstructionData, ToAccountMetas};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use zpx_router::{Config, Registry, SpokeEntry, MAX_SPOKES, SANDBOX_NAMESPACE_LEN};
+
+const NAMESPACE: &[u8] = b"acme-devnet";
+
+fn anchor_account<T: AccountSerialize>(value: &T) -> Account {
+    let mut data = Vec::new();
+    value.try_serialize(&mut data).unwrap();
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: zpx_router::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn sandbox_config(admin: Pubkey, bump: u8) -> Config {
+    let mut namespace = [0; SANDBOX_NAMESPACE_LEN];
+    namespace[..NAMESPACE.len()].copy_from_slice(NAMESPACE);
+    Config {
+        admin,
+        fee_recipient: Pubkey::new_unique(),
+        src_chain_id: 1,
+        relayer_fee_bps: 0,
+        protocol_fee_bps: 0,
+        relayer_pubkey: Pubkey::default(),
+        accept_any_token: true,
+        allowed_token_mint: Pubkey::default(),
+        direct_relayer_payout_default: false,
+        min_forward_amount: 0,
+        adapters_len: 0,
+        adapters: [Pubkey::default(); 8],
+        paused: false,
+        bump,
+        max_relayer_fee_lamports: 0,
+        usd_limits_enabled: false,
+        strict_vaults: false,
+        lp_fee_bps: 0,
+        lp_vault_program: Pubkey::default(),
+        killed: false,
+        kill_release_slot: 0,
+        guardian: Pubkey::default(),
+        admin_op_keys: [0; 8],
+        admin_op_cursor: 0,
+        legacy_message_hash: false,
+        features: zpx_router::FEATURES_DEFAULT,
+        bridge_hook: Pubkey::default(),
+        bridge_hook_best_effort: false,
+        namespace_len: NAMESPACE.len() as u8,
+        namespace,
+        schema_version: zpx_router::CONFIG_SCHEMA_VERSION,
+        rent_refund_policy: zpx_router::RENT_REFUND_PAYER,
+        rent_refund_caller_bps: 0,
+        config_version: 0,
+        paused_families: 0,
+    }
+}
+
+fn empty_registry(bump: u8) -> Registry {
+    Registry {
+        spokes_len: 0,
+        spokes: [SpokeEntry::default(); MAX_SPOKES],
+        bump,
+        schema_version: zpx_router::REGISTRY_SCHEMA_VERSION,
+    }
+}
+
+#[tokio::test]
+async fn namespaced_config_drives_its_own_registry() {
+    let program_id = zpx_router::ID;
+    let mut program_test =
+        ProgramTest::new("zpx_router", program_id, processor!(zpx_router::entry));
+
+    let admin = Keypair::new();
+    let (config, config_bump) =
+        Pubkey::find_program_address(&[b"zpx_config", NAMESPACE], &program_id);
+    let (registry, registry_bump) =
+        Pubkey::find_program_address(&[b"hub_registry", NAMESPACE], &program_id);
+    let (canonical_registry, canonical_bump) =
+        Pubkey::find_program_address(&[b"hub_registry"], &program_id);
+    assert_eq!(
+        zpx_router::zpx_core::pda::sandbox_config(&program_id.to_bytes(), NAMESPACE).0,
+        config.to_bytes()
+    );
+    program_test.add_account(
+        config,
+        anchor_account(&sandbox_config(admin.pubkey(), config_bump)),
+    );
+    program_test.add_account(registry, anchor_account(&empty_registry(registry_bump)));
+    program_test.add_account(
+        canonical_registry,
+        anchor_account(&empty_registry(canonical_bump)),
+    );
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let create_spoke = |registry: Pubkey| {
+        Transaction::new_signed_with_payer(
+            &[Instruction {
+                program_id,
+                accounts: zpx_router::accounts::CreateSpoke {
+                    authority: admin.pubkey(),
+                    config,
+                    registry,
+                    system_program: solana_sdk::system_program::ID,
+                }
+                .to_account_metas(None),
+                data: zpx_router::instruction::CreateSpoke {
+                    spoke_id: 7,
+                    adapter_program: Pubkey::new_unique(),
+                    direct_relayer_payout: false,
+                    version: 1,
+                    metadata: None,
+                    payload_schema: None,
+                }
+                .data(),
+            }],
+            Some(&payer.pubkey()),
+            &[&payer, &admin],
+            recent_blockhash,
+        )
+    };
+
+    // The sandbox admin cannot reach the canonical registry through its config
+    let err = banks_client
+        .process_transaction(create_spoke(canonical_registry))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(anchor_lang::error::ErrorCode::ConstraintSeeds as u32)
+        )
+    );
+
+    banks_client
+        .process_transaction(create_spoke(registry))
+        .await
+        .unwrap();
+    let account = banks_client.get_account(registry).await.unwrap().unwrap();
+    let state = Registry::try_deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!((state.spokes_len, state.spokes[0].spoke_id), (1, 7));
+}
+
+fn initialize_sandbox_tx(
+    payer: &Keypair,
+    recent_blockhash: solana_sdk::hash::Hash,
+) -> (Transaction, Pubkey) {
+    let program_id = zpx_router::ID;
+    let (config, _) = Pubkey::find_program_address(&[b"zpx_config", NAMESPACE], &program_id);
+    let (registry, _) = Pubkey::find_program_address(&[b"hub_registry", NAMESPACE], &program_id);
+    let tx = Transaction::new_signed_with_payer(
+        &[Instruction {
+            program_id,
+            accounts: zpx_router::accounts::InitializeSandbox {
+                payer: payer.pubkey(),
+                config,
+                registry,
+                system_program: solana_sdk::system_program::ID,
+            }
+            .to_account_metas(None),
+            data: zpx_router::instruction::InitializeSandbox {
+                namespace: String::from_utf8(NAMESPACE.to_vec()).unwrap(),
+                admin: payer.pubkey(),
+                fee_recipient: payer.pubkey(),
+                src_chain_id: 1,
+                relayer_fee_bps: 0,
+                protocol_fee_bps: 0,
+                relayer_pubkey: payer.pubkey(),
+                accept_any_token: true,
+                allowed_token_mint: Pubkey::default(),
+                min_forward_amount: 0,
+            }
+            .data(),
+        }],
+        Some(&payer.pubkey()),
+        &[payer],
+        recent_blockhash,
+    );
+    (tx, config)
+}
+
+#[cfg(not(feature = "sandbox"))]
+#[tokio::test]
+async fn initialize_sandbox_requires_sandbox_build() {
+    let program_test =
+        ProgramTest::new("zpx_router", zpx_router::ID, processor!(zpx_router::entry));
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let (tx, _) = initialize_sandbox_tx(&payer, recent_blockhash);
+    let err = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(zpx_router::ErrorCode::SandboxDisabled.into())
+        )
+    );
+}
+
+#[cfg(feature = "sandbox")]
+#[tokio::test]
+async fn initialize_sandbox_creates_namespaced_pair() {
+    let program_test =
+        ProgramTest::new("zpx_router", zpx_router::ID, processor!(zpx_router::entry));
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let (tx, config) = initialize_sandbox_tx(&payer, recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+    let account = banks_client.get_account(config).await.unwrap().unwrap();
+    let state = Config::try_deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(state.namespace_seed(), NAMESPACE);
+    assert_eq!(state.admin, payer.pubkey());
+}
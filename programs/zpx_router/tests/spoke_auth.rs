@@ -0,0 +1,183 @@
+//! Spoke CRUD is authorized by the config admin's signature alone. These used to accept any
+//! signer that also listed the admin's pubkey as an (unchecked, non-signer) `admin` account.
+
+use anchor_lang::{AccountDeserialize, AccountSerialize, InstructionData, ToAccountMetas};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use zpx_router::{Config, ErrorCode, Registry, SpokeEntry, MAX_SPOKES};
+
+fn anchor_account<T: AccountSerialize>(value: &T) -> Account {
+    let mut data = Vec::new();
+    value.try_serialize(&mut data).unwrap();
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: zpx_router::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn admin_config(admin: Pubkey, bump: u8) -> Config {
+    Config {
+        admin,
+        fee_recipient: Pubkey::new_unique(),
+        src_chain_id: 1,
+        relayer_fee_bps: 0,
+        protocol_fee_bps: 0,
+        relayer_pubkey: Pubkey::default(),
+        accept_any_token: true,
+        allowed_token_mint: Pubkey::default(),
+        direct_relayer_payout_default: false,
+        min_forward_amount: 0,
+        adapters_len: 0,
+        adapters: [Pubkey::default(); 8],
+        paused: false,
+        bump,
+        max_relayer_fee_lamports: 0,
+        usd_limits_enabled: false,
+        strict_vaults: false,
+        lp_fee_bps: 0,
+        lp_vault_program: Pubkey::default(),
+        killed: false,
+        kill_release_slot: 0,
+        guardian: Pubkey::default(),
+        admin_op_keys: [0; 8],
+        admin_op_cursor: 0,
+        legacy_message_hash: false,
+        features: zpx_router::FEATURES_DEFAULT,
+        bridge_hook: Pubkey::default(),
+        bridge_hook_best_effort: false,
+        namespace_len: 0,
+        namespace: [0; zpx_router::SANDBOX_NAMESPACE_LEN],
+        schema_version: zpx_router::CONFIG_SCHEMA_VERSION,
+        rent_refund_policy: zpx_router::RENT_REFUND_PAYER,
+        rent_refund_caller_bps: 0,
+        config_version: 0,
+        paused_families: 0,
+    }
+}
+
+fn custom(code: ErrorCode) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(code.into()))
+}
+
+#[tokio::test]
+async fn spoke_crud_requires_admin_signature() {
+    let program_id = zpx_router::ID;
+    let mut program_test =
+        ProgramTest::new("zpx_router", program_id, processor!(zpx_router::entry));
+
+    let admin = Keypair::new();
+    let attacker = Keypair::new();
+    let (config, config_bump) = Pubkey::find_program_address(&[b"zpx_config"], &program_id);
+    let (registry, registry_bump) = Pubkey::find_program_address(&[b"hub_registry"], &program_id);
+    program_test.add_account(
+        config,
+        anchor_account(&admin_config(admin.pubkey(), config_bump)),
+    );
+    let mut spokes = [SpokeEntry::default(); MAX_SPOKES];
+    spokes[0] = SpokeEntry {
+        spoke_id: 1,
+        adapter_program: Pubkey::new_unique(),
+        enabled: true,
+        ..SpokeEntry::default()
+    };
+    program_test.add_account(
+        registry,
+        anchor_account(&Registry {
+            spokes_len: 1,
+            spokes,
+            bump: registry_bump,
+            schema_version: zpx_router::REGISTRY_SCHEMA_VERSION,
+        }),
+    );
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let send = |authority: &Keypair, data: Vec<u8>, with_create_accounts: bool| {
+        let mut accounts = if with_create_accounts {
+            zpx_router::accounts::CreateSpoke {
+                authority: authority.pubkey(),
+                config,
+                registry,
+                system_program: solana_sdk::system_program::ID,
+            }
+            .to_account_metas(None)
+        } else {
+            zpx_router::accounts::PauseSpoke {
+                authority: authority.pubkey(),
+                config,
+                registry,
+            }
+            .to_account_metas(None)
+        };
+        // The old bypass: name the admin without its signature
+        accounts.push(AccountMeta::new_readonly(admin.pubkey(), false));
+        Transaction::new_signed_with_payer(
+            &[Instruction {
+                program_id,
+                accounts,
+                data,
+            }],
+            Some(&payer.pubkey()),
+            &[&payer, authority],
+            recent_blockhash,
+        )
+    };
+    let create = |spoke_id: u32| {
+        zpx_router::instruction::CreateSpoke {
+            spoke_id,
+            adapter_program: Pubkey::new_unique(),
+            direct_relayer_payout: false,
+            version: 1,
+            metadata: None,
+            payload_schema: None,
+        }
+        .data()
+    };
+    // UpdateSpoke and PauseSpoke share the same account layout
+    let update = zpx_router::instruction::UpdateSpoke {
+        spoke_id: 1,
+        adapter_program: Some(Pubkey::new_unique()),
+        direct_relayer_payout: None,
+        paused: None,
+        metadata: None,
+        payload_schema: None,
+    }
+    .data();
+    let pause = zpx_router::instruction::PauseSpoke { spoke_id: 1 }.data();
+    let enable = zpx_router::instruction::EnableSpoke { spoke_id: 1 }.data();
+
+    for (data, with_create_accounts) in [
+        (create(2), true),
+        (update, false),
+        (pause.clone(), false),
+        (enable, false),
+    ] {
+        let err = banks_client
+            .process_transaction(send(&attacker, data, with_create_accounts))
+            .await
+            .unwrap_err()
+            .unwrap();
+        assert_eq!(err, custom(ErrorCode::Unauthorized));
+    }
+
+    banks_client
+        .process_transaction(send(&admin, create(2), true))
+        .await
+        .unwrap();
+    banks_client
+        .process_transaction(send(&admin, pause, false))
+        .await
+        .unwrap();
+    let account = banks_client.get_account(registry).await.unwrap().unwrap();
+    let state = Registry::try_deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(state.spokes_len, 2);
+    assert!(state.spokes[0].paused);
+}
@@ -0,0 +1,344 @@
+use anchor_lang::{AccountDeserialize, AccountSerialize, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    hash::Hash,
+    instruction::Instruction,
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction,
+    transaction::Transaction,
+};
+use zpx_router::{
+    Config, FeeRecipientEpoch, FeeRecipientHistory, InFlight, RentBucket, RentLedger,
+    UserTransferIndex, RENT_KINDS, RENT_KIND_MESSAGE,
+};
+
+fn anchor_account<T: AccountSerialize>(value: &T) -> Account {
+    let mut data = Vec::new();
+    value.try_serialize(&mut data).unwrap();
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: zpx_router::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> Account {
+    let mut data = vec![0u8; spl_token::state::Account::LEN];
+    spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        delegate: COption::None,
+        state: spl_token::state::AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    }
+    .pack_into_slice(&mut data);
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: spl_token::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn mint_account() -> Account {
+    let mut data = vec![0u8; spl_token::state::Mint::LEN];
+    spl_token::state::Mint {
+        mint_authority: COption::None,
+        supply: 1_000_000,
+        decimals: 6,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }
+    .pack_into_slice(&mut data);
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: spl_token::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn token_amount(account: &Account) -> u64 {
+    spl_token::state::Account::unpack(&account.data)
+        .unwrap()
+        .amount
+}
+
+/// Durable nonce value stored in a system nonce account:
+/// versions tag(4) + state tag(4) + authority(32) + durable_nonce(32) + fee_calculator(8).
+fn durable_nonce(account: &Account) -> Hash {
+    Hash::new(&account.data[40..72])
+}
+
+#[tokio::test]
+async fn sponsor_pays_fees_and_rent_with_durable_nonce() {
+    let program_id = zpx_router::ID;
+    let mut program_test =
+        ProgramTest::new("zpx_router", program_id, processor!(zpx_router::entry));
+
+    // The user holds tokens but no SOL at all
+    let user = Keypair::new();
+    let mint = Pubkey::new_unique();
+    let from = Pubkey::new_unique();
+    let fee_recipient = Pubkey::new_unique();
+    let fee_recipient_ata =
+        anchor_spl::associated_token::get_associated_token_address(&fee_recipient, &mint);
+    let target_token_account = Pubkey::new_unique();
+    let target_adapter = Pubkey::new_unique();
+    let dst_chain_id = 10u64;
+    let nonce = 7u64;
+
+    let (config, config_bump) = Pubkey::find_program_address(&[b"zpx_config"], &program_id);
+    let (in_flight, in_flight_bump) = Pubkey::find_program_address(
+        &[b"in_flight", mint.as_ref(), &dst_chain_id.to_le_bytes()],
+        &program_id,
+    );
+    let (history, history_bump) =
+        Pubkey::find_program_address(&[b"fee_recipient_history"], &program_id);
+    let (message, _) = Pubkey::find_program_address(
+        &[b"message", user.pubkey().as_ref(), &nonce.to_le_bytes()],
+        &program_id,
+    );
+    let (user_index, _) =
+        Pubkey::find_program_address(&[b"user_index", user.pubkey().as_ref()], &program_id);
+    let (router_stats, _) = Pubkey::find_program_address(&[b"router_stats"], &program_id);
+    let (fee_split, _) = Pubkey::find_program_address(&[b"fee_split"], &program_id);
+    let (mint_pause, _) =
+        Pubkey::find_program_address(&[b"mint_pause", mint.as_ref()], &program_id);
+    let (fee_policy, _) = Pubkey::find_program_address(
+        &[b"fee_policy", mint.as_ref(), &dst_chain_id.to_le_bytes()],
+        &program_id,
+    );
+    let (mint_allowlist, _) = Pubkey::find_program_address(&[b"mint_allowlist"], &program_id);
+    let (rent_ledger, rent_ledger_bump) =
+        Pubkey::find_program_address(&[b"rent_ledger"], &program_id);
+    let (bridge_hook_authority, _) =
+        Pubkey::find_program_address(&[b"bridge_hook_authority"], &program_id);
+
+    let mut adapters = [Pubkey::default(); 8];
+    adapters[0] = target_adapter;
+    program_test.add_account(
+        config,
+        anchor_account(&Config {
+            admin: Pubkey::new_unique(),
+            fee_recipient,
+            src_chain_id: 1,
+            relayer_fee_bps: 0,
+            protocol_fee_bps: 5,
+            relayer_pubkey: Pubkey::default(),
+            accept_any_token: true,
+            allowed_token_mint: Pubkey::default(),
+            direct_relayer_payout_default: false,
+            min_forward_amount: 0,
+            adapters_len: 1,
+            adapters,
+            paused: false,
+            bump: config_bump,
+            max_relayer_fee_lamports: 0,
+            usd_limits_enabled: false,
+            strict_vaults: false,
+            lp_fee_bps: 0,
+            lp_vault_program: Pubkey::default(),
+            killed: false,
+            kill_release_slot: 0,
+            guardian: Pubkey::default(),
+            admin_op_keys: [0; 8],
+            admin_op_cursor: 0,
+            legacy_message_hash: false,
+            features: zpx_router::FEATURES_DEFAULT,
+            bridge_hook: Pubkey::default(),
+            bridge_hook_best_effort: false,
+            namespace_len: 0,
+            namespace: [0; zpx_router::SANDBOX_NAMESPACE_LEN],
+            schema_version: zpx_router::CONFIG_SCHEMA_VERSION,
+            rent_refund_policy: zpx_router::RENT_REFUND_PAYER,
+            rent_refund_caller_bps: 0,
+            config_version: 0,
+            paused_families: 0,
+        }),
+    );
+    program_test.add_account(
+        in_flight,
+        anchor_account(&InFlight {
+            mint,
+            dst_chain_id,
+            amount: 0,
+            exposure_cap: 0,
+            bump: in_flight_bump,
+        }),
+    );
+    let mut entries = [FeeRecipientEpoch::default(); 8];
+    entries[0].fee_recipient = fee_recipient;
+    program_test.add_account(
+        history,
+        anchor_account(&FeeRecipientHistory {
+            epoch: 0,
+            entries,
+            bump: history_bump,
+            grace_slots: 0,
+        }),
+    );
+    program_test.add_account(
+        rent_ledger,
+        anchor_account(&RentLedger {
+            buckets: [RentBucket::default(); RENT_KINDS],
+            updated_at_slot: 0,
+            bump: rent_ledger_bump,
+        }),
+    );
+    program_test.add_account(mint, mint_account());
+    program_test.add_account(from, token_account(mint, user.pubkey(), 1_000_000));
+    program_test.add_account(fee_recipient_ata, token_account(mint, fee_recipient, 0));
+    program_test.add_account(target_token_account, token_account(mint, target_adapter, 0));
+
+    let mut context = program_test.start_with_context().await;
+    let sponsor = context.payer.insecure_clone();
+
+    // Sponsor-owned durable nonce account
+    let nonce_account = Keypair::new();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let create_nonce = Transaction::new_signed_with_payer(
+        &system_instruction::create_nonce_account(
+            &sponsor.pubkey(),
+            &nonce_account.pubkey(),
+            &sponsor.pubkey(),
+            rent.minimum_balance(solana_sdk::nonce::State::size()),
+        ),
+        Some(&sponsor.pubkey()),
+        &[&sponsor, &nonce_account],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(create_nonce)
+        .await
+        .unwrap();
+    // A nonce can only advance once the bank has moved past the slot it was stored in
+    context.warp_to_slot(5).unwrap();
+    let nonce_hash = durable_nonce(
+        &context
+            .banks_client
+            .get_account(nonce_account.pubkey())
+            .await
+            .unwrap()
+            .unwrap(),
+    );
+
+    let bridge = Instruction {
+        program_id,
+        accounts: zpx_router::accounts::UniversalBridgeTransfer {
+            user: user.pubkey(),
+            rent_payer: sponsor.pubkey(),
+            mint,
+            from,
+            fee_recipient_ata,
+            target_token_account,
+            target_adapter_program: target_adapter,
+            config,
+            in_flight,
+            fee_recipient_history: history,
+            message,
+            user_index,
+            router_stats,
+            fee_split,
+            fee_policy,
+            mint_pause,
+            mint_allowlist,
+            rent_ledger,
+            // No hook registered: the account is ignored
+            bridge_hook_program: Pubkey::default(),
+            bridge_hook_authority,
+            // No referral
+            referral: Pubkey::default(),
+            partner_rebate: Pubkey::new_unique(),
+            partner_rebate_vault: Pubkey::new_unique(),
+            token_program: spl_token::ID,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zpx_router::instruction::UniversalBridgeTransfer {
+            amount: 1_000_000,
+            protocol_fee: 500,
+            relayer_fee: 0,
+            payload: vec![],
+            dst_chain_id,
+            nonce,
+            store_preimage: true,
+            skip_receipt: false,
+        }
+        .data(),
+    };
+    let message_to_sign = zpx_router::sponsored::sponsored_message(
+        &[bridge],
+        &sponsor.pubkey(),
+        &nonce_account.pubkey(),
+        &sponsor.pubkey(),
+        nonce_hash,
+    );
+    // The user signs offline; the sponsor co-signs and submits
+    let mut tx = Transaction::new_unsigned(message_to_sign);
+    tx.partial_sign(&[&user], nonce_hash);
+    tx.partial_sign(&[&sponsor], nonce_hash);
+    let sponsor_before = context
+        .banks_client
+        .get_balance(sponsor.pubkey())
+        .await
+        .unwrap();
+    // Durable-nonce transactions are executed straight against the bank; the default
+    // process_transaction path only tracks recent-blockhash lifetimes
+    context
+        .banks_client
+        .process_transaction_with_metadata(tx)
+        .await
+        .unwrap()
+        .result
+        .unwrap();
+
+    let banks = &mut context.banks_client;
+    assert_eq!(banks.get_balance(user.pubkey()).await.unwrap(), 0);
+    assert!(banks.get_balance(sponsor.pubkey()).await.unwrap() < sponsor_before);
+    let fee_ata = banks.get_account(fee_recipient_ata).await.unwrap().unwrap();
+    assert_eq!(token_amount(&fee_ata), 500);
+    let target = banks
+        .get_account(target_token_account)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(token_amount(&target), 999_500);
+    let preimage = banks.get_account(message).await.unwrap().unwrap();
+    assert_eq!(preimage.owner, program_id);
+    // The sponsor-funded preimage is counted in the rent ledger
+    let ledger = banks.get_account(rent_ledger).await.unwrap().unwrap();
+    let ledger = RentLedger::try_deserialize(&mut &ledger.data[..]).unwrap();
+    let bucket = ledger.buckets[RENT_KIND_MESSAGE as usize];
+    assert_eq!((bucket.accounts, bucket.created), (1, 1));
+    assert_eq!(bucket.lamports, preimage.lamports);
+    // The transfer shows up in the user's receipt index
+    let index = banks.get_account(user_index).await.unwrap().unwrap();
+    let index = UserTransferIndex::try_deserialize(&mut &index.data[..]).unwrap();
+    let receipt = index.recent().next().unwrap();
+    assert_eq!(index.total, 1);
+    assert_eq!(
+        (receipt.amount, receipt.dst_chain_id),
+        (999_500, dst_chain_id)
+    );
+    // The nonce advanced, so the same signed transaction cannot be replayed
+    let advanced = banks
+        .get_account(nonce_account.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_ne!(durable_nonce(&advanced), nonce_hash);
+}
@@ -0,0 +1,122 @@
+use anchor_lang::{AccountSerialize, InstructionData, ToAccountMetas};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use zpx_router::{Config, ErrorCode};
+
+fn anchor_account<T: AccountSerialize>(value: &T) -> Account {
+    let mut data = Vec::new();
+    value.try_serialize(&mut data).unwrap();
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: zpx_router::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn config_with_adapter(admin: Pubkey, adapter: Pubkey, bump: u8) -> Config {
+    let mut adapters = [Pubkey::default(); 8];
+    adapters[0] = adapter;
+    Config {
+        admin,
+        fee_recipient: Pubkey::new_unique(),
+        src_chain_id: 1,
+        relayer_fee_bps: 0,
+        protocol_fee_bps: 0,
+        relayer_pubkey: Pubkey::default(),
+        accept_any_token: true,
+        allowed_token_mint: Pubkey::default(),
+        direct_relayer_payout_default: false,
+        min_forward_amount: 0,
+        adapters_len: 1,
+        adapters,
+        paused: false,
+        bump,
+        max_relayer_fee_lamports: 0,
+        usd_limits_enabled: false,
+        strict_vaults: false,
+        lp_fee_bps: 0,
+        lp_vault_program: Pubkey::default(),
+        killed: false,
+        kill_release_slot: 0,
+        guardian: Pubkey::default(),
+        admin_op_keys: [0; 8],
+        admin_op_cursor: 0,
+        legacy_message_hash: false,
+        features: zpx_router::FEATURES_DEFAULT,
+        bridge_hook: Pubkey::default(),
+        bridge_hook_best_effort: false,
+        namespace_len: 0,
+        namespace: [0; zpx_router::SANDBOX_NAMESPACE_LEN],
+        schema_version: zpx_router::CONFIG_SCHEMA_VERSION,
+        rent_refund_policy: zpx_router::RENT_REFUND_PAYER,
+        rent_refund_caller_bps: 0,
+        config_version: 0,
+        paused_families: 0,
+    }
+}
+
+fn custom(code: ErrorCode) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(code.into()))
+}
+
+#[tokio::test]
+async fn adapter_cpi_helper_is_admin_only_and_off_by_default() {
+    let program_id = zpx_router::ID;
+    let mut program_test =
+        ProgramTest::new("zpx_router", program_id, processor!(zpx_router::entry));
+
+    let admin = Keypair::new();
+    let adapter = Pubkey::new_unique();
+    let (config, config_bump) = Pubkey::find_program_address(&[b"zpx_config"], &program_id);
+    program_test.add_account(
+        config,
+        anchor_account(&config_with_adapter(admin.pubkey(), adapter, config_bump)),
+    );
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let call = |signer: &Keypair| {
+        let ix = Instruction {
+            program_id,
+            accounts: zpx_router::accounts::BridgeWithAdapterCpi {
+                admin: signer.pubkey(),
+                config,
+                adapter_program: adapter,
+            }
+            .to_account_metas(None),
+            data: zpx_router::instruction::BridgeWithAdapterCpi {}.data(),
+        };
+        Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&payer.pubkey()),
+            &[&payer, signer],
+            recent_blockhash,
+        )
+    };
+
+    // Anyone other than the admin is turned away by the account constraints
+    let stranger = Keypair::new();
+    let err = banks_client
+        .process_transaction(call(&stranger))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::Unauthorized));
+
+    // Even the admin cannot reach it outside `test-instructions` builds
+    if !cfg!(feature = "test-instructions") {
+        let err = banks_client
+            .process_transaction(call(&admin))
+            .await
+            .unwrap_err()
+            .unwrap();
+        assert_eq!(err, custom(ErrorCode::TestInstructionsDisabled));
+    }
+}
@@ -0,0 +1,410 @@
+//! USDC fast path: configure_usdc_fast_path caches the spoke's registry slot and account
+//! addresses, and forward_usdc_fast then forwards whole escrows against them with the same
+//! fee split, fee policy and spoke accounting as forward_via_spoke.
+
+use anchor_lang::{AccountDeserialize, AccountSerialize, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::{Instruction, InstructionError},
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    sysvar,
+    transaction::{Transaction, TransactionError},
+};
+use zpx_router::{
+    Config, ErrorCode, FeeTier, Registry, SpokeEntry, MAX_SPOKES, USDC_FAST_PATH_SEED,
+};
+
+const AMOUNT: u64 = 1_000_000;
+
+fn anchor_account<T: AccountSerialize>(value: &T) -> Account {
+    let mut data = Vec::new();
+    value.try_serialize(&mut data).unwrap();
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: zpx_router::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn packed_account<T: Pack>(value: T) -> Account {
+    let mut data = vec![0u8; T::LEN];
+    value.pack_into_slice(&mut data);
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: spl_token::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> Account {
+    packed_account(spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        delegate: COption::None,
+        state: spl_token::state::AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    })
+}
+
+fn custom(code: ErrorCode) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(code.into()))
+}
+
+async fn token_balance(banks_client: &mut BanksClient, account: Pubkey) -> u64 {
+    let account = banks_client.get_account(account).await.unwrap().unwrap();
+    spl_token::state::Account::unpack(&account.data)
+        .unwrap()
+        .amount
+}
+
+#[tokio::test]
+async fn fast_path_forwards_whole_escrows_by_cached_accounts() {
+    let program_id = zpx_router::ID;
+    let mut program_test =
+        ProgramTest::new("zpx_router", program_id, processor!(zpx_router::entry));
+
+    let admin = Keypair::new();
+    let relayer = Keypair::new();
+    let user = Keypair::new();
+    let mint = Pubkey::new_unique();
+    let from = Pubkey::new_unique();
+    let custody = Pubkey::new_unique();
+    let adapter_target = Pubkey::new_unique();
+    let relayer_ata = Pubkey::new_unique();
+    let spoke_id = 2u32;
+    let mut evm_recipient = [0u8; 20];
+    evm_recipient[19] = 1;
+
+    let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &program_id);
+    let (config, config_bump) = pda(&[b"zpx_config"]);
+    let (registry, registry_bump) = pda(&[b"hub_registry"]);
+    let (hub_protocol_vault, _) = pda(&[b"hub_protocol_vault", mint.as_ref()]);
+    let (rent_ledger, _) = pda(&[b"rent_ledger"]);
+    let (fast_path, _) = pda(&[USDC_FAST_PATH_SEED, mint.as_ref()]);
+    let escrow_of = |nonce: u64| {
+        let escrow = pda(&[
+            b"forward_escrow",
+            user.pubkey().as_ref(),
+            &nonce.to_le_bytes(),
+        ])
+        .0;
+        (escrow, pda(&[b"forward_escrow_vault", escrow.as_ref()]).0)
+    };
+
+    program_test.add_account(
+        config,
+        anchor_account(&Config {
+            admin: admin.pubkey(),
+            fee_recipient: Pubkey::new_unique(),
+            src_chain_id: 1,
+            relayer_fee_bps: 100,
+            protocol_fee_bps: 5,
+            relayer_pubkey: relayer.pubkey(),
+            accept_any_token: false,
+            allowed_token_mint: mint,
+            direct_relayer_payout_default: true,
+            min_forward_amount: 0,
+            adapters_len: 0,
+            adapters: [Pubkey::default(); 8],
+            paused: false,
+            bump: config_bump,
+            max_relayer_fee_lamports: 0,
+            usd_limits_enabled: false,
+            strict_vaults: false,
+            lp_fee_bps: 0,
+            lp_vault_program: Pubkey::default(),
+            killed: false,
+            kill_release_slot: 0,
+            guardian: Pubkey::default(),
+            admin_op_keys: [0; 8],
+            admin_op_cursor: 0,
+            legacy_message_hash: false,
+            features: zpx_router::FEATURES_DEFAULT,
+            bridge_hook: Pubkey::default(),
+            bridge_hook_best_effort: false,
+            namespace_len: 0,
+            namespace: [0; zpx_router::SANDBOX_NAMESPACE_LEN],
+            schema_version: zpx_router::CONFIG_SCHEMA_VERSION,
+            rent_refund_policy: zpx_router::RENT_REFUND_PAYER,
+            rent_refund_caller_bps: 0,
+            config_version: 0,
+            paused_families: 0,
+        }),
+    );
+    // The fast spoke sits in the second registry slot
+    let mut spokes = [SpokeEntry::default(); MAX_SPOKES];
+    spokes[0] = SpokeEntry {
+        spoke_id: 1,
+        adapter_program: Pubkey::new_unique(),
+        enabled: true,
+        ..SpokeEntry::default()
+    };
+    spokes[1] = SpokeEntry {
+        spoke_id,
+        adapter_program: Pubkey::new_unique(),
+        enabled: true,
+        target_owner: custody,
+        ..SpokeEntry::default()
+    };
+    program_test.add_account(
+        registry,
+        anchor_account(&Registry {
+            spokes_len: 2,
+            spokes,
+            bump: registry_bump,
+            schema_version: zpx_router::REGISTRY_SCHEMA_VERSION,
+        }),
+    );
+    program_test.add_account(
+        mint,
+        packed_account(spl_token::state::Mint {
+            mint_authority: COption::None,
+            supply: 3 * AMOUNT,
+            decimals: 6,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        }),
+    );
+    program_test.add_account(from, token_account(mint, user.pubkey(), 3 * AMOUNT));
+    program_test.add_account(adapter_target, token_account(mint, custody, 0));
+    program_test.add_account(relayer_ata, token_account(mint, relayer.pubkey(), 0));
+    program_test.add_account(
+        hub_protocol_vault,
+        token_account(mint, hub_protocol_vault, 0),
+    );
+    for wallet in [&admin, &user, &relayer] {
+        program_test.add_account(
+            wallet.pubkey(),
+            Account::new(10_000_000_000, 0, &solana_sdk::system_program::ID),
+        );
+    }
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let tx = |ix: Instruction, signer: &Keypair| {
+        Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&payer.pubkey()),
+            &[&payer, signer],
+            recent_blockhash,
+        )
+    };
+
+    banks_client
+        .process_transaction(tx(
+            Instruction {
+                program_id,
+                accounts: zpx_router::accounts::ConfigureUsdcFastPath {
+                    authority: admin.pubkey(),
+                    config,
+                    registry,
+                    mint,
+                    hub_protocol_vault,
+                    adapter_target_token_account: adapter_target,
+                    fast_path,
+                    system_program: solana_sdk::system_program::ID,
+                }
+                .to_account_metas(None),
+                data: zpx_router::instruction::ConfigureUsdcFastPath { spoke_id }.data(),
+            },
+            &admin,
+        ))
+        .await
+        .unwrap();
+
+    for nonce in 1..=3u64 {
+        let (escrow, escrow_vault) = escrow_of(nonce);
+        banks_client
+            .process_transaction(tx(
+                Instruction {
+                    program_id,
+                    accounts: zpx_router::accounts::DepositForwardEscrow {
+                        user: user.pubkey(),
+                        mint,
+                        from,
+                        escrow,
+                        escrow_vault,
+                        config,
+                        rent_ledger,
+                        token_program: spl_token::ID,
+                        system_program: solana_sdk::system_program::ID,
+                        rent: sysvar::rent::ID,
+                    }
+                    .to_account_metas(None),
+                    data: zpx_router::instruction::DepositForwardEscrow {
+                        nonce,
+                        spoke_id,
+                        amount: AMOUNT,
+                        dst_domain: zpx_chains::CCTP_BASE.0,
+                        recipient: zpx_router::Recipient::Evm20(evm_recipient),
+                        lamport_budget: 0,
+                        relayer_tip: 0,
+                        expiry_reminder_slot: 0,
+                    }
+                    .data(),
+                },
+                &user,
+            ))
+            .await
+            .unwrap();
+    }
+
+    let forward = |nonce: u64| {
+        let (escrow, escrow_vault) = escrow_of(nonce);
+        tx(
+            Instruction {
+                program_id,
+                accounts: zpx_router::accounts::ForwardUsdcFast {
+                    relayer: relayer.pubkey(),
+                    user: user.pubkey(),
+                    escrow,
+                    escrow_vault,
+                    fast_path,
+                    config,
+                    registry,
+                    mint_pause: pda(&[b"mint_pause", mint.as_ref()]).0,
+                    spoke_relayers: pda(&[b"spoke_relayers", &spoke_id.to_le_bytes()]).0,
+                    hub_protocol_vault,
+                    relayer_token_account: relayer_ata,
+                    adapter_target_token_account: adapter_target,
+                    router_stats: pda(&[b"router_stats"]).0,
+                    rent_ledger,
+                    fee_policy: pda(&[
+                        b"fee_policy",
+                        mint.as_ref(),
+                        &zpx_chains::BASE.0.to_le_bytes(),
+                    ])
+                    .0,
+                    relayer_cosign: pda(&[b"relayer_cosign", mint.as_ref()]).0,
+                    token_program: spl_token::ID,
+                }
+                .to_account_metas(None),
+                data: zpx_router::instruction::ForwardUsdcFast {
+                    is_protocol_fee: true,
+                    is_relayer_fee: true,
+                }
+                .data(),
+            },
+            &relayer,
+        )
+    };
+
+    // 5 bps protocol + 100 bps relayer, paid straight to the relayer
+    banks_client.process_transaction(forward(1)).await.unwrap();
+    let (protocol_fee, relayer_fee) = (500u64, 10_000u64);
+    let net = AMOUNT - protocol_fee - relayer_fee;
+    assert_eq!(token_balance(&mut banks_client, adapter_target).await, net);
+    assert_eq!(
+        token_balance(&mut banks_client, hub_protocol_vault).await,
+        protocol_fee
+    );
+    assert_eq!(
+        token_balance(&mut banks_client, relayer_ata).await,
+        relayer_fee
+    );
+    let (escrow, escrow_vault) = escrow_of(1);
+    assert!(banks_client.get_account(escrow).await.unwrap().is_none());
+    assert!(banks_client
+        .get_account(escrow_vault)
+        .await
+        .unwrap()
+        .is_none());
+    // Spoke volume lands in the cached slot and nowhere else
+    let state = banks_client.get_account(registry).await.unwrap().unwrap();
+    let state = Registry::try_deserialize(&mut &state.data[..]).unwrap();
+    assert_eq!(state.spokes[1].dispatched_amount, net);
+    assert_eq!(state.spokes[0].dispatched_amount, 0);
+
+    // A route fee policy replaces the config rates: the protocol fee is lifted to its floor and
+    // the relayer fee cut to its cap
+    let fee_policy = pda(&[
+        b"fee_policy",
+        mint.as_ref(),
+        &zpx_chains::BASE.0.to_le_bytes(),
+    ])
+    .0;
+    let set_policy = |tiers: Vec<FeeTier>| {
+        tx(
+            Instruction {
+                program_id,
+                accounts: zpx_router::accounts::SetFeePolicy {
+                    authority: admin.pubkey(),
+                    config,
+                    mint,
+                    fee_policy,
+                    system_program: solana_sdk::system_program::ID,
+                }
+                .to_account_metas(None),
+                data: zpx_router::instruction::SetFeePolicy {
+                    dst_chain_id: zpx_chains::BASE.0,
+                    tiers,
+                    protocol_min_fee: 1_000,
+                    protocol_max_fee: 0,
+                    relayer_min_fee: 0,
+                    relayer_max_fee: 4_000,
+                }
+                .data(),
+            },
+            &admin,
+        )
+    };
+    let tier = |min_amount| FeeTier {
+        min_amount,
+        protocol_bps: 5,
+        relayer_bps: 100,
+    };
+    let err = banks_client
+        .process_transaction(set_policy(vec![tier(AMOUNT), tier(0)]))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::InvalidFeePolicy));
+    banks_client
+        .process_transaction(set_policy(vec![tier(0)]))
+        .await
+        .unwrap();
+    banks_client.process_transaction(forward(2)).await.unwrap();
+    assert_eq!(
+        token_balance(&mut banks_client, hub_protocol_vault).await,
+        protocol_fee + 1_000
+    );
+    assert_eq!(
+        token_balance(&mut banks_client, relayer_ata).await,
+        relayer_fee + 4_000
+    );
+
+    // Spoke pauses still apply to the fast path
+    banks_client
+        .process_transaction(tx(
+            Instruction {
+                program_id,
+                accounts: zpx_router::accounts::PauseSpoke {
+                    authority: admin.pubkey(),
+                    config,
+                    registry,
+                }
+                .to_account_metas(None),
+                data: zpx_router::instruction::PauseSpoke { spoke_id }.data(),
+            },
+            &admin,
+        ))
+        .await
+        .unwrap();
+    let err = banks_client
+        .process_transaction(forward(3))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::AdapterNotAllowed));
+}
@@ -0,0 +1,319 @@
+use anchor_lang::{AccountDeserialize, AccountSerialize, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::{Instruction, InstructionError},
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    sysvar,
+    transaction::{Transaction, TransactionError},
+};
+use zpx_router::{Config, ErrorCode, Registry, RelayerActivity, SpokeEntry, MAX_SPOKES};
+
+fn anchor_account<T: AccountSerialize>(value: &T) -> Account {
+    let mut data = Vec::new();
+    value.try_serialize(&mut data).unwrap();
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: zpx_router::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn packed_account<T: Pack>(value: T) -> Account {
+    let mut data = vec![0u8; T::LEN];
+    value.pack_into_slice(&mut data);
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: spl_token::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> Account {
+    packed_account(spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        delegate: COption::None,
+        state: spl_token::state::AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    })
+}
+
+fn custom(code: ErrorCode) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(code.into()))
+}
+
+#[tokio::test]
+async fn forward_names_missing_vaults_and_admin_can_provision_inline() {
+    let program_id = zpx_router::ID;
+    let mut program_test =
+        ProgramTest::new("zpx_router", program_id, processor!(zpx_router::entry));
+
+    let admin = Keypair::new();
+    let relayer = Keypair::new();
+    let user = Keypair::new();
+    let mint = Pubkey::new_unique();
+    let from = Pubkey::new_unique();
+    let custody = Pubkey::new_unique();
+    let adapter_target = Pubkey::new_unique();
+    let nonce = 1u64;
+    let spoke_id = 1u32;
+    // EVM address, left-padded to 32 bytes
+    let mut evm_recipient = [0u8; 20];
+    evm_recipient[19] = 1;
+    let mut mint_recipient = [0u8; 32];
+    mint_recipient[12..].copy_from_slice(&evm_recipient);
+
+    let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &program_id);
+    let (config, config_bump) = pda(&[b"zpx_config"]);
+    let (registry, registry_bump) = pda(&[b"hub_registry"]);
+    let (escrow, _) = pda(&[
+        b"forward_escrow",
+        user.pubkey().as_ref(),
+        &nonce.to_le_bytes(),
+    ]);
+    let (escrow_vault, _) = pda(&[b"forward_escrow_vault", escrow.as_ref()]);
+    let (hub_protocol_vault, _) = pda(&[b"hub_protocol_vault", mint.as_ref()]);
+    let (hub_relayer_vault, _) = pda(&[b"hub_relayer_vault", mint.as_ref()]);
+    let (vault_registry, _) = pda(&[b"vault_registry", mint.as_ref()]);
+    let (rent_ledger, _) = pda(&[b"rent_ledger"]);
+
+    program_test.add_account(
+        config,
+        anchor_account(&Config {
+            admin: admin.pubkey(),
+            fee_recipient: Pubkey::new_unique(),
+            src_chain_id: 1,
+            relayer_fee_bps: 0,
+            protocol_fee_bps: 0,
+            relayer_pubkey: relayer.pubkey(),
+            accept_any_token: true,
+            allowed_token_mint: Pubkey::default(),
+            direct_relayer_payout_default: false,
+            min_forward_amount: 0,
+            adapters_len: 0,
+            adapters: [Pubkey::default(); 8],
+            paused: false,
+            bump: config_bump,
+            max_relayer_fee_lamports: 0,
+            usd_limits_enabled: false,
+            strict_vaults: false,
+            lp_fee_bps: 0,
+            lp_vault_program: Pubkey::default(),
+            killed: false,
+            kill_release_slot: 0,
+            guardian: Pubkey::default(),
+            admin_op_keys: [0; 8],
+            admin_op_cursor: 0,
+            legacy_message_hash: false,
+            features: zpx_router::FEATURES_DEFAULT,
+            bridge_hook: Pubkey::default(),
+            bridge_hook_best_effort: false,
+            namespace_len: 0,
+            namespace: [0; zpx_router::SANDBOX_NAMESPACE_LEN],
+            schema_version: zpx_router::CONFIG_SCHEMA_VERSION,
+            rent_refund_policy: zpx_router::RENT_REFUND_PAYER,
+            rent_refund_caller_bps: 0,
+            config_version: 0,
+            paused_families: 0,
+        }),
+    );
+    let mut spokes = [SpokeEntry::default(); MAX_SPOKES];
+    spokes[0] = SpokeEntry {
+        spoke_id,
+        adapter_program: Pubkey::new_unique(),
+        enabled: true,
+        target_owner: custody,
+        ..SpokeEntry::default()
+    };
+    program_test.add_account(
+        registry,
+        anchor_account(&Registry {
+            spokes_len: 1,
+            spokes,
+            bump: registry_bump,
+            schema_version: zpx_router::REGISTRY_SCHEMA_VERSION,
+        }),
+    );
+    program_test.add_account(
+        mint,
+        packed_account(spl_token::state::Mint {
+            mint_authority: COption::None,
+            supply: 1_000_000,
+            decimals: 6,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        }),
+    );
+    program_test.add_account(from, token_account(mint, user.pubkey(), 1_000_000));
+    program_test.add_account(adapter_target, token_account(mint, custody, 0));
+    for wallet in [&user, &admin, &relayer] {
+        program_test.add_account(
+            wallet.pubkey(),
+            Account::new(10_000_000_000, 0, &solana_sdk::system_program::ID),
+        );
+    }
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let tx = |ix: Instruction, signer: &Keypair| {
+        Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&payer.pubkey()),
+            &[&payer, signer],
+            recent_blockhash,
+        )
+    };
+
+    let deposit = Instruction {
+        program_id,
+        accounts: zpx_router::accounts::DepositForwardEscrow {
+            user: user.pubkey(),
+            mint,
+            from,
+            escrow,
+            escrow_vault,
+            config,
+            rent_ledger,
+            token_program: spl_token::ID,
+            system_program: solana_sdk::system_program::ID,
+            rent: sysvar::rent::ID,
+        }
+        .to_account_metas(None),
+        data: zpx_router::instruction::DepositForwardEscrow {
+            nonce,
+            spoke_id,
+            amount: 1_000_000,
+            dst_domain: zpx_chains::CCTP_BASE.0,
+            recipient: zpx_router::Recipient::Evm20(evm_recipient),
+            lamport_budget: 0,
+            relayer_tip: 0,
+            expiry_reminder_slot: 0,
+        }
+        .data(),
+    };
+    banks_client
+        .process_transaction(tx(deposit, &user))
+        .await
+        .unwrap();
+
+    let accounts = |relayer: Pubkey| {
+        zpx_router::accounts::ForwardViaSpoke {
+            user: user.pubkey(),
+            relayer,
+            mint,
+            escrow,
+            escrow_vault,
+            hub_protocol_vault,
+            hub_relayer_vault,
+            relayer_token_account: adapter_target,
+            adapter_target_token_account: adapter_target,
+            registry,
+            config,
+            message_account: Pubkey::new_unique(),
+            adapter_program_data: Pubkey::new_unique(),
+            vault_registry,
+            spoke_relayers: pda(&[b"spoke_relayers", &spoke_id.to_le_bytes()]).0,
+            router_stats: pda(&[b"router_stats"]).0,
+            mint_pause: pda(&[b"mint_pause", mint.as_ref()]).0,
+            mint_allowlist: pda(&[b"mint_allowlist"]).0,
+            rent_ledger,
+            fee_policy: pda(&[
+                b"fee_policy",
+                mint.as_ref(),
+                &zpx_chains::BASE.0.to_le_bytes(),
+            ])
+            .0,
+            relayer_cosign: pda(&[b"relayer_cosign", mint.as_ref()]).0,
+            forward_state: pda(&[b"forward_state", escrow.as_ref()]).0,
+            relayer_vesting_config: pda(&[b"relayer_vesting_config"]).0,
+            relayer_vesting: pda(&[b"relayer_vesting", relayer.as_ref(), mint.as_ref()]).0,
+            token_program: spl_token::ID,
+            system_program: solana_sdk::system_program::ID,
+            relayer_activity: pda(&[b"relayer_activity", relayer.as_ref()]).0,
+        }
+        .to_account_metas(None)
+    };
+    let forward = Instruction {
+        program_id,
+        accounts: accounts(relayer.pubkey()),
+        data: zpx_router::instruction::ForwardViaSpoke {
+            spoke_id,
+            amount: 1_000_000,
+            dst_domain: zpx_chains::CCTP_BASE.0,
+            mint_recipient,
+            is_protocol_fee: false,
+            is_relayer_fee: false,
+            _nonce: nonce,
+            relayer_fee_lamports: 0,
+            adapter_payload: vec![],
+        }
+        .data(),
+    };
+    let err = banks_client
+        .process_transaction(tx(forward, &relayer))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::VaultsNotInitialized));
+
+    let ensure = |relayer: Pubkey| Instruction {
+        program_id,
+        accounts: accounts(relayer),
+        data: zpx_router::instruction::EnsureVaultsAndForward {
+            spoke_id,
+            amount: 1_000_000,
+            dst_domain: zpx_chains::CCTP_BASE.0,
+            mint_recipient,
+            is_protocol_fee: false,
+            is_relayer_fee: false,
+            relayer_fee_lamports: 0,
+            adapter_payload: vec![],
+        }
+        .data(),
+    };
+    // Only the admin may pay for new vaults
+    let err = banks_client
+        .process_transaction(tx(ensure(relayer.pubkey()), &relayer))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(ErrorCode::Unauthorized));
+
+    banks_client
+        .process_transaction(tx(ensure(admin.pubkey()), &admin))
+        .await
+        .unwrap();
+    for vault in [hub_protocol_vault, hub_relayer_vault] {
+        let account = banks_client.get_account(vault).await.unwrap().unwrap();
+        let state = spl_token::state::Account::unpack(&account.data).unwrap();
+        assert_eq!((state.mint, state.owner), (mint, vault));
+    }
+    let registry_account = banks_client.get_account(vault_registry).await.unwrap();
+    assert_eq!(registry_account.unwrap().owner, program_id);
+    let target = banks_client
+        .get_account(adapter_target)
+        .await
+        .unwrap()
+        .unwrap();
+    let target = spl_token::state::Account::unpack(&target.data).unwrap();
+    assert_eq!(target.amount, 1_000_000);
+    // The forwarding relayer's latency record is opened on its first forward
+    let activity = banks_client
+        .get_account(pda(&[b"relayer_activity", admin.pubkey().as_ref()]).0)
+        .await
+        .unwrap()
+        .unwrap();
+    let activity = RelayerActivity::try_deserialize(&mut &activity.data[..]).unwrap();
+    assert_eq!((activity.relayer, activity.forwards), (admin.pubkey(), 1));
+}